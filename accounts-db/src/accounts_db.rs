@@ -57,6 +57,9 @@ use {
         partitioned_rewards::PartitionedEpochRewardsConfig,
         read_only_accounts_cache::ReadOnlyAccountsCache,
         storable_accounts::{StorableAccounts, StorableAccountsBySlot},
+        storage_manifest::{
+            StorageFormat, StorageManifest, StorageManifestEntry, StorageManifestWriter,
+        },
         u64_align,
         utils::{self, create_account_shared_data},
     },
@@ -1219,6 +1222,9 @@ pub struct AccountsDb {
     /// This feature tracks obsolete accounts in the account storage entry allowing
     /// for earlier cleaning of obsolete accounts in the storages and index.
     pub mark_obsolete_accounts: MarkObsoleteAccounts,
+
+    /// Writes the manifest of the storages for other processes, if configured
+    storage_manifest_writer: Option<StorageManifestWriter>,
 }
 
 pub fn quarter_thread_count() -> usize {
@@ -1382,6 +1388,9 @@ impl AccountsDb {
             latest_full_snapshot_slot: SeqLock::new(None),
             best_ancient_slots_to_shrink: RwLock::default(),
             mark_obsolete_accounts: accounts_db_config.mark_obsolete_accounts,
+            storage_manifest_writer: accounts_db_config
+                .storage_manifest_path
+                .map(StorageManifestWriter::new),
         };
 
         {
@@ -6288,6 +6297,53 @@ impl AccountsDb {
         (storages, slots)
     }
 
+    /// Writes the storage manifest if one is configured and the previous one is old enough
+    pub fn maybe_write_storage_manifest(&self) {
+        let Some(writer) = &self.storage_manifest_writer else {
+            return;
+        };
+        let Some(generation) = writer.next_generation_if_due() else {
+            return;
+        };
+        let mut measure = Measure::start("write_storage_manifest");
+        // Read the root first, storages of newer slots may be flushed meanwhile
+        let root = self.accounts_cache.fetch_max_flush_root();
+        let (storages, _) = self.get_storages(..=root);
+        let manifest = StorageManifest {
+            generation,
+            root,
+            storages: storages
+                .iter()
+                .map(|storage| StorageManifestEntry {
+                    slot: storage.slot(),
+                    path: storage.accounts.path().to_path_buf(),
+                    format: StorageFormat::from(&storage.accounts),
+                    len: storage.accounts.len(),
+                    obsolete_offsets: storage
+                        .obsolete_accounts_read_lock()
+                        .filter_obsolete_accounts(None)
+                        .map(|(offset, _)| offset)
+                        .collect(),
+                })
+                .collect(),
+        };
+        if let Err(err) = manifest.save(writer.path()) {
+            warn!(
+                "Unable to write storage manifest {}: {err}",
+                writer.path().display()
+            );
+            return;
+        }
+        measure.stop();
+        datapoint_info!(
+            "accounts_db_storage_manifest",
+            ("generation", generation, i64),
+            ("root", root, i64),
+            ("num_storages", manifest.storages.len(), i64),
+            ("write_us", measure.as_us(), i64),
+        );
+    }
+
     /// Returns the latest full snapshot slot
     pub fn latest_full_snapshot_slot(&self) -> Option<Slot> {
         self.latest_full_snapshot_slot.read()
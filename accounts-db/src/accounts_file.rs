@@ -94,6 +94,17 @@ impl AccountsFile {
         Ok(Self::AppendVec(av))
     }
 
+    /// Opens the storage at `path`, which belongs to another process, for reading
+    ///
+    /// The file is left in place when the AccountsFile is dropped.
+    pub(crate) fn new_for_shared_read(
+        path: impl Into<PathBuf>,
+        current_len: usize,
+    ) -> Result<Self> {
+        let av = AppendVec::new_for_shared_read(path, current_len)?;
+        Ok(Self::AppendVec(av))
+    }
+
     /// if storage is not readonly, reopen another instance that is read only
     pub(crate) fn reopen_as_readonly(&self) -> Option<Self> {
         match self {
@@ -3,6 +3,7 @@ mod accounts_index_storage;
 mod bucket_map_holder;
 pub(crate) mod in_mem_accounts_index;
 mod iter;
+pub mod memory_estimate;
 mod roots_tracker;
 mod secondary;
 mod stats;
@@ -0,0 +1,119 @@
+//! Coarse estimates of how much memory the accounts index needs for a given number of accounts.
+//!
+//! Generating the index at startup can take a long time on large snapshots. These estimates are
+//! used before that work starts so that a host which clearly lacks memory is detected up front,
+//! rather than being OOM-killed part of the way through index generation. They are not meant to
+//! predict RSS precisely.
+
+use {
+    super::{account_map_entry::AccountMapEntry, IndexLimitMb},
+    crate::account_info::AccountInfo,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    std::mem::size_of,
+};
+
+/// hashbrown keeps its tables at most 7/8 full
+const MAP_LOAD_FACTOR_NUMERATOR: usize = 8;
+const MAP_LOAD_FACTOR_DENOMINATOR: usize = 7;
+
+/// Heap allocations are rounded up to this size by the allocator
+const ALLOCATION_GRANULARITY: usize = 16;
+
+/// Percent of in-mem entries that are expected to remain resident when the disk index is enabled.
+/// Entries are evicted to disk as they age, but hot accounts and not-yet-flushed startup inserts
+/// stay in memory.
+const DISK_INDEX_RESIDENT_PERCENT: usize = 10;
+
+/// Percent of disk index bucket slots expected to be occupied
+const DISK_INDEX_OCCUPANCY_PERCENT: usize = 75;
+
+/// Estimated memory and disk usage of the accounts index
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct AccountsIndexMemoryEstimate {
+    /// Number of accounts the estimate was computed for
+    pub num_accounts: u64,
+    /// Bytes expected to be resident in memory
+    pub in_mem_bytes: u64,
+    /// Bytes expected to be used by the disk index files
+    pub disk_bytes: u64,
+}
+
+impl AccountsIndexMemoryEstimate {
+    /// Estimate the usage of an accounts index holding `num_accounts` entries
+    pub fn new(num_accounts: u64, index_limit_mb: IndexLimitMb) -> Self {
+        let in_mem_bytes_per_account = in_mem_bytes_per_account() as u64;
+        match index_limit_mb {
+            IndexLimitMb::InMemOnly => Self {
+                num_accounts,
+                in_mem_bytes: num_accounts.saturating_mul(in_mem_bytes_per_account),
+                disk_bytes: 0,
+            },
+            IndexLimitMb::Minimal => Self {
+                num_accounts,
+                in_mem_bytes: num_accounts
+                    .saturating_mul(in_mem_bytes_per_account)
+                    .saturating_mul(DISK_INDEX_RESIDENT_PERCENT as u64)
+                    / 100,
+                disk_bytes: num_accounts
+                    .saturating_mul(disk_bytes_per_account() as u64)
+                    .saturating_mul(100)
+                    / DISK_INDEX_OCCUPANCY_PERCENT as u64,
+            },
+        }
+    }
+}
+
+/// Bytes used by one entry in the in-mem index: the map slot plus the boxed entry it points to
+fn in_mem_bytes_per_account() -> usize {
+    let map_slot = size_of::<Pubkey>() + size_of::<Box<AccountMapEntry<AccountInfo>>>() + 1;
+    let map_slot = map_slot * MAP_LOAD_FACTOR_NUMERATOR / MAP_LOAD_FACTOR_DENOMINATOR;
+    let entry = size_of::<AccountMapEntry<AccountInfo>>().next_multiple_of(ALLOCATION_GRANULARITY);
+    map_slot + entry
+}
+
+/// Bytes used by one entry in a disk index bucket
+fn disk_bytes_per_account() -> usize {
+    // the key, plus a single (slot, account info) element stored inline with its ref count
+    size_of::<Pubkey>() + size_of::<Slot>() + size_of::<AccountInfo>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_zero_accounts() {
+        for index_limit_mb in [IndexLimitMb::InMemOnly, IndexLimitMb::Minimal] {
+            assert_eq!(
+                AccountsIndexMemoryEstimate::new(0, index_limit_mb),
+                AccountsIndexMemoryEstimate::default(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_in_mem_only() {
+        let num_accounts = 1_000_000;
+        let estimate = AccountsIndexMemoryEstimate::new(num_accounts, IndexLimitMb::InMemOnly);
+        assert_eq!(estimate.num_accounts, num_accounts);
+        assert_eq!(estimate.disk_bytes, 0);
+        // every entry needs at least its key and its boxed entry
+        assert!(estimate.in_mem_bytes > num_accounts * size_of::<Pubkey>() as u64);
+    }
+
+    #[test]
+    fn test_estimate_disk_index_uses_less_memory() {
+        let num_accounts = 1_000_000;
+        let in_mem = AccountsIndexMemoryEstimate::new(num_accounts, IndexLimitMb::InMemOnly);
+        let disk = AccountsIndexMemoryEstimate::new(num_accounts, IndexLimitMb::Minimal);
+        assert!(disk.in_mem_bytes < in_mem.in_mem_bytes);
+        assert!(disk.disk_bytes > 0);
+    }
+
+    #[test]
+    fn test_estimate_saturates() {
+        let estimate = AccountsIndexMemoryEstimate::new(u64::MAX, IndexLimitMb::InMemOnly);
+        assert_eq!(estimate.in_mem_bytes, u64::MAX);
+    }
+}
@@ -537,6 +537,18 @@ impl AppendVec {
         })
     }
 
+    /// Opens the AppendVec at `path`, which belongs to another process, for reading
+    ///
+    /// Unlike the AppendVecs of this process, the file is left in place when dropped.
+    pub(crate) fn new_for_shared_read(
+        path: impl Into<PathBuf>,
+        current_len: usize,
+    ) -> Result<Self> {
+        let new = Self::new_for_startup(path, current_len, StorageAccess::File)?;
+        new.remove_file_on_drop.store(false, Ordering::Release);
+        Ok(new)
+    }
+
     /// Opens the AppendVec at `path` for use by `store-tool`
     #[cfg(feature = "dev-context-only-utils")]
     pub fn new_for_store_tool(path: impl Into<PathBuf>) -> Result<Self> {
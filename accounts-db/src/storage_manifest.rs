@@ -0,0 +1,385 @@
+//! Read-only access to the accounts storages of a running validator
+//!
+//! Analyzing the live state of a validator used to mean copying its accounts storages, which are
+//! terabytes in size. Instead, a validator configured with a storage manifest path periodically
+//! writes a [`StorageManifest`]: the storage files of its rooted slots, how many bytes of each hold
+//! accounts, and which of those accounts are obsolete.
+//!
+//! Rooted storage files are never modified in place, shrinking and packing write new files and
+//! delete the old ones. So a process that opens every file named in one manifest reads the
+//! accounts as of that manifest's root, even if the validator deletes some of the files
+//! afterwards. If a file was deleted before it could be opened, [`SharedStorages::open`] waits for
+//! the next manifest and tries again.
+//!
+//! Only AppendVec storages can be read this way. A manifest that names storages in another format
+//! is rejected by [`SharedStorages::open`].
+
+use {
+    crate::{
+        account_info::Offset,
+        account_storage::stored_account_info::StoredAccountInfo,
+        accounts_file::{AccountsFile, AccountsFileError},
+        append_vec::{self, AppendVecError},
+    },
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_clock::Slot,
+    std::{
+        collections::HashSet,
+        fs::{self, File},
+        io::{self, BufReader, BufWriter},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
+};
+
+/// Name of the manifest file in the ledger directory
+pub const STORAGE_MANIFEST_FILE_NAME: &str = "accounts_storage_manifest";
+
+/// Minimum time between two manifests written by a validator
+const STORAGE_MANIFEST_WRITE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a reader checks for a new manifest while waiting for one
+const STORAGE_MANIFEST_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Error, Debug)]
+pub enum StorageManifestError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid storage manifest: {0}")]
+    Serialization(#[from] bincode::Error),
+
+    #[error("unable to read storage: {0}")]
+    AccountsFile(#[from] AccountsFileError),
+
+    #[error("no storage manifest could be opened in {0:?}, is the validator still running?")]
+    Stale(Duration),
+
+    #[error("storage {0} is in the {1:?} format, only AppendVec storages can be shared")]
+    UnsupportedFormat(PathBuf, StorageFormat),
+}
+
+/// File format of a storage named in a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageFormat {
+    AppendVec,
+    TieredStorage,
+}
+
+impl From<&AccountsFile> for StorageFormat {
+    fn from(file: &AccountsFile) -> Self {
+        match file {
+            AccountsFile::AppendVec(_) => Self::AppendVec,
+            AccountsFile::TieredStorage(_) => Self::TieredStorage,
+        }
+    }
+}
+
+/// The storage files of an accounts db at one point in time
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageManifest {
+    /// Increases with every manifest written by the same validator process
+    pub generation: u64,
+    /// The storages hold the accounts of this slot and older ones
+    pub root: Slot,
+    pub storages: Vec<StorageManifestEntry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageManifestEntry {
+    pub slot: Slot,
+    pub path: PathBuf,
+    pub format: StorageFormat,
+    /// Number of bytes of the file that hold accounts
+    pub len: usize,
+    /// Accounts in the file that have been superseded by newer versions
+    pub obsolete_offsets: Vec<Offset>,
+}
+
+impl StorageManifest {
+    pub fn load(path: &Path) -> Result<Self, StorageManifestError> {
+        let file = File::open(path)?;
+        Ok(bincode::deserialize_from(BufReader::new(file))?)
+    }
+
+    /// Replaces the manifest at `path`, readers see either the old or the new manifest
+    pub fn save(&self, path: &Path) -> Result<(), StorageManifestError> {
+        let temp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        bincode::serialize_into(&mut writer, self)?;
+        writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+        fs::rename(temp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Decides when an accounts db writes its next manifest
+#[derive(Debug)]
+pub(crate) struct StorageManifestWriter {
+    path: PathBuf,
+    generation: AtomicU64,
+    last_write: Mutex<Option<Instant>>,
+}
+
+impl StorageManifestWriter {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        // A manifest left behind by a previous run may name storage files that have been replaced
+        // by different files of the same name since
+        if let Err(err) = fs::remove_file(&path) {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!(
+                    "Unable to remove storage manifest {}: {err}",
+                    path.display()
+                );
+            }
+        }
+        Self {
+            path,
+            generation: AtomicU64::default(),
+            last_write: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the generation of the next manifest if one is due
+    pub(crate) fn next_generation_if_due(&self) -> Option<u64> {
+        let mut last_write = self.last_write.lock().unwrap();
+        if last_write
+            .is_some_and(|last_write| last_write.elapsed() < STORAGE_MANIFEST_WRITE_INTERVAL)
+        {
+            return None;
+        }
+        *last_write = Some(Instant::now());
+        Some(self.generation.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+}
+
+struct SharedStorage {
+    slot: Slot,
+    file: AccountsFile,
+    obsolete_offsets: HashSet<Offset>,
+}
+
+/// The storages named in a manifest, opened read-only
+pub struct SharedStorages {
+    pub generation: u64,
+    pub root: Slot,
+    /// Newest slot first
+    storages: Vec<SharedStorage>,
+}
+
+impl SharedStorages {
+    /// Opens every storage of the manifest at `manifest_path`
+    ///
+    /// If the validator deleted one of the storages after writing the manifest, waits for the
+    /// next manifest and opens that one instead.
+    pub fn open(manifest_path: &Path) -> Result<Self, StorageManifestError> {
+        let timeout = STORAGE_MANIFEST_WRITE_INTERVAL * 3;
+        let start = Instant::now();
+        let mut stale_generation = None;
+        loop {
+            let manifest = StorageManifest::load(manifest_path)?;
+            if stale_generation != Some(manifest.generation) {
+                let generation = manifest.generation;
+                match Self::try_open(manifest) {
+                    Err(StorageManifestError::AccountsFile(err)) if is_not_found(&err) => {
+                        debug!("Storage manifest generation {generation} is stale: {err}");
+                        stale_generation = Some(generation);
+                    }
+                    result => return result,
+                }
+            }
+            if start.elapsed() > timeout {
+                return Err(StorageManifestError::Stale(timeout));
+            }
+            sleep(STORAGE_MANIFEST_POLL_INTERVAL);
+        }
+    }
+
+    fn try_open(manifest: StorageManifest) -> Result<Self, StorageManifestError> {
+        let mut storages = manifest
+            .storages
+            .into_iter()
+            .map(|entry| {
+                // Tiered storages delete their file when dropped, so they must never be opened
+                // for another process
+                if entry.format != StorageFormat::AppendVec {
+                    return Err(StorageManifestError::UnsupportedFormat(
+                        entry.path,
+                        entry.format,
+                    ));
+                }
+                Ok(SharedStorage {
+                    slot: entry.slot,
+                    file: AccountsFile::new_for_shared_read(entry.path, entry.len)?,
+                    obsolete_offsets: entry.obsolete_offsets.into_iter().collect(),
+                })
+            })
+            .collect::<Result<Vec<_>, StorageManifestError>>()?;
+        storages.sort_unstable_by(|a, b| b.slot.cmp(&a.slot));
+        Ok(Self {
+            generation: manifest.generation,
+            root: manifest.root,
+            storages,
+        })
+    }
+
+    pub fn num_storages(&self) -> usize {
+        self.storages.len()
+    }
+
+    /// Calls `callback` with the slot and the latest version of every account with lamports
+    ///
+    /// The address of every account visited is kept until the scan finishes, so memory use grows
+    /// with the number of accounts.
+    pub fn scan_accounts(
+        &self,
+        mut callback: impl FnMut(Slot, &StoredAccountInfo),
+    ) -> Result<(), StorageManifestError> {
+        let mut visited = HashSet::new();
+        let mut reader = append_vec::new_scan_accounts_reader();
+        for storage in &self.storages {
+            storage.file.scan_accounts(&mut reader, |offset, account| {
+                if storage.obsolete_offsets.contains(&offset) || !visited.insert(*account.pubkey) {
+                    return;
+                }
+                if account.lamports != 0 {
+                    callback(storage.slot, &account);
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn is_not_found(err: &AccountsFileError) -> bool {
+    match err {
+        AccountsFileError::Io(err) | AccountsFileError::AppendVecError(AppendVecError::Io(err)) => {
+            err.kind() == io::ErrorKind::NotFound
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::accounts_db::{AccountsDb, AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+        solana_account::AccountSharedData,
+        solana_pubkey::Pubkey,
+        std::sync::Arc,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_shared_storages() {
+        let manifest_dir = TempDir::new().unwrap();
+        let manifest_path = manifest_dir.path().join(STORAGE_MANIFEST_FILE_NAME);
+        let db = AccountsDb::new_with_config(
+            Vec::new(),
+            AccountsDbConfig {
+                storage_manifest_path: Some(manifest_path.clone()),
+                ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+            },
+            None,
+            Arc::default(),
+        );
+
+        let owner = Pubkey::new_unique();
+        let account = |lamports| AccountSharedData::new(lamports, 0, &owner);
+        let updated = Pubkey::new_unique();
+        let unchanged = Pubkey::new_unique();
+        let closed = Pubkey::new_unique();
+        db.store_for_tests((
+            1,
+            [
+                (&updated, &account(1)),
+                (&unchanged, &account(1)),
+                (&closed, &account(1)),
+            ]
+            .as_slice(),
+        ));
+        db.add_root_and_flush_write_cache(1);
+        db.store_for_tests((
+            2,
+            [(&updated, &account(2)), (&closed, &account(0))].as_slice(),
+        ));
+        db.add_root_and_flush_write_cache(2);
+        // not flushed, so not in the manifest
+        db.store_for_tests((3, [(&unchanged, &account(3))].as_slice()));
+
+        db.maybe_write_storage_manifest();
+        let storages = SharedStorages::open(&manifest_path).unwrap();
+        assert_eq!(storages.generation, 1);
+        assert_eq!(storages.root, 2);
+        assert_eq!(storages.num_storages(), 2);
+
+        let mut accounts = vec![];
+        storages
+            .scan_accounts(|slot, account| accounts.push((*account.pubkey, account.lamports, slot)))
+            .unwrap();
+        accounts.sort_unstable();
+        let mut expected = vec![(updated, 2, 2), (unchanged, 1, 1)];
+        expected.sort_unstable();
+        assert_eq!(accounts, expected);
+
+        // the next manifest is only written once the interval has passed
+        db.maybe_write_storage_manifest();
+        assert_eq!(StorageManifest::load(&manifest_path).unwrap().generation, 1);
+
+        // a new accounts db removes the manifest of the previous one
+        let _db = AccountsDb::new_with_config(
+            Vec::new(),
+            AccountsDbConfig {
+                storage_manifest_path: Some(manifest_path.clone()),
+                ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+            },
+            None,
+            Arc::default(),
+        );
+        assert!(!manifest_path.exists());
+    }
+
+    #[test]
+    fn test_shared_storages_unsupported_format() {
+        let manifest_dir = TempDir::new().unwrap();
+        let manifest_path = manifest_dir.path().join(STORAGE_MANIFEST_FILE_NAME);
+        let storage_path = manifest_dir.path().join("1.0");
+        fs::write(&storage_path, [0; 64]).unwrap();
+        StorageManifest {
+            generation: 1,
+            root: 1,
+            storages: vec![StorageManifestEntry {
+                slot: 1,
+                path: storage_path.clone(),
+                format: StorageFormat::TieredStorage,
+                len: 0,
+                obsolete_offsets: vec![],
+            }],
+        }
+        .save(&manifest_path)
+        .unwrap();
+
+        assert!(matches!(
+            SharedStorages::open(&manifest_path),
+            Err(StorageManifestError::UnsupportedFormat(path, StorageFormat::TieredStorage))
+                if path == storage_path
+        ));
+        // the storage of the other process is left alone
+        assert!(storage_path.exists());
+    }
+}
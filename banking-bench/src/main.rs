@@ -10,6 +10,7 @@ use {
     solana_compute_budget_interface::ComputeBudgetInstruction,
     solana_core::{
         banking_stage::{
+            reserved_capacity::ReservedCapacity,
             transaction_scheduler::scheduler_controller::SchedulerConfig,
             update_bank_forks_and_poh_recorder_for_new_tpu_bank, BankingStage,
         },
@@ -472,11 +473,13 @@ fn main() {
         SchedulerConfig {
             scheduler_pacing: SchedulerPacing::Disabled,
         },
+        ReservedCapacity::default(),
         None,
         replay_vote_sender,
         None,
         bank_forks.clone(),
         prioritization_fee_cache,
+        None,
     );
 
     // This is so that the signal_receiver does not go out of scope after the closure.
@@ -0,0 +1,182 @@
+//! Accounts exported from a ledger, to be loaded into the genesis of another cluster.
+//!
+//! An export holds the accounts owned by a set of programs at a slot, encoded like the output of
+//! `solana account --output json`. Each account carries a hash of its contents, and the export
+//! carries a hash of the account hashes, so that an export edited or truncated on its way to a
+//! staging environment is rejected instead of loaded.
+
+use {
+    crate::CliAccount,
+    serde::{Deserialize, Serialize},
+    solana_account::{AccountSharedData, ReadableAccount},
+    solana_clock::Slot,
+    solana_hash::Hash,
+    solana_pubkey::Pubkey,
+    solana_sha256_hasher::hashv,
+    std::str::FromStr,
+};
+
+pub const ACCOUNTS_EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliExportedAccount {
+    #[serde(flatten)]
+    pub account: CliAccount,
+    /// Hash of the address and contents of the account
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliAccountsExport {
+    pub version: u32,
+    /// Slot of the bank the accounts were exported from
+    pub slot: Slot,
+    pub bank_hash: String,
+    /// Owners of the exported accounts
+    pub owners: Vec<String>,
+    /// Exported accounts, sorted by address
+    pub accounts: Vec<CliExportedAccount>,
+    /// Hash of the hashes of the accounts, in order
+    pub hash: String,
+}
+
+fn account_hash(pubkey: &Pubkey, account: &impl ReadableAccount) -> Hash {
+    hashv(&[
+        pubkey.as_ref(),
+        &account.lamports().to_le_bytes(),
+        account.owner().as_ref(),
+        &[account.executable() as u8],
+        &account.rent_epoch().to_le_bytes(),
+        account.data(),
+    ])
+}
+
+fn export_hash(account_hashes: &[Hash]) -> Hash {
+    let account_hashes: Vec<_> = account_hashes.iter().map(Hash::as_ref).collect();
+    hashv(&account_hashes)
+}
+
+impl CliAccountsExport {
+    pub fn new<T: ReadableAccount>(
+        slot: Slot,
+        bank_hash: &Hash,
+        owners: &[Pubkey],
+        mut accounts: Vec<(Pubkey, T)>,
+    ) -> Self {
+        accounts.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let account_hashes: Vec<_> = accounts
+            .iter()
+            .map(|(pubkey, account)| account_hash(pubkey, account))
+            .collect();
+        let hash = export_hash(&account_hashes);
+        let accounts = accounts
+            .iter()
+            .zip(account_hashes)
+            .map(|((pubkey, account), hash)| CliExportedAccount {
+                account: CliAccount::new(pubkey, account, false),
+                hash: hash.to_string(),
+            })
+            .collect();
+        Self {
+            version: ACCOUNTS_EXPORT_VERSION,
+            slot,
+            bank_hash: bank_hash.to_string(),
+            owners: owners.iter().map(Pubkey::to_string).collect(),
+            accounts,
+            hash: hash.to_string(),
+        }
+    }
+
+    /// Decodes the exported accounts, checking them against their hashes
+    pub fn decode(&self) -> Result<Vec<(Pubkey, AccountSharedData)>, String> {
+        if self.version != ACCOUNTS_EXPORT_VERSION {
+            return Err(format!(
+                "unsupported accounts export version {}, expected {ACCOUNTS_EXPORT_VERSION}",
+                self.version
+            ));
+        }
+        let mut account_hashes = Vec::with_capacity(self.accounts.len());
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|exported| {
+                let keyed_account = &exported.account.keyed_account;
+                let pubkey = Pubkey::from_str(&keyed_account.pubkey)
+                    .map_err(|err| format!("invalid address {}: {err}", keyed_account.pubkey))?;
+                let account = keyed_account
+                    .account
+                    .decode::<AccountSharedData>()
+                    .ok_or_else(|| format!("unable to decode account {pubkey}"))?;
+                let hash = account_hash(&pubkey, &account);
+                if hash.to_string() != exported.hash {
+                    return Err(format!("hash mismatch for account {pubkey}"));
+                }
+                account_hashes.push(hash);
+                Ok((pubkey, account))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if export_hash(&account_hashes).to_string() != self.hash {
+            return Err("hash mismatch for the accounts export".to_string());
+        }
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_account::WritableAccount};
+
+    fn accounts(owner: &Pubkey) -> Vec<(Pubkey, AccountSharedData)> {
+        (0..3)
+            .map(|i| {
+                let mut account = AccountSharedData::new(1_000 + i, i as usize, owner);
+                account.data_as_mut_slice().fill(i as u8);
+                (Pubkey::new_unique(), account)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_accounts_export_roundtrip() {
+        let owner = Pubkey::new_unique();
+        let mut accounts = accounts(&owner);
+        accounts.reverse();
+        let export = CliAccountsExport::new(42, &Hash::new_unique(), &[owner], accounts.clone());
+        let export: CliAccountsExport =
+            serde_json::from_str(&serde_json::to_string(&export).unwrap()).unwrap();
+        assert_eq!(export.slot, 42);
+        assert_eq!(export.owners, vec![owner.to_string()]);
+
+        accounts.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(export.decode().unwrap(), accounts);
+    }
+
+    #[test]
+    fn test_accounts_export_tampered() {
+        let owner = Pubkey::new_unique();
+        let accounts = accounts(&owner);
+
+        let mut export = CliAccountsExport::new(42, &Hash::default(), &[owner], accounts.clone());
+        export.accounts[1].account.keyed_account.account.lamports += 1;
+        assert_eq!(
+            export.decode().unwrap_err(),
+            format!(
+                "hash mismatch for account {}",
+                export.accounts[1].account.keyed_account.pubkey
+            )
+        );
+
+        let mut export = CliAccountsExport::new(42, &Hash::default(), &[owner], accounts.clone());
+        export.accounts.pop();
+        assert_eq!(
+            export.decode().unwrap_err(),
+            "hash mismatch for the accounts export"
+        );
+
+        let mut export = CliAccountsExport::new(42, &Hash::default(), &[owner], accounts);
+        export.version += 1;
+        assert!(export.decode().is_err());
+    }
+}
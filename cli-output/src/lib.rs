@@ -8,6 +8,7 @@
     )
 )]
 #![allow(clippy::arithmetic_side_effects)]
+pub mod accounts_export;
 mod cli_output;
 pub mod cli_version;
 pub mod display;
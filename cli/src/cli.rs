@@ -403,6 +403,8 @@ pub enum CliCommand {
     VoteUpdateCommission {
         vote_account_pubkey: Pubkey,
         commission: u8,
+        max_commission_increase: Option<u8>,
+        commission_increase_deadline: u8,
         withdraw_authority: SignerIndex,
         sign_only: bool,
         dump_transaction_message: bool,
@@ -1627,6 +1629,8 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
         CliCommand::VoteUpdateCommission {
             vote_account_pubkey,
             commission,
+            max_commission_increase,
+            commission_increase_deadline,
             withdraw_authority,
             sign_only,
             dump_transaction_message,
@@ -1641,6 +1645,8 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             config,
             vote_account_pubkey,
             *commission,
+            *max_commission_increase,
+            *commission_increase_deadline,
             *withdraw_authority,
             *sign_only,
             *dump_transaction_message,
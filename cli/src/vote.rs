@@ -46,6 +46,8 @@ use {
     std::rc::Rc,
 };
 
+const DEFAULT_COMMISSION_INCREASE_DEADLINE: &str = "50";
+
 pub trait VoteSubCommands {
     fn vote_subcommands(self) -> Self;
 }
@@ -316,6 +318,34 @@ impl VoteSubCommands for App<'_, '_> {
                         .validator(is_valid_signer)
                         .help("Authorized withdrawer keypair"),
                 )
+                .arg(
+                    Arg::with_name("max_commission_increase")
+                        .long("max-commission-increase")
+                        .value_name("PERCENTAGE_POINTS")
+                        .takes_value(true)
+                        .validator(is_valid_percentage)
+                        .help(
+                            "Refuse to raise the commission by more than this many percentage \
+                             points over the current commission",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("commission_increase_deadline")
+                        .long("commission-increase-deadline")
+                        .value_name("PERCENTAGE")
+                        .takes_value(true)
+                        .validator(is_valid_percentage)
+                        .default_value(DEFAULT_COMMISSION_INCREASE_DEADLINE)
+                        .help(
+                            "Refuse to raise the commission once this percentage of the current \
+                             epoch has passed. The vote program rejects increases in the second \
+                             half of an epoch",
+                        ),
+                )
+                .after_help(
+                    "The commission checks need the current vote account state and epoch, so \
+                     they are skipped with --sign-only",
+                )
                 .offline_args()
                 .nonce_args(false)
                 .arg(fee_payer_arg())
@@ -635,6 +665,9 @@ pub fn parse_vote_update_commission(
     let (authorized_withdrawer, authorized_withdrawer_pubkey) =
         signer_of(matches, "authorized_withdrawer", wallet_manager)?;
     let commission = value_t_or_exit!(matches, "commission", u8);
+    let max_commission_increase = value_of(matches, "max_commission_increase");
+    let commission_increase_deadline =
+        value_t_or_exit!(matches, "commission_increase_deadline", u8);
 
     let sign_only = matches.is_present(SIGN_ONLY_ARG.name);
     let dump_transaction_message = matches.is_present(DUMP_TRANSACTION_MESSAGE.name);
@@ -657,6 +690,8 @@ pub fn parse_vote_update_commission(
         command: CliCommand::VoteUpdateCommission {
             vote_account_pubkey,
             commission,
+            max_commission_increase,
+            commission_increase_deadline,
             withdraw_authority: signer_info.index_of(authorized_withdrawer_pubkey).unwrap(),
             sign_only,
             dump_transaction_message,
@@ -1179,6 +1214,8 @@ pub fn process_vote_update_commission(
     config: &CliConfig,
     vote_account_pubkey: &Pubkey,
     commission: u8,
+    max_commission_increase: Option<u8>,
+    commission_increase_deadline: u8,
     withdraw_authority: SignerIndex,
     sign_only: bool,
     dump_transaction_message: bool,
@@ -1190,6 +1227,18 @@ pub fn process_vote_update_commission(
     compute_unit_price: Option<u64>,
 ) -> ProcessResult {
     let authorized_withdrawer = config.signers[withdraw_authority];
+    if !sign_only {
+        let (_, vote_state) = get_vote_account(rpc_client, vote_account_pubkey, config.commitment)?;
+        let epoch_info = rpc_client.get_epoch_info_with_commitment(config.commitment)?;
+        check_commission_increase(
+            vote_state.inflation_rewards_commission_bps,
+            commission,
+            max_commission_increase,
+            commission_increase_deadline,
+            epoch_info.slot_index,
+            epoch_info.slots_in_epoch,
+        )?;
+    }
     let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
     let compute_unit_limit = match blockhash_query {
         BlockhashQuery::None(_) | BlockhashQuery::FeeCalculator(_, _) => ComputeUnitLimit::Default,
@@ -1254,6 +1303,39 @@ pub fn process_vote_update_commission(
     }
 }
 
+/// Refuses commission increases that are larger than `max_increase` percentage points, or that
+/// come after `deadline` percent of the epoch has passed. Decreases are always allowed.
+fn check_commission_increase(
+    current_commission_bps: u16,
+    new_commission: u8,
+    max_increase: Option<u8>,
+    deadline: u8,
+    slot_index: u64,
+    slots_in_epoch: u64,
+) -> Result<(), CliError> {
+    let new_commission_bps = u16::from(new_commission) * 100;
+    if new_commission_bps <= current_commission_bps {
+        return Ok(());
+    }
+    let increase_bps = new_commission_bps - current_commission_bps;
+    if let Some(max_increase) = max_increase {
+        if increase_bps > u16::from(max_increase) * 100 {
+            return Err(CliError::BadParameter(format!(
+                "raising the commission from {current_commission_bps} bps to {new_commission}% \
+                 exceeds the maximum increase of {max_increase} percentage points"
+            )));
+        }
+    }
+    let epoch_progress = slot_index.saturating_mul(100) / slots_in_epoch.max(1);
+    if epoch_progress >= u64::from(deadline) {
+        return Err(CliError::BadParameter(format!(
+            "the current epoch is {epoch_progress}% complete, commission increases are only \
+             allowed in the first {deadline}% of an epoch"
+        )));
+    }
+    Ok(())
+}
+
 pub(crate) fn get_vote_account(
     rpc_client: &RpcClient,
     vote_account_pubkey: &Pubkey,
@@ -2122,6 +2204,44 @@ mod tests {
                 command: CliCommand::VoteUpdateCommission {
                     vote_account_pubkey: pubkey,
                     commission: 42,
+                    max_commission_increase: None,
+                    commission_increase_deadline: 50,
+                    withdraw_authority: 1,
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    blockhash_query: BlockhashQuery::All(blockhash_query::Source::Cluster),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    memo: None,
+                    fee_payer: 0,
+                    compute_unit_price: None,
+                },
+                signers: vec![
+                    Box::new(read_keypair_file(&default_keypair_file).unwrap()),
+                    Box::new(read_keypair_file(&keypair_file).unwrap()),
+                ],
+            }
+        );
+
+        let test_update_commission = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-update-commission",
+            &pubkey_string,
+            "42",
+            &keypair_file,
+            "--max-commission-increase",
+            "2",
+            "--commission-increase-deadline",
+            "25",
+        ]);
+        assert_eq!(
+            parse_command(&test_update_commission, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteUpdateCommission {
+                    vote_account_pubkey: pubkey,
+                    commission: 42,
+                    max_commission_increase: Some(2),
+                    commission_increase_deadline: 25,
                     withdraw_authority: 1,
                     sign_only: false,
                     dump_transaction_message: false,
@@ -2400,4 +2520,25 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_check_commission_increase() {
+        // decreases are always allowed
+        assert!(check_commission_increase(1_000, 5, Some(0), 0, 900, 1_000).is_ok());
+        assert!(check_commission_increase(1_000, 10, Some(0), 0, 900, 1_000).is_ok());
+
+        assert!(check_commission_increase(500, 7, Some(2), 50, 100, 1_000).is_ok());
+        assert!(check_commission_increase(500, 8, Some(2), 50, 100, 1_000).is_err());
+        assert!(check_commission_increase(500, 100, None, 50, 100, 1_000).is_ok());
+
+        // fractional commissions are compared without rounding
+        assert!(check_commission_increase(550, 5, Some(0), 50, 100, 1_000).is_ok());
+        assert!(check_commission_increase(550, 6, Some(0), 50, 100, 1_000).is_err());
+        assert!(check_commission_increase(550, 7, Some(1), 50, 100, 1_000).is_err());
+        assert!(check_commission_increase(550, 7, Some(2), 50, 100, 1_000).is_ok());
+
+        assert!(check_commission_increase(500, 7, None, 50, 499, 1_000).is_ok());
+        assert!(check_commission_increase(500, 7, None, 50, 500, 1_000).is_err());
+        assert!(check_commission_increase(500, 7, None, 100, 999, 1_000).is_ok());
+    }
 }
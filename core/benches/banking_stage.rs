@@ -4,7 +4,10 @@
 use {
     agave_banking_stage_ingress_types::BankingPacketBatch,
     solana_core::{
-        banking_stage::transaction_scheduler::scheduler_controller::SchedulerConfig,
+        banking_stage::{
+            reserved_capacity::ReservedCapacity,
+            transaction_scheduler::scheduler_controller::SchedulerConfig,
+        },
         banking_trace::Channels,
         validator::{BlockProductionMethod, SchedulerPacing},
     },
@@ -248,11 +251,13 @@ fn bench_banking(
         SchedulerConfig {
             scheduler_pacing: SchedulerPacing::Disabled,
         },
+        ReservedCapacity::default(),
         None,
         s,
         None,
         bank_forks,
         Arc::new(PrioritizationFeeCache::new(0u64)),
+        None,
     );
 
     let chunk_len = verified.len() / CHUNKS;
@@ -215,6 +215,7 @@ fn timing_scheduler<T: ReceiveAndBuffer, S: Scheduler<T::Transaction>>(
                         .schedule(
                             black_box(&mut container),
                             u64::MAX, // no budget
+                            u64::MAX, // no non-critical budget
                             false,
                             bench_env.filter_1,
                             bench_env.filter_2,
@@ -1,13 +1,26 @@
 use {
     crate::{
-        banking_stage::BankingControlMsg,
+        banking_stage::{
+            transaction_scheduler::{
+                in_flight_tracker::InFlightBatchesReport,
+                write_lock_contention::WriteLockContentionReport,
+            },
+            BankingControlMsg,
+        },
         cluster_slots_service::cluster_slots::ClusterSlots,
         repair::{outstanding_requests::OutstandingRequests, serve_repair::ShredRepairType},
     },
     solana_gossip::{cluster_info::ClusterInfo, node::NodeMultihoming},
+    solana_ledger::leader_schedule_cache::LeaderScheduleCache,
+    solana_poh::poh_timing_report::PohTimingReport,
     solana_pubkey::Pubkey,
     solana_quic_definitions::NotifyKeyUpdate,
+    solana_rpc::{
+        rpc_service::RpcThreadPoolResizer, rpc_usage::RpcUsageTracker,
+        transaction_status_service::TransactionHistorySwitch,
+    },
     solana_runtime::bank_forks::BankForks,
+    solana_wen_restart::cluster_halt_detector::ClusterHaltStatus,
     std::{
         collections::{HashMap, HashSet},
         net::UdpSocket,
@@ -82,4 +95,15 @@ pub struct AdminRpcRequestMetadataPostInit {
     pub cluster_slots: Arc<ClusterSlots>,
     pub node: Option<Arc<NodeMultihoming>>,
     pub banking_control_sender: mpsc::Sender<BankingControlMsg>,
+    pub write_lock_contention_report: Arc<WriteLockContentionReport>,
+    pub in_flight_batches_report: Arc<InFlightBatchesReport>,
+    pub leader_schedule_cache: Arc<LeaderScheduleCache>,
+    pub poh_timing_report: Arc<PohTimingReport>,
+    pub cluster_halt_status: Arc<ClusterHaltStatus>,
+    /// Switches the writing of the RPC transaction history, if it is enabled
+    pub transaction_history: Option<Arc<TransactionHistorySwitch>>,
+    /// Per-client usage of the JSON RPC service, if it is tracked
+    pub rpc_usage: Option<Arc<RpcUsageTracker>>,
+    /// Resizes the thread pools of the JSON RPC service, if it is running
+    pub rpc_thread_pools: Option<Arc<RpcThreadPoolResizer>>,
 }
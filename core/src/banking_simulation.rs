@@ -2,6 +2,7 @@
 use {
     crate::{
         banking_stage::{
+            reserved_capacity::ReservedCapacity,
             transaction_scheduler::scheduler_controller::SchedulerConfig,
             update_bank_forks_and_poh_recorder_for_new_tpu_bank, BankingStage, BankingStageHandle,
             LikeClusterInfo,
@@ -31,6 +32,7 @@ use {
         poh_controller::PohController,
         poh_recorder::{PohRecorder, GRACE_TICKS_FACTOR, MAX_GRACE_SLOTS},
         poh_service::{PohService, DEFAULT_HASHES_PER_BATCH, DEFAULT_PINNED_CPU_CORE},
+        poh_timing_report::PohAlertThresholds,
         record_channels::record_channels,
         transaction_recorder::TransactionRecorder,
     },
@@ -762,6 +764,7 @@ impl BankingSimulator {
             DEFAULT_HASHES_PER_BATCH,
             record_receiver,
             poh_service_message_receiver,
+            PohAlertThresholds::default(),
         );
 
         // Enable BankingTracer to approximate the real environment as close as possible because
@@ -842,11 +845,13 @@ impl BankingSimulator {
             mpsc::channel(1).1,
             BankingStage::default_num_workers(),
             SchedulerConfig::default(),
+            ReservedCapacity::default(),
             None,
             replay_vote_sender,
             None,
             bank_forks.clone(),
             prioritization_fee_cache.clone(),
+            None,
         );
 
         let (&_slot, &raw_base_event_time) = freeze_time_by_slot
@@ -7,18 +7,20 @@ use qualifier_attr::qualifiers;
 use {
     self::{
         committer::Committer, consumer::Consumer, decision_maker::DecisionMaker,
-        qos_service::QosService, vote_packet_receiver::VotePacketReceiver,
-        vote_storage::VoteStorage,
+        qos_service::QosService, reserved_capacity::ReservedCapacity,
+        vote_packet_receiver::VotePacketReceiver, vote_storage::VoteStorage,
     },
     crate::{
         banking_stage::{
             consume_worker::ConsumeWorker,
             transaction_scheduler::{
+                in_flight_tracker::InFlightBatchesReport,
                 prio_graph_scheduler::PrioGraphScheduler,
                 scheduler_controller::{
                     SchedulerConfig, SchedulerController, DEFAULT_SCHEDULER_PACING_FILL_TIME_MILLIS,
                 },
                 scheduler_error::SchedulerError,
+                write_lock_contention::WriteLockContentionReport,
             },
         },
         validator::BlockProductionMethod,
@@ -36,8 +38,8 @@ use {
     },
     solana_pubkey::Pubkey,
     solana_runtime::{
-        bank::Bank, bank_forks::BankForks, prioritization_fee_cache::PrioritizationFeeCache,
-        vote_sender_types::ReplayVoteSender,
+        bank::Bank, bank_forks::BankForks, banking_queue_snapshot::SharedBankingQueueSnapshot,
+        prioritization_fee_cache::PrioritizationFeeCache, vote_sender_types::ReplayVoteSender,
     },
     solana_time_utils::AtomicInterval,
     std::{
@@ -65,6 +67,7 @@ pub mod committer;
 pub mod consumer;
 pub mod leader_slot_metrics;
 pub mod qos_service;
+pub mod reserved_capacity;
 pub mod vote_storage;
 
 mod consume_worker;
@@ -381,6 +384,11 @@ pub struct BankingStage {
     bank_forks: Arc<RwLock<BankForks>>,
     committer: Committer,
     log_messages_bytes_limit: Option<usize>,
+    prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
+    reserved_capacity: Arc<ReservedCapacity>,
+    write_lock_contention_report: Arc<WriteLockContentionReport>,
+    in_flight_batches_report: Arc<InFlightBatchesReport>,
     threads: FuturesUnordered<NamedTask<std::thread::Result<()>>>,
 }
 
@@ -396,20 +404,24 @@ impl BankingStage {
         banking_control_receiver: mpsc::Receiver<BankingControlMsg>,
         num_workers: NonZeroUsize,
         scheduler_config: SchedulerConfig,
+        reserved_capacity: ReservedCapacity,
         transaction_status_sender: Option<TransactionStatusSender>,
         replay_vote_sender: ReplayVoteSender,
         log_messages_bytes_limit: Option<usize>,
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
     ) -> BankingStageHandle {
         let committer = Committer::new(
             transaction_status_sender,
             replay_vote_sender,
-            prioritization_fee_cache,
+            prioritization_fee_cache.clone(),
         );
 
         // Setup the manager thread state.
         let banking_shutdown_signal = CancellationToken::new();
+        let write_lock_contention_report = Arc::<WriteLockContentionReport>::default();
+        let in_flight_batches_report = Arc::<InFlightBatchesReport>::default();
         let manager = BankingStage {
             banking_shutdown_signal: banking_shutdown_signal.clone(),
             worker_exit_signal: Arc::new(AtomicBool::new(false)),
@@ -422,6 +434,11 @@ impl BankingStage {
             bank_forks,
             committer,
             log_messages_bytes_limit,
+            prioritization_fee_cache,
+            banking_queue_snapshot,
+            reserved_capacity: Arc::new(reserved_capacity),
+            write_lock_contention_report: write_lock_contention_report.clone(),
+            in_flight_batches_report: in_flight_batches_report.clone(),
             threads: FuturesUnordered::default(),
         };
 
@@ -444,6 +461,8 @@ impl BankingStage {
         BankingStageHandle {
             banking_shutdown_signal,
             thread,
+            write_lock_contention_report,
+            in_flight_batches_report,
         }
     }
 
@@ -534,6 +553,14 @@ impl BankingStage {
         info!("Spawning internal scheduler");
         assert!(num_workers <= BankingStage::max_num_workers());
         let num_workers = num_workers.get();
+        if self.reserved_capacity.is_enabled() {
+            info!(
+                "Reserving {}% of the block compute units and {} of {num_workers} workers for \
+                 critical transactions",
+                self.reserved_capacity.block_cu_percent,
+                self.reserved_capacity.num_reserved_threads(num_workers),
+            );
+        }
 
         let exit = self.worker_exit_signal.clone();
 
@@ -564,7 +591,12 @@ impl BankingStage {
                 Consumer::new(
                     self.committer.clone(),
                     self.transaction_recorder.clone(),
-                    QosService::new(id),
+                    QosService::new_with_reserved_capacity(
+                        id,
+                        self.reserved_capacity.clone(),
+                        self.reserved_capacity
+                            .is_reserved_thread(index, num_workers),
+                    ),
                     self.log_messages_bytes_limit,
                 ),
                 finished_work_sender.clone(),
@@ -589,6 +621,11 @@ impl BankingStage {
             ($scheduler:ident) => {
                 let exit = exit.clone();
                 let bank_forks = self.bank_forks.clone();
+                let write_lock_contention_report = self.write_lock_contention_report.clone();
+                let in_flight_batches_report = self.in_flight_batches_report.clone();
+                let prioritization_fee_cache = self.prioritization_fee_cache.clone();
+                let banking_queue_snapshot = self.banking_queue_snapshot.clone();
+                let reserved_capacity = self.reserved_capacity.clone();
                 threads.push(
                     Builder::new()
                         .name("solBnkTxSched".to_string())
@@ -596,11 +633,16 @@ impl BankingStage {
                             let scheduler_controller = SchedulerController::new(
                                 exit,
                                 scheduler_config,
+                                reserved_capacity,
                                 decision_maker,
                                 receive_and_buffer,
                                 bank_forks,
                                 $scheduler,
                                 worker_metrics,
+                                write_lock_contention_report,
+                                in_flight_batches_report,
+                                prioritization_fee_cache,
+                                banking_queue_snapshot,
                             );
 
                             match scheduler_controller.run() {
@@ -621,14 +663,20 @@ impl BankingStage {
             let scheduler = GreedyScheduler::new(
                 work_senders,
                 finished_work_receiver,
-                GreedySchedulerConfig::default(),
+                GreedySchedulerConfig {
+                    reserved_capacity: self.reserved_capacity.clone(),
+                    ..GreedySchedulerConfig::default()
+                },
             );
             spawn_scheduler!(scheduler);
         } else {
             let scheduler = PrioGraphScheduler::new(
                 work_senders,
                 finished_work_receiver,
-                PrioGraphSchedulerConfig::default(),
+                PrioGraphSchedulerConfig {
+                    reserved_capacity: self.reserved_capacity.clone(),
+                    ..PrioGraphSchedulerConfig::default()
+                },
             );
             spawn_scheduler!(scheduler);
         }
@@ -643,7 +691,7 @@ impl BankingStage {
         let consumer = Consumer::new(
             self.committer.clone(),
             self.transaction_recorder.clone(),
-            QosService::new(0),
+            QosService::new_with_reserved_capacity(0, self.reserved_capacity.clone(), false),
             self.log_messages_bytes_limit,
         );
         let decision_maker = DecisionMaker::from(self.poh_recorder.read().unwrap().deref());
@@ -745,7 +793,11 @@ mod external {
                     Consumer::new(
                         self.committer.clone(),
                         self.transaction_recorder.clone(),
-                        QosService::new(id),
+                        QosService::new_with_reserved_capacity(
+                            id,
+                            self.reserved_capacity.clone(),
+                            false,
+                        ),
                         self.log_messages_bytes_limit,
                     ),
                     worker_to_pack,
@@ -794,9 +846,22 @@ mod external {
 pub struct BankingStageHandle {
     banking_shutdown_signal: CancellationToken,
     thread: JoinHandle<std::thread::Result<()>>,
+    write_lock_contention_report: Arc<WriteLockContentionReport>,
+    in_flight_batches_report: Arc<InFlightBatchesReport>,
 }
 
 impl BankingStageHandle {
+    /// Hottest writable accounts of the recent leader slots, as seen by the
+    /// internal scheduler
+    pub fn write_lock_contention_report(&self) -> &Arc<WriteLockContentionReport> {
+        &self.write_lock_contention_report
+    }
+
+    /// Work in flight on each worker thread of the internal scheduler
+    pub fn in_flight_batches_report(&self) -> &Arc<InFlightBatchesReport> {
+        &self.in_flight_batches_report
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.banking_shutdown_signal.cancel();
         self.thread.join().unwrap()
@@ -949,11 +1014,13 @@ mod tests {
             SchedulerConfig {
                 scheduler_pacing: SchedulerPacing::Disabled,
             },
+            ReservedCapacity::default(),
             None,
             replay_vote_sender,
             None,
             bank_forks,
             Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
         drop(non_vote_sender);
         drop(tpu_vote_sender);
@@ -1013,11 +1080,13 @@ mod tests {
             SchedulerConfig {
                 scheduler_pacing: SchedulerPacing::Disabled,
             },
+            ReservedCapacity::default(),
             None,
             replay_vote_sender,
             None,
             bank_forks,
             Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
         trace!("sending bank");
         drop(non_vote_sender);
@@ -1085,11 +1154,13 @@ mod tests {
             SchedulerConfig {
                 scheduler_pacing: SchedulerPacing::Disabled,
             },
+            ReservedCapacity::default(),
             None,
             replay_vote_sender,
             None,
             bank_forks.clone(), // keep a local-copy of bank-forks so worker threads do not lose weak access to bank-forks
             Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
 
         // good tx, and no verify
@@ -1235,11 +1306,13 @@ mod tests {
                 SchedulerConfig {
                     scheduler_pacing: SchedulerPacing::Disabled,
                 },
+                ReservedCapacity::default(),
                 None,
                 replay_vote_sender,
                 None,
                 bank_forks,
                 Arc::new(PrioritizationFeeCache::new(0u64)),
+                None,
             );
 
             // wait for banking_stage to eat the packets
@@ -1388,11 +1461,13 @@ mod tests {
             SchedulerConfig {
                 scheduler_pacing: SchedulerPacing::Disabled,
             },
+            ReservedCapacity::default(),
             None,
             replay_vote_sender,
             None,
             bank_forks,
             Arc::new(PrioritizationFeeCache::new(0u64)),
+            None,
         );
 
         let keypairs = (0..100).map(|_| Keypair::new()).collect_vec();
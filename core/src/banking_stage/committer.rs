@@ -178,6 +178,7 @@ impl Committer {
 
             transaction_status_sender.send_transaction_status_batch(
                 bank.slot(),
+                bank.clock().unix_timestamp,
                 txs,
                 commit_results,
                 balances,
@@ -5,8 +5,8 @@
 
 use {
     super::{
-        committer::CommitTransactionDetails, BatchedTransactionCostDetails,
-        BatchedTransactionDetails, BatchedTransactionErrorDetails,
+        committer::CommitTransactionDetails, reserved_capacity::ReservedCapacity,
+        BatchedTransactionCostDetails, BatchedTransactionDetails, BatchedTransactionErrorDetails,
     },
     agave_feature_set::FeatureSet,
     solana_clock::Slot,
@@ -19,7 +19,10 @@ use {
     solana_transaction_error::TransactionError,
     std::{
         num::Saturating,
-        sync::atomic::{AtomicU64, Ordering},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
     },
 };
 
@@ -34,12 +37,25 @@ mod transaction {
 //
 pub struct QosService {
     metrics: QosServiceMetrics,
+    reserved_capacity: Arc<ReservedCapacity>,
 }
 
 impl QosService {
     pub fn new(id: u32) -> Self {
+        Self::new_with_reserved_capacity(id, Arc::default(), false)
+    }
+
+    /// Only lets critical transactions use the block compute units of `reserved_capacity`.
+    /// `reserved_worker` is whether the banking thread is one of the reserved ones, which is
+    /// reported with the metrics.
+    pub fn new_with_reserved_capacity(
+        id: u32,
+        reserved_capacity: Arc<ReservedCapacity>,
+        reserved_worker: bool,
+    ) -> Self {
         Self {
-            metrics: QosServiceMetrics::new(id),
+            metrics: QosServiceMetrics::new(id, reserved_worker),
+            reserved_capacity,
         }
     }
 
@@ -109,9 +125,31 @@ impl QosService {
         let mut cost_tracking_time = Measure::start("cost_tracking_time");
         let mut cost_tracker = bank.write_cost_tracker().unwrap();
         let mut num_included = 0;
+        let block_limit = cost_tracker.get_block_limit();
+        let reserved_block_cus = self.reserved_capacity.reserved_block_cus(block_limit);
+        let unreserved_block_cus = block_limit.saturating_sub(reserved_block_cus);
         let select_results = transactions
             .zip(transactions_costs)
             .map(|(tx, cost)| match cost {
+                Ok(cost)
+                    if reserved_block_cus > 0
+                        && cost_tracker.block_cost().saturating_add(cost.sum())
+                            > unreserved_block_cus
+                        && !self.reserved_capacity.is_critical(tx) =>
+                {
+                    debug!(
+                        "slot {:?}, transaction {:?}, cost {:?}, would use the block capacity \
+                         reserved for critical transactions",
+                        bank.slot(),
+                        tx,
+                        cost
+                    );
+                    self.metrics
+                        .errors
+                        .retried_txs_per_reserved_capacity_count
+                        .fetch_add(1, Ordering::Relaxed);
+                    Err(TransactionError::WouldExceedMaxBlockCostLimit)
+                }
                 Ok(cost) => match cost_tracker.try_add(&cost) {
                     Ok(UpdatedCosts {
                         updated_block_cost,
@@ -130,6 +168,12 @@ impl QosService {
                             .stats
                             .selected_txs_count
                             .fetch_add(1, Ordering::Relaxed);
+                        if self.reserved_capacity.is_critical(tx) {
+                            self.accumulate_critical_transaction_cost(
+                                cost.sum(),
+                                updated_block_cost.saturating_sub(unreserved_block_cus),
+                            );
+                        }
                         num_included += 1;
                         Ok(cost)
                     }
@@ -321,6 +365,21 @@ impl QosService {
             );
     }
 
+    fn accumulate_critical_transaction_cost(&self, cost: u64, reserved_block_cu_used: u64) {
+        self.metrics
+            .stats
+            .critical_txs_count
+            .fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .stats
+            .estimated_critical_txs_cu
+            .fetch_add(cost, Ordering::Relaxed);
+        self.metrics
+            .stats
+            .reserved_block_cu_used
+            .fetch_max(reserved_block_cu_used, Ordering::Relaxed);
+    }
+
     pub fn accumulate_actual_execute_cu(&self, units: u64) {
         self.metrics
             .stats
@@ -395,6 +454,9 @@ struct QosServiceMetrics {
     /// and other transactions.
     id: String,
 
+    /// whether the banking thread is reserved for critical transactions
+    reserved_worker: bool,
+
     /// aggregate metrics per slot
     slot: AtomicU64,
 
@@ -439,6 +501,17 @@ struct QosServiceMetricsStats {
 
     /// accumulated actual program execute micro-sec that have been packed into block
     actual_execute_time_us: AtomicU64,
+
+    /// number of vote and critical program transactions to be included in blocks
+    critical_txs_count: AtomicU64,
+
+    /// accumulated estimated Compute Units of vote and critical program transactions to be
+    /// packed into block
+    estimated_critical_txs_cu: AtomicU64,
+
+    /// highest number of the block's reserved Compute Units used by vote and critical program
+    /// transactions
+    reserved_block_cu_used: AtomicU64,
 }
 
 #[derive(Debug, Default)]
@@ -460,12 +533,17 @@ struct QosServiceMetricsErrors {
     /// number of transactions to be dropped due to their potential to breach account data total
     /// limits
     dropped_txs_per_account_data_total_limit_count: AtomicU64,
+
+    /// number of transactions to be queued for retry due to their potential to use the block
+    /// Compute Units reserved for vote and critical program transactions
+    retried_txs_per_reserved_capacity_count: AtomicU64,
 }
 
 impl QosServiceMetrics {
-    pub fn new(id: u32) -> Self {
+    pub fn new(id: u32, reserved_worker: bool) -> Self {
         QosServiceMetrics {
             id: id.to_string(),
+            reserved_worker,
             ..QosServiceMetrics::default()
         }
     }
@@ -541,6 +619,24 @@ impl QosServiceMetrics {
                     self.stats.actual_execute_time_us.swap(0, Ordering::Relaxed),
                     i64
                 ),
+                ("reserved_worker", self.reserved_worker, bool),
+                (
+                    "critical_txs_count",
+                    self.stats.critical_txs_count.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "estimated_critical_txs_cu",
+                    self.stats
+                        .estimated_critical_txs_cu
+                        .swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "reserved_block_cu_used",
+                    self.stats.reserved_block_cu_used.swap(0, Ordering::Relaxed),
+                    i64
+                ),
             );
             datapoint_info!(
                 "qos-service-errors",
@@ -581,6 +677,13 @@ impl QosServiceMetrics {
                         .swap(0, Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "retried_txs_per_reserved_capacity_count",
+                    self.errors
+                        .retried_txs_per_reserved_capacity_count
+                        .swap(0, Ordering::Relaxed),
+                    i64
+                ),
             );
             self.slot.store(bank_slot, Ordering::Relaxed);
         }
@@ -601,7 +704,6 @@ mod tests {
         solana_system_transaction as system_transaction,
         solana_vote::vote_transaction,
         solana_vote_program::vote_state::TowerSync,
-        std::sync::Arc,
     };
 
     #[test]
@@ -696,6 +798,97 @@ mod tests {
         assert!(results[3].is_err());
     }
 
+    #[test]
+    fn test_select_transactions_per_cost_with_reserved_capacity() {
+        agave_logger::setup();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let keypair = Keypair::new();
+        let transfer_tx = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let vote_tx = RuntimeTransaction::from_transaction_for_tests(
+            vote_transaction::new_tower_sync_transaction(
+                TowerSync::from(vec![(42, 1)]),
+                Hash::default(),
+                &keypair,
+                &keypair,
+                &keypair,
+                None,
+            ),
+        );
+        let transfer_tx_cost =
+            CostModel::calculate_cost(&transfer_tx, &FeatureSet::all_enabled()).sum();
+        let vote_tx_cost = CostModel::calculate_cost(&vote_tx, &FeatureSet::all_enabled()).sum();
+        // many transfers, then a vote
+        let num_transfers = 100;
+        let txs = std::iter::repeat_n(transfer_tx, num_transfers)
+            .chain(std::iter::once(vote_tx))
+            .collect_vec();
+
+        // half of the block is reserved, and the transfers only fit in the other half
+        let qos_service = QosService::new_with_reserved_capacity(
+            1,
+            Arc::new(ReservedCapacity {
+                block_cu_percent: 50,
+                ..ReservedCapacity::default()
+            }),
+            false,
+        );
+        let cost_limit = 100 * (transfer_tx_cost + vote_tx_cost);
+        bank.write_cost_tracker()
+            .unwrap()
+            .set_limits(cost_limit, cost_limit, cost_limit);
+        let num_unreserved_transfers = (cost_limit / 2 / transfer_tx_cost) as usize;
+        assert!(num_unreserved_transfers < num_transfers);
+
+        let txs_costs = qos_service.compute_transaction_costs(
+            &FeatureSet::all_enabled(),
+            txs.iter(),
+            std::iter::repeat(Ok(())),
+        );
+        let (results, num_selected) =
+            qos_service.select_transactions_per_cost(txs.iter(), txs_costs.into_iter(), &bank);
+        assert_eq!(num_selected, num_unreserved_transfers + 1);
+        assert!(results[..num_unreserved_transfers]
+            .iter()
+            .all(|result| result.is_ok()));
+        assert!(results[num_unreserved_transfers..num_transfers]
+            .iter()
+            .all(|result| {
+                matches!(result, Err(TransactionError::WouldExceedMaxBlockCostLimit))
+            }));
+        // the vote is let into the reserved half
+        assert!(results[num_transfers].is_ok());
+
+        assert_eq!(
+            qos_service
+                .metrics
+                .errors
+                .retried_txs_per_reserved_capacity_count
+                .load(Ordering::Relaxed),
+            (num_transfers - num_unreserved_transfers) as u64
+        );
+        assert_eq!(
+            qos_service
+                .metrics
+                .stats
+                .critical_txs_count
+                .load(Ordering::Relaxed),
+            1
+        );
+        assert_eq!(
+            qos_service
+                .metrics
+                .stats
+                .reserved_block_cu_used
+                .load(Ordering::Relaxed),
+            (num_unreserved_transfers as u64 * transfer_tx_cost + vote_tx_cost)
+                .saturating_sub(cost_limit / 2)
+        );
+    }
+
     #[test]
     fn test_update_and_remove_transaction_costs_committed() {
         agave_logger::setup();
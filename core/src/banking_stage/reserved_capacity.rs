@@ -0,0 +1,179 @@
+//! Capacity of the leader's banking stage reserved for vote transactions and the transactions of
+//! critical programs.
+//!
+//! During an ingress flood, every transaction competes for the block compute units and the
+//! consume workers. A reservation sets aside a share of the block compute units for critical
+//! transactions, meaning simple vote transactions and transactions that invoke one of the
+//! operator's critical programs. Other transactions are only packed into the block up to the
+//! unreserved compute units.
+//!
+//! A reservation can also set aside the last few consume workers of the central scheduler for the
+//! transactions of the critical programs, which are only scheduled on the unreserved workers
+//! otherwise. Simple vote transactions are processed by the vote worker and never reach the
+//! central scheduler, so the reserved workers do not apply to them.
+
+use {
+    agave_scheduling_utils::thread_aware_account_locks::ThreadSet, solana_pubkey::Pubkey,
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    std::collections::HashSet,
+};
+
+/// Largest share of the block compute units that can be reserved
+pub const MAX_RESERVED_BLOCK_CU_PERCENT: u8 = 50;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReservedCapacity {
+    /// Percentage of the block compute unit limit only critical transactions can use
+    pub block_cu_percent: u8,
+    /// Number of consume workers only critical transactions are scheduled on. Simple vote
+    /// transactions are processed by the vote worker instead.
+    pub num_workers: usize,
+    /// Programs whose transactions are critical, in addition to simple vote transactions
+    pub critical_programs: HashSet<Pubkey>,
+}
+
+impl ReservedCapacity {
+    pub fn is_enabled(&self) -> bool {
+        self.block_cu_percent > 0 || self.num_workers > 0
+    }
+
+    /// Whether `transaction` may use the reserved capacity
+    pub fn is_critical(&self, transaction: &impl TransactionWithMeta) -> bool {
+        transaction.is_simple_vote_transaction()
+            || (!self.critical_programs.is_empty()
+                && transaction
+                    .program_instructions_iter()
+                    .any(|(program_id, _)| self.critical_programs.contains(program_id)))
+    }
+
+    /// Compute units of a block with `block_limit` that only critical transactions can use
+    pub fn reserved_block_cus(&self, block_limit: u64) -> u64 {
+        let percent = self.block_cu_percent.min(MAX_RESERVED_BLOCK_CU_PERCENT);
+        block_limit / 100 * u64::from(percent)
+    }
+
+    /// Number of the `num_threads` consume workers that are reserved, always leaving at least one
+    /// unreserved
+    pub fn num_reserved_threads(&self, num_threads: usize) -> usize {
+        self.num_workers.min(num_threads.saturating_sub(1))
+    }
+
+    /// Whether the consume worker `thread_id` of `num_threads` is reserved
+    pub fn is_reserved_thread(&self, thread_id: usize, num_threads: usize) -> bool {
+        thread_id >= num_threads.saturating_sub(self.num_reserved_threads(num_threads))
+    }
+
+    /// Consume workers `transaction` may be scheduled on, out of `num_threads`
+    pub fn schedulable_threads(
+        &self,
+        transaction: &impl TransactionWithMeta,
+        num_threads: usize,
+    ) -> ThreadSet {
+        let num_reserved_threads = self.num_reserved_threads(num_threads);
+        if num_reserved_threads == 0 || self.is_critical(transaction) {
+            ThreadSet::any(num_threads)
+        } else {
+            ThreadSet::any(num_threads - num_reserved_threads)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_hash::Hash, solana_keypair::Keypair,
+        solana_runtime_transaction::runtime_transaction::RuntimeTransaction, solana_signer::Signer,
+        solana_system_transaction as system_transaction, solana_vote::vote_transaction,
+        solana_vote_program::vote_state::TowerSync,
+    };
+
+    #[test]
+    fn test_is_critical() {
+        let keypair = Keypair::new();
+        let transfer_tx = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let vote_tx = RuntimeTransaction::from_transaction_for_tests(
+            vote_transaction::new_tower_sync_transaction(
+                TowerSync::from(vec![(42, 1)]),
+                Hash::default(),
+                &keypair,
+                &keypair,
+                &keypair,
+                None,
+            ),
+        );
+
+        let mut reserved_capacity = ReservedCapacity::default();
+        assert!(reserved_capacity.is_critical(&vote_tx));
+        assert!(!reserved_capacity.is_critical(&transfer_tx));
+
+        reserved_capacity
+            .critical_programs
+            .insert(solana_system_interface::program::id());
+        assert!(reserved_capacity.is_critical(&vote_tx));
+        assert!(reserved_capacity.is_critical(&transfer_tx));
+    }
+
+    #[test]
+    fn test_reserved_block_cus() {
+        let block_limit = 60_000_000;
+        let mut reserved_capacity = ReservedCapacity::default();
+        assert_eq!(reserved_capacity.reserved_block_cus(block_limit), 0);
+
+        reserved_capacity.block_cu_percent = 10;
+        assert_eq!(reserved_capacity.reserved_block_cus(block_limit), 6_000_000);
+
+        // Never more than the maximum share
+        reserved_capacity.block_cu_percent = 100;
+        assert_eq!(
+            reserved_capacity.reserved_block_cus(block_limit),
+            30_000_000
+        );
+    }
+
+    #[test]
+    fn test_schedulable_threads() {
+        let keypair = Keypair::new();
+        let transfer_tx = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&keypair, &keypair.pubkey(), 1, Hash::default()),
+        );
+        let vote_tx = RuntimeTransaction::from_transaction_for_tests(
+            vote_transaction::new_tower_sync_transaction(
+                TowerSync::from(vec![(42, 1)]),
+                Hash::default(),
+                &keypair,
+                &keypair,
+                &keypair,
+                None,
+            ),
+        );
+
+        let mut reserved_capacity = ReservedCapacity::default();
+        assert_eq!(
+            reserved_capacity.schedulable_threads(&transfer_tx, 4),
+            ThreadSet::any(4)
+        );
+
+        reserved_capacity.num_workers = 1;
+        assert_eq!(
+            reserved_capacity.schedulable_threads(&transfer_tx, 4),
+            ThreadSet::any(3)
+        );
+        assert_eq!(
+            reserved_capacity.schedulable_threads(&vote_tx, 4),
+            ThreadSet::any(4)
+        );
+        assert!(!reserved_capacity.is_reserved_thread(2, 4));
+        assert!(reserved_capacity.is_reserved_thread(3, 4));
+
+        // At least one worker is left to the other transactions
+        reserved_capacity.num_workers = 8;
+        assert_eq!(reserved_capacity.num_reserved_threads(4), 3);
+        assert_eq!(
+            reserved_capacity.schedulable_threads(&transfer_tx, 4),
+            ThreadSet::any(1)
+        );
+        assert_eq!(reserved_capacity.num_reserved_threads(1), 0);
+    }
+}
@@ -14,6 +14,7 @@ use {
     crate::banking_stage::{
         consumer::TARGET_NUM_TRANSACTIONS_PER_BATCH,
         read_write_account_set::ReadWriteAccountSet,
+        reserved_capacity::ReservedCapacity,
         scheduler_messages::{ConsumeWork, FinishedConsumeWork},
     },
     agave_scheduling_utils::thread_aware_account_locks::{
@@ -22,7 +23,7 @@ use {
     crossbeam_channel::{Receiver, Sender},
     solana_cost_model::block_cost_limits::MAX_BLOCK_UNITS,
     solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
-    std::num::Saturating,
+    std::{num::Saturating, sync::Arc, time::Instant},
 };
 
 #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
@@ -30,6 +31,7 @@ pub(crate) struct GreedySchedulerConfig {
     pub target_scheduled_cus: u64,
     pub max_scanned_transactions_per_scheduling_pass: usize,
     pub target_transactions_per_batch: usize,
+    pub reserved_capacity: Arc<ReservedCapacity>,
 }
 
 impl Default for GreedySchedulerConfig {
@@ -38,6 +40,7 @@ impl Default for GreedySchedulerConfig {
             target_scheduled_cus: MAX_BLOCK_UNITS / 4,
             max_scanned_transactions_per_scheduling_pass: 100_000,
             target_transactions_per_batch: TARGET_NUM_TRANSACTIONS_PER_BATCH,
+            reserved_capacity: Arc::default(),
         }
     }
 }
@@ -77,19 +80,22 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for GreedyScheduler<Tx> {
         &mut self,
         container: &mut S,
         budget: u64,
+        non_critical_budget: u64,
         relax_intrabatch_account_locks: bool,
         _pre_graph_filter: impl Fn(&[&Tx], &mut [bool]),
         pre_lock_filter: impl Fn(&TransactionState<Tx>) -> PreLockFilterAction,
     ) -> Result<SchedulingSummary, SchedulerError> {
         // Subtract any in-flight compute units from the budget.
-        let mut budget = budget.saturating_sub(
-            self.common
-                .in_flight_tracker
-                .cus_in_flight_per_thread()
-                .iter()
-                .sum(),
-        );
-
+        let cus_in_flight = self
+            .common
+            .in_flight_tracker
+            .cus_in_flight_per_thread()
+            .iter()
+            .sum();
+        let mut budget = budget.saturating_sub(cus_in_flight);
+        let mut non_critical_budget = non_critical_budget.saturating_sub(cus_in_flight);
+
+        let now = Instant::now();
         let starting_queue_size = container.queue_size();
         let starting_buffer_size = container.buffer_size();
 
@@ -124,6 +130,7 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for GreedyScheduler<Tx> {
         let mut num_sent: usize = 0;
         let mut num_unschedulable_conflicts: usize = 0;
         let mut num_unschedulable_threads: usize = 0;
+        let mut num_unschedulable_reserved_capacity: usize = 0;
 
         while budget > 0
             && num_scanned < self.config.max_scanned_transactions_per_scheduling_pass
@@ -154,35 +161,65 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for GreedyScheduler<Tx> {
             }
 
             // Now check if the transaction can actually be scheduled.
-            match try_schedule_transaction(
-                transaction_state,
-                &pre_lock_filter,
-                &mut self.common.account_locks,
-                schedulable_threads,
-                |thread_set| {
-                    select_thread(
-                        thread_set,
-                        self.common.batches.total_cus(),
-                        self.common.in_flight_tracker.cus_in_flight_per_thread(),
-                        self.common.batches.transactions(),
-                        self.common.in_flight_tracker.num_in_flight_per_thread(),
-                    )
-                },
-            ) {
+            // Transactions outside the reserved capacity wait for a later
+            // pass once their share of the block is used up.
+            let critical = self
+                .config
+                .reserved_capacity
+                .is_critical(transaction_state.transaction());
+            let maybe_schedule_info = if !critical && non_critical_budget == 0 {
+                Err(TransactionSchedulingError::UnschedulableReservedCapacity)
+            } else {
+                let allowed_threads = schedulable_threads
+                    & self
+                        .config
+                        .reserved_capacity
+                        .schedulable_threads(transaction_state.transaction(), num_threads);
+                try_schedule_transaction(
+                    transaction_state,
+                    &pre_lock_filter,
+                    &mut self.common.account_locks,
+                    allowed_threads,
+                    |thread_set| {
+                        select_thread(
+                            thread_set,
+                            self.common.batches.total_cus(),
+                            self.common.in_flight_tracker.cus_in_flight_per_thread(),
+                            self.common.batches.transactions(),
+                            self.common.in_flight_tracker.num_in_flight_per_thread(),
+                        )
+                    },
+                )
+            };
+            match maybe_schedule_info {
                 Err(TransactionSchedulingError::UnschedulableConflicts) => {
                     num_unschedulable_conflicts += 1;
+                    self.common
+                        .write_lock_contention
+                        .record_conflict(transaction_state, now);
                     self.unschedulables.push(id);
                 }
                 Err(TransactionSchedulingError::UnschedulableThread) => {
                     num_unschedulable_threads += 1;
                     self.unschedulables.push(id);
                 }
+                Err(TransactionSchedulingError::UnschedulableReservedCapacity) => {
+                    num_unschedulable_reserved_capacity += 1;
+                    self.unschedulables.push(id);
+                }
                 Ok(TransactionSchedulingInfo {
                     thread_id,
                     transaction,
                     max_age,
                     cost,
                 }) => {
+                    if let Some(blocked_since) = transaction_state.take_blocked_since() {
+                        self.common.write_lock_contention.record_scheduled(
+                            &transaction,
+                            blocked_since,
+                            now,
+                        );
+                    }
                     if !relax_intrabatch_account_locks {
                         assert!(
                             self.working_account_set.take_locks(&transaction),
@@ -198,6 +235,9 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for GreedyScheduler<Tx> {
                         cost,
                     );
                     budget = budget.saturating_sub(cost);
+                    if !critical {
+                        non_critical_budget = non_critical_budget.saturating_sub(cost);
+                    }
 
                     // If target batch size is reached, send all the batches
                     if self.common.batches.transactions()[thread_id].len()
@@ -239,6 +279,7 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for GreedyScheduler<Tx> {
             num_scheduled,
             num_unschedulable_conflicts,
             num_unschedulable_threads,
+            num_unschedulable_reserved_capacity,
             num_filtered_out: 0,
             filter_time_us: 0,
         })
@@ -431,6 +472,7 @@ mod test {
             scheduler.schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter
@@ -452,6 +494,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -474,7 +517,8 @@ mod test {
         let scheduling_summary = scheduler
             .schedule(
                 &mut container,
-                0, // zero budget
+                0,        // zero budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -484,6 +528,58 @@ mod test {
         assert_eq!(scheduling_summary.num_unschedulable_conflicts, 0);
     }
 
+    #[test]
+    fn test_schedule_non_critical_budget() {
+        let mut reserved_capacity = ReservedCapacity {
+            block_cu_percent: 10,
+            ..ReservedCapacity::default()
+        };
+        let (mut scheduler, work_receivers, _finished_work_sender) = create_test_frame(
+            1,
+            GreedySchedulerConfig {
+                reserved_capacity: Arc::new(reserved_capacity.clone()),
+                ..GreedySchedulerConfig::default()
+            },
+        );
+        let mut container =
+            create_container((0..4).map(|i| (Keypair::new(), [Pubkey::new_unique()], 1, i)));
+
+        // The transfers are not critical, so only one fits the non-critical budget
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                u64::MAX, // no budget
+                5_000,    // non-critical budget for a single transaction
+                false,
+                test_pre_graph_filter,
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 1);
+        assert_eq!(scheduling_summary.num_unschedulable_reserved_capacity, 3);
+        assert_eq!(collect_work(&work_receivers[0]).1, [vec![3]]);
+        assert_eq!(container.queue_size(), 3);
+
+        // Critical transactions use the rest of the budget
+        reserved_capacity
+            .critical_programs
+            .insert(solana_system_interface::program::id());
+        scheduler.config.reserved_capacity = Arc::new(reserved_capacity);
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                u64::MAX, // no budget
+                0,        // zero non-critical budget
+                false,
+                test_pre_graph_filter,
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 3);
+        assert_eq!(scheduling_summary.num_unschedulable_reserved_capacity, 0);
+        assert_eq!(collect_work(&work_receivers[0]).1, [vec![2, 1, 0]]);
+    }
+
     #[test]
     fn test_schedule_single_threaded_scheduling_cu_limit() {
         let (mut scheduler, work_receivers, _finished_work_sender) = create_test_frame(
@@ -502,6 +598,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -530,6 +627,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -558,6 +656,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -583,6 +682,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 relax_intrabatch_account_locks,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -608,6 +708,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -650,6 +751,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -688,6 +790,7 @@ mod test {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
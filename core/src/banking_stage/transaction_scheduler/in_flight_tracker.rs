@@ -1,15 +1,54 @@
 use {
     super::batch_id_generator::BatchIdGenerator,
     crate::banking_stage::scheduler_messages::TransactionBatchId,
-    agave_scheduling_utils::thread_aware_account_locks::ThreadId, std::collections::HashMap,
+    agave_scheduling_utils::thread_aware_account_locks::ThreadId,
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
 };
 
+/// Work in flight on a worker thread
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InFlightBatches {
+    pub num_batches: usize,
+    pub num_transactions: usize,
+    pub cus: u64,
+}
+
+/// Work in flight on each worker thread, kept up to date by the scheduler's [`InFlightTracker`] as
+/// batches are sent and completed, so that it can be looked at from outside the banking stage, for
+/// example in crash reports
+#[derive(Debug, Default)]
+pub struct InFlightBatchesReport {
+    per_thread: Mutex<Vec<InFlightBatches>>,
+}
+
+impl InFlightBatchesReport {
+    /// Returns the work in flight on each worker thread
+    pub fn per_thread(&self) -> Vec<InFlightBatches> {
+        self.per_thread.lock().unwrap().clone()
+    }
+
+    fn reset(&self, num_threads: usize) {
+        *self.per_thread.lock().unwrap() = vec![InFlightBatches::default(); num_threads];
+    }
+
+    fn update_thread(&self, thread_id: ThreadId, update: impl FnOnce(&mut InFlightBatches)) {
+        if let Some(in_flight) = self.per_thread.lock().unwrap().get_mut(thread_id) {
+            update(in_flight);
+        }
+    }
+}
+
 /// Tracks the number of transactions that are in flight for each thread.
 pub struct InFlightTracker {
+    num_batches_per_thread: Vec<usize>,
     num_in_flight_per_thread: Vec<usize>,
     cus_in_flight_per_thread: Vec<u64>,
     batches: HashMap<TransactionBatchId, BatchEntry>,
     batch_id_generator: BatchIdGenerator,
+    report: Option<Arc<InFlightBatchesReport>>,
 }
 
 struct BatchEntry {
@@ -21,13 +60,23 @@ struct BatchEntry {
 impl InFlightTracker {
     pub fn new(num_threads: usize) -> Self {
         Self {
+            num_batches_per_thread: vec![0; num_threads],
             num_in_flight_per_thread: vec![0; num_threads],
             cus_in_flight_per_thread: vec![0; num_threads],
             batches: HashMap::new(),
             batch_id_generator: BatchIdGenerator::default(),
+            report: None,
         }
     }
 
+    /// Publishes the work in flight to `report` from now on. Must be set before any batch is
+    /// tracked.
+    pub fn set_report(&mut self, report: Arc<InFlightBatchesReport>) {
+        debug_assert!(self.batches.is_empty());
+        report.reset(self.num_in_flight_per_thread.len());
+        self.report = Some(report);
+    }
+
     /// Returns the number of transactions that are in flight for each thread.
     pub fn num_in_flight_per_thread(&self) -> &[usize] {
         &self.num_in_flight_per_thread
@@ -48,8 +97,16 @@ impl InFlightTracker {
         thread_id: ThreadId,
     ) -> TransactionBatchId {
         let batch_id = self.batch_id_generator.next();
+        self.num_batches_per_thread[thread_id] += 1;
         self.num_in_flight_per_thread[thread_id] += num_transactions;
         self.cus_in_flight_per_thread[thread_id] += total_cus;
+        if let Some(report) = &self.report {
+            report.update_thread(thread_id, |in_flight| {
+                in_flight.num_batches += 1;
+                in_flight.num_transactions += num_transactions;
+                in_flight.cus += total_cus;
+            });
+        }
         self.batches.insert(
             batch_id,
             BatchEntry {
@@ -77,8 +134,16 @@ impl InFlightTracker {
         else {
             panic!("batch id {batch_id} is not being tracked");
         };
+        self.num_batches_per_thread[thread_id] -= 1;
         self.num_in_flight_per_thread[thread_id] -= num_transactions;
         self.cus_in_flight_per_thread[thread_id] -= total_cus;
+        if let Some(report) = &self.report {
+            report.update_thread(thread_id, |in_flight| {
+                in_flight.num_batches -= 1;
+                in_flight.num_transactions -= num_transactions;
+                in_flight.cus -= total_cus;
+            });
+        }
 
         thread_id
     }
@@ -120,4 +185,37 @@ mod tests {
         assert_eq!(in_flight_tracker.num_in_flight_per_thread(), &[0, 0]);
         assert_eq!(in_flight_tracker.cus_in_flight_per_thread(), &[0, 0]);
     }
+
+    #[test]
+    fn test_in_flight_batches_report() {
+        let mut in_flight_tracker = InFlightTracker::new(2);
+        let report = Arc::<InFlightBatchesReport>::default();
+        assert!(report.per_thread().is_empty());
+        in_flight_tracker.set_report(report.clone());
+        assert_eq!(report.per_thread(), vec![InFlightBatches::default(); 2]);
+
+        in_flight_tracker.track_batch(2, 10_000, 0);
+        let batch_id = in_flight_tracker.track_batch(1, 5_000, 0);
+        assert_eq!(
+            report.per_thread(),
+            vec![
+                InFlightBatches {
+                    num_batches: 2,
+                    num_transactions: 3,
+                    cus: 15_000,
+                },
+                InFlightBatches::default(),
+            ]
+        );
+
+        in_flight_tracker.complete_batch(batch_id);
+        assert_eq!(
+            report.per_thread()[0],
+            InFlightBatches {
+                num_batches: 1,
+                num_transactions: 2,
+                cus: 10_000,
+            }
+        );
+    }
 }
@@ -5,13 +5,15 @@ pub mod greedy_scheduler;
 #[cfg(not(feature = "dev-context-only-utils"))]
 pub(crate) mod greedy_scheduler;
 
-mod in_flight_tracker;
+pub mod in_flight_tracker;
 
 #[cfg(feature = "dev-context-only-utils")]
 pub mod prio_graph_scheduler;
 #[cfg(not(feature = "dev-context-only-utils"))]
 pub(crate) mod prio_graph_scheduler;
 
+mod queue_snapshot;
+
 #[cfg(feature = "dev-context-only-utils")]
 pub mod receive_and_buffer;
 #[cfg(not(feature = "dev-context-only-utils"))]
@@ -42,3 +44,5 @@ pub(crate) mod transaction_state;
 pub mod transaction_state_container;
 #[cfg(not(feature = "dev-context-only-utils"))]
 pub(crate) mod transaction_state_container;
+
+pub mod write_lock_contention;
@@ -11,6 +11,7 @@ use {
     crate::banking_stage::{
         consumer::TARGET_NUM_TRANSACTIONS_PER_BATCH,
         read_write_account_set::ReadWriteAccountSet,
+        reserved_capacity::ReservedCapacity,
         scheduler_messages::{ConsumeWork, FinishedConsumeWork},
         transaction_scheduler::{
             scheduler_common::select_thread, transaction_priority_id::TransactionPriorityId,
@@ -27,7 +28,7 @@ use {
     solana_pubkey::Pubkey,
     solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
     solana_svm_transaction::svm_message::SVMMessage,
-    std::num::Saturating,
+    std::{num::Saturating, sync::Arc, time::Instant},
 };
 
 #[inline(always)]
@@ -51,6 +52,7 @@ pub(crate) struct PrioGraphSchedulerConfig {
     pub max_scanned_transactions_per_scheduling_pass: usize,
     pub look_ahead_window_size: usize,
     pub target_transactions_per_batch: usize,
+    pub reserved_capacity: Arc<ReservedCapacity>,
 }
 
 impl Default for PrioGraphSchedulerConfig {
@@ -60,6 +62,7 @@ impl Default for PrioGraphSchedulerConfig {
             max_scanned_transactions_per_scheduling_pass: 1000,
             look_ahead_window_size: 256,
             target_transactions_per_batch: TARGET_NUM_TRANSACTIONS_PER_BATCH,
+            reserved_capacity: Arc::default(),
         }
     }
 }
@@ -111,19 +114,22 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for PrioGraphScheduler<Tx> {
         &mut self,
         container: &mut S,
         budget: u64,
+        non_critical_budget: u64,
         _relax_intrabatch_account_locks: bool,
         pre_graph_filter: impl Fn(&[&Tx], &mut [bool]),
         pre_lock_filter: impl Fn(&TransactionState<Tx>) -> PreLockFilterAction,
     ) -> Result<SchedulingSummary, SchedulerError> {
         // Subtract any in-flight compute units from the budget.
-        let mut budget = budget.saturating_sub(
-            self.common
-                .in_flight_tracker
-                .cus_in_flight_per_thread()
-                .iter()
-                .sum(),
-        );
+        let cus_in_flight = self
+            .common
+            .in_flight_tracker
+            .cus_in_flight_per_thread()
+            .iter()
+            .sum();
+        let mut budget = budget.saturating_sub(cus_in_flight);
+        let mut non_critical_budget = non_critical_budget.saturating_sub(cus_in_flight);
 
+        let now = Instant::now();
         let starting_queue_size = container.queue_size();
         let starting_buffer_size = container.buffer_size();
 
@@ -222,6 +228,7 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for PrioGraphScheduler<Tx> {
         let mut num_sent = Saturating::<usize>(0);
         let mut num_unschedulable_conflicts: usize = 0;
         let mut num_unschedulable_threads: usize = 0;
+        let mut num_unschedulable_reserved_capacity: usize = 0;
         while budget > 0 && num_scanned < self.config.max_scanned_transactions_per_scheduling_pass {
             // If nothing is in the main-queue of the `PrioGraph` then there's nothing left to schedule.
             if self.prio_graph.is_empty() {
@@ -238,38 +245,67 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for PrioGraphScheduler<Tx> {
                     panic!("transaction state must exist")
                 };
 
-                let maybe_schedule_info = try_schedule_transaction(
-                    transaction_state,
-                    &pre_lock_filter,
-                    &mut blocking_locks,
-                    &mut self.common.account_locks,
-                    num_threads,
-                    |thread_set| {
-                        select_thread(
-                            thread_set,
-                            self.common.batches.total_cus(),
-                            self.common.in_flight_tracker.cus_in_flight_per_thread(),
-                            self.common.batches.transactions(),
-                            self.common.in_flight_tracker.num_in_flight_per_thread(),
-                        )
-                    },
-                );
+                // Transactions outside the reserved capacity wait for a later
+                // pass once their share of the block is used up, without
+                // blocking the critical transactions they conflict with.
+                let critical = self
+                    .config
+                    .reserved_capacity
+                    .is_critical(transaction_state.transaction());
+                let maybe_schedule_info = if !critical && non_critical_budget == 0 {
+                    Err(TransactionSchedulingError::UnschedulableReservedCapacity)
+                } else {
+                    let allowed_threads = self
+                        .config
+                        .reserved_capacity
+                        .schedulable_threads(transaction_state.transaction(), num_threads);
+                    try_schedule_transaction(
+                        transaction_state,
+                        &pre_lock_filter,
+                        &mut blocking_locks,
+                        &mut self.common.account_locks,
+                        allowed_threads,
+                        |thread_set| {
+                            select_thread(
+                                thread_set,
+                                self.common.batches.total_cus(),
+                                self.common.in_flight_tracker.cus_in_flight_per_thread(),
+                                self.common.batches.transactions(),
+                                self.common.in_flight_tracker.num_in_flight_per_thread(),
+                            )
+                        },
+                    )
+                };
 
                 match maybe_schedule_info {
                     Err(TransactionSchedulingError::UnschedulableConflicts) => {
                         num_unschedulable_conflicts += 1;
+                        self.common
+                            .write_lock_contention
+                            .record_conflict(transaction_state, now);
                         unschedulable_ids.push(id);
                     }
                     Err(TransactionSchedulingError::UnschedulableThread) => {
                         num_unschedulable_threads += 1;
                         unschedulable_ids.push(id);
                     }
+                    Err(TransactionSchedulingError::UnschedulableReservedCapacity) => {
+                        num_unschedulable_reserved_capacity += 1;
+                        unschedulable_ids.push(id);
+                    }
                     Ok(TransactionSchedulingInfo {
                         thread_id,
                         transaction,
                         max_age,
                         cost,
                     }) => {
+                        if let Some(blocked_since) = transaction_state.take_blocked_since() {
+                            self.common.write_lock_contention.record_scheduled(
+                                &transaction,
+                                blocked_since,
+                                now,
+                            );
+                        }
                         num_scheduled += 1;
                         self.common.batches.add_transaction_to_batch(
                             thread_id,
@@ -279,6 +315,9 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for PrioGraphScheduler<Tx> {
                             cost,
                         );
                         budget = budget.saturating_sub(cost);
+                        if !critical {
+                            non_critical_budget = non_critical_budget.saturating_sub(cost);
+                        }
 
                         // If target batch size is reached, send only this batch.
                         if self.common.batches.transactions()[thread_id].len()
@@ -350,6 +389,7 @@ impl<Tx: TransactionWithMeta> Scheduler<Tx> for PrioGraphScheduler<Tx> {
             num_scheduled,
             num_unschedulable_conflicts,
             num_unschedulable_threads,
+            num_unschedulable_reserved_capacity,
             num_filtered_out,
             filter_time_us: total_filter_time_us,
         })
@@ -384,7 +424,7 @@ fn try_schedule_transaction<Tx: TransactionWithMeta>(
     pre_lock_filter: impl Fn(&TransactionState<Tx>) -> PreLockFilterAction,
     blocking_locks: &mut ReadWriteAccountSet,
     account_locks: &mut ThreadAwareAccountLocks,
-    num_threads: usize,
+    allowed_threads: ThreadSet,
     thread_selector: impl Fn(ThreadSet) -> ThreadId,
 ) -> Result<TransactionSchedulingInfo<Tx>, TransactionSchedulingError> {
     match pre_lock_filter(transaction_state) {
@@ -412,7 +452,7 @@ fn try_schedule_transaction<Tx: TransactionWithMeta>(
     let thread_id = match account_locks.try_lock_accounts(
         write_account_locks,
         read_account_locks,
-        ThreadSet::any(num_threads),
+        allowed_threads,
         thread_selector,
     ) {
         Ok(thread_id) => thread_id,
@@ -585,6 +625,7 @@ mod tests {
             scheduler.schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter
@@ -605,6 +646,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -626,7 +668,8 @@ mod tests {
         let scheduling_summary = scheduler
             .schedule(
                 &mut container,
-                0, // zero budget. nothing should be scheduled
+                0,        // zero budget. nothing should be scheduled
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -649,6 +692,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -672,6 +716,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -700,6 +745,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -711,6 +757,80 @@ mod tests {
         assert_eq!(collect_work(&work_receivers[1]).1, [vec![2, 0]]);
     }
 
+    #[test]
+    fn test_schedule_reserved_thread() {
+        let (mut scheduler, work_receivers, _finished_work_sender) = create_test_frame(2);
+        scheduler.config.reserved_capacity = Arc::new(ReservedCapacity {
+            num_workers: 1,
+            ..ReservedCapacity::default()
+        });
+        let mut container =
+            create_container((0..4).map(|i| (Keypair::new(), [Pubkey::new_unique()], 1, i)));
+
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
+                false,
+                test_pre_graph_filter,
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 4);
+        assert_eq!(scheduling_summary.num_unschedulable_conflicts, 0);
+        // the transfers are not critical, so they all go to the unreserved thread
+        assert_eq!(collect_work(&work_receivers[0]).1, [vec![3, 2, 1, 0]]);
+        assert!(collect_work(&work_receivers[1]).1.is_empty());
+    }
+
+    #[test]
+    fn test_schedule_non_critical_budget() {
+        let (mut scheduler, work_receivers, _finished_work_sender) = create_test_frame(1);
+        let mut reserved_capacity = ReservedCapacity {
+            block_cu_percent: 10,
+            ..ReservedCapacity::default()
+        };
+        scheduler.config.reserved_capacity = Arc::new(reserved_capacity.clone());
+        let mut container =
+            create_container((0..4).map(|i| (Keypair::new(), [Pubkey::new_unique()], 1, i)));
+
+        // The transfers are not critical, so only one fits the non-critical budget
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                u64::MAX, // no budget
+                5_000,    // non-critical budget for a single transaction
+                false,
+                test_pre_graph_filter,
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 1);
+        assert_eq!(scheduling_summary.num_unschedulable_reserved_capacity, 3);
+        assert_eq!(collect_work(&work_receivers[0]).1, [vec![3]]);
+        assert_eq!(container.queue_size(), 3);
+
+        // Critical transactions use the rest of the budget
+        reserved_capacity
+            .critical_programs
+            .insert(solana_system_interface::program::id());
+        scheduler.config.reserved_capacity = Arc::new(reserved_capacity);
+        let scheduling_summary = scheduler
+            .schedule(
+                &mut container,
+                u64::MAX, // no budget
+                0,        // zero non-critical budget
+                false,
+                test_pre_graph_filter,
+                test_pre_lock_filter,
+            )
+            .unwrap();
+        assert_eq!(scheduling_summary.num_scheduled, 3);
+        assert_eq!(scheduling_summary.num_unschedulable_reserved_capacity, 0);
+        assert_eq!(collect_work(&work_receivers[0]).1, [vec![2, 1, 0]]);
+    }
+
     #[test]
     fn test_schedule_priority_guard() {
         let (mut scheduler, work_receivers, finished_work_sender) = create_test_frame(2);
@@ -747,6 +867,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -763,6 +884,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -783,6 +905,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -812,6 +935,7 @@ mod tests {
             .schedule(
                 &mut container,
                 u64::MAX, // no budget
+                u64::MAX, // no non-critical budget
                 false,
                 test_pre_graph_filter,
                 test_pre_lock_filter,
@@ -0,0 +1,128 @@
+//! Snapshots of the scheduler's queue, published for previewing transaction priorities over RPC.
+
+use {
+    super::transaction_state::TransactionState,
+    solana_clock::Slot,
+    solana_runtime::banking_queue_snapshot::{
+        BankingQueueSnapshot, LockingTransaction, QueuedTransaction,
+        MAX_SNAPSHOT_LOCKING_TRANSACTIONS,
+    },
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    std::{cmp::Reverse, time::Duration},
+};
+
+/// How often the scheduler publishes a snapshot of its queue
+pub(crate) const QUEUE_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Takes a snapshot of the `queued` transactions at `slot`
+pub(crate) fn take_queue_snapshot<'a, Tx: TransactionWithMeta + 'a>(
+    slot: Slot,
+    queued: impl Iterator<Item = &'a TransactionState<Tx>>,
+) -> BankingQueueSnapshot {
+    let mut queued: Vec<_> = queued.collect();
+    let transactions = queued
+        .iter()
+        .map(|state| QueuedTransaction {
+            priority: state.priority(),
+            cost: state.cost(),
+        })
+        .collect();
+
+    // Only the highest priority transactions are sorted, the others are not needed for the locks
+    let num_locking = queued.len().min(MAX_SNAPSHOT_LOCKING_TRANSACTIONS);
+    if num_locking < queued.len() {
+        queued.select_nth_unstable_by_key(num_locking, |state| Reverse(state.priority()));
+        queued.truncate(num_locking);
+    }
+    queued.sort_unstable_by_key(|state| Reverse(state.priority()));
+    let locking_transactions = queued
+        .into_iter()
+        .map(|state| {
+            let transaction = state.transaction();
+            let (writable_accounts, readonly_accounts) = transaction
+                .account_keys()
+                .iter()
+                .enumerate()
+                .partition::<Vec<_>, _>(|(index, _)| transaction.is_writable(*index));
+            LockingTransaction {
+                priority: state.priority(),
+                writable_accounts: writable_accounts.into_iter().map(|(_, key)| *key).collect(),
+                readonly_accounts: readonly_accounts.into_iter().map(|(_, key)| *key).collect(),
+            }
+        })
+        .collect();
+
+    BankingQueueSnapshot {
+        slot,
+        transactions,
+        locking_transactions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::banking_stage::scheduler_messages::MaxAge,
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_message::Message,
+        solana_pubkey::Pubkey,
+        solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+        solana_signer::Signer,
+        solana_svm_transaction::svm_message::SVMMessage,
+        solana_system_interface::instruction as system_instruction,
+        solana_transaction::{sanitized::SanitizedTransaction, Transaction},
+    };
+
+    fn transaction_state(
+        priority: u64,
+        to: &Pubkey,
+    ) -> TransactionState<RuntimeTransaction<SanitizedTransaction>> {
+        let from_keypair = Keypair::new();
+        let message = Message::new(
+            &[system_instruction::transfer(&from_keypair.pubkey(), to, 1)],
+            Some(&from_keypair.pubkey()),
+        );
+        let tx = Transaction::new(&[&from_keypair], message, Hash::default());
+        TransactionState::new(
+            RuntimeTransaction::from_transaction_for_tests(tx),
+            MaxAge::MAX,
+            priority,
+            5_000,
+        )
+    }
+
+    #[test]
+    fn test_take_queue_snapshot() {
+        let to = Pubkey::new_unique();
+        let states: Vec<_> = [3, 1, 2]
+            .into_iter()
+            .map(|priority| transaction_state(priority, &to))
+            .collect();
+
+        let snapshot = take_queue_snapshot(7, states.iter());
+        assert_eq!(snapshot.slot, 7);
+        assert_eq!(snapshot.transactions.len(), 3);
+        assert!(snapshot
+            .transactions
+            .iter()
+            .all(|transaction| transaction.cost == 5_000));
+        let priorities: Vec<_> = snapshot
+            .locking_transactions
+            .iter()
+            .map(|transaction| transaction.priority)
+            .collect();
+        assert_eq!(priorities, vec![3, 2, 1]);
+
+        let locking = &snapshot.locking_transactions[0];
+        assert_eq!(
+            locking.writable_accounts,
+            vec![*states[0].transaction().fee_payer(), to]
+        );
+        assert_eq!(
+            locking.readonly_accounts,
+            vec![solana_system_interface::program::id()]
+        );
+    }
+}
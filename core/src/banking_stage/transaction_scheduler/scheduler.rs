@@ -14,10 +14,13 @@ pub(crate) trait Scheduler<Tx: TransactionWithMeta> {
     /// Schedule transactions from `container`.
     /// pre-graph and pre-lock filters may be passed to be applied
     /// before specific actions internally.
+    /// Transactions which may not use the reserved capacity are only
+    /// scheduled up to `non_critical_budget`, on top of `budget`.
     fn schedule<S: StateContainer<Tx>>(
         &mut self,
         container: &mut S,
         budget: u64,
+        non_critical_budget: u64,
         relax_intrabatch_account_locks: bool,
         pre_graph_filter: impl Fn(&[&Tx], &mut [bool]),
         pre_lock_filter: impl Fn(&TransactionState<Tx>) -> PreLockFilterAction,
@@ -73,6 +76,9 @@ pub(crate) struct SchedulingSummary {
     pub num_unschedulable_conflicts: usize,
     /// Number of transactions that were skipped due to thread capacity.
     pub num_unschedulable_threads: usize,
+    /// Number of transactions that were skipped because the budget outside
+    /// the reserved capacity was used up.
+    pub num_unschedulable_reserved_capacity: usize,
     /// Number of transactions that were dropped due to filter.
     pub num_filtered_out: usize,
     /// Time spent filtering transactions
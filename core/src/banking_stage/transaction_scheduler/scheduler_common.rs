@@ -4,6 +4,7 @@ use {
     super::{
         in_flight_tracker::InFlightTracker, scheduler_error::SchedulerError,
         transaction_state_container::StateContainer,
+        write_lock_contention::WriteLockContentionMetrics,
     },
     crate::banking_stage::scheduler_messages::{
         ConsumeWork, FinishedConsumeWork, MaxAge, TransactionBatchId, TransactionId,
@@ -16,12 +17,32 @@ use {
     solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
 };
 
+/// Buffers of a batch, kept for reuse once the batch is completed.
+type BatchBuffers<Tx> = (Vec<TransactionId>, Vec<Tx>, Vec<MaxAge>);
+
+/// Number of completed batch buffers kept per worker thread. Enough for the
+/// batches in flight to be replaced without going to the allocator.
+const SPARE_BATCH_BUFFERS_PER_THREAD: usize = 4;
+
+/// Counts of the batch buffers handed out since the last call to
+/// [`Batches::take_buffer_counts`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchBufferCounts {
+    /// Buffers created because no completed batch buffers were kept
+    pub num_new: usize,
+    /// Buffers taken from the completed batches
+    pub num_recycled: usize,
+}
+
 pub struct Batches<Tx> {
     ids: Vec<Vec<TransactionId>>,
     transactions: Vec<Vec<Tx>>,
     max_ages: Vec<Vec<MaxAge>>,
     total_cus: Vec<u64>,
     target_num_transactions_per_batch: usize,
+    spare_buffers: Vec<BatchBuffers<Tx>>,
+    max_spare_buffers: usize,
+    buffer_counts: BatchBufferCounts,
 }
 
 impl<Tx> Batches<Tx> {
@@ -41,6 +62,9 @@ impl<Tx> Batches<Tx> {
             max_ages: make_vecs(num_threads, target_num_transactions_per_batch),
             total_cus: vec![0; num_threads],
             target_num_transactions_per_batch,
+            spare_buffers: Vec::with_capacity(num_threads * SPARE_BATCH_BUFFERS_PER_THREAD),
+            max_spare_buffers: num_threads * SPARE_BATCH_BUFFERS_PER_THREAD,
+            buffer_counts: BatchBufferCounts::default(),
         }
     }
 
@@ -78,22 +102,51 @@ impl<Tx> Batches<Tx> {
         &mut self,
         thread_id: ThreadId,
     ) -> (Vec<TransactionId>, Vec<Tx>, Vec<MaxAge>, u64) {
+        let (ids, transactions, max_ages) = match self.spare_buffers.pop() {
+            Some(buffers) => {
+                self.buffer_counts.num_recycled += 1;
+                buffers
+            }
+            None => {
+                self.buffer_counts.num_new += 1;
+                (
+                    Vec::with_capacity(self.target_num_transactions_per_batch),
+                    Vec::with_capacity(self.target_num_transactions_per_batch),
+                    Vec::with_capacity(self.target_num_transactions_per_batch),
+                )
+            }
+        };
         (
-            core::mem::replace(
-                &mut self.ids[thread_id],
-                Vec::with_capacity(self.target_num_transactions_per_batch),
-            ),
-            core::mem::replace(
-                &mut self.transactions[thread_id],
-                Vec::with_capacity(self.target_num_transactions_per_batch),
-            ),
-            core::mem::replace(
-                &mut self.max_ages[thread_id],
-                Vec::with_capacity(self.target_num_transactions_per_batch),
-            ),
+            core::mem::replace(&mut self.ids[thread_id], ids),
+            core::mem::replace(&mut self.transactions[thread_id], transactions),
+            core::mem::replace(&mut self.max_ages[thread_id], max_ages),
             core::mem::replace(&mut self.total_cus[thread_id], 0),
         )
     }
+
+    /// Keep the buffers of a completed batch to be handed out by
+    /// [`Self::take_batch`] instead of allocating new ones. The buffers are
+    /// dropped if enough are kept already.
+    pub fn recycle_buffers(
+        &mut self,
+        mut ids: Vec<TransactionId>,
+        mut transactions: Vec<Tx>,
+        mut max_ages: Vec<MaxAge>,
+    ) {
+        if self.spare_buffers.len() >= self.max_spare_buffers {
+            return;
+        }
+        ids.clear();
+        transactions.clear();
+        max_ages.clear();
+        self.spare_buffers.push((ids, transactions, max_ages));
+    }
+
+    /// Returns the counts of batch buffers handed out since the last call,
+    /// and resets them.
+    pub fn take_buffer_counts(&mut self) -> BatchBufferCounts {
+        core::mem::take(&mut self.buffer_counts)
+    }
 }
 
 /// A transaction has been scheduled to a thread.
@@ -111,6 +164,9 @@ pub enum TransactionSchedulingError {
     UnschedulableConflicts,
     /// Thread is not allowed to be scheduled on at this time.
     UnschedulableThread,
+    /// Transaction may not use the reserved capacity, and the rest of the
+    /// budget is used up.
+    UnschedulableReservedCapacity,
 }
 
 /// Given the schedulable `thread_set`, select the thread with the least amount
@@ -151,6 +207,7 @@ pub(crate) struct SchedulingCommon<Tx> {
     pub(crate) in_flight_tracker: InFlightTracker,
     pub(crate) account_locks: ThreadAwareAccountLocks,
     pub(crate) batches: Batches<Tx>,
+    pub(crate) write_lock_contention: WriteLockContentionMetrics,
 }
 
 impl<Tx> SchedulingCommon<Tx> {
@@ -174,6 +231,7 @@ impl<Tx> SchedulingCommon<Tx> {
             finished_consume_work_receiver,
             in_flight_tracker: InFlightTracker::new(num_threads),
             account_locks: ThreadAwareAccountLocks::new(num_threads),
+            write_lock_contention: WriteLockContentionMetrics::default(),
         }
     }
 
@@ -225,9 +283,9 @@ impl<Tx: TransactionWithMeta> SchedulingCommon<Tx> {
                 work:
                     ConsumeWork {
                         batch_id,
-                        ids,
-                        transactions,
-                        max_ages: _,
+                        mut ids,
+                        mut transactions,
+                        max_ages,
                     },
                 retryable_indexes,
             }) => {
@@ -239,7 +297,9 @@ impl<Tx: TransactionWithMeta> SchedulingCommon<Tx> {
 
                 // Assumption - retryable indexes are in order (sorted by workers).
                 let mut retryable_iter = retryable_indexes.iter().peekable();
-                for (index, (id, transaction)) in izip!(ids, transactions).enumerate() {
+                for (index, (id, transaction)) in
+                    izip!(ids.drain(..), transactions.drain(..)).enumerate()
+                {
                     if let Some(&retryable_index) = retryable_iter.peek() {
                         if retryable_index.index == index {
                             container.retry_transaction(
@@ -262,6 +322,7 @@ impl<Tx: TransactionWithMeta> SchedulingCommon<Tx> {
                         .map(|index| index.index)
                         .collect::<Vec<_>>(),
                 );
+                self.batches.recycle_buffers(ids, transactions, max_ages);
 
                 Ok((num_transactions, num_retryable))
             }
@@ -552,6 +613,50 @@ mod tests {
         assert_eq!(container.queue_size(), expected_num_retryable - 1); // held transaction not in queue.
     }
 
+    #[test]
+    fn test_receive_completed_recycles_batch_buffers() {
+        let mut container = TransactionStateContainer::with_capacity(1024);
+        add_transactions_to_container(&mut container, 2);
+
+        let (work_senders, work_receivers): (Vec<Sender<_>>, Vec<Receiver<_>>) =
+            (0..NUM_WORKERS).map(|_| unbounded()).unzip();
+        let (finished_work_sender, finished_work_receiver) = unbounded();
+        let mut common = SchedulingCommon::new(work_senders, finished_work_receiver, 10);
+
+        pop_and_add_transaction(&mut container, &mut common, 0);
+        common.send_batch(0).unwrap();
+        assert_eq!(
+            common.batches.take_buffer_counts(),
+            BatchBufferCounts {
+                num_new: 1,
+                num_recycled: 0,
+            }
+        );
+
+        let work = work_receivers[0].try_recv().unwrap();
+        let ids_ptr = work.ids.as_ptr();
+        finished_work_sender
+            .send(FinishedConsumeWork {
+                work,
+                retryable_indexes: vec![],
+            })
+            .unwrap();
+        common.try_receive_completed(&mut container).unwrap();
+
+        // The next batch replaces the one sent with the buffers of the completed batch.
+        pop_and_add_transaction(&mut container, &mut common, 0);
+        common.send_batch(0).unwrap();
+        assert_eq!(
+            common.batches.take_buffer_counts(),
+            BatchBufferCounts {
+                num_new: 0,
+                num_recycled: 1,
+            }
+        );
+        assert_eq!(common.batches.ids[0].as_ptr(), ids_ptr);
+        assert!(common.batches.ids[0].is_empty());
+    }
+
     #[test]
     #[should_panic = "retryable indexes were not in order: [1, 0]"]
     fn test_receive_completed_out_of_order() {
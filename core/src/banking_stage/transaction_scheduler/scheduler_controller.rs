@@ -3,16 +3,20 @@
 
 use {
     super::{
+        in_flight_tracker::InFlightBatchesReport,
+        queue_snapshot::{take_queue_snapshot, QUEUE_SNAPSHOT_INTERVAL},
         receive_and_buffer::{DisconnectedError, ReceiveAndBuffer},
         scheduler::{PreLockFilterAction, Scheduler},
         scheduler_error::SchedulerError,
         scheduler_metrics::{SchedulerCountMetrics, SchedulerTimingMetrics, SchedulingDetails},
+        write_lock_contention::WriteLockContentionReport,
     },
     crate::{
         banking_stage::{
             consume_worker::ConsumeWorkerMetrics,
             consumer::Consumer,
             decision_maker::{BufferedPacketsDecision, DecisionMaker},
+            reserved_capacity::ReservedCapacity,
             transaction_scheduler::{
                 receive_and_buffer::ReceivingStats, transaction_state_container::StateContainer,
             },
@@ -23,7 +27,10 @@ use {
     solana_clock::MAX_PROCESSING_AGE,
     solana_cost_model::cost_tracker::SharedBlockCost,
     solana_measure::measure_us,
-    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, banking_queue_snapshot::SharedBankingQueueSnapshot,
+        prioritization_fee_cache::PrioritizationFeeCache,
+    },
     solana_svm::transaction_error_metrics::TransactionErrorMetrics,
     std::{
         num::{NonZeroU64, Saturating},
@@ -62,6 +69,8 @@ where
     /// Exit signal for the scheduler thread.
     exit: Arc<AtomicBool>,
     config: SchedulerConfig,
+    /// Capacity reserved for critical transactions.
+    reserved_capacity: Arc<ReservedCapacity>,
     /// Decision maker for determining what should be done with transactions.
     decision_maker: DecisionMaker,
     receive_and_buffer: R,
@@ -81,6 +90,14 @@ where
     worker_metrics: Vec<Arc<ConsumeWorkerMetrics>>,
     /// Detailed scheduling metrics.
     scheduling_details: SchedulingDetails,
+    /// Hottest writable accounts of the recent leader slots.
+    write_lock_contention_report: Arc<WriteLockContentionReport>,
+    /// Receives the depth of the queue, for the RPC service.
+    prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    /// Receives the snapshots of the queue, if the RPC service previews transaction priorities.
+    banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
+    /// When the last snapshot of the queue was published.
+    last_queue_snapshot: Instant,
 }
 
 impl<R, S> SchedulerController<R, S>
@@ -88,18 +105,29 @@ where
     R: ReceiveAndBuffer,
     S: Scheduler<R::Transaction>,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         exit: Arc<AtomicBool>,
         config: SchedulerConfig,
+        reserved_capacity: Arc<ReservedCapacity>,
         decision_maker: DecisionMaker,
         receive_and_buffer: R,
         bank_forks: Arc<RwLock<BankForks>>,
-        scheduler: S,
+        mut scheduler: S,
         worker_metrics: Vec<Arc<ConsumeWorkerMetrics>>,
+        write_lock_contention_report: Arc<WriteLockContentionReport>,
+        in_flight_batches_report: Arc<InFlightBatchesReport>,
+        prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
     ) -> Self {
+        scheduler
+            .scheduling_common_mut()
+            .in_flight_tracker
+            .set_report(in_flight_batches_report);
         Self {
             exit,
             config,
+            reserved_capacity,
             decision_maker,
             receive_and_buffer,
             bank_forks,
@@ -109,6 +137,10 @@ where
             timing_metrics: SchedulerTimingMetrics::default(),
             worker_metrics,
             scheduling_details: SchedulingDetails::default(),
+            write_lock_contention_report,
+            prioritization_fee_cache,
+            banking_queue_snapshot,
+            last_queue_snapshot: Instant::now(),
         }
     }
 
@@ -138,6 +170,10 @@ where
                 .maybe_report_and_reset_slot(new_leader_slot);
             self.timing_metrics
                 .maybe_report_and_reset_slot(new_leader_slot);
+            self.scheduler
+                .scheduling_common_mut()
+                .write_lock_contention
+                .maybe_report_and_reset_slot(new_leader_slot, &self.write_lock_contention_report);
 
             if most_recent_leader_slot != new_leader_slot {
                 self.container.flush_held_transactions();
@@ -167,6 +203,7 @@ where
 
                     CostPacer {
                         block_limit,
+                        reserved_block_cus: self.reserved_capacity.reserved_block_cus(block_limit),
                         shared_block_cost,
                         detection_time: now,
                         fill_time,
@@ -179,6 +216,18 @@ where
             if self.receive_and_buffer_packets(&decision).is_err() {
                 break;
             }
+            self.prioritization_fee_cache
+                .update_banking_queue_depth(self.container.queue_size());
+            if let Some(banking_queue_snapshot) = &self.banking_queue_snapshot {
+                if self.last_queue_snapshot.elapsed() >= QUEUE_SNAPSHOT_INTERVAL {
+                    let slot = self.bank_forks.read().unwrap().working_bank().slot();
+                    banking_queue_snapshot.update(take_queue_snapshot(
+                        slot,
+                        self.container.queued_transactions(),
+                    ));
+                    self.last_queue_snapshot = Instant::now();
+                }
+            }
             // Report metrics only if there is data.
             // Reset intervals when appropriate, regardless of report.
             let should_report = self.count_metrics.interval_has_data();
@@ -208,12 +257,13 @@ where
     ) -> Result<(), SchedulerError> {
         match decision {
             BufferedPacketsDecision::Consume(bank) => {
-                let scheduling_budget = cost_pacer
-                    .expect("cost pacer must be set for Consume")
-                    .scheduling_budget(now);
+                let cost_pacer = cost_pacer.expect("cost pacer must be set for Consume");
+                let scheduling_budget = cost_pacer.scheduling_budget(now);
+                let non_critical_scheduling_budget = cost_pacer.non_critical_scheduling_budget(now);
                 let (scheduling_summary, schedule_time_us) = measure_us!(self.scheduler.schedule(
                     &mut self.container,
                     scheduling_budget,
+                    non_critical_scheduling_budget,
                     bank.feature_set
                         .is_active(&agave_feature_set::relax_intrabatch_account_locks::ID),
                     |txs, results| {
@@ -221,13 +271,22 @@ where
                     },
                     |_| PreLockFilterAction::AttemptToSchedule // no pre-lock filter for now
                 )?);
+                let batch_buffer_counts = self
+                    .scheduler
+                    .scheduling_common_mut()
+                    .batches
+                    .take_buffer_counts();
 
                 self.count_metrics.update(|count_metrics| {
                     count_metrics.num_scheduled += scheduling_summary.num_scheduled;
+                    count_metrics.num_new_batch_buffers += batch_buffer_counts.num_new;
+                    count_metrics.num_recycled_batch_buffers += batch_buffer_counts.num_recycled;
                     count_metrics.num_unschedulable_conflicts +=
                         scheduling_summary.num_unschedulable_conflicts;
                     count_metrics.num_unschedulable_threads +=
                         scheduling_summary.num_unschedulable_threads;
+                    count_metrics.num_unschedulable_reserved_capacity +=
+                        scheduling_summary.num_unschedulable_reserved_capacity;
                     count_metrics.num_schedule_filtered_out += scheduling_summary.num_filtered_out;
                 });
 
@@ -423,6 +482,8 @@ where
 
 struct CostPacer {
     block_limit: u64,
+    /// Compute units of the block only critical transactions can use.
+    reserved_block_cus: u64,
     shared_block_cost: SharedBlockCost,
     detection_time: Instant,
     fill_time: Option<Duration>,
@@ -430,7 +491,22 @@ struct CostPacer {
 
 impl CostPacer {
     fn scheduling_budget(&self, current_time: &Instant) -> u64 {
-        let target = if let Some(fill_time) = &self.fill_time {
+        self.target(current_time)
+            .saturating_sub(self.shared_block_cost.load())
+    }
+
+    /// Budget of the transactions which may not use the reserved compute
+    /// units. Workers would reject them past the unreserved compute units, only
+    /// for them to be rescheduled for the rest of the slot.
+    fn non_critical_scheduling_budget(&self, current_time: &Instant) -> u64 {
+        let unreserved_block_cus = self.block_limit.saturating_sub(self.reserved_block_cus);
+        self.target(current_time)
+            .min(unreserved_block_cus)
+            .saturating_sub(self.shared_block_cost.load())
+    }
+
+    fn target(&self, current_time: &Instant) -> u64 {
+        if let Some(fill_time) = &self.fill_time {
             let time_since = current_time.saturating_duration_since(self.detection_time);
             if time_since >= *fill_time {
                 self.block_limit
@@ -442,9 +518,7 @@ impl CostPacer {
             }
         } else {
             self.block_limit
-        };
-
-        target.saturating_sub(self.shared_block_cost.load())
+        }
     }
 }
 
@@ -551,11 +625,16 @@ mod tests {
         let scheduler_controller = SchedulerController::new(
             exit,
             SchedulerConfig::default(),
+            Arc::default(), // reserved_capacity
             decision_maker,
             receive_and_buffer,
             bank_forks,
             scheduler,
             vec![], // no actual workers with metrics to report, this can be empty
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            None,
         );
 
         (test_frame, scheduler_controller)
@@ -622,6 +701,7 @@ mod tests {
                 &decision,
                 Some(&CostPacer {
                     block_limit: u64::MAX,
+                    reserved_block_cus: 0,
                     shared_block_cost: SharedBlockCost::new(0),
                     detection_time: now.checked_sub(Duration::from_millis(400)).unwrap(),
                     fill_time: Some(Duration::from_millis(300)),
@@ -993,4 +1073,41 @@ mod tests {
             .collect_vec();
         assert_eq!(message_hashes, vec![&tx1_hash]);
     }
+
+    #[test]
+    fn test_cost_pacer_non_critical_scheduling_budget() {
+        let now = Instant::now();
+        let cost_pacer = CostPacer {
+            block_limit: 1_000,
+            reserved_block_cus: 200,
+            shared_block_cost: SharedBlockCost::new(500),
+            detection_time: now,
+            fill_time: None,
+        };
+        assert_eq!(cost_pacer.scheduling_budget(&now), 500);
+        assert_eq!(cost_pacer.non_critical_scheduling_budget(&now), 300);
+
+        // Unreserved share is full, only the reserved share remains.
+        let cost_pacer = CostPacer {
+            shared_block_cost: SharedBlockCost::new(900),
+            ..cost_pacer
+        };
+        assert_eq!(cost_pacer.scheduling_budget(&now), 100);
+        assert_eq!(cost_pacer.non_critical_scheduling_budget(&now), 0);
+
+        // Paced target below the unreserved share limits both budgets.
+        let cost_pacer = CostPacer {
+            block_limit: 1_000,
+            reserved_block_cus: 200,
+            shared_block_cost: SharedBlockCost::new(0),
+            detection_time: now,
+            fill_time: Some(Duration::from_millis(10)),
+        };
+        let current_time = now + Duration::from_millis(5);
+        assert_eq!(cost_pacer.scheduling_budget(&current_time), 500);
+        assert_eq!(
+            cost_pacer.non_critical_scheduling_budget(&current_time),
+            500
+        );
+    }
 }
@@ -58,6 +58,9 @@ pub struct SchedulerCountMetricsInner {
     pub num_unschedulable_conflicts: Saturating<usize>,
     /// Number of transactions that were unschedulable due to thread capacity.
     pub num_unschedulable_threads: Saturating<usize>,
+    /// Number of transactions that were unschedulable because the budget
+    /// outside the reserved capacity was used up.
+    pub num_unschedulable_reserved_capacity: Saturating<usize>,
     /// Number of transactions that were filtered out during scheduling.
     pub num_schedule_filtered_out: Saturating<usize>,
     /// Number of completed transactions received from workers.
@@ -86,6 +89,10 @@ pub struct SchedulerCountMetricsInner {
     pub num_dropped_on_clean: Saturating<usize>,
     /// Number of transactions that were dropped due to exceeded capacity.
     pub num_dropped_on_capacity: Saturating<usize>,
+    /// Number of batch buffers created to send work to the workers.
+    pub num_new_batch_buffers: Saturating<usize>,
+    /// Number of batch buffers recycled from completed batches.
+    pub num_recycled_batch_buffers: Saturating<usize>,
     /// Min prioritization fees in the transaction container
     pub min_prioritization_fees: u64,
     /// Max prioritization fees in the transaction container
@@ -126,6 +133,7 @@ impl SchedulerCountMetricsInner {
             num_scheduled: Saturating(num_scheduled),
             num_unschedulable_conflicts: Saturating(num_unschedulable_conflicts),
             num_unschedulable_threads: Saturating(num_unschedulable_threads),
+            num_unschedulable_reserved_capacity: Saturating(num_unschedulable_reserved_capacity),
             num_schedule_filtered_out: Saturating(num_schedule_filtered_out),
             num_finished: Saturating(num_finished),
             num_retryable: Saturating(num_retryable),
@@ -141,6 +149,8 @@ impl SchedulerCountMetricsInner {
             num_dropped_on_clear: Saturating(num_dropped_on_clear),
             num_dropped_on_clean: Saturating(num_dropped_on_clean),
             num_dropped_on_capacity: Saturating(num_dropped_on_capacity),
+            num_new_batch_buffers: Saturating(num_new_batch_buffers),
+            num_recycled_batch_buffers: Saturating(num_recycled_batch_buffers),
             min_prioritization_fees: _min_prioritization_fees,
             max_prioritization_fees: _max_prioritization_fees,
         } = self;
@@ -151,6 +161,11 @@ impl SchedulerCountMetricsInner {
             ("num_scheduled", num_scheduled, i64),
             ("num_unschedulable_conflicts", num_unschedulable_conflicts, i64),
             ("num_unschedulable_threads", num_unschedulable_threads, i64),
+            (
+                "num_unschedulable_reserved_capacity",
+                num_unschedulable_reserved_capacity,
+                i64
+            ),
             (
                 "num_schedule_filtered_out",
                 num_schedule_filtered_out,
@@ -192,6 +207,8 @@ impl SchedulerCountMetricsInner {
                 i64
             ),
             ("num_dropped_on_capacity", num_dropped_on_capacity, i64),
+            ("num_new_batch_buffers", num_new_batch_buffers, i64),
+            ("num_recycled_batch_buffers", num_recycled_batch_buffers, i64),
             ("min_priority", self.get_min_priority(), i64),
             ("max_priority", self.get_max_priority(), i64)
         );
@@ -207,6 +224,7 @@ impl SchedulerCountMetricsInner {
             || self.num_scheduled != Saturating(0)
             || self.num_unschedulable_conflicts != Saturating(0)
             || self.num_unschedulable_threads != Saturating(0)
+            || self.num_unschedulable_reserved_capacity != Saturating(0)
             || self.num_schedule_filtered_out != Saturating(0)
             || self.num_finished != Saturating(0)
             || self.num_retryable != Saturating(0)
@@ -218,6 +236,7 @@ impl SchedulerCountMetricsInner {
         self.num_scheduled = Saturating(0);
         self.num_unschedulable_conflicts = Saturating(0);
         self.num_unschedulable_threads = Saturating(0);
+        self.num_unschedulable_reserved_capacity = Saturating(0);
         self.num_schedule_filtered_out = Saturating(0);
         self.num_finished = Saturating(0);
         self.num_retryable = Saturating(0);
@@ -231,6 +250,8 @@ impl SchedulerCountMetricsInner {
         self.num_dropped_on_clear = Saturating(0);
         self.num_dropped_on_clean = Saturating(0);
         self.num_dropped_on_capacity = Saturating(0);
+        self.num_new_batch_buffers = Saturating(0);
+        self.num_recycled_batch_buffers = Saturating(0);
         self.min_prioritization_fees = u64::MAX;
         self.max_prioritization_fees = 0;
     }
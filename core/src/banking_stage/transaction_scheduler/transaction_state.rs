@@ -1,6 +1,6 @@
-use crate::banking_stage::scheduler_messages::MaxAge;
 #[cfg(feature = "dev-context-only-utils")]
 use qualifier_attr::qualifiers;
+use {crate::banking_stage::scheduler_messages::MaxAge, std::time::Instant};
 
 /// TransactionState is used to track the state of a transaction in the transaction scheduler
 /// and banking stage as a whole.
@@ -23,6 +23,9 @@ pub(crate) struct TransactionState<Tx> {
     priority: u64,
     /// Estimated cost of the transaction.
     cost: u64,
+    /// When the transaction was first found to be unschedulable due to
+    /// account lock conflicts, if it has not been scheduled since.
+    blocked_since: Option<Instant>,
 }
 
 impl<Tx> TransactionState<Tx> {
@@ -33,6 +36,7 @@ impl<Tx> TransactionState<Tx> {
             max_age,
             priority,
             cost,
+            blocked_since: None,
         }
     }
 
@@ -48,6 +52,22 @@ impl<Tx> TransactionState<Tx> {
         self.cost
     }
 
+    /// Marks the transaction as blocked by account lock conflicts.
+    /// Returns `false` if it was already blocked.
+    pub(crate) fn mark_blocked(&mut self, now: Instant) -> bool {
+        if self.blocked_since.is_some() {
+            return false;
+        }
+        self.blocked_since = Some(now);
+        true
+    }
+
+    /// Takes the time the transaction was first blocked by account lock
+    /// conflicts, if it was blocked.
+    pub(crate) fn take_blocked_since(&mut self) -> Option<Instant> {
+        self.blocked_since.take()
+    }
+
     /// Intended to be called when a transaction is scheduled. This method
     /// takes ownership of the transaction from the state.
     ///
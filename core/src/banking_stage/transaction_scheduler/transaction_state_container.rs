@@ -112,6 +112,10 @@ pub(crate) trait StateContainer<Tx: TransactionWithMeta> {
 
     fn get_min_max_priority(&self) -> MinMaxResult<u64>;
 
+    /// Get the state of the transactions in the queue and the held transactions, in no
+    /// particular order.
+    fn queued_transactions(&self) -> impl Iterator<Item = &TransactionState<Tx>>;
+
     #[cfg(feature = "dev-context-only-utils")]
     fn clear(&mut self);
 }
@@ -208,6 +212,13 @@ impl<Tx: TransactionWithMeta> StateContainer<Tx> for TransactionStateContainer<T
         }
     }
 
+    fn queued_transactions(&self) -> impl Iterator<Item = &TransactionState<Tx>> {
+        self.priority_queue
+            .iter()
+            .chain(self.held_transactions.iter())
+            .filter_map(|priority_id| self.id_to_transaction_state.get(priority_id.id))
+    }
+
     #[cfg(feature = "dev-context-only-utils")]
     fn clear(&mut self) {
         self.priority_queue.clear();
@@ -370,6 +381,11 @@ impl StateContainer<RuntimeTransactionView> for TransactionViewStateContainer {
         self.inner.get_min_max_priority()
     }
 
+    #[inline]
+    fn queued_transactions(&self) -> impl Iterator<Item = &TransactionViewState> {
+        self.inner.queued_transactions()
+    }
+
     #[cfg(feature = "dev-context-only-utils")]
     #[inline]
     fn clear(&mut self) {
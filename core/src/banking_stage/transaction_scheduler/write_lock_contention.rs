@@ -0,0 +1,268 @@
+//! Tracking of the writable accounts that most often keep the scheduler from scheduling
+//! transactions.
+//!
+//! When a transaction cannot be scheduled because of account lock conflicts, every account it
+//! write-locks is charged with a conflicting transaction, and once the transaction is finally
+//! scheduled they are charged with the time it waited. Hot accounts show up in many blocked
+//! transactions, so they quickly rise above the other accounts those transactions happen to write.
+//!
+//! The totals are kept per leader slot. At the end of each slot the hottest accounts are reported
+//! as metrics and added to a [`WriteLockContentionReport`], which keeps the most recent slots for
+//! the admin RPC.
+
+use {
+    super::transaction_state::TransactionState,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::Mutex,
+        time::Instant,
+    },
+};
+
+/// Number of leader slots kept in a [`WriteLockContentionReport`]
+pub const WRITE_LOCK_CONTENTION_REPORT_SLOTS: usize = 64;
+
+/// Number of accounts kept per slot in a [`WriteLockContentionReport`]
+const MAX_ACCOUNTS_PER_SLOT: usize = 32;
+
+/// Number of accounts per slot reported as metrics
+const MAX_ACCOUNTS_PER_SLOT_METRICS: usize = 5;
+
+/// Contention on a single writable account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccountContention {
+    /// Number of transactions writing the account that could not be scheduled because of lock
+    /// conflicts
+    pub conflicting_transactions: u64,
+    /// Total time those transactions waited before they were scheduled
+    pub lock_wait_us: u64,
+}
+
+impl AccountContention {
+    fn add(&mut self, other: &Self) {
+        self.conflicting_transactions = self
+            .conflicting_transactions
+            .saturating_add(other.conflicting_transactions);
+        self.lock_wait_us = self.lock_wait_us.saturating_add(other.lock_wait_us);
+    }
+}
+
+/// The hottest writable accounts over the most recent leader slots
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WriteLockContentionSummary {
+    /// The leader slots the summary covers, oldest first
+    pub slots: Vec<Slot>,
+    /// The hottest accounts, most conflicting transactions first
+    pub accounts: Vec<(Pubkey, AccountContention)>,
+}
+
+/// Rolling report of the hottest writable accounts of the recent leader slots
+#[derive(Default)]
+pub struct WriteLockContentionReport {
+    slots: Mutex<VecDeque<(Slot, Vec<(Pubkey, AccountContention)>)>>,
+}
+
+impl WriteLockContentionReport {
+    fn push_slot(&self, slot: Slot, accounts: Vec<(Pubkey, AccountContention)>) {
+        let mut slots = self.slots.lock().unwrap();
+        if slots.len() == WRITE_LOCK_CONTENTION_REPORT_SLOTS {
+            slots.pop_front();
+        }
+        slots.push_back((slot, accounts));
+    }
+
+    /// Returns the `limit` hottest accounts summed over the slots in the report
+    pub fn summary(&self, limit: usize) -> WriteLockContentionSummary {
+        let slots = self.slots.lock().unwrap();
+        let mut totals = HashMap::<Pubkey, AccountContention>::new();
+        for (_, accounts) in slots.iter() {
+            for (pubkey, contention) in accounts {
+                totals.entry(*pubkey).or_default().add(contention);
+            }
+        }
+        WriteLockContentionSummary {
+            slots: slots.iter().map(|(slot, _)| *slot).collect(),
+            accounts: hottest_accounts(totals, limit),
+        }
+    }
+}
+
+/// Contention recorded by the scheduler during the current leader slot
+#[derive(Default)]
+pub(crate) struct WriteLockContentionMetrics {
+    slot: Option<Slot>,
+    accounts: HashMap<Pubkey, AccountContention>,
+}
+
+impl WriteLockContentionMetrics {
+    /// Records that `transaction_state` could not be scheduled because of lock conflicts
+    ///
+    /// A transaction is only counted the first time it is blocked.
+    pub(crate) fn record_conflict<Tx: TransactionWithMeta>(
+        &mut self,
+        transaction_state: &mut TransactionState<Tx>,
+        now: Instant,
+    ) {
+        if !transaction_state.mark_blocked(now) {
+            return;
+        }
+        for pubkey in writable_accounts(transaction_state.transaction()) {
+            let contention = self.accounts.entry(*pubkey).or_default();
+            contention.conflicting_transactions =
+                contention.conflicting_transactions.saturating_add(1);
+        }
+    }
+
+    /// Records that `transaction`, which was blocked since `blocked_since`, has been scheduled
+    pub(crate) fn record_scheduled<Tx: TransactionWithMeta>(
+        &mut self,
+        transaction: &Tx,
+        blocked_since: Instant,
+        now: Instant,
+    ) {
+        let lock_wait_us = now.saturating_duration_since(blocked_since).as_micros() as u64;
+        for pubkey in writable_accounts(transaction) {
+            let contention = self.accounts.entry(*pubkey).or_default();
+            contention.lock_wait_us = contention.lock_wait_us.saturating_add(lock_wait_us);
+        }
+    }
+
+    /// Reports the hottest accounts of the previous leader slot once `slot` changes
+    pub(crate) fn maybe_report_and_reset_slot(
+        &mut self,
+        slot: Option<Slot>,
+        report: &WriteLockContentionReport,
+    ) {
+        if self.slot == slot {
+            return;
+        }
+        let accounts = std::mem::take(&mut self.accounts);
+        if let Some(previous_slot) = std::mem::replace(&mut self.slot, slot) {
+            let accounts = hottest_accounts(accounts, MAX_ACCOUNTS_PER_SLOT);
+            for (rank, (pubkey, contention)) in accounts
+                .iter()
+                .take(MAX_ACCOUNTS_PER_SLOT_METRICS)
+                .enumerate()
+            {
+                datapoint_info!(
+                    "banking_stage_write_lock_contention",
+                    ("slot", previous_slot, i64),
+                    ("rank", rank, i64),
+                    ("account", pubkey.to_string(), String),
+                    (
+                        "conflicting_transactions",
+                        contention.conflicting_transactions,
+                        i64
+                    ),
+                    ("lock_wait_us", contention.lock_wait_us, i64),
+                );
+            }
+            report.push_slot(previous_slot, accounts);
+        }
+    }
+}
+
+fn writable_accounts<Tx: TransactionWithMeta>(transaction: &Tx) -> impl Iterator<Item = &Pubkey> {
+    transaction
+        .account_keys()
+        .iter()
+        .enumerate()
+        .filter_map(|(index, key)| transaction.is_writable(index).then_some(key))
+}
+
+fn hottest_accounts(
+    accounts: HashMap<Pubkey, AccountContention>,
+    limit: usize,
+) -> Vec<(Pubkey, AccountContention)> {
+    let mut accounts: Vec<_> = accounts.into_iter().collect();
+    accounts.sort_unstable_by(|(pubkey_a, a), (pubkey_b, b)| {
+        b.conflicting_transactions
+            .cmp(&a.conflicting_transactions)
+            .then(b.lock_wait_us.cmp(&a.lock_wait_us))
+            .then(pubkey_a.cmp(pubkey_b))
+    });
+    accounts.truncate(limit);
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::banking_stage::scheduler_messages::MaxAge, solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+        solana_system_transaction as system_transaction,
+        solana_transaction::sanitized::SanitizedTransaction, std::time::Duration,
+    };
+
+    fn transfer_state(to: &Pubkey) -> TransactionState<RuntimeTransaction<SanitizedTransaction>> {
+        let transaction = RuntimeTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&Keypair::new(), to, 1, Hash::default()),
+        );
+        TransactionState::new(transaction, MaxAge::MAX, 0, 0)
+    }
+
+    #[test]
+    fn test_write_lock_contention() {
+        let hot_account = Pubkey::new_unique();
+        let report = WriteLockContentionReport::default();
+        let mut metrics = WriteLockContentionMetrics::default();
+        metrics.maybe_report_and_reset_slot(Some(1), &report);
+
+        let now = Instant::now();
+        let mut states: Vec<_> = (0..3).map(|_| transfer_state(&hot_account)).collect();
+        for state in &mut states {
+            metrics.record_conflict(state, now);
+        }
+        // a transaction is counted once however often it is blocked
+        metrics.record_conflict(&mut states[0], now);
+        let blocked_since = states[0].take_blocked_since().unwrap();
+        metrics.record_scheduled(
+            states[0].transaction(),
+            blocked_since,
+            now + Duration::from_micros(250),
+        );
+
+        // nothing is reported until the slot ends
+        assert_eq!(report.summary(1), WriteLockContentionSummary::default());
+        metrics.maybe_report_and_reset_slot(None, &report);
+
+        let summary = report.summary(1);
+        assert_eq!(summary.slots, vec![1]);
+        assert_eq!(
+            summary.accounts,
+            vec![(
+                hot_account,
+                AccountContention {
+                    conflicting_transactions: 3,
+                    lock_wait_us: 250,
+                }
+            )]
+        );
+        // the fee payers were charged as well, but only once each
+        let summary = report.summary(usize::MAX);
+        assert_eq!(summary.accounts.len(), 4);
+        assert!(summary.accounts[1..]
+            .iter()
+            .all(|(_, contention)| contention.conflicting_transactions == 1));
+
+        // the totals are summed over the slots in the report
+        metrics.maybe_report_and_reset_slot(Some(5), &report);
+        metrics.record_conflict(&mut transfer_state(&hot_account), now);
+        metrics.maybe_report_and_reset_slot(Some(6), &report);
+        let summary = report.summary(1);
+        assert_eq!(summary.slots, vec![1, 5]);
+        assert_eq!(summary.accounts[0].1.conflicting_transactions, 4);
+
+        // only the most recent slots are kept
+        for slot in 6..6 + WRITE_LOCK_CONTENTION_REPORT_SLOTS as Slot {
+            metrics.maybe_report_and_reset_slot(Some(slot + 1), &report);
+        }
+        let summary = report.summary(1);
+        assert_eq!(summary.slots.len(), WRITE_LOCK_CONTENTION_REPORT_SLOTS);
+        assert!(summary.accounts.is_empty());
+    }
+}
@@ -29,10 +29,7 @@
 #[cfg(feature = "dev-context-only-utils")]
 use qualifier_attr::qualifiers;
 use {
-    super::{
-        decision_maker::{BufferedPacketsDecision, DecisionMaker, DecisionMakerWrapper},
-        transaction_scheduler::receive_and_buffer::calculate_priority_and_cost,
-    },
+    super::decision_maker::{BufferedPacketsDecision, DecisionMaker, DecisionMakerWrapper},
     crate::banking_trace::Channels,
     agave_banking_stage_ingress_types::BankingPacketBatch,
     solana_accounts_db::account_locks::validate_account_locks,
@@ -40,7 +37,9 @@ use {
     solana_clock::Slot,
     solana_message::{v0::LoadedAddresses, SimpleAddressLoader},
     solana_poh::{poh_recorder::PohRecorder, transaction_recorder::TransactionRecorder},
-    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, banking_queue_snapshot::calculate_priority_and_cost,
+    },
     solana_runtime_transaction::{
         runtime_transaction::RuntimeTransaction, transaction_meta::StaticMeta,
     },
@@ -0,0 +1,144 @@
+//! Prepares for an epoch before replay and retransmit need it.
+//!
+//! The stakes of the leader schedule epoch are fixed as soon as the first bank of the previous
+//! epoch is created, but its leader schedule is only computed by replay once such a bank is
+//! rooted, and the turbine tree of an epoch is computed by retransmit on receiving its first
+//! shred. This service computes both on its own thread instead, starting a configurable number
+//! of slots before the epoch boundary.
+
+use {
+    solana_clock::{Epoch, Slot},
+    solana_gossip::cluster_info::ClusterInfo,
+    solana_ledger::leader_schedule_cache::LeaderScheduleCache,
+    solana_measure::measure::Measure,
+    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    solana_turbine::{cluster_nodes::ClusterNodesCache, retransmit_stage::RetransmitStage},
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// ~1 minute of slots
+pub const DEFAULT_EPOCH_PRECOMPUTE_SLOTS: u64 = 150;
+
+const LOOP_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct EpochPrecomputeService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl EpochPrecomputeService {
+    pub fn new(
+        precompute_slots: Slot,
+        bank_forks: Arc<RwLock<BankForks>>,
+        leader_schedule_cache: Arc<LeaderScheduleCache>,
+        cluster_nodes_cache: Arc<ClusterNodesCache<RetransmitStage>>,
+        cluster_info: Arc<ClusterInfo>,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("solEpochPrecomp".to_string())
+            .spawn(move || {
+                let mut precomputed_epoch = Epoch::default();
+                while !exit.load(Ordering::Relaxed) {
+                    let (root_bank, working_bank) = {
+                        let bank_forks = bank_forks.read().unwrap();
+                        (bank_forks.root_bank(), bank_forks.working_bank())
+                    };
+                    precompute_leader_schedule(
+                        &working_bank,
+                        &leader_schedule_cache,
+                        &mut precomputed_epoch,
+                    );
+                    precompute_turbine_tree(
+                        precompute_slots,
+                        &root_bank,
+                        &working_bank,
+                        &cluster_nodes_cache,
+                        &cluster_info,
+                    );
+                    sleep(LOOP_INTERVAL);
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+/// Computes the leader schedule of the leader schedule epoch of `working_bank` once per epoch,
+/// to be used by [`LeaderScheduleCache::set_root`] if the root ends up with the same stakes
+fn precompute_leader_schedule(
+    working_bank: &Bank,
+    leader_schedule_cache: &LeaderScheduleCache,
+    precomputed_epoch: &mut Epoch,
+) {
+    let epoch = working_bank.get_leader_schedule_epoch(working_bank.slot());
+    if epoch <= *precomputed_epoch {
+        return;
+    }
+    let mut measure = Measure::start("precompute_leader_schedule");
+    if leader_schedule_cache.precompute_epoch_schedule(epoch, working_bank) {
+        measure.stop();
+        *precomputed_epoch = epoch;
+        info!(
+            "Precomputed the leader schedule of epoch {epoch} from slot {} in {}us",
+            working_bank.slot(),
+            measure.as_us()
+        );
+    }
+}
+
+/// Keeps the retransmit turbine tree of the next epoch fresh while the working bank is within
+/// `precompute_slots` of the epoch boundary
+fn precompute_turbine_tree(
+    precompute_slots: Slot,
+    root_bank: &Bank,
+    working_bank: &Bank,
+    cluster_nodes_cache: &ClusterNodesCache<RetransmitStage>,
+    cluster_info: &ClusterInfo,
+) {
+    let next_epoch_slot = working_bank
+        .epoch_schedule()
+        .get_first_slot_in_epoch(working_bank.epoch().saturating_add(1));
+    if next_epoch_slot.saturating_sub(working_bank.slot()) <= precompute_slots {
+        cluster_nodes_cache.refresh(next_epoch_slot, root_bank, working_bank, cluster_info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_pubkey::Pubkey,
+    };
+
+    #[test]
+    fn test_precompute_leader_schedule() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&bank);
+        let mut precomputed_epoch = Epoch::default();
+
+        // The schedule of epoch 1 was computed from the root
+        precompute_leader_schedule(&bank, &leader_schedule_cache, &mut precomputed_epoch);
+        assert_eq!(precomputed_epoch, 0);
+
+        // A bank in epoch 1 has the stakes of epoch 2, which is not rooted yet
+        let bank = Bank::new_from_parent(bank, &Pubkey::new_unique(), 95);
+        precompute_leader_schedule(&bank, &leader_schedule_cache, &mut precomputed_epoch);
+        assert_eq!(precomputed_epoch, 2);
+        assert!(leader_schedule_cache.get_epoch_leader_schedule(2).is_none());
+        leader_schedule_cache.set_root(&bank);
+        assert!(leader_schedule_cache.get_epoch_leader_schedule(2).is_some());
+    }
+}
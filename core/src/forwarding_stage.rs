@@ -7,6 +7,7 @@ use {
     agave_transaction_view::transaction_view::SanitizedTransactionView,
     async_trait::async_trait,
     crossbeam_channel::{Receiver, RecvTimeoutError},
+    forwarding_deny::ForwardingDeny,
     packet_container::PacketContainer,
     solana_client::connection_cache::ConnectionCache,
     solana_connection_cache::client_connection::ClientConnection,
@@ -50,6 +51,7 @@ use {
     tokio_util::sync::CancellationToken,
 };
 
+pub mod forwarding_deny;
 mod packet_container;
 
 /// [`ForwardingClientOption`] enum represents the available client types for
@@ -137,6 +139,7 @@ pub(crate) fn spawn_forwarding_stage(
     sharable_banks: SharableBanks,
     forward_address_getter: ForwardAddressGetter,
     data_budget: DataBudget,
+    forwarding_deny: Option<ForwardingDeny>,
 ) -> SpawnForwardingStageResult {
     let vote_client = VoteClient::new(vote_client_udp_socket, forward_address_getter.clone());
     match client {
@@ -150,6 +153,7 @@ pub(crate) fn spawn_forwarding_stage(
                 sharable_banks,
                 data_budget,
                 None,
+                forwarding_deny,
             );
             SpawnForwardingStageResult {
                 join_handle: Builder::new()
@@ -188,6 +192,7 @@ pub(crate) fn spawn_forwarding_stage(
                 sharable_banks,
                 data_budget,
                 Some(node_multihoming.bind_ip_addrs.clone()),
+                forwarding_deny,
             );
             SpawnForwardingStageResult {
                 join_handle: Builder::new()
@@ -218,6 +223,7 @@ struct ForwardingStage<VoteClient: ForwardingClient, NonVoteClient: ForwardingCl
     data_budget: DataBudget,
     metrics: ForwardingStageMetrics,
     bind_ip_addrs: Option<Arc<BindIpAddrs>>,
+    forwarding_deny: Option<ForwardingDeny>,
 }
 
 impl<VoteClient: ForwardingClient, NonVoteClient: ForwardingClient>
@@ -230,6 +236,7 @@ impl<VoteClient: ForwardingClient, NonVoteClient: ForwardingClient>
         sharable_banks: SharableBanks,
         data_budget: DataBudget,
         bind_ip_addrs: Option<Arc<BindIpAddrs>>,
+        forwarding_deny: Option<ForwardingDeny>,
     ) -> Self {
         Self {
             receiver,
@@ -240,6 +247,7 @@ impl<VoteClient: ForwardingClient, NonVoteClient: ForwardingClient>
             data_budget,
             metrics: ForwardingStageMetrics::default(),
             bind_ip_addrs,
+            forwarding_deny,
         }
     }
 
@@ -360,10 +368,14 @@ impl<VoteClient: ForwardingClient, NonVoteClient: ForwardingClient>
 
     /// Forwards packets that have been buffered. This will loop through all
     /// packets. If the data budget is exceeded then remaining packets are
-    /// dropped.
+    /// dropped. Non-vote packets are dropped while forwarding is denied.
     fn forward_buffered_packets(&mut self) {
         self.metrics.did_something |= !self.packet_container.is_empty();
         self.refresh_data_budget();
+        let deny_non_votes = self
+            .forwarding_deny
+            .as_mut()
+            .is_some_and(|forwarding_deny| forwarding_deny.update(&self.sharable_banks));
 
         let mut non_vote_batch = Vec::with_capacity(FORWARD_BATCH_SIZE);
         let mut vote_batch = Vec::with_capacity(FORWARD_BATCH_SIZE);
@@ -380,6 +392,11 @@ impl<VoteClient: ForwardingClient, NonVoteClient: ForwardingClient>
         };
         // Loop through packets creating batches of packets to forward.
         while let Some(packet) = self.packet_container.pop_max() {
+            if deny_non_votes && !packet.meta().is_simple_vote_tx() {
+                self.metrics.non_votes_dropped_on_deny += 1;
+                continue;
+            }
+
             // If it exceeds our data-budget, drop.
             if !self.data_budget.take(packet.meta().size) {
                 self.metrics.votes_dropped_on_data_budget +=
@@ -755,6 +772,8 @@ struct ForwardingStageMetrics {
     non_votes_dropped_on_data_budget: usize,
     non_votes_forwarded: usize,
     non_votes_dropped_on_send: usize,
+    /// Number of non-votes dropped because forwarding is denied while the node is unhealthy.
+    non_votes_dropped_on_deny: usize,
 }
 
 impl ForwardingStageMetrics {
@@ -812,6 +831,11 @@ impl ForwardingStageMetrics {
                     metrics.non_votes_dropped_on_send,
                     i64
                 ),
+                (
+                    "non_votes_dropped_on_deny",
+                    metrics.non_votes_dropped_on_deny,
+                    i64
+                ),
             );
         }
     }
@@ -834,6 +858,7 @@ impl Default for ForwardingStageMetrics {
             non_votes_dropped_on_data_budget: 0,
             non_votes_forwarded: 0,
             non_votes_dropped_on_send: 0,
+            non_votes_dropped_on_deny: 0,
         }
     }
 }
@@ -938,6 +963,7 @@ mod tests {
             sharable_banks,
             DataBudget::default(),
             None,
+            None,
         );
 
         // Send packet batches.
@@ -0,0 +1,319 @@
+//! Denies forwarding while this node is unhealthy.
+//!
+//! A node that stopped voting, or whose vote account names another identity, for example after
+//! switching identities with a backup node, keeps receiving transactions from clients and from
+//! other nodes that forward to it. Forwarding them on from a view of the cluster that may be stale
+//! loses them without anyone noticing. With a deny policy, the forwarding stage drops the
+//! non-vote transactions while the node is unhealthy, and the node stops advertising its TPU
+//! forwards address in gossip so that other nodes forward to healthy peers instead. Both resume
+//! once the node is healthy again.
+
+use {
+    solana_clock::Slot,
+    solana_gossip::{cluster_info::ClusterInfo, contact_info::Protocol},
+    solana_pubkey::Pubkey,
+    solana_runtime::bank_forks::SharableBanks,
+    solana_vote::vote_account::VoteAccount,
+    std::{
+        fmt,
+        net::SocketAddr,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+};
+
+/// How often the health of the node is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForwardingDenyConfig {
+    /// Deny forwarding when the last vote of the vote account is more than this many slots behind
+    /// the working bank
+    pub max_delinquent_slots: Option<u64>,
+    /// Deny forwarding when the vote account does not exist or names another validator identity
+    pub deny_on_identity_mismatch: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Unhealthy {
+    Delinquent { last_vote: Option<Slot>, slot: Slot },
+    VoteAccountNotFound,
+    IdentityMismatch { node_pubkey: Pubkey },
+}
+
+impl fmt::Display for Unhealthy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Delinquent {
+                last_vote: Some(last_vote),
+                slot,
+            } => write!(f, "last vote {last_vote} is delinquent at slot {slot}"),
+            Self::Delinquent {
+                last_vote: None,
+                slot,
+            } => write!(f, "no vote at slot {slot}"),
+            Self::VoteAccountNotFound => write!(f, "vote account not found"),
+            Self::IdentityMismatch { node_pubkey } => {
+                write!(f, "vote account names validator identity {node_pubkey}")
+            }
+        }
+    }
+}
+
+impl ForwardingDenyConfig {
+    /// Returns why forwarding should be denied for the node `identity` voting with
+    /// `vote_account` at `slot`, if it should
+    pub(crate) fn check(
+        &self,
+        vote_account: Option<&VoteAccount>,
+        identity: &Pubkey,
+        slot: Slot,
+    ) -> Option<Unhealthy> {
+        if self.deny_on_identity_mismatch {
+            let Some(vote_account) = vote_account else {
+                return Some(Unhealthy::VoteAccountNotFound);
+            };
+            if vote_account.node_pubkey() != identity {
+                return Some(Unhealthy::IdentityMismatch {
+                    node_pubkey: *vote_account.node_pubkey(),
+                });
+            }
+        }
+        let max_delinquent_slots = self.max_delinquent_slots?;
+        let last_vote =
+            vote_account.and_then(|vote_account| vote_account.vote_state_view().last_voted_slot());
+        match last_vote {
+            Some(last_vote) if slot.saturating_sub(last_vote) <= max_delinquent_slots => None,
+            _ => Some(Unhealthy::Delinquent { last_vote, slot }),
+        }
+    }
+}
+
+/// Tracks whether forwarding is denied, and withdraws the TPU forwards address from gossip while
+/// it is
+pub struct ForwardingDeny {
+    config: ForwardingDenyConfig,
+    vote_account: Pubkey,
+    cluster_info: Arc<ClusterInfo>,
+    last_check: Option<Instant>,
+    unhealthy: Option<Unhealthy>,
+    /// TPU forwards address advertised before forwarding was denied
+    tpu_forwards: Option<SocketAddr>,
+}
+
+impl ForwardingDeny {
+    pub fn new(
+        config: ForwardingDenyConfig,
+        vote_account: Pubkey,
+        cluster_info: Arc<ClusterInfo>,
+    ) -> Self {
+        Self {
+            config,
+            vote_account,
+            cluster_info,
+            last_check: None,
+            unhealthy: None,
+            tpu_forwards: None,
+        }
+    }
+
+    /// Checks the health of the node if it was not checked recently, and returns true if
+    /// forwarding is denied
+    pub(crate) fn update(&mut self, sharable_banks: &SharableBanks) -> bool {
+        if self
+            .last_check
+            .is_some_and(|last_check| last_check.elapsed() < CHECK_INTERVAL)
+        {
+            return self.unhealthy.is_some();
+        }
+        self.last_check = Some(Instant::now());
+
+        let bank = sharable_banks.working();
+        let vote_accounts = bank.vote_accounts();
+        let unhealthy = self.config.check(
+            vote_accounts
+                .get(&self.vote_account)
+                .map(|(_stake, vote_account)| vote_account),
+            &self.cluster_info.id(),
+            bank.slot(),
+        );
+        match (&self.unhealthy, &unhealthy) {
+            (None, Some(reason)) => {
+                warn!("Denying transaction forwarding: {reason}");
+                self.tpu_forwards = self
+                    .cluster_info
+                    .my_contact_info()
+                    .tpu_forwards(Protocol::UDP);
+                self.cluster_info.remove_tpu_forwards();
+            }
+            (Some(_), None) => {
+                info!("Resuming transaction forwarding");
+                if let Some(tpu_forwards) = self.tpu_forwards.take() {
+                    if let Err(err) = self.cluster_info.set_tpu_forwards(tpu_forwards) {
+                        error!("Failed to advertise the TPU forwards address again: {err:?}");
+                    }
+                }
+            }
+            _ => (),
+        }
+        self.unhealthy = unhealthy;
+        datapoint_info!(
+            "forwarding_stage-deny",
+            ("denied", self.unhealthy.is_some(), bool),
+        );
+        self.unhealthy.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_account::{Account, AccountSharedData, WritableAccount},
+        solana_gossip::contact_info::ContactInfo,
+        solana_keypair::Keypair,
+        solana_runtime::{
+            bank::Bank,
+            bank_forks::BankForks,
+            genesis_utils::{
+                bootstrap_validator_stake_lamports, create_genesis_config_with_leader,
+            },
+        },
+        solana_signer::Signer,
+        solana_streamer::socket::SocketAddrSpace,
+        solana_time_utils::timestamp,
+        solana_vote_program::vote_state::{
+            process_slot_vote_unchecked, VoteStateV4, VoteStateVersions,
+        },
+    };
+
+    fn vote_account(node_pubkey: Pubkey, votes: &[Slot]) -> VoteAccount {
+        let mut account = AccountSharedData::from(Account {
+            data: vec![0; VoteStateV4::size_of()],
+            lamports: 1,
+            owner: solana_vote_program::id(),
+            ..Account::default()
+        });
+        let mut vote_state = VoteStateV4 {
+            node_pubkey,
+            ..VoteStateV4::default()
+        };
+        for slot in votes {
+            process_slot_vote_unchecked(&mut vote_state, *slot);
+        }
+        VoteStateV4::serialize(
+            &VoteStateVersions::new_v4(vote_state),
+            account.data_as_mut_slice(),
+        )
+        .unwrap();
+        VoteAccount::try_from(account).unwrap()
+    }
+
+    #[test]
+    fn test_check_delinquent() {
+        let identity = Pubkey::new_unique();
+        let config = ForwardingDenyConfig {
+            max_delinquent_slots: Some(100),
+            deny_on_identity_mismatch: false,
+        };
+        let voting = vote_account(identity, &[10, 20]);
+        assert_eq!(config.check(Some(&voting), &identity, 120), None);
+        assert_eq!(
+            config.check(Some(&voting), &identity, 121),
+            Some(Unhealthy::Delinquent {
+                last_vote: Some(20),
+                slot: 121
+            })
+        );
+        assert_eq!(
+            config.check(Some(&vote_account(identity, &[])), &identity, 10),
+            Some(Unhealthy::Delinquent {
+                last_vote: None,
+                slot: 10
+            })
+        );
+        assert_eq!(
+            config.check(None, &identity, 10),
+            Some(Unhealthy::Delinquent {
+                last_vote: None,
+                slot: 10
+            })
+        );
+
+        // The identity is not checked
+        assert_eq!(config.check(Some(&voting), &Pubkey::new_unique(), 20), None);
+    }
+
+    #[test]
+    fn test_check_identity_mismatch() {
+        let identity = Pubkey::new_unique();
+        let other_identity = Pubkey::new_unique();
+        let config = ForwardingDenyConfig {
+            max_delinquent_slots: None,
+            deny_on_identity_mismatch: true,
+        };
+        assert_eq!(
+            config.check(Some(&vote_account(identity, &[])), &identity, 1_000),
+            None
+        );
+        assert_eq!(
+            config.check(Some(&vote_account(other_identity, &[])), &identity, 1_000),
+            Some(Unhealthy::IdentityMismatch {
+                node_pubkey: other_identity
+            })
+        );
+        assert_eq!(
+            config.check(None, &identity, 1_000),
+            Some(Unhealthy::VoteAccountNotFound)
+        );
+    }
+
+    #[test]
+    fn test_update_toggles_tpu_forwards() {
+        let identity = Arc::new(Keypair::new());
+        let genesis_config_info = create_genesis_config_with_leader(
+            10_000,
+            &identity.pubkey(),
+            bootstrap_validator_stake_lamports(),
+        );
+        let bank = Bank::new_for_tests(&genesis_config_info.genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let sharable_banks = bank_forks.read().unwrap().sharable_banks();
+
+        // The node runs with another identity than the vote account names
+        let other_identity = Arc::new(Keypair::new());
+        let contact_info = ContactInfo::new_localhost(&other_identity.pubkey(), timestamp());
+        let tpu_forwards = contact_info.tpu_forwards(Protocol::UDP).unwrap();
+        let cluster_info = Arc::new(ClusterInfo::new(
+            contact_info,
+            other_identity,
+            SocketAddrSpace::Unspecified,
+        ));
+        let config = ForwardingDenyConfig {
+            max_delinquent_slots: None,
+            deny_on_identity_mismatch: true,
+        };
+        let mut forwarding_deny = ForwardingDeny::new(
+            config,
+            genesis_config_info.voting_keypair.pubkey(),
+            cluster_info.clone(),
+        );
+        let gossip_tpu_forwards = || {
+            cluster_info
+                .lookup_contact_info(&cluster_info.id(), |node| node.tpu_forwards(Protocol::UDP))
+                .unwrap()
+        };
+
+        assert!(forwarding_deny.update(&sharable_banks));
+        assert_eq!(gossip_tpu_forwards(), None);
+
+        // Not checked again until the check interval elapsed
+        cluster_info.set_keypair(identity);
+        assert!(forwarding_deny.update(&sharable_banks));
+        assert_eq!(gossip_tpu_forwards(), None);
+
+        forwarding_deny.last_check = None;
+        assert!(!forwarding_deny.update(&sharable_banks));
+        assert_eq!(gossip_tpu_forwards(), Some(tpu_forwards));
+    }
+}
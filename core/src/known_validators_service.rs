@@ -0,0 +1,203 @@
+//! Derives the known validators from stake at every epoch.
+//!
+//! A static list of known validators goes stale as the cluster's stake moves, so that the node
+//! ends up trusting snapshot hashes and RPC peers of validators that left or lost their stake.
+//! This service makes every validator with at least a configured share of the current epoch's
+//! stake a known validator, in addition to the ones given on the command line. The set is
+//! recomputed when the root bank enters a new epoch and written to the ledger, so that a restart
+//! bootstraps from the set of the last epoch the node saw.
+
+use {
+    log::*,
+    solana_clock::Epoch,
+    solana_pubkey::Pubkey,
+    solana_rpc::known_validators::KnownValidators,
+    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    std::{
+        collections::{HashMap, HashSet},
+        fs, io,
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// File in the ledger directory holding the known validators derived at the last epoch
+pub const KNOWN_VALIDATORS_FROM_STAKE_FILE: &str = "known_validators_from_stake";
+
+const LOOP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Configures deriving known validators from stake
+#[derive(Clone, Debug, PartialEq)]
+pub struct KnownValidatorsFromStake {
+    /// Share of the epoch's total stake, in percent, that makes a validator known
+    pub min_stake_percent: f64,
+    /// Known validators given on the command line, which stay known whatever their stake
+    pub static_known_validators: HashSet<Pubkey>,
+}
+
+pub struct KnownValidatorsService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl KnownValidatorsService {
+    /// Publishes the validators holding `min_stake_percent` of the stake or more, along with
+    /// `static_known_validators`, to `known_validators` at every epoch
+    pub fn new(
+        min_stake_percent: f64,
+        static_known_validators: HashSet<Pubkey>,
+        known_validators: KnownValidators,
+        bank_forks: Arc<RwLock<BankForks>>,
+        ledger_path: PathBuf,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("solKnownVals".to_string())
+            .spawn(move || {
+                let mut last_epoch = None::<Epoch>;
+                while !exit.load(Ordering::Relaxed) {
+                    let root_bank = bank_forks.read().unwrap().root_bank();
+                    if last_epoch != Some(root_bank.epoch()) {
+                        last_epoch = Some(root_bank.epoch());
+                        refresh_known_validators(
+                            &root_bank,
+                            min_stake_percent,
+                            &static_known_validators,
+                            &known_validators,
+                            &ledger_path,
+                        );
+                    }
+                    sleep(LOOP_INTERVAL);
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+fn refresh_known_validators(
+    root_bank: &Bank,
+    min_stake_percent: f64,
+    static_known_validators: &HashSet<Pubkey>,
+    known_validators: &KnownValidators,
+    ledger_path: &Path,
+) {
+    let from_stake =
+        known_validators_from_stake(&root_bank.current_epoch_staked_nodes(), min_stake_percent);
+    if let Err(err) = store_known_validators_from_stake(ledger_path, root_bank.epoch(), &from_stake)
+    {
+        warn!("Failed to store the known validators derived from stake: {err}");
+    }
+    let num_from_stake = from_stake.len();
+    let new_known_validators: HashSet<_> = from_stake
+        .into_iter()
+        .chain(static_known_validators.iter().copied())
+        .collect();
+    info!(
+        "Known validators for epoch {}: {} holding at least {min_stake_percent}% of the stake, {} \
+         in total",
+        root_bank.epoch(),
+        num_from_stake,
+        new_known_validators.len(),
+    );
+    datapoint_info!(
+        "known-validators",
+        ("epoch", root_bank.epoch(), i64),
+        ("num_from_stake", num_from_stake, i64),
+        ("num_known_validators", new_known_validators.len(), i64),
+    );
+    known_validators.set(new_known_validators);
+}
+
+/// Returns the nodes holding at least `min_stake_percent` of the total stake of `staked_nodes`
+pub fn known_validators_from_stake(
+    staked_nodes: &HashMap<Pubkey, u64>,
+    min_stake_percent: f64,
+) -> HashSet<Pubkey> {
+    let total_stake: u64 = staked_nodes.values().sum();
+    if total_stake == 0 {
+        return HashSet::new();
+    }
+    staked_nodes
+        .iter()
+        .filter(|(_, stake)| **stake as f64 * 100.0 / total_stake as f64 >= min_stake_percent)
+        .map(|(node, _)| *node)
+        .collect()
+}
+
+/// Loads the known validators stored by the service when it last ran on the ledger at
+/// `ledger_path`, `None` if there are none
+pub fn load_known_validators_from_stake(ledger_path: &Path) -> Option<HashSet<Pubkey>> {
+    let contents = fs::read_to_string(ledger_path.join(KNOWN_VALIDATORS_FROM_STAKE_FILE)).ok()?;
+    let known_validators = contents
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| Pubkey::from_str(line.trim()).ok())
+        .collect();
+    Some(known_validators)
+}
+
+fn store_known_validators_from_stake(
+    ledger_path: &Path,
+    epoch: Epoch,
+    known_validators: &HashSet<Pubkey>,
+) -> io::Result<()> {
+    let mut contents = format!("# Validators holding the most stake in epoch {epoch}\n");
+    for known_validator in known_validators {
+        contents.push_str(&known_validator.to_string());
+        contents.push('\n');
+    }
+    // Write then rename, so that a crash never leaves a partial list behind
+    let path = ledger_path.join(KNOWN_VALIDATORS_FROM_STAKE_FILE);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_known_validators_from_stake() {
+        let nodes: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let staked_nodes = HashMap::from([
+            (nodes[0], 50),
+            (nodes[1], 30),
+            (nodes[2], 19),
+            (nodes[3], 1),
+        ]);
+        assert_eq!(
+            known_validators_from_stake(&staked_nodes, 20.0),
+            HashSet::from([nodes[0], nodes[1]])
+        );
+        assert_eq!(
+            known_validators_from_stake(&staked_nodes, 1.0),
+            nodes.iter().copied().collect()
+        );
+        assert!(known_validators_from_stake(&staked_nodes, 60.0).is_empty());
+        assert!(known_validators_from_stake(&HashMap::new(), 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_store_and_load_known_validators_from_stake() {
+        let ledger_path = TempDir::new().unwrap();
+        assert_eq!(load_known_validators_from_stake(ledger_path.path()), None);
+
+        let known_validators = HashSet::from([Pubkey::new_unique(), Pubkey::new_unique()]);
+        store_known_validators_from_stake(ledger_path.path(), 7, &known_validators).unwrap();
+        assert_eq!(
+            load_known_validators_from_stake(ledger_path.path()),
+            Some(known_validators)
+        );
+    }
+}
@@ -30,12 +30,15 @@ pub mod completed_data_sets_service;
 pub mod consensus;
 pub mod cost_update_service;
 pub mod drop_bank_service;
+pub mod epoch_precompute_service;
 pub mod fetch_stage;
 pub mod forwarding_stage;
 pub mod gen_keys;
+pub mod known_validators_service;
 mod mock_alpenglow_consensus;
 pub mod next_leader;
 pub mod optimistic_confirmation_verifier;
+pub mod poh_calibration;
 pub mod repair;
 pub mod replay_stage;
 pub mod resource_limits;
@@ -52,6 +55,7 @@ pub mod stats_reporter_service;
 pub mod system_monitor_service;
 pub mod tpu;
 mod tpu_entry_notifier;
+pub mod tpu_mirror_stage;
 pub mod tvu;
 pub mod unfrozen_gossip_verified_vote_hashes;
 pub mod validator;
@@ -0,0 +1,167 @@
+//! Persisted PoH hash rate calibration
+//!
+//! Measuring the PoH hash rate at startup is only meaningful on the core the PoH thread will be
+//! pinned to, so the measurement runs on a thread pinned to that core. The result is stored in
+//! the ledger directory so that a restart on the same host can use it right away, while a fresh
+//! measurement is taken in the background to keep the stored value honest.
+
+use {
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_entry::poh::compute_hash_time,
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+        thread::{self, JoinHandle},
+    },
+};
+
+pub const POH_CALIBRATION_FILENAME: &str = "poh_calibration.bin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PohCalibration {
+    /// CPU core the measurement ran on
+    pub pinned_cpu_core: usize,
+    /// Number of hashes that were timed
+    pub hash_samples: u64,
+    /// Measured hash rate
+    pub hashes_per_second: u64,
+}
+
+impl PohCalibration {
+    /// Times `hash_samples` hashes on a thread pinned to `pinned_cpu_core`
+    pub fn measure(pinned_cpu_core: usize, hash_samples: u64) -> Self {
+        thread::Builder::new()
+            .name("solPohCalibrate".to_string())
+            .spawn(move || {
+                pin_current_thread(pinned_cpu_core);
+                let hash_time = compute_hash_time(hash_samples);
+                Self {
+                    pinned_cpu_core,
+                    hash_samples,
+                    hashes_per_second: (hash_samples as f64 / hash_time.as_secs_f64()) as u64,
+                }
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    /// Loads the calibration stored in `ledger_path`, if any
+    pub fn load(ledger_path: &Path) -> Option<Self> {
+        let path = calibration_path(ledger_path);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                warn!("Unable to read PoH calibration {}: {err}", path.display());
+                return None;
+            }
+        };
+        bincode::deserialize(&bytes)
+            .inspect_err(|err| warn!("Ignoring invalid PoH calibration {}: {err}", path.display()))
+            .ok()
+    }
+
+    /// Stores the calibration in `ledger_path`
+    pub fn save(&self, ledger_path: &Path) -> io::Result<()> {
+        let path = calibration_path(ledger_path);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(
+            &tmp_path,
+            bincode::serialize(self).map_err(io::Error::other)?,
+        )?;
+        fs::rename(tmp_path, path)
+    }
+
+    /// Whether this calibration was measured under the same conditions that are requested now
+    pub fn is_applicable(&self, pinned_cpu_core: usize, hash_samples: u64) -> bool {
+        self.pinned_cpu_core == pinned_cpu_core && self.hash_samples == hash_samples
+    }
+
+    /// Takes a fresh measurement in the background and stores it in `ledger_path`
+    ///
+    /// `on_measured` is called with the new calibration once it is available.
+    pub fn revalidate_in_background(
+        &self,
+        ledger_path: PathBuf,
+        on_measured: impl FnOnce(&PohCalibration) + Send + 'static,
+    ) -> JoinHandle<()> {
+        let Self {
+            pinned_cpu_core,
+            hash_samples,
+            ..
+        } = *self;
+        thread::Builder::new()
+            .name("solPohRecalib".to_string())
+            .spawn(move || {
+                let calibration = Self::measure(pinned_cpu_core, hash_samples);
+                if let Err(err) = calibration.save(&ledger_path) {
+                    warn!("Unable to save PoH calibration: {err}");
+                }
+                on_measured(&calibration);
+            })
+            .unwrap()
+    }
+}
+
+/// Pins the current thread to `cpu_core`, logging rather than failing if the core doesn't exist
+fn pin_current_thread(cpu_core: usize) {
+    let core_id =
+        core_affinity::get_core_ids().and_then(|core_ids| core_ids.get(cpu_core).copied());
+    match core_id {
+        Some(core_id) => core_affinity::set_for_current(core_id),
+        None => warn!("Unable to pin thread to CPU core {cpu_core}"),
+    }
+}
+
+fn calibration_path(ledger_path: &Path) -> PathBuf {
+    ledger_path.join(POH_CALIBRATION_FILENAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_poh_calibration_save_load() {
+        let ledger_dir = TempDir::new().unwrap();
+        assert_eq!(PohCalibration::load(ledger_dir.path()), None);
+
+        let calibration = PohCalibration {
+            pinned_cpu_core: 3,
+            hash_samples: 1_000,
+            hashes_per_second: 5_000_000,
+        };
+        calibration.save(ledger_dir.path()).unwrap();
+        assert_eq!(PohCalibration::load(ledger_dir.path()), Some(calibration));
+        assert!(calibration.is_applicable(3, 1_000));
+        assert!(!calibration.is_applicable(0, 1_000));
+        assert!(!calibration.is_applicable(3, 2_000));
+
+        fs::write(calibration_path(ledger_dir.path()), b"garbage").unwrap();
+        assert_eq!(PohCalibration::load(ledger_dir.path()), None);
+    }
+
+    #[test]
+    fn test_poh_calibration_revalidate_in_background() {
+        let ledger_dir = TempDir::new().unwrap();
+        let stale = PohCalibration {
+            pinned_cpu_core: 0,
+            hash_samples: 1_000,
+            hashes_per_second: 1,
+        };
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        stale
+            .revalidate_in_background(ledger_dir.path().to_path_buf(), move |calibration| {
+                sender.send(*calibration).unwrap()
+            })
+            .join()
+            .unwrap();
+
+        let measured = receiver.recv().unwrap();
+        assert!(measured.is_applicable(0, 1_000));
+        assert!(measured.hashes_per_second > stale.hashes_per_second);
+        assert_eq!(PohCalibration::load(ledger_dir.path()), Some(measured));
+    }
+}
@@ -904,7 +904,7 @@ mod test {
             repair::{
                 cluster_slot_state_verifier::{DuplicateSlotsToRepair, PurgeRepairSlotCounter},
                 duplicate_repair_status::DuplicateAncestorDecision,
-                serve_repair::MAX_ANCESTOR_RESPONSES,
+                serve_repair::{ServeRepairConfig, MAX_ANCESTOR_RESPONSES},
                 serve_repair_service::adapt_repair_requests_packets,
             },
             replay_stage::{
@@ -1323,6 +1323,7 @@ mod test {
                 remote_request_receiver,
                 response_sender,
                 repair_response_quic_sender,
+                ServeRepairConfig::default(),
                 exit.clone(),
             );
 
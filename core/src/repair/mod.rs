@@ -5,6 +5,7 @@ pub(crate) mod malicious_repair_handler;
 pub mod outstanding_requests;
 pub mod packet_threshold;
 pub(crate) mod quic_endpoint;
+pub mod repair_client;
 pub mod repair_generic_traversal;
 pub mod repair_handler;
 pub mod repair_response;
@@ -0,0 +1,196 @@
+//! A blocking client for the repair protocol, fetching shreds from one peer outside of a running
+//! validator, such as for `agave-ledger-tool blockstore sync`
+
+use {
+    crate::repair::serve_repair::{
+        RepairProtocol, RepairRequestHeader, RepairResponse, ServeRepair,
+        REPAIR_RESPONSE_SERIALIZED_PING_BYTES,
+    },
+    solana_clock::Slot,
+    solana_gossip::ping_pong::Pong,
+    solana_keypair::{signable::Signable, Keypair},
+    solana_ledger::shred::{Nonce, Shred, SIZE_OF_NONCE},
+    solana_packet::PACKET_DATA_SIZE,
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+    solana_time_utils::timestamp,
+    std::{
+        io,
+        net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+};
+
+pub struct RepairClient {
+    socket: UdpSocket,
+    keypair: Arc<Keypair>,
+    peer_id: Pubkey,
+    peer_addr: SocketAddr,
+    timeout: Duration,
+    next_nonce: Nonce,
+}
+
+impl RepairClient {
+    /// Creates a client for the peer `peer_id` serving repair over UDP at `peer_addr`
+    ///
+    /// Requests are signed with `keypair`. The peer does not need to know it, but serves unstaked
+    /// requesters with a lower priority.
+    pub fn new(
+        keypair: Arc<Keypair>,
+        peer_id: Pubkey,
+        peer_addr: SocketAddr,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let bind_addr = if peer_addr.is_ipv4() {
+            SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+        } else {
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        };
+        Ok(Self {
+            socket: UdpSocket::bind(bind_addr)?,
+            keypair,
+            peer_id,
+            peer_addr,
+            timeout,
+            next_nonce: rand::random(),
+        })
+    }
+
+    /// Requests data shred `index` of `slot`, returning `None` if the peer does not answer in
+    /// time, which is also how it treats shreds it does not have
+    pub fn request_data_shred(&mut self, slot: Slot, index: u64) -> io::Result<Option<Shred>> {
+        let nonce = self.next_nonce;
+        self.next_nonce = self.next_nonce.wrapping_add(1);
+        self.send_request(slot, index, nonce)?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = [0u8; PACKET_DATA_SIZE];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+            let (size, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None);
+                }
+                Err(err) => return Err(err),
+            };
+            if from != self.peer_addr {
+                continue;
+            }
+            let bytes = &buffer[..size];
+            if size == REPAIR_RESPONSE_SERIALIZED_PING_BYTES {
+                if let Ok(RepairResponse::Ping(ping)) = bincode::deserialize(bytes) {
+                    if ping.verify() {
+                        // The peer drops requests from an address until it answered a ping
+                        let pong = RepairProtocol::Pong(Pong::new(&ping, &self.keypair));
+                        let pong = bincode::serialize(&pong).map_err(io::Error::other)?;
+                        self.socket.send_to(&pong, self.peer_addr)?;
+                        self.send_request(slot, index, nonce)?;
+                        continue;
+                    }
+                }
+            }
+            let Some(payload_size) = size.checked_sub(SIZE_OF_NONCE) else {
+                continue;
+            };
+            let (payload, response_nonce) = bytes.split_at(payload_size);
+            if Nonce::from_le_bytes(response_nonce.try_into().unwrap()) != nonce {
+                continue;
+            }
+            match Shred::new_from_serialized_shred(payload.to_vec()) {
+                Ok(shred)
+                    if shred.is_data()
+                        && shred.slot() == slot
+                        && u64::from(shred.index()) == index =>
+                {
+                    return Ok(Some(shred));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn send_request(&self, slot: Slot, index: u64, nonce: Nonce) -> io::Result<()> {
+        let request = RepairProtocol::WindowIndex {
+            header: RepairRequestHeader::new(
+                self.keypair.pubkey(),
+                self.peer_id,
+                timestamp(),
+                nonce,
+            ),
+            slot,
+            shred_index: index,
+        };
+        let request = ServeRepair::repair_proto_to_bytes(&request, &self.keypair)
+            .map_err(io::Error::other)?;
+        self.socket.send_to(&request, self.peer_addr)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_gossip::ping_pong::Ping, solana_ledger::shred::Shredder, std::thread};
+
+    const NONCE: Nonce = 42;
+
+    #[test]
+    fn test_repair_client() {
+        let peer_keypair = Keypair::new();
+        let leader_keypair = Keypair::new();
+        let peer_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let peer_addr = peer_socket.local_addr().unwrap();
+        let shred = Shredder::single_shred_for_tests(5, &leader_keypair);
+        let payload = shred.payload().to_vec();
+
+        let peer_id = peer_keypair.pubkey();
+        let peer = thread::spawn(move || {
+            let mut buffer = [0u8; PACKET_DATA_SIZE];
+            // Ping the client before serving it
+            let (_, client_addr) = peer_socket.recv_from(&mut buffer).unwrap();
+            let ping = RepairResponse::Ping(Ping::new([7u8; 32], &peer_keypair));
+            peer_socket
+                .send_to(&bincode::serialize(&ping).unwrap(), client_addr)
+                .unwrap();
+            let (size, _) = peer_socket.recv_from(&mut buffer).unwrap();
+            let Ok(RepairProtocol::Pong(pong)) = bincode::deserialize(&buffer[..size]) else {
+                panic!("expected a pong");
+            };
+            assert!(pong.verify());
+
+            let (size, _) = peer_socket.recv_from(&mut buffer).unwrap();
+            let Ok(RepairProtocol::WindowIndex {
+                slot, shred_index, ..
+            }) = bincode::deserialize::<RepairProtocol>(&buffer[..size])
+            else {
+                panic!("expected a window index request");
+            };
+            assert_eq!((slot, shred_index), (5, 0));
+            let mut response = payload;
+            response.extend_from_slice(&NONCE.to_le_bytes());
+            peer_socket.send_to(&response, client_addr).unwrap();
+        });
+
+        let mut client = RepairClient::new(
+            Arc::new(Keypair::new()),
+            peer_id,
+            peer_addr,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        client.next_nonce = NONCE;
+        let received = client.request_data_shred(5, 0).unwrap().unwrap();
+        assert_eq!(received.payload(), shred.payload());
+        peer.join().unwrap();
+    }
+}
@@ -29,7 +29,7 @@ use {
     solana_cluster_type::ClusterType,
     solana_gossip::{
         cluster_info::{ClusterInfo, ClusterInfoError},
-        contact_info::{ContactInfo, Protocol},
+        contact_info::{ContactInfo, Protocol, CAPABILITY_ENCRYPTED_SHRED_TRANSPORT},
         ping_pong::{self, Pong},
         weighted_shuffle::WeightedShuffle,
     },
@@ -37,10 +37,7 @@ use {
     solana_keypair::{signable::Signable, Keypair},
     solana_ledger::shred::{self, Nonce, ShredFetchStats, SIZE_OF_NONCE},
     solana_packet::PACKET_DATA_SIZE,
-    solana_perf::{
-        data_budget::DataBudget,
-        packet::{Packet, PacketBatch, PacketBatchRecycler, PinnedPacketBatch},
-    },
+    solana_perf::packet::{Packet, PacketBatch, PacketBatchRecycler, PinnedPacketBatch},
     solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_runtime::bank_forks::SharableBanks,
     solana_signature::{Signature, SIGNATURE_BYTES},
@@ -52,12 +49,11 @@ use {
     },
     solana_time_utils::timestamp,
     std::{
-        cmp::Reverse,
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet, VecDeque},
         net::{SocketAddr, UdpSocket},
         sync::{
             atomic::{AtomicBool, Ordering},
-            Arc, RwLock,
+            Arc, Mutex, RwLock,
         },
         thread::{Builder, JoinHandle},
         time::{Duration, Instant},
@@ -90,6 +86,13 @@ const REPAIR_PING_CACHE_RATE_LIMIT_DELAY: Duration = Duration::from_secs(2);
 pub(crate) const REPAIR_RESPONSE_SERIALIZED_PING_BYTES: usize =
     4 /*enum discriminator*/ + PUBKEY_BYTES + REPAIR_PING_TOKEN_SIZE + SIGNATURE_BYTES;
 const SIGNED_REPAIR_TIME_WINDOW: Duration = Duration::from_secs(60 * 10); // 10 min
+pub const DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND: usize = 12_000_000;
+// Interval at which the limits of ServeRepairConfig are replenished.
+const SERVE_REPAIR_BUDGET_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a slot served to a repair peer is remembered
+pub const MAX_REPAIR_SERVED_SLOT_AGE: Duration = Duration::from_secs(60);
+// Number of remembered repair served slots above which the earliest served are forgotten.
+const MAX_REPAIR_SERVED_SLOTS: usize = 10_000;
 
 #[cfg(test)]
 static_assertions::const_assert_eq!(MAX_ANCESTOR_RESPONSES, 30);
@@ -175,6 +178,8 @@ struct ServeRepairStats {
     dropped_requests_outbound_bandwidth: usize,
     dropped_requests_load_shed: usize,
     dropped_requests_low_stake: usize,
+    dropped_requests_peer_limit: usize,
+    dropped_requests_request_limit: usize,
     whitelisted_requests: usize,
     total_dropped_response_packets: usize,
     total_response_packets: usize,
@@ -335,6 +340,179 @@ impl RepairProtocol {
     fn max_response_bytes(&self) -> usize {
         self.max_response_packets() * PACKET_DATA_SIZE
     }
+
+    /// The slot whose data is requested
+    fn slot(&self) -> Option<Slot> {
+        match self {
+            Self::WindowIndex { slot, .. }
+            | Self::HighestWindowIndex { slot, .. }
+            | Self::Orphan { slot, .. }
+            | Self::AncestorHashes { slot, .. } => Some(*slot),
+            Self::Pong(_)
+            | Self::LegacyWindowIndex
+            | Self::LegacyHighestWindowIndex
+            | Self::LegacyOrphan
+            | Self::LegacyWindowIndexWithNonce
+            | Self::LegacyHighestWindowIndexWithNonce
+            | Self::LegacyOrphanWithNonce
+            | Self::LegacyAncestorHashes => None,
+        }
+    }
+}
+
+/// Limits on the resources spent serving the repair requests of other nodes
+///
+/// A node helping many others catch up must still have the bandwidth and CPU to vote. Within the
+/// limits, requests of nodes in the repair whitelist are served first, and the others in an order
+/// weighted by the stake of their senders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServeRepairConfig {
+    /// Bytes of repair responses sent per second
+    pub max_bytes_per_second: usize,
+    /// Repair requests handled per second, bounding the blockstore reads they cost
+    pub max_requests_per_second: Option<usize>,
+    /// Bytes of repair responses sent per second to a single node outside of the repair whitelist
+    pub max_peer_bytes_per_second: Option<usize>,
+}
+
+impl Default for ServeRepairConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes_per_second: DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND,
+            max_requests_per_second: None,
+            max_peer_bytes_per_second: None,
+        }
+    }
+}
+
+/// What remains of the [`ServeRepairConfig`] limits in the current interval
+struct ServeRepairBudget {
+    config: ServeRepairConfig,
+    asof: Instant,
+    bytes: usize,
+    requests: Option<usize>,
+    // Response bytes sent to each node in the current interval.
+    peer_bytes: HashMap<Pubkey, usize>,
+}
+
+impl ServeRepairBudget {
+    fn new(config: ServeRepairConfig, now: Instant) -> Self {
+        Self {
+            config,
+            asof: now,
+            bytes: config.max_bytes_per_second,
+            requests: config.max_requests_per_second,
+            peer_bytes: HashMap::new(),
+        }
+    }
+
+    fn replenish(&mut self, now: Instant) {
+        if now.duration_since(self.asof) >= SERVE_REPAIR_BUDGET_INTERVAL {
+            *self = Self::new(self.config, now);
+        }
+    }
+
+    fn check_bytes(&self, bytes: usize) -> bool {
+        bytes <= self.bytes
+    }
+
+    fn check_peer_bytes(&self, sender: Option<&Pubkey>, bytes: usize) -> bool {
+        let (Some(max_peer_bytes), Some(sender)) = (self.config.max_peer_bytes_per_second, sender)
+        else {
+            return true;
+        };
+        let peer_bytes = self.peer_bytes.get(sender).copied().unwrap_or_default();
+        peer_bytes.saturating_add(bytes) <= max_peer_bytes
+    }
+
+    fn take_request(&mut self) -> bool {
+        match &mut self.requests {
+            None => true,
+            Some(0) => false,
+            Some(requests) => {
+                *requests -= 1;
+                true
+            }
+        }
+    }
+
+    fn take_bytes(&mut self, sender: Option<&Pubkey>, bytes: usize) -> bool {
+        let Some(remaining) = self.bytes.checked_sub(bytes) else {
+            return false;
+        };
+        self.bytes = remaining;
+        if let Some(sender) = sender {
+            *self.peer_bytes.entry(*sender).or_default() += bytes;
+        }
+        true
+    }
+}
+
+/// Slots served to repair peers, with the last time they were served
+///
+/// Every slot is queued once, in the order slots were first served, so expired slots are pruned
+/// from the front of the queue in amortized constant time per served slot.
+#[derive(Default)]
+struct ServedSlots {
+    last_served: BTreeMap<Slot, Instant>,
+    queue: VecDeque<(Slot, Instant)>,
+}
+
+impl ServedSlots {
+    fn insert(&mut self, slot: Slot, now: Instant) {
+        if self.last_served.insert(slot, now).is_none() {
+            self.queue.push_back((slot, now));
+        }
+        if self.queue.len() > MAX_REPAIR_SERVED_SLOTS {
+            if let Some((slot, _)) = self.queue.pop_front() {
+                self.last_served.remove(&slot);
+            }
+        }
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(&(slot, queued_at)) = self.queue.front() {
+            if now.duration_since(queued_at) < MAX_REPAIR_SERVED_SLOT_AGE {
+                break;
+            }
+            self.queue.pop_front();
+            let last_served = self.last_served[&slot];
+            if now.duration_since(last_served) < MAX_REPAIR_SERVED_SLOT_AGE {
+                // Served again since it was queued
+                self.queue.push_back((slot, last_served));
+            } else {
+                self.last_served.remove(&slot);
+            }
+        }
+    }
+}
+
+/// The slots recently served to repair peers, which the blockstore cleanup service holds off on
+/// purging
+#[derive(Default)]
+pub struct RepairServedSlots(Mutex<ServedSlots>);
+
+impl RepairServedSlots {
+    /// Records that data of `slot` was just served to a repair peer
+    fn record(&self, slot: Slot) {
+        let now = Instant::now();
+        let mut served_slots = self.0.lock().unwrap();
+        served_slots.insert(slot, now);
+        served_slots.prune(now);
+    }
+
+    /// Returns the lowest slot served to a repair peer within `max_age`, which is capped at
+    /// [`MAX_REPAIR_SERVED_SLOT_AGE`]
+    pub fn lowest_served_slot(&self, max_age: Duration) -> Option<Slot> {
+        let now = Instant::now();
+        let mut served_slots = self.0.lock().unwrap();
+        served_slots.prune(now);
+        served_slots
+            .last_served
+            .iter()
+            .find(|(_, served_at)| now.duration_since(**served_at) < max_age)
+            .map(|(slot, _)| *slot)
+    }
 }
 
 pub struct ServeRepair {
@@ -342,6 +520,7 @@ pub struct ServeRepair {
     sharable_banks: SharableBanks,
     repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>,
     repair_handler: Box<dyn RepairHandler + Send + Sync>,
+    repair_served_slots: Arc<RepairServedSlots>,
 }
 
 // Cache entry for repair peers for a slot.
@@ -355,10 +534,18 @@ struct Node {
     pubkey: Pubkey,
     serve_repair: SocketAddr,
     serve_repair_quic: SocketAddr,
+    /// Both this node and the peer declared CAPABILITY_ENCRYPTED_SHRED_TRANSPORT, so repair
+    /// requests to the peer go over QUIC regardless of the repair protocol
+    encrypted_transport: bool,
 }
 
 impl RepairPeers {
-    fn new(asof: Instant, peers: &[ContactInfo], weights: &[u64]) -> Result<Self> {
+    fn new(
+        asof: Instant,
+        peers: &[ContactInfo],
+        weights: &[u64],
+        encrypted_transport: bool,
+    ) -> Result<Self> {
         if peers.len() != weights.len() {
             return Err(Error::from(WeightedError::InvalidWeight));
         }
@@ -370,6 +557,8 @@ impl RepairPeers {
                     pubkey: *peer.pubkey(),
                     serve_repair: peer.serve_repair(Protocol::UDP)?,
                     serve_repair_quic: peer.serve_repair(Protocol::QUIC)?,
+                    encrypted_transport: encrypted_transport
+                        && peer.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT),
                 };
                 Some((node, weight))
             })
@@ -411,9 +600,15 @@ impl ServeRepair {
             sharable_banks,
             repair_whitelist,
             repair_handler,
+            repair_served_slots: Arc::default(),
         }
     }
 
+    /// The slots this node recently served to repair peers
+    pub fn repair_served_slots(&self) -> Arc<RepairServedSlots> {
+        self.repair_served_slots.clone()
+    }
+
     #[cfg(test)]
     pub fn new_for_test(
         cluster_info: Arc<ClusterInfo>,
@@ -532,6 +727,10 @@ impl ServeRepair {
             }
         };
         Self::report_time_spent(label, &now.elapsed(), "");
+        if let (Some(_), Some(slot)) = (&res, request.slot()) {
+            // lets the blockstore cleanup service hold off on purging slots being repaired
+            self.repair_served_slots.record(slot);
+        }
         res
     }
 
@@ -647,6 +846,37 @@ impl ServeRepair {
         requests.into_iter().filter_map(decode_request).collect()
     }
 
+    /// Orders the requests of nodes in the repair whitelist first, followed by the others shuffled
+    /// by the stake of their senders. The stake of a node is split across its requests, so that
+    /// sending more requests does not raise its priority.
+    fn prioritize_requests<R: Rng>(
+        requests: Vec<RepairRequestWithMeta>,
+        rng: &mut R,
+    ) -> Vec<RepairRequestWithMeta> {
+        let (mut prioritized, requests): (Vec<_>, Vec<_>) =
+            requests.into_iter().partition(|r| r.whitelisted);
+        let weights: Vec<u64> = {
+            let mut num_requests = HashMap::<Option<&Pubkey>, u64>::new();
+            for r in &requests {
+                *num_requests.entry(r.request.sender()).or_default() += 1;
+            }
+            requests
+                .iter()
+                .map(|r| {
+                    let weight = r.stake / num_requests[&r.request.sender()];
+                    weight.max(u64::from(r.stake > 0))
+                })
+                .collect()
+        };
+        let mut requests: Vec<_> = requests.into_iter().map(Some).collect();
+        prioritized.extend(
+            WeightedShuffle::new("serve_repair_prioritize_requests", weights)
+                .shuffle(rng)
+                .filter_map(|index| requests[index].take()),
+        );
+        prioritized
+    }
+
     /// Process messages from the network
     fn run_listen(
         &mut self,
@@ -656,7 +886,7 @@ impl ServeRepair {
         response_sender: &PacketBatchSender,
         repair_response_quic_sender: &AsyncSender<(SocketAddr, Bytes)>,
         stats: &mut ServeRepairStats,
-        data_budget: &DataBudget,
+        budget: &mut ServeRepairBudget,
     ) -> std::result::Result<(), RecvTimeoutError> {
         const TIMEOUT: Duration = Duration::from_secs(1);
         let mut requests = vec![requests_receiver.recv_timeout(TIMEOUT)?];
@@ -701,7 +931,7 @@ impl ServeRepair {
         stats.total_requests += total_requests;
 
         let decode_start = Instant::now();
-        let mut decoded_requests = {
+        let decoded_requests = {
             let whitelist = self.repair_whitelist.read().unwrap();
             Self::decode_requests(
                 requests,
@@ -716,9 +946,10 @@ impl ServeRepair {
         stats.decode_time_us += decode_start.elapsed().as_micros() as u64;
         stats.whitelisted_requests += whitelisted_request_count.min(MAX_REQUESTS_PER_ITERATION);
 
+        let mut decoded_requests =
+            Self::prioritize_requests(decoded_requests, &mut rand::thread_rng());
         if decoded_requests.len() > MAX_REQUESTS_PER_ITERATION {
             stats.dropped_requests_low_stake += decoded_requests.len() - MAX_REQUESTS_PER_ITERATION;
-            decoded_requests.sort_unstable_by_key(|r| Reverse((r.whitelisted, r.stake)));
             decoded_requests.truncate(MAX_REQUESTS_PER_ITERATION);
         }
 
@@ -730,7 +961,7 @@ impl ServeRepair {
             response_sender,
             repair_response_quic_sender,
             stats,
-            data_budget,
+            budget,
         );
         stats.handle_requests_time_us += handle_requests_start.elapsed().as_micros() as u64;
 
@@ -764,6 +995,16 @@ impl ServeRepair {
                 stats.dropped_requests_low_stake,
                 i64
             ),
+            (
+                "dropped_requests_peer_limit",
+                stats.dropped_requests_peer_limit,
+                i64
+            ),
+            (
+                "dropped_requests_request_limit",
+                stats.dropped_requests_request_limit,
+                i64
+            ),
             ("whitelisted_requests", stats.whitelisted_requests, i64),
             (
                 "total_dropped_response_packets",
@@ -830,12 +1071,9 @@ impl ServeRepair {
         requests_receiver: Receiver<RemoteRequest>,
         response_sender: PacketBatchSender,
         repair_response_quic_sender: AsyncSender<(SocketAddr, Bytes)>,
+        config: ServeRepairConfig,
         exit: Arc<AtomicBool>,
     ) -> JoinHandle<()> {
-        const INTERVAL_MS: u64 = 1000;
-        const MAX_BYTES_PER_SECOND: usize = 12_000_000;
-        const MAX_BYTES_PER_INTERVAL: usize = MAX_BYTES_PER_SECOND * INTERVAL_MS as usize / 1000;
-
         // rate limit delay should be greater than the repair request iteration delay
         assert!(REPAIR_PING_CACHE_RATE_LIMIT_DELAY > Duration::from_millis(REPAIR_MS));
 
@@ -853,7 +1091,7 @@ impl ServeRepair {
             .spawn(move || {
                 let mut last_print = Instant::now();
                 let mut stats = ServeRepairStats::default();
-                let data_budget = DataBudget::default();
+                let mut budget = ServeRepairBudget::new(config, Instant::now());
                 while !exit.load(Ordering::Relaxed) {
                     let result = self.run_listen(
                         &mut ping_cache,
@@ -862,7 +1100,7 @@ impl ServeRepair {
                         &response_sender,
                         &repair_response_quic_sender,
                         &mut stats,
-                        &data_budget,
+                        &mut budget,
                     );
                     match result {
                         Ok(_) | Err(RecvTimeoutError::Timeout) => {}
@@ -875,7 +1113,7 @@ impl ServeRepair {
                         self.report_reset_stats(&mut stats);
                         last_print = Instant::now();
                     }
-                    data_budget.update(INTERVAL_MS, |_bytes| MAX_BYTES_PER_INTERVAL);
+                    budget.replenish(Instant::now());
                 }
             })
             .unwrap()
@@ -995,7 +1233,7 @@ impl ServeRepair {
         packet_batch_sender: &PacketBatchSender,
         repair_response_quic_sender: &AsyncSender<(SocketAddr, Bytes)>,
         stats: &mut ServeRepairStats,
-        data_budget: &DataBudget,
+        budget: &mut ServeRepairBudget,
     ) {
         let identity_keypair = self.cluster_info.keypair();
         let mut pending_pings = Vec::default();
@@ -1005,13 +1243,20 @@ impl ServeRepair {
             from_addr,
             protocol,
             stake,
-            whitelisted: _,
+            whitelisted,
         } in requests.into_iter()
         {
-            if !data_budget.check(request.max_response_bytes()) {
+            let sender = request.sender().copied();
+            if !budget.check_bytes(request.max_response_bytes()) {
                 stats.dropped_requests_outbound_bandwidth += 1;
                 continue;
             }
+            if !whitelisted
+                && !budget.check_peer_bytes(sender.as_ref(), request.max_response_bytes())
+            {
+                stats.dropped_requests_peer_limit += 1;
+                continue;
+            }
             // Bypass ping/pong check for requests coming from QUIC endpoint.
             if !matches!(&request, RepairProtocol::Pong(_)) && protocol == Protocol::UDP {
                 let (check, ping_pkt) =
@@ -1024,6 +1269,10 @@ impl ServeRepair {
                     continue;
                 }
             }
+            if !budget.take_request() {
+                stats.dropped_requests_request_limit += 1;
+                continue;
+            }
             stats.processed += 1;
             let Some(rsp) = self.handle_repair(recycler, &from_addr, request, stats, ping_cache)
             else {
@@ -1031,7 +1280,7 @@ impl ServeRepair {
             };
             let num_response_packets = rsp.len();
             let num_response_bytes = rsp.iter().map(|p| p.meta().size).sum();
-            if data_budget.take(num_response_bytes)
+            if budget.take_bytes(sender.as_ref(), num_response_bytes)
                 && send_response(
                     rsp,
                     protocol,
@@ -1100,8 +1349,15 @@ impl ServeRepair {
                 peers_cache.pop(&slot);
                 let repair_peers =
                     self.repair_peers(repair_validators, slot, &identity_keypair.pubkey());
-                let weights = cluster_slots.compute_weights(slot, &repair_peers);
-                let repair_peers = RepairPeers::new(Instant::now(), &repair_peers, &weights)?;
+                let mut weights = cluster_slots.compute_weights(slot, &repair_peers);
+                self.cluster_info
+                    .apply_peer_preference(&repair_peers, &mut weights);
+                let encrypted_transport = self
+                    .cluster_info
+                    .my_contact_info()
+                    .has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+                let repair_peers =
+                    RepairPeers::new(Instant::now(), &repair_peers, &weights, encrypted_transport)?;
                 peers_cache.put(slot, repair_peers);
                 peers_cache.get(&slot).unwrap()
             }
@@ -1121,6 +1377,12 @@ impl ServeRepair {
             peer.pubkey,
             repair_request
         );
+        // The response comes back over the connection the request was sent on
+        let repair_protocol = if peer.encrypted_transport {
+            Protocol::QUIC
+        } else {
+            repair_protocol
+        };
         match repair_protocol {
             Protocol::UDP => Ok(Some((peer.serve_repair, out))),
             Protocol::QUIC => {
@@ -1370,6 +1632,24 @@ mod tests {
         std::{io::Cursor, net::Ipv4Addr},
     };
 
+    #[test]
+    fn test_repair_peers_encrypted_transport() {
+        let plain = ContactInfo::new_localhost(&solana_pubkey::new_rand(), timestamp());
+        let mut encrypted = ContactInfo::new_localhost(&solana_pubkey::new_rand(), timestamp());
+        encrypted.set_capabilities(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+        let peers = [plain, encrypted];
+        for encrypted_transport in [false, true] {
+            let repair_peers =
+                RepairPeers::new(Instant::now(), &peers, &[1, 1], encrypted_transport).unwrap();
+            let peers_encrypted_transport: Vec<_> = repair_peers
+                .peers
+                .iter()
+                .map(|peer| peer.encrypted_transport)
+                .collect();
+            assert_eq!(peers_encrypted_transport, [false, encrypted_transport]);
+        }
+    }
+
     #[test]
     fn test_serialized_ping_size() {
         let mut rng = rand::thread_rng();
@@ -1655,6 +1935,87 @@ mod tests {
         }
     }
 
+    fn new_request_with_meta(
+        sender: Pubkey,
+        stake: u64,
+        whitelisted: bool,
+    ) -> RepairRequestWithMeta {
+        RepairRequestWithMeta {
+            request: RepairProtocol::WindowIndex {
+                header: RepairRequestHeader::new(sender, Pubkey::new_unique(), timestamp(), 0),
+                slot: 1,
+                shred_index: 0,
+            },
+            from_addr: socketaddr!(Ipv4Addr::LOCALHOST, 8001),
+            protocol: Protocol::UDP,
+            stake,
+            whitelisted,
+        }
+    }
+
+    #[test]
+    fn test_prioritize_requests() {
+        let mut rng = rand::thread_rng();
+        let whitelisted = Pubkey::new_unique();
+        let staked = Pubkey::new_unique();
+        let unstaked = Pubkey::new_unique();
+        let requests = vec![
+            new_request_with_meta(unstaked, 0, false),
+            new_request_with_meta(staked, 100, false),
+            new_request_with_meta(unstaked, 0, false),
+            new_request_with_meta(whitelisted, 0, true),
+            new_request_with_meta(staked, 100, false),
+        ];
+        let senders: Vec<_> = ServeRepair::prioritize_requests(requests, &mut rng)
+            .iter()
+            .map(|r| *r.request.sender().unwrap())
+            .collect();
+        assert_eq!(
+            senders,
+            vec![whitelisted, staked, staked, unstaked, unstaked]
+        );
+    }
+
+    #[test]
+    fn test_serve_repair_budget() {
+        let peer = Pubkey::new_unique();
+        let other_peer = Pubkey::new_unique();
+        let now = Instant::now();
+        let mut budget = ServeRepairBudget::new(
+            ServeRepairConfig {
+                max_bytes_per_second: 3000,
+                max_requests_per_second: Some(2),
+                max_peer_bytes_per_second: Some(2000),
+            },
+            now,
+        );
+
+        assert!(budget.check_peer_bytes(Some(&peer), 2000));
+        assert!(budget.take_bytes(Some(&peer), 1500));
+        assert!(!budget.check_peer_bytes(Some(&peer), 1000));
+        assert!(budget.check_peer_bytes(Some(&other_peer), 1000));
+        assert!(budget.check_bytes(1500));
+        assert!(!budget.check_bytes(1501));
+        assert!(!budget.take_bytes(Some(&other_peer), 1501));
+        assert!(budget.take_request());
+        assert!(budget.take_request());
+        assert!(!budget.take_request());
+
+        // Nothing is replenished before the interval elapses
+        budget.replenish(now + SERVE_REPAIR_BUDGET_INTERVAL / 2);
+        assert!(!budget.take_request());
+        budget.replenish(now + SERVE_REPAIR_BUDGET_INTERVAL);
+        assert!(budget.take_request());
+        assert!(budget.check_bytes(3000));
+        assert!(budget.check_peer_bytes(Some(&peer), 2000));
+
+        // Without limits on requests and peers only the bytes are limited
+        let mut budget = ServeRepairBudget::new(ServeRepairConfig::default(), now);
+        assert!((0..10_000).all(|_| budget.take_request()));
+        assert!(budget.check_peer_bytes(Some(&peer), DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND));
+        assert!(!budget.check_bytes(DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND + 1));
+    }
+
     #[test]
     fn test_verify_signed_packet() {
         let my_keypair = Keypair::new();
@@ -1991,6 +2352,71 @@ mod tests {
         assert!(one && two);
     }
 
+    #[test]
+    fn test_repair_request_encrypted_transport() {
+        use Protocol::{QUIC, UDP};
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
+        let cluster_slots = ClusterSlots::default_for_tests();
+        for (self_encrypted, peer_encrypted) in
+            [(false, false), (false, true), (true, false), (true, true)]
+        {
+            let keypair = Arc::new(Keypair::new());
+            let mut contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+            if self_encrypted {
+                contact_info.set_capabilities(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+            }
+            let cluster_info = Arc::new(ClusterInfo::new(
+                contact_info,
+                keypair,
+                SocketAddrSpace::Unspecified,
+            ));
+            let serve_repair = ServeRepair::new_for_test(
+                cluster_info.clone(),
+                bank_forks.clone(),
+                Arc::new(RwLock::new(HashSet::default())),
+            );
+            let mut peer = ContactInfo::new(
+                solana_pubkey::new_rand(),
+                timestamp(), // wallclock
+                0u16,        // shred_version
+            );
+            peer.set_gossip((Ipv4Addr::LOCALHOST, 1234)).unwrap();
+            peer.set_tvu(UDP, (Ipv4Addr::LOCALHOST, 1235)).unwrap();
+            peer.set_serve_repair(UDP, (Ipv4Addr::LOCALHOST, 1243))
+                .unwrap();
+            peer.set_serve_repair(QUIC, (Ipv4Addr::LOCALHOST, 1237))
+                .unwrap();
+            if peer_encrypted {
+                peer.set_capabilities(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+            }
+            cluster_info.insert_info(peer.clone());
+            let (repair_request_quic_sender, mut repair_request_quic_receiver) =
+                tokio::sync::mpsc::channel(/*buffer:*/ 128);
+            let rv = serve_repair
+                .repair_request(
+                    &cluster_slots,
+                    ShredRepairType::Shred(0, 0),
+                    &mut LruCache::new(100),
+                    &mut RepairStats::default(),
+                    &None,
+                    &mut OutstandingShredRepairs::default(),
+                    &cluster_info.keypair(),
+                    &repair_request_quic_sender,
+                    UDP, // repair_protocol
+                )
+                .unwrap();
+            if self_encrypted && peer_encrypted {
+                assert_eq!(rv, None);
+                let (addr, _) = repair_request_quic_receiver.try_recv().unwrap();
+                assert_eq!(addr, peer.serve_repair(QUIC).unwrap());
+            } else {
+                assert_eq!(rv.unwrap().0, peer.serve_repair(UDP).unwrap());
+                assert!(repair_request_quic_receiver.try_recv().is_err());
+            }
+        }
+    }
+
     #[test]
     fn test_run_orphan() {
         run_orphan(2, 3, 9);
@@ -2360,4 +2786,41 @@ mod tests {
         response.push((request_slot, Hash::new_unique()));
         assert!(!repair.verify_response(&AncestorHashesResponse::Hashes(response)));
     }
+
+    #[test]
+    fn test_repair_served_slots() {
+        let start = Instant::now();
+        let mut served_slots = ServedSlots::default();
+        served_slots.insert(10, start);
+        served_slots.insert(20, start);
+        // served again later, so it outlives slot 20
+        served_slots.insert(10, start + MAX_REPAIR_SERVED_SLOT_AGE / 2);
+        assert_eq!(served_slots.queue.len(), 2);
+
+        served_slots.prune(start + MAX_REPAIR_SERVED_SLOT_AGE);
+        assert_eq!(
+            served_slots.last_served.keys().copied().collect::<Vec<_>>(),
+            vec![10]
+        );
+        served_slots.prune(start + MAX_REPAIR_SERVED_SLOT_AGE * 2);
+        assert!(served_slots.last_served.is_empty());
+        assert!(served_slots.queue.is_empty());
+
+        // the earliest served slots are forgotten beyond the capacity
+        for slot in 0..=MAX_REPAIR_SERVED_SLOTS as Slot {
+            served_slots.insert(slot, start);
+        }
+        assert_eq!(served_slots.last_served.len(), MAX_REPAIR_SERVED_SLOTS);
+        assert_eq!(served_slots.queue.len(), MAX_REPAIR_SERVED_SLOTS);
+        assert!(!served_slots.last_served.contains_key(&0));
+
+        let repair_served_slots = RepairServedSlots::default();
+        repair_served_slots.record(30);
+        repair_served_slots.record(20);
+        assert_eq!(
+            repair_served_slots.lowest_served_slot(MAX_REPAIR_SERVED_SLOT_AGE),
+            Some(20)
+        );
+        assert_eq!(repair_served_slots.lowest_served_slot(Duration::ZERO), None);
+    }
 }
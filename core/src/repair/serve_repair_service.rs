@@ -1,5 +1,8 @@
 use {
-    crate::repair::{quic_endpoint::RemoteRequest, serve_repair::ServeRepair},
+    crate::repair::{
+        quic_endpoint::RemoteRequest,
+        serve_repair::{ServeRepair, ServeRepairConfig},
+    },
     bytes::Bytes,
     crossbeam_channel::{unbounded, Receiver, Sender},
     solana_perf::{packet::PacketBatch, recycler::Recycler},
@@ -23,6 +26,7 @@ pub struct ServeRepairService {
 impl ServeRepairService {
     pub(crate) fn new(
         serve_repair: ServeRepair,
+        serve_repair_config: ServeRepairConfig,
         remote_request_sender: Sender<RemoteRequest>,
         remote_request_receiver: Receiver<RemoteRequest>,
         repair_response_quic_sender: AsyncSender<(SocketAddr, Bytes)>,
@@ -61,6 +65,7 @@ impl ServeRepairService {
             remote_request_receiver,
             response_sender,
             repair_response_quic_sender,
+            serve_repair_config,
             exit,
         );
 
@@ -67,7 +67,10 @@ use {
     solana_rpc_client_api::response::SlotUpdate,
     solana_runtime::{
         bank::{bank_hash_details, Bank, NewBankOptions},
-        bank_forks::{BankForks, MAX_ROOT_DISTANCE_FOR_VOTE_ONLY},
+        bank_forks::{
+            BankForks, BankForksEviction, BankForksLimits, ForkOverLimits,
+            MAX_ROOT_DISTANCE_FOR_VOTE_ONLY,
+        },
         commitment::BlockCommitmentCache,
         installed_scheduler_pool::BankWithScheduler,
         prioritization_fee_cache::PrioritizationFeeCache,
@@ -81,7 +84,7 @@ use {
     solana_vote::vote_transaction::VoteTransaction,
     solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
     std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeSet, HashMap, HashSet},
         num::{NonZeroUsize, Saturating},
         result,
         sync::{
@@ -196,6 +199,16 @@ struct SkippedSlotsInfo {
     last_skipped_slot: u64,
 }
 
+/// The forks found over the [`BankForksLimits`], by their first slot
+#[derive(Default)]
+struct ForksOverLimits {
+    /// Forks kept after being reported, so each is reported once
+    reported: BTreeSet<Slot>,
+    /// Forks removed from BankForks, which are not replayed again unless the cluster confirms
+    /// one of their slots
+    pruned: BTreeSet<Slot>,
+}
+
 pub struct TowerBFTStructures {
     pub heaviest_subtree_fork_choice: HeaviestSubtreeForkChoice,
     pub duplicate_slots_tracker: DuplicateSlotsTracker,
@@ -291,6 +304,7 @@ pub struct ReplayStageConfig {
     pub prioritization_fee_cache: Arc<PrioritizationFeeCache>,
     pub banking_tracer: Arc<BankingTracer>,
     pub snapshot_controller: Option<Arc<SnapshotController>>,
+    pub bank_forks_limits: BankForksLimits,
 }
 
 pub struct ReplaySenders {
@@ -597,6 +611,7 @@ impl ReplayStage {
             prioritization_fee_cache,
             banking_tracer,
             snapshot_controller,
+            bank_forks_limits,
         } = config;
 
         let ReplaySenders {
@@ -689,6 +704,7 @@ impl ReplayStage {
             let mut last_reset_bank_descendants = Vec::new();
             let mut partition_info = PartitionInfo::new();
             let mut skipped_slots_info = SkippedSlotsInfo::default();
+            let mut forks_over_limits = ForksOverLimits::default();
             let mut replay_timing = ReplayLoopTiming::default();
             let duplicate_slots_tracker = DuplicateSlotsTracker::default();
             let duplicate_confirmed_slots = DuplicateConfirmedSlots::default();
@@ -758,6 +774,19 @@ impl ReplayStage {
                     break;
                 }
 
+                if !is_alpenglow_migration_complete && bank_forks_limits.is_enabled() {
+                    Self::enforce_bank_forks_limits(
+                        &bank_forks_limits,
+                        &bank_forks,
+                        &blockstore,
+                        &mut progress,
+                        &tower,
+                        &poh_recorder,
+                        &mut tbft_structs,
+                        &mut forks_over_limits,
+                    );
+                }
+
                 let mut generate_new_bank_forks_time =
                     Measure::start("generate_new_bank_forks_time");
                 Self::generate_new_bank_forks(
@@ -768,6 +797,7 @@ impl ReplayStage {
                     &slot_status_notifier,
                     &mut progress,
                     &mut replay_timing,
+                    &forks_over_limits.pruned,
                 );
                 generate_new_bank_forks_time.stop();
 
@@ -1921,6 +1951,150 @@ impl ReplayStage {
         }
     }
 
+    /// Reports the forks over `limits` once each, and removes them from BankForks when they are
+    /// to be pruned
+    ///
+    /// The forks of the heaviest bank, the last vote, the PoH reset bank and the duplicate
+    /// confirmed slots are kept. A pruned fork is replayed again once the cluster duplicate
+    /// confirms one of its slots.
+    #[allow(clippy::too_many_arguments)]
+    fn enforce_bank_forks_limits(
+        limits: &BankForksLimits,
+        bank_forks: &RwLock<BankForks>,
+        blockstore: &Blockstore,
+        progress: &mut ProgressMap,
+        tower: &Tower,
+        poh_recorder: &RwLock<PohRecorder>,
+        tbft_structs: &mut TowerBFTStructures,
+        forks_over_limits: &mut ForksOverLimits,
+    ) {
+        let root = bank_forks.read().unwrap().root();
+        forks_over_limits.reported.retain(|slot| *slot > root);
+        forks_over_limits.pruned.retain(|slot| *slot > root);
+
+        if let Some(&first_pruned_slot) = forks_over_limits.pruned.first() {
+            let r_bank_forks = bank_forks.read().unwrap();
+            for &confirmed_slot in tbft_structs.duplicate_confirmed_slots.keys() {
+                // Walk the ancestors of the confirmed slot down to a bank that is kept
+                let mut slot = confirmed_slot;
+                while slot >= first_pruned_slot && r_bank_forks.get(slot).is_none() {
+                    if forks_over_limits.pruned.remove(&slot) {
+                        warn!(
+                            "Replaying the pruned fork of slot {slot} again, slot \
+                             {confirmed_slot} is duplicate confirmed"
+                        );
+                        break;
+                    }
+                    let Some(parent_slot) = blockstore
+                        .meta(slot)
+                        .ok()
+                        .flatten()
+                        .and_then(|meta| meta.parent_slot)
+                    else {
+                        break;
+                    };
+                    slot = parent_slot;
+                }
+            }
+        }
+
+        let keep: Vec<Slot> = [
+            Some(
+                tbft_structs
+                    .heaviest_subtree_fork_choice
+                    .best_overall_slot()
+                    .0,
+            ),
+            tower.last_voted_slot(),
+            Some(poh_recorder.read().unwrap().start_slot()),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(tbft_structs.duplicate_confirmed_slots.keys().copied())
+        .collect();
+        let (forks, num_unrooted_banks) = {
+            let r_bank_forks = bank_forks.read().unwrap();
+            (
+                r_bank_forks.forks_over_limits(limits, &keep),
+                r_bank_forks.len().saturating_sub(1),
+            )
+        };
+        let forks: Vec<_> = forks
+            .into_iter()
+            .filter(|fork| !forks_over_limits.reported.contains(&fork.slot))
+            .collect();
+        if forks.is_empty() {
+            return;
+        }
+
+        for fork in &forks {
+            warn!(
+                "The fork of slot {} with {} banks up to slot {} is over the bank forks limits \
+                 of {num_unrooted_banks} unrooted banks: {limits:?}",
+                fork.slot,
+                fork.slots.len(),
+                fork.slots.last().unwrap(),
+            );
+            match limits.eviction {
+                BankForksEviction::Report => {
+                    forks_over_limits.reported.insert(fork.slot);
+                }
+                BankForksEviction::Prune => {
+                    Self::prune_fork_over_limits(
+                        fork,
+                        bank_forks,
+                        progress,
+                        &mut tbft_structs.heaviest_subtree_fork_choice,
+                    );
+                    forks_over_limits.pruned.insert(fork.slot);
+                }
+            }
+        }
+        datapoint_info!(
+            "bank_forks_limits",
+            ("unrooted_banks", num_unrooted_banks, i64),
+            ("forks_over_limits", forks.len(), i64),
+            (
+                "banks_over_limits",
+                forks.iter().map(|fork| fork.slots.len()).sum::<usize>(),
+                i64
+            ),
+            ("pruned", limits.eviction == BankForksEviction::Prune, bool),
+        );
+    }
+
+    /// Removes the banks of `fork` like duplicate slots are purged, but keeps their shreds so the
+    /// fork can be replayed again
+    fn prune_fork_over_limits(
+        fork: &ForkOverLimits,
+        bank_forks: &RwLock<BankForks>,
+        progress: &mut ProgressMap,
+        fork_choice: &mut HeaviestSubtreeForkChoice,
+    ) {
+        let (slots_to_purge, removed_banks, root_bank) = {
+            let mut w_bank_forks = bank_forks.write().unwrap();
+            let fork_key = w_bank_forks
+                .get(fork.slot)
+                .filter(|bank| bank.is_frozen())
+                .map(|bank| (fork.slot, bank.hash()));
+            if let Some(fork_key) = fork_key.filter(|key| fork_choice.contains_block(key)) {
+                fork_choice.split_off(&fork_key);
+            }
+            let (slots_to_purge, removed_banks) = w_bank_forks.evict_slots(fork.slots.iter());
+            (slots_to_purge, removed_banks, w_bank_forks.root_bank())
+        };
+
+        // Clear the accounts of the slots before dropping their banks, as for duplicate slots
+        root_bank.remove_unrooted_slots(&slots_to_purge);
+        drop(removed_banks);
+
+        for (slot, _) in slots_to_purge {
+            root_bank.clear_slot_signatures(slot);
+            root_bank.prune_program_cache_by_deployment_slot(slot);
+            progress.remove(&slot);
+        }
+    }
+
     // Check for any newly duplicate confirmed slots by the cluster.
     // This only tracks duplicate slot confirmations on the exact
     // single slots and does not account for votes on their descendants. Used solely
@@ -3435,6 +3609,16 @@ impl ReplayStage {
                         .parent()
                         .map(|bank| bank.last_blockhash())
                         .unwrap_or_default();
+                    let first_shred_timestamp_ms = if bank.collector_id() == my_pubkey {
+                        None
+                    } else {
+                        blockstore
+                            .meta(bank.slot())
+                            .ok()
+                            .flatten()
+                            .map(|meta| meta.first_shred_timestamp)
+                            .filter(|timestamp| *timestamp != 0)
+                    };
                     block_metadata_notifier.notify_block_metadata(
                         bank.parent_slot(),
                         &parent_blockhash.to_string(),
@@ -3445,6 +3629,8 @@ impl ReplayStage {
                         Some(bank.block_height()),
                         bank.executed_transaction_count(),
                         r_replay_progress.num_entries as u64,
+                        first_shred_timestamp_ms,
+                        &r_replay_progress.entry_batch_timings,
                     )
                 }
                 bank_complete_time.stop();
@@ -4246,6 +4432,7 @@ impl ReplayStage {
         slot_status_notifier: &Option<SlotStatusNotifier>,
         progress: &mut ProgressMap,
         replay_timing: &mut ReplayLoopTiming,
+        pruned_forks: &BTreeSet<Slot>,
     ) {
         // Find the next slot that chains to the old slot
         let mut generate_new_bank_forks_read_lock =
@@ -4284,6 +4471,10 @@ impl ReplayStage {
                     trace!("child already active or frozen {child_slot}");
                     continue;
                 }
+                if pruned_forks.contains(&child_slot) {
+                    trace!("child pruned over the bank forks limits {child_slot}");
+                    continue;
+                }
                 let leader = leader_schedule_cache
                     .slot_leader_at(child_slot, Some(parent_bank))
                     .unwrap();
@@ -4675,6 +4866,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert!(bank_forks
             .read()
@@ -4699,6 +4891,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert!(bank_forks
             .read()
@@ -6516,6 +6709,52 @@ pub(crate) mod tests {
         assert!(!blockstore.get_slot_entries(7, 0).unwrap().is_empty());
     }
 
+    #[test]
+    fn test_prune_fork_over_limits() {
+        // Create the tree of banks in a BankForks object
+        let (vote_simulator, _blockstore) = setup_default_forks(1, None::<GenerateVotes>);
+        let VoteSimulator {
+            bank_forks,
+            mut progress,
+            mut tbft_structs,
+            ..
+        } = vote_simulator;
+
+        // Keeping slot 6 selects the fork of slot 2
+        let limits = BankForksLimits {
+            max_unrooted_banks: Some(4),
+            max_fork_age: None,
+            eviction: BankForksEviction::Prune,
+        };
+        let forks = bank_forks.read().unwrap().forks_over_limits(&limits, &[6]);
+        assert_eq!(
+            forks,
+            vec![ForkOverLimits {
+                slot: 2,
+                slots: vec![2, 4],
+            }]
+        );
+
+        let bank2_hash = bank_forks.read().unwrap().bank_hash(2).unwrap();
+        ReplayStage::prune_fork_over_limits(
+            &forks[0],
+            &bank_forks,
+            &mut progress,
+            &mut tbft_structs.heaviest_subtree_fork_choice,
+        );
+        for slot in [2, 4] {
+            assert!(bank_forks.read().unwrap().get(slot).is_none());
+            assert!(progress.get(&slot).is_none());
+        }
+        assert!(!tbft_structs
+            .heaviest_subtree_fork_choice
+            .contains_block(&(2, bank2_hash)));
+        for slot in [0, 1, 3, 5, 6] {
+            assert!(bank_forks.read().unwrap().get(slot).is_some());
+            assert!(progress.get(&slot).is_some());
+        }
+    }
+
     #[test]
     fn test_purge_unconfirmed_duplicate_slots_and_reattach() {
         let ReplayBlockstoreComponents {
@@ -6609,6 +6848,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert_eq!(bank_forks.read().unwrap().active_bank_slots(), vec![3]);
 
@@ -6639,6 +6879,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert_eq!(bank_forks.read().unwrap().active_bank_slots(), vec![5]);
 
@@ -6670,6 +6911,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert_eq!(bank_forks.read().unwrap().active_bank_slots(), vec![6]);
 
@@ -6700,6 +6942,7 @@ pub(crate) mod tests {
             &None,
             &mut progress,
             &mut replay_timing,
+            &BTreeSet::new(),
         );
         assert_eq!(bank_forks.read().unwrap().active_bank_slots(), vec![7]);
     }
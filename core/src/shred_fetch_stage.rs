@@ -43,14 +43,6 @@ pub(crate) struct ShredFetchStage {
     thread_hdls: Vec<JoinHandle<()>>,
 }
 
-/// Ingress limit for the shred fetch channel (in terms of packet _batches_).
-///
-/// The general case sees shred and repair ingress in the hundreds of packet batches per second.
-/// However, in the case of catch-up, we may see upwards of 8k packet batches per second, which would
-/// suggest a roughly 16k packet batch limit for ample headroom. We're setting it to 4x that amount
-/// to future proof for increases of CU limits (e.g., a future 100k CU limit).
-pub(crate) const SHRED_FETCH_CHANNEL_SIZE: usize = 1024 * 64;
-
 #[derive(Clone)]
 struct RepairContext {
     repair_socket: Arc<UdpSocket>,
@@ -210,10 +202,10 @@ impl ShredFetchStage {
         flags: PacketFlags,
         repair_context: Option<RepairContext>,
         turbine_disabled: Arc<AtomicBool>,
+        channel_size: usize,
     ) -> (Vec<JoinHandle<()>>, JoinHandle<()>) {
         let sharable_banks = bank_forks.read().unwrap().sharable_banks();
-        let (packet_sender, packet_receiver) =
-            EvictingSender::new_bounded(SHRED_FETCH_CHANNEL_SIZE);
+        let (packet_sender, packet_receiver) = EvictingSender::new_bounded(channel_size);
         let receiver_stats = Arc::new(StreamerReceiveStats::new(receiver_name));
         let streamers = sockets
             .into_iter()
@@ -259,6 +251,8 @@ impl ShredFetchStage {
         repair_response_quic_receiver: Receiver<(Pubkey, SocketAddr, Bytes)>,
         repair_socket: Arc<UdpSocket>,
         sender: EvictingSender<PacketBatch>,
+        // Packet batches each of the turbine and repair receivers may buffer
+        channel_size: usize,
         shred_version: u16,
         bank_forks: Arc<RwLock<BankForks>>,
         cluster_info: Arc<ClusterInfo>,
@@ -287,6 +281,7 @@ impl ShredFetchStage {
             PacketFlags::empty(),
             None, // repair_context
             turbine_disabled.clone(),
+            channel_size,
         );
 
         let (repair_receiver, repair_handler) = Self::packet_modifier(
@@ -303,6 +298,7 @@ impl ShredFetchStage {
             PacketFlags::REPAIR,
             Some(repair_context.clone()),
             turbine_disabled.clone(),
+            channel_size,
         );
 
         tvu_threads.extend(repair_receiver);
@@ -11,6 +11,7 @@ use {
     crate::{
         banking_trace::BankingPacketSender,
         sigverify_stage::{SigVerifier, SigVerifyServiceError},
+        tpu_mirror_stage::TpuMirrorSender,
     },
     agave_banking_stage_ingress_types::BankingPacketBatch,
     crossbeam_channel::{Sender, TrySendError},
@@ -20,6 +21,7 @@ use {
 pub struct TransactionSigVerifier {
     banking_stage_sender: BankingPacketSender,
     forward_stage_sender: Option<Sender<(BankingPacketBatch, bool)>>,
+    mirror_sender: Option<TpuMirrorSender>,
     recycler: Recycler<TxOffset>,
     recycler_out: Recycler<PinnedVec<u8>>,
     reject_non_vote: bool,
@@ -43,11 +45,18 @@ impl TransactionSigVerifier {
         Self {
             banking_stage_sender,
             forward_stage_sender,
+            mirror_sender: None,
             recycler: Recycler::warmed(50, 4096),
             recycler_out: Recycler::warmed(50, 4096),
             reject_non_vote: false,
         }
     }
+
+    /// Also sends the verified packets to the `TpuMirrorStage`
+    pub(crate) fn with_mirror_sender(mut self, mirror_sender: Option<TpuMirrorSender>) -> Self {
+        self.mirror_sender = mirror_sender;
+        self
+    }
 }
 
 impl SigVerifier for TransactionSigVerifier {
@@ -58,6 +67,9 @@ impl SigVerifier for TransactionSigVerifier {
         packet_batches: Vec<PacketBatch>,
     ) -> Result<(), SigVerifyServiceError<Self::SendType>> {
         let banking_packet_batch = BankingPacketBatch::new(packet_batches);
+        if let Some(mirror_sender) = &self.mirror_sender {
+            mirror_sender.send(&banking_packet_batch);
+        }
         if let Some(forward_stage_sender) = &self.forward_stage_sender {
             self.banking_stage_sender
                 .send(banking_packet_batch.clone())?;
@@ -113,7 +113,15 @@ impl SnapshotPackagerService {
                         break;
                     }
 
-                    if let Some(snapshot_gossip_manager) = snapshot_gossip_manager.as_mut() {
+                    // Only advertise snapshots that peers can download from this node
+                    let has_local_archive = snapshot_config
+                        .archive_upload
+                        .as_ref()
+                        .is_none_or(|upload| upload.keep_local_archive);
+                    if let Some(snapshot_gossip_manager) = snapshot_gossip_manager
+                        .as_mut()
+                        .filter(|_| has_local_archive)
+                    {
                         snapshot_gossip_manager
                             .push_snapshot_hash(snapshot_kind, (snapshot_slot, snapshot_hash));
                     }
@@ -6,8 +6,12 @@ use {
     crate::{
         admin_rpc_post_init::{KeyUpdaterType, KeyUpdaters},
         banking_stage::{
-            transaction_scheduler::scheduler_controller::SchedulerConfig, BankingControlMsg,
-            BankingStage, BankingStageHandle,
+            reserved_capacity::ReservedCapacity,
+            transaction_scheduler::{
+                in_flight_tracker::InFlightBatchesReport, scheduler_controller::SchedulerConfig,
+                write_lock_contention::WriteLockContentionReport,
+            },
+            BankingControlMsg, BankingStage, BankingStageHandle,
         },
         banking_trace::{Channels, TracerThread},
         cluster_info_vote_listener::{
@@ -16,12 +20,14 @@ use {
         },
         fetch_stage::FetchStage,
         forwarding_stage::{
-            spawn_forwarding_stage, ForwardAddressGetter, SpawnForwardingStageResult,
+            forwarding_deny::ForwardingDeny, spawn_forwarding_stage, ForwardAddressGetter,
+            SpawnForwardingStageResult,
         },
         sigverify::TransactionSigVerifier,
         sigverify_stage::SigVerifyStage,
         staked_nodes_updater_service::StakedNodesUpdaterService,
         tpu_entry_notifier::TpuEntryNotifier,
+        tpu_mirror_stage::{TpuMirrorConfig, TpuMirrorStage},
         validator::{BlockProductionMethod, GeneratorConfig},
         vortexor_receiver_adapter::VortexorReceiverAdapter,
     },
@@ -46,6 +52,7 @@ use {
     },
     solana_runtime::{
         bank_forks::BankForks,
+        banking_queue_snapshot::SharedBankingQueueSnapshot,
         prioritization_fee_cache::PrioritizationFeeCache,
         vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
     },
@@ -118,6 +125,7 @@ pub struct Tpu {
     staked_nodes_updater_service: StakedNodesUpdaterService,
     tracer_thread_hdl: TracerThread,
     tpu_vote_quic_t: thread::JoinHandle<()>,
+    tpu_mirror_stage: Option<TpuMirrorStage>,
 }
 
 impl Tpu {
@@ -158,14 +166,18 @@ impl Tpu {
         tpu_fwd_quic_server_config: SwQosQuicStreamerConfig,
         vote_quic_server_config: SimpleQosQuicStreamerConfig,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
         block_production_method: BlockProductionMethod,
         block_production_num_workers: NonZeroUsize,
         block_production_scheduler_config: SchedulerConfig,
+        block_production_reserved_capacity: ReservedCapacity,
         enable_block_production_forwarding: bool,
         _generator_config: Option<GeneratorConfig>, /* vestigial code for replay invalidator */
         key_notifiers: Arc<RwLock<KeyUpdaters>>,
         banking_control_receiver: mpsc::Receiver<BankingControlMsg>,
         scheduler_bindings: Option<(PathBuf, mpsc::Sender<BankingControlMsg>)>,
+        tpu_mirror_config: Option<TpuMirrorConfig>,
+        forwarding_deny: Option<ForwardingDeny>,
         cancel: CancellationToken,
     ) -> Self {
         let TpuSockets {
@@ -278,6 +290,17 @@ impl Tpu {
             (None, None)
         };
 
+        let (tpu_mirror_stage, mirror_sender) = match tpu_mirror_config.map(TpuMirrorStage::new) {
+            Some(Ok((tpu_mirror_stage, mirror_sender))) => {
+                (Some(tpu_mirror_stage), Some(mirror_sender))
+            }
+            Some(Err(err)) => {
+                error!("Failed to start mirroring TPU transactions: {err}");
+                (None, None)
+            }
+            None => (None, None),
+        };
+
         let (forward_stage_sender, forward_stage_receiver) = bounded(1024);
         let sig_verifier = if let Some(vortexor_receivers) = vortexor_receivers {
             info!("starting vortexor adapter");
@@ -287,6 +310,7 @@ impl Tpu {
                 Duration::from_millis(5),
                 non_vote_sender,
                 enable_block_production_forwarding.then(|| forward_stage_sender.clone()),
+                mirror_sender,
                 exit.clone(),
             );
             SigVerifier::Remote(adapter)
@@ -295,7 +319,8 @@ impl Tpu {
             let verifier = TransactionSigVerifier::new(
                 non_vote_sender,
                 enable_block_production_forwarding.then(|| forward_stage_sender.clone()),
-            );
+            )
+            .with_mirror_sender(mirror_sender);
             SigVerifier::Local(SigVerifyStage::new(
                 packet_receiver,
                 verifier,
@@ -342,11 +367,13 @@ impl Tpu {
             banking_control_receiver,
             block_production_num_workers,
             block_production_scheduler_config,
+            block_production_reserved_capacity,
             transaction_status_sender,
             replay_vote_sender,
             log_messages_bytes_limit,
             bank_forks.clone(),
             prioritization_fee_cache.clone(),
+            banking_queue_snapshot,
         );
 
         #[cfg(unix)]
@@ -366,6 +393,7 @@ impl Tpu {
             bank_forks.read().unwrap().sharable_banks(),
             ForwardAddressGetter::new(cluster_info.clone(), poh_recorder.clone()),
             DataBudget::default(),
+            forwarding_deny,
         );
 
         let (entry_receiver, tpu_entry_notifier) =
@@ -420,9 +448,18 @@ impl Tpu {
             staked_nodes_updater_service,
             tracer_thread_hdl,
             tpu_vote_quic_t,
+            tpu_mirror_stage,
         }
     }
 
+    pub fn write_lock_contention_report(&self) -> &Arc<WriteLockContentionReport> {
+        self.banking_stage.write_lock_contention_report()
+    }
+
+    pub fn in_flight_batches_report(&self) -> &Arc<InFlightBatchesReport> {
+        self.banking_stage.in_flight_batches_report()
+    }
+
     pub fn join(self) -> thread::Result<()> {
         let results = vec![
             self.fetch_stage.join(),
@@ -435,6 +472,7 @@ impl Tpu {
             self.tpu_quic_t.map_or(Ok(()), |t| t.join()),
             self.tpu_forwards_quic_t.map_or(Ok(()), |t| t.join()),
             self.tpu_vote_quic_t.join(),
+            self.tpu_mirror_stage.map_or(Ok(()), TpuMirrorStage::join),
         ];
         let broadcast_result = self.broadcast_stage.join();
         for result in results {
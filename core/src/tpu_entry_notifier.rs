@@ -83,6 +83,7 @@ impl TpuEntryNotifier {
             index,
             entry: entry_summary,
             starting_transaction_index: *current_transaction_index,
+            is_last_in_slot: tick_height == bank.max_tick_height(),
         }) {
             warn!(
                 "Failed to send slot {slot:?} entry {index:?} from Tpu to EntryNotifierService, \
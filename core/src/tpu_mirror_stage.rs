@@ -0,0 +1,286 @@
+//! `TpuMirrorStage` sends a copy of the verified transactions received over
+//! the TPU to a secondary destination, such as a node running a new version or
+//! a banking stage experiment, so that it can be tested against live traffic.
+//!
+//! Mirroring never holds back the TPU: batches arriving while the stage is
+//! behind are dropped, and transactions failing to send are not retried.
+
+use {
+    agave_banking_stage_ingress_types::BankingPacketBatch,
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender},
+    rand::Rng,
+    solana_client::connection_cache::ConnectionCache,
+    solana_connection_cache::client_connection::ClientConnection,
+    solana_gossip::contact_info::Protocol,
+    solana_perf::packet::PacketBatch,
+    solana_streamer::sendmmsg::batch_send,
+    std::{
+        net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::{Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+/// Number of verified batches buffered for the mirror before dropping new ones
+const MIRROR_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TpuMirrorConfig {
+    pub address: SocketAddr,
+    pub protocol: Protocol,
+    /// Fraction of the transactions to mirror, from 0.0 to 1.0
+    pub sample_rate: f64,
+}
+
+/// Hands verified batches to the [`TpuMirrorStage`] without blocking
+#[derive(Clone)]
+pub(crate) struct TpuMirrorSender {
+    sender: Sender<BankingPacketBatch>,
+    dropped_batches: Arc<AtomicUsize>,
+}
+
+impl TpuMirrorSender {
+    pub(crate) fn send(&self, packet_batches: &BankingPacketBatch) {
+        if self.sender.try_send(packet_batches.clone()).is_err() {
+            self.dropped_batches.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+enum MirrorClient {
+    Udp(UdpSocket),
+    Quic(ConnectionCache),
+}
+
+impl MirrorClient {
+    fn new(config: &TpuMirrorConfig) -> std::io::Result<Self> {
+        match config.protocol {
+            Protocol::UDP => {
+                let bind_addr = if config.address.is_ipv4() {
+                    SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+                } else {
+                    SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+                };
+                Ok(Self::Udp(UdpSocket::bind(bind_addr)?))
+            }
+            Protocol::QUIC => Ok(Self::Quic(ConnectionCache::new_quic(
+                "connection_cache_tpu_mirror",
+                1,
+            ))),
+        }
+    }
+
+    fn send(&self, address: &SocketAddr, wire_transactions: Vec<Vec<u8>>) -> bool {
+        match self {
+            Self::Udp(socket) => batch_send(
+                socket,
+                wire_transactions.iter().map(|bytes| (bytes, *address)),
+            )
+            .is_ok(),
+            Self::Quic(connection_cache) => connection_cache
+                .get_connection(address)
+                .send_data_batch_async(wire_transactions)
+                .is_ok(),
+        }
+    }
+}
+
+pub(crate) struct TpuMirrorStage {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl TpuMirrorStage {
+    /// Spawns the stage, which exits once every [`TpuMirrorSender`] is dropped
+    pub(crate) fn new(config: TpuMirrorConfig) -> std::io::Result<(Self, TpuMirrorSender)> {
+        let (sender, receiver) = bounded(MIRROR_CHANNEL_CAPACITY);
+        let dropped_batches = Arc::<AtomicUsize>::default();
+        let client = MirrorClient::new(&config)?;
+        info!(
+            "mirroring {}% of the TPU transactions to {} over {:?}",
+            config.sample_rate * 100.0,
+            config.address,
+            config.protocol,
+        );
+        let thread_hdl = {
+            let dropped_batches = dropped_batches.clone();
+            Builder::new()
+                .name("solTpuMirror".to_string())
+                .spawn(move || Self::run(receiver, client, config, dropped_batches))
+                .unwrap()
+        };
+        Ok((
+            Self { thread_hdl },
+            TpuMirrorSender {
+                sender,
+                dropped_batches,
+            },
+        ))
+    }
+
+    fn run(
+        receiver: Receiver<BankingPacketBatch>,
+        client: MirrorClient,
+        config: TpuMirrorConfig,
+        dropped_batches: Arc<AtomicUsize>,
+    ) {
+        const RECV_TIMEOUT: Duration = Duration::from_secs(1);
+        let mut rng = rand::thread_rng();
+        let mut metrics = TpuMirrorMetrics::default();
+        loop {
+            match receiver.recv_timeout(RECV_TIMEOUT) {
+                Ok(packet_batches) => {
+                    let wire_transactions =
+                        sample_packets(&packet_batches, config.sample_rate, &mut rng);
+                    metrics.received_packets +=
+                        packet_batches.iter().map(|b| b.len()).sum::<usize>();
+                    if !wire_transactions.is_empty() {
+                        let num_packets = wire_transactions.len();
+                        if client.send(&config.address, wire_transactions) {
+                            metrics.mirrored_packets += num_packets;
+                        } else {
+                            metrics.failed_packets += num_packets;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            metrics.maybe_report(&dropped_batches);
+        }
+    }
+
+    pub(crate) fn join(self) -> std::thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+/// Picks the wire bytes of the verified packets to mirror, each with
+/// probability `sample_rate`
+fn sample_packets<R: Rng>(
+    packet_batches: &[PacketBatch],
+    sample_rate: f64,
+    rng: &mut R,
+) -> Vec<Vec<u8>> {
+    packet_batches
+        .iter()
+        .flat_map(|batch| batch.iter())
+        .filter(|packet| !packet.meta().discard())
+        .filter(|_| sample_rate >= 1.0 || rng.gen_bool(sample_rate))
+        .filter_map(|packet| packet.data(..).map(<[u8]>::to_vec))
+        .collect()
+}
+
+struct TpuMirrorMetrics {
+    last_reported: Instant,
+    received_packets: usize,
+    mirrored_packets: usize,
+    failed_packets: usize,
+}
+
+impl Default for TpuMirrorMetrics {
+    fn default() -> Self {
+        Self {
+            last_reported: Instant::now(),
+            received_packets: 0,
+            mirrored_packets: 0,
+            failed_packets: 0,
+        }
+    }
+}
+
+impl TpuMirrorMetrics {
+    fn maybe_report(&mut self, dropped_batches: &AtomicUsize) {
+        const REPORTING_INTERVAL: Duration = Duration::from_secs(1);
+
+        if self.last_reported.elapsed() > REPORTING_INTERVAL {
+            let metrics = std::mem::take(self);
+            datapoint_info!(
+                "tpu_mirror_stage",
+                ("received_packets", metrics.received_packets, i64),
+                ("mirrored_packets", metrics.mirrored_packets, i64),
+                ("failed_packets", metrics.failed_packets, i64),
+                (
+                    "dropped_batches",
+                    dropped_batches.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_perf::packet::{Packet, PinnedPacketBatch},
+    };
+
+    fn new_packet_batch(payloads: &[&[u8]]) -> PacketBatch {
+        PinnedPacketBatch::new(
+            payloads
+                .iter()
+                .map(|payload| Packet::from_data(None, payload).unwrap())
+                .collect(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_sample_packets() {
+        let mut rng = rand::thread_rng();
+        let mut packet_batch = new_packet_batch(&[b"tx0", b"tx1", b"tx2"]);
+        packet_batch
+            .iter_mut()
+            .nth(1)
+            .unwrap()
+            .meta_mut()
+            .set_discard(true);
+        let packet_batches = vec![packet_batch, new_packet_batch(&[b"tx3"])];
+
+        let sampled = sample_packets(&packet_batches, 1.0, &mut rng);
+        let expected: Vec<Vec<u8>> = [&packet_batches[0], &packet_batches[1]]
+            .iter()
+            .flat_map(|batch| batch.iter())
+            .filter(|packet| !packet.meta().discard())
+            .map(|packet| packet.data(..).unwrap().to_vec())
+            .collect();
+        assert_eq!(sampled.len(), 3);
+        assert_eq!(sampled, expected);
+        assert!(sample_packets(&packet_batches, 0.0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_tpu_mirror_stage_udp() {
+        let receiver = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let config = TpuMirrorConfig {
+            address: receiver.local_addr().unwrap(),
+            protocol: Protocol::UDP,
+            sample_rate: 1.0,
+        };
+        let (stage, sender) = TpuMirrorStage::new(config).unwrap();
+        let packet_batch = new_packet_batch(&[b"tx0"]);
+        let expected = packet_batch
+            .iter()
+            .next()
+            .unwrap()
+            .data(..)
+            .unwrap()
+            .to_vec();
+        sender.send(&BankingPacketBatch::new(vec![packet_batch]));
+
+        let mut buffer = [0u8; 1232];
+        let size = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..size], &expected[..]);
+
+        drop(sender);
+        stage.join().unwrap();
+    }
+}
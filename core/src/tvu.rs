@@ -13,9 +13,13 @@ use {
         consensus::{tower_storage::TowerStorage, Tower},
         cost_update_service::CostUpdateService,
         drop_bank_service::DropBankService,
-        repair::repair_service::{OutstandingShredRepairs, RepairInfo, RepairServiceChannels},
+        epoch_precompute_service::EpochPrecomputeService,
+        repair::{
+            repair_service::{OutstandingShredRepairs, RepairInfo, RepairServiceChannels},
+            serve_repair::RepairServedSlots,
+        },
         replay_stage::{ReplayReceivers, ReplaySenders, ReplayStage, ReplayStageConfig},
-        shred_fetch_stage::{ShredFetchStage, SHRED_FETCH_CHANNEL_SIZE},
+        shred_fetch_stage::ShredFetchStage,
         voting_service::VotingService,
         warm_quic_cache_service::WarmQuicCacheService,
         window_service::{WindowService, WindowServiceChannels},
@@ -31,8 +35,13 @@ use {
     },
     solana_keypair::Keypair,
     solana_ledger::{
-        blockstore::Blockstore, blockstore_cleanup_service::BlockstoreCleanupService,
-        blockstore_processor::TransactionStatusSender, entry_notifier_service::EntryNotifierSender,
+        blockstore::Blockstore,
+        blockstore_cleanup_service::{
+            BlockstoreCleanupPauseConfig, BlockstoreCleanupService,
+            TransactionHistoryRetentionConfig,
+        },
+        blockstore_processor::TransactionStatusSender,
+        entry_notifier_service::EntryNotifierSender,
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_poh::{poh_controller::PohController, poh_recorder::PohRecorder},
@@ -42,12 +51,16 @@ use {
         rpc_subscriptions::RpcSubscriptions, slot_status_notifier::SlotStatusNotifier,
     },
     solana_runtime::{
-        bank_forks::BankForks, commitment::BlockCommitmentCache,
-        prioritization_fee_cache::PrioritizationFeeCache, snapshot_controller::SnapshotController,
+        bank_forks::{BankForks, BankForksLimits},
+        commitment::BlockCommitmentCache,
+        prioritization_fee_cache::PrioritizationFeeCache,
+        snapshot_controller::SnapshotController,
         vote_sender_types::ReplayVoteSender,
     },
     solana_streamer::evicting_sender::EvictingSender,
-    solana_turbine::{retransmit_stage::RetransmitStage, xdp::XdpSender},
+    solana_turbine::{
+        retransmit_stage::RetransmitStage, sigverify_shreds::ShredSigverifyConfig, xdp::XdpSender,
+    },
     std::{
         collections::HashSet,
         net::{SocketAddr, UdpSocket},
@@ -79,6 +92,7 @@ pub struct Tvu {
     warm_quic_cache_service: Option<WarmQuicCacheService>,
     drop_bank_service: DropBankService,
     duplicate_shred_listener: DuplicateShredListener,
+    epoch_precompute_service: Option<EpochPrecomputeService>,
 }
 
 pub struct TvuSockets {
@@ -91,30 +105,45 @@ pub struct TvuSockets {
 
 pub struct TvuConfig {
     pub max_ledger_shreds: Option<u64>,
+    pub blockstore_cleanup_pause_config: BlockstoreCleanupPauseConfig,
+    pub transaction_history_retention: TransactionHistoryRetentionConfig,
     pub shred_version: u16,
     // Validators from which repairs are requested
     pub repair_validators: Option<HashSet<Pubkey>>,
     // Validators which should be given priority when serving repairs
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>,
+    // Slots recently served to repair peers, which the ledger cleanup holds off on purging
+    pub repair_served_slots: Arc<RepairServedSlots>,
     pub wait_for_vote_to_start_leader: bool,
     pub replay_forks_threads: NonZeroUsize,
     pub replay_transactions_threads: NonZeroUsize,
     pub shred_sigverify_threads: NonZeroUsize,
+    pub shred_sigverify: ShredSigverifyConfig,
     pub xdp_sender: Option<XdpSender>,
+    pub bank_forks_limits: BankForksLimits,
+    // Slots before an epoch boundary to start computing the turbine tree of the next epoch, and
+    // whether to compute leader schedules ahead of replay at all
+    pub epoch_precompute_slots: Option<Slot>,
 }
 
 impl Default for TvuConfig {
     fn default() -> Self {
         Self {
             max_ledger_shreds: None,
+            blockstore_cleanup_pause_config: BlockstoreCleanupPauseConfig::default(),
+            transaction_history_retention: TransactionHistoryRetentionConfig::default(),
             shred_version: 0,
             repair_validators: None,
             repair_whitelist: Arc::new(RwLock::new(HashSet::default())),
+            repair_served_slots: Arc::default(),
             wait_for_vote_to_start_leader: false,
             replay_forks_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             replay_transactions_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             shred_sigverify_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
+            shred_sigverify: ShredSigverifyConfig::default(),
             xdp_sender: None,
+            bank_forks_limits: BankForksLimits::default(),
+            epoch_precompute_slots: None,
         }
     }
 }
@@ -185,7 +214,8 @@ impl Tvu {
             alpenglow: alpenglow_socket,
         } = sockets;
 
-        let (fetch_sender, fetch_receiver) = EvictingSender::new_bounded(SHRED_FETCH_CHANNEL_SIZE);
+        let (fetch_sender, fetch_receiver) =
+            EvictingSender::new_bounded(tvu_config.shred_sigverify.queue_depth.get());
 
         let repair_socket = Arc::new(repair_socket);
         let ancestor_hashes_socket = Arc::new(ancestor_hashes_socket);
@@ -196,6 +226,7 @@ impl Tvu {
             repair_response_quic_receiver,
             repair_socket.clone(),
             fetch_sender,
+            tvu_config.shred_sigverify.queue_depth.get(),
             tvu_config.shred_version,
             bank_forks.clone(),
             cluster_info.clone(),
@@ -217,6 +248,7 @@ impl Tvu {
             retransmit_sender.clone(),
             verified_sender,
             tvu_config.shred_sigverify_threads,
+            tvu_config.shred_sigverify.clone(),
         );
 
         let retransmit_stage = RetransmitStage::new(
@@ -348,6 +380,7 @@ impl Tvu {
             prioritization_fee_cache: prioritization_fee_cache.clone(),
             banking_tracer,
             snapshot_controller,
+            bank_forks_limits: tvu_config.bank_forks_limits,
         };
 
         let voting_service = VotingService::new(
@@ -368,6 +401,17 @@ impl Tvu {
             &exit,
         );
 
+        let epoch_precompute_service = tvu_config.epoch_precompute_slots.map(|precompute_slots| {
+            EpochPrecomputeService::new(
+                precompute_slots,
+                bank_forks.clone(),
+                leader_schedule_cache.clone(),
+                retransmit_stage.cluster_nodes_cache().clone(),
+                cluster_info.clone(),
+                exit.clone(),
+            )
+        });
+
         let cost_update_service = CostUpdateService::new(cost_update_receiver);
 
         let drop_bank_service = DropBankService::new(drop_bank_receiver);
@@ -383,7 +427,20 @@ impl Tvu {
         };
 
         let blockstore_cleanup_service = tvu_config.max_ledger_shreds.map(|max_ledger_shreds| {
-            BlockstoreCleanupService::new(blockstore.clone(), max_ledger_shreds, exit.clone())
+            let leader_schedule_cache = leader_schedule_cache.clone();
+            let cluster_info = cluster_info.clone();
+            let repair_served_slots = tvu_config.repair_served_slots.clone();
+            BlockstoreCleanupService::new(
+                blockstore.clone(),
+                max_ledger_shreds,
+                tvu_config.blockstore_cleanup_pause_config,
+                tvu_config.transaction_history_retention,
+                move |slot| {
+                    leader_schedule_cache.slot_leader_at(slot, None) == Some(cluster_info.id())
+                },
+                move |max_age| repair_served_slots.lowest_served_slot(max_age),
+                exit.clone(),
+            )
         });
 
         let duplicate_shred_listener = DuplicateShredListener::new(
@@ -411,6 +468,7 @@ impl Tvu {
             warm_quic_cache_service,
             drop_bank_service,
             duplicate_shred_listener,
+            epoch_precompute_service,
         })
     }
 
@@ -433,6 +491,9 @@ impl Tvu {
         }
         self.drop_bank_service.join()?;
         self.duplicate_shred_listener.join()?;
+        if let Some(epoch_precompute_service) = self.epoch_precompute_service {
+            epoch_precompute_service.join()?;
+        }
         Ok(())
     }
 }
@@ -5,6 +5,7 @@ use {
     crate::{
         admin_rpc_post_init::{AdminRpcRequestMetadataPostInit, KeyUpdaterType, KeyUpdaters},
         banking_stage::{
+            reserved_capacity::ReservedCapacity,
             transaction_scheduler::scheduler_controller::SchedulerConfig, BankingStage,
         },
         banking_trace::{self, BankingTracer, TraceError},
@@ -15,10 +16,15 @@ use {
             tower_storage::{NullTowerStorage, TowerStorage},
             ExternalRootSource, Tower,
         },
+        epoch_precompute_service::DEFAULT_EPOCH_PRECOMPUTE_SLOTS,
+        forwarding_stage::forwarding_deny::{ForwardingDeny, ForwardingDenyConfig},
+        known_validators_service::{KnownValidatorsFromStake, KnownValidatorsService},
+        poh_calibration::PohCalibration,
         repair::{
             self,
             quic_endpoint::{RepairQuicAsyncSenders, RepairQuicSenders, RepairQuicSockets},
             repair_handler::RepairHandlerType,
+            serve_repair::ServeRepairConfig,
             serve_repair_service::ServeRepairService,
         },
         resource_limits::{
@@ -32,11 +38,12 @@ use {
             verify_net_stats_access, SystemMonitorService, SystemMonitorStatsReportConfig,
         },
         tpu::{ForwardingClientOption, Tpu, TpuSockets},
+        tpu_mirror_stage::TpuMirrorConfig,
         tvu::{Tvu, TvuConfig, TvuSockets},
     },
     agave_snapshots::{
-        snapshot_archive_info::SnapshotArchiveInfoGetter as _, snapshot_config::SnapshotConfig,
-        snapshot_hash::StartingSnapshotHashes, SnapshotInterval,
+        paths as snapshot_paths, snapshot_archive_info::SnapshotArchiveInfoGetter as _,
+        snapshot_config::SnapshotConfig, snapshot_hash::StartingSnapshotHashes, SnapshotInterval,
     },
     anyhow::{anyhow, Context, Result},
     crossbeam_channel::{bounded, unbounded, Receiver},
@@ -54,7 +61,6 @@ use {
     },
     solana_clock::Slot,
     solana_cluster_type::ClusterType,
-    solana_entry::poh::compute_hash_time,
     solana_epoch_schedule::MAX_LEADER_SCHEDULE_EPOCH_OFFSET,
     solana_genesis_config::GenesisConfig,
     solana_genesis_utils::{
@@ -68,10 +74,11 @@ use {
             ClusterInfo, DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
             DEFAULT_CONTACT_SAVE_INTERVAL_MILLIS,
         },
-        contact_info::ContactInfo,
+        contact_info::{ContactInfo, CAPABILITY_ENCRYPTED_SHRED_TRANSPORT},
         crds_gossip_pull::CRDS_GOSSIP_PULL_CRDS_TIMEOUT_MS,
         gossip_service::GossipService,
         node::{Node, NodeMultihoming},
+        peer_preference::PeerPreference,
     },
     solana_hard_forks::HardForks,
     solana_hash::Hash,
@@ -82,6 +89,9 @@ use {
             Blockstore, BlockstoreError, PurgeType, MAX_COMPLETED_SLOTS_IN_CHANNEL,
             MAX_REPLAY_WAKE_UP_SIGNALS,
         },
+        blockstore_cleanup_service::{
+            BlockstoreCleanupPauseConfig, TransactionHistoryRetentionConfig,
+        },
         blockstore_metric_report_service::BlockstoreMetricReportService,
         blockstore_options::{BlockstoreOptions, BLOCKSTORE_DIRECTORY_ROCKS_LEVEL},
         blockstore_processor::{self, TransactionStatusSender},
@@ -97,6 +107,7 @@ use {
         poh_controller::PohController,
         poh_recorder::PohRecorder,
         poh_service::{self, PohService},
+        poh_timing_report::PohAlertThresholds,
         record_channels::record_channels,
         transaction_recorder::TransactionRecorder,
     },
@@ -114,7 +125,7 @@ use {
         rpc_service::{JsonRpcService, JsonRpcServiceConfig},
         rpc_subscriptions::RpcSubscriptions,
         transaction_notifier_interface::TransactionNotifierArc,
-        transaction_status_service::TransactionStatusService,
+        transaction_status_service::{TransactionHistorySwitch, TransactionStatusService},
     },
     solana_runtime::{
         accounts_background_service::{
@@ -122,7 +133,8 @@ use {
             PendingSnapshotPackages, PrunedBanksRequestHandler, SnapshotRequestHandler,
         },
         bank::Bank,
-        bank_forks::BankForks,
+        bank_forks::{BankForks, BankForksLimits},
+        banking_queue_snapshot::SharedBankingQueueSnapshot,
         commitment::BlockCommitmentCache,
         dependency_tracker::DependencyTracker,
         prioritization_fee_cache::PrioritizationFeeCache,
@@ -147,12 +159,16 @@ use {
     solana_turbine::{
         self,
         broadcast_stage::BroadcastStageType,
+        sigverify_shreds::ShredSigverifyConfig,
         xdp::{master_ip_if_bonded, XdpConfig, XdpRetransmitter},
     },
     solana_unified_scheduler_pool::DefaultSchedulerPool,
     solana_validator_exit::Exit,
     solana_vote_program::vote_state::VoteStateV4,
-    solana_wen_restart::wen_restart::{wait_for_wen_restart, WenRestartConfig},
+    solana_wen_restart::{
+        cluster_halt_detector::{ClusterHaltDetector, ClusterHaltStatus},
+        wen_restart::{wait_for_wen_restart, WenRestartConfig},
+    },
     std::{
         borrow::Cow,
         collections::{HashMap, HashSet},
@@ -174,6 +190,9 @@ use {
     tokio_util::sync::CancellationToken,
 };
 
+/// Name of the exit backpressure flag that is raised until the exit snapshot has been created
+pub const EXIT_SNAPSHOT_NAME: &str = "ExitSnapshot";
+
 const MAX_COMPLETED_DATA_SETS_IN_CHANNEL: usize = 100_000;
 const WAIT_FOR_SUPERMAJORITY_THRESHOLD_PERCENT: u64 = 80;
 // Right now since we reuse the wait for supermajority code, the
@@ -322,7 +341,12 @@ pub struct ValidatorConfig {
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
     pub snapshot_config: SnapshotConfig,
+    /// On graceful exit, archive the root bank if the latest snapshot archive is more than this
+    /// many slots behind it
+    pub exit_snapshot_max_age_slots: Option<Slot>,
     pub max_ledger_shreds: Option<u64>,
+    pub blockstore_cleanup_pause_config: BlockstoreCleanupPauseConfig,
+    pub transaction_history_retention: TransactionHistoryRetentionConfig,
     pub blockstore_options: BlockstoreOptions,
     pub broadcast_stage_type: BroadcastStageType,
     pub turbine_disabled: Arc<AtomicBool>,
@@ -330,9 +354,13 @@ pub struct ValidatorConfig {
     pub wait_for_supermajority: Option<Slot>,
     pub new_hard_forks: Option<Vec<Slot>>,
     pub known_validators: Option<HashSet<Pubkey>>, // None = trust all
+    /// Also trust the validators holding enough of the stake, rederived at every epoch
+    pub known_validators_from_stake: Option<KnownValidatorsFromStake>,
     pub repair_validators: Option<HashSet<Pubkey>>, // None = repair from all
     pub repair_whitelist: Arc<RwLock<HashSet<Pubkey>>>, // Empty = repair with all
     pub gossip_validators: Option<HashSet<Pubkey>>, // None = gossip with all
+    /// How gossip entrypoints and repair peers are picked among the eligible peers
+    pub peer_preference: PeerPreference,
     pub max_genesis_archive_unpacked_size: u64,
     /// Run PoH, transaction signature and other transaction verifications during blockstore
     /// processing.
@@ -351,6 +379,7 @@ pub struct ValidatorConfig {
     pub enforce_ulimit_nofile: bool,
     pub poh_pinned_cpu_core: usize,
     pub poh_hashes_per_batch: u64,
+    pub poh_alert_thresholds: PohAlertThresholds,
     pub process_ledger_before_services: bool,
     pub accounts_db_config: AccountsDbConfig,
     pub warp_slot: Option<Slot>,
@@ -367,22 +396,38 @@ pub struct ValidatorConfig {
     pub block_production_method: BlockProductionMethod,
     pub block_production_num_workers: NonZeroUsize,
     pub block_production_scheduler_config: SchedulerConfig,
+    /// Block compute units and banking workers reserved for votes and critical programs
+    pub block_production_reserved_capacity: ReservedCapacity,
     pub enable_block_production_forwarding: bool,
     pub enable_scheduler_bindings: bool,
     pub generator_config: Option<GeneratorConfig>,
     pub use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup,
     pub wen_restart_proto_path: Option<PathBuf>,
     pub wen_restart_coordinator: Option<Pubkey>,
+    /// Report the cluster as halted when the root has not advanced for this long
+    pub cluster_halt_detection_timeout: Option<Duration>,
     pub unified_scheduler_handler_threads: Option<usize>,
     pub ip_echo_server_threads: NonZeroUsize,
     pub rayon_global_threads: NonZeroUsize,
     pub replay_forks_threads: NonZeroUsize,
     pub replay_transactions_threads: NonZeroUsize,
     pub tvu_shred_sigverify_threads: NonZeroUsize,
+    pub tvu_shred_sigverify: ShredSigverifyConfig,
     pub delay_leader_block_for_pending_fork: bool,
+    /// Limits on the unrooted banks replay keeps
+    pub bank_forks_limits: BankForksLimits,
+    /// Slots before an epoch boundary to start preparing for the next epoch, or `None` to leave
+    /// it to replay and retransmit
+    pub epoch_precompute_slots: Option<Slot>,
     pub use_tpu_client_next: bool,
     pub retransmit_xdp: Option<XdpConfig>,
     pub repair_handler_type: RepairHandlerType,
+    pub serve_repair_config: ServeRepairConfig,
+    /// Send a copy of the verified TPU transactions to a secondary destination
+    pub tpu_mirror_config: Option<TpuMirrorConfig>,
+    /// Stop forwarding transactions while the node is delinquent or its vote account names
+    /// another identity
+    pub forwarding_deny_config: Option<ForwardingDenyConfig>,
 }
 
 impl ValidatorConfig {
@@ -397,6 +442,8 @@ impl ValidatorConfig {
             expected_shred_version: None,
             voting_disabled: false,
             max_ledger_shreds: None,
+            blockstore_cleanup_pause_config: BlockstoreCleanupPauseConfig::default(),
+            transaction_history_retention: TransactionHistoryRetentionConfig::default(),
             blockstore_options: BlockstoreOptions::default_for_tests(),
             account_paths: Vec::new(),
             account_snapshot_paths: Vec::new(),
@@ -406,15 +453,18 @@ impl ValidatorConfig {
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
             snapshot_config: SnapshotConfig::new_load_only(),
+            exit_snapshot_max_age_slots: None,
             broadcast_stage_type: BroadcastStageType::Standard,
             turbine_disabled: Arc::<AtomicBool>::default(),
             fixed_leader_schedule: None,
             wait_for_supermajority: None,
             new_hard_forks: None,
             known_validators: None,
+            known_validators_from_stake: None,
             repair_validators: None,
             repair_whitelist: Arc::new(RwLock::new(HashSet::default())),
             gossip_validators: None,
+            peer_preference: PeerPreference::default(),
             max_genesis_archive_unpacked_size: MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
             run_verification: true,
             require_tower: false,
@@ -432,6 +482,7 @@ impl ValidatorConfig {
             enforce_ulimit_nofile: false,
             poh_pinned_cpu_core: poh_service::DEFAULT_PINNED_CPU_CORE,
             poh_hashes_per_batch: poh_service::DEFAULT_HASHES_PER_BATCH,
+            poh_alert_thresholds: PohAlertThresholds::default(),
             process_ledger_before_services: false,
             warp_slot: None,
             accounts_db_skip_shrink: false,
@@ -448,6 +499,7 @@ impl ValidatorConfig {
             block_production_method: BlockProductionMethod::default(),
             block_production_num_workers: BankingStage::default_num_workers(),
             block_production_scheduler_config: SchedulerConfig::default(),
+            block_production_reserved_capacity: ReservedCapacity::default(),
             // enable forwarding by default for tests
             enable_block_production_forwarding: true,
             enable_scheduler_bindings: false,
@@ -455,6 +507,7 @@ impl ValidatorConfig {
             use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup::default(),
             wen_restart_proto_path: None,
             wen_restart_coordinator: None,
+            cluster_halt_detection_timeout: None,
             unified_scheduler_handler_threads: None,
             ip_echo_server_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             rayon_global_threads: max_thread_count,
@@ -462,10 +515,16 @@ impl ValidatorConfig {
             replay_transactions_threads: max_thread_count,
             tvu_shred_sigverify_threads: NonZeroUsize::new(get_thread_count())
                 .expect("thread count is non-zero"),
+            tvu_shred_sigverify: ShredSigverifyConfig::default(),
             delay_leader_block_for_pending_fork: false,
+            bank_forks_limits: BankForksLimits::default(),
+            epoch_precompute_slots: Some(DEFAULT_EPOCH_PRECOMPUTE_SLOTS),
             use_tpu_client_next: true,
             retransmit_xdp: None,
             repair_handler_type: RepairHandlerType::default(),
+            serve_repair_config: ServeRepairConfig::default(),
+            tpu_mirror_config: None,
+            forwarding_deny_config: None,
         }
     }
 
@@ -550,6 +609,7 @@ impl BlockstoreRootScan {
 struct TransactionHistoryServices {
     transaction_status_sender: Option<TransactionStatusSender>,
     transaction_status_service: Option<TransactionStatusService>,
+    transaction_history: Option<Arc<TransactionHistorySwitch>>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
 }
 
@@ -623,6 +683,8 @@ pub struct Validator {
     transaction_status_service: Option<TransactionStatusService>,
     entry_notifier_service: Option<EntryNotifierService>,
     system_monitor_service: Option<SystemMonitorService>,
+    cluster_halt_detector: Option<ClusterHaltDetector>,
+    known_validators_service: Option<KnownValidatorsService>,
     sample_performance_service: Option<SamplePerformanceService>,
     stats_reporter_service: StatsReporterService,
     gossip_service: GossipService,
@@ -647,6 +709,7 @@ pub struct Validator {
     repair_quic_endpoints_runtime: Option<TokioRuntime>,
     repair_quic_endpoints_join_handle: Option<repair::quic_endpoint::AsyncTryJoinHandle>,
     xdp_retransmitter: Option<XdpRetransmitter>,
+    exit_snapshot: Option<ExitSnapshot>,
     // This runtime is used to run the client owned by SendTransactionService.
     // We don't wait for its JoinHandle here because ownership and shutdown
     // are managed elsewhere. This variable is intentionally unused.
@@ -866,6 +929,7 @@ impl Validator {
             TransactionHistoryServices {
                 transaction_status_sender,
                 transaction_status_service,
+                transaction_history,
                 max_complete_transaction_status_slot,
             },
             blockstore_process_options,
@@ -889,7 +953,12 @@ impl Validator {
         .map_err(ValidatorError::Other)?;
 
         if !config.no_poh_speed_test {
-            check_poh_speed(&bank_forks.read().unwrap().root_bank(), None)?;
+            check_poh_speed(
+                &bank_forks.read().unwrap().root_bank(),
+                None,
+                config.poh_pinned_cpu_core,
+                Some(ledger_path),
+            )?;
         }
 
         let (root_slot, hard_forks) = {
@@ -936,6 +1005,10 @@ impl Validator {
             socket_addr_space,
         );
         cluster_info.set_contact_debug_interval(config.contact_debug_interval);
+        cluster_info.set_peer_preference(config.peer_preference);
+        if let Some(known_validators) = &config.known_validators {
+            cluster_info.set_region_peers(known_validators.clone());
+        }
         cluster_info.set_entrypoints(cluster_entrypoints);
         cluster_info.restore_contact_info(ledger_path, config.contact_save_interval);
         cluster_info.set_bind_ip_addrs(node.bind_ip_addrs.clone());
@@ -1001,6 +1074,10 @@ impl Validator {
         // block min prioritization fee cache should be readable by RPC, and writable by validator
         // (by both replay stage and banking stage)
         let prioritization_fee_cache = Arc::new(PrioritizationFeeCache::default());
+        // snapshots of the banking stage queue, taken only if RPC previews transaction priorities
+        let banking_queue_snapshot = (config.rpc_addrs.is_some()
+            && config.rpc_config.enable_transaction_priority_preview)
+            .then(Arc::<SharedBankingQueueSnapshot>::default);
 
         let leader_schedule_cache = Arc::new(leader_schedule_cache);
         let (poh_recorder, entry_receiver) = {
@@ -1265,6 +1342,7 @@ impl Validator {
                 leader_schedule_cache: leader_schedule_cache.clone(),
                 max_complete_transaction_status_slot: max_complete_transaction_status_slot.clone(),
                 prioritization_fee_cache: prioritization_fee_cache.clone(),
+                banking_queue_snapshot: banking_queue_snapshot.clone(),
                 client_option,
             };
             let json_rpc_service =
@@ -1406,6 +1484,7 @@ impl Validator {
             bank_forks.read().unwrap().sharable_banks(),
             config.repair_whitelist.clone(),
         );
+        let repair_served_slots = serve_repair.repair_served_slots();
         let (repair_request_quic_sender, repair_request_quic_receiver) = unbounded();
         let (repair_response_quic_sender, repair_response_quic_receiver) = unbounded();
         let (ancestor_hashes_response_quic_sender, ancestor_hashes_response_quic_receiver) =
@@ -1435,6 +1514,7 @@ impl Validator {
             config.poh_hashes_per_batch,
             record_receiver,
             poh_service_message_receiver,
+            config.poh_alert_thresholds,
         );
         assert_eq!(
             blockstore.get_new_shred_signals_len(),
@@ -1453,9 +1533,14 @@ impl Validator {
             .as_ref()
             .map(|service| service.sender_cloned());
 
-        let turbine_quic_endpoint_runtime = (current_runtime_handle.is_err()
-            && genesis_config.cluster_type != ClusterType::MainnetBeta)
-            .then(|| {
+        // Nodes which take repair and turbine traffic encrypted need the QUIC endpoints on any
+        // cluster
+        let quic_shred_endpoints = genesis_config.cluster_type != ClusterType::MainnetBeta
+            || node
+                .info
+                .has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+        let turbine_quic_endpoint_runtime =
+            (current_runtime_handle.is_err() && quic_shred_endpoints).then(|| {
                 tokio::runtime::Builder::new_multi_thread()
                     .enable_all()
                     .thread_name("solTurbineQuic")
@@ -1467,7 +1552,7 @@ impl Validator {
             turbine_quic_endpoint,
             turbine_quic_endpoint_sender,
             turbine_quic_endpoint_join_handle,
-        ) = if genesis_config.cluster_type == ClusterType::MainnetBeta {
+        ) = if !quic_shred_endpoints {
             let (sender, _receiver) = tokio::sync::mpsc::channel(1);
             (None, sender, None)
         } else {
@@ -1480,15 +1565,15 @@ impl Validator {
                 node.sockets.tvu_quic,
                 turbine_quic_endpoint_sender,
                 bank_forks.clone(),
+                node.info.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT),
             )
             .map(|(endpoint, sender, join_handle)| (Some(endpoint), sender, Some(join_handle)))
             .unwrap()
         };
 
         // Repair quic endpoint.
-        let repair_quic_endpoints_runtime = (current_runtime_handle.is_err()
-            && genesis_config.cluster_type != ClusterType::MainnetBeta)
-            .then(|| {
+        let repair_quic_endpoints_runtime =
+            (current_runtime_handle.is_err() && quic_shred_endpoints).then(|| {
                 tokio::runtime::Builder::new_multi_thread()
                     .enable_all()
                     .thread_name("solRepairQuic")
@@ -1496,7 +1581,7 @@ impl Validator {
                     .unwrap()
             });
         let (repair_quic_endpoints, repair_quic_async_senders, repair_quic_endpoints_join_handle) =
-            if genesis_config.cluster_type == ClusterType::MainnetBeta {
+            if !quic_shred_endpoints {
                 (None, RepairQuicAsyncSenders::new_dummy(), None)
             } else {
                 let repair_quic_sockets = RepairQuicSockets {
@@ -1526,6 +1611,7 @@ impl Validator {
             };
         let serve_repair_service = ServeRepairService::new(
             serve_repair,
+            config.serve_repair_config,
             // Incoming UDP repair requests are adapted into RemoteRequest
             // and also sent through the same channel.
             repair_request_quic_sender,
@@ -1637,14 +1723,20 @@ impl Validator {
             duplicate_confirmed_slots_receiver,
             TvuConfig {
                 max_ledger_shreds: config.max_ledger_shreds,
+                blockstore_cleanup_pause_config: config.blockstore_cleanup_pause_config.clone(),
+                transaction_history_retention: config.transaction_history_retention.clone(),
                 shred_version: node.info.shred_version(),
                 repair_validators: config.repair_validators.clone(),
                 repair_whitelist: config.repair_whitelist.clone(),
+                repair_served_slots,
                 wait_for_vote_to_start_leader,
                 replay_forks_threads: config.replay_forks_threads,
                 replay_transactions_threads: config.replay_transactions_threads,
                 shred_sigverify_threads: config.tvu_shred_sigverify_threads,
+                shred_sigverify: config.tvu_shred_sigverify.clone(),
                 xdp_sender: xdp_sender.clone(),
+                bank_forks_limits: config.bank_forks_limits,
+                epoch_precompute_slots: config.epoch_precompute_slots,
             },
             &max_slots,
             block_metadata_notifier,
@@ -1688,6 +1780,36 @@ impl Validator {
             return Err(ValidatorError::WenRestartFinished.into());
         }
 
+        let cluster_halt_status = Arc::<ClusterHaltStatus>::default();
+        let cluster_halt_detector = config.cluster_halt_detection_timeout.map(|halt_timeout| {
+            ClusterHaltDetector::new(
+                bank_forks.clone(),
+                cluster_info.clone(),
+                halt_timeout,
+                cluster_halt_status.clone(),
+                exit.clone(),
+            )
+        });
+
+        let known_validators_service =
+            config
+                .known_validators_from_stake
+                .as_ref()
+                .map(|known_validators_from_stake| {
+                    KnownValidatorsService::new(
+                        known_validators_from_stake.min_stake_percent,
+                        known_validators_from_stake.static_known_validators.clone(),
+                        config
+                            .rpc_config
+                            .known_validators
+                            .clone()
+                            .unwrap_or_default(),
+                        bank_forks.clone(),
+                        ledger_path.to_path_buf(),
+                        exit.clone(),
+                    )
+                });
+
         let key_notifiers = Arc::new(RwLock::new(KeyUpdaters::default()));
         let forwarding_tpu_client = if let Some(connection_cache) = &connection_cache {
             ForwardingClientOption::ConnectionCache(connection_cache.clone())
@@ -1751,9 +1873,11 @@ impl Validator {
             tpu_fwd_quic_server_config,
             vote_quic_server_config,
             &prioritization_fee_cache,
+            banking_queue_snapshot,
             config.block_production_method.clone(),
             config.block_production_num_workers,
             config.block_production_scheduler_config.clone(),
+            config.block_production_reserved_capacity.clone(),
             config.enable_block_production_forwarding,
             config.generator_config.clone(),
             key_notifiers.clone(),
@@ -1764,6 +1888,13 @@ impl Validator {
                     banking_control_sender.clone(),
                 )
             }),
+            config.tpu_mirror_config.clone(),
+            config
+                .forwarding_deny_config
+                .filter(|_| !config.voting_disabled)
+                .map(|forwarding_deny_config| {
+                    ForwardingDeny::new(forwarding_deny_config, *vote_account, cluster_info.clone())
+                }),
             cancel,
         );
 
@@ -1789,6 +1920,24 @@ impl Validator {
             // because it is added inside Tpu.
         }
 
+        let exit_snapshot = config
+            .exit_snapshot_max_age_slots
+            .filter(|_| config.snapshot_config.should_generate_snapshots())
+            .map(|max_age_slots| {
+                let exit_backpressure = config
+                    .validator_exit_backpressure
+                    .get(EXIT_SNAPSHOT_NAME)
+                    .cloned();
+                if let Some(exit_backpressure) = &exit_backpressure {
+                    exit_backpressure.store(true, Ordering::Relaxed);
+                }
+                ExitSnapshot {
+                    snapshot_config: config.snapshot_config.clone(),
+                    max_age_slots,
+                    exit_backpressure,
+                }
+            });
+
         *admin_rpc_service_post_init.write().unwrap() = Some(AdminRpcRequestMetadataPostInit {
             bank_forks: bank_forks.clone(),
             cluster_info: cluster_info.clone(),
@@ -1800,6 +1949,21 @@ impl Validator {
             cluster_slots,
             node: Some(node_multihoming),
             banking_control_sender,
+            write_lock_contention_report: tpu.write_lock_contention_report().clone(),
+            in_flight_batches_report: tpu.in_flight_batches_report().clone(),
+            leader_schedule_cache: leader_schedule_cache.clone(),
+            poh_timing_report: poh_service.timing_report().clone(),
+            cluster_halt_status,
+            // Only the RPC transaction history can be switched, not the one kept for plugins
+            transaction_history: transaction_history.filter(|_| {
+                config.rpc_addrs.is_some() && config.rpc_config.enable_rpc_transaction_history
+            }),
+            rpc_usage: json_rpc_service
+                .as_ref()
+                .and_then(|json_rpc_service| json_rpc_service.usage_tracker()),
+            rpc_thread_pools: json_rpc_service
+                .as_ref()
+                .map(|json_rpc_service| json_rpc_service.thread_pool_resizer()),
         });
 
         Ok(Self {
@@ -1813,6 +1977,8 @@ impl Validator {
             transaction_status_service,
             entry_notifier_service,
             system_monitor_service,
+            cluster_halt_detector,
+            known_validators_service,
             sample_performance_service,
             snapshot_packager_service,
             completed_data_sets_service,
@@ -1835,6 +2001,7 @@ impl Validator {
             repair_quic_endpoints_runtime,
             repair_quic_endpoints_join_handle,
             xdp_retransmitter,
+            exit_snapshot,
             _tpu_client_next_runtime: tpu_client_next_runtime,
         })
     }
@@ -1878,6 +2045,9 @@ impl Validator {
     }
 
     pub fn join(self) {
+        // the exit snapshot is taken after every service has stopped, until then only keep the
+        // banks alive if it is needed
+        let exit_snapshot_bank_forks = self.exit_snapshot.as_ref().map(|_| self.bank_forks.clone());
         drop(self.bank_forks);
         drop(self.cluster_info);
 
@@ -1918,6 +2088,16 @@ impl Validator {
                 .expect("system_monitor_service");
         }
 
+        if let Some(cluster_halt_detector) = self.cluster_halt_detector {
+            cluster_halt_detector.join().expect("cluster_halt_detector");
+        }
+
+        if let Some(known_validators_service) = self.known_validators_service {
+            known_validators_service
+                .join()
+                .expect("known_validators_service");
+        }
+
         if let Some(sample_performance_service) = self.sample_performance_service {
             sample_performance_service
                 .join()
@@ -1971,6 +2151,18 @@ impl Validator {
                 .transpose()
                 .unwrap();
         }
+        if let (Some(exit_snapshot), Some(bank_forks)) =
+            (&self.exit_snapshot, exit_snapshot_bank_forks)
+        {
+            create_exit_snapshot(
+                &bank_forks,
+                &exit_snapshot.snapshot_config,
+                exit_snapshot.max_age_slots,
+            );
+            if let Some(exit_backpressure) = &exit_snapshot.exit_backpressure {
+                exit_backpressure.store(false, Ordering::Relaxed);
+            }
+        }
         if let Some(completed_data_sets_service) = self.completed_data_sets_service {
             completed_data_sets_service
                 .join()
@@ -1986,6 +2178,86 @@ impl Validator {
     }
 }
 
+struct ExitSnapshot {
+    snapshot_config: SnapshotConfig,
+    max_age_slots: Slot,
+    exit_backpressure: Option<Arc<AtomicBool>>,
+}
+
+/// Archives the root bank if the latest snapshot archive is more than `max_age_slots` behind it
+///
+/// The snapshot is taken on the calling thread, so this must only be called once the accounts
+/// background service and the snapshot packager have stopped.
+fn create_exit_snapshot(
+    bank_forks: &RwLock<BankForks>,
+    snapshot_config: &SnapshotConfig,
+    max_age_slots: Slot,
+) {
+    let root_bank = bank_forks.read().unwrap().root_bank();
+    let full_snapshot_slot = snapshot_paths::get_highest_full_snapshot_archive_slot(
+        &snapshot_config.full_snapshot_archives_dir,
+    );
+    let latest_snapshot_slot = full_snapshot_slot.map(|full_snapshot_slot| {
+        snapshot_paths::get_highest_incremental_snapshot_archive_slot(
+            &snapshot_config.incremental_snapshot_archives_dir,
+            full_snapshot_slot,
+        )
+        .unwrap_or(full_snapshot_slot)
+    });
+    if let Some(latest_snapshot_slot) = latest_snapshot_slot {
+        if root_bank.slot().saturating_sub(latest_snapshot_slot) <= max_age_slots {
+            info!(
+                "Skipping exit snapshot, latest snapshot at slot {latest_snapshot_slot} is recent \
+                 enough for root slot {}",
+                root_bank.slot()
+            );
+            return;
+        }
+    }
+
+    info!("Creating exit snapshot at slot {}", root_bank.slot());
+    let mut timer = Measure::start("exit snapshot");
+    let result = match full_snapshot_slot {
+        Some(full_snapshot_slot)
+            if snapshot_config.incremental_snapshot_archive_interval
+                != SnapshotInterval::Disabled =>
+        {
+            snapshot_bank_utils::bank_to_incremental_snapshot_archive(
+                &snapshot_config.bank_snapshots_dir,
+                &root_bank,
+                full_snapshot_slot,
+                Some(snapshot_config.snapshot_version),
+                &snapshot_config.full_snapshot_archives_dir,
+                &snapshot_config.incremental_snapshot_archives_dir,
+                snapshot_config.archive_format,
+            )
+            .map(|archive_info| archive_info.path().clone())
+        }
+        _ => snapshot_bank_utils::bank_to_full_snapshot_archive(
+            &snapshot_config.bank_snapshots_dir,
+            &root_bank,
+            Some(snapshot_config.snapshot_version),
+            &snapshot_config.full_snapshot_archives_dir,
+            &snapshot_config.incremental_snapshot_archives_dir,
+            snapshot_config.archive_format,
+        )
+        .map(|archive_info| archive_info.path().clone()),
+    };
+    timer.stop();
+    match result {
+        Ok(path) => {
+            info!("Created exit snapshot {} {timer}", path.display());
+            snapshot_utils::purge_old_snapshot_archives(
+                &snapshot_config.full_snapshot_archives_dir,
+                &snapshot_config.incremental_snapshot_archives_dir,
+                snapshot_config.maximum_full_snapshot_archives_to_retain,
+                snapshot_config.maximum_incremental_snapshot_archives_to_retain,
+            );
+        }
+        Err(err) => warn!("Unable to create exit snapshot: {err}"),
+    }
+}
+
 fn active_vote_account_exists_in_bank(bank: &Bank, vote_account: &Pubkey) -> bool {
     if let Some(account) = &bank.get_account(vote_account) {
         if let Ok(vote_state) = VoteStateV4::deserialize(account.data(), vote_account) {
@@ -1995,7 +2267,18 @@ fn active_vote_account_exists_in_bank(bank: &Bank, vote_account: &Pubkey) -> boo
     false
 }
 
-fn check_poh_speed(bank: &Bank, maybe_hash_samples: Option<u64>) -> Result<(), ValidatorError> {
+/// Checks that this host hashes fast enough to keep up with the cluster's PoH rate
+///
+/// The hash rate is measured on `pinned_cpu_core`, where the PoH service will run. If
+/// `ledger_path` holds a passing calibration from a previous start under the same conditions, it
+/// is trusted and a fresh measurement is taken in the background instead, which replaces the
+/// stored calibration and reports if the host has become too slow.
+fn check_poh_speed(
+    bank: &Bank,
+    maybe_hash_samples: Option<u64>,
+    pinned_cpu_core: usize,
+    ledger_path: Option<&Path>,
+) -> Result<(), ValidatorError> {
     let Some(hashes_per_tick) = bank.hashes_per_tick() else {
         warn!("Unable to read hashes per tick from Bank, skipping PoH speed check");
         return Ok(());
@@ -2005,13 +2288,45 @@ fn check_poh_speed(bank: &Bank, maybe_hash_samples: Option<u64>) -> Result<(), V
     let hashes_per_slot = hashes_per_tick * ticks_per_slot;
     let hash_samples = maybe_hash_samples.unwrap_or(hashes_per_slot);
 
-    let hash_time = compute_hash_time(hash_samples);
-    let my_hashes_per_second = (hash_samples as f64 / hash_time.as_secs_f64()) as u64;
-
     let target_slot_duration = Duration::from_nanos(bank.ns_per_slot as u64);
     let target_hashes_per_second =
         (hashes_per_slot as f64 / target_slot_duration.as_secs_f64()) as u64;
 
+    let persisted_calibration = ledger_path
+        .and_then(PohCalibration::load)
+        .filter(|calibration| calibration.is_applicable(pinned_cpu_core, hash_samples))
+        .filter(|calibration| calibration.hashes_per_second >= target_hashes_per_second);
+    if let (Some(calibration), Some(ledger_path)) = (persisted_calibration, ledger_path) {
+        info!(
+            "PoH speed check: persisted hashes per second {}, target hashes per second \
+             {target_hashes_per_second}, re-validating in the background",
+            calibration.hashes_per_second,
+        );
+        calibration.revalidate_in_background(ledger_path.to_path_buf(), move |calibration| {
+            let mine = calibration.hashes_per_second;
+            if mine < target_hashes_per_second {
+                error!(
+                    "PoH speed re-validation: computed hashes per second {mine} is below the \
+                     target hashes per second {target_hashes_per_second}"
+                );
+                datapoint_error!(
+                    "poh_speed_revalidation_failed",
+                    ("mine", mine, i64),
+                    ("target", target_hashes_per_second, i64),
+                );
+            }
+        });
+        return Ok(());
+    }
+
+    let calibration = PohCalibration::measure(pinned_cpu_core, hash_samples);
+    if let Some(ledger_path) = ledger_path {
+        if let Err(err) = calibration.save(ledger_path) {
+            warn!("Unable to save PoH calibration: {err}");
+        }
+    }
+    let my_hashes_per_second = calibration.hashes_per_second;
+
     info!(
         "PoH speed check: computed hashes per second {my_hashes_per_second}, target hashes per \
          second {target_hashes_per_second}"
@@ -2215,7 +2530,7 @@ fn load_blockstore(
             initialize_rpc_transaction_history_services(
                 blockstore.clone(),
                 exit.clone(),
-                enable_rpc_transaction_history,
+                enable_rpc_transaction_history && !config.rpc_config.transaction_history_paused,
                 config.rpc_config.enable_extended_tx_metadata_storage,
                 transaction_notifier,
                 dependency_tracker,
@@ -2639,6 +2954,10 @@ fn initialize_rpc_transaction_history_services(
     dependency_tracker: Option<Arc<DependencyTracker>>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
+    let transaction_history = Arc::new(TransactionHistorySwitch::new(
+        enable_rpc_transaction_history,
+        enable_extended_tx_metadata_storage,
+    ));
     let (transaction_status_sender, transaction_status_receiver) = unbounded();
     let transaction_status_sender = Some(TransactionStatusSender {
         sender: transaction_status_sender,
@@ -2647,10 +2966,9 @@ fn initialize_rpc_transaction_history_services(
     let transaction_status_service = Some(TransactionStatusService::new(
         transaction_status_receiver,
         max_complete_transaction_status_slot.clone(),
-        enable_rpc_transaction_history,
+        transaction_history.clone(),
         transaction_notifier,
         blockstore.clone(),
-        enable_extended_tx_metadata_storage,
         dependency_tracker,
         exit.clone(),
     ));
@@ -2658,6 +2976,7 @@ fn initialize_rpc_transaction_history_services(
     TransactionHistoryServices {
         transaction_status_sender,
         transaction_status_service,
+        transaction_history: Some(transaction_history),
         max_complete_transaction_status_slot,
     }
 }
@@ -2925,6 +3244,7 @@ mod tests {
         solana_sha256_hasher::hash,
         solana_tpu_client::tpu_client::DEFAULT_TPU_ENABLE_UDP,
         std::{fs::remove_dir_all, num::NonZeroU64, thread, time::Duration},
+        tempfile::TempDir,
     };
 
     #[test]
@@ -3277,6 +3597,56 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_create_exit_snapshot() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(1_000_000_000);
+        let (bank0, bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+        bank0.fill_bank_with_ticks_for_tests();
+
+        let snapshot_dir = TempDir::new().unwrap();
+        let snapshot_config = SnapshotConfig {
+            full_snapshot_archives_dir: snapshot_dir.path().join("full"),
+            incremental_snapshot_archives_dir: snapshot_dir.path().join("incremental"),
+            bank_snapshots_dir: snapshot_dir.path().join("bank"),
+            ..SnapshotConfig::default()
+        };
+        std::fs::create_dir_all(&snapshot_config.bank_snapshots_dir).unwrap();
+        let latest_snapshot_slots = || {
+            let full_snapshot_slot = snapshot_paths::get_highest_full_snapshot_archive_slot(
+                &snapshot_config.full_snapshot_archives_dir,
+            );
+            let incremental_snapshot_slot = full_snapshot_slot.and_then(|full_snapshot_slot| {
+                snapshot_paths::get_highest_incremental_snapshot_archive_slot(
+                    &snapshot_config.incremental_snapshot_archives_dir,
+                    full_snapshot_slot,
+                )
+            });
+            (full_snapshot_slot, incremental_snapshot_slot)
+        };
+
+        // without any snapshot archive, a full snapshot is created
+        create_exit_snapshot(&bank_forks, &snapshot_config, 100);
+        assert_eq!(latest_snapshot_slots(), (Some(0), None));
+
+        let bank10 = Bank::new_from_parent_with_bank_forks(
+            bank_forks.as_ref(),
+            bank0,
+            &Pubkey::default(),
+            10,
+        );
+        bank10.fill_bank_with_ticks_for_tests();
+        bank10.freeze();
+        bank_forks.write().unwrap().set_root(10, None, None);
+
+        // the latest snapshot is recent enough
+        create_exit_snapshot(&bank_forks, &snapshot_config, 10);
+        assert_eq!(latest_snapshot_slots(), (Some(0), None));
+
+        // the latest snapshot is too old, an incremental snapshot is created
+        create_exit_snapshot(&bank_forks, &snapshot_config, 9);
+        assert_eq!(latest_snapshot_slots(), (Some(0), Some(10)));
+    }
+
     #[test]
     fn test_is_snapshot_config_valid() {
         fn new_snapshot_config(
@@ -3365,7 +3735,63 @@ mod tests {
             ..GenesisConfig::default()
         };
         let bank = Bank::new_for_tests(&genesis_config);
-        assert!(check_poh_speed(&bank, Some(10_000)).is_err());
+        assert!(check_poh_speed(
+            &bank,
+            Some(10_000),
+            poh_service::DEFAULT_PINNED_CPU_CORE,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_poh_speed_persisted_calibration() {
+        agave_logger::setup();
+        let poh_config = PohConfig {
+            target_tick_duration: target_tick_duration(),
+            // make PoH rate really fast so that only a persisted calibration can pass
+            hashes_per_tick: Some(100 * solana_clock::DEFAULT_HASHES_PER_TICK),
+            ..PohConfig::default()
+        };
+        let genesis_config = GenesisConfig {
+            poh_config,
+            ..GenesisConfig::default()
+        };
+        let bank = Bank::new_for_tests(&genesis_config);
+        let ledger_dir = TempDir::new().unwrap();
+
+        // a failed measurement is still persisted, but not trusted on the next start
+        assert!(check_poh_speed(
+            &bank,
+            Some(10_000),
+            poh_service::DEFAULT_PINNED_CPU_CORE,
+            Some(ledger_dir.path())
+        )
+        .is_err());
+        let measured = PohCalibration::load(ledger_dir.path()).unwrap();
+        assert!(measured.is_applicable(poh_service::DEFAULT_PINNED_CPU_CORE, 10_000));
+        assert!(check_poh_speed(
+            &bank,
+            Some(10_000),
+            poh_service::DEFAULT_PINNED_CPU_CORE,
+            Some(ledger_dir.path())
+        )
+        .is_err());
+
+        // a passing calibration is trusted
+        PohCalibration {
+            hashes_per_second: u64::MAX,
+            ..measured
+        }
+        .save(ledger_dir.path())
+        .unwrap();
+        check_poh_speed(
+            &bank,
+            Some(10_000),
+            poh_service::DEFAULT_PINNED_CPU_CORE,
+            Some(ledger_dir.path()),
+        )
+        .unwrap();
     }
 
     #[test]
@@ -3381,6 +3807,12 @@ mod tests {
             ..GenesisConfig::default()
         };
         let bank = Bank::new_for_tests(&genesis_config);
-        check_poh_speed(&bank, Some(10_000)).unwrap();
+        check_poh_speed(
+            &bank,
+            Some(10_000),
+            poh_service::DEFAULT_PINNED_CPU_CORE,
+            None,
+        )
+        .unwrap();
     }
 }
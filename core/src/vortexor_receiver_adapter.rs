@@ -3,7 +3,7 @@
 //! banking stage.
 
 use {
-    crate::banking_trace::TracedSender,
+    crate::{banking_trace::TracedSender, tpu_mirror_stage::TpuMirrorSender},
     agave_banking_stage_ingress_types::BankingPacketBatch,
     agave_verified_packet_receiver::receiver::VerifiedPacketReceiver,
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
@@ -40,6 +40,7 @@ impl VortexorReceiverAdapter {
         recv_timeout: Duration,
         packets_sender: TracedSender,
         forward_stage_sender: Option<Sender<(BankingPacketBatch, bool)>>,
+        mirror_sender: Option<TpuMirrorSender>,
         exit: Arc<AtomicBool>,
     ) -> Self {
         let (batch_sender, batch_receiver) = unbounded();
@@ -55,6 +56,7 @@ impl VortexorReceiverAdapter {
                     MAX_PACKET_BATCH_SIZE,
                     packets_sender,
                     forward_stage_sender,
+                    mirror_sender,
                 ) {
                     info!("Quiting VortexorReceiverAdapter: {msg}");
                 }
@@ -77,11 +79,15 @@ impl VortexorReceiverAdapter {
         batch_size: usize,
         traced_sender: TracedSender,
         forward_stage_sender: Option<Sender<(BankingPacketBatch, bool)>>,
+        mirror_sender: Option<TpuMirrorSender>,
     ) -> Result<(), String> {
         loop {
             match Self::receive_until(packet_batch_receiver.clone(), recv_timeout, batch_size) {
                 Ok(packet_batch) => {
                     let count = packet_batch.len();
+                    if let Some(mirror_sender) = &mirror_sender {
+                        mirror_sender.send(&packet_batch);
+                    }
                     // Send out packet batches
                     if let Some(forward_stage_sender) = &forward_stage_sender {
                         send(&traced_sender, packet_batch.clone(), count)?;
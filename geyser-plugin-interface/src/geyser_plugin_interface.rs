@@ -109,6 +109,44 @@ pub struct ReplicaAccountInfoV3<'a> {
     pub txn: Option<&'a SanitizedTransaction>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+/// Information about an account being updated
+/// (extended with the sequence number of the notification in its slot)
+pub struct ReplicaAccountInfoV4<'a> {
+    /// The Pubkey for the account
+    pub pubkey: &'a [u8],
+
+    /// The lamports for the account
+    pub lamports: u64,
+
+    /// The Pubkey of the owner program account
+    pub owner: &'a [u8],
+
+    /// This account's data contains a loaded program (and is now read-only)
+    pub executable: bool,
+
+    /// The epoch at which this account will next owe rent
+    pub rent_epoch: u64,
+
+    /// The data held in this account.
+    pub data: &'a [u8],
+
+    /// A global monotonically increasing atomic number, which can be used
+    /// to tell the order of the account update. For example, when an
+    /// account is updated in the same slot multiple times, the update
+    /// with higher write_version should supersede the one with lower
+    /// write_version.
+    pub write_version: u64,
+
+    /// Reference to transaction causing this account modification
+    pub txn: Option<&'a SanitizedTransaction>,
+
+    /// The position of this notification among the notifications of its slot, see
+    /// `SlotStatus::EndOfSlot`. None for the accounts restored from a snapshot at startup.
+    pub sequence: Option<u64>,
+}
+
 /// A wrapper to future-proof ReplicaAccountInfo handling.
 /// If there were a change to the structure of ReplicaAccountInfo,
 /// there would be new enum entry for the newer version, forcing
@@ -118,6 +156,7 @@ pub enum ReplicaAccountInfoVersions<'a> {
     V0_0_1(&'a ReplicaAccountInfo<'a>),
     V0_0_2(&'a ReplicaAccountInfoV2<'a>),
     V0_0_3(&'a ReplicaAccountInfoV3<'a>),
+    V0_0_4(&'a ReplicaAccountInfoV4<'a>),
 }
 
 /// Information about a transaction
@@ -180,6 +219,34 @@ pub struct ReplicaTransactionInfoV3<'a> {
     pub index: usize,
 }
 
+/// Information about a transaction, including the sequence number of the notification in its
+/// slot
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaTransactionInfoV4<'a> {
+    /// The transaction signature, used for identifying the transaction.
+    pub signature: &'a Signature,
+
+    /// The transaction message hash, used for identifying the transaction.
+    pub message_hash: &'a Hash,
+
+    /// Indicates if the transaction is a simple vote transaction.
+    pub is_vote: bool,
+
+    /// The versioned transaction.
+    pub transaction: &'a VersionedTransaction,
+
+    /// Metadata of the transaction status.
+    pub transaction_status_meta: &'a TransactionStatusMeta,
+
+    /// The transaction's index in the block
+    pub index: usize,
+
+    /// The position of this notification among the notifications of its slot, see
+    /// `SlotStatus::EndOfSlot`
+    pub sequence: u64,
+}
+
 /// A wrapper to future-proof ReplicaTransactionInfo handling.
 /// If there were a change to the structure of ReplicaTransactionInfo,
 /// there would be new enum entry for the newer version, forcing
@@ -189,6 +256,7 @@ pub enum ReplicaTransactionInfoVersions<'a> {
     V0_0_1(&'a ReplicaTransactionInfo<'a>),
     V0_0_2(&'a ReplicaTransactionInfoV2<'a>),
     V0_0_3(&'a ReplicaTransactionInfoV3<'a>),
+    V0_0_4(&'a ReplicaTransactionInfoV4<'a>),
 }
 
 #[derive(Clone, Debug)]
@@ -225,6 +293,27 @@ pub struct ReplicaEntryInfoV2<'a> {
     pub starting_transaction_index: usize,
 }
 
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaEntryInfoV3<'a> {
+    /// The slot number of the block containing this Entry
+    pub slot: Slot,
+    /// The Entry's index in the block
+    pub index: usize,
+    /// The number of hashes since the previous Entry
+    pub num_hashes: u64,
+    /// The Entry's SHA-256 hash, generated from the previous Entry's hash with
+    /// `solana_entry::entry::next_hash()`
+    pub hash: &'a [u8],
+    /// The number of executed transactions in the Entry
+    pub executed_transaction_count: u64,
+    /// The index-in-block of the first executed transaction in this Entry
+    pub starting_transaction_index: usize,
+    /// The position of this notification among the notifications of its slot, see
+    /// `SlotStatus::EndOfSlot`
+    pub sequence: u64,
+}
+
 /// A wrapper to future-proof ReplicaEntryInfo handling. To make a change to the structure of
 /// ReplicaEntryInfo, add an new enum variant wrapping a newer version, which will force plugin
 /// implementations to handle the change.
@@ -232,6 +321,7 @@ pub struct ReplicaEntryInfoV2<'a> {
 pub enum ReplicaEntryInfoVersions<'a> {
     V0_0_1(&'a ReplicaEntryInfo<'a>),
     V0_0_2(&'a ReplicaEntryInfoV2<'a>),
+    V0_0_3(&'a ReplicaEntryInfoV3<'a>),
 }
 
 #[derive(Clone, Debug)]
@@ -288,12 +378,58 @@ pub struct ReplicaBlockInfoV4<'a> {
     pub entry_count: u64,
 }
 
+/// Timings of a batch of consecutive Entries of a block, in the order they were replayed
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaEntryBatchTiming {
+    /// The index in the block of the first Entry of the batch
+    pub first_entry_index: usize,
+    /// The number of Entries in the batch
+    pub entry_count: usize,
+    /// When replay loaded the Entries of the batch from the blockstore to process them, in
+    /// microseconds since the UNIX epoch. This is not when their shreds arrived, which can be much
+    /// earlier if replay was behind.
+    pub loaded_timestamp_us: u64,
+    /// When the PoH and signature verification of the Entries completed, in microseconds since
+    /// the UNIX epoch
+    ///
+    /// Both timestamps are read from the system clock, which is not monotonic, so an adjustment of
+    /// the clock between them can make this earlier than `loaded_timestamp_us`.
+    pub verified_timestamp_us: u64,
+}
+
+/// Extending ReplicaBlockInfo by sending the timings of the block's Entries and the sequence
+/// number of the notification in its slot.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct ReplicaBlockInfoV5<'a> {
+    pub parent_slot: Slot,
+    pub parent_blockhash: &'a str,
+    pub slot: Slot,
+    pub blockhash: &'a str,
+    pub rewards: &'a RewardsAndNumPartitions,
+    pub block_time: Option<UnixTimestamp>,
+    pub block_height: Option<u64>,
+    pub executed_transaction_count: u64,
+    pub entry_count: u64,
+    /// When the first shred of the block was received from the leader, in milliseconds since the
+    /// UNIX epoch. Estimated from the reference tick of the first shred that arrived, and None if
+    /// this node was the leader.
+    pub first_shred_received_timestamp_ms: Option<u64>,
+    /// Empty if the block was not replayed, e.g. because this node was the leader
+    pub entry_batch_timings: &'a [ReplicaEntryBatchTiming],
+    /// The position of this notification among the notifications of its slot, see
+    /// `SlotStatus::EndOfSlot`
+    pub sequence: u64,
+}
+
 #[repr(u32)]
 pub enum ReplicaBlockInfoVersions<'a> {
     V0_0_1(&'a ReplicaBlockInfo<'a>),
     V0_0_2(&'a ReplicaBlockInfoV2<'a>),
     V0_0_3(&'a ReplicaBlockInfoV3<'a>),
     V0_0_4(&'a ReplicaBlockInfoV4<'a>),
+    V0_0_5(&'a ReplicaBlockInfoV5<'a>),
 }
 
 /// Errors returned by plugin calls
@@ -353,6 +489,13 @@ pub enum SlotStatus {
 
     /// A slot is marked dead
     Dead(String),
+
+    /// All the account, transaction, entry and block metadata notifications of the slot were
+    /// sent. They are numbered from 0 in the order they were sent, and `notification_count` is
+    /// the number of them, so that a missing notification can be detected. Sent once the block
+    /// metadata and, for the notifications that are enabled, as many transactions as the block
+    /// metadata counts and the last entry of the slot were sent.
+    EndOfSlot { notification_count: u64 },
 }
 
 impl SlotStatus {
@@ -365,6 +508,7 @@ impl SlotStatus {
             SlotStatus::Completed => "completed",
             SlotStatus::CreatedBank => "created_bank",
             SlotStatus::Dead(_error) => "dead",
+            SlotStatus::EndOfSlot { .. } => "end_of_slot",
         }
     }
 }
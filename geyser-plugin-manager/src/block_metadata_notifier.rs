@@ -1,13 +1,15 @@
 use {
     crate::{
         block_metadata_notifier_interface::BlockMetadataNotifier,
-        geyser_plugin_manager::GeyserPluginManager,
+        geyser_plugin_manager::GeyserPluginManager, slot_sequencer::SlotSequencer,
+        slot_status_notifier::SlotStatusNotifierImpl,
     },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
-        ReplicaBlockInfoV4, ReplicaBlockInfoVersions,
+        ReplicaBlockInfoV5, ReplicaBlockInfoVersions, ReplicaEntryBatchTiming, SlotStatus,
     },
     log::*,
     solana_clock::UnixTimestamp,
+    solana_ledger::blockstore_processor::EntryBatchTiming,
     solana_measure::measure::Measure,
     solana_metrics::*,
     solana_runtime::bank::KeyedRewardsAndNumPartitions,
@@ -17,6 +19,7 @@ use {
 
 pub(crate) struct BlockMetadataNotifierImpl {
     plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    slot_sequencer: Arc<SlotSequencer>,
 }
 
 impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
@@ -32,13 +35,11 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
         block_height: Option<u64>,
         executed_transaction_count: u64,
         entry_count: u64,
+        first_shred_timestamp_ms: Option<u64>,
+        entry_batch_timings: &[EntryBatchTiming],
     ) {
-        let plugin_manager = self.plugin_manager.read().unwrap();
-        if plugin_manager.plugins.is_empty() {
-            return;
-        }
-
         let rewards = Self::build_rewards(rewards);
+        let entry_batch_timings = Self::build_entry_batch_timings(entry_batch_timings);
         let block_info = Self::build_replica_block_info(
             parent_slot,
             parent_blockhash,
@@ -49,11 +50,15 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
             block_height,
             executed_transaction_count,
             entry_count,
+            first_shred_timestamp_ms,
+            &entry_batch_timings,
+            self.slot_sequencer.next(slot),
         );
 
+        let plugin_manager = self.plugin_manager.read().unwrap();
         for plugin in plugin_manager.plugins.iter() {
             let mut measure = Measure::start("geyser-plugin-update-slot");
-            let block_info = ReplicaBlockInfoVersions::V0_0_4(&block_info);
+            let block_info = ReplicaBlockInfoVersions::V0_0_5(&block_info);
             match plugin.notify_block_metadata(block_info) {
                 Err(err) => {
                     error!(
@@ -79,6 +84,17 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
                 1000
             );
         }
+        if let Some(notification_count) = self
+            .slot_sequencer
+            .block_metadata_sent(slot, executed_transaction_count)
+        {
+            SlotStatusNotifierImpl::notify_plugins(
+                &plugin_manager,
+                slot,
+                None,
+                &SlotStatus::EndOfSlot { notification_count },
+            );
+        }
     }
 }
 
@@ -100,6 +116,21 @@ impl BlockMetadataNotifierImpl {
         }
     }
 
+    fn build_entry_batch_timings(
+        entry_batch_timings: &[EntryBatchTiming],
+    ) -> Vec<ReplicaEntryBatchTiming> {
+        entry_batch_timings
+            .iter()
+            .map(|timing| ReplicaEntryBatchTiming {
+                first_entry_index: timing.first_entry_index,
+                entry_count: timing.num_entries,
+                loaded_timestamp_us: timing.loaded_timestamp_us,
+                verified_timestamp_us: timing.verified_timestamp_us,
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn build_replica_block_info<'a>(
         parent_slot: u64,
         parent_blockhash: &'a str,
@@ -110,8 +141,11 @@ impl BlockMetadataNotifierImpl {
         block_height: Option<u64>,
         executed_transaction_count: u64,
         entry_count: u64,
-    ) -> ReplicaBlockInfoV4<'a> {
-        ReplicaBlockInfoV4 {
+        first_shred_received_timestamp_ms: Option<u64>,
+        entry_batch_timings: &'a [ReplicaEntryBatchTiming],
+        sequence: u64,
+    ) -> ReplicaBlockInfoV5<'a> {
+        ReplicaBlockInfoV5 {
             parent_slot,
             parent_blockhash,
             slot,
@@ -121,10 +155,19 @@ impl BlockMetadataNotifierImpl {
             block_height,
             executed_transaction_count,
             entry_count,
+            first_shred_received_timestamp_ms,
+            entry_batch_timings,
+            sequence,
         }
     }
 
-    pub fn new(plugin_manager: Arc<RwLock<GeyserPluginManager>>) -> Self {
-        Self { plugin_manager }
+    pub fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        slot_sequencer: Arc<SlotSequencer>,
+    ) -> Self {
+        Self {
+            plugin_manager,
+            slot_sequencer,
+        }
     }
 }
@@ -1,5 +1,6 @@
 use {
-    solana_clock::UnixTimestamp, solana_runtime::bank::KeyedRewardsAndNumPartitions, std::sync::Arc,
+    solana_clock::UnixTimestamp, solana_ledger::blockstore_processor::EntryBatchTiming,
+    solana_runtime::bank::KeyedRewardsAndNumPartitions, std::sync::Arc,
 };
 
 /// Interface for notifying block metadata changes
@@ -17,6 +18,8 @@ pub trait BlockMetadataNotifier {
         block_height: Option<u64>,
         executed_transaction_count: u64,
         entry_count: u64,
+        first_shred_timestamp_ms: Option<u64>,
+        entry_batch_timings: &[EntryBatchTiming],
     );
 }
 
@@ -1,8 +1,11 @@
 /// Module responsible for notifying plugins about entries
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{
+        geyser_plugin_manager::GeyserPluginManager, slot_sequencer::SlotSequencer,
+        slot_status_notifier::SlotStatusNotifierImpl,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
-        ReplicaEntryInfoV2, ReplicaEntryInfoVersions,
+        ReplicaEntryInfoV3, ReplicaEntryInfoVersions, SlotStatus,
     },
     log::*,
     solana_clock::Slot,
@@ -15,6 +18,7 @@ use {
 
 pub(crate) struct EntryNotifierImpl {
     plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    slot_sequencer: Arc<SlotSequencer>,
 }
 
 impl EntryNotifier for EntryNotifierImpl {
@@ -24,22 +28,24 @@ impl EntryNotifier for EntryNotifierImpl {
         index: usize,
         entry: &'a EntrySummary,
         starting_transaction_index: usize,
+        is_last_in_slot: bool,
     ) {
         let mut measure = Measure::start("geyser-plugin-notify_plugins_of_entry_info");
 
-        let plugin_manager = self.plugin_manager.read().unwrap();
-        if plugin_manager.plugins.is_empty() {
-            return;
-        }
-
-        let entry_info =
-            Self::build_replica_entry_info(slot, index, entry, starting_transaction_index);
+        let entry_info = Self::build_replica_entry_info(
+            slot,
+            index,
+            entry,
+            starting_transaction_index,
+            self.slot_sequencer.next(slot),
+        );
 
+        let plugin_manager = self.plugin_manager.read().unwrap();
         for plugin in plugin_manager.plugins.iter() {
             if !plugin.entry_notifications_enabled() {
                 continue;
             }
-            match plugin.notify_entry(ReplicaEntryInfoVersions::V0_0_2(&entry_info)) {
+            match plugin.notify_entry(ReplicaEntryInfoVersions::V0_0_3(&entry_info)) {
                 Err(err) => {
                     error!(
                         "Failed to notify entry, error: ({}) to plugin {}",
@@ -52,6 +58,17 @@ impl EntryNotifier for EntryNotifierImpl {
                 }
             }
         }
+        if let Some(notification_count) = is_last_in_slot
+            .then(|| self.slot_sequencer.last_entry_sent(slot))
+            .flatten()
+        {
+            SlotStatusNotifierImpl::notify_plugins(
+                &plugin_manager,
+                slot,
+                None,
+                &SlotStatus::EndOfSlot { notification_count },
+            );
+        }
         measure.stop();
         inc_new_counter_debug!(
             "geyser-plugin-notify_plugins_of_entry_info-us",
@@ -63,8 +80,14 @@ impl EntryNotifier for EntryNotifierImpl {
 }
 
 impl EntryNotifierImpl {
-    pub fn new(plugin_manager: Arc<RwLock<GeyserPluginManager>>) -> Self {
-        Self { plugin_manager }
+    pub fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        slot_sequencer: Arc<SlotSequencer>,
+    ) -> Self {
+        Self {
+            plugin_manager,
+            slot_sequencer,
+        }
     }
 
     fn build_replica_entry_info(
@@ -72,14 +95,16 @@ impl EntryNotifierImpl {
         index: usize,
         entry: &'_ EntrySummary,
         starting_transaction_index: usize,
-    ) -> ReplicaEntryInfoV2<'_> {
-        ReplicaEntryInfoV2 {
+        sequence: u64,
+    ) -> ReplicaEntryInfoV3<'_> {
+        ReplicaEntryInfoV3 {
             slot,
             index,
             num_hashes: entry.num_hashes,
             hash: entry.hash.as_ref(),
             executed_transaction_count: entry.num_transactions,
             starting_transaction_index,
+            sequence,
         }
     }
 }
@@ -5,6 +5,7 @@ use {
         block_metadata_notifier_interface::BlockMetadataNotifierArc,
         entry_notifier::EntryNotifierImpl,
         geyser_plugin_manager::{GeyserPluginManager, GeyserPluginManagerRequest},
+        slot_sequencer::SlotSequencer,
         slot_status_notifier::SlotStatusNotifierImpl,
         slot_status_observer::SlotStatusObserver,
         transaction_notifier::TransactionNotifierImpl,
@@ -94,12 +95,17 @@ impl GeyserPluginService {
         let entry_notifications_enabled =
             plugin_manager.entry_notifications_enabled() || geyser_plugin_always_enabled;
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
+        let slot_sequencer = Arc::new(SlotSequencer::new(
+            transaction_notifications_enabled,
+            entry_notifications_enabled,
+        ));
 
         let accounts_update_notifier: Option<AccountsUpdateNotifier> =
             if account_data_notifications_enabled {
                 let accounts_update_notifier = AccountsUpdateNotifierImpl::new(
                     plugin_manager.clone(),
                     account_data_snapshot_notifications_enabled,
+                    slot_sequencer.clone(),
                 );
                 Some(Arc::new(accounts_update_notifier))
             } else {
@@ -108,14 +114,16 @@ impl GeyserPluginService {
 
         let transaction_notifier: Option<TransactionNotifierArc> =
             if transaction_notifications_enabled {
-                let transaction_notifier = TransactionNotifierImpl::new(plugin_manager.clone());
+                let transaction_notifier =
+                    TransactionNotifierImpl::new(plugin_manager.clone(), slot_sequencer.clone());
                 Some(Arc::new(transaction_notifier))
             } else {
                 None
             };
 
         let entry_notifier: Option<EntryNotifierArc> = if entry_notifications_enabled {
-            let entry_notifier = EntryNotifierImpl::new(plugin_manager.clone());
+            let entry_notifier =
+                EntryNotifierImpl::new(plugin_manager.clone(), slot_sequencer.clone());
             Some(Arc::new(entry_notifier))
         } else {
             None
@@ -129,7 +137,8 @@ impl GeyserPluginService {
             || transaction_notifications_enabled
             || entry_notifications_enabled
         {
-            let slot_status_notifier = SlotStatusNotifierImpl::new(plugin_manager.clone());
+            let slot_status_notifier =
+                SlotStatusNotifierImpl::new(plugin_manager.clone(), slot_sequencer.clone());
             let slot_status_notifier = Arc::new(RwLock::new(slot_status_notifier));
             (
                 Some(SlotStatusObserver::new(
@@ -138,6 +147,7 @@ impl GeyserPluginService {
                 )),
                 Some(Arc::new(BlockMetadataNotifierImpl::new(
                     plugin_manager.clone(),
+                    slot_sequencer,
                 ))),
                 Some(slot_status_notifier),
             )
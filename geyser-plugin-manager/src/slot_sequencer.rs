@@ -0,0 +1,192 @@
+/// Module numbering the notifications of each slot.
+///
+/// The notifications of a slot are sent from several threads: account updates from replay,
+/// transactions from the TransactionStatusService, entries from the EntryNotifierService and the
+/// block metadata from replay once the bank is frozen. Each notification is numbered in the order
+/// it is sent so that plugins can tell when they missed one and reorder them, and the slot is
+/// ended once all the transactions its block metadata counts and its last entry were sent. Entries
+/// are sent in order from a single thread, so the last one comes after all the others: this also
+/// covers the blocks of this leader, whose entries are not counted when their metadata is sent.
+use {
+    solana_clock::Slot,
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+    },
+};
+
+#[derive(Debug, Default)]
+struct SlotSequence {
+    /// Sequence number of the next notification
+    next: AtomicU64,
+    transactions_sent: AtomicU64,
+    last_entry_sent: AtomicBool,
+    /// Number of transactions of the block, set before `block_metadata_sent`
+    transaction_count: AtomicU64,
+    block_metadata_sent: AtomicBool,
+    ended: AtomicBool,
+}
+
+#[derive(Debug)]
+pub(crate) struct SlotSequencer {
+    slots: RwLock<HashMap<Slot, Arc<SlotSequence>>>,
+    /// Whether transactions are notified, so that the end of a slot waits for them
+    wait_for_transactions: bool,
+    /// Whether entries are notified, so that the end of a slot waits for them
+    wait_for_entries: bool,
+}
+
+impl SlotSequencer {
+    pub(crate) fn new(wait_for_transactions: bool, wait_for_entries: bool) -> Self {
+        Self {
+            slots: RwLock::default(),
+            wait_for_transactions,
+            wait_for_entries,
+        }
+    }
+
+    fn slot(&self, slot: Slot) -> Arc<SlotSequence> {
+        if let Some(sequence) = self.slots.read().unwrap().get(&slot) {
+            return sequence.clone();
+        }
+        self.slots.write().unwrap().entry(slot).or_default().clone()
+    }
+
+    /// Returns the sequence number of the next notification of `slot`
+    pub(crate) fn next(&self, slot: Slot) -> u64 {
+        self.slot(slot).next.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Records that a transaction of `slot` was sent, and returns the number of notifications of
+    /// the slot if it ended
+    pub(crate) fn transaction_sent(&self, slot: Slot) -> Option<u64> {
+        let sequence = self.slot(slot);
+        sequence.transactions_sent.fetch_add(1, Ordering::SeqCst);
+        self.try_end(&sequence)
+    }
+
+    /// Records that the last entry of `slot` was sent, and returns the number of notifications of
+    /// the slot if it ended
+    pub(crate) fn last_entry_sent(&self, slot: Slot) -> Option<u64> {
+        let sequence = self.slot(slot);
+        sequence.last_entry_sent.store(true, Ordering::SeqCst);
+        self.try_end(&sequence)
+    }
+
+    /// Records that the metadata of the block of `slot` was sent, and returns the number of
+    /// notifications of the slot if it ended
+    pub(crate) fn block_metadata_sent(&self, slot: Slot, transaction_count: u64) -> Option<u64> {
+        let sequence = self.slot(slot);
+        sequence
+            .transaction_count
+            .store(transaction_count, Ordering::Relaxed);
+        sequence.block_metadata_sent.store(true, Ordering::SeqCst);
+        self.try_end(&sequence)
+    }
+
+    // The count of sent transactions, and whether the last entry and block metadata were sent, are
+    // updated from different threads, and each thread checks the others' updates after its own:
+    // sequential consistency makes sure that at least the last one sees them all.
+    fn try_end(&self, sequence: &SlotSequence) -> Option<u64> {
+        if !sequence.block_metadata_sent.load(Ordering::SeqCst) {
+            return None;
+        }
+        let transactions_sent = !self.wait_for_transactions
+            || sequence.transactions_sent.load(Ordering::SeqCst)
+                >= sequence.transaction_count.load(Ordering::Relaxed);
+        let entries_sent =
+            !self.wait_for_entries || sequence.last_entry_sent.load(Ordering::SeqCst);
+        (transactions_sent && entries_sent && !sequence.ended.swap(true, Ordering::SeqCst))
+            .then(|| sequence.next.load(Ordering::SeqCst))
+    }
+
+    /// Forgets the slots up to `root`, and returns the number of notifications of those whose
+    /// block metadata was sent but which did not end, to end them
+    pub(crate) fn prune(&self, root: Slot) -> Vec<(Slot, u64)> {
+        let mut unended = vec![];
+        self.slots.write().unwrap().retain(|slot, sequence| {
+            if *slot > root {
+                return true;
+            }
+            if sequence.block_metadata_sent.load(Ordering::SeqCst)
+                && !sequence.ended.swap(true, Ordering::SeqCst)
+            {
+                unended.push((*slot, sequence.next.load(Ordering::SeqCst)));
+            }
+            false
+        });
+        unended.sort_unstable();
+        unended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_sequencer() {
+        let sequencer = SlotSequencer::new(true, true);
+        assert_eq!(sequencer.next(1), 0);
+        assert_eq!(sequencer.next(2), 0);
+        assert_eq!(sequencer.next(1), 1);
+        assert_eq!(sequencer.transaction_sent(1), None);
+
+        // The block has two transactions, one transaction is still to be sent
+        assert_eq!(sequencer.next(1), 2);
+        assert_eq!(sequencer.block_metadata_sent(1, 2), None);
+        assert_eq!(sequencer.next(1), 3);
+        assert_eq!(sequencer.last_entry_sent(1), None);
+        assert_eq!(sequencer.next(1), 4);
+        assert_eq!(sequencer.transaction_sent(1), Some(5));
+        // Slots are ended once
+        assert_eq!(sequencer.transaction_sent(1), None);
+
+        // A block without transactions ends with its metadata once its last entry was sent
+        assert_eq!(sequencer.last_entry_sent(2), None);
+        assert_eq!(sequencer.block_metadata_sent(2, 0), Some(1));
+        assert_eq!(sequencer.prune(2), vec![]);
+    }
+
+    #[test]
+    fn test_slot_sequencer_leader_block() {
+        let sequencer = SlotSequencer::new(true, true);
+        // The metadata of a block of this leader is sent before its entries
+        assert_eq!(sequencer.next(1), 0);
+        assert_eq!(sequencer.transaction_sent(1), None);
+        assert_eq!(sequencer.next(1), 1);
+        assert_eq!(sequencer.block_metadata_sent(1, 1), None);
+        for sequence in 2..5 {
+            assert_eq!(sequencer.next(1), sequence);
+        }
+        assert_eq!(sequencer.last_entry_sent(1), Some(5));
+    }
+
+    #[test]
+    fn test_slot_sequencer_without_transactions() {
+        let sequencer = SlotSequencer::new(false, true);
+        assert_eq!(sequencer.next(1), 0);
+        assert_eq!(sequencer.block_metadata_sent(1, 10), None);
+        assert_eq!(sequencer.next(1), 1);
+        assert_eq!(sequencer.last_entry_sent(1), Some(2));
+    }
+
+    #[test]
+    fn test_slot_sequencer_prune() {
+        let sequencer = SlotSequencer::new(true, false);
+        for slot in [1, 2, 3, 4] {
+            sequencer.next(slot);
+        }
+        sequencer.block_metadata_sent(1, 1);
+        sequencer.block_metadata_sent(3, 1);
+        sequencer.block_metadata_sent(4, 1);
+
+        // Slots 1 and 3 were processed but did not end, slot 2 was not processed
+        assert_eq!(sequencer.prune(3), vec![(1, 1), (3, 1)]);
+        assert_eq!(sequencer.transaction_sent(4), Some(1));
+        assert_eq!(sequencer.prune(4), vec![]);
+    }
+}
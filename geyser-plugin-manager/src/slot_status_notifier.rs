@@ -1,5 +1,5 @@
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{geyser_plugin_manager::GeyserPluginManager, slot_sequencer::SlotSequencer},
     agave_geyser_plugin_interface::geyser_plugin_interface::SlotStatus,
     log::*,
     solana_clock::Slot,
@@ -11,6 +11,7 @@ use {
 
 pub struct SlotStatusNotifierImpl {
     plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    slot_sequencer: Arc<SlotSequencer>,
 }
 
 impl SlotStatusNotifierInterface for SlotStatusNotifierImpl {
@@ -23,6 +24,14 @@ impl SlotStatusNotifierInterface for SlotStatusNotifierImpl {
     }
 
     fn notify_slot_rooted(&self, slot: Slot, parent: Option<Slot>) {
+        // Slots whose transactions or entries did not all come through are ended once rooted
+        for (unended_slot, notification_count) in self.slot_sequencer.prune(slot) {
+            self.notify_slot_status(
+                unended_slot,
+                None,
+                SlotStatus::EndOfSlot { notification_count },
+            );
+        }
         self.notify_slot_status(slot, parent, SlotStatus::Rooted);
     }
 
@@ -44,19 +53,34 @@ impl SlotStatusNotifierInterface for SlotStatusNotifierImpl {
 }
 
 impl SlotStatusNotifierImpl {
-    pub fn new(plugin_manager: Arc<RwLock<GeyserPluginManager>>) -> Self {
-        Self { plugin_manager }
+    pub(crate) fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        slot_sequencer: Arc<SlotSequencer>,
+    ) -> Self {
+        Self {
+            plugin_manager,
+            slot_sequencer,
+        }
     }
 
     pub fn notify_slot_status(&self, slot: Slot, parent: Option<Slot>, slot_status: SlotStatus) {
         let plugin_manager = self.plugin_manager.read().unwrap();
+        Self::notify_plugins(&plugin_manager, slot, parent, &slot_status);
+    }
+
+    pub(crate) fn notify_plugins(
+        plugin_manager: &GeyserPluginManager,
+        slot: Slot,
+        parent: Option<Slot>,
+        slot_status: &SlotStatus,
+    ) {
         if plugin_manager.plugins.is_empty() {
             return;
         }
 
         for plugin in plugin_manager.plugins.iter() {
             let mut measure = Measure::start("geyser-plugin-update-slot");
-            match plugin.update_slot_status(slot, parent, &slot_status) {
+            match plugin.update_slot_status(slot, parent, slot_status) {
                 Err(err) => {
                     error!(
                         "Failed to update slot status at slot {}, error: {} to plugin {}",
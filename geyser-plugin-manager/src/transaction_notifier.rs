@@ -1,8 +1,11 @@
 /// Module responsible for notifying plugins of transactions
 use {
-    crate::geyser_plugin_manager::GeyserPluginManager,
+    crate::{
+        geyser_plugin_manager::GeyserPluginManager, slot_sequencer::SlotSequencer,
+        slot_status_notifier::SlotStatusNotifierImpl,
+    },
     agave_geyser_plugin_interface::geyser_plugin_interface::{
-        ReplicaTransactionInfoV3, ReplicaTransactionInfoVersions,
+        ReplicaTransactionInfoV4, ReplicaTransactionInfoVersions, SlotStatus,
     },
     log::*,
     solana_clock::Slot,
@@ -22,6 +25,7 @@ use {
 /// plugin enabled with transaction notification managed by the GeyserPluginManager.
 pub(crate) struct TransactionNotifierImpl {
     plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    slot_sequencer: Arc<SlotSequencer>,
 }
 
 impl TransactionNotifier for TransactionNotifierImpl {
@@ -43,20 +47,17 @@ impl TransactionNotifier for TransactionNotifierImpl {
             is_vote,
             transaction_status_meta,
             transaction,
+            self.slot_sequencer.next(slot),
         );
 
         let plugin_manager = self.plugin_manager.read().unwrap();
 
-        if plugin_manager.plugins.is_empty() {
-            return;
-        }
-
         for plugin in plugin_manager.plugins.iter() {
             if !plugin.transaction_notifications_enabled() {
                 continue;
             }
             match plugin.notify_transaction(
-                ReplicaTransactionInfoVersions::V0_0_3(&transaction_log_info),
+                ReplicaTransactionInfoVersions::V0_0_4(&transaction_log_info),
                 slot,
             ) {
                 Err(err) => {
@@ -74,6 +75,14 @@ impl TransactionNotifier for TransactionNotifierImpl {
                 }
             }
         }
+        if let Some(notification_count) = self.slot_sequencer.transaction_sent(slot) {
+            SlotStatusNotifierImpl::notify_plugins(
+                &plugin_manager,
+                slot,
+                None,
+                &SlotStatus::EndOfSlot { notification_count },
+            );
+        }
         measure.stop();
         inc_new_counter_debug!(
             "geyser-plugin-notify_plugins_of_transaction_info-us",
@@ -85,8 +94,14 @@ impl TransactionNotifier for TransactionNotifierImpl {
 }
 
 impl TransactionNotifierImpl {
-    pub fn new(plugin_manager: Arc<RwLock<GeyserPluginManager>>) -> Self {
-        Self { plugin_manager }
+    pub fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        slot_sequencer: Arc<SlotSequencer>,
+    ) -> Self {
+        Self {
+            plugin_manager,
+            slot_sequencer,
+        }
     }
 
     fn build_replica_transaction_info<'a>(
@@ -96,14 +111,16 @@ impl TransactionNotifierImpl {
         is_vote: bool,
         transaction_status_meta: &'a TransactionStatusMeta,
         transaction: &'a VersionedTransaction,
-    ) -> ReplicaTransactionInfoV3<'a> {
-        ReplicaTransactionInfoV3 {
+        sequence: u64,
+    ) -> ReplicaTransactionInfoV4<'a> {
+        ReplicaTransactionInfoV4 {
             index,
             message_hash,
             signature,
             is_vote,
             transaction,
             transaction_status_meta,
+            sequence,
         }
     }
 }
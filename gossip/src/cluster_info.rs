@@ -31,6 +31,7 @@ use {
         epoch_slots::EpochSlots,
         epoch_specs::EpochSpecs,
         gossip_error::GossipError,
+        peer_preference::{PeerLatencies, PeerPreference},
         ping_pong::Pong,
         protocol::{
             split_gossip_messages, Ping, PingCache, Protocol, PruneData,
@@ -46,7 +47,11 @@ use {
     arc_swap::ArcSwap,
     crossbeam_channel::{Receiver, TrySendError},
     itertools::{Either, Itertools},
-    rand::{seq::SliceRandom, CryptoRng, Rng},
+    rand::{
+        distributions::{Distribution, WeightedIndex},
+        seq::{IteratorRandom, SliceRandom},
+        CryptoRng, Rng,
+    },
     rayon::{prelude::*, ThreadPool, ThreadPoolBuilder},
     solana_clock::{Slot, DEFAULT_MS_PER_SLOT, DEFAULT_SLOTS_PER_EPOCH},
     solana_hash::Hash,
@@ -105,6 +110,8 @@ const DEFAULT_EPOCH_DURATION: Duration =
 pub const GOSSIP_SLEEP_MILLIS: u64 = 100;
 /// Interval between pull requests (in gossip rounds)
 const PULL_REQUEST_PERIOD: usize = 5;
+/// Number of peers probed for their round trip time along with each round of pull requests
+const NUM_RTT_PROBES: usize = 8;
 
 /// Capacity for the [`ClusterInfo::run_socket_consume`] and [`ClusterInfo::run_listen`]
 /// intermediate packet batch buffers.
@@ -169,6 +176,10 @@ pub struct ClusterInfo {
     contact_info_path: PathBuf,
     socket_addr_space: SocketAddrSpace,
     bind_ip_addrs: Arc<BindIpAddrs>,
+    peer_preference: PeerPreference,
+    /// Peers whose declared region the region preference relies on
+    region_peers: HashSet<Pubkey>,
+    peer_latencies: PeerLatencies,
 }
 
 impl ClusterInfo {
@@ -198,6 +209,9 @@ impl ClusterInfo {
             contact_save_interval: 0, // disabled
             socket_addr_space,
             bind_ip_addrs: Arc::new(BindIpAddrs::default()),
+            peer_preference: PeerPreference::default(),
+            region_peers: HashSet::default(),
+            peer_latencies: PeerLatencies::default(),
         };
         me.refresh_my_gossip_contact_info();
         me
@@ -219,6 +233,51 @@ impl ClusterInfo {
         self.bind_ip_addrs.clone()
     }
 
+    pub fn set_peer_preference(&mut self, peer_preference: PeerPreference) {
+        self.peer_preference = peer_preference;
+    }
+
+    pub fn peer_preference(&self) -> PeerPreference {
+        self.peer_preference
+    }
+
+    /// Sets the peers the region preference favors when they declare the same region as this
+    /// node. Anyone can declare any region, so the regions of other peers are ignored.
+    pub fn set_region_peers(&mut self, region_peers: HashSet<Pubkey>) {
+        self.region_peers = region_peers;
+    }
+
+    /// Round trip time to the gossip address of `node`, if measured recently
+    pub fn peer_rtt(&self, node: &ContactInfo) -> Option<Duration> {
+        let addr = node.gossip()?;
+        self.peer_latencies.rtt(&addr.ip(), Instant::now())
+    }
+
+    /// Multiplies the stake based sampling `weights` of `peers` according to the peer preference
+    pub fn apply_peer_preference(&self, peers: &[ContactInfo], weights: &mut [u64]) {
+        if self.peer_preference == PeerPreference::Stake {
+            return;
+        }
+        let my_region = self
+            .my_contact_info
+            .read()
+            .unwrap()
+            .region()
+            .map(str::to_string);
+        let now = Instant::now();
+        for (peer, weight) in peers.iter().zip(weights) {
+            let rtt = peer
+                .gossip()
+                .and_then(|addr| self.peer_latencies.rtt(&addr.ip(), now));
+            let my_region = my_region
+                .as_deref()
+                .filter(|_| self.region_peers.contains(peer.pubkey()));
+            *weight = self
+                .peer_preference
+                .peer_weight(*weight, peer, my_region, rtt);
+        }
+    }
+
     fn refresh_push_active_set(
         &self,
         recycler: &PacketBatchRecycler,
@@ -428,6 +487,12 @@ impl ClusterInfo {
         Ok(())
     }
 
+    /// Stops advertising the TPU forwards addresses, until they are set again
+    pub fn remove_tpu_forwards(&self) {
+        self.my_contact_info.write().unwrap().remove_tpu_forwards();
+        self.refresh_my_gossip_contact_info();
+    }
+
     pub fn set_tpu_vote(
         &self,
         protocol: contact_info::Protocol,
@@ -1194,9 +1259,12 @@ impl ClusterInfo {
         let mut pulls = pulls.peekable();
         let entrypoint = {
             let mut entrypoints = self.entrypoints.write().unwrap();
-            let Some(entrypoint) = entrypoints.choose_mut(&mut rand::thread_rng()) else {
+            let mut weights = vec![1; entrypoints.len()];
+            self.apply_peer_preference(&entrypoints, &mut weights);
+            let Ok(weighted_index) = WeightedIndex::new(weights) else {
                 return Either::Left(pulls);
             };
+            let entrypoint = &mut entrypoints[weighted_index.sample(&mut rand::thread_rng())];
             if pulls.peek().is_some() {
                 let now = timestamp();
                 if now <= entrypoint.wallclock().saturating_add(THROTTLE_DELAY) {
@@ -1265,6 +1333,9 @@ impl ClusterInfo {
                 .into_iter()
                 .flatten()
         };
+        if self.peer_preference == PeerPreference::Latency {
+            self.new_rtt_probes(&keypair, &mut pings);
+        }
         let pings = pings
             .into_iter()
             .map(|(addr, ping)| (addr, Protocol::PingMessage(ping)));
@@ -1276,6 +1347,25 @@ impl ClusterInfo {
             .chain(pings)
     }
 
+    // Pings a few gossip peers whose round trip time is unknown or old, so that
+    // peers can be preferred by latency.
+    fn new_rtt_probes(&self, keypair: &Keypair, pings: &mut Vec<(SocketAddr, Ping)>) {
+        let now = Instant::now();
+        let mut rng = rand::thread_rng();
+        let nodes = self
+            .gossip_peers()
+            .into_iter()
+            .filter_map(|node| Some((*node.pubkey(), node.gossip()?)))
+            .filter(|(_, addr)| self.peer_latencies.should_probe(&addr.ip(), now))
+            .choose_multiple(&mut rng, NUM_RTT_PROBES);
+        let mut ping_cache = self.ping_cache.lock().unwrap();
+        for node in nodes {
+            if let Some(ping) = ping_cache.probe(&mut rng, keypair, now, node) {
+                pings.push((node.1, ping));
+            }
+        }
+    }
+
     pub fn flush_push_queue(&self) {
         let entries: Vec<CrdsValue> =
             std::mem::take(&mut *self.local_message_pending_push_queue.lock().unwrap());
@@ -1863,7 +1953,11 @@ impl ClusterInfo {
         if pongs.peek().is_some() {
             let mut ping_cache = self.ping_cache.lock().unwrap();
             for (addr, pong) in pongs {
-                ping_cache.add(&pong, addr, now);
+                if ping_cache.add(&pong, addr, now) {
+                    if let Some(rtt) = ping_cache.take_rtt(&addr.ip()) {
+                        self.peer_latencies.record(addr.ip(), rtt, now);
+                    }
+                }
             }
         }
     }
@@ -3772,6 +3866,45 @@ mod tests {
         assert_eq!(heaviest_forks[0].from, pubkey2);
     }
 
+    #[test]
+    fn test_apply_peer_preference() {
+        let keypair = Arc::new(Keypair::new());
+        let mut contact_info = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+        contact_info.set_region("eu-west".to_string()).unwrap();
+        let mut cluster_info =
+            ClusterInfo::new(contact_info, keypair, SocketAddrSpace::Unspecified);
+        let mut peers: Vec<_> =
+            repeat_with(|| ContactInfo::new_localhost(&Pubkey::new_unique(), timestamp()))
+                .take(3)
+                .collect();
+        peers[0].set_region("eu-west".to_string()).unwrap();
+        peers[2].set_region("eu-west".to_string()).unwrap();
+
+        let mut weights = vec![3, 3, 3];
+        cluster_info.apply_peer_preference(&peers, &mut weights);
+        assert_eq!(weights, [3, 3, 3]);
+
+        // Only the region of the region peers is trusted
+        cluster_info.set_peer_preference(PeerPreference::Region);
+        cluster_info.set_region_peers(HashSet::from([*peers[0].pubkey(), *peers[1].pubkey()]));
+        cluster_info.apply_peer_preference(&peers, &mut weights);
+        assert_eq!(weights, [24, 3, 3]);
+
+        // Without a measured round trip time, peers keep their weight
+        cluster_info.set_peer_preference(PeerPreference::Latency);
+        let mut weights = vec![3, 3, 3];
+        cluster_info.apply_peer_preference(&peers, &mut weights);
+        assert_eq!(weights, [3, 3, 3]);
+        let addr = peers[1].gossip().unwrap().ip();
+        cluster_info
+            .peer_latencies
+            .record(addr, Duration::from_millis(10), Instant::now());
+        assert_eq!(
+            cluster_info.peer_rtt(&peers[1]),
+            Some(Duration::from_millis(10))
+        );
+    }
+
     #[test]
     fn test_contact_trace() {
         agave_logger::setup();
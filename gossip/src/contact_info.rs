@@ -22,6 +22,15 @@ use {
     thiserror::Error,
 };
 
+/// Maximum length in bytes of the region a node declares in its contact info
+pub const MAX_REGION_LEN: usize = 32;
+
+/// The node sends repair requests, broadcast and retransmitted shreds over QUIC to the peers which
+/// declare this capability too, instead of over UDP, and drops the shreds they send it over UDP.
+/// QUIC authenticates both ends by their identity and encrypts the traffic, and turbine datagrams
+/// between such peers carry an authenticity tag.
+pub const CAPABILITY_ENCRYPTED_SHRED_TRANSPORT: u64 = 1 << 0;
+
 const DEFAULT_RPC_PORT: u16 = 8899;
 const DEFAULT_RPC_PUBSUB_PORT: u16 = 8900;
 
@@ -70,6 +79,8 @@ pub enum Error {
     MulticastIpAddr(IpAddr),
     #[error("Port offsets overflow")]
     PortOffsetsOverflow,
+    #[error("Region longer than {MAX_REGION_LEN} bytes")]
+    RegionTooLong,
     #[error("Socket not found: {0}")]
     SocketNotFound(/*key:*/ u8),
     #[error("Unspecified IP address: {0}")]
@@ -121,7 +132,10 @@ define_tlv_enum!(
     /// Always add new TLV records to the end of this enum.
     /// Never reorder or reuse a type.
     /// Ensure new type collisions do not happen.
-    pub(crate) enum Extension {}
+    pub(crate) enum Extension {
+        1 => Region(String),
+        2 => Capabilities(u64),
+    }
 );
 
 // As part of deserialization, self.addrs and self.sockets should be cross
@@ -269,6 +283,49 @@ impl ContactInfo {
         self.shred_version = shred_version
     }
 
+    /// Region the node declared it runs in, e.g. a cloud region or city, if any
+    pub fn region(&self) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::Region(region) => Some(region.as_str()),
+                _ => None,
+            })
+    }
+
+    pub fn set_region(&mut self, region: String) -> Result<(), Error> {
+        if region.len() > MAX_REGION_LEN {
+            return Err(Error::RegionTooLong);
+        }
+        self.extensions
+            .retain(|extension| !matches!(extension, Extension::Region(_)));
+        self.extensions.push(Extension::Region(region));
+        Ok(())
+    }
+
+    /// Optional capabilities the node declared, a combination of the `CAPABILITY_*` flags
+    pub fn capabilities(&self) -> u64 {
+        self.extensions
+            .iter()
+            .find_map(|extension| match extension {
+                Extension::Capabilities(capabilities) => Some(*capabilities),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: u64) {
+        self.extensions
+            .retain(|extension| !matches!(extension, Extension::Capabilities(_)));
+        if capabilities != 0 {
+            self.extensions.push(Extension::Capabilities(capabilities));
+        }
+    }
+
+    pub fn has_capability(&self, capability: u64) -> bool {
+        self.capabilities() & capability == capability
+    }
+
     get_socket!(gossip, SOCKET_TAG_GOSSIP);
     get_socket!(rpc, SOCKET_TAG_RPC);
     get_socket!(rpc_pubsub, SOCKET_TAG_RPC_PUBSUB);
@@ -595,6 +652,12 @@ impl Sanitize for ContactInfo {
         if self.wallclock >= MAX_WALLCLOCK {
             return Err(SanitizeError::ValueOutOfBounds);
         }
+        if self
+            .region()
+            .is_some_and(|region| region.len() > MAX_REGION_LEN)
+        {
+            return Err(SanitizeError::ValueOutOfBounds);
+        }
         Ok(())
     }
 }
@@ -1078,6 +1141,53 @@ mod tests {
         assert_matches!(node.alpenglow(), None);
     }
 
+    #[test]
+    fn test_region() {
+        let mut node = ContactInfo::new(Keypair::new().pubkey(), 0, 0);
+        assert_eq!(node.region(), None);
+        node.set_region("eu-west".to_string()).unwrap();
+        node.set_region("ap-east".to_string()).unwrap();
+        assert_eq!(node.region(), Some("ap-east"));
+        assert_eq!(
+            node.set_region("x".repeat(MAX_REGION_LEN + 1)),
+            Err(Error::RegionTooLong)
+        );
+
+        let bytes = bincode::serialize(&node).unwrap();
+        let other: ContactInfo = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(other.region(), Some("ap-east"));
+        assert!(other.sanitize().is_ok());
+
+        // Nodes that do not know the extension skip it
+        let node = ContactInfoLite {
+            extensions: vec![TlvRecord {
+                typ: u8::MAX,
+                bytes: vec![1, 2, 3],
+            }],
+            ..bincode::deserialize(&bytes).unwrap()
+        };
+        assert_eq!(ContactInfo::try_from(node).unwrap().region(), None);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let mut node = ContactInfo::new(Keypair::new().pubkey(), 0, 0);
+        assert_eq!(node.capabilities(), 0);
+        assert!(!node.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT));
+        node.set_region("eu-west".to_string()).unwrap();
+        node.set_capabilities(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+        assert!(node.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT));
+
+        let bytes = bincode::serialize(&node).unwrap();
+        let other: ContactInfo = bincode::deserialize(&bytes).unwrap();
+        assert!(other.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT));
+        assert_eq!(other.region(), Some("eu-west"));
+
+        node.set_capabilities(0);
+        assert_eq!(node.capabilities(), 0);
+        assert_eq!(node.region(), Some("eu-west"));
+    }
+
     #[test]
     fn test_check_duplicate() {
         let mut rng = rand::thread_rng();
@@ -0,0 +1,244 @@
+//! Preference for peers close to this node.
+//!
+//! Gossip entrypoints, repair peers and snapshot peers are sampled by stake, which spreads the
+//! load over the cluster but ignores where nodes are. A validator far from the data centers most
+//! of the cluster runs in then mostly talks to peers on other continents. With a peer preference,
+//! the sampling weight of peers declaring the same region as this node, or of peers with a short
+//! round trip time, is multiplied so that they are picked more often. Other peers stay eligible,
+//! so a node is never cut off from the rest of the cluster.
+//!
+//! Regions are declared by the peers themselves and nothing checks them, so only the regions of
+//! the known validators are taken into account. A round trip time is measured by this node.
+//!
+//! Round trip times are measured with the gossip ping and pong messages. Gossip pings the peers it
+//! exchanges messages with anyway; with the latency preference it also probes a few other peers
+//! each round whose round trip time is unknown or old.
+
+use {
+    crate::contact_info::ContactInfo,
+    lru::LruCache,
+    std::{
+        fmt,
+        net::IpAddr,
+        str::FromStr,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// Multiplier of the sampling weight of the peers in the same region as this node
+const SAME_REGION_WEIGHT_MULTIPLIER: u64 = 8;
+/// Round trip times up to which the sampling weight of a peer is multiplied, and by how much
+const LATENCY_WEIGHT_MULTIPLIERS: [(Duration, u64); 3] = [
+    (Duration::from_millis(25), 8),
+    (Duration::from_millis(50), 4),
+    (Duration::from_millis(100), 2),
+];
+/// Round trip times measured longer ago are measured again
+pub(crate) const PEER_RTT_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+/// Round trip times measured longer ago are not used anymore
+const PEER_RTT_TTL: Duration = Duration::from_secs(600);
+const PEER_LATENCIES_CAPACITY: usize = 8192;
+
+/// How peers are preferred over others of the same stake
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PeerPreference {
+    /// Peers are sampled by stake only
+    #[default]
+    Stake,
+    /// Peers declaring the same region as this node are sampled more often
+    Region,
+    /// Peers with a shorter round trip time are sampled more often
+    Latency,
+}
+
+impl PeerPreference {
+    const STAKE: &'static str = "stake";
+    const REGION: &'static str = "region";
+    const LATENCY: &'static str = "latency";
+
+    pub const fn cli_names() -> &'static [&'static str] {
+        &[Self::STAKE, Self::REGION, Self::LATENCY]
+    }
+
+    /// Returns the sampling weight of `peer`, given its stake based `weight`, the region this
+    /// node declared and the round trip time to the peer
+    pub fn peer_weight(
+        &self,
+        weight: u64,
+        peer: &ContactInfo,
+        my_region: Option<&str>,
+        rtt: Option<Duration>,
+    ) -> u64 {
+        let multiplier = match self {
+            Self::Stake => 1,
+            Self::Region => {
+                if my_region.is_some() && peer.region() == my_region {
+                    SAME_REGION_WEIGHT_MULTIPLIER
+                } else {
+                    1
+                }
+            }
+            Self::Latency => rtt
+                .and_then(|rtt| {
+                    LATENCY_WEIGHT_MULTIPLIERS
+                        .iter()
+                        .find(|(max_rtt, _)| rtt <= *max_rtt)
+                })
+                .map(|(_, multiplier)| *multiplier)
+                .unwrap_or(1),
+        };
+        weight.saturating_mul(multiplier)
+    }
+}
+
+impl fmt::Display for PeerPreference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Stake => Self::STAKE,
+            Self::Region => Self::REGION,
+            Self::Latency => Self::LATENCY,
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for PeerPreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::STAKE => Ok(Self::Stake),
+            Self::REGION => Ok(Self::Region),
+            Self::LATENCY => Ok(Self::Latency),
+            _ => Err(format!("invalid peer preference: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PeerRtt {
+    rtt: Duration,
+    measured_at: Instant,
+}
+
+/// Smoothed round trip times to peers, by IP address
+pub struct PeerLatencies {
+    rtts: Mutex<LruCache<IpAddr, PeerRtt>>,
+}
+
+impl Default for PeerLatencies {
+    fn default() -> Self {
+        Self {
+            rtts: Mutex::new(LruCache::new(PEER_LATENCIES_CAPACITY)),
+        }
+    }
+}
+
+impl PeerLatencies {
+    /// Folds a round trip time measured at `now` into the one known for `addr`
+    pub fn record(&self, addr: IpAddr, rtt: Duration, now: Instant) {
+        let mut rtts = self.rtts.lock().unwrap();
+        let rtt = match rtts.peek(&addr) {
+            // Moving average giving a quarter of the weight to the new measurement, so that a
+            // single delayed pong does not make a peer look far away
+            Some(peer_rtt)
+                if now.saturating_duration_since(peer_rtt.measured_at) < PEER_RTT_TTL =>
+            {
+                (peer_rtt.rtt * 3 + rtt) / 4
+            }
+            _ => rtt,
+        };
+        rtts.put(
+            addr,
+            PeerRtt {
+                rtt,
+                measured_at: now,
+            },
+        );
+    }
+
+    /// Returns the round trip time to `addr`, unless it is unknown or too old
+    pub fn rtt(&self, addr: &IpAddr, now: Instant) -> Option<Duration> {
+        self.rtts
+            .lock()
+            .unwrap()
+            .peek(addr)
+            .filter(|peer_rtt| now.saturating_duration_since(peer_rtt.measured_at) < PEER_RTT_TTL)
+            .map(|peer_rtt| peer_rtt.rtt)
+    }
+
+    /// Returns true if the round trip time to `addr` is unknown or should be measured again
+    pub fn should_probe(&self, addr: &IpAddr, now: Instant) -> bool {
+        self.rtts.lock().unwrap().peek(addr).is_none_or(|peer_rtt| {
+            now.saturating_duration_since(peer_rtt.measured_at) >= PEER_RTT_PROBE_INTERVAL
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_pubkey::Pubkey, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_peer_weight() {
+        let mut peer = ContactInfo::new(Pubkey::new_unique(), 0, 0);
+        peer.set_region("eu-west".to_string()).unwrap();
+        let rtt = Some(Duration::from_millis(40));
+
+        assert_eq!(
+            PeerPreference::Stake.peer_weight(10, &peer, Some("eu-west"), rtt),
+            10
+        );
+        assert_eq!(
+            PeerPreference::Region.peer_weight(10, &peer, Some("eu-west"), rtt),
+            80
+        );
+        assert_eq!(
+            PeerPreference::Region.peer_weight(10, &peer, Some("us-east"), rtt),
+            10
+        );
+        assert_eq!(PeerPreference::Region.peer_weight(10, &peer, None, rtt), 10);
+        assert_eq!(
+            PeerPreference::Latency.peer_weight(10, &peer, None, rtt),
+            40
+        );
+        assert_eq!(
+            PeerPreference::Latency.peer_weight(10, &peer, None, Some(Duration::from_secs(1))),
+            10
+        );
+        assert_eq!(
+            PeerPreference::Latency.peer_weight(10, &peer, None, None),
+            10
+        );
+        assert_eq!(PeerPreference::Latency.peer_weight(0, &peer, None, rtt), 0);
+    }
+
+    #[test]
+    fn test_peer_preference_names() {
+        for name in PeerPreference::cli_names() {
+            let preference = PeerPreference::from_str(name).unwrap();
+            assert_eq!(preference.to_string(), *name);
+        }
+        assert!(PeerPreference::from_str("nearest").is_err());
+    }
+
+    #[test]
+    fn test_peer_latencies() {
+        let latencies = PeerLatencies::default();
+        let addr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let now = Instant::now();
+        assert!(latencies.should_probe(&addr, now));
+        assert_eq!(latencies.rtt(&addr, now), None);
+
+        latencies.record(addr, Duration::from_millis(100), now);
+        assert!(!latencies.should_probe(&addr, now));
+        latencies.record(addr, Duration::from_millis(20), now);
+        assert_eq!(latencies.rtt(&addr, now), Some(Duration::from_millis(80)));
+
+        let later = now + PEER_RTT_PROBE_INTERVAL;
+        assert!(latencies.should_probe(&addr, later));
+        assert_eq!(latencies.rtt(&addr, later), Some(Duration::from_millis(80)));
+        assert_eq!(latencies.rtt(&addr, now + PEER_RTT_TTL), None);
+    }
+}
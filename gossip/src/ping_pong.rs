@@ -65,6 +65,8 @@ pub struct PingCache<const N: usize> {
     pongs: LruCache<(Pubkey, SocketAddr), Instant>,
     // Timestamp of last ping message sent to a remote IP.
     ping_times: LruCache<IpAddr, Instant>,
+    // Round trip time of the last pong received from a remote IP.
+    rtts: LruCache<IpAddr, Duration>,
 }
 
 impl<const N: usize> Ping<N> {
@@ -171,6 +173,7 @@ impl<const N: usize> PingCache<N> {
             pings: LruCache::new(cap),
             pongs: LruCache::new(cap),
             ping_times: LruCache::new(cap),
+            rtts: LruCache::new(cap),
         }
     }
 
@@ -188,11 +191,12 @@ impl<const N: usize> PingCache<N> {
         };
         self.pongs.put(remote_node, now);
         if let Some(sent_time) = self.ping_times.pop(&socket.ip()) {
+            let rtt = now.saturating_duration_since(sent_time);
+            self.rtts.put(socket.ip(), rtt);
             if should_report_message_signature(
                 pong.signature(),
                 PONG_SIGNATURE_SAMPLE_LEADING_ZEROS,
             ) {
-                let rtt = now.saturating_duration_since(sent_time);
                 datapoint_info!(
                     "ping_rtt",
                     ("peer_ip", socket.ip().to_string(), String),
@@ -226,6 +230,24 @@ impl<const N: usize> PingCache<N> {
         Some(Ping::new(token, keypair))
     }
 
+    /// Returns the round trip time measured by the last pong received from
+    /// the remote IP address, if not taken already.
+    pub fn take_rtt(&mut self, addr: &IpAddr) -> Option<Duration> {
+        self.rtts.pop(addr)
+    }
+
+    /// Returns a ping message to measure the round trip time to the remote
+    /// node, unless it has been pinged recently.
+    pub fn probe<R: Rng + CryptoRng>(
+        &mut self,
+        rng: &mut R,
+        keypair: &Keypair,
+        now: Instant,
+        remote_node: (Pubkey, SocketAddr),
+    ) -> Option<Ping<N>> {
+        self.maybe_ping(rng, keypair, now, remote_node)
+    }
+
     /// Returns true if the remote node has responded to a ping message.
     /// Removes expired pong messages. In order to extend verifications before
     /// expiration, if the pong message is not too recent, and the node has not
@@ -444,4 +466,28 @@ mod tests {
             assert_eq!(seen_nodes.insert(node), ping.is_some());
         }
     }
+
+    #[test]
+    fn test_ping_cache_rtt() {
+        let mut rng = rand::thread_rng();
+        let ttl = Duration::from_secs(60);
+        let mut cache = PingCache::<32>::new(&mut rng, Instant::now(), ttl, ttl / 4, 1000);
+        let this_node = Keypair::new();
+        let remote_node = Keypair::new();
+        let socket = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 8001));
+        let node = (remote_node.pubkey(), socket);
+
+        let ping = cache
+            .probe(&mut rng, &this_node, Instant::now(), node)
+            .unwrap();
+        // Probes are rate limited like any other ping
+        assert!(cache
+            .probe(&mut rng, &this_node, Instant::now(), node)
+            .is_none());
+
+        let pong = Pong::new(&ping, &remote_node);
+        assert!(cache.add(&pong, socket, Instant::now() + Duration::from_millis(10)));
+        assert!(cache.take_rtt(&socket.ip()).unwrap() >= Duration::from_millis(10));
+        assert_eq!(cache.take_rtt(&socket.ip()), None);
+    }
 }
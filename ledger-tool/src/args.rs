@@ -195,6 +195,7 @@ pub fn parse_process_options(ledger_path: &Path, arg_matches: &ArgMatches<'_>) -
     let log_messages_bytes_limit = value_t!(arg_matches, "log_messages_bytes_limit", usize).ok();
     let runtime_config = RuntimeConfig {
         log_messages_bytes_limit,
+        replay_determinism_check: arg_matches.is_present("replay_determinism_check"),
         ..RuntimeConfig::default()
     };
 
@@ -2,10 +2,16 @@
 
 use {
     crate::{
+        args::{accounts_db_args, load_genesis_arg, parse_process_options, snapshot_args},
+        blockstore_sync::{sync_blockstore, SyncSource},
         error::{LedgerToolError, Result},
         ledger_path::canonicalize_ledger_path,
-        ledger_utils::get_program_ids,
+        ledger_utils::{
+            get_program_ids, load_and_process_ledger_or_exit, open_genesis_config_by,
+            LoadAndProcessLedgerOutput,
+        },
         output::{output_ledger, output_slot, CliDuplicateSlotProof, SlotBounds, SlotInfo},
+        rebroadcast::{rebroadcast_slots, RebroadcastConfig},
     },
     chrono::{DateTime, Utc},
     clap::{
@@ -15,10 +21,16 @@ use {
     log::*,
     regex::Regex,
     serde_json::json,
-    solana_clap_utils::{hidden_unless_forced, input_validators::is_slot},
+    solana_clap_utils::{
+        hidden_unless_forced,
+        input_parsers::{keypair_of, pubkey_of},
+        input_validators::{is_keypair, is_parsable, is_pubkey, is_slot},
+    },
     solana_cli_output::OutputFormat,
     solana_clock::{Slot, UnixTimestamp},
+    solana_core::repair::repair_client::RepairClient,
     solana_hash::Hash,
+    solana_keypair::Keypair,
     solana_ledger::{
         ancestor_iterator::AncestorIterator,
         blockstore::{
@@ -33,8 +45,10 @@ use {
         collections::{BTreeMap, BTreeSet, HashMap},
         fs::File,
         io::{stdout, BufRead, BufReader, Write},
+        net::{IpAddr, SocketAddr},
+        num::NonZeroU64,
         path::{Path, PathBuf},
-        sync::atomic::AtomicBool,
+        sync::{atomic::AtomicBool, Arc},
         time::{Duration, UNIX_EPOCH},
     },
 };
@@ -507,6 +521,56 @@ pub fn blockstore_subcommands<'a, 'b>(hidden: bool) -> Vec<App<'a, 'b>> {
                     .takes_value(false)
                     .help("Limit purging to dead slots only"),
             ),
+        SubCommand::with_name("rebroadcast")
+            .about("Send the shreds of rooted slots to the TVU ports of other nodes")
+            .long_about(
+                "Send the data and coding shreds of rooted slots to the TVU ports of other nodes, \
+                 so that nodes missing those slots during a cluster restart can ingest them. The \
+                 shreds are sent unchanged and are verified by the receivers like any other \
+                 turbine traffic.",
+            )
+            .settings(&hidden)
+            .arg(
+                Arg::with_name("slots")
+                    .index(1)
+                    .value_name("SLOTS")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .multiple(true)
+                    .required(true)
+                    .help("Rooted slots to send"),
+            )
+            .arg(
+                Arg::with_name("target")
+                    .long("target")
+                    .value_name("IP:PORT")
+                    .validator(is_parsable::<SocketAddr>)
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .required(true)
+                    .help(
+                        "TVU address of a node to send the shreds to, as shown by getClusterNodes",
+                    ),
+            )
+            .arg(
+                Arg::with_name("bind_address")
+                    .long("bind-address")
+                    .value_name("IP")
+                    .validator(is_parsable::<IpAddr>)
+                    .takes_value(true)
+                    .default_value("0.0.0.0")
+                    .help("IP address to send the shreds from"),
+            )
+            .arg(
+                Arg::with_name("max_packets_per_second")
+                    .long("max-packets-per-second")
+                    .value_name("NUM")
+                    .validator(is_parsable::<NonZeroU64>)
+                    .takes_value(true)
+                    .default_value("10000")
+                    .help("Maximum number of shreds sent per second, over all targets combined"),
+            ),
         SubCommand::with_name("remove-dead-slot")
             .about("Remove the dead flag for a slot")
             .settings(&hidden)
@@ -581,6 +645,71 @@ pub fn blockstore_subcommands<'a, 'b>(hidden: bool) -> Vec<App<'a, 'b>> {
                     .required(true)
                     .help("Slots to print"),
             ),
+        SubCommand::with_name("sync")
+            .about("Copy the rooted slots missing from the ledger from another ledger or node")
+            .long_about(
+                "Copy the rooted slots between the starting and ending slots that the ledger is \
+                 missing, or only has some of the shreds of, from another ledger or from a node \
+                 serving repair. The data shreds of a slot are only inserted once all of them \
+                 were received and verified to form the same block. A node can only be asked \
+                 for the slots the ledger knows of, but has incomplete, and its shreds must be \
+                 signed by the leaders in the leader schedule of the ledger's snapshot.",
+            )
+            .settings(&hidden)
+            .arg(load_genesis_arg())
+            .args(&accounts_db_args())
+            .args(&snapshot_args())
+            .arg(&starting_slot_arg)
+            .arg(&ending_slot_arg)
+            .arg(
+                Arg::with_name("source_ledger")
+                    .long("source-ledger")
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .required_unless("source_peer")
+                    .conflicts_with("source_peer")
+                    .help("Ledger directory to copy the missing slots from"),
+            )
+            .arg(
+                Arg::with_name("source_peer")
+                    .long("source-peer")
+                    .value_name("IP:PORT")
+                    .validator(is_parsable::<SocketAddr>)
+                    .takes_value(true)
+                    .requires("source_peer_pubkey")
+                    .help(
+                        "Serve repair address of a node to fetch the missing slots from, as shown \
+                         by getClusterNodes",
+                    ),
+            )
+            .arg(
+                Arg::with_name("source_peer_pubkey")
+                    .long("source-peer-pubkey")
+                    .value_name("PUBKEY")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .help("Identity of the node at --source-peer"),
+            )
+            .arg(
+                Arg::with_name("identity")
+                    .long("identity")
+                    .value_name("KEYPAIR")
+                    .validator(is_keypair)
+                    .takes_value(true)
+                    .help(
+                        "Keypair to sign repair requests with. Nodes serve staked identities \
+                         first [default: a new keypair]",
+                    ),
+            )
+            .arg(
+                Arg::with_name("timeout_ms")
+                    .long("timeout-ms")
+                    .value_name("MILLISECONDS")
+                    .validator(is_parsable::<u64>)
+                    .takes_value(true)
+                    .default_value("1000")
+                    .help("Time to wait for each shred requested from --source-peer"),
+            ),
     ]
 }
 
@@ -928,6 +1057,28 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 }
             }
         }
+        ("rebroadcast", Some(arg_matches)) => {
+            let slots = values_t_or_exit!(arg_matches, "slots", Slot);
+            let config = RebroadcastConfig {
+                targets: values_t_or_exit!(arg_matches, "target", SocketAddr),
+                bind_address: value_t_or_exit!(arg_matches, "bind_address", IpAddr),
+                max_packets_per_second: value_t_or_exit!(
+                    arg_matches,
+                    "max_packets_per_second",
+                    NonZeroU64
+                ),
+            };
+            let blockstore =
+                crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            let stats = rebroadcast_slots(&blockstore, &slots, &config)?;
+            println!(
+                "Sent {} data and {} coding shreds of {} slots to {} nodes",
+                stats.data_shreds,
+                stats.coding_shreds,
+                slots.len(),
+                config.targets.len(),
+            );
+        }
         ("remove-dead-slot", Some(arg_matches)) => {
             let slots = values_t_or_exit!(arg_matches, "slots", Slot);
             let blockstore = crate::open_blockstore(
@@ -1041,6 +1192,61 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 )?;
             }
         }
+        ("sync", Some(arg_matches)) => {
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+
+            let target = Arc::new(crate::open_blockstore(
+                &ledger_path,
+                arg_matches,
+                AccessType::PrimaryForMaintenance,
+            ));
+            let source_blockstore;
+            let mut source = if let Some(source_ledger) = arg_matches.value_of("source_ledger") {
+                source_blockstore = crate::open_blockstore(
+                    &PathBuf::from(source_ledger),
+                    arg_matches,
+                    AccessType::Secondary,
+                );
+                SyncSource::Blockstore(&source_blockstore)
+            } else {
+                let peer_addr = value_t_or_exit!(arg_matches, "source_peer", SocketAddr);
+                let peer_id = pubkey_of(arg_matches, "source_peer_pubkey").unwrap();
+                let identity = keypair_of(arg_matches, "identity").unwrap_or_else(Keypair::new);
+                let timeout =
+                    Duration::from_millis(value_t_or_exit!(arg_matches, "timeout_ms", u64));
+                let client = RepairClient::new(Arc::new(identity), peer_id, peer_addr, timeout)?;
+
+                let mut process_options = parse_process_options(&ledger_path, arg_matches);
+                // Only the snapshot bank is needed for the leader schedule
+                process_options.halt_at_slot = Some(0);
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let LoadAndProcessLedgerOutput { bank_forks, .. } = load_and_process_ledger_or_exit(
+                    arg_matches,
+                    &genesis_config,
+                    target.clone(),
+                    process_options,
+                    None,
+                );
+                let bank = bank_forks.read().unwrap().working_bank();
+                SyncSource::Peer { client, bank }
+            };
+
+            let stats = sync_blockstore(&target, &mut source, starting_slot, ending_slot)?;
+            println!("Synced {} slots", stats.synced_slots.len());
+            if !stats.unavailable_slots.is_empty() {
+                println!(
+                    "The source does not have all shreds of slots: {:?}",
+                    stats.unavailable_slots
+                );
+            }
+            if !stats.invalid_slots.is_empty() {
+                println!(
+                    "Shreds failed validation for slots: {:?}",
+                    stats.invalid_slots
+                );
+            }
+        }
         _ => unreachable!(),
     }
     Ok(())
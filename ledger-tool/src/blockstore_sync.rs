@@ -0,0 +1,353 @@
+//! Filling holes in the local blockstore from another blockstore
+//!
+//! A node that was down or fell behind can end up with slots missing from the middle of its
+//! history. Rather than downloading a whole ledger again, the missing slots can be copied from a
+//! blockstore on the same machine, or fetched shred by shred from a node serving the repair
+//! protocol. Every slot is checked before it is inserted: its data shreds must be complete, agree
+//! on the slot's parent, and form a valid chain of Merkle trees, so a slot either arrives whole
+//! or not at all. Shreds from a node must also be signed by the leader of their slot, and are
+//! inserted as untrusted.
+
+use {
+    crate::error::Result,
+    log::*,
+    solana_clock::Slot,
+    solana_core::repair::repair_client::RepairClient,
+    solana_ledger::{
+        blockstore::Blockstore,
+        leader_schedule_cache::LeaderScheduleCache,
+        shred::{Shred, MAX_DATA_SHREDS_PER_SLOT},
+    },
+    solana_pubkey::Pubkey,
+    solana_runtime::bank::Bank,
+    std::{borrow::Cow, collections::BTreeMap, sync::Arc},
+};
+
+pub enum SyncSource<'a> {
+    /// A blockstore on the local filesystem, from which rooted slots are copied
+    Blockstore(&'a Blockstore),
+    /// A node serving the repair protocol, whose shreds are checked against the leader schedule
+    /// of `bank`
+    Peer {
+        client: RepairClient,
+        bank: Arc<Bank>,
+    },
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    pub synced_slots: Vec<Slot>,
+    /// Slots the source does not have in full
+    pub unavailable_slots: Vec<Slot>,
+    /// Slots whose shreds failed validation, or whose leader is not known, and were not inserted
+    pub invalid_slots: Vec<Slot>,
+}
+
+/// Copies the slots from `starting_slot` to `ending_slot` that `target` is missing from `source`
+///
+/// From a local blockstore, every rooted slot that is not full in `target` is copied and rooted in
+/// `target` as well. A peer cannot be asked which slots it rooted, so only the slots `target`
+/// already knows of, because it has some of their shreds, has a child of them or rooted them,
+/// are fetched from a peer. Slots outside the epochs the leader schedule of the peer's bank
+/// covers cannot be verified, and are not fetched.
+pub fn sync_blockstore(
+    target: &Blockstore,
+    source: &mut SyncSource,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<SyncStats> {
+    let leader_schedule_cache = match source {
+        SyncSource::Blockstore(_) => None,
+        SyncSource::Peer { bank, .. } => Some(LeaderScheduleCache::new_from_bank(bank)),
+    };
+    let mut stats = SyncStats::default();
+    for slot in missing_slots(target, source, starting_slot, ending_slot)? {
+        let (shreds, leader) = match source {
+            SyncSource::Blockstore(source) => {
+                if !is_full(source, slot)? {
+                    stats.unavailable_slots.push(slot);
+                    continue;
+                }
+                (source.get_data_shreds_for_slot(slot, 0)?, None)
+            }
+            SyncSource::Peer { client, bank } => {
+                let Some(leader) = leader_schedule_cache
+                    .as_ref()
+                    .and_then(|cache| cache.slot_leader_at(slot, Some(bank)))
+                else {
+                    warn!("Not fetching slot {slot}: its leader is not known");
+                    stats.invalid_slots.push(slot);
+                    continue;
+                };
+                match fetch_slot(client, slot)? {
+                    Some(shreds) => (shreds, Some(leader)),
+                    None => {
+                        stats.unavailable_slots.push(slot);
+                        continue;
+                    }
+                }
+            }
+        };
+        if let Err(err) = validate_slot_shreds(slot, leader.as_ref(), &shreds) {
+            warn!("Not inserting slot {slot}: {err}");
+            stats.invalid_slots.push(slot);
+            continue;
+        }
+
+        // Only shreds copied from a local blockstore are trusted
+        let is_trusted = leader.is_none();
+        target.insert_cow_shreds(
+            shreds.into_iter().map(Cow::Owned),
+            leader_schedule_cache.as_ref(),
+            is_trusted,
+        )?;
+        if !is_full(target, slot)? {
+            warn!("Slot {slot} is not full after inserting all of its shreds");
+            stats.invalid_slots.push(slot);
+            continue;
+        }
+        if let SyncSource::Blockstore(_) = source {
+            target.set_roots(std::iter::once(&slot))?;
+        }
+        info!("Synced slot {slot}");
+        stats.synced_slots.push(slot);
+    }
+    Ok(stats)
+}
+
+fn is_full(blockstore: &Blockstore, slot: Slot) -> Result<bool> {
+    Ok(blockstore.meta(slot)?.is_some_and(|meta| meta.is_full()))
+}
+
+fn missing_slots(
+    target: &Blockstore,
+    source: &SyncSource,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<Vec<Slot>> {
+    let mut slots = Vec::new();
+    match source {
+        SyncSource::Blockstore(source) => {
+            for slot in source.rooted_slot_iterator(starting_slot)? {
+                if slot > ending_slot {
+                    break;
+                }
+                if !is_full(target, slot)? {
+                    slots.push(slot);
+                }
+            }
+        }
+        SyncSource::Peer { .. } => {
+            for (slot, meta) in target.slot_meta_iterator(starting_slot)? {
+                if slot > ending_slot {
+                    break;
+                }
+                if !meta.is_full() {
+                    slots.push(slot);
+                }
+            }
+            for slot in target.rooted_slot_iterator(starting_slot)? {
+                if slot > ending_slot {
+                    break;
+                }
+                if target.meta(slot)?.is_none() {
+                    slots.push(slot);
+                }
+            }
+            slots.sort_unstable();
+        }
+    }
+    Ok(slots)
+}
+
+/// Requests the data shreds of `slot` one by one until the last one, returning `None` if the peer
+/// does not have one of them, or never marks one as the last
+fn fetch_slot(client: &mut RepairClient, slot: Slot) -> Result<Option<Vec<Shred>>> {
+    let mut shreds = Vec::new();
+    for index in 0..MAX_DATA_SHREDS_PER_SLOT as u64 {
+        let Some(shred) = client.request_data_shred(slot, index)? else {
+            return Ok(None);
+        };
+        let last_in_slot = shred.last_in_slot();
+        shreds.push(shred);
+        if last_in_slot {
+            return Ok(Some(shreds));
+        }
+    }
+    warn!("Slot {slot} has no last shred within {MAX_DATA_SHREDS_PER_SLOT} data shreds");
+    Ok(None)
+}
+
+/// Checks that `shreds` are all the data shreds of a single block of `slot`, signed by `leader`
+/// if it is given
+fn validate_slot_shreds(
+    slot: Slot,
+    leader: Option<&Pubkey>,
+    shreds: &[Shred],
+) -> std::result::Result<(), String> {
+    let Some(first_shred) = shreds.first() else {
+        return Err("no shreds".to_string());
+    };
+    let parent = first_shred
+        .parent()
+        .map_err(|err| format!("shred 0 has no parent: {err}"))?;
+
+    // Merkle root of each erasure set, and the root the set chains to
+    let mut erasure_sets = BTreeMap::new();
+    for (expected_index, shred) in shreds.iter().enumerate() {
+        if shred.slot() != slot || !shred.is_data() {
+            return Err(format!("unexpected shred {:?}", shred.id()));
+        }
+        if shred.index() as usize != expected_index {
+            return Err(format!("shred {expected_index} is missing"));
+        }
+        if shred.last_in_slot() != (expected_index + 1 == shreds.len()) {
+            return Err(format!(
+                "shred {expected_index} of {} is marked as the last one",
+                shreds.len()
+            ));
+        }
+        shred
+            .sanitize()
+            .map_err(|err| format!("shred {expected_index} is malformed: {err}"))?;
+        if let Some(leader) = leader {
+            if !shred.verify(leader) {
+                return Err(format!(
+                    "shred {expected_index} is not signed by the slot leader {leader}"
+                ));
+            }
+        }
+        if shred.parent().ok() != Some(parent) {
+            return Err(format!("shred {expected_index} has a different parent"));
+        }
+        let merkle_root = shred
+            .merkle_root()
+            .map_err(|err| format!("shred {expected_index} has no merkle root: {err}"))?;
+        let chained_merkle_root = shred.chained_merkle_root().ok();
+        let erasure_set = erasure_sets.entry(shred.fec_set_index()).or_insert((
+            merkle_root,
+            chained_merkle_root,
+            *shred.signature(),
+        ));
+        if *erasure_set != (merkle_root, chained_merkle_root, *shred.signature()) {
+            return Err(format!(
+                "shred {expected_index} does not belong to erasure set {}",
+                shred.fec_set_index()
+            ));
+        }
+    }
+
+    for ((_, (merkle_root, _, _)), (fec_set_index, (_, chained_merkle_root, _))) in
+        erasure_sets.iter().zip(erasure_sets.iter().skip(1))
+    {
+        if let Some(chained_merkle_root) = chained_merkle_root {
+            if chained_merkle_root != merkle_root {
+                return Err(format!(
+                    "erasure set {fec_set_index} does not chain to the previous one"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::create_ticks,
+        solana_hash::Hash,
+        solana_keypair::Keypair,
+        solana_ledger::{
+            blockstore::entries_to_test_shreds,
+            get_tmp_ledger_path_auto_delete,
+            shred::{ProcessShredsStats, ReedSolomonCache, Shredder},
+        },
+        solana_signer::Signer,
+    };
+
+    fn slot_shreds(slot: Slot, parent: Slot) -> Vec<Shred> {
+        let entries = create_ticks(64, 1, Hash::default());
+        entries_to_test_shreds(&entries, slot, parent, true, 0)
+    }
+
+    fn signed_slot_shreds(slot: Slot, parent: Slot, leader: &Keypair) -> Vec<Shred> {
+        let entries = create_ticks(64, 1, Hash::default());
+        Shredder::new(slot, parent, 0, 0)
+            .unwrap()
+            .make_merkle_shreds_from_entries(
+                leader,
+                &entries,
+                true,
+                Hash::default(),
+                0,
+                0,
+                &ReedSolomonCache::default(),
+                &mut ProcessShredsStats::default(),
+            )
+            .filter(Shred::is_data)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_slot_shreds() {
+        let shreds = slot_shreds(3, 2);
+        assert!(shreds.len() > 1);
+        assert_eq!(validate_slot_shreds(3, None, &shreds), Ok(()));
+        assert!(validate_slot_shreds(4, None, &shreds).is_err());
+        assert!(validate_slot_shreds(3, None, &shreds[..shreds.len() - 1]).is_err());
+        assert!(validate_slot_shreds(3, None, &shreds[1..]).is_err());
+        assert!(validate_slot_shreds(3, None, &[]).is_err());
+
+        // Shreds of a different block of the same slot
+        let mut mixed = shreds.clone();
+        mixed[0] = slot_shreds(3, 1).swap_remove(0);
+        assert!(validate_slot_shreds(3, None, &mixed).is_err());
+    }
+
+    #[test]
+    fn test_validate_slot_shreds_leader() {
+        let leader = Keypair::new();
+        let shreds = signed_slot_shreds(3, 2, &leader);
+        assert_eq!(
+            validate_slot_shreds(3, Some(&leader.pubkey()), &shreds),
+            Ok(())
+        );
+        assert!(validate_slot_shreds(3, Some(&Keypair::new().pubkey()), &shreds).is_err());
+    }
+
+    #[test]
+    fn test_sync_blockstore() {
+        let source_path = get_tmp_ledger_path_auto_delete!();
+        let target_path = get_tmp_ledger_path_auto_delete!();
+        let source = Blockstore::open(source_path.path()).unwrap();
+        let target = Blockstore::open(target_path.path()).unwrap();
+
+        for (slot, parent) in [(1, 0), (2, 1), (4, 2), (5, 4)] {
+            source
+                .insert_shreds(slot_shreds(slot, parent), None, true)
+                .unwrap();
+        }
+        source.set_roots([1, 2, 4].iter()).unwrap();
+        // The target is missing slot 2
+        for (slot, parent) in [(1, 0), (4, 2)] {
+            target
+                .insert_shreds(slot_shreds(slot, parent), None, true)
+                .unwrap();
+        }
+
+        let stats =
+            sync_blockstore(&target, &mut SyncSource::Blockstore(&source), 1, Slot::MAX).unwrap();
+        // Slot 5 is not rooted in the source
+        assert_eq!(
+            stats,
+            SyncStats {
+                synced_slots: vec![2],
+                ..SyncStats::default()
+            }
+        );
+        assert!(is_full(&target, 2).unwrap());
+        assert!(target.is_root(2));
+        assert!(!is_full(&target, 5).unwrap());
+    }
+}
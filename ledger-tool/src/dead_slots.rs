@@ -0,0 +1,347 @@
+//! Analysis of dead slots
+//!
+//! The blockstore only records that a slot is dead, not why replay gave up on it. The checks here
+//! re-examine the shreds and entries of each dead slot to find the likely cause, so that incidents
+//! can be investigated from the ledger of any node.
+
+use {
+    crate::error::Result,
+    agave_feature_set as feature_set,
+    serde::Serialize,
+    solana_cli_output::{QuietDisplay, VerboseDisplay},
+    solana_clock::Slot,
+    solana_entry::entry::{Entry, EntrySlice},
+    solana_hash::Hash,
+    solana_ledger::{
+        block_error::BlockError, blockstore::Blockstore, leader_schedule_cache::LeaderScheduleCache,
+    },
+    solana_runtime::bank::Bank,
+    std::{
+        collections::{btree_map::Entry as BTreeMapEntry, BTreeMap},
+        fmt::{self, Display, Formatter},
+    },
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeadSlotCause {
+    /// A proof that the leader produced two versions of the slot was stored
+    DuplicateProof,
+    /// The merkle roots of the slot's shreds do not chain together
+    InvalidShredChaining(String),
+    /// The slot's shreds could not be deserialized into entries
+    InvalidEntries(String),
+    /// The entries violate the tick or PoH rules of a block
+    InvalidBlock(BlockError),
+}
+
+impl Display for DeadSlotCause {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::DuplicateProof => write!(f, "duplicate proof"),
+            Self::InvalidShredChaining(detail) => write!(f, "bad shred chaining: {detail}"),
+            Self::InvalidEntries(detail) => write!(f, "invalid entries: {detail}"),
+            Self::InvalidBlock(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliDeadSlot {
+    pub slot: Slot,
+    pub parent_slot: Option<Slot>,
+    pub leader: Option<String>,
+    pub is_full: bool,
+    pub causes: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliDeadSlots {
+    pub dead_slots: Vec<CliDeadSlot>,
+}
+
+impl QuietDisplay for CliDeadSlots {}
+impl VerboseDisplay for CliDeadSlots {}
+
+impl Display for CliDeadSlots {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>12} {:>12} {:<44} {:>5}  Causes",
+            "Slot", "Parent", "Leader", "Full"
+        )?;
+        for dead_slot in &self.dead_slots {
+            let causes = if dead_slot.causes.is_empty() {
+                "unknown, likely a transaction or replay error".to_string()
+            } else {
+                dead_slot.causes.join(", ")
+            };
+            writeln!(
+                f,
+                "{:>12} {:>12} {:<44} {:>5}  {causes}",
+                dead_slot.slot,
+                dead_slot
+                    .parent_slot
+                    .map(|slot| slot.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                dead_slot.leader.as_deref().unwrap_or("unknown"),
+                dead_slot.is_full,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Analyzes the dead slots in `[starting_slot, ending_slot]`
+///
+/// `bank` provides the tick parameters and the leader schedule. Leaders are only known for slots
+/// in the epochs covered by the leader schedules of `bank`.
+pub fn analyze_dead_slots(
+    blockstore: &Blockstore,
+    bank: &Bank,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<CliDeadSlots> {
+    let leader_schedule_cache = LeaderScheduleCache::new_from_bank(bank);
+    let mut dead_slots = Vec::new();
+    for slot in blockstore.dead_slots_iterator(starting_slot)? {
+        if slot > ending_slot {
+            break;
+        }
+        let meta = blockstore.meta(slot)?;
+        let causes = dead_slot_causes(blockstore, bank, slot)?;
+        dead_slots.push(CliDeadSlot {
+            slot,
+            parent_slot: meta.as_ref().and_then(|meta| meta.parent_slot),
+            leader: leader_schedule_cache
+                .slot_leader_at(slot, Some(bank))
+                .map(|leader| leader.to_string()),
+            is_full: meta.is_some_and(|meta| meta.is_full()),
+            causes: causes.iter().map(ToString::to_string).collect(),
+        });
+    }
+    Ok(CliDeadSlots { dead_slots })
+}
+
+/// Returns every cause found for `slot` being dead
+///
+/// An empty result means the shreds and entries of the slot look valid, so replay most likely
+/// failed while executing its transactions.
+pub fn dead_slot_causes(
+    blockstore: &Blockstore,
+    bank: &Bank,
+    slot: Slot,
+) -> Result<Vec<DeadSlotCause>> {
+    let mut causes = Vec::new();
+    if blockstore.get_duplicate_slot(slot).is_some() {
+        causes.push(DeadSlotCause::DuplicateProof);
+    }
+
+    let parent_slot = blockstore.meta(slot)?.and_then(|meta| meta.parent_slot);
+    if let Some(detail) = check_shred_chaining(blockstore, slot, parent_slot)? {
+        causes.push(DeadSlotCause::InvalidShredChaining(detail));
+    }
+
+    let (entries, slot_full) = match blockstore.get_slot_entries_with_shred_info(slot, 0, true) {
+        Ok((entries, _num_shreds, slot_full)) => (entries, slot_full),
+        Err(err) => {
+            causes.push(DeadSlotCause::InvalidEntries(err.to_string()));
+            return Ok(causes);
+        }
+    };
+    let Some(parent_slot) = parent_slot else {
+        return Ok(causes);
+    };
+    if let Some(err) = check_ticks(bank, &entries, slot, parent_slot, slot_full) {
+        causes.push(DeadSlotCause::InvalidBlock(err));
+    }
+    if let Some(start_hash) = last_entry_hash(blockstore, parent_slot) {
+        if !verify_entry_hashes(&entries, start_hash) {
+            causes.push(DeadSlotCause::InvalidBlock(BlockError::InvalidEntryHash));
+        }
+    }
+    Ok(causes)
+}
+
+/// Checks that the first FEC set of `slot` chains to the last FEC set of its parent and that every
+/// following FEC set chains to the one before it
+///
+/// FEC sets whose predecessor is not (completely) in the blockstore are not checked.
+fn check_shred_chaining(
+    blockstore: &Blockstore,
+    slot: Slot,
+    parent_slot: Option<Slot>,
+) -> Result<Option<String>> {
+    // merkle root, chained merkle root and last data shred index of each FEC set
+    let mut fec_sets = BTreeMap::<u32, (Hash, Hash, u32)>::new();
+    for shred in blockstore.get_data_shreds_for_slot(slot, 0)? {
+        let (Ok(merkle_root), Ok(chained_merkle_root)) =
+            (shred.merkle_root(), shred.chained_merkle_root())
+        else {
+            // Shreds without chained merkle roots can not be checked
+            return Ok(None);
+        };
+        match fec_sets.entry(shred.fec_set_index()) {
+            BTreeMapEntry::Vacant(entry) => {
+                entry.insert((merkle_root, chained_merkle_root, shred.index()));
+            }
+            BTreeMapEntry::Occupied(mut entry) => {
+                if entry.get().0 != merkle_root {
+                    return Ok(Some(format!(
+                        "shreds of FEC set {} have different merkle roots",
+                        shred.fec_set_index()
+                    )));
+                }
+                entry.get_mut().2 = shred.index();
+            }
+        }
+    }
+
+    // merkle root of the previous FEC set and the index at which the next FEC set starts
+    let mut previous = parent_slot
+        .filter(|parent_slot| blockstore.is_full(*parent_slot))
+        .and_then(|parent_slot| {
+            let shreds = blockstore.get_data_shreds_for_slot(parent_slot, 0).ok()?;
+            shreds.last()?.merkle_root().ok()
+        })
+        .map(|merkle_root| (merkle_root, 0));
+    for (fec_set_index, (merkle_root, chained_merkle_root, last_index)) in fec_sets {
+        if let Some((previous_merkle_root, next_fec_set_index)) = previous {
+            if next_fec_set_index == fec_set_index && previous_merkle_root != chained_merkle_root {
+                return Ok(Some(if fec_set_index == 0 {
+                    format!(
+                        "first FEC set does not chain to parent slot {}",
+                        parent_slot.unwrap()
+                    )
+                } else {
+                    format!("FEC set {fec_set_index} does not chain to the previous FEC set")
+                }));
+            }
+        }
+        previous = Some((merkle_root, last_index + 1));
+    }
+    Ok(None)
+}
+
+/// Mirrors the tick checks of block verification, using the tick parameters of `bank`
+fn check_ticks(
+    bank: &Bank,
+    entries: &[Entry],
+    slot: Slot,
+    parent_slot: Slot,
+    slot_full: bool,
+) -> Option<BlockError> {
+    let first_alpenglow_slot = bank
+        .feature_set
+        .activated_slot(&feature_set::alpenglow::id());
+    if first_alpenglow_slot.is_some_and(|first_alpenglow_slot| slot >= first_alpenglow_slot) {
+        // Ticks carry no meaning once alpenglow is active
+        return None;
+    }
+
+    let tick_count = entries.tick_count();
+    let max_tick_count = slot.saturating_sub(parent_slot) * bank.ticks_per_slot();
+    if tick_count > max_tick_count {
+        return Some(BlockError::TooManyTicks);
+    }
+    if tick_count < max_tick_count && slot_full {
+        return Some(BlockError::TooFewTicks);
+    }
+    if tick_count == max_tick_count {
+        if entries.last().is_some_and(|entry| !entry.is_tick()) {
+            return Some(BlockError::TrailingEntry);
+        }
+        if !slot_full {
+            return Some(BlockError::InvalidLastTick);
+        }
+    }
+
+    let hashes_per_tick = bank.hashes_per_tick().unwrap_or(0);
+    (!entries.verify_tick_hash_count(&mut 0, hashes_per_tick))
+        .then_some(BlockError::InvalidTickHashCount)
+}
+
+fn last_entry_hash(blockstore: &Blockstore, slot: Slot) -> Option<Hash> {
+    if !blockstore.is_full(slot) {
+        return None;
+    }
+    let entries = blockstore.get_slot_entries(slot, 0).ok()?;
+    entries.last().map(|entry| entry.hash)
+}
+
+fn verify_entry_hashes(entries: &[Entry], mut start_hash: Hash) -> bool {
+    entries.iter().all(|entry| {
+        let valid = entry.verify(&start_hash);
+        start_hash = entry.hash;
+        valid
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::create_ticks,
+        solana_ledger::{
+            blockstore::entries_to_test_shreds,
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            get_tmp_ledger_path_auto_delete,
+        },
+    };
+
+    #[test]
+    fn test_dead_slot_causes() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(1_000_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let ticks_per_slot = bank.ticks_per_slot();
+        let hashes_per_tick = bank.hashes_per_tick().unwrap_or(0);
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let insert_slot = |slot, parent_slot, entries: &[Entry]| {
+            let shreds = entries_to_test_shreds(entries, slot, parent_slot, true, 0);
+            blockstore.insert_shreds(shreds, None, true).unwrap();
+        };
+
+        let parent_entries = create_ticks(ticks_per_slot, hashes_per_tick, Hash::default());
+        insert_slot(0, 0, &parent_entries);
+        let last_hash = parent_entries.last().unwrap().hash;
+
+        // Valid entries, but the test shreds have a random chained merkle root
+        insert_slot(
+            1,
+            0,
+            &create_ticks(ticks_per_slot, hashes_per_tick, last_hash),
+        );
+        let causes = dead_slot_causes(&blockstore, &bank, 1).unwrap();
+        assert_eq!(causes.len(), 1);
+        assert!(matches!(causes[0], DeadSlotCause::InvalidShredChaining(_)));
+
+        // One tick short, and not chained to the parent's entries either
+        insert_slot(
+            2,
+            0,
+            &create_ticks(2 * ticks_per_slot - 1, hashes_per_tick, Hash::new_unique()),
+        );
+        let causes = dead_slot_causes(&blockstore, &bank, 2).unwrap();
+        assert_eq!(causes.len(), 3);
+        assert_eq!(
+            causes[1],
+            DeadSlotCause::InvalidBlock(BlockError::TooFewTicks)
+        );
+        assert_eq!(
+            causes[2],
+            DeadSlotCause::InvalidBlock(BlockError::InvalidEntryHash)
+        );
+
+        blockstore.set_dead_slot(1).unwrap();
+        blockstore.set_dead_slot(2).unwrap();
+        let dead_slots = analyze_dead_slots(&blockstore, &bank, 0, 1).unwrap();
+        assert_eq!(dead_slots.dead_slots.len(), 1);
+        assert_eq!(dead_slots.dead_slots[0].slot, 1);
+        assert_eq!(dead_slots.dead_slots[0].parent_slot, Some(0));
+        assert!(dead_slots.dead_slots[0].leader.is_some());
+        assert!(dead_slots.dead_slots[0].is_full);
+    }
+}
@@ -0,0 +1,220 @@
+//! Structured description of the forks replayed by ledger-tool
+//!
+//! The Graphviz rendering written by `graph` is meant to be looked at. The same information, the
+//! votes and stake on each slot and the dead slots next to the forks, is collected here so that it
+//! can also be written as JSON and processed by scripts.
+
+use {
+    crate::{
+        dead_slots::{analyze_dead_slots, CliDeadSlot},
+        error::Result,
+    },
+    serde::Serialize,
+    solana_clock::{Epoch, Slot},
+    solana_ledger::blockstore::Blockstore,
+    solana_pubkey::Pubkey,
+    solana_runtime::bank_forks::BankForks,
+    solana_vote::vote_state_view::VoteStateView,
+    std::collections::{HashMap, HashSet},
+};
+
+/// Latest vote of a validator across all forks
+pub struct LastVote {
+    pub slot: Slot,
+    pub vote_state_view: VoteStateView,
+    pub stake: u64,
+    /// Total stake of the fork the vote was found on
+    pub total_stake: u64,
+}
+
+/// Returns the slots of the frozen banks that no other frozen bank descends from
+pub fn fork_tips(bank_forks: &BankForks) -> HashSet<Slot> {
+    let mut fork_tips: HashSet<_> = bank_forks
+        .frozen_banks()
+        .map(|(slot, _bank)| slot)
+        .collect();
+    for (_, bank) in bank_forks.frozen_banks() {
+        for parent in bank.parents() {
+            fork_tips.remove(&parent.slot());
+        }
+    }
+    fork_tips
+}
+
+/// Searches the forks ending at `fork_tips` for the last vote made by each validator
+pub fn last_votes(bank_forks: &BankForks, fork_tips: &HashSet<Slot>) -> HashMap<Pubkey, LastVote> {
+    let mut last_votes = HashMap::new();
+    for fork_tip in fork_tips {
+        let bank = &bank_forks[*fork_tip];
+
+        let total_stake = bank
+            .vote_accounts()
+            .iter()
+            .map(|(_, (stake, _))| stake)
+            .sum();
+        for (stake, vote_account) in bank.vote_accounts().values() {
+            let vote_state_view = vote_account.vote_state_view();
+            if let Some(last_vote) = vote_state_view.last_voted_slot() {
+                let new_last_vote = || LastVote {
+                    slot: last_vote,
+                    vote_state_view: vote_state_view.clone(),
+                    stake: *stake,
+                    total_stake,
+                };
+                let entry = last_votes
+                    .entry(*vote_state_view.node_pubkey())
+                    .or_insert_with(new_last_vote);
+                if entry.slot < last_vote {
+                    *entry = new_last_vote();
+                }
+            }
+        }
+    }
+    last_votes
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliForkGraphSlot {
+    pub slot: Slot,
+    pub parent_slot: Option<Slot>,
+    pub epoch: Epoch,
+    pub leader: String,
+    /// Transactions processed in the slot, unknown if the parent bank was pruned
+    pub transaction_count: Option<u64>,
+    pub is_fork_tip: bool,
+    /// Number and stake of the validators whose latest vote is for this slot
+    pub latest_vote_count: usize,
+    pub latest_vote_stake: u64,
+    /// Number and stake of the validators whose latest vote tower holds this slot
+    pub vote_count: usize,
+    pub voted_stake: u64,
+    /// Total stake of the slot's epoch
+    pub total_stake: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliForkGraph {
+    pub starting_slot: Slot,
+    pub ending_slot: Slot,
+    /// Frozen banks in the range, by slot
+    pub slots: Vec<CliForkGraphSlot>,
+    /// Slots in the range that replay marked dead
+    pub dead_slots: Vec<CliDeadSlot>,
+}
+
+/// Describes the frozen banks of `bank_forks` and the dead slots of `blockstore` in
+/// `[starting_slot, ending_slot]`
+pub fn fork_graph(
+    bank_forks: &BankForks,
+    blockstore: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<CliForkGraph> {
+    let fork_tips = fork_tips(bank_forks);
+    let last_votes = last_votes(bank_forks, &fork_tips);
+
+    // Number and stake of the validators voting for each slot
+    let mut latest_votes = HashMap::<Slot, (usize, u64)>::new();
+    let mut tower_votes = HashMap::<Slot, (usize, u64)>::new();
+    for last_vote in last_votes.values() {
+        let entry = latest_votes.entry(last_vote.slot).or_default();
+        entry.0 += 1;
+        entry.1 += last_vote.stake;
+        for vote in last_vote.vote_state_view.votes_iter() {
+            let entry = tower_votes.entry(vote.slot()).or_default();
+            entry.0 += 1;
+            entry.1 += last_vote.stake;
+        }
+    }
+
+    let mut slots: Vec<_> = bank_forks
+        .frozen_banks()
+        .filter(|(slot, _bank)| (starting_slot..=ending_slot).contains(slot))
+        .map(|(slot, bank)| {
+            let (latest_vote_count, latest_vote_stake) =
+                latest_votes.get(&slot).copied().unwrap_or_default();
+            let (vote_count, voted_stake) = tower_votes.get(&slot).copied().unwrap_or_default();
+            CliForkGraphSlot {
+                slot,
+                parent_slot: (slot != 0).then(|| bank.parent_slot()),
+                epoch: bank.epoch(),
+                leader: bank.collector_id().to_string(),
+                transaction_count: bank
+                    .parent()
+                    .map(|parent| bank.transaction_count() - parent.transaction_count()),
+                is_fork_tip: fork_tips.contains(&slot),
+                latest_vote_count,
+                latest_vote_stake,
+                vote_count,
+                voted_stake,
+                total_stake: bank.get_current_epoch_total_stake(),
+            }
+        })
+        .collect();
+    slots.sort_unstable_by_key(|slot| slot.slot);
+
+    let dead_slots = analyze_dead_slots(
+        blockstore,
+        &bank_forks.root_bank(),
+        starting_slot,
+        ending_slot,
+    )?
+    .dead_slots;
+
+    Ok(CliForkGraph {
+        starting_slot,
+        ending_slot,
+        slots,
+        dead_slots,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::{
+            genesis_utils::{create_genesis_config, GenesisConfigInfo},
+            get_tmp_ledger_path_auto_delete,
+        },
+        solana_runtime::bank::Bank,
+    };
+
+    #[test]
+    fn test_fork_graph() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(1_000_000);
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
+        bank_forks.read().unwrap().root_bank().freeze();
+        // 0 - 1 - 2
+        //   \ 3
+        for (slot, parent_slot) in [(1, 0), (2, 1), (3, 0)] {
+            let parent = bank_forks.read().unwrap().get(parent_slot).unwrap();
+            let bank = Bank::new_from_parent(parent, &Pubkey::default(), slot);
+            bank.freeze();
+            bank_forks.write().unwrap().insert(bank);
+        }
+        let bank_forks = bank_forks.read().unwrap();
+        assert_eq!(fork_tips(&bank_forks), HashSet::from([2, 3]));
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore.set_dead_slot(4).unwrap();
+        blockstore.set_dead_slot(5).unwrap();
+
+        let graph = fork_graph(&bank_forks, &blockstore, 1, 4).unwrap();
+        let slots: Vec<_> = graph
+            .slots
+            .iter()
+            .map(|slot| (slot.slot, slot.parent_slot, slot.is_fork_tip))
+            .collect();
+        assert_eq!(
+            slots,
+            vec![(1, Some(0), false), (2, Some(1), true), (3, Some(0), true)]
+        );
+        assert_eq!(graph.slots[0].transaction_count, Some(0));
+        assert_eq!(graph.dead_slots.len(), 1);
+        assert_eq!(graph.dead_slots[0].slot, 4);
+    }
+}
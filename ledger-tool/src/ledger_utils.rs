@@ -29,7 +29,7 @@ use {
     },
     solana_measure::measure_time,
     solana_pubkey::Pubkey,
-    solana_rpc::transaction_status_service::TransactionStatusService,
+    solana_rpc::transaction_status_service::{TransactionHistorySwitch, TransactionStatusService},
     solana_runtime::{
         accounts_background_service::{
             AbsRequestHandlers, AccountsBackgroundService, PendingSnapshotPackages,
@@ -132,7 +132,86 @@ pub fn load_and_process_ledger(
     process_options: ProcessOptions,
     transaction_status_sender: Option<TransactionStatusSender>,
 ) -> Result<LoadAndProcessLedgerOutput, LoadAndProcessLedgerError> {
-    let mut starting_slot = 0; // default start check with genesis
+    load_and_process_ledger_from(
+        arg_matches,
+        genesis_config,
+        blockstore,
+        process_options,
+        transaction_status_sender,
+        None,
+    )
+}
+
+/// Loads and processes the ledger like [`load_and_process_ledger_or_exit`], but from the
+/// snapshot archives in `snapshot_archives_dir` rather than those given on the command line
+pub(crate) fn load_and_process_ledger_from_snapshot_archives_or_exit(
+    arg_matches: &ArgMatches,
+    genesis_config: &GenesisConfig,
+    blockstore: Arc<Blockstore>,
+    process_options: ProcessOptions,
+    transaction_status_sender: Option<TransactionStatusSender>,
+    snapshot_archives_dir: &Path,
+) -> LoadAndProcessLedgerOutput {
+    load_and_process_ledger_from(
+        arg_matches,
+        genesis_config,
+        blockstore,
+        process_options,
+        transaction_status_sender,
+        Some(snapshot_archives_dir),
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Exiting. Failed to load and process ledger: {err}");
+        exit(1);
+    })
+}
+
+/// The full and incremental snapshot archive directories given on the command line
+pub(crate) fn snapshot_archives_dirs(
+    arg_matches: &ArgMatches,
+    ledger_path: &Path,
+) -> (PathBuf, PathBuf) {
+    let snapshots_dir = arg_matches
+        .value_of("snapshots")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| ledger_path.to_path_buf());
+    let full_snapshot_archives_dir = arg_matches
+        .value_of("full_snapshot_archive_path")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| snapshots_dir.clone());
+    let incremental_snapshot_archives_dir = arg_matches
+        .value_of("incremental_snapshot_archive_path")
+        .map(PathBuf::from)
+        .unwrap_or(snapshots_dir);
+    (
+        full_snapshot_archives_dir,
+        incremental_snapshot_archives_dir,
+    )
+}
+
+/// The slot of the highest snapshot archive in the directories, which processing starts from
+pub(crate) fn highest_snapshot_archive_slot(
+    full_snapshot_archives_dir: &Path,
+    incremental_snapshot_archives_dir: &Path,
+) -> Option<Slot> {
+    let full_snapshot_slot =
+        snapshot_paths::get_highest_full_snapshot_archive_slot(full_snapshot_archives_dir)?;
+    let incremental_snapshot_slot = snapshot_paths::get_highest_incremental_snapshot_archive_slot(
+        incremental_snapshot_archives_dir,
+        full_snapshot_slot,
+    )
+    .unwrap_or_default();
+    Some(std::cmp::max(full_snapshot_slot, incremental_snapshot_slot))
+}
+
+fn load_and_process_ledger_from(
+    arg_matches: &ArgMatches,
+    genesis_config: &GenesisConfig,
+    blockstore: Arc<Blockstore>,
+    process_options: ProcessOptions,
+    transaction_status_sender: Option<TransactionStatusSender>,
+    snapshot_archives_dir: Option<&Path>,
+) -> Result<LoadAndProcessLedgerOutput, LoadAndProcessLedgerError> {
     let snapshot_config = {
         let snapshots_dir = arg_matches
             .value_of("snapshots")
@@ -146,26 +225,15 @@ pub fn load_and_process_ledger(
                 .join(LEDGER_TOOL_DIRECTORY)
                 .join(BANK_SNAPSHOTS_DIR)
         };
-        let full_snapshot_archives_dir = arg_matches
-            .value_of("full_snapshot_archive_path")
-            .map(PathBuf::from)
-            .unwrap_or_else(|| snapshots_dir.clone());
-        let incremental_snapshot_archives_dir = arg_matches
-            .value_of("incremental_snapshot_archive_path")
-            .map(PathBuf::from)
-            .unwrap_or_else(|| snapshots_dir.clone());
-        if let Some(full_snapshot_slot) =
-            snapshot_paths::get_highest_full_snapshot_archive_slot(&full_snapshot_archives_dir)
-        {
-            let incremental_snapshot_slot =
-                snapshot_paths::get_highest_incremental_snapshot_archive_slot(
-                    &incremental_snapshot_archives_dir,
-                    full_snapshot_slot,
-                )
-                .unwrap_or_default();
-            starting_slot = std::cmp::max(full_snapshot_slot, incremental_snapshot_slot);
-        }
-        let usage = if arg_matches.is_present("no_snapshot") {
+        let (full_snapshot_archives_dir, incremental_snapshot_archives_dir) =
+            match snapshot_archives_dir {
+                Some(snapshot_archives_dir) => (
+                    snapshot_archives_dir.to_path_buf(),
+                    snapshot_archives_dir.to_path_buf(),
+                ),
+                None => snapshot_archives_dirs(arg_matches, blockstore.ledger_path()),
+            };
+        let usage = if arg_matches.is_present("no_snapshot") && snapshot_archives_dir.is_none() {
             SnapshotUsage::Disabled
         } else {
             SnapshotUsage::LoadOnly
@@ -179,6 +247,12 @@ pub fn load_and_process_ledger(
             ..SnapshotConfig::default()
         }
     };
+    // default start check with genesis
+    let starting_slot = highest_snapshot_archive_slot(
+        &snapshot_config.full_snapshot_archives_dir,
+        &snapshot_config.incremental_snapshot_archives_dir,
+    )
+    .unwrap_or_default();
 
     match process_options.halt_at_slot {
         // Skip the following checks for sentinel values of Some(0) and None.
@@ -316,10 +390,12 @@ pub fn load_and_process_ledger(
             let transaction_status_service = TransactionStatusService::new(
                 transaction_status_receiver,
                 Arc::default(),
-                enable_rpc_transaction_history,
+                Arc::new(TransactionHistorySwitch::new(
+                    enable_rpc_transaction_history,
+                    arg_matches.is_present("enable_extended_tx_metadata_storage"),
+                )),
                 transaction_notifier,
                 write_blockstore.clone(),
-                arg_matches.is_present("enable_extended_tx_metadata_storage"),
                 None,
                 tss_exit.clone(),
             );
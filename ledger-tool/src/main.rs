@@ -4,6 +4,8 @@ use {
         args::*,
         bigtable::*,
         blockstore::*,
+        dead_slots::{analyze_dead_slots, CliDeadSlot},
+        fork_graph::{fork_graph, fork_tips, last_votes, LastVote},
         ledger_path::*,
         ledger_utils::*,
         output::{
@@ -11,6 +13,8 @@ use {
             SlotBankHash,
         },
         program::*,
+        snapshot_archive::{extract_from_snapshot_archive, list_snapshot_archive},
+        verify_checkpoint::load_and_process_ledger_with_checkpoints_or_exit,
     },
     agave_feature_set::{self as feature_set, FeatureSet},
     agave_reserved_account_keys::ReservedAccountKeys,
@@ -26,7 +30,10 @@ use {
     log::*,
     serde::Serialize,
     solana_account::{state_traits::StateMut, AccountSharedData, ReadableAccount, WritableAccount},
-    solana_accounts_db::accounts_index::{ScanConfig, ScanOrder},
+    solana_accounts_db::{
+        accounts_index::{ScanConfig, ScanOrder},
+        storage_manifest::{SharedStorages, STORAGE_MANIFEST_FILE_NAME},
+    },
     solana_clap_utils::{
         input_parsers::{cluster_type_of, pubkey_of, pubkeys_of},
         input_validators::{
@@ -34,7 +41,10 @@ use {
             is_within_range,
         },
     },
-    solana_cli_output::{display::build_balance_message, CliAccount, OutputFormat},
+    solana_cli_output::{
+        accounts_export::CliAccountsExport, display::build_balance_message, CliAccount,
+        OutputFormat,
+    },
     solana_clock::{Epoch, Slot},
     solana_cluster_type::ClusterType,
     solana_core::{
@@ -104,11 +114,17 @@ use {
 mod args;
 mod bigtable;
 mod blockstore;
+mod blockstore_sync;
+mod dead_slots;
 mod error;
+mod fork_graph;
 mod ledger_path;
 mod ledger_utils;
 mod output;
 mod program;
+mod rebroadcast;
+mod snapshot_archive;
+mod verify_checkpoint;
 
 fn render_dot(dot: String, output_file: &str, output_format: &str) -> io::Result<()> {
     let mut child = Command::new("dot")
@@ -186,51 +202,33 @@ impl FromStr for GraphVoteAccountMode {
 struct GraphConfig {
     include_all_votes: bool,
     vote_account_mode: GraphVoteAccountMode,
+    starting_slot: Slot,
+    ending_slot: Slot,
 }
 
-#[allow(clippy::cognitive_complexity)]
-fn graph_forks(bank_forks: &BankForks, config: &GraphConfig) -> String {
-    let frozen_banks = bank_forks.frozen_banks();
-    let mut fork_slots: HashSet<_> = bank_forks
-        .frozen_banks()
-        .map(|(slot, _bank)| slot)
-        .collect();
-    for (_, bank) in frozen_banks {
-        for parent in bank.parents() {
-            fork_slots.remove(&parent.slot());
-        }
+impl GraphConfig {
+    fn contains(&self, slot: Slot) -> bool {
+        (self.starting_slot..=self.ending_slot).contains(&slot)
     }
+}
+
+#[allow(clippy::cognitive_complexity)]
+fn graph_forks(bank_forks: &BankForks, dead_slots: &[CliDeadSlot], config: &GraphConfig) -> String {
+    let fork_slots = fork_tips(bank_forks);
 
     // Search all forks and collect the last vote made by each validator
-    let mut last_votes = HashMap::new();
-    for fork_slot in &fork_slots {
-        let bank = &bank_forks[*fork_slot];
-
-        let total_stake = bank
-            .vote_accounts()
-            .iter()
-            .map(|(_, (stake, _))| stake)
-            .sum();
-        for (stake, vote_account) in bank.vote_accounts().values() {
-            let vote_state_view = vote_account.vote_state_view();
-            if let Some(last_vote) = vote_state_view.last_voted_slot() {
-                let entry = last_votes.entry(*vote_state_view.node_pubkey()).or_insert((
-                    last_vote,
-                    vote_state_view.clone(),
-                    *stake,
-                    total_stake,
-                ));
-                if entry.0 < last_vote {
-                    *entry = (last_vote, vote_state_view.clone(), *stake, total_stake);
-                }
-            }
-        }
-    }
+    let last_votes = last_votes(bank_forks, &fork_slots);
 
     // Figure the stake distribution at all the nodes containing the last vote from each
     // validator
     let mut slot_stake_and_vote_count = HashMap::new();
-    for (last_vote_slot, _, stake, total_stake) in last_votes.values() {
+    for LastVote {
+        slot: last_vote_slot,
+        stake,
+        total_stake,
+        ..
+    } in last_votes.values()
+    {
         let entry = slot_stake_and_vote_count
             .entry(last_vote_slot)
             .or_insert((0, 0, *total_stake));
@@ -262,6 +260,20 @@ fn graph_forks(bank_forks: &BankForks, config: &GraphConfig) -> String {
                 }
             }
 
+            if bank.slot() < config.starting_slot {
+                break;
+            }
+            if bank.slot() > config.ending_slot {
+                first = false;
+                match bank.parent() {
+                    Some(parent) => {
+                        bank = parent;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+
             if !styled_slots.contains(&bank.slot()) {
                 dot.push(format!(
                     r#"    "{}"[label="{} (epoch {})\nleader: {}{}{}",style="{}{}"];"#,
@@ -296,7 +308,10 @@ fn graph_forks(bank_forks: &BankForks, config: &GraphConfig) -> String {
             }
             first = false;
 
-            match bank.parent() {
+            match bank
+                .parent()
+                .filter(|parent| config.contains(parent.slot()))
+            {
                 None => {
                     if bank.slot() > 0 {
                         dot.push(format!(r#"    "{}" -> "..." [dir=back]"#, bank.slot(),));
@@ -328,6 +343,31 @@ fn graph_forks(bank_forks: &BankForks, config: &GraphConfig) -> String {
             }
         }
     }
+
+    // Attach the dead slots in the range to the banks they were built on
+    for dead_slot in dead_slots {
+        let causes = if dead_slot.causes.is_empty() {
+            "unknown".to_string()
+        } else {
+            dead_slot.causes.join("\\n").replace('"', "\\\"")
+        };
+        dot.push(format!(
+            r#"    "{}"[label="{} (dead)\nleader: {}\ncauses: {}",style="filled,dashed",fillcolor=lightcoral];"#,
+            dead_slot.slot,
+            dead_slot.slot,
+            dead_slot.leader.as_deref().unwrap_or("unknown"),
+            causes,
+        ));
+        if let Some(parent_slot) = dead_slot
+            .parent_slot
+            .filter(|parent_slot| styled_slots.contains(parent_slot))
+        {
+            dot.push(format!(
+                r#"    "{}" -> "{}"[dir=back,style=dashed,color=red];"#,
+                dead_slot.slot, parent_slot,
+            ));
+        }
+    }
     dot.push("  }".to_string());
 
     // Strafe the banks with links from validators to the bank they last voted on,
@@ -336,7 +376,16 @@ fn graph_forks(bank_forks: &BankForks, config: &GraphConfig) -> String {
     let mut absent_votes = 0;
     let mut lowest_last_vote_slot = u64::MAX;
     let mut lowest_total_stake = 0;
-    for (node_pubkey, (last_vote_slot, vote_state_view, stake, total_stake)) in &last_votes {
+    for (
+        node_pubkey,
+        LastVote {
+            slot: last_vote_slot,
+            vote_state_view,
+            stake,
+            total_stake,
+        },
+    ) in &last_votes
+    {
         all_votes.entry(*node_pubkey).and_modify(|validator_votes| {
             validator_votes.remove(last_vote_slot);
         });
@@ -936,6 +985,14 @@ fn main() {
         .value_name("BYTES")
         .help("Maximum number of bytes written to the program log before truncation");
 
+    let replay_determinism_check_arg = Arg::with_name("replay_determinism_check")
+        .long("replay-determinism-check")
+        .takes_value(false)
+        .help(
+            "Re-execute every replayed transaction batch one transaction at a time and report \
+             transactions whose results differ from the batched execution",
+        );
+
     let accounts_data_encoding_arg = Arg::with_name("encoding")
         .long("encoding")
         .takes_value(true)
@@ -1128,6 +1185,7 @@ fn main() {
                 .arg(&debug_key_arg)
                 .arg(&geyser_plugin_args)
                 .arg(&log_messages_bytes_limit_arg)
+                .arg(&replay_determinism_check_arg)
                 .arg(
                     Arg::with_name("skip_poh_verify")
                         .long("skip-poh-verify")
@@ -1143,6 +1201,31 @@ fn main() {
                         .takes_value(false)
                         .help("Skip ledger PoH and transaction verification."),
                 )
+                .arg(
+                    Arg::with_name("checkpoint_interval")
+                        .long("checkpoint-interval")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .validator(is_slot)
+                        .help(
+                            "Verify the ledger in runs of this many slots. After each run, write \
+                             a snapshot archive of the highest root to \
+                             <LEDGER_DIR>/ledger_tool/verify_checkpoint/ for --resume to \
+                             continue from. Each run starts by loading the snapshot archive \
+                             written by the previous one",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .long("resume")
+                        .takes_value(false)
+                        .requires("checkpoint_interval")
+                        .help(
+                            "Continue from the last checkpoint written by an interrupted \
+                             verification of this ledger rather than from the snapshot archives, \
+                             if there is one",
+                        ),
+                )
                 .arg(
                     Arg::with_name("enable_rpc_transaction_history")
                         .long("enable-rpc-transaction-history")
@@ -1267,7 +1350,29 @@ fn main() {
                         .index(1)
                         .value_name("FILENAME")
                         .takes_value(true)
-                        .help("Output file"),
+                        .help(
+                            "Output file. Files ending in .json receive the slots of the graph \
+                             with their votes and stake and the dead slots as JSON, files \
+                             ending in .pdf or .png a Graphviz rendering, and other files the \
+                             graph in the DOT language",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Leave out the slots before this one"),
+                )
+                .arg(
+                    Arg::with_name("ending_slot")
+                        .long("ending-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .help("Leave out the slots after this one"),
                 )
                 .arg(
                     Arg::with_name("vote_account_mode")
@@ -1610,6 +1715,72 @@ fn main() {
                         .validator(is_pubkey)
                         .conflicts_with("account")
                         .help("Limit output to accounts owned by the provided program pubkey"),
+                )
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about(
+                            "Export the accounts owned by programs after processing the ledger, \
+                             to load them into a test validator with --accounts-export",
+                        )
+                        .arg(&load_genesis_config_arg)
+                        .args(&accounts_db_config_args)
+                        .args(&snapshot_config_args)
+                        .arg(&halt_at_slot_arg)
+                        .arg(&hard_forks_arg)
+                        .arg(&geyser_plugin_args)
+                        .arg(&log_messages_bytes_limit_arg)
+                        .arg(
+                            Arg::with_name("owner")
+                                .long("owner")
+                                .takes_value(true)
+                                .value_name("PUBKEY")
+                                .validator(is_pubkey)
+                                .multiple(true)
+                                .required(true)
+                                .help(
+                                    "Export the accounts owned by this program, may be specified \
+                                     multiple times",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .required(true)
+                                .help("Write the exported accounts to FILE, as JSON"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("live-accounts")
+                .about(
+                    "Print the rooted accounts of a running validator started with \
+                     --accounts-db-storage-manifest, without copying its accounts storages",
+                )
+                .arg(
+                    Arg::with_name("storage_manifest")
+                        .long("storage-manifest")
+                        .takes_value(true)
+                        .value_name("PATH")
+                        .help(
+                            "Path of the storage manifest written by the validator \
+                             [default: <LEDGER_DIR>/accounts_storage_manifest]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("program_accounts")
+                        .long("program-accounts")
+                        .takes_value(true)
+                        .value_name("PUBKEY")
+                        .validator(is_pubkey)
+                        .help("Limit output to accounts owned by the provided program pubkey"),
+                )
+                .arg(
+                    Arg::with_name("summary_only")
+                        .long("summary-only")
+                        .takes_value(false)
+                        .help("Only print the number of accounts and their total lamports"),
                 ),
         )
         .subcommand(
@@ -1686,6 +1857,68 @@ fn main() {
                 )
                 .arg(&allow_dead_slots_arg),
         )
+        .subcommand(
+            SubCommand::with_name("analyze-dead-slots")
+                .about(
+                    "Print the dead slots in a range with their leader and the likely reason \
+                     replay marked them dead",
+                )
+                .arg(&load_genesis_config_arg)
+                .args(&accounts_db_config_args)
+                .args(&snapshot_config_args)
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Start at this slot"),
+                )
+                .arg(
+                    Arg::with_name("ending_slot")
+                        .long("ending-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .help("The last slot to analyze [default: the highest dead slot]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("extract-from-snapshot-archive")
+                .about(
+                    "List the files of a seekable snapshot archive, or extract some of them \
+                     without unpacking the whole archive",
+                )
+                .arg(
+                    Arg::with_name("archive")
+                        .index(1)
+                        .value_name("ARCHIVE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Snapshot archive created with the zstd-seekable archive format"),
+                )
+                .arg(
+                    Arg::with_name("paths")
+                        .index(2)
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .multiple(true)
+                        .help(
+                            "Files or directories of the archive to extract, such as \
+                             snapshots/SLOT/SLOT for the bank snapshot or accounts/SLOT.ID for \
+                             an account storage file [default: list the files of the archive]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("output_directory")
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .default_value(".")
+                        .help("Directory to extract the files into"),
+                ),
+        )
         .program_subcommand()
         .get_matches();
 
@@ -1710,6 +1943,31 @@ fn main() {
         ("bigtable", Some(arg_matches)) => bigtable_process_command(&ledger_path, arg_matches),
         ("blockstore", Some(arg_matches)) => blockstore_process_command(&ledger_path, arg_matches),
         ("program", Some(arg_matches)) => program(&ledger_path, arg_matches),
+        ("extract-from-snapshot-archive", Some(arg_matches)) => {
+            let archive_path = PathBuf::from(value_t_or_exit!(arg_matches, "archive", String));
+            let result = if let Some(paths) = arg_matches.values_of("paths") {
+                let paths: Vec<_> = paths.map(PathBuf::from).collect();
+                let output_directory =
+                    PathBuf::from(value_t_or_exit!(arg_matches, "output_directory", String));
+                extract_from_snapshot_archive(&archive_path, &paths, &output_directory).map(
+                    |extracted| {
+                        for path in extracted {
+                            println!("{}", path.display());
+                        }
+                    },
+                )
+            } else {
+                list_snapshot_archive(&archive_path).map(|entries| {
+                    for entry in entries {
+                        println!("{} {}", entry.path.display(), entry.size);
+                    }
+                })
+            };
+            if let Err(err) = result {
+                eprintln!("Failed to read {}: {err}", archive_path.display());
+                exit(1);
+            }
+        }
         // This match case provides legacy support for commands that were previously top level
         // subcommands of the binary, but have been moved under the blockstore subcommand.
         ("analyze-storage", Some(_))
@@ -1723,6 +1981,7 @@ fn main() {
         | ("print", Some(_))
         | ("print-file-metadata", Some(_))
         | ("purge", Some(_))
+        | ("rebroadcast", Some(_))
         | ("remove-dead-slot", Some(_))
         | ("repair-roots", Some(_))
         | ("set-dead-slot", Some(_))
@@ -1874,13 +2133,26 @@ fn main() {
                         get_access_type(&process_options),
                     );
                     let LoadAndProcessLedgerOutput { bank_forks, .. } =
-                        load_and_process_ledger_or_exit(
-                            arg_matches,
-                            &genesis_config,
-                            Arc::new(blockstore),
-                            process_options,
-                            transaction_status_sender,
-                        );
+                        match value_t!(arg_matches, "checkpoint_interval", Slot) {
+                            Ok(checkpoint_interval) => {
+                                load_and_process_ledger_with_checkpoints_or_exit(
+                                    arg_matches,
+                                    &genesis_config,
+                                    Arc::new(blockstore),
+                                    process_options,
+                                    transaction_status_sender,
+                                    checkpoint_interval,
+                                    arg_matches.is_present("resume"),
+                                )
+                            }
+                            Err(_) => load_and_process_ledger_or_exit(
+                                arg_matches,
+                                &genesis_config,
+                                Arc::new(blockstore),
+                                process_options,
+                                transaction_status_sender,
+                            ),
+                        };
 
                     let working_bank = bank_forks.read().unwrap().working_bank();
                     if print_accounts_stats {
@@ -1935,33 +2207,54 @@ fn main() {
                             "vote_account_mode",
                             GraphVoteAccountMode
                         ),
+                        starting_slot: value_t_or_exit!(arg_matches, "starting_slot", Slot),
+                        ending_slot: value_t!(arg_matches, "ending_slot", Slot)
+                            .unwrap_or(Slot::MAX),
                     };
 
                     let process_options = parse_process_options(&ledger_path, arg_matches);
                     let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
-                    let blockstore = open_blockstore(
+                    let blockstore = Arc::new(open_blockstore(
                         &ledger_path,
                         arg_matches,
                         get_access_type(&process_options),
-                    );
+                    ));
                     let LoadAndProcessLedgerOutput { bank_forks, .. } =
                         load_and_process_ledger_or_exit(
                             arg_matches,
                             &genesis_config,
-                            Arc::new(blockstore),
+                            blockstore.clone(),
                             process_options,
                             None,
                         );
 
-                    let dot = graph_forks(&bank_forks.read().unwrap(), &graph_config);
+                    let bank_forks = bank_forks.read().unwrap();
+                    let fork_graph = fork_graph(
+                        &bank_forks,
+                        &blockstore,
+                        graph_config.starting_slot,
+                        graph_config.ending_slot,
+                    )
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to collect the fork graph: {err}");
+                        exit(1);
+                    });
                     let extension = Path::new(&output_file).extension();
-                    let result = if extension == Some(OsStr::new("pdf")) {
-                        render_dot(dot, &output_file, "pdf")
-                    } else if extension == Some(OsStr::new("png")) {
-                        render_dot(dot, &output_file, "png")
+                    let result = if extension == Some(OsStr::new("json")) {
+                        File::create(&output_file).and_then(|file| {
+                            serde_json::to_writer_pretty(io::BufWriter::new(file), &fork_graph)
+                                .map_err(io::Error::from)
+                        })
                     } else {
-                        File::create(&output_file)
-                            .and_then(|mut file| file.write_all(&dot.into_bytes()))
+                        let dot = graph_forks(&bank_forks, &fork_graph.dead_slots, &graph_config);
+                        if extension == Some(OsStr::new("pdf")) {
+                            render_dot(dot, &output_file, "pdf")
+                        } else if extension == Some(OsStr::new("png")) {
+                            render_dot(dot, &output_file, "png")
+                        } else {
+                            File::create(&output_file)
+                                .and_then(|mut file| file.write_all(&dot.into_bytes()))
+                        }
                     };
 
                     match result {
@@ -2609,6 +2902,66 @@ fn main() {
                         }
                     };
                 }
+                ("accounts", Some(arg_matches))
+                    if arg_matches.subcommand_name() == Some("export") =>
+                {
+                    let arg_matches = arg_matches.subcommand_matches("export").unwrap();
+                    let mut owners = pubkeys_of(arg_matches, "owner").unwrap();
+                    owners.sort_unstable();
+                    owners.dedup();
+                    let output_file = value_t_or_exit!(arg_matches, "output", PathBuf);
+
+                    let process_options = parse_process_options(&ledger_path, arg_matches);
+                    let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                    let blockstore = open_blockstore(
+                        &ledger_path,
+                        arg_matches,
+                        get_access_type(&process_options),
+                    );
+                    let LoadAndProcessLedgerOutput { bank_forks, .. } =
+                        load_and_process_ledger_or_exit(
+                            arg_matches,
+                            &genesis_config,
+                            Arc::new(blockstore),
+                            process_options,
+                            None,
+                        );
+                    let bank = bank_forks.read().unwrap().working_bank();
+
+                    let mut accounts = vec![];
+                    for owner in &owners {
+                        let program_accounts = bank
+                            .get_program_accounts(owner, &ScanConfig::new(ScanOrder::Sorted))
+                            .unwrap_or_else(|err| {
+                                eprintln!("Unable to scan the accounts owned by {owner}: {err}");
+                                exit(1);
+                            });
+                        info!(
+                            "Exporting {} accounts owned by {owner}",
+                            program_accounts.len()
+                        );
+                        accounts.extend(program_accounts);
+                    }
+                    let num_accounts = accounts.len();
+                    let export =
+                        CliAccountsExport::new(bank.slot(), &bank.hash(), &owners, accounts);
+                    let result = File::create(&output_file).and_then(|file| {
+                        let mut writer = io::BufWriter::new(file);
+                        serde_json::to_writer(&mut writer, &export).map_err(io::Error::from)?;
+                        writer.flush()
+                    });
+                    match result {
+                        Ok(()) => println!(
+                            "Exported {num_accounts} accounts at slot {} to {}",
+                            bank.slot(),
+                            output_file.display()
+                        ),
+                        Err(err) => {
+                            eprintln!("Unable to write {}: {err}", output_file.display());
+                            exit(1);
+                        }
+                    }
+                }
                 ("accounts", Some(arg_matches)) => {
                     let process_options = parse_process_options(&ledger_path, arg_matches);
                     let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
@@ -2662,6 +3015,51 @@ fn main() {
                     );
                     info!("{scan_time}");
                 }
+                ("live-accounts", Some(arg_matches)) => {
+                    let manifest_path = value_t!(arg_matches, "storage_manifest", PathBuf)
+                        .unwrap_or_else(|_| ledger_path.join(STORAGE_MANIFEST_FILE_NAME));
+                    let owner = pubkey_of(arg_matches, "program_accounts");
+                    let summary_only = arg_matches.is_present("summary_only");
+
+                    let storages = SharedStorages::open(&manifest_path).unwrap_or_else(|err| {
+                        eprintln!(
+                            "Unable to open storage manifest {}: {err}",
+                            manifest_path.display()
+                        );
+                        exit(1);
+                    });
+                    let mut num_accounts = 0usize;
+                    let mut total_lamports = 0u64;
+                    storages
+                        .scan_accounts(|slot, account| {
+                            if owner.is_some_and(|owner| owner != *account.owner) {
+                                return;
+                            }
+                            num_accounts += 1;
+                            total_lamports = total_lamports.saturating_add(account.lamports);
+                            if !summary_only {
+                                println!(
+                                    "{}: lamports: {}, owner: {}, data len: {}, slot: {slot}",
+                                    account.pubkey,
+                                    account.lamports,
+                                    account.owner,
+                                    account.data.len(),
+                                );
+                            }
+                        })
+                        .unwrap_or_else(|err| {
+                            eprintln!("Unable to scan accounts: {err}");
+                            exit(1);
+                        });
+                    println!(
+                        "{num_accounts} accounts with {} as of root {} (manifest generation {}, \
+                         {} storages)",
+                        Sol(total_lamports),
+                        storages.root,
+                        storages.generation,
+                        storages.num_storages(),
+                    );
+                }
                 ("capitalization", Some(arg_matches)) => {
                     let process_options = parse_process_options(&ledger_path, arg_matches);
                     let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
@@ -3163,6 +3561,42 @@ fn main() {
                         println!("Capitalization: {}", Sol(bank.capitalization()));
                     }
                 }
+                ("analyze-dead-slots", Some(arg_matches)) => {
+                    let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+                    let ending_slot =
+                        value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+                    let output_format =
+                        OutputFormat::from_matches(arg_matches, "output_format", false);
+
+                    let mut process_options = parse_process_options(&ledger_path, arg_matches);
+                    // Only the snapshot bank is needed for the tick parameters and leader schedule
+                    process_options.halt_at_slot = Some(0);
+                    let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                    let blockstore = Arc::new(open_blockstore(
+                        &ledger_path,
+                        arg_matches,
+                        get_access_type(&process_options),
+                    ));
+                    let LoadAndProcessLedgerOutput { bank_forks, .. } =
+                        load_and_process_ledger_or_exit(
+                            arg_matches,
+                            &genesis_config,
+                            blockstore.clone(),
+                            process_options,
+                            None,
+                        );
+                    let bank = bank_forks.read().unwrap().working_bank();
+
+                    match analyze_dead_slots(&blockstore, &bank, starting_slot, ending_slot) {
+                        Ok(dead_slots) => {
+                            println!("{}", output_format.formatted_string(&dead_slots))
+                        }
+                        Err(err) => {
+                            eprintln!("Failed to analyze dead slots: {err}");
+                            exit(1);
+                        }
+                    }
+                }
                 ("compute-slot-cost", Some(arg_matches)) => {
                     let blockstore =
                         open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
@@ -0,0 +1,164 @@
+//! Re-broadcast of rooted slots from the local blockstore
+//!
+//! During a cluster restart some nodes can be missing slots that other nodes hold locally. Sending
+//! the stored shreds of those slots to the TVU ports of the lagging nodes lets them ingest the
+//! slots like any other turbine traffic. The shreds are sent unchanged, so the receivers still
+//! verify the leader's signature on every one of them.
+
+use {
+    crate::error::{LedgerToolError, Result},
+    log::*,
+    solana_clock::Slot,
+    solana_ledger::blockstore::Blockstore,
+    std::{
+        net::{IpAddr, SocketAddr, UdpSocket},
+        num::NonZeroU64,
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+};
+
+pub struct RebroadcastConfig {
+    /// TVU addresses of the nodes to send the shreds to
+    pub targets: Vec<SocketAddr>,
+    pub bind_address: IpAddr,
+    /// Packets per second sent over all targets combined
+    pub max_packets_per_second: NonZeroU64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RebroadcastStats {
+    pub data_shreds: usize,
+    pub coding_shreds: usize,
+    pub packets_sent: u64,
+}
+
+/// Spreads the packets evenly over time so that at most `max_packets_per_second` are sent
+struct Pacer {
+    start: Instant,
+    max_packets_per_second: NonZeroU64,
+    packets_sent: u64,
+}
+
+impl Pacer {
+    fn new(max_packets_per_second: NonZeroU64) -> Self {
+        Self {
+            start: Instant::now(),
+            max_packets_per_second,
+            packets_sent: 0,
+        }
+    }
+
+    fn wait(&mut self) {
+        let due = Duration::from_secs_f64(
+            self.packets_sent as f64 / self.max_packets_per_second.get() as f64,
+        );
+        let elapsed = self.start.elapsed();
+        if due > elapsed {
+            sleep(due - elapsed);
+        }
+        self.packets_sent += 1;
+    }
+}
+
+/// Sends the data and coding shreds of each of `slots` to every target
+///
+/// All slots must be rooted, so that only blocks the cluster has agreed on are sent.
+pub fn rebroadcast_slots(
+    blockstore: &Blockstore,
+    slots: &[Slot],
+    config: &RebroadcastConfig,
+) -> Result<RebroadcastStats> {
+    if let Some(slot) = slots.iter().find(|slot| !blockstore.is_root(**slot)) {
+        return Err(LedgerToolError::BadArgument(format!(
+            "slot {slot} is not rooted"
+        )));
+    }
+
+    let socket = UdpSocket::bind((config.bind_address, 0))?;
+    let mut pacer = Pacer::new(config.max_packets_per_second);
+    let mut stats = RebroadcastStats::default();
+    for slot in slots {
+        if !blockstore.is_full(*slot) {
+            warn!("Slot {slot} is not full, sending the shreds the blockstore has");
+        }
+        let mut send = |payload: &[u8]| -> Result<()> {
+            for target in &config.targets {
+                pacer.wait();
+                socket.send_to(payload, target)?;
+                stats.packets_sent += 1;
+            }
+            Ok(())
+        };
+        let mut data_shreds = 0;
+        for (_, payload) in blockstore.slot_data_iterator(*slot, 0)? {
+            send(&payload)?;
+            data_shreds += 1;
+        }
+        let mut coding_shreds = 0;
+        for (_, payload) in blockstore.slot_coding_iterator(*slot, 0)? {
+            send(&payload)?;
+            coding_shreds += 1;
+        }
+        info!("Sent {data_shreds} data and {coding_shreds} coding shreds of slot {slot}");
+        stats.data_shreds += data_shreds;
+        stats.coding_shreds += coding_shreds;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::{blockstore::make_many_slot_entries, get_tmp_ledger_path_auto_delete},
+        std::net::Ipv4Addr,
+    };
+
+    #[test]
+    fn test_rebroadcast_slots() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, _) = make_many_slot_entries(1, 2, 4);
+        let num_shreds = shreds.len();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([1].iter()).unwrap();
+
+        let receivers: Vec<_> = (0..2)
+            .map(|_| {
+                let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+                socket
+                    .set_read_timeout(Some(Duration::from_secs(5)))
+                    .unwrap();
+                socket
+            })
+            .collect();
+        let config = RebroadcastConfig {
+            targets: receivers
+                .iter()
+                .map(|socket| socket.local_addr().unwrap())
+                .collect(),
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            max_packets_per_second: NonZeroU64::new(1_000_000).unwrap(),
+        };
+
+        // slot 2 was never rooted
+        assert!(rebroadcast_slots(&blockstore, &[1, 2], &config).is_err());
+
+        let stats = rebroadcast_slots(&blockstore, &[1], &config).unwrap();
+        let slot_shreds = blockstore.get_data_shreds_for_slot(1, 0).unwrap();
+        assert_eq!(stats.data_shreds, slot_shreds.len());
+        assert!(stats.data_shreds < num_shreds);
+        assert_eq!(
+            stats.packets_sent,
+            2 * (stats.data_shreds + stats.coding_shreds) as u64
+        );
+        let mut buf = [0; 2048];
+        for receiver in &receivers {
+            for shred in slot_shreds.iter() {
+                let (size, _) = receiver.recv_from(&mut buf).unwrap();
+                assert_eq!(&buf[..size], &shred.payload()[..]);
+            }
+        }
+    }
+}
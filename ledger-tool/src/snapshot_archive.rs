@@ -0,0 +1,76 @@
+//! Partial extraction of seekable snapshot archives
+
+use {
+    crate::error::{LedgerToolError, Result},
+    agave_snapshots::seekable_zstd::{SeekableArchiveEntry, SeekableZstdReader},
+    std::{
+        fs::{self, File},
+        io::{self, BufReader},
+        path::{Component, Path, PathBuf},
+    },
+};
+
+fn open_archive(archive_path: &Path) -> Result<SeekableZstdReader<BufReader<File>>> {
+    let file = File::open(archive_path)?;
+    SeekableZstdReader::open(BufReader::new(file))?.ok_or_else(|| {
+        LedgerToolError::BadArgument(format!(
+            "{} has no seek table, only archives created with the zstd-seekable format can be \
+             partially extracted",
+            archive_path.display()
+        ))
+    })
+}
+
+/// Lists the files of a seekable snapshot archive
+pub fn list_snapshot_archive(archive_path: &Path) -> Result<Vec<SeekableArchiveEntry>> {
+    Ok(open_archive(archive_path)?.entries()?)
+}
+
+/// Extracts the files at `paths` from a seekable snapshot archive into `output_dir`
+///
+/// A path names either a file of the archive, such as `snapshots/<slot>/<slot>` for the bank
+/// snapshot or `accounts/<slot>.<id>` for an account storage file, or a directory whose files
+/// are all extracted.
+pub fn extract_from_snapshot_archive(
+    archive_path: &Path,
+    paths: &[PathBuf],
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut archive = open_archive(archive_path)?;
+    let entries = archive.entries()?;
+
+    let mut extracted = Vec::new();
+    for path in paths {
+        let matching_entries: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.path.starts_with(path))
+            .collect();
+        if matching_entries.is_empty() {
+            return Err(LedgerToolError::BadArgument(format!(
+                "{} is not in the archive",
+                path.display()
+            )));
+        }
+        for entry in matching_entries {
+            // Entries are only written below the output directory
+            if entry
+                .path
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+            {
+                return Err(LedgerToolError::Generic(format!(
+                    "invalid path in archive: {}",
+                    entry.path.display()
+                )));
+            }
+            let output_path = output_dir.join(&entry.path);
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut output_file = File::create(&output_path)?;
+            io::copy(&mut archive.entry_reader(entry)?, &mut output_file)?;
+            extracted.push(output_path);
+        }
+    }
+    Ok(extracted)
+}
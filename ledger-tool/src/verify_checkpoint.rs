@@ -0,0 +1,302 @@
+//! Checkpoints of `agave-ledger-tool verify`
+//!
+//! Verifying a long stretch of ledger takes hours, and an OOM or a reboot used to throw all of it
+//! away. With checkpoints, the ledger is verified in runs of a given number of slots. After each
+//! run, a full snapshot archive of the highest root is written to the checkpoint directory along
+//! with a record of its slot and bank hash. `verify --resume` starts from that snapshot archive
+//! instead of the ones given on the command line.
+
+use {
+    crate::{
+        error::{LedgerToolError, Result},
+        ledger_utils::{
+            highest_snapshot_archive_slot, load_and_process_ledger_from_snapshot_archives_or_exit,
+            load_and_process_ledger_or_exit, snapshot_archives_dirs, LoadAndProcessLedgerOutput,
+        },
+        LEDGER_TOOL_DIRECTORY,
+    },
+    agave_snapshots::{snapshot_archive_info::SnapshotArchiveInfoGetter as _, ArchiveFormat},
+    clap::ArgMatches,
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_clock::Slot,
+    solana_genesis_config::GenesisConfig,
+    solana_ledger::{
+        blockstore::Blockstore,
+        blockstore_processor::{ProcessOptions, TransactionStatusSender},
+    },
+    solana_runtime::{bank::Bank, bank_forks::BankForks, snapshot_bank_utils},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        process::exit,
+        sync::Arc,
+    },
+};
+
+const VERIFY_CHECKPOINT_DIR: &str = "verify_checkpoint";
+const VERIFY_CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// The bank verification last stopped at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyCheckpoint {
+    pub slot: Slot,
+    pub bank_hash: String,
+    /// Full snapshot archive of the bank
+    pub snapshot_archive: PathBuf,
+}
+
+/// Loads and processes the ledger in runs of `checkpoint_interval` slots, writing a checkpoint of
+/// the highest root after each run, and with `resume`, starting from the last checkpoint written
+///
+/// A run that roots no new slot is retried with twice the length, as there is nothing new to
+/// checkpoint. The checkpoints are removed once the end of the ledger is reached. They are kept
+/// when processing stops at `--halt-at-slot`, so that a later verification can go on from there.
+pub fn load_and_process_ledger_with_checkpoints_or_exit(
+    arg_matches: &ArgMatches,
+    genesis_config: &GenesisConfig,
+    blockstore: Arc<Blockstore>,
+    process_options: ProcessOptions,
+    transaction_status_sender: Option<TransactionStatusSender>,
+    checkpoint_interval: Slot,
+    resume: bool,
+) -> LoadAndProcessLedgerOutput {
+    let checkpoint_dir = verify_checkpoint_dir(blockstore.ledger_path());
+    let checkpoint = if resume {
+        load_verify_checkpoint(&checkpoint_dir).unwrap_or_else(|err| exit_on_checkpoint_error(err))
+    } else {
+        remove_verify_checkpoints(&checkpoint_dir)
+            .unwrap_or_else(|err| exit_on_checkpoint_error(err));
+        None
+    };
+
+    let mut from_checkpoint = checkpoint.is_some();
+    let mut starting_slot = match checkpoint {
+        Some(checkpoint) => {
+            println!(
+                "Resuming from the checkpoint at slot {}, bank hash {}",
+                checkpoint.slot, checkpoint.bank_hash
+            );
+            checkpoint.slot
+        }
+        None if arg_matches.is_present("no_snapshot") => 0,
+        None => {
+            if resume {
+                println!("No checkpoint to resume from, starting over");
+            }
+            let (full_snapshot_archives_dir, incremental_snapshot_archives_dir) =
+                snapshot_archives_dirs(arg_matches, blockstore.ledger_path());
+            highest_snapshot_archive_slot(
+                &full_snapshot_archives_dir,
+                &incremental_snapshot_archives_dir,
+            )
+            .unwrap_or_default()
+        }
+    };
+    let halt_at_slot = process_options.halt_at_slot;
+    let mut run_length = checkpoint_interval;
+    loop {
+        let run_halt_at_slot = starting_slot.saturating_add(run_length);
+        let process_options = ProcessOptions {
+            halt_at_slot: Some(halt_at_slot.map_or(run_halt_at_slot, |halt_at_slot| {
+                halt_at_slot.min(run_halt_at_slot)
+            })),
+            ..process_options.clone()
+        };
+        let output = if from_checkpoint {
+            load_and_process_ledger_from_snapshot_archives_or_exit(
+                arg_matches,
+                genesis_config,
+                blockstore.clone(),
+                process_options,
+                transaction_status_sender.clone(),
+                &checkpoint_dir,
+            )
+        } else {
+            load_and_process_ledger_or_exit(
+                arg_matches,
+                genesis_config,
+                blockstore.clone(),
+                process_options,
+                transaction_status_sender.clone(),
+            )
+        };
+
+        let (highest_slot, root_bank, ledger_continues) = {
+            let bank_forks = output.bank_forks.read().unwrap();
+            (
+                bank_forks.highest_slot(),
+                bank_forks.root_bank(),
+                ledger_continues(&blockstore, &bank_forks),
+            )
+        };
+        if halt_at_slot.is_some_and(|halt_at_slot| highest_slot >= halt_at_slot) {
+            return output;
+        }
+        if !ledger_continues {
+            remove_verify_checkpoints(&checkpoint_dir)
+                .unwrap_or_else(|err| exit_on_checkpoint_error(err));
+            return output;
+        }
+
+        if root_bank.slot() == starting_slot {
+            run_length = run_length.saturating_mul(2);
+            println!(
+                "No new root within {} slots of slot {starting_slot}, retrying with a run of \
+                 {run_length} slots",
+                run_length / 2
+            );
+        } else {
+            let checkpoint = write_verify_checkpoint(&checkpoint_dir, &root_bank)
+                .unwrap_or_else(|err| exit_on_checkpoint_error(err));
+            println!(
+                "Checkpoint written at slot {}, bank hash {}",
+                checkpoint.slot, checkpoint.bank_hash
+            );
+            starting_slot = checkpoint.slot;
+            from_checkpoint = true;
+            run_length = checkpoint_interval;
+        }
+
+        // Release the banks of this run before loading the checkpoint for the next one
+        drop(root_bank);
+        let LoadAndProcessLedgerOutput {
+            bank_forks,
+            accounts_background_service,
+            ..
+        } = output;
+        drop(bank_forks);
+        if accounts_background_service.join().is_err() {
+            warn!("The accounts background service of the previous run panicked");
+        }
+    }
+}
+
+/// Whether the blockstore holds a slot that could be processed on top of a bank in `bank_forks`,
+/// on any fork, not only the one of the working bank
+fn ledger_continues(blockstore: &Blockstore, bank_forks: &BankForks) -> bool {
+    bank_forks.frozen_banks().any(|(slot, _bank)| {
+        blockstore.meta(slot).ok().flatten().is_some_and(|meta| {
+            meta.next_slots.iter().any(|next_slot| {
+                bank_forks.get(*next_slot).is_none()
+                    && !blockstore.is_dead(*next_slot)
+                    && blockstore
+                        .meta(*next_slot)
+                        .ok()
+                        .flatten()
+                        .is_some_and(|next_meta| next_meta.is_full())
+            })
+        })
+    })
+}
+
+fn exit_on_checkpoint_error(err: LedgerToolError) -> ! {
+    eprintln!("Exiting. Failed to checkpoint the verification: {err}");
+    exit(1);
+}
+
+/// Directory of the checkpoints of the ledger at `ledger_path`
+pub fn verify_checkpoint_dir(ledger_path: &Path) -> PathBuf {
+    ledger_path
+        .join(LEDGER_TOOL_DIRECTORY)
+        .join(VERIFY_CHECKPOINT_DIR)
+}
+
+/// Loads the checkpoint in `checkpoint_dir`, `None` if there is none
+pub fn load_verify_checkpoint(checkpoint_dir: &Path) -> Result<Option<VerifyCheckpoint>> {
+    let path = checkpoint_dir.join(VERIFY_CHECKPOINT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let checkpoint: VerifyCheckpoint = serde_json::from_slice(&fs::read(path)?)?;
+    if !checkpoint.snapshot_archive.exists() {
+        return Err(LedgerToolError::Generic(format!(
+            "the snapshot archive of the checkpoint at slot {} is missing: {}",
+            checkpoint.slot,
+            checkpoint.snapshot_archive.display()
+        )));
+    }
+    Ok(Some(checkpoint))
+}
+
+/// Writes a checkpoint of `bank` to `checkpoint_dir`, replacing the previous one
+pub fn write_verify_checkpoint(checkpoint_dir: &Path, bank: &Bank) -> Result<VerifyCheckpoint> {
+    fs::create_dir_all(checkpoint_dir)?;
+    let previous_checkpoint = load_verify_checkpoint(checkpoint_dir).ok().flatten();
+
+    let snapshot_archive_info = snapshot_bank_utils::bank_to_full_snapshot_archive(
+        checkpoint_dir,
+        bank,
+        None,
+        checkpoint_dir,
+        checkpoint_dir,
+        ArchiveFormat::TarZstd {
+            config: Default::default(),
+        },
+    )
+    .map_err(|err| LedgerToolError::Generic(format!("unable to create snapshot: {err}")))?;
+    let checkpoint = VerifyCheckpoint {
+        slot: bank.slot(),
+        bank_hash: bank.hash().to_string(),
+        snapshot_archive: snapshot_archive_info.path().clone(),
+    };
+    store_verify_checkpoint(checkpoint_dir, &checkpoint)?;
+
+    // Only once the new checkpoint is recorded, so that there always is one to resume from
+    if let Some(previous_checkpoint) = previous_checkpoint {
+        if previous_checkpoint.snapshot_archive != checkpoint.snapshot_archive {
+            fs::remove_file(previous_checkpoint.snapshot_archive)?;
+        }
+    }
+    Ok(checkpoint)
+}
+
+/// Removes the checkpoints in `checkpoint_dir`, so that the next verification starts over
+pub fn remove_verify_checkpoints(checkpoint_dir: &Path) -> Result<()> {
+    if checkpoint_dir.exists() {
+        fs::remove_dir_all(checkpoint_dir)?;
+    }
+    Ok(())
+}
+
+fn store_verify_checkpoint(checkpoint_dir: &Path, checkpoint: &VerifyCheckpoint) -> Result<()> {
+    // Write then rename, so that an interruption never leaves a partial checkpoint behind
+    let path = checkpoint_dir.join(VERIFY_CHECKPOINT_FILE);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_vec_pretty(checkpoint)?)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_ledger::get_tmp_ledger_path_auto_delete};
+
+    #[test]
+    fn test_load_verify_checkpoint() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let checkpoint_dir = verify_checkpoint_dir(ledger_path.path());
+        assert!(load_verify_checkpoint(&checkpoint_dir).unwrap().is_none());
+
+        fs::create_dir_all(&checkpoint_dir).unwrap();
+        let snapshot_archive = checkpoint_dir.join("snapshot-100-hash.tar.zst");
+        let checkpoint = VerifyCheckpoint {
+            slot: 100,
+            bank_hash: "hash".to_string(),
+            snapshot_archive: snapshot_archive.clone(),
+        };
+        store_verify_checkpoint(&checkpoint_dir, &checkpoint).unwrap();
+        // The snapshot archive is missing
+        assert!(load_verify_checkpoint(&checkpoint_dir).is_err());
+
+        fs::write(&snapshot_archive, []).unwrap();
+        assert_eq!(
+            load_verify_checkpoint(&checkpoint_dir).unwrap(),
+            Some(checkpoint)
+        );
+
+        remove_verify_checkpoints(&checkpoint_dir).unwrap();
+        assert!(load_verify_checkpoint(&checkpoint_dir).unwrap().is_none());
+    }
+}
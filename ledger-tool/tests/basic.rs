@@ -1,8 +1,10 @@
 use {
     assert_cmd::prelude::*,
+    solana_hash::Hash,
     solana_ledger::{
-        blockstore, blockstore::Blockstore, create_new_tmp_ledger_auto_delete,
-        genesis_utils::create_genesis_config, get_tmp_ledger_path_auto_delete,
+        blockstore, blockstore::Blockstore, blockstore_processor::fill_blockstore_slot_with_ticks,
+        create_new_tmp_ledger_auto_delete, genesis_utils::create_genesis_config,
+        get_tmp_ledger_path_auto_delete,
     },
     std::{
         path::Path,
@@ -87,3 +89,65 @@ fn ledger_tool_copy_test() {
         assert!(!src_slot_output.stdout.is_empty());
     }
 }
+
+/// Rooted slots 1 to 12, but for slot 4, which forks off slot 3 and ends there
+fn insert_forked_test_slots(ledger_path: &Path, ticks_per_slot: u64, blockhash: Hash) {
+    let blockstore = Blockstore::open(ledger_path).unwrap();
+    let slot_3_hash = (1..=3).fold(blockhash, |hash, slot| {
+        fill_blockstore_slot_with_ticks(&blockstore, ticks_per_slot, slot, slot - 1, hash)
+    });
+    fill_blockstore_slot_with_ticks(&blockstore, ticks_per_slot, 4, 3, slot_3_hash);
+    let mut parent_slot = 3;
+    let mut hash = slot_3_hash;
+    for slot in 5..=12 {
+        hash =
+            fill_blockstore_slot_with_ticks(&blockstore, ticks_per_slot, slot, parent_slot, hash);
+        parent_slot = slot;
+    }
+    let roots: Vec<u64> = (1..=12).filter(|slot| *slot != 4).collect();
+    blockstore.set_roots(roots.iter()).unwrap();
+}
+
+#[test]
+fn verify_with_checkpoints() {
+    let genesis_config = create_genesis_config(100).genesis_config;
+    let (ledger_path, blockhash) = create_new_tmp_ledger_auto_delete!(&genesis_config);
+    insert_forked_test_slots(ledger_path.path(), genesis_config.ticks_per_slot, blockhash);
+    let checkpoint_dir = ledger_path
+        .path()
+        .join("ledger_tool")
+        .join("verify_checkpoint");
+    let ledger_path = ledger_path.path().to_str().unwrap();
+
+    // The first run ends on the dead end at slot 4 and is checkpointed at the root, slot 3. The
+    // checkpoint is kept when stopping at --halt-at-slot
+    let output = run_ledger_tool(&[
+        "-l",
+        ledger_path,
+        "verify",
+        "--checkpoint-interval",
+        "4",
+        "--halt-at-slot",
+        "6",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Checkpoint written at slot 3,"));
+    assert!(checkpoint_dir.join("checkpoint.json").exists());
+
+    // Resumed, verification goes on to the end of the ledger and removes the checkpoints
+    let output = run_ledger_tool(&[
+        "-l",
+        ledger_path,
+        "verify",
+        "--checkpoint-interval",
+        "4",
+        "--resume",
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Resuming from the checkpoint at slot 3,"));
+    assert!(stdout.contains("Checkpoint written at slot 7,"));
+    assert!(stdout.contains("Checkpoint written at slot 11,"));
+    assert!(!checkpoint_dir.exists());
+}
@@ -8,12 +8,16 @@ use {
     crate::{
         ancestor_iterator::AncestorIterator,
         blockstore::column::{columns as cf, Column, ColumnIndexDeprecation, TypedColumn},
-        blockstore_db::{IteratorDirection, IteratorMode, LedgerColumn, Rocks, WriteBatch},
+        blockstore_db::{
+            use_manual_wal_flush, IteratorDirection, IteratorMode, LedgerColumn, Rocks,
+            WalFlushService, WriteBatch,
+        },
         blockstore_meta::*,
         blockstore_options::{
             BlockstoreOptions, LedgerColumnOptions, BLOCKSTORE_DIRECTORY_ROCKS_LEVEL,
         },
         blockstore_processor::BlockstoreProcessorError,
+        fork_stats::ForkStats,
         leader_schedule_cache::LeaderScheduleCache,
         next_slots_iterator::NextSlotsIterator,
         shred::{
@@ -53,6 +57,7 @@ use {
     solana_transaction::versioned::{
         sanitized::SanitizedVersionedTransaction, VersionedTransaction,
     },
+    solana_transaction_error::TransactionError,
     solana_transaction_status::{
         ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta, Rewards,
         RewardsAndNumPartitions, TransactionStatusMeta, TransactionWithStatusMeta,
@@ -242,9 +247,12 @@ pub struct BlockstoreSignals {
 // ledger window
 pub struct Blockstore {
     ledger_path: PathBuf,
+    // Declared before `db` so the flush thread stops before the database is closed
+    _wal_flush_service: Option<WalFlushService>,
     db: Arc<Rocks>,
     // Column families
     address_signatures_cf: LedgerColumn<cf::AddressSignatures>,
+    address_transactions_cf: LedgerColumn<cf::AddressTransactions>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     block_height_cf: LedgerColumn<cf::BlockHeight>,
     blocktime_cf: LedgerColumn<cf::Blocktime>,
@@ -265,6 +273,7 @@ pub struct Blockstore {
     transaction_status_cf: LedgerColumn<cf::TransactionStatus>,
     transaction_status_index_cf: LedgerColumn<cf::TransactionStatusIndex>,
 
+    enable_address_time_index: bool,
     highest_primary_index_slot: RwLock<Option<Slot>>,
     max_root: AtomicU64,
     insert_shreds_lock: Mutex<()>,
@@ -272,6 +281,7 @@ pub struct Blockstore {
     completed_slots_senders: Mutex<Vec<CompletedSlotsSender>>,
     pub lowest_cleanup_slot: RwLock<Slot>,
     pub slots_stats: SlotsStats,
+    pub fork_stats: ForkStats,
 }
 
 pub struct IndexMetaWorkingSetEntry {
@@ -386,9 +396,16 @@ impl Blockstore {
         // Open the database
         let mut measure = Measure::start("blockstore open");
         info!("Opening blockstore at {blockstore_path:?}");
+        let wal_flush_interval = options
+            .wal_flush_interval
+            .filter(|_| use_manual_wal_flush(&options));
+        let enable_address_time_index = options.enable_address_time_index;
         let db = Arc::new(Rocks::open(blockstore_path, options)?);
+        let wal_flush_service =
+            wal_flush_interval.map(|interval| WalFlushService::new(Arc::downgrade(&db), interval));
 
         let address_signatures_cf = db.column();
+        let address_transactions_cf = db.column();
         let bank_hash_cf = db.column();
         let block_height_cf = db.column();
         let blocktime_cf = db.column();
@@ -421,8 +438,10 @@ impl Blockstore {
         info!("Opening blockstore done; {measure}");
         let blockstore = Blockstore {
             ledger_path: ledger_path.to_path_buf(),
+            _wal_flush_service: wal_flush_service,
             db,
             address_signatures_cf,
+            address_transactions_cf,
             bank_hash_cf,
             block_height_cf,
             blocktime_cf,
@@ -442,6 +461,7 @@ impl Blockstore {
             transaction_memos_cf,
             transaction_status_cf,
             transaction_status_index_cf,
+            enable_address_time_index,
             highest_primary_index_slot: RwLock::<Option<Slot>>::default(),
             new_shreds_signals: Mutex::default(),
             completed_slots_senders: Mutex::default(),
@@ -449,6 +469,7 @@ impl Blockstore {
             max_root,
             lowest_cleanup_slot: RwLock::<Slot>::default(),
             slots_stats: SlotsStats::default(),
+            fork_stats: ForkStats::default(),
         };
         blockstore.cleanup_old_entries()?;
         blockstore.update_highest_primary_index_slot()?;
@@ -850,6 +871,7 @@ impl Blockstore {
         self.code_shred_cf.submit_rocksdb_cf_metrics();
         self.transaction_status_cf.submit_rocksdb_cf_metrics();
         self.address_signatures_cf.submit_rocksdb_cf_metrics();
+        self.address_transactions_cf.submit_rocksdb_cf_metrics();
         self.transaction_memos_cf.submit_rocksdb_cf_metrics();
         self.transaction_status_index_cf.submit_rocksdb_cf_metrics();
         self.rewards_cf.submit_rocksdb_cf_metrics();
@@ -2982,6 +3004,43 @@ impl Blockstore {
         )
     }
 
+    /// Whether the transaction history should also be written to the address time index, with
+    /// [`Self::add_address_transactions_to_batch`]
+    pub fn is_address_time_index_enabled(&self) -> bool {
+        self.enable_address_time_index
+    }
+
+    /// Adds the transaction to the address time index of each of its addresses
+    ///
+    /// The block time of the slot is recorded too, as the entries are only found by it when they
+    /// are purged.
+    pub fn add_address_transactions_to_batch<'a>(
+        &self,
+        slot: Slot,
+        block_time: UnixTimestamp,
+        signature: Signature,
+        keys_with_writable: impl Iterator<Item = (&'a Pubkey, bool)>,
+        err: Option<TransactionError>,
+        transaction_index: usize,
+        db_write_batch: &mut WriteBatch,
+    ) -> Result<()> {
+        let transaction_index = u32::try_from(transaction_index)
+            .map_err(|_| BlockstoreError::TransactionIndexOverflow)?;
+        self.blocktime_cf
+            .put_in_batch(db_write_batch, slot, &block_time)?;
+        for (address, writeable) in keys_with_writable {
+            self.address_transactions_cf.put_in_batch(
+                db_write_batch,
+                (*address, block_time, slot, transaction_index, signature),
+                &AddressTransactionMeta {
+                    writeable,
+                    err: err.clone(),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn read_transaction_memos(
         &self,
         signature: Signature,
@@ -3461,6 +3520,90 @@ impl Blockstore {
         })
     }
 
+    /// Returns up to `limit` transactions of `address` with a block time in \[`start_time`,
+    /// `end_time`), oldest first, from the address time index
+    ///
+    /// Only transactions of roots and of the unrooted ancestors of `highest_slot` are returned.
+    /// When `after` is given, the listing resumes after that transaction, or is empty if it is
+    /// not such a transaction.
+    pub fn get_address_transactions_by_time(
+        &self,
+        address: Pubkey,
+        highest_slot: Slot,
+        start_time: UnixTimestamp,
+        end_time: UnixTimestamp,
+        after: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<ConfirmedTransactionStatusWithSignature>> {
+        let max_root = self.max_root();
+        let confirmed_unrooted_slots: HashSet<_> =
+            AncestorIterator::new_inclusive(highest_slot, self)
+                .take_while(|&slot| slot > max_root)
+                .collect();
+
+        // Resume at the block time and slot of `after`, skipping the transactions before it
+        let mut start = (start_time, 0);
+        let mut skip_through = None;
+        if let Some(after) = after {
+            let Some((slot, _)) = self.get_transaction_status(after, &confirmed_unrooted_slots)?
+            else {
+                return Ok(vec![]);
+            };
+            let Some(block_time) = self.get_block_time(slot)? else {
+                return Ok(vec![]);
+            };
+            if block_time >= start_time {
+                start = (block_time, slot);
+                skip_through = Some(after);
+            }
+        }
+
+        let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
+        let iterator = self.address_transactions_cf.iter(IteratorMode::From(
+            (address, start.0, start.1, 0, Signature::default()),
+            IteratorDirection::Forward,
+        ))?;
+        let mut address_transactions = vec![];
+        for ((key_address, block_time, slot, _transaction_index, signature), value) in iterator {
+            if key_address != address
+                || block_time >= end_time
+                || address_transactions.len() >= limit
+            {
+                break;
+            }
+            if let Some(after) = skip_through {
+                if (block_time, slot) == start {
+                    if signature == after {
+                        skip_through = None;
+                    }
+                    continue;
+                }
+                skip_through = None;
+            }
+            if slot < lowest_available_slot
+                || !(self.is_root(slot) || confirmed_unrooted_slots.contains(&slot))
+            {
+                continue;
+            }
+            let AddressTransactionMeta { err, .. } = cf::AddressTransactions::deserialize(&value)?;
+            address_transactions.push((signature, slot, err, block_time));
+        }
+        drop(lock);
+
+        address_transactions
+            .into_iter()
+            .map(|(signature, slot, err, block_time)| {
+                Ok(ConfirmedTransactionStatusWithSignature {
+                    signature,
+                    slot,
+                    err,
+                    memo: self.read_transaction_memos(signature, slot)?,
+                    block_time: Some(block_time),
+                })
+            })
+            .collect()
+    }
+
     pub fn read_rewards(&self, index: Slot) -> Result<Option<Rewards>> {
         self.rewards_cf
             .get_protobuf_or_bincode::<Rewards>(index)
@@ -9164,6 +9307,113 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_get_address_transactions_by_time() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open_with_options(
+            ledger_path.path(),
+            BlockstoreOptions {
+                enable_address_time_index: true,
+                ..BlockstoreOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(blockstore.is_address_time_index_enabled());
+
+        let address0 = solana_pubkey::new_rand();
+        let address1 = solana_pubkey::new_rand();
+        // (slot, block time, signatures); slot 4 is on a fork that is never rooted
+        let slots = [
+            (1, 100, vec![1, 2]),
+            (2, 200, vec![3, 4]),
+            (3, 200, vec![5]),
+            (4, 150, vec![6]),
+        ];
+        for (slot, block_time, signatures) in &slots {
+            let mut batch = blockstore.get_write_batch().unwrap();
+            for (transaction_index, x) in signatures.iter().enumerate() {
+                let signature = Signature::from([*x; 64]);
+                let err = (*x == 4).then_some(TransactionError::AccountNotFound);
+                blockstore
+                    .write_transaction_status(
+                        *slot,
+                        signature,
+                        vec![(&address0, true)].into_iter(),
+                        TransactionStatusMeta {
+                            status: err.clone().map_or(Ok(()), Err),
+                            ..TransactionStatusMeta::default()
+                        },
+                        transaction_index,
+                    )
+                    .unwrap();
+                blockstore
+                    .add_address_transactions_to_batch(
+                        *slot,
+                        *block_time,
+                        signature,
+                        vec![(&address0, true), (&address1, false)].into_iter(),
+                        err,
+                        transaction_index,
+                        &mut batch,
+                    )
+                    .unwrap();
+            }
+            blockstore.write_batch(batch).unwrap();
+            blockstore.set_block_time(*slot, *block_time).unwrap();
+        }
+        blockstore.set_roots([1, 2, 3].iter()).unwrap();
+
+        let signatures = |start_time, end_time, after: Option<u8>, limit| {
+            blockstore
+                .get_address_transactions_by_time(
+                    address0,
+                    3,
+                    start_time,
+                    end_time,
+                    after.map(|x| Signature::from([x; 64])),
+                    limit,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|info| (info.signature.as_ref()[0], info.slot, info.block_time))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            signatures(UnixTimestamp::MIN, UnixTimestamp::MAX, None, 10),
+            vec![
+                (1, 1, Some(100)),
+                (2, 1, Some(100)),
+                (3, 2, Some(200)),
+                (4, 2, Some(200)),
+                (5, 3, Some(200)),
+            ]
+        );
+        assert_eq!(
+            signatures(150, 250, None, 10),
+            vec![(3, 2, Some(200)), (4, 2, Some(200)), (5, 3, Some(200))]
+        );
+        assert_eq!(
+            signatures(0, 200, None, 10),
+            vec![(1, 1, Some(100)), (2, 1, Some(100))]
+        );
+
+        // Paging through the range resumes after the last signature of the previous page
+        assert_eq!(
+            signatures(0, 300, Some(2), 2),
+            vec![(3, 2, Some(200)), (4, 2, Some(200))]
+        );
+        assert_eq!(signatures(0, 300, Some(4), 2), vec![(5, 3, Some(200))]);
+        assert_eq!(signatures(0, 300, Some(5), 2), vec![]);
+        assert_eq!(signatures(150, 300, Some(1), 1), vec![(3, 2, Some(200))]);
+
+        let infos = blockstore
+            .get_address_transactions_by_time(address1, 3, 200, 201, None, 10)
+            .unwrap();
+        assert_eq!(infos.len(), 3);
+        assert_eq!(infos[1].err, Some(TransactionError::AccountNotFound));
+        assert_eq!(infos[0].err, None);
+    }
+
     #[test]
     fn test_get_confirmed_signatures_for_address2() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -126,6 +126,95 @@ impl Blockstore {
         }
     }
 
+    /// Purges the transaction history (statuses, memos and address indexes) of the
+    /// transactions in \[`from_slot`, `to_slot`\] for which `should_purge` returns true, and
+    /// returns the number of transactions purged.
+    ///
+    /// The transactions are found by deserializing the entries of each slot, so the history of
+    /// slots whose shreds are already gone is left to the regular purge. The same holds for
+    /// history written with the deprecated primary index keys.
+    pub fn purge_transaction_history(
+        &self,
+        from_slot: Slot,
+        to_slot: Slot,
+        should_purge: impl Fn(&AccountKeys) -> bool,
+    ) -> Result<usize> {
+        let mut batch = self.get_write_batch()?;
+        let mut num_purged = 0;
+        for slot in from_slot..=to_slot {
+            let block_time = self.blocktime_cf.get(slot)?;
+            let (slot_entries, _, _) =
+                self.get_slot_entries_with_shred_info(slot, 0, true /* allow_dead_slots */)?;
+            let transactions = slot_entries
+                .into_iter()
+                .flat_map(|entry| entry.transactions);
+            for (i, transaction) in transactions.enumerate() {
+                let Some(&signature) = transaction.signatures.first() else {
+                    continue;
+                };
+                let meta = self.read_transaction_status((signature, slot))?;
+                let loaded_addresses = meta.map(|meta| meta.loaded_addresses);
+                let account_keys = AccountKeys::new(
+                    transaction.message.static_account_keys(),
+                    loaded_addresses.as_ref(),
+                );
+                if !should_purge(&account_keys) {
+                    continue;
+                }
+
+                self.transaction_status_cf
+                    .delete_in_batch(&mut batch, (signature, slot))?;
+                self.transaction_memos_cf
+                    .delete_in_batch(&mut batch, (signature, slot))?;
+                let transaction_index =
+                    u32::try_from(i).map_err(|_| BlockstoreError::TransactionIndexOverflow)?;
+                for pubkey in account_keys.iter() {
+                    self.address_signatures_cf.delete_in_batch(
+                        &mut batch,
+                        (*pubkey, slot, transaction_index, signature),
+                    )?;
+                }
+                self.purge_address_transactions(
+                    &mut batch,
+                    block_time,
+                    &account_keys,
+                    slot,
+                    transaction_index,
+                    signature,
+                )?;
+                num_purged += 1;
+            }
+        }
+        self.write_batch(batch)?;
+        Ok(num_purged)
+    }
+
+    /// Deletes the address time index entries of a transaction, given the block time of its slot
+    ///
+    /// The block time of a slot is recorded along with its first address time index entries, see
+    /// [`Blockstore::add_address_transactions_to_batch`], so there are no entries to delete if
+    /// it is missing.
+    fn purge_address_transactions(
+        &self,
+        batch: &mut WriteBatch,
+        block_time: Option<UnixTimestamp>,
+        account_keys: &AccountKeys,
+        slot: Slot,
+        transaction_index: u32,
+        signature: Signature,
+    ) -> Result<()> {
+        let Some(block_time) = block_time else {
+            return Ok(());
+        };
+        for pubkey in account_keys.iter() {
+            self.address_transactions_cf.delete_in_batch(
+                batch,
+                (*pubkey, block_time, slot, transaction_index, signature),
+            )?;
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     pub(crate) fn run_purge(
         &self,
@@ -443,6 +532,7 @@ impl Blockstore {
 
         for slot in from_slot..=to_slot {
             let primary_indexes = slot_indexes(slot);
+            let block_time = self.blocktime_cf.get(slot)?;
 
             let (slot_entries, _, _) =
                 self.get_slot_entries_with_shred_info(slot, 0, true /* allow_dead_slots */)?;
@@ -485,9 +575,18 @@ impl Blockstore {
                             )?;
                         }
                     }
+                    self.purge_address_transactions(
+                        batch,
+                        block_time,
+                        &account_keys,
+                        slot,
+                        transaction_index,
+                        signature,
+                    )?;
                 }
             }
         }
+
         let mut update_highest_primary_index_slot = false;
         if index0.max_slot >= from_slot && index0.max_slot <= to_slot {
             index0.max_slot = from_slot.saturating_sub(1);
@@ -584,6 +683,101 @@ pub mod tests {
         assert_eq!(status_entries.len(), 10);
     }
 
+    #[test]
+    fn test_purge_transaction_history() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let mut transactions = vec![];
+        for slot in 0..4 {
+            let entries = make_slot_entries_with_transactions(1);
+            let shreds = entries_to_test_shreds(&entries, slot, slot.saturating_sub(1), true, 0);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+            let transaction = entries
+                .into_iter()
+                .flat_map(|entry| entry.transactions)
+                .next()
+                .unwrap();
+            blockstore
+                .write_transaction_status(
+                    slot,
+                    transaction.signatures[0],
+                    transaction
+                        .message
+                        .static_account_keys()
+                        .iter()
+                        .map(|key| (key, true)),
+                    TransactionStatusMeta::default(),
+                    0,
+                )
+                .unwrap();
+            let mut batch = blockstore.get_write_batch().unwrap();
+            blockstore
+                .add_address_transactions_to_batch(
+                    slot,
+                    1_000 + slot as UnixTimestamp,
+                    transaction.signatures[0],
+                    transaction
+                        .message
+                        .static_account_keys()
+                        .iter()
+                        .map(|key| (key, true)),
+                    None,
+                    0,
+                    &mut batch,
+                )
+                .unwrap();
+            blockstore.write_batch(batch).unwrap();
+            // The block time the entries are keyed by is recorded along with them
+            assert_eq!(
+                blockstore.blocktime_cf.get(slot).unwrap(),
+                Some(1_000 + slot as UnixTimestamp)
+            );
+            transactions.push(transaction);
+        }
+
+        // Slot 3 is out of range and the transaction in slot 1 is retained
+        let retained_address = transactions[1].message.static_account_keys()[0];
+        let num_purged = blockstore
+            .purge_transaction_history(0, 2, |account_keys| {
+                !account_keys.iter().any(|key| *key == retained_address)
+            })
+            .unwrap();
+        assert_eq!(num_purged, 2);
+
+        for (slot, transaction) in (0..).zip(&transactions) {
+            let status = blockstore
+                .read_transaction_status((transaction.signatures[0], slot))
+                .unwrap();
+            assert_eq!(status.is_some(), slot == 1 || slot == 3);
+        }
+        let num_address_signatures = blockstore
+            .address_signatures_cf
+            .iter(IteratorMode::Start)
+            .unwrap()
+            .count();
+        assert_eq!(
+            num_address_signatures,
+            2 * transactions[0].message.static_account_keys().len()
+        );
+        let num_address_transactions = blockstore
+            .address_transactions_cf
+            .iter(IteratorMode::Start)
+            .unwrap()
+            .count();
+        assert_eq!(num_address_transactions, num_address_signatures);
+
+        // The rest of the range is purged along with the slots
+        blockstore.run_purge(0, 2, PurgeType::Exact).unwrap();
+        let address_transaction_slots = blockstore
+            .address_transactions_cf
+            .iter(IteratorMode::Start)
+            .unwrap()
+            .map(|((_, _, slot, _, _), _)| slot)
+            .collect::<HashSet<_>>();
+        assert_eq!(address_transaction_slots, HashSet::from([3]));
+    }
+
     fn clear_and_repopulate_transaction_statuses_for_test(blockstore: &Blockstore, max_slot: u64) {
         blockstore.run_purge(0, max_slot, PurgeType::Exact).unwrap();
         let mut iter = blockstore
@@ -147,6 +147,17 @@ pub mod columns {
     /// * value type: [`blockstore_meta::AddressSignatureMeta`]
     pub struct AddressSignatures;
 
+    #[derive(Debug)]
+    /// The address transactions column.
+    ///
+    /// This column family indexes the transactions of an address by block time, so that the
+    /// transactions in a time range are found with a single range scan. It is only written to
+    /// when the address time index is enabled.
+    ///
+    /// * index type: `(`[`Pubkey`]`, `[`UnixTimestamp`]`, `[`Slot`]`, u32, `[`Signature`]`)`
+    /// * value type: [`blockstore_meta::AddressTransactionMeta`]
+    pub struct AddressTransactions;
+
     #[derive(Debug)]
     /// The transaction memos column
     ///
@@ -306,6 +317,10 @@ impl TypedColumn for columns::AddressSignatures {
     type Type = blockstore_meta::AddressSignatureMeta;
 }
 
+impl TypedColumn for columns::AddressTransactions {
+    type Type = blockstore_meta::AddressTransactionMeta;
+}
+
 impl TypedColumn for columns::TransactionMemos {
     type Type = String;
 }
@@ -446,6 +461,64 @@ impl ColumnName for columns::AddressSignatures {
     const NAME: &'static str = "address_signatures";
 }
 
+// Flipping the sign bit makes the big-endian bytes of timestamps sort in time order
+fn block_time_to_be_bytes(block_time: UnixTimestamp) -> [u8; 8] {
+    ((block_time as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+fn block_time_from_be_bytes(bytes: [u8; 8]) -> UnixTimestamp {
+    (u64::from_be_bytes(bytes) ^ (1 << 63)) as UnixTimestamp
+}
+
+impl Column for columns::AddressTransactions {
+    type Index = (
+        Pubkey,
+        UnixTimestamp,
+        Slot,
+        /*transaction index:*/ u32,
+        Signature,
+    );
+    type Key = [u8; PUBKEY_BYTES
+        + std::mem::size_of::<UnixTimestamp>()
+        + std::mem::size_of::<Slot>()
+        + std::mem::size_of::<u32>()
+        + SIGNATURE_BYTES];
+
+    #[inline]
+    fn key((pubkey, block_time, slot, transaction_index, signature): &Self::Index) -> Self::Key {
+        convert_column_index_to_key_bytes!(Key,
+              ..32 => pubkey.as_ref(),
+            32..40 => &block_time_to_be_bytes(*block_time),
+            40..48 => &slot.to_be_bytes(),
+            48..52 => &transaction_index.to_be_bytes(),
+            52..   => signature.as_ref(),
+        )
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        convert_column_key_bytes_to_index!(key,
+             0..32  => Pubkey::from,
+            32..40  => block_time_from_be_bytes,
+            40..48  => Slot::from_be_bytes,
+            48..52  => u32::from_be_bytes,
+            52..116 => Signature::from,
+        )
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.2
+    }
+
+    // The AddressTransactions column is not keyed by slot so this method is meaningless
+    // See Column::as_index() declaration for more details
+    fn as_index(_index: u64) -> Self::Index {
+        (Pubkey::default(), 0, 0, 0, Signature::default())
+    }
+}
+impl ColumnName for columns::AddressTransactions {
+    const NAME: &'static str = "address_transactions";
+}
+
 impl ColumnIndexDeprecation for columns::AddressSignatures {
     const CURRENT_INDEX_LEN: usize = 108;
     type DeprecatedIndex = (u64, Pubkey, Slot, Signature);
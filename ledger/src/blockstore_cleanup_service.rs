@@ -3,6 +3,13 @@
 //! can be done quickly and should have a fairly stable correlation to actual bytes.
 //! Once the shred count (and thus roughly the byte count) reaches a threshold,
 //! the services begins removing data in FIFO order.
+//!
+//! Purging competes with other writes to the ledger, so it is held off while the node is close to
+//! one of its leader slots and while slots that would be purged are being served to repair peers.
+//!
+//! The service also enforces the [`TransactionHistoryRetentionConfig`], which can purge the RPC
+//! transaction history well before the slots it belongs to, while keeping the history of a set of
+//! addresses for longer.
 
 use {
     crate::blockstore::{
@@ -12,7 +19,11 @@ use {
     },
     solana_clock::{Slot, DEFAULT_MS_PER_SLOT},
     solana_measure::measure::Measure,
+    solana_message::AccountKeys,
+    solana_pubkey::Pubkey,
     std::{
+        collections::HashSet,
+        ops::RangeInclusive,
         string::ToString,
         sync::{
             atomic::{AtomicBool, Ordering},
@@ -48,14 +59,89 @@ const DEFAULT_CLEANUP_SLOT_INTERVAL: u64 = 512;
 const LOOP_LIMITER: Duration =
     Duration::from_millis(DEFAULT_CLEANUP_SLOT_INTERVAL * DEFAULT_MS_PER_SLOT / 10);
 
+// Limit the number of slots whose transaction history is purged in one go, so that catching up
+// after a restart does not hold up the ledger cleanup
+const MAX_TRANSACTION_HISTORY_PURGE_SLOTS: u64 = 1024;
+
+pub const DEFAULT_CLEANUP_PAUSE_LEADER_SLOT_WINDOW: u64 = 8;
+pub const DEFAULT_CLEANUP_PAUSE_REPAIR_SERVING_WINDOW: Duration = Duration::from_secs(10);
+
+// Longest the cleanup is held off in a row, so that back to back leader slots or a steady stream
+// of repair requests cannot let the ledger grow without bound
+const MAX_CLEANUP_PAUSE: Duration = Duration::from_secs(300);
+
+/// When the cleanup service holds off on purging
+#[derive(Clone, Debug)]
+pub struct BlockstoreCleanupPauseConfig {
+    /// Nothing is purged while the highest slot in the blockstore is within this many slots of
+    /// one of the node's leader slots. Zero disables the pause.
+    pub leader_slot_window: u64,
+    /// Nothing is purged while a slot that would be purged was served to a repair peer within
+    /// this duration. Zero disables the pause.
+    pub repair_serving_window: Duration,
+}
+
+impl Default for BlockstoreCleanupPauseConfig {
+    fn default() -> Self {
+        Self {
+            leader_slot_window: DEFAULT_CLEANUP_PAUSE_LEADER_SLOT_WINDOW,
+            repair_serving_window: DEFAULT_CLEANUP_PAUSE_REPAIR_SERVING_WINDOW,
+        }
+    }
+}
+
+/// How long the RPC transaction history (statuses, memos and address signatures) is kept
+///
+/// The history never outlives the slots it belongs to, so these retention periods only matter when
+/// they are shorter than what `max_ledger_shreds` keeps.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionHistoryRetentionConfig {
+    /// The history of transactions that involve none of `retained_addresses` is purged once it is
+    /// more than this many slots older than the latest root
+    pub slots: Option<u64>,
+    /// Addresses whose history is kept according to `retained_address_slots` instead
+    pub retained_addresses: HashSet<Pubkey>,
+    /// The history of transactions that involve one of `retained_addresses` is purged once it is
+    /// more than this many slots older than the latest root
+    pub retained_address_slots: Option<u64>,
+}
+
+impl TransactionHistoryRetentionConfig {
+    fn is_retained(&self, account_keys: &AccountKeys) -> bool {
+        account_keys
+            .iter()
+            .any(|pubkey| self.retained_addresses.contains(pubkey))
+    }
+}
+
+/// The slots from which the transaction history purge continues
+#[derive(Debug, Default, PartialEq, Eq)]
+struct TransactionHistoryPurgeProgress {
+    next_slot: Slot,
+    next_retained_slot: Slot,
+}
+
 pub struct BlockstoreCleanupService {
     t_cleanup: JoinHandle<()>,
 }
 
 impl BlockstoreCleanupService {
-    pub fn new(blockstore: Arc<Blockstore>, max_ledger_shreds: u64, exit: Arc<AtomicBool>) -> Self {
+    /// `is_leader_slot` returns whether the node is the leader of a slot, and
+    /// `lowest_repair_served_slot` the lowest slot the node served to a repair peer within a
+    /// duration
+    pub fn new(
+        blockstore: Arc<Blockstore>,
+        max_ledger_shreds: u64,
+        pause_config: BlockstoreCleanupPauseConfig,
+        history_retention: TransactionHistoryRetentionConfig,
+        is_leader_slot: impl Fn(Slot) -> bool + Send + 'static,
+        lowest_repair_served_slot: impl Fn(Duration) -> Option<Slot> + Send + 'static,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
         let mut last_purge_slot = 0;
         let mut last_check_time = Instant::now();
+        let mut paused_since: Option<Instant> = None;
+        let mut history_purge_progress = TransactionHistoryPurgeProgress::default();
 
         let t_cleanup = Builder::new()
             .name("solBstoreClean".to_string())
@@ -68,15 +154,52 @@ impl BlockstoreCleanupService {
                     if exit.load(Ordering::Relaxed) {
                         break;
                     }
+                    // While paused, check again every second so that the cleanup happens as
+                    // soon as the leader slots are over
                     if last_check_time.elapsed() > LOOP_LIMITER {
-                        Self::cleanup_ledger(
-                            &blockstore,
-                            max_ledger_shreds,
-                            &mut last_purge_slot,
-                            DEFAULT_CLEANUP_SLOT_INTERVAL,
-                        );
-
-                        last_check_time = Instant::now();
+                        let can_pause = paused_since
+                            .is_none_or(|paused_since| paused_since.elapsed() < MAX_CLEANUP_PAUSE);
+                        if can_pause
+                            && Self::is_near_leader_slot(
+                                &blockstore,
+                                pause_config.leader_slot_window,
+                                &is_leader_slot,
+                            )
+                        {
+                            paused_since.get_or_insert_with(Instant::now);
+                        } else {
+                            let repair_served_slot = if !can_pause {
+                                warn!(
+                                    "Blockstore cleanup has been paused for more than {}s, \
+                                     cleaning up anyway",
+                                    MAX_CLEANUP_PAUSE.as_secs()
+                                );
+                                None
+                            } else if pause_config.repair_serving_window.is_zero() {
+                                None
+                            } else {
+                                lowest_repair_served_slot(pause_config.repair_serving_window)
+                            };
+                            let held_off = Self::cleanup_ledger(
+                                &blockstore,
+                                max_ledger_shreds,
+                                &mut last_purge_slot,
+                                DEFAULT_CLEANUP_SLOT_INTERVAL,
+                                repair_served_slot,
+                            );
+                            if held_off {
+                                paused_since.get_or_insert_with(Instant::now);
+                            } else {
+                                paused_since = None;
+                            }
+                            Self::purge_transaction_history(
+                                &blockstore,
+                                &history_retention,
+                                &mut history_purge_progress,
+                            );
+
+                            last_check_time = Instant::now();
+                        }
                     }
                     // Only sleep for 1 second instead of LOOP_LIMITER so that this
                     // thread can respond to the exit flag in a timely manner
@@ -89,6 +212,35 @@ impl BlockstoreCleanupService {
         Self { t_cleanup }
     }
 
+    /// Whether one of the node's leader slots is within `window` slots of the highest slot in the
+    /// blockstore
+    fn is_near_leader_slot(
+        blockstore: &Blockstore,
+        window: u64,
+        is_leader_slot: impl Fn(Slot) -> bool,
+    ) -> bool {
+        if window == 0 {
+            return false;
+        }
+        let current_slot = blockstore
+            .highest_slot()
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| blockstore.max_root());
+        let near_leader_slot = (current_slot.saturating_sub(window)
+            ..=current_slot.saturating_add(window))
+            .any(is_leader_slot);
+        if near_leader_slot {
+            debug!("Pausing Blockstore cleanup near leader slots, current slot: {current_slot}");
+            datapoint_info!(
+                "blockstore_cleanup_paused",
+                ("reason", "leader_slot", String),
+                ("slot", current_slot, i64),
+            );
+        }
+        near_leader_slot
+    }
+
     /// A helper function to `cleanup_ledger` which returns a tuple of the
     /// following four elements suggesting whether to clean up the ledger:
     ///
@@ -192,6 +344,11 @@ impl BlockstoreCleanupService {
     ///   `last_purge_slot` is fewer than `purge_interval`, the function will
     ///   simply return `Ok` without actually running the ledger cleanup.
     ///   In this case, `purge_interval` will remain unchanged.
+    /// - `repair_served_slot`: the lowest slot recently served to a repair
+    ///   peer, if any.  Nothing is purged, and `last_purge_slot` is left
+    ///   unchanged, if this slot would be purged.
+    ///
+    /// Returns whether the cleanup was held off because of `repair_served_slot`.
     ///
     /// Also see `blockstore::purge_slot`.
     pub fn cleanup_ledger(
@@ -199,18 +356,31 @@ impl BlockstoreCleanupService {
         max_ledger_shreds: u64,
         last_purge_slot: &mut u64,
         purge_interval: u64,
-    ) {
+        repair_served_slot: Option<Slot>,
+    ) -> bool {
         let root = blockstore.max_root();
         if root - *last_purge_slot <= purge_interval {
-            return;
+            return false;
         }
-        *last_purge_slot = root;
         info!("Looking for Blockstore data to cleanup, latest root: {root}");
 
         let disk_utilization_pre = blockstore.storage_size();
         let (slots_to_clean, lowest_cleanup_slot, total_shreds) =
             Self::find_slots_to_clean(blockstore, root, max_ledger_shreds);
 
+        if let Some(served_slot) = repair_served_slot
+            .filter(|served_slot| slots_to_clean && *served_slot <= lowest_cleanup_slot)
+        {
+            info!("Pausing Blockstore cleanup, slot {served_slot} is being served to repair peers");
+            datapoint_info!(
+                "blockstore_cleanup_paused",
+                ("reason", "repair_serving", String),
+                ("slot", served_slot, i64),
+            );
+            return true;
+        }
+        *last_purge_slot = root;
+
         if slots_to_clean {
             *blockstore.lowest_cleanup_slot.write().unwrap() = lowest_cleanup_slot;
 
@@ -235,6 +405,82 @@ impl BlockstoreCleanupService {
 
         let disk_utilization_post = blockstore.storage_size();
         Self::report_disk_metrics(disk_utilization_pre, disk_utilization_post, total_shreds);
+        false
+    }
+
+    /// Purges the transaction history that is past its retention period
+    ///
+    /// At most `MAX_TRANSACTION_HISTORY_PURGE_SLOTS` slots are purged per retention period, and
+    /// `progress` records where the next call continues.
+    fn purge_transaction_history(
+        blockstore: &Blockstore,
+        config: &TransactionHistoryRetentionConfig,
+        progress: &mut TransactionHistoryPurgeProgress,
+    ) {
+        let root = blockstore.max_root();
+        let lowest_slot = blockstore.lowest_slot();
+        if let Some(slot_range) = config.slots.and_then(|slots| {
+            next_history_purge_range(
+                &mut progress.next_slot,
+                lowest_slot,
+                root.checked_sub(slots)?,
+            )
+        }) {
+            Self::purge_transaction_history_in_range(
+                blockstore,
+                slot_range,
+                false,
+                |account_keys| !config.is_retained(account_keys),
+            );
+        }
+        if let Some(slot_range) = config.retained_address_slots.and_then(|slots| {
+            next_history_purge_range(
+                &mut progress.next_retained_slot,
+                lowest_slot,
+                root.checked_sub(slots)?,
+            )
+        }) {
+            Self::purge_transaction_history_in_range(
+                blockstore,
+                slot_range,
+                true,
+                |account_keys| config.is_retained(account_keys),
+            );
+        }
+    }
+
+    fn purge_transaction_history_in_range(
+        blockstore: &Blockstore,
+        slot_range: RangeInclusive<Slot>,
+        retained: bool,
+        should_purge: impl Fn(&AccountKeys) -> bool,
+    ) {
+        let mut purge_time = Measure::start("purge_transaction_history");
+        let result = blockstore.purge_transaction_history(
+            *slot_range.start(),
+            *slot_range.end(),
+            should_purge,
+        );
+        purge_time.stop();
+        match result {
+            Ok(num_purged) => {
+                debug!(
+                    "Purged the transaction history of {num_purged} transactions in slots \
+                     {slot_range:?}, retained addresses: {retained}. {purge_time}"
+                );
+                datapoint_info!(
+                    "blockstore_transaction_history_purge",
+                    ("from_slot", *slot_range.start(), i64),
+                    ("to_slot", *slot_range.end(), i64),
+                    ("retained_addresses", retained, bool),
+                    ("num_purged", num_purged, i64),
+                    ("purge_us", purge_time.as_us(), i64),
+                );
+            }
+            Err(err) => {
+                warn!("Failed to purge the transaction history of slots {slot_range:?}: {err}")
+            }
+        }
     }
 
     fn report_disk_metrics(
@@ -257,11 +503,34 @@ impl BlockstoreCleanupService {
         self.t_cleanup.join()
     }
 }
+/// Returns the next slots, at most `MAX_TRANSACTION_HISTORY_PURGE_SLOTS` of them, that are due to
+/// be purged up to `to_slot`, and advances `next_slot` past them
+fn next_history_purge_range(
+    next_slot: &mut Slot,
+    lowest_slot: Slot,
+    to_slot: Slot,
+) -> Option<RangeInclusive<Slot>> {
+    let from_slot = (*next_slot).max(lowest_slot);
+    if from_slot > to_slot {
+        return None;
+    }
+    let to_slot = to_slot.min(from_slot + MAX_TRANSACTION_HISTORY_PURGE_SLOTS - 1);
+    *next_slot = to_slot + 1;
+    Some(from_slot..=to_slot)
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
-        crate::{blockstore::make_many_slot_entries, get_tmp_ledger_path_auto_delete},
+        crate::{
+            blockstore::{
+                entries_to_test_shreds, make_many_slot_entries,
+                tests::make_slot_entries_with_transactions,
+            },
+            get_tmp_ledger_path_auto_delete,
+        },
+        solana_transaction_status::TransactionStatusMeta,
     };
 
     fn flush_blockstore_contents_to_disk(blockstore: Blockstore) -> Blockstore {
@@ -365,7 +634,25 @@ mod tests {
         // Mark 50 as a root to kill all but 5 shreds, which will be in the newest slots
         let mut last_purge_slot = 0;
         blockstore.set_roots([50].iter()).unwrap();
-        BlockstoreCleanupService::cleanup_ledger(&blockstore, 5, &mut last_purge_slot, 10);
+
+        // nothing is purged while a slot that would be purged is served to repair peers
+        assert!(BlockstoreCleanupService::cleanup_ledger(
+            &blockstore,
+            5,
+            &mut last_purge_slot,
+            10,
+            Some(20),
+        ));
+        assert_eq!(last_purge_slot, 0);
+        assert_eq!(blockstore.lowest_slot(), 1);
+
+        assert!(!BlockstoreCleanupService::cleanup_ledger(
+            &blockstore,
+            5,
+            &mut last_purge_slot,
+            10,
+            None,
+        ));
         assert_eq!(last_purge_slot, 50);
 
         //check that 0-40 don't exist
@@ -375,6 +662,37 @@ mod tests {
             .for_each(|(slot, _)| assert!(slot > 40));
     }
 
+    #[test]
+    fn test_is_near_leader_slot() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 100, 1);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        assert_eq!(blockstore.highest_slot().unwrap(), Some(99));
+
+        let is_leader_slot = |leader_slot| move |slot| slot == leader_slot;
+        assert!(BlockstoreCleanupService::is_near_leader_slot(
+            &blockstore,
+            8,
+            is_leader_slot(107)
+        ));
+        assert!(BlockstoreCleanupService::is_near_leader_slot(
+            &blockstore,
+            8,
+            is_leader_slot(91)
+        ));
+        assert!(!BlockstoreCleanupService::is_near_leader_slot(
+            &blockstore,
+            8,
+            is_leader_slot(108)
+        ));
+        assert!(!BlockstoreCleanupService::is_near_leader_slot(
+            &blockstore,
+            0,
+            is_leader_slot(99)
+        ));
+    }
+
     #[test]
     fn test_cleanup_speed() {
         agave_logger::setup();
@@ -412,6 +730,7 @@ mod tests {
                 initial_slots,
                 &mut last_purge_slot,
                 10,
+                None,
             );
             time.stop();
             info!("slot: {slot} size: {num_slots} {insert_time} {time}");
@@ -419,4 +738,88 @@ mod tests {
             num_slots *= 2;
         }
     }
+
+    #[test]
+    fn test_purge_transaction_history() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let mut signatures = vec![];
+        let mut retained_address = Pubkey::default();
+        for slot in 1..=10 {
+            let entries = make_slot_entries_with_transactions(1);
+            let shreds = entries_to_test_shreds(&entries, slot, slot.saturating_sub(1), true, 0);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+            let transaction = entries
+                .into_iter()
+                .flat_map(|entry| entry.transactions)
+                .next()
+                .unwrap();
+            let account_keys = transaction.message.static_account_keys();
+            blockstore
+                .write_transaction_status(
+                    slot,
+                    transaction.signatures[0],
+                    account_keys.iter().map(|key| (key, true)),
+                    TransactionStatusMeta::default(),
+                    0,
+                )
+                .unwrap();
+            if slot == 4 {
+                retained_address = account_keys[0];
+            }
+            signatures.push(transaction.signatures[0]);
+        }
+        blockstore.set_roots([10].iter()).unwrap();
+
+        let config = TransactionHistoryRetentionConfig {
+            slots: Some(4),
+            retained_addresses: HashSet::from([retained_address]),
+            retained_address_slots: Some(7),
+        };
+        let mut progress = TransactionHistoryPurgeProgress::default();
+        BlockstoreCleanupService::purge_transaction_history(&blockstore, &config, &mut progress);
+        assert_eq!(
+            progress,
+            TransactionHistoryPurgeProgress {
+                next_slot: 7,
+                next_retained_slot: 4,
+            }
+        );
+        // the history of slots 1-6 is purged, except for the retained transaction in slot 4
+        for (slot, signature) in (1..).zip(&signatures) {
+            let status = blockstore
+                .read_transaction_status((*signature, slot))
+                .unwrap();
+            assert_eq!(status.is_some(), slot == 4 || slot > 6, "slot {slot}");
+        }
+
+        // once the retained history is old enough, it is purged as well
+        blockstore.set_roots([13].iter()).unwrap();
+        BlockstoreCleanupService::purge_transaction_history(&blockstore, &config, &mut progress);
+        assert!(blockstore
+            .read_transaction_status((signatures[3], 4))
+            .unwrap()
+            .is_none());
+        assert!(blockstore
+            .read_transaction_status((signatures[9], 10))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_next_history_purge_range() {
+        let mut next_slot = 0;
+        assert_eq!(next_history_purge_range(&mut next_slot, 10, 5), None);
+        assert_eq!(
+            next_history_purge_range(&mut next_slot, 10, 20),
+            Some(10..=20)
+        );
+        assert_eq!(next_slot, 21);
+        assert_eq!(next_history_purge_range(&mut next_slot, 10, 20), None);
+        assert_eq!(
+            next_history_purge_range(&mut next_slot, 10, 5000),
+            Some(21..=20 + MAX_TRANSACTION_HISTORY_PURGE_SLOTS)
+        );
+    }
 }
@@ -17,6 +17,7 @@ use {
         blockstore_options::{AccessType, BlockstoreOptions, LedgerColumnOptions},
     },
     bincode::deserialize,
+    crossbeam_channel::{bounded, RecvTimeoutError, Sender},
     log::*,
     prost::Message,
     rocksdb::{
@@ -38,8 +39,10 @@ use {
         path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
-            Arc,
+            Arc, Weak,
         },
+        thread::{Builder, JoinHandle},
+        time::{Duration, Instant},
     },
 };
 
@@ -102,11 +105,14 @@ pub(crate) struct Rocks {
     oldest_slot: OldestSlot,
     column_options: Arc<LedgerColumnOptions>,
     write_batch_perf_status: PerfSamplingStatus,
+    // Whether writes are buffered until the WAL is flushed by a WalFlushService
+    manual_wal_flush: bool,
 }
 
 impl Rocks {
     pub(crate) fn open(path: PathBuf, options: BlockstoreOptions) -> Result<Rocks> {
         let recovery_mode = options.recovery_mode.clone();
+        let manual_wal_flush = use_manual_wal_flush(&options);
 
         fs::create_dir_all(&path)?;
 
@@ -152,6 +158,7 @@ impl Rocks {
             oldest_slot,
             column_options,
             write_batch_perf_status: PerfSamplingStatus::default(),
+            manual_wal_flush,
         };
 
         rocks.configure_compaction();
@@ -186,6 +193,7 @@ impl Rocks {
             new_cf_descriptor::<columns::ShredCode>(options, oldest_slot),
             new_cf_descriptor::<columns::TransactionStatus>(options, oldest_slot),
             new_cf_descriptor::<columns::AddressSignatures>(options, oldest_slot),
+            new_cf_descriptor::<columns::AddressTransactions>(options, oldest_slot),
             new_cf_descriptor::<columns::TransactionMemos>(options, oldest_slot),
             new_cf_descriptor::<columns::TransactionStatusIndex>(options, oldest_slot),
             new_cf_descriptor::<columns::Rewards>(options, oldest_slot),
@@ -242,7 +250,7 @@ impl Rocks {
         cf_descriptors
     }
 
-    const fn columns() -> [&'static str; 20] {
+    const fn columns() -> [&'static str; 21] {
         [
             columns::ErasureMeta::NAME,
             columns::DeadSlots::NAME,
@@ -256,6 +264,7 @@ impl Rocks {
             columns::ShredCode::NAME,
             columns::TransactionStatus::NAME,
             columns::AddressSignatures::NAME,
+            columns::AddressTransactions::NAME,
             columns::TransactionMemos::NAME,
             columns::TransactionStatusIndex::NAME,
             columns::Rewards::NAME,
@@ -428,6 +437,12 @@ impl Rocks {
         }
     }
 
+    /// Writes the buffered WAL entries to the WAL file, without syncing it to disk
+    pub(crate) fn flush_wal(&self) -> Result<()> {
+        self.db.flush_wal(false)?;
+        Ok(())
+    }
+
     pub(crate) fn is_primary_access(&self) -> bool {
         self.access_type == AccessType::Primary
             || self.access_type == AccessType::PrimaryForMaintenance
@@ -466,6 +481,108 @@ impl Rocks {
     }
 }
 
+impl Drop for Rocks {
+    fn drop(&mut self) {
+        if self.manual_wal_flush {
+            if let Err(err) = self.db.flush_wal(true) {
+                error!(
+                    "Failed to flush the blockstore WAL at {:?}: {err}",
+                    self.path
+                );
+            }
+        }
+    }
+}
+
+/// Flushes the WAL of a [`Rocks`] opened with a `wal_flush_interval` from a dedicated thread, so
+/// that the writes buffered since the previous flush are committed as a group rather than each
+/// insert paying for its own WAL write
+pub(crate) struct WalFlushService {
+    exit_sender: Option<Sender<()>>,
+    thread_hdl: Option<JoinHandle<()>>,
+}
+
+impl WalFlushService {
+    pub(crate) fn new(db: Weak<Rocks>, interval: Duration) -> Self {
+        let (exit_sender, exit_receiver) = bounded::<()>(0);
+        let thread_hdl = Builder::new()
+            .name("solBstoreWal".to_string())
+            .spawn(move || {
+                let mut stats = WalFlushStats::default();
+                // Nothing is sent on the channel; it is disconnected on exit
+                while let Err(RecvTimeoutError::Timeout) = exit_receiver.recv_timeout(interval) {
+                    let Some(db) = db.upgrade() else {
+                        break;
+                    };
+                    let flush_start = Instant::now();
+                    if let Err(err) = db.flush_wal() {
+                        warn!("Failed to flush the blockstore WAL: {err}");
+                        stats.num_failed_flushes += 1;
+                    }
+                    stats.record(flush_start.elapsed());
+                }
+            })
+            .unwrap();
+        Self {
+            exit_sender: Some(exit_sender),
+            thread_hdl: Some(thread_hdl),
+        }
+    }
+}
+
+impl Drop for WalFlushService {
+    fn drop(&mut self) {
+        drop(self.exit_sender.take());
+        if let Some(thread_hdl) = self.thread_hdl.take() {
+            if thread_hdl.join().is_err() {
+                error!("Blockstore WAL flush thread panicked");
+            }
+        }
+    }
+}
+
+struct WalFlushStats {
+    last_report: Instant,
+    num_flushes: u64,
+    num_failed_flushes: u64,
+    total_flush_us: u64,
+    max_flush_us: u64,
+}
+
+impl Default for WalFlushStats {
+    fn default() -> Self {
+        Self {
+            last_report: Instant::now(),
+            num_flushes: 0,
+            num_failed_flushes: 0,
+            total_flush_us: 0,
+            max_flush_us: 0,
+        }
+    }
+}
+
+impl WalFlushStats {
+    const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+    fn record(&mut self, flush_time: Duration) {
+        let flush_us = flush_time.as_micros() as u64;
+        self.num_flushes += 1;
+        self.total_flush_us += flush_us;
+        self.max_flush_us = self.max_flush_us.max(flush_us);
+
+        if self.last_report.elapsed() > Self::REPORT_INTERVAL {
+            datapoint_info!(
+                "blockstore_wal_flush",
+                ("num_flushes", self.num_flushes, i64),
+                ("num_failed_flushes", self.num_failed_flushes, i64),
+                ("total_flush_us", self.total_flush_us, i64),
+                ("max_flush_us", self.max_flush_us, i64),
+            );
+            *self = Self::default();
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct LedgerColumn<C: Column + ColumnName> {
     backend: Arc<Rocks>,
@@ -1174,6 +1291,20 @@ fn get_db_options(blockstore_options: &BlockstoreOptions) -> Options {
     }
     // Set max total wal size to 4G.
     options.set_max_total_wal_size(4 * 1024 * 1024 * 1024);
+    if use_manual_wal_flush(blockstore_options) {
+        options.set_manual_wal_flush(true);
+    }
+    if blockstore_options.wal_bytes_per_sync > 0 {
+        options.set_wal_bytes_per_sync(blockstore_options.wal_bytes_per_sync);
+    }
+    if blockstore_options.bytes_per_sync > 0 {
+        options.set_bytes_per_sync(blockstore_options.bytes_per_sync);
+    }
+
+    if let Some(rate) = blockstore_options.write_rate_limit_bytes_per_second {
+        // Refill every 100ms, with the default fairness between flushes and compactions
+        options.set_ratelimiter(rate.get() as i64, 100_000, 10);
+    }
 
     if should_disable_auto_compactions(&blockstore_options.access_type) {
         options.set_disable_auto_compactions(true);
@@ -1192,6 +1323,12 @@ fn get_db_options(blockstore_options: &BlockstoreOptions) -> Options {
     options
 }
 
+/// Whether the WAL is left to a [`WalFlushService`] rather than written on every write. Only
+/// primary access writes to the blockstore.
+pub(crate) fn use_manual_wal_flush(options: &BlockstoreOptions) -> bool {
+    options.wal_flush_interval.is_some() && !matches!(options.access_type, AccessType::Secondary)
+}
+
 /// The default number of threads to use for rocksdb compaction in the rocksdb
 /// low priority threadpool
 pub fn default_num_compaction_threads() -> NonZeroUsize {
@@ -1235,6 +1372,7 @@ fn should_enable_cf_compaction(cf_name: &str) -> bool {
         columns::TransactionStatus::NAME
             | columns::TransactionMemos::NAME
             | columns::AddressSignatures::NAME
+            | columns::AddressTransactions::NAME
     )
 }
 
@@ -1326,6 +1464,7 @@ pub mod tests {
         let columns_to_compact = [
             columns::TransactionStatus::NAME,
             columns::AddressSignatures::NAME,
+            columns::AddressTransactions::NAME,
         ];
         columns_to_compact.iter().for_each(|cf_name| {
             assert!(should_enable_cf_compaction(cf_name));
@@ -1421,6 +1560,38 @@ pub mod tests {
         assert!(!is_program_costs_column_present(db_path));
     }
 
+    #[test]
+    fn test_manual_wal_flush() {
+        agave_logger::setup();
+
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path();
+
+        let options = BlockstoreOptions {
+            wal_flush_interval: Some(Duration::from_millis(10)),
+            ..BlockstoreOptions::default()
+        };
+        assert!(use_manual_wal_flush(&options));
+        assert!(!use_manual_wal_flush(&BlockstoreOptions {
+            access_type: AccessType::Secondary,
+            ..options.clone()
+        }));
+
+        {
+            let rocks = Arc::new(Rocks::open(db_path.to_path_buf(), options.clone()).unwrap());
+            assert!(rocks.manual_wal_flush);
+            let _service = WalFlushService::new(Arc::downgrade(&rocks), Duration::from_millis(10));
+            rocks.column::<columns::Root>().put(1, &true).unwrap();
+            rocks.column::<columns::Root>().put(2, &true).unwrap();
+        }
+
+        // The buffered writes were flushed before the database was closed
+        let rocks = Arc::new(Rocks::open(db_path.to_path_buf(), options).unwrap());
+        let roots = rocks.column::<columns::Root>();
+        assert_eq!(roots.get(1).unwrap(), Some(true));
+        assert_eq!(roots.get(2).unwrap(), Some(true));
+    }
+
     impl<C> LedgerColumn<C>
     where
         C: ColumnIndexDeprecation + ProtobufColumn + ColumnName,
@@ -7,6 +7,7 @@ use {
     serde::{Deserialize, Deserializer, Serialize, Serializer},
     solana_clock::{Slot, UnixTimestamp},
     solana_hash::Hash,
+    solana_transaction_error::TransactionError,
     std::{
         collections::BTreeSet,
         ops::{Range, RangeBounds},
@@ -812,6 +813,12 @@ pub struct AddressSignatureMeta {
     pub writeable: bool,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AddressTransactionMeta {
+    pub writeable: bool,
+    pub err: Option<TransactionError>,
+}
+
 /// Performance information about validator execution during a time slice.
 ///
 /// Older versions should only arise as a result of deserialization of entries stored by a previous
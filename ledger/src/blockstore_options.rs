@@ -1,7 +1,10 @@
 use {
     crate::blockstore_db::{default_num_compaction_threads, default_num_flush_threads},
     rocksdb::{DBCompressionType as RocksCompressionType, DBRecoveryMode},
-    std::num::NonZeroUsize,
+    std::{
+        num::{NonZeroU64, NonZeroUsize},
+        time::Duration,
+    },
 };
 
 /// The subdirectory under ledger directory where the Blockstore lives
@@ -16,6 +19,21 @@ pub struct BlockstoreOptions {
     pub column_options: LedgerColumnOptions,
     pub num_rocksdb_compaction_threads: NonZeroUsize,
     pub num_rocksdb_flush_threads: NonZeroUsize,
+    // Limit on the bytes per second rocksdb writes in flushes and compactions. Default: None
+    pub write_rate_limit_bytes_per_second: Option<NonZeroU64>,
+    // Interval at which a dedicated thread flushes the write-ahead log. When set, writes are
+    // buffered in memory and reach the WAL together on the next flush instead of one by one, so
+    // writes since the last flush are lost if the process crashes. Default: None
+    pub wal_flush_interval: Option<Duration>,
+    // Bytes written to the WAL between background syncs to disk, 0 to leave it to the OS.
+    // Default: 0
+    pub wal_bytes_per_sync: u64,
+    // Bytes written to SST files between background syncs to disk, 0 to leave it to the OS.
+    // Default: 0
+    pub bytes_per_sync: u64,
+    // Whether the transaction history also indexes the transactions of each address by block
+    // time, for time range queries. Default: false
+    pub enable_address_time_index: bool,
 }
 
 impl Default for BlockstoreOptions {
@@ -29,6 +47,11 @@ impl Default for BlockstoreOptions {
             column_options: LedgerColumnOptions::default(),
             num_rocksdb_compaction_threads: default_num_compaction_threads(),
             num_rocksdb_flush_threads: default_num_flush_threads(),
+            write_rate_limit_bytes_per_second: None,
+            wal_flush_interval: None,
+            wal_bytes_per_sync: 0,
+            bytes_per_sync: 0,
+            enable_address_time_index: false,
         }
     }
 }
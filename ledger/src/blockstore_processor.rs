@@ -5,6 +5,7 @@ use {
         blockstore_meta::SlotMeta,
         entry_notifier_service::{EntryNotification, EntryNotifierSender},
         leader_schedule_cache::LeaderScheduleCache,
+        replay_determinism,
         transaction_balances::compile_collected_balances,
         use_snapshot_archives_at_startup::UseSnapshotArchivesAtStartup,
     },
@@ -18,7 +19,7 @@ use {
     solana_accounts_db::{
         accounts_db::AccountsDbConfig, accounts_update_notifier_interface::AccountsUpdateNotifier,
     },
-    solana_clock::{Slot, MAX_PROCESSING_AGE},
+    solana_clock::{Slot, UnixTimestamp, MAX_PROCESSING_AGE},
     solana_cost_model::{cost_model::CostModel, transaction_cost::TransactionCost},
     solana_entry::entry::{
         self, create_ticks, Entry, EntrySlice, EntryType, EntryVerificationStatus, VerifyRecyclers,
@@ -68,7 +69,7 @@ use {
         path::PathBuf,
         result,
         sync::{atomic::AtomicBool, Arc, Mutex, RwLock},
-        time::{Duration, Instant},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
         vec::Drain,
     },
     thiserror::Error,
@@ -176,6 +177,11 @@ pub fn execute_batch<'a>(
             None => {
                 // We're entering into one of the block-verification methods.
                 get_first_error(batch, processing_results)?;
+                if bank.replay_determinism_check() {
+                    let divergences =
+                        replay_determinism::check_batch(bank, batch, processing_results);
+                    replay_determinism::report_divergences(bank.slot(), &divergences);
+                }
                 Ok(None)
             }
             Some(extra_pre_commit_callback) => {
@@ -296,6 +302,7 @@ pub fn execute_batch<'a>(
 
         transaction_status_sender.send_transaction_status_batch(
             bank.slot(),
+            bank.clock().unix_timestamp,
             transactions,
             commit_results,
             balances,
@@ -1469,6 +1476,21 @@ pub struct ConfirmationProgress {
     pub num_shreds: u64,
     pub num_entries: usize,
     pub num_txs: usize,
+    /// Timings of the batches of entries confirmed so far, in replay order
+    pub entry_batch_timings: Vec<EntryBatchTiming>,
+}
+
+/// When a batch of entries loaded from the blockstore was replayed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntryBatchTiming {
+    /// Index in the slot of the first entry of the batch
+    pub first_entry_index: usize,
+    pub num_entries: usize,
+    /// When the entries were loaded for replay, in microseconds since the UNIX epoch
+    pub loaded_timestamp_us: u64,
+    /// When the PoH and signature verification of the entries completed, in microseconds since
+    /// the UNIX epoch
+    pub verified_timestamp_us: u64,
 }
 
 impl ConfirmationProgress {
@@ -1557,6 +1579,7 @@ fn confirm_slot_entries(
         *confirmation_elapsed += confirmation_elapsed_timer.end_as_us();
     };
 
+    let loaded_timestamp_us = timestamp_us();
     let slot = bank.slot();
     let (entries, num_shreds, slot_full) = slot_entries_load_result;
     let num_entries = entries.len();
@@ -1573,6 +1596,7 @@ fn confirm_slot_entries(
                     index: entry_index,
                     entry: entry.into(),
                     starting_transaction_index: entry_tx_starting_index,
+                    is_last_in_slot: slot_full && i + 1 == num_entries,
                 }) {
                     warn!(
                         "Slot {slot}, entry {entry_index} entry_notification_sender send failed: \
@@ -1717,6 +1741,14 @@ fn confirm_slot_entries(
 
     process_result?;
 
+    if num_entries > 0 {
+        progress.entry_batch_timings.push(EntryBatchTiming {
+            first_entry_index: progress.num_entries,
+            num_entries,
+            loaded_timestamp_us,
+            verified_timestamp_us: timestamp_us(),
+        });
+    }
     progress.num_shreds += num_shreds;
     progress.num_entries += num_entries;
     progress.num_txs += num_txs;
@@ -1727,6 +1759,13 @@ fn confirm_slot_entries(
     Ok(())
 }
 
+fn timestamp_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_micros() as u64)
+        .unwrap_or_default()
+}
+
 // Special handling required for processing the entries in slot 0
 #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
 fn process_bank_0(
@@ -2263,6 +2302,7 @@ pub enum TransactionStatusMessage {
 #[derive(Debug)]
 pub struct TransactionStatusBatch {
     pub slot: Slot,
+    pub block_time: UnixTimestamp,
     pub transactions: Vec<SanitizedTransaction>,
     pub commit_results: Vec<TransactionCommitResult>,
     pub balances: TransactionBalancesSet,
@@ -2281,6 +2321,7 @@ impl TransactionStatusSender {
     pub fn send_transaction_status_batch(
         &self,
         slot: Slot,
+        block_time: UnixTimestamp,
         transactions: Vec<SanitizedTransaction>,
         commit_results: Vec<TransactionCommitResult>,
         balances: TransactionBalancesSet,
@@ -2296,6 +2337,7 @@ impl TransactionStatusSender {
         if let Err(e) = self.sender.send(TransactionStatusMessage::Batch((
             TransactionStatusBatch {
                 slot,
+                block_time,
                 transactions,
                 commit_results,
                 balances,
@@ -5053,6 +5095,19 @@ pub mod tests {
         } else {
             panic!("batch should have been sent");
         }
+
+        let timings = &progress.entry_batch_timings;
+        assert_eq!(
+            timings
+                .iter()
+                .map(|timing| (timing.first_entry_index, timing.num_entries))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 1)]
+        );
+        assert!(timings
+            .iter()
+            .all(|timing| timing.loaded_timestamp_us <= timing.verified_timestamp_us));
+        assert!(timings[0].verified_timestamp_us <= timings[1].loaded_timestamp_us);
     }
 
     fn do_test_schedule_batches_for_execution(should_succeed: bool) {
@@ -7,6 +7,7 @@ pub trait EntryNotifier {
         index: usize,
         entry: &EntrySummary,
         starting_transaction_index: usize,
+        is_last_in_slot: bool,
     );
 }
 
@@ -18,6 +18,8 @@ pub struct EntryNotification {
     pub index: usize,
     pub entry: EntrySummary,
     pub starting_transaction_index: usize,
+    /// Whether this is the last entry of the slot
+    pub is_last_in_slot: bool,
 }
 
 pub type EntryNotifierSender = Sender<EntryNotification>;
@@ -60,8 +62,15 @@ impl EntryNotifierService {
             index,
             entry,
             starting_transaction_index,
+            is_last_in_slot,
         } = entry_notification_receiver.recv_timeout(Duration::from_secs(1))?;
-        entry_notifier.notify_entry(slot, index, &entry, starting_transaction_index);
+        entry_notifier.notify_entry(
+            slot,
+            index,
+            &entry,
+            starting_transaction_index,
+            is_last_in_slot,
+        );
         Ok(())
     }
 
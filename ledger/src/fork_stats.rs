@@ -0,0 +1,203 @@
+//! Rolling statistics on skipped slots and forks of the cluster
+//!
+//! Every block that replay roots is recorded with its parent and the number of children the
+//! blockstore has seen for it. A gap between a block and its parent means the leaders of the
+//! slots in between did not produce a block that the cluster built on, and a block with more
+//! than one child means the cluster forked at that block. As only rooted blocks are recorded,
+//! the statistics describe the cluster as a whole rather than the forks this node happened to
+//! replay.
+
+use {
+    solana_clock::Slot,
+    solana_metrics::datapoint_info,
+    std::{
+        collections::VecDeque,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
+};
+
+/// About an hour of slots
+pub const FORK_STATS_WINDOW_SLOTS: Slot = 9_000;
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RootedBlock {
+    slot: Slot,
+    parent_slot: Slot,
+    num_children: usize,
+}
+
+#[derive(Debug, Default)]
+struct ForkStatsInner {
+    /// Rooted blocks in the window, oldest first
+    rooted_blocks: VecDeque<RootedBlock>,
+    last_report: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+pub struct ForkStats {
+    inner: Mutex<ForkStatsInner>,
+}
+
+/// Statistics over the slots from `first_slot` to `last_slot`, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkStatsSummary {
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    pub num_blocks: u64,
+    pub num_skipped_slots: u64,
+    /// Number of rooted blocks that more than one block was built on
+    pub num_forks: u64,
+}
+
+impl ForkStatsSummary {
+    /// Percentage of the slots in the window that have no rooted block
+    pub fn skip_rate(&self) -> f64 {
+        let num_slots = self.num_blocks.saturating_add(self.num_skipped_slots);
+        if num_slots == 0 {
+            return 0.0;
+        }
+        100.0 * self.num_skipped_slots as f64 / num_slots as f64
+    }
+
+    /// Number of forks per thousand slots in the window
+    pub fn forks_per_thousand_slots(&self) -> f64 {
+        let num_slots = self.num_blocks.saturating_add(self.num_skipped_slots);
+        if num_slots == 0 {
+            return 0.0;
+        }
+        1_000.0 * self.num_forks as f64 / num_slots as f64
+    }
+}
+
+impl ForkStats {
+    /// Records a newly rooted block, which must be rooted after the blocks recorded before it
+    pub fn record_rooted_block(&self, slot: Slot, parent_slot: Slot, num_children: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner
+            .rooted_blocks
+            .back()
+            .is_some_and(|last| last.slot >= slot)
+        {
+            return;
+        }
+        inner.rooted_blocks.push_back(RootedBlock {
+            slot,
+            parent_slot,
+            num_children,
+        });
+        let window_start = slot.saturating_sub(FORK_STATS_WINDOW_SLOTS);
+        while inner
+            .rooted_blocks
+            .front()
+            .is_some_and(|block| block.parent_slot < window_start)
+        {
+            inner.rooted_blocks.pop_front();
+        }
+    }
+
+    pub fn summary(&self) -> Option<ForkStatsSummary> {
+        Self::summarize(&self.inner.lock().unwrap().rooted_blocks)
+    }
+
+    fn summarize(rooted_blocks: &VecDeque<RootedBlock>) -> Option<ForkStatsSummary> {
+        let first_block = rooted_blocks.front()?;
+        let last_block = rooted_blocks.back()?;
+        let mut summary = ForkStatsSummary {
+            first_slot: first_block.parent_slot.saturating_add(1),
+            last_slot: last_block.slot,
+            num_blocks: 0,
+            num_skipped_slots: 0,
+            num_forks: 0,
+        };
+        for block in rooted_blocks {
+            summary.num_blocks = summary.num_blocks.saturating_add(1);
+            summary.num_skipped_slots = summary.num_skipped_slots.saturating_add(
+                block
+                    .slot
+                    .saturating_sub(block.parent_slot)
+                    .saturating_sub(1),
+            );
+            if block.num_children > 1 {
+                summary.num_forks = summary.num_forks.saturating_add(1);
+            }
+        }
+        Some(summary)
+    }
+
+    /// Reports the statistics, at most once every few seconds
+    pub fn maybe_report(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner
+            .last_report
+            .is_some_and(|last_report| last_report.elapsed() < REPORT_INTERVAL)
+        {
+            return;
+        }
+        inner.last_report = Some(Instant::now());
+        let Some(summary) = Self::summarize(&inner.rooted_blocks) else {
+            return;
+        };
+        drop(inner);
+        datapoint_info!(
+            "cluster-fork-stats",
+            ("first_slot", summary.first_slot, i64),
+            ("last_slot", summary.last_slot, i64),
+            ("num_blocks", summary.num_blocks, i64),
+            ("num_skipped_slots", summary.num_skipped_slots, i64),
+            ("num_forks", summary.num_forks, i64),
+            ("skip_rate", summary.skip_rate(), f64),
+            (
+                "forks_per_thousand_slots",
+                summary.forks_per_thousand_slots(),
+                f64
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_stats() {
+        let fork_stats = ForkStats::default();
+        assert_eq!(fork_stats.summary(), None);
+
+        fork_stats.record_rooted_block(1, 0, 1);
+        // Slots 2 and 3 were skipped, and a fork started at slot 4
+        fork_stats.record_rooted_block(4, 1, 2);
+        fork_stats.record_rooted_block(6, 4, 1);
+        // Already recorded
+        fork_stats.record_rooted_block(6, 4, 1);
+
+        let summary = fork_stats.summary().unwrap();
+        assert_eq!(
+            summary,
+            ForkStatsSummary {
+                first_slot: 1,
+                last_slot: 6,
+                num_blocks: 3,
+                num_skipped_slots: 3,
+                num_forks: 1,
+            }
+        );
+        assert_eq!(summary.skip_rate(), 50.0);
+        assert!((summary.forks_per_thousand_slots() - 1_000.0 / 6.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_fork_stats_window() {
+        let fork_stats = ForkStats::default();
+        for slot in 1..=FORK_STATS_WINDOW_SLOTS + 10 {
+            fork_stats.record_rooted_block(slot, slot - 1, 1);
+        }
+        let summary = fork_stats.summary().unwrap();
+        assert_eq!(summary.first_slot, 11);
+        assert_eq!(summary.last_slot, FORK_STATS_WINDOW_SLOTS + 10);
+        assert_eq!(summary.num_blocks, FORK_STATS_WINDOW_SLOTS);
+        assert_eq!(summary.skip_rate(), 0.0);
+    }
+}
@@ -14,7 +14,7 @@ use {
         collections::{hash_map::Entry, HashMap, VecDeque},
         sync::{
             atomic::{AtomicU64, Ordering},
-            Arc, RwLock,
+            Arc, Mutex, RwLock,
         },
     },
 };
@@ -29,6 +29,17 @@ impl Default for CacheCapacity {
     }
 }
 
+/// The inputs a leader schedule is derived from: whether it is keyed by vote account, and the
+/// (vote account, stake, node) of every staked vote account
+type ScheduleStakes = (bool, Vec<(Pubkey, u64, Pubkey)>);
+
+/// A leader schedule computed before the root reached the epoch fixing its stakes
+struct PrecomputedSchedule {
+    epoch: Epoch,
+    stakes: ScheduleStakes,
+    leader_schedule: Arc<LeaderSchedule>,
+}
+
 #[derive(Default)]
 pub struct LeaderScheduleCache {
     // Map from an epoch to a leader schedule for that epoch
@@ -37,6 +48,7 @@ pub struct LeaderScheduleCache {
     max_epoch: AtomicU64,
     max_schedules: CacheCapacity,
     fixed_schedule: Option<Arc<FixedSchedule>>,
+    precomputed_schedule: Mutex<Option<PrecomputedSchedule>>,
 }
 
 impl LeaderScheduleCache {
@@ -51,6 +63,7 @@ impl LeaderScheduleCache {
             max_epoch: AtomicU64::new(0),
             max_schedules: CacheCapacity::default(),
             fixed_schedule: None,
+            precomputed_schedule: Mutex::default(),
         };
 
         // This sets the root and calculates the schedule at leader_schedule_epoch(root)
@@ -86,8 +99,73 @@ impl LeaderScheduleCache {
 
         // Calculate the epoch as soon as it's rooted
         if new_max_epoch > old_max_epoch {
-            self.compute_epoch_schedule(new_max_epoch, root_bank);
+            match self.take_precomputed_schedule(new_max_epoch, root_bank) {
+                Some(leader_schedule) => {
+                    self.insert_epoch_schedule(new_max_epoch, leader_schedule);
+                }
+                None => {
+                    self.compute_epoch_schedule(new_max_epoch, root_bank);
+                }
+            }
+        }
+    }
+
+    /// Computes the leader schedule of `epoch` from `bank` before the root reaches the epoch in
+    /// which its stakes are fixed, so that [`Self::set_root`] does not have to compute it then.
+    ///
+    /// The schedule is only used if the new root has the same stakes for `epoch` as `bank`,
+    /// which is not the case if `bank` is on a fork that diverged before the epoch boundary.
+    /// Returns whether a schedule was computed.
+    pub fn precompute_epoch_schedule(&self, epoch: Epoch, bank: &Bank) -> bool {
+        if self.fixed_schedule.is_some() || epoch <= self.max_epoch.load(Ordering::Acquire) {
+            return false;
+        }
+        let Some(stakes) = Self::schedule_stakes(epoch, bank) else {
+            return false;
+        };
+        let Some(leader_schedule) = leader_schedule_utils::leader_schedule(epoch, bank) else {
+            return false;
+        };
+        *self.precomputed_schedule.lock().unwrap() = Some(PrecomputedSchedule {
+            epoch,
+            stakes,
+            leader_schedule: Arc::new(leader_schedule),
+        });
+        true
+    }
+
+    fn take_precomputed_schedule(
+        &self,
+        epoch: Epoch,
+        root_bank: &Bank,
+    ) -> Option<Arc<LeaderSchedule>> {
+        let precomputed = {
+            let mut precomputed_schedule = self.precomputed_schedule.lock().unwrap();
+            if precomputed_schedule.as_ref()?.epoch > epoch {
+                return None;
+            }
+            precomputed_schedule.take()?
+        };
+        if precomputed.epoch != epoch
+            || Some(precomputed.stakes) != Self::schedule_stakes(epoch, root_bank)
+        {
+            debug!("Discarding the leader schedule precomputed for epoch {epoch}");
+            return None;
         }
+        Some(precomputed.leader_schedule)
+    }
+
+    fn schedule_stakes(epoch: Epoch, bank: &Bank) -> Option<ScheduleStakes> {
+        let vote_keyed = bank.should_use_vote_keyed_leader_schedule(epoch)?;
+        let mut stakes: Vec<_> = bank
+            .epoch_vote_accounts(epoch)?
+            .iter()
+            .map(|(vote_pubkey, (stake, vote_account))| {
+                (*vote_pubkey, *stake, *vote_account.node_pubkey())
+            })
+            .collect();
+        stakes.sort_unstable();
+        Some((vote_keyed, stakes))
     }
 
     pub fn slot_leader_at(&self, slot: Slot, bank: Option<&Bank>) -> Option<Pubkey> {
@@ -215,19 +293,23 @@ impl LeaderScheduleCache {
         let leader_schedule = leader_schedule_utils::leader_schedule(epoch, bank);
         leader_schedule.map(|leader_schedule| {
             let leader_schedule = Arc::new(leader_schedule);
-            let (ref mut cached_schedules, ref mut order) = *self.cached_schedules.write().unwrap();
-            // Check to see if schedule exists in case somebody already inserted in the time we were
-            // waiting for the lock
-            let entry = cached_schedules.entry(epoch);
-            if let Entry::Vacant(v) = entry {
-                v.insert(leader_schedule.clone());
-                order.push_back(epoch);
-                Self::retain_latest(cached_schedules, order, self.max_schedules());
-            }
+            self.insert_epoch_schedule(epoch, leader_schedule.clone());
             leader_schedule
         })
     }
 
+    fn insert_epoch_schedule(&self, epoch: Epoch, leader_schedule: Arc<LeaderSchedule>) {
+        let (ref mut cached_schedules, ref mut order) = *self.cached_schedules.write().unwrap();
+        // Check to see if schedule exists in case somebody already inserted in the time we were
+        // waiting for the lock
+        let entry = cached_schedules.entry(epoch);
+        if let Entry::Vacant(v) = entry {
+            v.insert(leader_schedule);
+            order.push_back(epoch);
+            Self::retain_latest(cached_schedules, order, self.max_schedules());
+        }
+    }
+
     fn retain_latest(
         schedules: &mut HashMap<Epoch, Arc<LeaderSchedule>>,
         order: &mut VecDeque<u64>,
@@ -610,6 +692,52 @@ mod tests {
         assert!(cache.slot_leader_at(224, Some(&bank2)).is_none());
     }
 
+    #[test]
+    fn test_precompute_epoch_schedule() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let cache = LeaderScheduleCache::new_from_bank(&bank);
+        assert_eq!(cache.max_epoch.load(Ordering::Acquire), 1);
+        let bank2 = Bank::new_from_parent(bank.clone(), &solana_pubkey::new_rand(), 95);
+
+        // Epoch 1 is already confirmed and the stakes of epoch 3 are not known yet
+        assert!(!cache.precompute_epoch_schedule(1, &bank2));
+        assert!(!cache.precompute_epoch_schedule(3, &bank2));
+
+        assert!(cache.precompute_epoch_schedule(2, &bank2));
+        assert!(cache.get_epoch_leader_schedule(2).is_none());
+        let precomputed = cache
+            .precomputed_schedule
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .leader_schedule
+            .clone();
+        cache.set_root(&bank2);
+        assert!(Arc::ptr_eq(
+            &cache.get_epoch_leader_schedule(2).unwrap(),
+            &precomputed
+        ));
+        assert!(cache.precomputed_schedule.lock().unwrap().is_none());
+
+        // A schedule precomputed from different stakes is discarded
+        let cache = LeaderScheduleCache::new_from_bank(&bank);
+        assert!(cache.precompute_epoch_schedule(2, &bank2));
+        let precomputed = {
+            let mut precomputed_schedule = cache.precomputed_schedule.lock().unwrap();
+            let precomputed_schedule = precomputed_schedule.as_mut().unwrap();
+            precomputed_schedule.stakes.1.clear();
+            precomputed_schedule.leader_schedule.clone()
+        };
+        cache.set_root(&bank2);
+        assert!(!Arc::ptr_eq(
+            &cache.get_epoch_leader_schedule(2).unwrap(),
+            &precomputed
+        ));
+        assert!(cache.slot_leader_at(96, None).is_some());
+    }
+
     #[test]
     fn test_set_max_schedules() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
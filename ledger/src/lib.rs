@@ -29,11 +29,13 @@ pub mod blockstore_options;
 pub mod blockstore_processor;
 pub mod entry_notifier_interface;
 pub mod entry_notifier_service;
+pub mod fork_stats;
 pub mod genesis_utils;
 pub mod leader_schedule;
 pub mod leader_schedule_cache;
 pub mod leader_schedule_utils;
 pub mod next_slots_iterator;
+mod replay_determinism;
 pub mod rooted_slot_iterator;
 
 #[cfg(feature = "agave-unstable-api")]
@@ -0,0 +1,276 @@
+//! Replay determinism checking
+//!
+//! When [`RuntimeConfig::replay_determinism_check`] is set, every batch replayed for block
+//! verification is executed a second time before its results are committed: one transaction at a
+//! time, in reverse order, instead of as a single batch. Transactions in a batch never share a
+//! writable account, so both executions must produce the same outcome for every transaction. Any
+//! difference is nondeterministic behavior in the runtime or a program, the kind of bug that
+//! shows up as a consensus divergence between validators.
+//!
+//! [`RuntimeConfig::replay_determinism_check`]: solana_runtime::runtime_config::RuntimeConfig
+
+use {
+    solana_account::AccountSharedData,
+    solana_clock::{Slot, MAX_PROCESSING_AGE},
+    solana_pubkey::Pubkey,
+    solana_runtime::{bank::Bank, transaction_batch::TransactionBatch},
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    solana_signature::Signature,
+    solana_svm::{
+        rollback_accounts::RollbackAccounts,
+        transaction_error_metrics::TransactionErrorMetrics,
+        transaction_processing_result::{ProcessedTransaction, TransactionProcessingResult},
+        transaction_processor::{ExecutionRecordingConfig, TransactionProcessingConfig},
+    },
+    solana_svm_timings::ExecuteTimings,
+    solana_svm_transaction::{svm_message::SVMMessage, svm_transaction::SVMTransaction},
+    solana_transaction_error::TransactionResult as Result,
+    std::fmt,
+};
+
+/// The parts of a transaction's processing result that end up in the bank
+#[derive(Debug, PartialEq)]
+struct TransactionOutcome {
+    status: Result<()>,
+    executed_units: u64,
+    fee: u64,
+    account_changes: AccountChanges,
+}
+
+#[derive(Debug, PartialEq)]
+enum AccountChanges {
+    /// Post-execution state of every writable account of a successful transaction
+    Committed(Vec<(Pubkey, AccountSharedData)>),
+    /// Fee payer and nonce state of a transaction that failed
+    RolledBack(RollbackAccounts),
+    /// The transaction was not processed
+    None,
+}
+
+impl TransactionOutcome {
+    fn new(transaction: &impl SVMMessage, processing_result: &TransactionProcessingResult) -> Self {
+        let Ok(processed_tx) = processing_result else {
+            return Self {
+                status: processing_result.as_ref().map(|_| ()).map_err(Clone::clone),
+                executed_units: 0,
+                fee: 0,
+                account_changes: AccountChanges::None,
+            };
+        };
+        let account_changes = match processed_tx {
+            ProcessedTransaction::Executed(executed_tx) if executed_tx.was_successful() => {
+                AccountChanges::Committed(
+                    executed_tx
+                        .loaded_transaction
+                        .accounts
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| transaction.is_writable(*index))
+                        .map(|(_, account)| account.clone())
+                        .collect(),
+                )
+            }
+            ProcessedTransaction::Executed(executed_tx) => {
+                AccountChanges::RolledBack(executed_tx.loaded_transaction.rollback_accounts.clone())
+            }
+            ProcessedTransaction::FeesOnly(fees_only_tx) => {
+                AccountChanges::RolledBack(fees_only_tx.rollback_accounts.clone())
+            }
+        };
+        Self {
+            status: processed_tx.status(),
+            executed_units: processed_tx.executed_units(),
+            fee: processed_tx.fee_details().total_fee(),
+            account_changes,
+        }
+    }
+}
+
+/// A transaction whose outcome differed between the two executions
+pub(crate) struct Divergence {
+    signature: Signature,
+    batch_outcome: TransactionOutcome,
+    rerun_outcome: TransactionOutcome,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Self {
+            signature,
+            batch_outcome: batch,
+            rerun_outcome: rerun,
+        } = self;
+        write!(f, "transaction {signature}:")?;
+        if batch.status != rerun.status {
+            write!(f, " status {:?} != {:?}", batch.status, rerun.status)?;
+        }
+        if batch.executed_units != rerun.executed_units {
+            write!(
+                f,
+                " executed units {} != {}",
+                batch.executed_units, rerun.executed_units
+            )?;
+        }
+        if batch.fee != rerun.fee {
+            write!(f, " fee {} != {}", batch.fee, rerun.fee)?;
+        }
+        match (&batch.account_changes, &rerun.account_changes) {
+            (AccountChanges::Committed(batch), AccountChanges::Committed(rerun)) => {
+                for ((pubkey, batch_account), (_, rerun_account)) in batch.iter().zip(rerun) {
+                    if batch_account != rerun_account {
+                        write!(
+                            f,
+                            " account {pubkey} {batch_account:?} != {rerun_account:?}"
+                        )?;
+                    }
+                }
+            }
+            (batch, rerun) if batch != rerun => {
+                write!(f, " account changes {batch:?} != {rerun:?}")?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Re-executes the transactions of `batch` one at a time in reverse order and compares the
+/// outcomes with `processing_results`, the results of executing `batch` as a whole
+///
+/// Must be called before `processing_results` are committed, so that both executions see the
+/// same account state.
+pub(crate) fn check_batch(
+    bank: &Bank,
+    batch: &TransactionBatch<impl TransactionWithMeta>,
+    processing_results: &[TransactionProcessingResult],
+) -> Vec<Divergence> {
+    let transactions = batch.sanitized_transactions();
+    let mut rerun_outcomes: Vec<_> = transactions
+        .iter()
+        .rev()
+        .map(|transaction| {
+            let single_tx_batch = bank.prepare_unlocked_batch_from_single_tx(transaction);
+            let mut processing_results = bank
+                .load_and_execute_transactions(
+                    &single_tx_batch,
+                    MAX_PROCESSING_AGE,
+                    &mut ExecuteTimings::default(),
+                    &mut TransactionErrorMetrics::default(),
+                    TransactionProcessingConfig {
+                        account_overrides: None,
+                        check_program_modification_slot: bank.check_program_modification_slot(),
+                        log_messages_bytes_limit: None,
+                        limit_to_load_programs: false,
+                        recording_config: ExecutionRecordingConfig::new_single_setting(false),
+                        drop_on_failure: false,
+                        all_or_nothing: false,
+                    },
+                )
+                .processing_results;
+            let processing_result = processing_results
+                .pop()
+                .expect("one result per transaction");
+            TransactionOutcome::new(transaction, &processing_result)
+        })
+        .collect();
+    rerun_outcomes.reverse();
+
+    transactions
+        .iter()
+        .zip(processing_results)
+        .zip(rerun_outcomes)
+        .filter_map(|((transaction, processing_result), rerun_outcome)| {
+            let batch_outcome = TransactionOutcome::new(transaction, processing_result);
+            (batch_outcome != rerun_outcome).then(|| Divergence {
+                signature: *transaction.signature(),
+                batch_outcome,
+                rerun_outcome,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn report_divergences(slot: Slot, divergences: &[Divergence]) {
+    for divergence in divergences {
+        error!("Nondeterministic replay in slot {slot}: {divergence}");
+        datapoint_error!(
+            "replay_nondeterminism",
+            ("slot", slot, i64),
+            ("signature", divergence.signature.to_string(), String),
+            ("detail", divergence.to_string(), String),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_keypair::Keypair,
+        solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+        solana_signer::Signer,
+        solana_system_transaction as system_transaction,
+    };
+
+    #[test]
+    fn test_check_batch() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1_000_000_000);
+        let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+        let payer = Keypair::new();
+        bank.transfer(100_000_000, &mint_keypair, &payer.pubkey())
+            .unwrap();
+
+        let transactions = [
+            system_transaction::transfer(
+                &mint_keypair,
+                &Pubkey::new_unique(),
+                1_000_000,
+                bank.last_blockhash(),
+            ),
+            system_transaction::transfer(
+                &payer,
+                &Pubkey::new_unique(),
+                1_000_000_000,
+                bank.last_blockhash(),
+            ),
+        ]
+        .map(RuntimeTransaction::from_transaction_for_tests);
+        let batch = bank.prepare_sanitized_batch(&transactions);
+        let mut processing_results = bank
+            .load_and_execute_transactions(
+                &batch,
+                MAX_PROCESSING_AGE,
+                &mut ExecuteTimings::default(),
+                &mut TransactionErrorMetrics::default(),
+                TransactionProcessingConfig::default(),
+            )
+            .processing_results;
+        assert!(processing_results[1].as_ref().unwrap().status().is_err());
+        assert!(check_batch(&bank, &batch, &processing_results).is_empty());
+
+        let Ok(ProcessedTransaction::Executed(executed_tx)) = &mut processing_results[0] else {
+            panic!("transfer should have been executed");
+        };
+        executed_tx.execution_details.executed_units += 1;
+        let divergences = check_batch(&bank, &batch, &processing_results);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].signature, *transactions[0].signature());
+        assert!(divergences[0].to_string().contains("executed units"));
+
+        let Ok(ProcessedTransaction::Executed(executed_tx)) = &mut processing_results[0] else {
+            unreachable!();
+        };
+        executed_tx.execution_details.executed_units -= 1;
+        executed_tx.loaded_transaction.accounts[1].1.set_lamports(0);
+        let divergences = check_batch(&bank, &batch, &processing_results);
+        assert_eq!(divergences.len(), 1);
+        assert!(divergences[0]
+            .to_string()
+            .contains(&transactions[0].account_keys()[1].to_string()));
+    }
+}
@@ -79,7 +79,7 @@ pub fn verify_shred_cpu(
     }
 }
 
-fn verify_shreds_cpu(
+pub fn verify_shreds_cpu(
     thread_pool: &ThreadPool,
     batches: &[PacketBatch],
     slot_leaders: &SlotPubkeys,
@@ -19,7 +19,10 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         rpc_addrs: config.rpc_addrs,
         pubsub_config: config.pubsub_config.clone(),
         snapshot_config: config.snapshot_config.clone(),
+        exit_snapshot_max_age_slots: config.exit_snapshot_max_age_slots,
         max_ledger_shreds: config.max_ledger_shreds,
+        blockstore_cleanup_pause_config: config.blockstore_cleanup_pause_config.clone(),
+        transaction_history_retention: config.transaction_history_retention.clone(),
         blockstore_options: config.blockstore_options.clone(),
         broadcast_stage_type: config.broadcast_stage_type.clone(),
         turbine_disabled: config.turbine_disabled.clone(),
@@ -27,9 +30,11 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         wait_for_supermajority: config.wait_for_supermajority,
         new_hard_forks: config.new_hard_forks.clone(),
         known_validators: config.known_validators.clone(),
+        known_validators_from_stake: config.known_validators_from_stake.clone(),
         repair_validators: config.repair_validators.clone(),
         repair_whitelist: config.repair_whitelist.clone(),
         gossip_validators: config.gossip_validators.clone(),
+        peer_preference: config.peer_preference,
         max_genesis_archive_unpacked_size: config.max_genesis_archive_unpacked_size,
         run_verification: config.run_verification,
         require_tower: config.require_tower,
@@ -56,6 +61,7 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
             .map(|name| (name.clone(), Arc::new(AtomicBool::new(false))))
             .collect(),
         poh_hashes_per_batch: config.poh_hashes_per_batch,
+        poh_alert_thresholds: config.poh_alert_thresholds,
         process_ledger_before_services: config.process_ledger_before_services,
         no_wait_for_vote_to_start_leader: config.no_wait_for_vote_to_start_leader,
         accounts_db_config: config.accounts_db_config.clone(),
@@ -66,22 +72,30 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         block_production_method: config.block_production_method.clone(),
         block_production_num_workers: config.block_production_num_workers,
         block_production_scheduler_config: config.block_production_scheduler_config.clone(),
+        block_production_reserved_capacity: config.block_production_reserved_capacity.clone(),
         enable_block_production_forwarding: config.enable_block_production_forwarding,
         enable_scheduler_bindings: config.enable_scheduler_bindings,
         generator_config: config.generator_config.clone(),
         use_snapshot_archives_at_startup: config.use_snapshot_archives_at_startup,
         wen_restart_proto_path: config.wen_restart_proto_path.clone(),
         wen_restart_coordinator: config.wen_restart_coordinator,
+        cluster_halt_detection_timeout: config.cluster_halt_detection_timeout,
         unified_scheduler_handler_threads: config.unified_scheduler_handler_threads,
         ip_echo_server_threads: config.ip_echo_server_threads,
         rayon_global_threads: config.rayon_global_threads,
         replay_forks_threads: config.replay_forks_threads,
         replay_transactions_threads: config.replay_transactions_threads,
         tvu_shred_sigverify_threads: config.tvu_shred_sigverify_threads,
+        tvu_shred_sigverify: config.tvu_shred_sigverify.clone(),
         delay_leader_block_for_pending_fork: config.delay_leader_block_for_pending_fork,
+        bank_forks_limits: config.bank_forks_limits,
+        epoch_precompute_slots: config.epoch_precompute_slots,
         use_tpu_client_next: config.use_tpu_client_next,
         retransmit_xdp: config.retransmit_xdp.clone(),
         repair_handler_type: config.repair_handler_type.clone(),
+        serve_repair_config: config.serve_repair_config,
+        tpu_mirror_config: config.tpu_mirror_config.clone(),
+        forwarding_deny_config: config.forwarding_deny_config,
     }
 }
 
@@ -56,6 +56,7 @@ enum MetricsCommand {
     Flush(Arc<Barrier>),
     Submit(DataPoint, log::Level),
     SubmitCounter(CounterPoint, log::Level, u64),
+    Snapshot(Sender<Vec<DataPoint>>),
 }
 
 pub struct MetricsAgent {
@@ -332,6 +333,14 @@ impl MetricsAgent {
                             counters.insert(key, counter);
                         }
                     }
+                    MetricsCommand::Snapshot(sender) => {
+                        let snapshot = points
+                            .iter()
+                            .cloned()
+                            .chain(counters.values().map(Into::into))
+                            .collect();
+                        let _ = sender.send(snapshot);
+                    }
                 },
                 Err(TryRecvError::Empty) => {
                     std::thread::sleep(Duration::from_millis(5));
@@ -370,6 +379,13 @@ impl MetricsAgent {
             .unwrap();
     }
 
+    /// Returns the points and counters submitted since they were last written
+    pub fn snapshot(&self) -> Vec<DataPoint> {
+        let (sender, receiver) = unbounded();
+        self.sender.send(MetricsCommand::Snapshot(sender)).unwrap();
+        receiver.recv().unwrap_or_default()
+    }
+
     pub fn flush(&self) {
         debug!("Flush");
         let barrier = Arc::new(Barrier::new(2));
@@ -411,6 +427,13 @@ pub fn get_host_id() -> String {
     HOST_ID.read().unwrap().clone()
 }
 
+/// Returns the points and counters submitted from any thread that have not been written yet,
+/// which is those of the last few seconds
+pub fn snapshot() -> Vec<DataPoint> {
+    let agent = get_singleton_agent();
+    agent.snapshot()
+}
+
 /// Submits a new point from any thread.  Note that points are internally queued
 /// and transmitted periodically in batches.
 pub fn submit(point: DataPoint, level: log::Level) {
@@ -616,6 +639,23 @@ mod test {
         assert_eq!(writer.points_written(), 21);
     }
 
+    #[test]
+    fn test_snapshot() {
+        let writer = Arc::new(MockMetricsWriter::new());
+        let agent = MetricsAgent::new(writer.clone(), Duration::from_secs(9_999_999), 1000);
+        agent.submit(DataPoint::new("point 1"), Level::Info);
+        agent.submit_counter(CounterPoint::new("counter 1"), Level::Info, 0);
+
+        let snapshot = agent.snapshot();
+        let mut names: Vec<_> = snapshot.iter().map(|point| point.name).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["counter 1", "point 1"]);
+        assert_eq!(writer.points_written(), 0);
+
+        agent.flush();
+        assert!(agent.snapshot().is_empty());
+    }
+
     #[test]
     fn test_submit_counter_increment() {
         let writer = Arc::new(MockMetricsWriter::new());
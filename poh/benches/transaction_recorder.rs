@@ -10,6 +10,7 @@ use {
         poh_controller::PohController,
         poh_recorder::PohRecorder,
         poh_service::{PohService, DEFAULT_HASHES_PER_BATCH, DEFAULT_PINNED_CPU_CORE},
+        poh_timing_report::PohAlertThresholds,
         record_channels::record_channels,
         transaction_recorder::TransactionRecorder,
     },
@@ -85,6 +86,7 @@ fn bench_record_transactions(c: &mut Criterion) {
         DEFAULT_HASHES_PER_BATCH,
         record_receiver,
         poh_service_receiver,
+        PohAlertThresholds::default(),
     );
     poh_controller
         .set_bank_sync(BankWithScheduler::new_without_scheduler(bank.clone()))
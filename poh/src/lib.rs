@@ -11,6 +11,7 @@
 pub mod poh_controller;
 pub mod poh_recorder;
 pub mod poh_service;
+pub mod poh_timing_report;
 pub mod record_channels;
 pub mod transaction_recorder;
 
@@ -997,6 +997,7 @@ fn do_create_test_recorder(
         crate::poh_service::DEFAULT_HASHES_PER_BATCH,
         record_receiver,
         poh_service_message_receiver,
+        crate::poh_timing_report::PohAlertThresholds::default(),
     );
 
     poh_controller
@@ -4,6 +4,7 @@ use {
     crate::{
         poh_controller::{PohServiceMessage, PohServiceMessageGuard, PohServiceMessageReceiver},
         poh_recorder::{PohRecorder, Record},
+        poh_timing_report::{PohAlertThresholds, PohTimingReport},
         record_channels::RecordReceiver,
     },
     log::*,
@@ -11,6 +12,7 @@ use {
     solana_entry::poh::Poh,
     solana_measure::measure::Measure,
     solana_poh_config::PohConfig,
+    solana_time_utils::timestamp,
     std::{
         sync::{
             atomic::{AtomicBool, Ordering},
@@ -23,6 +25,7 @@ use {
 
 pub struct PohService {
     tick_producer: JoinHandle<()>,
+    timing_report: Arc<PohTimingReport>,
 }
 
 // Amount of time to hash continuously.
@@ -39,35 +42,46 @@ pub const DEFAULT_PINNED_CPU_CORE: usize = 0;
 
 const TARGET_SLOT_ADJUSTMENT_NS: u64 = 50_000_000;
 
-#[derive(Debug)]
 struct PohTiming {
     num_ticks: u64,
     num_hashes: u64,
+    num_batch_hashes: u64,
     total_sleep_us: u64,
     total_lock_time_ns: u64,
     total_hash_time_ns: u64,
     total_tick_time_ns: u64,
     last_metric: Instant,
     total_record_time_us: u64,
+    report: Arc<PohTimingReport>,
 }
 
 impl PohTiming {
-    fn new() -> Self {
+    fn new(report: Arc<PohTimingReport>) -> Self {
         Self {
             num_ticks: 0,
             num_hashes: 0,
+            num_batch_hashes: 0,
             total_sleep_us: 0,
             total_lock_time_ns: 0,
             total_hash_time_ns: 0,
             total_tick_time_ns: 0,
             last_metric: Instant::now(),
             total_record_time_us: 0,
+            report,
         }
     }
     fn report(&mut self, ticks_per_slot: u64) {
         if self.last_metric.elapsed().as_millis() > 1000 {
             let elapsed_us = self.last_metric.elapsed().as_micros() as u64;
             let us_per_slot = (elapsed_us * ticks_per_slot) / self.num_ticks;
+            let sample = self.report.sample(
+                timestamp(),
+                self.num_ticks,
+                self.num_hashes,
+                self.num_batch_hashes,
+                elapsed_us,
+                self.total_hash_time_ns,
+            );
             datapoint_info!(
                 "poh-service",
                 ("ticks", self.num_ticks as i64, i64),
@@ -78,10 +92,24 @@ impl PohTiming {
                 ("total_lock_time_us", self.total_lock_time_ns / 1000, i64),
                 ("total_hash_time_us", self.total_hash_time_ns / 1000, i64),
                 ("total_record_time_us", self.total_record_time_us, i64),
+                ("tick_duration_us", sample.tick_duration_us, i64),
+                ("hashes_per_second", sample.hashes_per_second, i64),
+                ("max_hashes_per_second", sample.max_hashes_per_second, i64),
+                ("slot_drift_us", sample.slot_drift_us, i64),
             );
+            let alerts = self.report.push_sample(sample);
+            if !alerts.is_empty() {
+                warn!("PoH is degrading: {}", alerts.join(", "));
+                datapoint_warn!(
+                    "poh-service-alert",
+                    ("max_hashes_per_second", sample.max_hashes_per_second, i64),
+                    ("slot_drift_us", sample.slot_drift_us, i64),
+                );
+            }
             self.total_sleep_us = 0;
             self.num_ticks = 0;
             self.num_hashes = 0;
+            self.num_batch_hashes = 0;
             self.total_tick_time_ns = 0;
             self.total_lock_time_ns = 0;
             self.total_hash_time_ns = 0;
@@ -101,8 +129,19 @@ impl PohService {
         hashes_per_batch: u64,
         record_receiver: RecordReceiver,
         poh_service_receiver: PohServiceMessageReceiver,
+        alert_thresholds: PohAlertThresholds,
     ) -> Self {
         let poh_config = poh_config.clone();
+        let target_ns_per_tick = Self::target_ns_per_tick(
+            ticks_per_slot,
+            poh_config.target_tick_duration.as_nanos() as u64,
+        );
+        let timing_report = Arc::new(PohTimingReport::new(
+            alert_thresholds,
+            target_ns_per_tick,
+            ticks_per_slot,
+        ));
+        let timing = PohTiming::new(timing_report.clone());
         let tick_producer = Builder::new()
             .name("solPohTickProd".to_string())
             .spawn(move || {
@@ -140,17 +179,23 @@ impl PohService {
                         hashes_per_batch,
                         record_receiver,
                         poh_service_receiver,
-                        Self::target_ns_per_tick(
-                            ticks_per_slot,
-                            poh_config.target_tick_duration.as_nanos() as u64,
-                        ),
+                        target_ns_per_tick,
+                        timing,
                     );
                 }
                 poh_exit.store(true, Ordering::Relaxed);
             })
             .unwrap();
 
-        Self { tick_producer }
+        Self {
+            tick_producer,
+            timing_report,
+        }
+    }
+
+    /// Recent timing of the tick producer; empty if PoH does not hash
+    pub fn timing_report(&self) -> &Arc<PohTimingReport> {
+        &self.timing_report
     }
 
     pub fn target_ns_per_tick(ticks_per_slot: u64, target_tick_duration_ns: u64) -> u64 {
@@ -437,6 +482,7 @@ impl PohService {
                 timing.total_lock_time_ns += lock_time.as_ns();
                 loop {
                     timing.num_hashes += hashes_per_batch;
+                    timing.num_batch_hashes += hashes_per_batch;
                     let mut hash_time = Measure::start("hash");
                     let should_tick = poh_l.hash(hashes_per_batch);
                     let ideal_time = poh_l.target_poh_time(target_ns_per_tick);
@@ -496,9 +542,9 @@ impl PohService {
         mut record_receiver: RecordReceiver,
         poh_service_receiver: PohServiceMessageReceiver,
         target_ns_per_tick: u64,
+        mut timing: PohTiming,
     ) {
         let poh = poh_recorder.read().unwrap().poh.clone();
-        let mut timing = PohTiming::new();
         let mut next_record = None;
         let mut should_exit = poh_exit.load(Ordering::Relaxed);
 
@@ -771,6 +817,7 @@ mod tests {
             hashes_per_batch,
             record_receiver,
             poh_service_message_receiver,
+            PohAlertThresholds::default(),
         );
         poh_recorder.write().unwrap().set_bank_for_test(bank);
 
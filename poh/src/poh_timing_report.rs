@@ -0,0 +1,229 @@
+//! Timing of the PoH tick producer, kept for the admin RPC.
+//!
+//! The tick producer paces itself to a target duration per tick. When the core it runs on slows
+//! down (thermal throttling, the thread migrating to a busy core), PoH first loses the slack it
+//! spends waiting for the target time, and then starts falling behind the wall clock, which makes
+//! the node's leader slots late. The hash rate the core sustains while hashing and the drift of
+//! each slot from the target show the degradation before slots are skipped.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Number of samples kept in a [`PohTimingReport`], about a minute worth
+pub const POH_TIMING_REPORT_SAMPLES: usize = 60;
+
+/// Thresholds past which the tick producer warns about its timing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PohAlertThresholds {
+    /// Time a slot may take longer than the target before alerting
+    pub max_slot_drift: Option<Duration>,
+    /// Lowest number of hashes per second the PoH core may sustain while hashing
+    pub min_hash_rate: Option<u64>,
+}
+
+/// Timing of the tick producer over one reporting period
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PohTimingSample {
+    /// End of the period, in milliseconds since the unix epoch
+    pub timestamp_ms: u64,
+    pub ticks: u64,
+    pub hashes: u64,
+    pub elapsed_us: u64,
+    /// Average time between two ticks
+    pub tick_duration_us: u64,
+    /// Hashes per second of wall clock, which is capped by the target tick duration
+    pub hashes_per_second: u64,
+    /// Hashes per second spent hashing, which is the rate the PoH core can sustain
+    pub max_hashes_per_second: u64,
+    /// How much longer than the target a slot took at this pace, negative if shorter
+    pub slot_drift_us: i64,
+}
+
+impl PohTimingSample {
+    /// Returns the thresholds this sample exceeds, as readable messages
+    pub fn alerts(&self, thresholds: &PohAlertThresholds) -> Vec<String> {
+        let mut alerts = vec![];
+        if let Some(max_slot_drift) = thresholds.max_slot_drift {
+            if self.slot_drift_us > max_slot_drift.as_micros() as i64 {
+                alerts.push(format!(
+                    "slots take {}us longer than the target, over the {}us threshold",
+                    self.slot_drift_us,
+                    max_slot_drift.as_micros(),
+                ));
+            }
+        }
+        if let Some(min_hash_rate) = thresholds.min_hash_rate {
+            if self.max_hashes_per_second < min_hash_rate {
+                alerts.push(format!(
+                    "PoH core sustains {} hashes per second, under the {min_hash_rate} threshold",
+                    self.max_hashes_per_second,
+                ));
+            }
+        }
+        alerts
+    }
+}
+
+/// Rolling report of the recent timing of the tick producer
+pub struct PohTimingReport {
+    alert_thresholds: PohAlertThresholds,
+    target_ns_per_tick: u64,
+    ticks_per_slot: u64,
+    samples: Mutex<VecDeque<PohTimingSample>>,
+    num_alerts: AtomicU64,
+}
+
+impl PohTimingReport {
+    pub fn new(
+        alert_thresholds: PohAlertThresholds,
+        target_ns_per_tick: u64,
+        ticks_per_slot: u64,
+    ) -> Self {
+        Self {
+            alert_thresholds,
+            target_ns_per_tick,
+            ticks_per_slot,
+            samples: Mutex::default(),
+            num_alerts: AtomicU64::default(),
+        }
+    }
+
+    pub fn alert_thresholds(&self) -> &PohAlertThresholds {
+        &self.alert_thresholds
+    }
+
+    /// Duration of a tick the producer paces itself to
+    pub fn target_tick_duration(&self) -> Duration {
+        Duration::from_nanos(self.target_ns_per_tick)
+    }
+
+    pub fn ticks_per_slot(&self) -> u64 {
+        self.ticks_per_slot
+    }
+
+    /// Number of reporting periods that exceeded a threshold since the node started
+    pub fn num_alerts(&self) -> u64 {
+        self.num_alerts.load(Ordering::Relaxed)
+    }
+
+    /// Returns the samples in the report, oldest first
+    pub fn samples(&self) -> Vec<PohTimingSample> {
+        self.samples.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Builds the sample of a reporting period from the tick producer's totals
+    pub(crate) fn sample(
+        &self,
+        timestamp_ms: u64,
+        ticks: u64,
+        hashes: u64,
+        batch_hashes: u64,
+        elapsed_us: u64,
+        hash_time_ns: u64,
+    ) -> PohTimingSample {
+        let tick_duration_us = elapsed_us.checked_div(ticks).unwrap_or_default();
+        let target_slot_us = self.target_ns_per_tick * self.ticks_per_slot / 1000;
+        let slot_us = tick_duration_us * self.ticks_per_slot;
+        PohTimingSample {
+            timestamp_ms,
+            ticks,
+            hashes,
+            elapsed_us,
+            tick_duration_us,
+            hashes_per_second: (hashes as u128 * 1_000_000)
+                .checked_div(elapsed_us as u128)
+                .unwrap_or_default() as u64,
+            max_hashes_per_second: (batch_hashes as u128 * 1_000_000_000)
+                .checked_div(hash_time_ns as u128)
+                .unwrap_or_default() as u64,
+            slot_drift_us: slot_us as i64 - target_slot_us as i64,
+        }
+    }
+
+    /// Adds `sample` to the report, returning the thresholds it exceeds
+    pub(crate) fn push_sample(&self, sample: PohTimingSample) -> Vec<String> {
+        let alerts = sample.alerts(&self.alert_thresholds);
+        if !alerts.is_empty() {
+            self.num_alerts.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() == POH_TIMING_REPORT_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poh_timing_sample() {
+        // 64 ticks of 6_250us per slot is a 400ms slot
+        let report = PohTimingReport::new(PohAlertThresholds::default(), 6_250_000, 64);
+        let sample = report.sample(1_000, 160, 2_000_000, 1_900_000, 1_040_000, 950_000_000);
+        assert_eq!(sample.tick_duration_us, 6_500);
+        assert_eq!(sample.hashes_per_second, 1_923_076);
+        assert_eq!(sample.max_hashes_per_second, 2_000_000);
+        assert_eq!(sample.slot_drift_us, 16_000);
+
+        let sample = report.sample(1_000, 160, 2_000_000, 1_900_000, 960_000, 950_000_000);
+        assert_eq!(sample.slot_drift_us, -16_000);
+
+        // Nothing hashed in the period
+        let sample = report.sample(1_000, 0, 0, 0, 0, 0);
+        assert_eq!(sample.hashes_per_second, 0);
+        assert_eq!(sample.max_hashes_per_second, 0);
+    }
+
+    #[test]
+    fn test_poh_timing_alerts() {
+        let sample = PohTimingSample {
+            max_hashes_per_second: 10_000_000,
+            slot_drift_us: 20_000,
+            ..PohTimingSample::default()
+        };
+        assert!(sample.alerts(&PohAlertThresholds::default()).is_empty());
+
+        let thresholds = PohAlertThresholds {
+            max_slot_drift: Some(Duration::from_millis(20)),
+            min_hash_rate: Some(10_000_000),
+        };
+        assert!(sample.alerts(&thresholds).is_empty());
+
+        let thresholds = PohAlertThresholds {
+            max_slot_drift: Some(Duration::from_millis(10)),
+            min_hash_rate: Some(12_000_000),
+        };
+        assert_eq!(sample.alerts(&thresholds).len(), 2);
+    }
+
+    #[test]
+    fn test_poh_timing_report() {
+        let thresholds = PohAlertThresholds {
+            max_slot_drift: Some(Duration::from_millis(10)),
+            min_hash_rate: None,
+        };
+        let report = PohTimingReport::new(thresholds, 6_250_000, 64);
+        for i in 0..POH_TIMING_REPORT_SAMPLES as u64 + 1 {
+            let alerts = report.push_sample(PohTimingSample {
+                timestamp_ms: i,
+                slot_drift_us: if i == 0 { 20_000 } else { 0 },
+                ..PohTimingSample::default()
+            });
+            assert_eq!(alerts.is_empty(), i != 0);
+        }
+        let samples = report.samples();
+        assert_eq!(samples.len(), POH_TIMING_REPORT_SAMPLES);
+        assert_eq!(samples[0].timestamp_ms, 1);
+        assert_eq!(report.num_alerts(), 1);
+    }
+}
@@ -28,6 +28,13 @@ pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
 pub const JSON_RPC_SERVER_ERROR_EPOCH_REWARDS_PERIOD_ACTIVE: i64 = -32017;
 pub const JSON_RPC_SERVER_ERROR_SLOT_NOT_EPOCH_BOUNDARY: i64 = -32018;
 pub const JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_UNREACHABLE: i64 = -32019;
+pub const JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE: i64 = -32020;
+pub const JSON_RPC_SERVER_ERROR_ADDRESS_TIME_INDEX_NOT_AVAILABLE: i64 = -32021;
+pub const JSON_RPC_SERVER_ERROR_SIMULATION_COMPUTE_UNIT_LIMIT_EXCEEDED: i64 = -32022;
+pub const JSON_RPC_SERVER_ERROR_SIMULATION_QUOTA_EXCEEDED: i64 = -32023;
+pub const JSON_RPC_SERVER_ERROR_READ_ONLY_NODE: i64 = -32024;
+pub const JSON_RPC_SERVER_ERROR_NODE_CONGESTED: i64 = -32025;
+pub const JSON_RPC_SERVER_ERROR_RESPONSE_TOO_LARGE: i64 = -32026;
 
 #[derive(Error, Debug)]
 #[allow(clippy::large_enum_variant)]
@@ -80,6 +87,27 @@ pub enum RpcCustomError {
     SlotNotEpochBoundary { slot: Slot },
     #[error("LongTermStorageUnreachable")]
     LongTermStorageUnreachable,
+    #[error("BankNotAvailable")]
+    BankNotAvailable { slot: Slot },
+    #[error("AddressTimeIndexNotAvailable")]
+    AddressTimeIndexNotAvailable,
+    #[error("SimulationComputeUnitLimitExceeded")]
+    SimulationComputeUnitLimitExceeded { requested: u32, max: u32 },
+    #[error("SimulationQuotaExceeded")]
+    SimulationQuotaExceeded { max_concurrent: usize },
+    #[error("ReadOnlyNode")]
+    ReadOnlyNode,
+    #[error("NodeCongested")]
+    NodeCongested {
+        queue_depth: usize,
+        retry_after_ms: u64,
+    },
+    #[error("ResponseTooLarge")]
+    ResponseTooLarge {
+        max_bytes: usize,
+        partial_result: serde_json::Value,
+        cursor: Option<String>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,6 +116,23 @@ pub struct NodeUnhealthyErrorData {
     pub num_slots_behind: Option<Slot>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeCongestedErrorData {
+    pub queue_depth: usize,
+    pub retry_after_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseTooLargeErrorData {
+    pub max_bytes: usize,
+    /// The response, cut short at the limit
+    pub partial_result: serde_json::Value,
+    /// Where to continue from to get the rest of the response, for methods that can continue
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MinContextSlotNotReachedErrorData {
@@ -259,6 +304,78 @@ impl From<RpcCustomError> for Error {
                 message: "Failed to query long-term storage; please try again".to_string(),
                 data: None,
             },
+            RpcCustomError::BankNotAvailable { slot } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE),
+                message: format!(
+                    "Account state at slot {slot} is not available; the slot must be frozen and \
+                     not older than the root on the fork of the requested commitment"
+                ),
+                data: None,
+            },
+            RpcCustomError::AddressTimeIndexNotAvailable => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_ADDRESS_TIME_INDEX_NOT_AVAILABLE,
+                ),
+                message: "Address time index is not available from this node".to_string(),
+                data: None,
+            },
+            RpcCustomError::SimulationComputeUnitLimitExceeded { requested, max } => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_SIMULATION_COMPUTE_UNIT_LIMIT_EXCEEDED,
+                ),
+                message: format!(
+                    "Transaction requests {requested} compute units; this node simulates \
+                     transactions of at most {max} compute units"
+                ),
+                data: Some(serde_json::json!({
+                    "requested": requested,
+                    "max": max,
+                })),
+            },
+            RpcCustomError::SimulationQuotaExceeded { max_concurrent } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_SIMULATION_QUOTA_EXCEEDED),
+                message: format!(
+                    "This node is already running {max_concurrent} simulations; please try again \
+                     later"
+                ),
+                data: None,
+            },
+            RpcCustomError::ReadOnlyNode => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_READ_ONLY_NODE),
+                message: "This node serves a snapshot read-only and does not accept transactions"
+                    .to_string(),
+                data: None,
+            },
+            RpcCustomError::NodeCongested {
+                queue_depth,
+                retry_after_ms,
+            } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_NODE_CONGESTED),
+                message: format!(
+                    "Node is congested with {queue_depth} transactions queued, retry after \
+                     {retry_after_ms}ms"
+                ),
+                data: Some(serde_json::json!(NodeCongestedErrorData {
+                    queue_depth,
+                    retry_after_ms,
+                })),
+            },
+            RpcCustomError::ResponseTooLarge {
+                max_bytes,
+                partial_result,
+                cursor,
+            } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_RESPONSE_TOO_LARGE),
+                message: format!(
+                    "Response exceeds the maximum size of {max_bytes} bytes, the partial result \
+                     is in the error data"
+                ),
+                data: Some(serde_json::json!(ResponseTooLargeErrorData {
+                    max_bytes,
+                    partial_result,
+                    cursor,
+                })),
+            },
         }
     }
 }
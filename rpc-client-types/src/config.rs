@@ -1,7 +1,7 @@
 use {
     crate::filter::RpcFilterType,
     serde::{Deserialize, Serialize},
-    solana_clock::{Epoch, Slot},
+    solana_clock::{Epoch, Slot, UnixTimestamp},
 };
 pub use {
     solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig},
@@ -49,6 +49,15 @@ pub struct RpcSimulateTransactionConfig {
     pub inner_instructions: bool,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPreviewTransactionPriorityConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub min_context_slot: Option<Slot>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcRequestAirdropConfig {
@@ -159,6 +168,41 @@ pub struct RpcAccountInfoConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMultipleAccountsAtSlotConfig {
+    /// Slot of the bank to read all the accounts from, which must be frozen and be the bank of
+    /// the commitment level or one of its ancestors [default: the bank of the commitment level]
+    pub slot: Option<Slot>,
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountWithBankHashConfig {
+    /// Slot of the bank to read the account from, which must be frozen and be the bank of the
+    /// commitment level or one of its ancestors [default: the bank of the commitment level]
+    pub slot: Option<Slot>,
+    /// Encoding of the account data, which must be binary
+    pub encoding: Option<UiAccountEncoding>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountSubscribeConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Only send the parts of the account data that changed since the previous notification
+    pub changed_data_only: Option<bool>,
+    /// Send at most one notification per this many milliseconds, with the latest state of the
+    /// account [default: every update]
+    pub coalesce_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcProgramAccountsConfig {
@@ -169,6 +213,16 @@ pub struct RpcProgramAccountsConfig {
     pub sort_results: Option<bool>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramSubscribeConfig {
+    #[serde(flatten)]
+    pub program_config: RpcProgramAccountsConfig,
+    /// Send at most one notification per account per this many milliseconds, with the latest
+    /// state of the account [default: every update]
+    pub coalesce_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RpcTransactionLogsFilter {
@@ -228,6 +282,31 @@ pub struct RpcSignaturesForAddressConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStakeActivationProjectionConfig {
+    /// Number of epochs to project, starting with the current one
+    pub epochs: Option<u64>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignaturesForAddressByTimeConfig {
+    /// Lowest block time to return, inclusive [default: no lower bound]
+    pub start_time: Option<UnixTimestamp>,
+    /// Block time to stop at, exclusive [default: no upper bound]
+    pub end_time: Option<UnixTimestamp>,
+    /// Resume the listing after this signature, the last one of the previous page
+    pub after: Option<String>, // Signature as base-58 string
+    pub limit: Option<usize>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcEncodingConfigWrapper<T> {
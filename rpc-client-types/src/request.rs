@@ -13,6 +13,7 @@ pub enum RpcRequest {
     Custom { method: &'static str },
     DeregisterNode,
     GetAccountInfo,
+    GetAccountWithBankHash,
     GetBalance,
     GetBlock,
     GetBlockHeight,
@@ -21,12 +22,17 @@ pub enum RpcRequest {
     GetBlocksWithLimit,
     GetBlockTime,
     GetClusterNodes,
+    GetClusterVersionBreakdown,
+    GetDuplicateSlotProof,
+    GetDuplicateSlots,
     GetEpochInfo,
     GetEpochSchedule,
     GetFeeForMessage,
     GetFirstAvailableBlock,
+    GetForkStats,
     GetGenesisHash,
     GetHealth,
+    GetHealthDetailed,
     GetIdentity,
     GetInflationGovernor,
     GetInflationRate,
@@ -38,11 +44,13 @@ pub enum RpcRequest {
     GetMaxShredInsertSlot,
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
+    GetMultipleAccountsAtSlot,
     GetProgramAccounts,
     GetRecentPerformanceSamples,
     GetRecentPrioritizationFees,
     GetHighestSnapshotSlot,
     GetSignaturesForAddress,
+    GetSignaturesForAddressByTime,
     GetSignatureStatuses,
     GetSlot,
     GetSlotLeader,
@@ -50,6 +58,7 @@ pub enum RpcRequest {
     GetStorageTurn,
     GetStorageTurnRate,
     GetSlotsPerSegment,
+    GetStakeActivationProjection,
     GetStakeMinimumDelegation,
     GetStoragePubkeysForSlot,
     GetSupply,
@@ -64,6 +73,7 @@ pub enum RpcRequest {
     GetVoteAccounts,
     IsBlockhashValid,
     MinimumLedgerSlot,
+    PreviewTransactionPriority,
     RegisterNode,
     RequestAirdrop,
     SendTransaction,
@@ -78,6 +88,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::Custom { method } => method,
             RpcRequest::DeregisterNode => "deregisterNode",
             RpcRequest::GetAccountInfo => "getAccountInfo",
+            RpcRequest::GetAccountWithBankHash => "getAccountWithBankHash",
             RpcRequest::GetBalance => "getBalance",
             RpcRequest::GetBlock => "getBlock",
             RpcRequest::GetBlockHeight => "getBlockHeight",
@@ -86,12 +97,17 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetBlocksWithLimit => "getBlocksWithLimit",
             RpcRequest::GetBlockTime => "getBlockTime",
             RpcRequest::GetClusterNodes => "getClusterNodes",
+            RpcRequest::GetClusterVersionBreakdown => "getClusterVersionBreakdown",
+            RpcRequest::GetDuplicateSlotProof => "getDuplicateSlotProof",
+            RpcRequest::GetDuplicateSlots => "getDuplicateSlots",
             RpcRequest::GetEpochInfo => "getEpochInfo",
             RpcRequest::GetEpochSchedule => "getEpochSchedule",
             RpcRequest::GetFeeForMessage => "getFeeForMessage",
             RpcRequest::GetFirstAvailableBlock => "getFirstAvailableBlock",
+            RpcRequest::GetForkStats => "getForkStats",
             RpcRequest::GetGenesisHash => "getGenesisHash",
             RpcRequest::GetHealth => "getHealth",
+            RpcRequest::GetHealthDetailed => "getHealthDetailed",
             RpcRequest::GetIdentity => "getIdentity",
             RpcRequest::GetInflationGovernor => "getInflationGovernor",
             RpcRequest::GetInflationRate => "getInflationRate",
@@ -103,15 +119,18 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMaxShredInsertSlot => "getMaxShredInsertSlot",
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
+            RpcRequest::GetMultipleAccountsAtSlot => "getMultipleAccountsAtSlot",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
             RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
+            RpcRequest::GetSignaturesForAddressByTime => "getSignaturesForAddressByTime",
             RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
             RpcRequest::GetSlot => "getSlot",
             RpcRequest::GetSlotLeader => "getSlotLeader",
             RpcRequest::GetSlotLeaders => "getSlotLeaders",
+            RpcRequest::GetStakeActivationProjection => "getStakeActivationProjection",
             RpcRequest::GetStakeMinimumDelegation => "getStakeMinimumDelegation",
             RpcRequest::GetStorageTurn => "getStorageTurn",
             RpcRequest::GetStorageTurnRate => "getStorageTurnRate",
@@ -129,6 +148,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
             RpcRequest::IsBlockhashValid => "isBlockhashValid",
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
+            RpcRequest::PreviewTransactionPriority => "previewTransactionPriority",
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
             RpcRequest::SendTransaction => "sendTransaction",
@@ -145,10 +165,12 @@ pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE: u64 = 10_000;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
+pub const MAX_GET_DUPLICATE_SLOTS_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+pub const MAX_STAKE_ACTIVATION_PROJECTION_EPOCHS: u64 = 64;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
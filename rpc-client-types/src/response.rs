@@ -508,6 +508,116 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+/// Software versions of the cluster weighted by the stake of the current epoch
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterVersionBreakdown {
+    pub epoch: Epoch,
+    /// Stake of all nodes in the current epoch, in lamports
+    pub total_activated_stake: u64,
+    /// One entry per version and feature set, most stake first
+    pub versions: Vec<RpcClusterVersionShare>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClusterVersionShare {
+    /// Software version advertised in gossip, None for staked nodes not seen in gossip
+    pub version: Option<String>,
+    /// Unique identifier of the current software's feature set
+    pub feature_set: Option<u32>,
+    pub node_count: usize,
+    /// Stake of the nodes running this version, in lamports
+    pub activated_stake: u64,
+}
+
+/// Skipped slots and forks among the recently rooted slots of the cluster
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcForkStats {
+    /// First slot of the window, included
+    pub first_slot: Slot,
+    /// Last rooted slot
+    pub last_slot: Slot,
+    pub num_blocks: u64,
+    pub num_skipped_slots: u64,
+    /// Number of rooted blocks that more than one block was built on
+    pub num_forks: u64,
+    /// Percentage of the slots in the window without a rooted block
+    pub skip_rate: f64,
+    pub forks_per_thousand_slots: f64,
+}
+
+/// An account with what the hash of the bank it was read from is computed from
+///
+/// The bank hash is `sha256(sha256(parentBankHash, signatureCount, lastBlockhash),
+/// accountsLtHash)`, hashed once more with `hardForkData` if present. The accounts lattice hash
+/// is a base64 encoded array of 1024 little-endian u16.
+///
+/// A client can check that the components hash to the bank hash, but not that the account is
+/// part of the bank: the accounts lattice hash does not bind individual accounts, so this is no
+/// inclusion proof and the account has to be trusted to come from the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountWithBankHash {
+    /// The account, or `None` if it does not exist
+    pub account: Option<UiAccount>,
+    pub bank_hash: String,
+    pub parent_bank_hash: String,
+    pub signature_count: u64,
+    pub last_blockhash: String,
+    pub accounts_lt_hash: String,
+    /// Base64 encoded hard fork data
+    pub hard_fork_data: Option<String>,
+}
+
+/// Where a transaction would rank among the transactions buffered by the node's banking stage
+///
+/// Transactions are scheduled by priority, the reward of the transaction per compute unit scaled
+/// by 1000000. The buffered transactions are those of the latest snapshot of the queue, taken at
+/// `queueSlot`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionPriorityPreview {
+    pub priority: u64,
+    /// Compute units the transaction is charged towards the block limits
+    pub cost: u64,
+    pub queue_slot: Slot,
+    /// Number of buffered transactions
+    pub queue_size: usize,
+    /// Number of buffered transactions that would be scheduled first
+    pub rank: usize,
+    /// Percentage of the buffered transactions with a lower priority
+    pub fee_percentile: u8,
+    /// Compute units of the buffered transactions that would be scheduled first
+    pub compute_units_ahead: u64,
+    /// Accounts of the transaction locked by buffered transactions that would be scheduled first,
+    /// most contended first
+    pub conflicting_locks: Vec<RpcConflictingLock>,
+    /// Slot the transaction would be included in if this node were the leader from the current
+    /// slot on and no other transactions arrived
+    pub estimated_inclusion_slot: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcConflictingLock {
+    pub account: String,
+    /// Number of higher priority buffered transactions locking the account
+    pub transactions: usize,
+}
+
+/// Two conflicting shreds for the same slot, both signed by the slot leader
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDuplicateSlotProof {
+    pub slot: Slot,
+    /// Base64 encoded shred payload
+    pub shred1: String,
+    /// Base64 encoded shred payload
+    pub shred2: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
@@ -518,6 +628,15 @@ pub struct RpcInflationReward {
     pub commission: Option<u8>, // Vote account commission when the reward was credited
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStakeActivationProjection {
+    pub epoch: Epoch,
+    pub effective: u64,    // lamports
+    pub activating: u64,   // lamports
+    pub deactivating: u64, // lamports
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug, Error, Eq, PartialEq)]
 pub enum RpcBlockUpdateError {
     #[error("block store error")]
@@ -561,6 +680,65 @@ pub struct RpcSnapshotSlotInfo {
     pub incremental: Option<Slot>,
 }
 
+/// Account notification of a subscription that only receives the changed parts of the data
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountChanges {
+    pub lamports: u64,
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: Epoch,
+    pub space: u64,
+    /// The data that changed since the previous notification, all of it in the first notification
+    pub data_changes: Vec<RpcAccountDataChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountDataChange {
+    /// Offset of the changed bytes within the account data
+    pub offset: usize,
+    pub data: UiAccountData,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHealthDetailed {
+    pub healthy: bool,
+    pub causes: Vec<RpcUnhealthyCause>,
+}
+
+/// Why a node reports itself as unhealthy
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase",
+    tag = "cause"
+)]
+pub enum RpcUnhealthyCause {
+    /// The node's latest optimistically confirmed slot is too far behind the cluster's
+    Behind {
+        num_slots: Slot,
+        max_slot_distance: u64,
+    },
+    /// No slot has been optimistically confirmed recently, so the node is not seeing votes
+    NoRecentVotes {
+        secs_since_last_vote: u64,
+        max_vote_age_secs: u64,
+    },
+    /// Snapshots are enabled but there is no full snapshot archive to serve
+    SnapshotMissing,
+    /// A known validator published a different hash for a snapshot slot the node also has
+    AccountsHashMismatch {
+        slot: Slot,
+        hash: String,
+        known_validator: String,
+        known_validator_hash: String,
+    },
+    /// The node has not seen any optimistically confirmed slot, or its blockstore failed
+    Unknown,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcPrioritizationFee {
@@ -0,0 +1,291 @@
+//! Limits on the snapshot and genesis downloads served by the RPC service
+//!
+//! Nodes joining the cluster download genesis and snapshot archives from the RPC ports of other
+//! nodes. A [`FileServingConfig`] lets an operator turn that off, or cap how many downloads run
+//! at once, in total and per client, and how much bandwidth they may use together.
+//!
+//! Clients are told apart by the address of the peer that sent a request. Behind a reverse proxy,
+//! that is the address of the proxy, unless it is configured as trusted and its `X-Forwarded-For`
+//! header is used instead.
+
+use {
+    jsonrpc_core::futures::Stream,
+    jsonrpc_http_server::hyper,
+    std::{
+        collections::HashMap,
+        future::Future,
+        net::IpAddr,
+        num::{NonZeroU64, NonZeroUsize},
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+        time::{Duration, Instant},
+    },
+    tokio::time::Sleep,
+    tokio_util::bytes::Bytes,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileServingConfig {
+    /// Serve genesis and snapshot archives at all
+    pub enabled: bool,
+    /// Number of downloads that may run at once
+    pub max_downloads: Option<NonZeroUsize>,
+    /// Number of downloads that may run at once for the same client address
+    pub max_downloads_per_ip: Option<NonZeroUsize>,
+    /// Bandwidth shared by all downloads
+    pub max_bytes_per_second: Option<NonZeroU64>,
+}
+
+impl Default for FileServingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_downloads: None,
+            max_downloads_per_ip: None,
+            max_bytes_per_second: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum FileServingRejection {
+    Disabled,
+    TooManyDownloads,
+    TooManyDownloadsFromIp(IpAddr),
+}
+
+impl FileServingRejection {
+    pub(crate) fn response(&self) -> hyper::Response<hyper::Body> {
+        let status = match self {
+            Self::Disabled => hyper::StatusCode::FORBIDDEN,
+            Self::TooManyDownloads | Self::TooManyDownloadsFromIp(_) => {
+                hyper::StatusCode::TOO_MANY_REQUESTS
+            }
+        };
+        hyper::Response::builder()
+            .status(status)
+            .body(hyper::Body::empty())
+            .unwrap()
+    }
+}
+
+#[derive(Default)]
+struct Downloads {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+pub(crate) struct FileServingLimiter {
+    config: FileServingConfig,
+    downloads: Mutex<Downloads>,
+    /// When the bandwidth limit allows the next chunk of any download to be sent
+    next_send: Mutex<Instant>,
+}
+
+impl FileServingLimiter {
+    pub(crate) fn new(config: FileServingConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            downloads: Mutex::default(),
+            next_send: Mutex::new(Instant::now()),
+        })
+    }
+
+    pub(crate) fn check_enabled(&self) -> Result<(), FileServingRejection> {
+        if self.config.enabled {
+            Ok(())
+        } else {
+            inc_new_counter_info!("rpc-file_serving-rejected_disabled", 1);
+            Err(FileServingRejection::Disabled)
+        }
+    }
+
+    /// Starts a download for `ip` unless that would exceed one of the limits
+    pub(crate) fn try_start(
+        self: &Arc<Self>,
+        ip: Option<IpAddr>,
+    ) -> Result<DownloadGuard, FileServingRejection> {
+        self.check_enabled()?;
+        let mut downloads = self.downloads.lock().unwrap();
+        if self
+            .config
+            .max_downloads
+            .is_some_and(|max| downloads.total >= max.get())
+        {
+            inc_new_counter_info!("rpc-file_serving-rejected_max_downloads", 1);
+            return Err(FileServingRejection::TooManyDownloads);
+        }
+        if let Some(ip) = ip {
+            let ip_downloads = downloads.per_ip.entry(ip).or_default();
+            if self
+                .config
+                .max_downloads_per_ip
+                .is_some_and(|max| *ip_downloads >= max.get())
+            {
+                inc_new_counter_info!("rpc-file_serving-rejected_max_downloads_per_ip", 1);
+                return Err(FileServingRejection::TooManyDownloadsFromIp(ip));
+            }
+            *ip_downloads += 1;
+        }
+        downloads.total += 1;
+        Ok(DownloadGuard {
+            limiter: Arc::clone(self),
+            ip,
+            start: Instant::now(),
+            bytes: 0,
+        })
+    }
+
+    fn finish(&self, ip: Option<IpAddr>) {
+        let mut downloads = self.downloads.lock().unwrap();
+        downloads.total -= 1;
+        if let Some(ip) = ip {
+            if let Some(ip_downloads) = downloads.per_ip.get_mut(&ip) {
+                *ip_downloads -= 1;
+                if *ip_downloads == 0 {
+                    downloads.per_ip.remove(&ip);
+                }
+            }
+        }
+    }
+
+    /// Returns how long to wait before sending `bytes` to stay within the bandwidth limit
+    fn reserve(&self, bytes: usize) -> Duration {
+        let Some(max_bytes_per_second) = self.config.max_bytes_per_second else {
+            return Duration::ZERO;
+        };
+        let now = Instant::now();
+        let mut next_send = self.next_send.lock().unwrap();
+        let send_at = (*next_send).max(now);
+        *next_send =
+            send_at + Duration::from_secs_f64(bytes as f64 / max_bytes_per_second.get() as f64);
+        send_at - now
+    }
+}
+
+/// A running download, counted against the limits until dropped
+pub(crate) struct DownloadGuard {
+    limiter: Arc<FileServingLimiter>,
+    ip: Option<IpAddr>,
+    start: Instant,
+    bytes: u64,
+}
+
+impl Drop for DownloadGuard {
+    fn drop(&mut self) {
+        self.limiter.finish(self.ip);
+        datapoint_info!(
+            "rpc-file_serving-download",
+            ("bytes", self.bytes, i64),
+            ("duration_ms", self.start.elapsed().as_millis(), i64),
+        );
+    }
+}
+
+/// Passes on the chunks of a download at the pace the bandwidth limit allows
+pub(crate) struct ThrottledStream<S> {
+    inner: S,
+    guard: DownloadGuard,
+    delayed: Option<(Bytes, Pin<Box<Sleep>>)>,
+}
+
+impl<S> ThrottledStream<S> {
+    pub(crate) fn new(inner: S, guard: DownloadGuard) -> Self {
+        Self {
+            inner,
+            guard,
+            delayed: None,
+        }
+    }
+}
+
+impl<S> Stream for ThrottledStream<S>
+where
+    S: Stream<Item = std::io::Result<Bytes>> + Unpin,
+{
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some((_, sleep)) = self.delayed.as_mut() {
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            let (chunk, _) = self.delayed.take().unwrap();
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+        let chunk = match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => chunk,
+            other => return other,
+        };
+        self.guard.bytes += chunk.len() as u64;
+        let delay = self.guard.limiter.reserve(chunk.len());
+        if delay.is_zero() {
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+        let mut sleep = Box::pin(tokio::time::sleep(delay));
+        if sleep.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+        self.delayed = Some((chunk, sleep));
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_file_serving_limiter() {
+        let ip_a = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let ip_b = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let limiter = FileServingLimiter::new(FileServingConfig {
+            max_downloads: NonZeroUsize::new(3),
+            max_downloads_per_ip: NonZeroUsize::new(1),
+            ..FileServingConfig::default()
+        });
+
+        let guard_a = limiter.try_start(Some(ip_a)).unwrap();
+        assert_eq!(
+            limiter.try_start(Some(ip_a)).err(),
+            Some(FileServingRejection::TooManyDownloadsFromIp(ip_a))
+        );
+        let _guard_b = limiter.try_start(Some(ip_b)).unwrap();
+        // requests of unknown clients only count against the total
+        let _guard = limiter.try_start(None).unwrap();
+        assert_eq!(
+            limiter.try_start(None).err(),
+            Some(FileServingRejection::TooManyDownloads)
+        );
+
+        drop(guard_a);
+        let _guard_a = limiter.try_start(Some(ip_a)).unwrap();
+        assert_eq!(limiter.downloads.lock().unwrap().per_ip.len(), 2);
+
+        let limiter = FileServingLimiter::new(FileServingConfig {
+            enabled: false,
+            ..FileServingConfig::default()
+        });
+        assert_eq!(
+            limiter.try_start(Some(ip_a)).err(),
+            Some(FileServingRejection::Disabled)
+        );
+    }
+
+    #[test]
+    fn test_file_serving_bandwidth() {
+        let limiter = FileServingLimiter::new(FileServingConfig::default());
+        assert_eq!(limiter.reserve(1_000_000), Duration::ZERO);
+
+        let limiter = FileServingLimiter::new(FileServingConfig {
+            max_bytes_per_second: NonZeroU64::new(1_000),
+            ..FileServingConfig::default()
+        });
+        assert_eq!(limiter.reserve(500), Duration::ZERO);
+        let delay = limiter.reserve(500);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+        let delay = limiter.reserve(500);
+        assert!(delay > Duration::from_millis(900) && delay <= Duration::from_millis(1_000));
+    }
+}
@@ -0,0 +1,39 @@
+//! The validators whose snapshot hashes the node trusts, which can change while the node runs.
+
+use {
+    solana_pubkey::Pubkey,
+    std::{
+        collections::HashSet,
+        sync::{Arc, RwLock},
+    },
+};
+
+/// A set of known validators shared between the services reading it and the one updating it
+#[derive(Debug, Clone, Default)]
+pub struct KnownValidators(Arc<RwLock<HashSet<Pubkey>>>);
+
+impl KnownValidators {
+    pub fn new(known_validators: HashSet<Pubkey>) -> Self {
+        Self(Arc::new(RwLock::new(known_validators)))
+    }
+
+    pub fn get(&self) -> HashSet<Pubkey> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn set(&self, known_validators: HashSet<Pubkey>) {
+        *self.0.write().unwrap() = known_validators;
+    }
+}
+
+impl PartialEq for KnownValidators {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || *self.0.read().unwrap() == *other.0.read().unwrap()
+    }
+}
+
+impl From<HashSet<Pubkey>> for KnownValidators {
+    fn from(known_validators: HashSet<Pubkey>) -> Self {
+        Self::new(known_validators)
+    }
+}
@@ -9,20 +9,29 @@
 )]
 #![allow(clippy::arithmetic_side_effects)]
 mod cluster_tpu_info;
+pub mod file_serving;
 pub mod filter;
+pub mod known_validators;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
 pub mod rpc;
 mod rpc_cache;
 pub mod rpc_completed_slots_service;
+pub mod rpc_follower_service;
 pub mod rpc_health;
+mod rpc_http_server;
 pub mod rpc_pubsub;
 pub mod rpc_pubsub_service;
+pub mod rpc_response_limits;
 pub mod rpc_service;
 pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
+pub mod rpc_usage;
+pub mod send_transaction_backpressure;
+pub mod simulation_quota;
 pub mod slot_status_notifier;
+mod stake_projection;
 pub mod transaction_notifier_interface;
 pub mod transaction_status_service;
 
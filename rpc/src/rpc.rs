@@ -3,11 +3,27 @@
 use solana_runtime::installed_scheduler_pool::BankWithScheduler;
 use {
     crate::{
-        filter::filter_allows, max_slots::MaxSlots,
+        file_serving::FileServingConfig,
+        filter::filter_allows,
+        known_validators::KnownValidators,
+        max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_cache::LargestAccountsCache, rpc_health::*,
+        parsed_token_accounts::*,
+        rpc_cache::LargestAccountsCache,
+        rpc_health::*,
+        rpc_response_limits::{
+            collect_within_limit, max_list_bytes, response_too_large, LimitedItems, ListSize,
+            RpcResponseLimits, ScanBudget,
+        },
+        rpc_usage::RpcUsageConfig,
+        send_transaction_backpressure::{check_banking_queue_depth, RpcBackpressureConfig},
+        simulation_quota::{check_compute_unit_limit, RpcSimulationConfig, SimulationQuota},
+        stake_projection::project_stake_activation,
+    },
+    agave_snapshots::{
+        paths as snapshot_paths, snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_config::SnapshotConfig,
     },
-    agave_snapshots::{paths as snapshot_paths, snapshot_config::SnapshotConfig},
     base64::{prelude::BASE64_STANDARD, Engine},
     bincode::{config::Options, serialize},
     crossbeam_channel::{unbounded, Receiver, Sender},
@@ -17,6 +33,7 @@ use {
         BoxFuture, Error, Metadata, Result,
     },
     jsonrpc_derive::rpc,
+    serde::Serialize,
     solana_account::{AccountSharedData, ReadableAccount},
     solana_account_decoder::{
         encode_ui_account,
@@ -38,9 +55,11 @@ use {
     solana_epoch_rewards_hasher::EpochRewardsHasher,
     solana_epoch_schedule::EpochSchedule,
     solana_faucet::faucet::request_airdrop_transaction,
+    solana_fee_structure::FeeBudgetLimits,
     solana_gossip::cluster_info::ClusterInfo,
     solana_hash::Hash,
     solana_keypair::Keypair,
+    solana_lattice_hash::lt_hash::LtHash,
     solana_ledger::{
         blockstore::{Blockstore, BlockstoreError, SignatureInfosForAddress},
         blockstore_meta::{PerfSample, PerfSampleV1, PerfSampleV2},
@@ -58,24 +77,31 @@ use {
         request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
-            MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
-            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, NUM_LARGEST_ACCOUNTS,
+            MAX_GET_DUPLICATE_SLOTS_LIMIT, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+            MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY,
+            MAX_STAKE_ACTIVATION_PROJECTION_EPOCHS, NUM_LARGEST_ACCOUNTS,
         },
         response::{Response as RpcResponse, *},
     },
     solana_runtime::{
         bank::{Bank, TransactionSimulationResult},
         bank_forks::BankForks,
+        banking_queue_snapshot::{calculate_priority_and_cost, SharedBankingQueueSnapshot},
         commitment::{BlockCommitmentArray, BlockCommitmentCache},
         non_circulating_supply::{calculate_non_circulating_supply, NonCirculatingSupply},
         prioritization_fee_cache::PrioritizationFeeCache,
         stake_utils,
     },
-    solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+    solana_runtime_transaction::{
+        runtime_transaction::RuntimeTransaction, transaction_meta::StaticMeta,
+    },
     solana_send_transaction_service::send_transaction_service::TransactionInfo,
     solana_signature::Signature,
     solana_signer::Signer,
+    solana_stake_interface::{
+        stake_history::StakeHistory, state::StakeStateV2, sysvar::stake_history,
+    },
     solana_storage_bigtable::Error as StorageError,
     solana_transaction::{
         sanitized::{MessageHash, SanitizedTransaction, MAX_TX_ACCOUNT_LOCKS},
@@ -108,7 +134,8 @@ use {
         cmp::{max, min, Reverse},
         collections::{BinaryHeap, HashMap, HashSet},
         convert::TryFrom,
-        net::SocketAddr,
+        net::{IpAddr, SocketAddr},
+        num::NonZeroUsize,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
@@ -162,8 +189,17 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_extended_tx_metadata_storage: bool,
+    /// Start the transaction status service with writing the transaction history paused, so it
+    /// can be resumed over the admin RPC without a restart
+    pub transaction_history_paused: bool,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
+    /// Report the node as unhealthy in `getHealthDetailed` if the cluster's latest
+    /// optimistically confirmed slot was seen longer ago than this
+    pub health_check_max_vote_age_secs: Option<u64>,
+    /// Validators whose published snapshot hashes `getHealthDetailed` compares against the
+    /// node's own snapshot archives
+    pub known_validators: Option<KnownValidators>,
     pub skip_preflight_health_check: bool,
     pub rpc_bigtable_config: Option<RpcBigtableConfig>,
     pub max_multiple_accounts: Option<usize>,
@@ -176,6 +212,22 @@ pub struct JsonRpcConfig {
     pub max_request_body_size: Option<usize>,
     /// Disable the health check, used for tests and TestValidator
     pub disable_health_check: bool,
+    /// Whether and how fast genesis and snapshot archives are served to other nodes
+    pub file_serving: FileServingConfig,
+    /// Reverse proxies whose `X-Forwarded-For` header is believed when telling clients apart
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Per-client accounting of the requests served, disabled if `None`
+    pub usage: Option<RpcUsageConfig>,
+    pub simulation: RpcSimulationConfig,
+    pub backpressure: RpcBackpressureConfig,
+    /// Maximum response size of the methods that can return unbounded results
+    pub response_limits: RpcResponseLimits,
+    /// Reject `sendTransaction` and `requestAirdrop`, for nodes serving a snapshot without
+    /// joining the cluster
+    pub read_only: bool,
+    /// Serve `previewTransactionPriority`, for which the banking stage takes snapshots of its
+    /// queue
+    pub enable_transaction_priority_preview: bool,
 }
 
 impl Default for JsonRpcConfig {
@@ -183,8 +235,11 @@ impl Default for JsonRpcConfig {
         Self {
             enable_rpc_transaction_history: Default::default(),
             enable_extended_tx_metadata_storage: Default::default(),
+            transaction_history_paused: Default::default(),
             faucet_addr: Option::default(),
             health_check_slot_distance: Default::default(),
+            health_check_max_vote_age_secs: Option::default(),
+            known_validators: Option::default(),
             skip_preflight_health_check: bool::default(),
             rpc_bigtable_config: Option::default(),
             max_multiple_accounts: Option::default(),
@@ -196,6 +251,14 @@ impl Default for JsonRpcConfig {
             rpc_scan_and_fix_roots: Default::default(),
             max_request_body_size: Option::default(),
             disable_health_check: Default::default(),
+            file_serving: FileServingConfig::default(),
+            trusted_proxies: Vec::new(),
+            usage: Option::default(),
+            simulation: RpcSimulationConfig::default(),
+            backpressure: RpcBackpressureConfig::default(),
+            response_limits: RpcResponseLimits::default(),
+            read_only: false,
+            enable_transaction_priority_preview: false,
         }
     }
 }
@@ -217,6 +280,12 @@ pub struct RpcBigtableConfig {
     pub bigtable_app_profile_id: String,
     pub timeout: Option<Duration>,
     pub max_message_size: usize,
+    /// Service account credentials file, GOOGLE_APPLICATION_CREDENTIALS is used if not set.
+    /// Modifications to the file are picked up without restarting.
+    pub credential_path: Option<String>,
+    pub connection_pool_size: NonZeroUsize,
+    /// Fail startup instead of serving RPC without BigTable if the connection can't be initialized
+    pub required: bool,
 }
 
 impl Default for RpcBigtableConfig {
@@ -229,6 +298,9 @@ impl Default for RpcBigtableConfig {
             bigtable_app_profile_id,
             timeout: None,
             max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+            credential_path: None,
+            connection_pool_size: solana_storage_bigtable::DEFAULT_CONNECTION_POOL_SIZE,
+            required: false,
         }
     }
 }
@@ -253,7 +325,12 @@ pub struct JsonRpcRequestProcessor {
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    /// Snapshots of the banking stage queue, if `previewTransactionPriority` is enabled
+    banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
     runtime: Arc<Runtime>,
+    /// Client the request is accounted to, when usage tracking is enabled
+    usage_client: Option<String>,
+    simulation_quota: Arc<SimulationQuota>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -264,6 +341,24 @@ impl JsonRpcRequestProcessor {
             ..self.clone()
         }
     }
+
+    pub fn clone_with_usage_client(&self, usage_client: String) -> JsonRpcRequestProcessor {
+        Self {
+            usage_client: Some(usage_client),
+            ..self.clone()
+        }
+    }
+
+    pub fn usage_client(&self) -> Option<&String> {
+        self.usage_client.as_ref()
+    }
+
+    pub(crate) fn clone_with_runtime(&self, runtime: Arc<Runtime>) -> JsonRpcRequestProcessor {
+        Self {
+            runtime,
+            ..self.clone()
+        }
+    }
 }
 
 impl JsonRpcRequestProcessor {
@@ -309,6 +404,7 @@ impl JsonRpcRequestProcessor {
         program_id: &Pubkey,
         filters: Vec<RpcFilterType>,
         sort_results: bool,
+        scan_budget: Option<Arc<ScanBudget>>,
     ) -> ScanResult<Vec<KeyedAccountSharedData>> {
         let scan_order = if sort_results {
             ScanOrder::Sorted
@@ -332,6 +428,9 @@ impl JsonRpcRequestProcessor {
                             && filters
                                 .iter()
                                 .all(|filter_type| filter_allows(filter_type, account))
+                            && scan_budget
+                                .as_ref()
+                                .is_none_or(|scan_budget| scan_budget.keep(account))
                     },
                     &ScanConfig::new(scan_order),
                     bank.byte_limit_for_scans(),
@@ -416,9 +515,11 @@ impl JsonRpcRequestProcessor {
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
         runtime: Arc<Runtime>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (transaction_sender, transaction_receiver) = unbounded();
+        let simulation_quota = Arc::new(SimulationQuota::new(config.simulation.max_concurrent));
         (
             Self {
                 config,
@@ -438,7 +539,10 @@ impl JsonRpcRequestProcessor {
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
                 prioritization_fee_cache,
+                banking_queue_snapshot,
                 runtime,
+                usage_client: None,
+                simulation_quota,
             },
             transaction_receiver,
         )
@@ -511,6 +615,7 @@ impl JsonRpcRequestProcessor {
                 Arc::clone(&optimistically_confirmed_bank),
                 blockstore,
                 0,
+                None,
                 exit,
             )),
             cluster_info,
@@ -523,7 +628,10 @@ impl JsonRpcRequestProcessor {
             leader_schedule_cache,
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
+            banking_queue_snapshot: None,
             runtime,
+            usage_client: None,
+            simulation_quota: Arc::new(SimulationQuota::default()),
         }
     }
 
@@ -571,22 +679,228 @@ impl JsonRpcRequestProcessor {
             min_context_slot,
         })?;
         let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
+        let max_bytes = self
+            .config
+            .response_limits
+            .max_response_bytes("getMultipleAccounts");
 
+        let mut list_size = ListSize::new(max_list_bytes(
+            max_bytes,
+            &new_response(&bank, Vec::<()>::new()),
+        ));
+        let mut truncated = false;
         let mut accounts = Vec::with_capacity(pubkeys.len());
         for pubkey in pubkeys {
             let bank = Arc::clone(&bank);
-            accounts.push(
-                self.runtime
-                    .spawn_blocking(move || {
-                        get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
-                    })
-                    .await
-                    .expect("rpc: get_encoded_account panicked")?,
-            );
+            let account = self
+                .runtime
+                .spawn_blocking(move || {
+                    get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
+                })
+                .await
+                .expect("rpc: get_encoded_account panicked")?;
+            if !list_size.push(&account) {
+                truncated = true;
+                break;
+            }
+            accounts.push(account);
         }
+        limit_accounts_response(
+            "getMultipleAccounts",
+            max_bytes,
+            truncated,
+            new_response(&bank, accounts),
+        )
+    }
+
+    /// Reads all of `pubkeys` from a single frozen bank, so that separate requests pinned to the
+    /// returned context slot see the same account state
+    pub async fn get_multiple_accounts_at_slot(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        config: Option<RpcMultipleAccountsAtSlotConfig>,
+    ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
+        let RpcMultipleAccountsAtSlotConfig {
+            slot,
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice,
+                    commitment,
+                    min_context_slot,
+                },
+        } = config.unwrap_or_default();
+        let bank = self.get_frozen_bank(
+            slot,
+            RpcContextConfig {
+                commitment,
+                min_context_slot,
+            },
+        )?;
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
+
+        let accounts = self
+            .runtime
+            .spawn_blocking({
+                let bank = Arc::clone(&bank);
+                move || {
+                    pubkeys
+                        .iter()
+                        .map(|pubkey| {
+                            get_encoded_account(&bank, pubkey, encoding, data_slice, None)
+                        })
+                        .collect::<Result<Vec<_>>>()
+                }
+            })
+            .await
+            .expect("rpc: get_encoded_account panicked")?;
         Ok(new_response(&bank, accounts))
     }
 
+    /// Returns where `transaction` would rank among the transactions buffered by the banking
+    /// stage
+    fn preview_transaction_priority(
+        &self,
+        transaction: &RuntimeTransaction<SanitizedTransaction>,
+        bank: &Bank,
+    ) -> Result<RpcTransactionPriorityPreview> {
+        let compute_budget_limits = transaction
+            .compute_budget_instruction_details()
+            .sanitize_and_convert_to_compute_budget_limits(&bank.feature_set)
+            .map_err(|err| {
+                Error::invalid_params(format!("invalid compute budget instructions: {err}"))
+            })?;
+        let (priority, cost) = calculate_priority_and_cost(
+            transaction,
+            &FeeBudgetLimits::from(compute_budget_limits),
+            bank,
+        );
+        let message = transaction.message();
+        let (writable_accounts, readonly_accounts): (Vec<_>, Vec<_>) = message
+            .account_keys()
+            .iter()
+            .enumerate()
+            .partition(|(index, _)| message.is_writable(*index));
+        let writable_accounts: Vec<_> =
+            writable_accounts.into_iter().map(|(_, key)| *key).collect();
+        let readonly_accounts: Vec<_> =
+            readonly_accounts.into_iter().map(|(_, key)| *key).collect();
+
+        let snapshot = self
+            .banking_queue_snapshot
+            .as_ref()
+            .ok_or_else(|| Error {
+                code: error::ErrorCode::MethodNotFound,
+                message: "previewTransactionPriority is not enabled on this node".to_string(),
+                data: None,
+            })?
+            .load();
+        let preview = snapshot.preview(priority, &writable_accounts, &readonly_accounts);
+
+        // Leading from the working bank on, blocks fill up with the transactions ahead first
+        let working_bank = self.bank_forks.read().unwrap().working_bank();
+        let block_cost_limit = working_bank.read_cost_tracker().unwrap().get_block_limit();
+        let estimated_inclusion_slot = working_bank
+            .slot()
+            .saturating_add(preview.cost_ahead / block_cost_limit.max(1));
+
+        Ok(RpcTransactionPriorityPreview {
+            priority,
+            cost,
+            queue_slot: snapshot.slot,
+            queue_size: snapshot.transactions.len(),
+            rank: preview.rank,
+            fee_percentile: preview.percentile,
+            compute_units_ahead: preview.cost_ahead,
+            conflicting_locks: preview
+                .conflicting_locks
+                .into_iter()
+                .map(|(account, transactions)| RpcConflictingLock {
+                    account: account.to_string(),
+                    transactions,
+                })
+                .collect(),
+            estimated_inclusion_slot,
+        })
+    }
+
+    /// Reads `pubkey` from a frozen bank, along with the components of the bank hash
+    pub async fn get_account_with_bank_hash(
+        &self,
+        pubkey: Pubkey,
+        config: Option<RpcAccountWithBankHashConfig>,
+    ) -> Result<RpcResponse<RpcAccountWithBankHash>> {
+        let RpcAccountWithBankHashConfig {
+            slot,
+            encoding,
+            commitment,
+            min_context_slot,
+        } = config.unwrap_or_default();
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
+        if encoding == UiAccountEncoding::JsonParsed {
+            return Err(Error::invalid_params(
+                "jsonParsed encoding is not supported by getAccountWithBankHash",
+            ));
+        }
+        let bank = self.get_frozen_bank(
+            slot,
+            RpcContextConfig {
+                commitment,
+                min_context_slot,
+            },
+        )?;
+
+        let account_with_bank_hash = self
+            .runtime
+            .spawn_blocking({
+                let bank = Arc::clone(&bank);
+                move || {
+                    let hash_components = bank.hash_components();
+                    let account = bank.get_account(&pubkey);
+                    Ok::<_, Error>(RpcAccountWithBankHash {
+                        account: account
+                            .map(|account| encode_account(&account, &pubkey, encoding, None))
+                            .transpose()?,
+                        bank_hash: bank.hash().to_string(),
+                        parent_bank_hash: hash_components.parent_hash.to_string(),
+                        signature_count: hash_components.signature_count,
+                        last_blockhash: hash_components.last_blockhash.to_string(),
+                        accounts_lt_hash: encode_lt_hash(&hash_components.accounts_lt_hash.0),
+                        hard_fork_data: hash_components
+                            .hard_fork_data
+                            .map(|hard_fork_data| BASE64_STANDARD.encode(hard_fork_data)),
+                    })
+                }
+            })
+            .await
+            .expect("rpc: get_account_with_bank_hash panicked")?;
+        Ok(new_response(&bank, account_with_bank_hash))
+    }
+
+    /// Returns the bank at `slot`, or the bank of the commitment level if `slot` is not given
+    ///
+    /// The bank must be frozen and be the bank of the commitment level or one of its ancestors.
+    fn get_frozen_bank(&self, slot: Option<Slot>, config: RpcContextConfig) -> Result<Arc<Bank>> {
+        let commitment_bank = self.get_bank_with_config(config)?;
+        let bank = match slot {
+            None => commitment_bank,
+            Some(slot) => {
+                if !commitment_bank.ancestors.contains_key(&slot) {
+                    return Err(RpcCustomError::BankNotAvailable { slot }.into());
+                }
+                self.bank_forks
+                    .read()
+                    .unwrap()
+                    .get(slot)
+                    .ok_or(RpcCustomError::BankNotAvailable { slot })?
+            }
+        };
+        if !bank.is_frozen() {
+            return Err(RpcCustomError::BankNotAvailable { slot: bank.slot() }.into());
+        }
+        Ok(bank)
+    }
+
     pub fn get_minimum_balance_for_rent_exemption(
         &self,
         data_len: usize,
@@ -615,6 +929,11 @@ impl JsonRpcRequestProcessor {
             min_context_slot,
         })?;
         let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        let max_bytes = self
+            .config
+            .response_limits
+            .max_response_bytes("getProgramAccounts");
+        let scan_budget = ScanBudget::new(max_bytes, encoding, data_slice_config).map(Arc::new);
         optimize_filters(&mut filters);
         let keyed_accounts = {
             if let Some(owner) = get_spl_token_owner_filter(&program_id, &filters)? {
@@ -624,6 +943,7 @@ impl JsonRpcRequestProcessor {
                     owner,
                     filters,
                     sort_results,
+                    scan_budget.clone(),
                 )
                 .await?
             } else if let Some(mint) = get_spl_token_mint_filter(&program_id, &filters)? {
@@ -633,6 +953,7 @@ impl JsonRpcRequestProcessor {
                     mint,
                     filters,
                     sort_results,
+                    scan_budget.clone(),
                 )
                 .await?
             } else {
@@ -641,29 +962,34 @@ impl JsonRpcRequestProcessor {
                     program_id,
                     filters,
                     sort_results,
+                    scan_budget.clone(),
                 )
                 .await?
             }
         };
-        let accounts = if is_known_spl_token_id(&program_id)
-            && encoding == UiAccountEncoding::JsonParsed
-        {
-            get_parsed_token_accounts(Arc::clone(&bank), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
-        Ok(match with_context {
-            true => OptionalContext::Context(new_response(&bank, accounts)),
-            false => OptionalContext::NoContext(accounts),
-        })
+        let LimitedItems {
+            items: accounts,
+            truncated_at,
+        } = encode_keyed_accounts(
+            &bank,
+            keyed_accounts,
+            is_known_spl_token_id(&program_id),
+            encoding,
+            data_slice_config,
+            match with_context {
+                true => max_list_bytes(max_bytes, &new_response(&bank, Vec::<()>::new())),
+                false => max_bytes,
+            },
+        )?;
+        limit_accounts_response(
+            "getProgramAccounts",
+            max_bytes,
+            truncated_at.is_some() || scan_budget.is_some_and(|scan_budget| scan_budget.exceeded()),
+            match with_context {
+                true => OptionalContext::Context(new_response(&bank, accounts)),
+                false => OptionalContext::NoContext(accounts),
+            },
+        )
     }
 
     fn filter_map_rewards<'a, F>(
@@ -1019,6 +1345,180 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    fn get_cluster_version_breakdown(
+        &self,
+        config: RpcContextConfig,
+    ) -> Result<RpcResponse<RpcClusterVersionBreakdown>> {
+        let bank = self.get_bank_with_config(config)?;
+        let staked_nodes = bank.current_epoch_staked_nodes();
+        let socket_addr_space = self.cluster_info.socket_addr_space();
+        let mut versions = HashMap::<(Option<String>, Option<u32>), (usize, u64)>::new();
+        let mut seen_nodes = HashSet::new();
+        for (contact_info, _) in self.cluster_info.all_peers() {
+            // Exclude spy nodes, like getClusterNodes does
+            if !contact_info
+                .gossip()
+                .map(|addr| socket_addr_space.check(&addr))
+                .unwrap_or_default()
+            {
+                continue;
+            }
+            let pubkey = *contact_info.pubkey();
+            seen_nodes.insert(pubkey);
+            let version = self.cluster_info.get_node_version(&pubkey);
+            let key = (
+                version.as_ref().map(ToString::to_string),
+                version.map(|version| version.feature_set),
+            );
+            let (node_count, activated_stake) = versions.entry(key).or_default();
+            *node_count += 1;
+            *activated_stake += staked_nodes.get(&pubkey).copied().unwrap_or_default();
+        }
+        // Staked nodes missing from gossip still count against the total
+        for (pubkey, stake) in staked_nodes.iter() {
+            if *stake > 0 && !seen_nodes.contains(pubkey) {
+                let (node_count, activated_stake) = versions.entry((None, None)).or_default();
+                *node_count += 1;
+                *activated_stake += stake;
+            }
+        }
+
+        let mut versions: Vec<_> = versions
+            .into_iter()
+            .map(
+                |((version, feature_set), (node_count, activated_stake))| RpcClusterVersionShare {
+                    version,
+                    feature_set,
+                    node_count,
+                    activated_stake,
+                },
+            )
+            .collect();
+        versions.sort_by(|a, b| {
+            b.activated_stake
+                .cmp(&a.activated_stake)
+                .then(b.node_count.cmp(&a.node_count))
+                .then_with(|| a.version.cmp(&b.version))
+                .then(a.feature_set.cmp(&b.feature_set))
+        });
+        Ok(new_response(
+            &bank,
+            RpcClusterVersionBreakdown {
+                epoch: bank.epoch(),
+                total_activated_stake: staked_nodes.values().sum(),
+                versions,
+            },
+        ))
+    }
+
+    fn get_duplicate_slots(&self, start_slot: Slot, limit: Option<usize>) -> Result<Vec<Slot>> {
+        let limit = limit.unwrap_or(MAX_GET_DUPLICATE_SLOTS_LIMIT);
+        if limit > MAX_GET_DUPLICATE_SLOTS_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {MAX_GET_DUPLICATE_SLOTS_LIMIT}"
+            )));
+        }
+        Ok(self
+            .blockstore
+            .duplicate_slots_iterator(start_slot)
+            .map_err(|err| {
+                warn!("duplicate_slots_iterator failed: {err:?}");
+                Error::invalid_request()
+            })?
+            .take(limit)
+            .collect())
+    }
+
+    fn get_duplicate_slot_proof(&self, slot: Slot) -> Option<RpcDuplicateSlotProof> {
+        self.blockstore
+            .get_duplicate_slot(slot)
+            .map(|proof| RpcDuplicateSlotProof {
+                slot,
+                shred1: BASE64_STANDARD.encode(&proof.shred1),
+                shred2: BASE64_STANDARD.encode(&proof.shred2),
+            })
+    }
+
+    fn get_health_detailed(&self) -> RpcHealthDetailed {
+        let mut causes = self.health.check_detailed();
+        if let Some(snapshot_config) = self
+            .snapshot_config
+            .as_ref()
+            .filter(|snapshot_config| snapshot_config.should_generate_snapshots())
+        {
+            causes.extend(self.check_snapshot_health(snapshot_config));
+        }
+        RpcHealthDetailed {
+            healthy: causes.is_empty(),
+            causes,
+        }
+    }
+
+    /// Checks that the node has a full snapshot archive and that the known validators agree with
+    /// the hashes of its latest snapshot archives
+    fn check_snapshot_health(&self, snapshot_config: &SnapshotConfig) -> Vec<RpcUnhealthyCause> {
+        let keeps_local_archives = snapshot_config
+            .archive_upload
+            .as_ref()
+            .is_none_or(|archive_upload| archive_upload.keep_local_archive);
+        if !keeps_local_archives {
+            return vec![];
+        }
+        let Some(full_snapshot_archive_info) =
+            snapshot_paths::get_highest_full_snapshot_archive_info(
+                &snapshot_config.full_snapshot_archives_dir,
+            )
+        else {
+            return vec![RpcUnhealthyCause::SnapshotMissing];
+        };
+        let mut snapshot_hashes = vec![(
+            full_snapshot_archive_info.slot(),
+            full_snapshot_archive_info.hash().0,
+        )];
+        if let Some(incremental_snapshot_archive_info) =
+            snapshot_paths::get_highest_incremental_snapshot_archive_info(
+                &snapshot_config.incremental_snapshot_archives_dir,
+                full_snapshot_archive_info.slot(),
+            )
+        {
+            snapshot_hashes.push((
+                incremental_snapshot_archive_info.slot(),
+                incremental_snapshot_archive_info.hash().0,
+            ));
+        }
+
+        let mut causes = vec![];
+        let known_validators = self
+            .config
+            .known_validators
+            .as_ref()
+            .map(KnownValidators::get)
+            .unwrap_or_default();
+        for known_validator in &known_validators {
+            let Some(known_validator_hashes) = self
+                .cluster_info
+                .get_snapshot_hashes_for_node(known_validator)
+            else {
+                continue;
+            };
+            let known_validator_hashes = std::iter::once(&known_validator_hashes.full)
+                .chain(&known_validator_hashes.incremental);
+            for (known_validator_slot, known_validator_hash) in known_validator_hashes {
+                if let Some((slot, hash)) = snapshot_hashes.iter().find(|(slot, hash)| {
+                    slot == known_validator_slot && hash != known_validator_hash
+                }) {
+                    causes.push(RpcUnhealthyCause::AccountsHashMismatch {
+                        slot: *slot,
+                        hash: hash.to_string(),
+                        known_validator: known_validator.to_string(),
+                        known_validator_hash: known_validator_hash.to_string(),
+                    });
+                }
+            }
+        }
+        causes
+    }
+
     fn get_transaction_count(&self, config: RpcContextConfig) -> Result<u64> {
         let bank = self.get_bank_with_config(config)?;
         Ok(bank.transaction_count())
@@ -1822,28 +2322,8 @@ impl JsonRpcRequestProcessor {
         config: RpcContextConfig,
     ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
         self.check_if_transaction_history_enabled()?;
-
-        let commitment = config.commitment.unwrap_or_default();
-        check_is_at_least_confirmed(commitment)?;
-
-        let highest_super_majority_root = self
-            .block_commitment_cache
-            .read()
-            .unwrap()
-            .highest_super_majority_root();
-        let highest_slot = if commitment.is_confirmed() {
-            let confirmed_bank = self.get_bank_with_config(config)?;
-            confirmed_bank.slot()
-        } else {
-            let min_context_slot = config.min_context_slot.unwrap_or_default();
-            if highest_super_majority_root < min_context_slot {
-                return Err(RpcCustomError::MinContextSlotNotReached {
-                    context_slot: highest_super_majority_root,
-                }
-                .into());
-            }
-            highest_super_majority_root
-        };
+        let (highest_super_majority_root, highest_slot) =
+            self.get_signatures_for_address_highest_slot(config)?;
 
         let SignatureInfosForAddress {
             infos: mut results,
@@ -1853,27 +2333,6 @@ impl JsonRpcRequestProcessor {
             .get_confirmed_signatures_for_address2(address, highest_slot, before, until, limit)
             .map_err(|err| Error::invalid_params(format!("{err}")))?;
 
-        let map_results = |results: Vec<ConfirmedTransactionStatusWithSignature>| {
-            results
-                .into_iter()
-                .map(|x| {
-                    let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
-                    if item.slot <= highest_super_majority_root {
-                        item.confirmation_status = Some(TransactionConfirmationStatus::Finalized);
-                    } else {
-                        item.confirmation_status = Some(TransactionConfirmationStatus::Confirmed);
-                        if item.block_time.is_none() {
-                            let r_bank_forks = self.bank_forks.read().unwrap();
-                            item.block_time = r_bank_forks
-                                .get(item.slot)
-                                .map(|bank| bank.clock().unix_timestamp);
-                        }
-                    }
-                    item
-                })
-                .collect()
-        };
-
         if results.len() < limit {
             if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                 let mut bigtable_before = before;
@@ -1933,7 +2392,112 @@ impl JsonRpcRequestProcessor {
             }
         }
 
-        Ok(map_results(results))
+        let LimitedItems {
+            items: results,
+            truncated_at,
+        } = collect_within_limit(
+            self.map_signatures_for_address(results, highest_super_majority_root)
+                .into_iter()
+                .map(Ok::<_, Error>),
+            self.config
+                .response_limits
+                .max_response_bytes("getSignaturesForAddress"),
+        )?;
+        match truncated_at {
+            // The rest of the signatures are the ones before the last one returned
+            Some(max_bytes) => Err(response_too_large(
+                "getSignaturesForAddress",
+                max_bytes,
+                &results,
+                results.last().map(|result| result.signature.clone()),
+            )),
+            None => Ok(results),
+        }
+    }
+
+    pub fn get_signatures_for_address_by_time(
+        &self,
+        address: Pubkey,
+        start_time: UnixTimestamp,
+        end_time: UnixTimestamp,
+        after: Option<Signature>,
+        limit: usize,
+        config: RpcContextConfig,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.check_if_transaction_history_enabled()?;
+        if !self.blockstore.is_address_time_index_enabled() {
+            return Err(RpcCustomError::AddressTimeIndexNotAvailable.into());
+        }
+        let (highest_super_majority_root, highest_slot) =
+            self.get_signatures_for_address_highest_slot(config)?;
+
+        let results = self
+            .blockstore
+            .get_address_transactions_by_time(
+                address,
+                highest_slot,
+                start_time,
+                end_time,
+                after,
+                limit,
+            )
+            .map_err(|err| Error::invalid_params(format!("{err}")))?;
+        Ok(self.map_signatures_for_address(results, highest_super_majority_root))
+    }
+
+    /// Returns the highest super majority root and the highest slot whose transactions are
+    /// listed by the signatures for address methods
+    fn get_signatures_for_address_highest_slot(
+        &self,
+        config: RpcContextConfig,
+    ) -> Result<(Slot, Slot)> {
+        let commitment = config.commitment.unwrap_or_default();
+        check_is_at_least_confirmed(commitment)?;
+
+        let highest_super_majority_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_super_majority_root();
+        let highest_slot = if commitment.is_confirmed() {
+            let confirmed_bank = self.get_bank_with_config(config)?;
+            confirmed_bank.slot()
+        } else {
+            let min_context_slot = config.min_context_slot.unwrap_or_default();
+            if highest_super_majority_root < min_context_slot {
+                return Err(RpcCustomError::MinContextSlotNotReached {
+                    context_slot: highest_super_majority_root,
+                }
+                .into());
+            }
+            highest_super_majority_root
+        };
+        Ok((highest_super_majority_root, highest_slot))
+    }
+
+    fn map_signatures_for_address(
+        &self,
+        results: Vec<ConfirmedTransactionStatusWithSignature>,
+        highest_super_majority_root: Slot,
+    ) -> Vec<RpcConfirmedTransactionStatusWithSignature> {
+        results
+            .into_iter()
+            .map(|x| {
+                let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
+                if item.slot <= highest_super_majority_root {
+                    item.confirmation_status = Some(TransactionConfirmationStatus::Finalized);
+                } else {
+                    item.confirmation_status = Some(TransactionConfirmationStatus::Confirmed);
+                    if item.block_time.is_none() {
+                        let r_bank_forks = self.bank_forks.read().unwrap();
+                        item.block_time = r_bank_forks
+                            .get(item.slot)
+                            .map(|bank| bank.clock().unix_timestamp);
+                    }
+                }
+                item
+            })
+            .collect()
     }
 
     pub async fn get_first_available_block(&self) -> Slot {
@@ -2041,6 +2605,7 @@ impl JsonRpcRequestProcessor {
                 mint,
                 vec![],
                 true,
+                None,
             )
             .await?
         {
@@ -2104,6 +2669,11 @@ impl JsonRpcRequestProcessor {
             )));
         }
 
+        let max_bytes = self
+            .config
+            .response_limits
+            .max_response_bytes("getTokenAccountsByOwner");
+        let scan_budget = ScanBudget::new(max_bytes, encoding, data_slice_config).map(Arc::new);
         let keyed_accounts = self
             .get_filtered_spl_token_accounts_by_owner(
                 Arc::clone(&bank),
@@ -2111,22 +2681,26 @@ impl JsonRpcRequestProcessor {
                 owner,
                 filters,
                 sort_results,
+                scan_budget.clone(),
             )
             .await?;
-        let accounts = if encoding == UiAccountEncoding::JsonParsed {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
-        Ok(new_response(&bank, accounts))
+        let LimitedItems {
+            items: accounts,
+            truncated_at,
+        } = encode_keyed_accounts(
+            &bank,
+            keyed_accounts,
+            /*is_token_program:*/ true,
+            encoding,
+            data_slice_config,
+            max_list_bytes(max_bytes, &new_response(&bank, Vec::<()>::new())),
+        )?;
+        limit_accounts_response(
+            "getTokenAccountsByOwner",
+            max_bytes,
+            truncated_at.is_some() || scan_budget.is_some_and(|scan_budget| scan_budget.exceeded()),
+            new_response(&bank, accounts),
+        )
     }
 
     pub async fn get_token_accounts_by_delegate(
@@ -2158,6 +2732,11 @@ impl JsonRpcRequestProcessor {
             // Filter on Delegate address
             RpcFilterType::Memcmp(Memcmp::new_raw_bytes(76, delegate.to_bytes().into())),
         ];
+        let max_bytes = self
+            .config
+            .response_limits
+            .max_response_bytes("getTokenAccountsByDelegate");
+        let scan_budget = ScanBudget::new(max_bytes, encoding, data_slice_config).map(Arc::new);
         // Optional filter on Mint address, uses mint account index for scan
         let keyed_accounts = if let Some(mint) = mint {
             self.get_filtered_spl_token_accounts_by_mint(
@@ -2166,6 +2745,7 @@ impl JsonRpcRequestProcessor {
                 mint,
                 filters,
                 sort_results,
+                scan_budget.clone(),
             )
             .await?
         } else {
@@ -2176,23 +2756,27 @@ impl JsonRpcRequestProcessor {
                 token_program_id,
                 filters,
                 sort_results,
+                scan_budget.clone(),
             )
             .await?
         };
-        let accounts = if encoding == UiAccountEncoding::JsonParsed {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
-        Ok(new_response(&bank, accounts))
+        let LimitedItems {
+            items: accounts,
+            truncated_at,
+        } = encode_keyed_accounts(
+            &bank,
+            keyed_accounts,
+            /*is_token_program:*/ true,
+            encoding,
+            data_slice_config,
+            max_list_bytes(max_bytes, &new_response(&bank, Vec::<()>::new())),
+        )?;
+        limit_accounts_response(
+            "getTokenAccountsByDelegate",
+            max_bytes,
+            truncated_at.is_some() || scan_budget.is_some_and(|scan_budget| scan_budget.exceeded()),
+            new_response(&bank, accounts),
+        )
     }
 
     /// Use a set of filters to get an iterator of keyed program accounts from a bank
@@ -2202,6 +2786,7 @@ impl JsonRpcRequestProcessor {
         program_id: Pubkey,
         mut filters: Vec<RpcFilterType>,
         sort_results: bool,
+        scan_budget: Option<Arc<ScanBudget>>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         optimize_filters(&mut filters);
         if self
@@ -2220,6 +2805,7 @@ impl JsonRpcRequestProcessor {
                 &program_id,
                 filters,
                 sort_results,
+                scan_budget,
             )
             .await
             .map_err(|e| RpcCustomError::ScanError {
@@ -2240,6 +2826,9 @@ impl JsonRpcRequestProcessor {
                             filters
                                 .iter()
                                 .all(|filter_type| filter_allows(filter_type, account))
+                                && scan_budget
+                                    .as_ref()
+                                    .is_none_or(|scan_budget| scan_budget.keep(account))
                         },
                         &ScanConfig::new(scan_order),
                     )
@@ -2260,6 +2849,7 @@ impl JsonRpcRequestProcessor {
         owner_key: Pubkey,
         mut filters: Vec<RpcFilterType>,
         sort_results: bool,
+        scan_budget: Option<Arc<ScanBudget>>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-owner accounts index checks for Token Account state and Owner address on
         // inclusion. However, due to the current AccountsDb implementation, an account may remain
@@ -2290,13 +2880,14 @@ impl JsonRpcRequestProcessor {
                 &program_id,
                 filters,
                 sort_results,
+                scan_budget,
             )
             .await
             .map_err(|e| RpcCustomError::ScanError {
                 message: e.to_string(),
             })
         } else {
-            self.get_filtered_program_accounts(bank, program_id, filters, sort_results)
+            self.get_filtered_program_accounts(bank, program_id, filters, sort_results, scan_budget)
                 .await
         }
     }
@@ -2309,6 +2900,7 @@ impl JsonRpcRequestProcessor {
         mint_key: Pubkey,
         mut filters: Vec<RpcFilterType>,
         sort_results: bool,
+        scan_budget: Option<Arc<ScanBudget>>,
     ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
         // The by-mint accounts index checks for Token Account state and Mint address on inclusion.
         // However, due to the current AccountsDb implementation, an account may remain in storage
@@ -2338,13 +2930,14 @@ impl JsonRpcRequestProcessor {
                 &program_id,
                 filters,
                 sort_results,
+                scan_budget,
             )
             .await
             .map_err(|e| RpcCustomError::ScanError {
                 message: e.to_string(),
             })
         } else {
-            self.get_filtered_program_accounts(bank, program_id, filters, sort_results)
+            self.get_filtered_program_accounts(bank, program_id, filters, sort_results, scan_budget)
                 .await
         }
     }
@@ -2383,6 +2976,73 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, stake_minimum_delegation))
     }
 
+    fn get_stake_activation_projection(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcStakeActivationProjectionConfig,
+    ) -> Result<RpcResponse<Vec<RpcStakeActivationProjection>>> {
+        let RpcStakeActivationProjectionConfig {
+            epochs,
+            commitment,
+            min_context_slot,
+        } = config;
+        let epochs = epochs.unwrap_or(MAX_STAKE_ACTIVATION_PROJECTION_EPOCHS);
+        if epochs == 0 || epochs > MAX_STAKE_ACTIVATION_PROJECTION_EPOCHS {
+            return Err(Error::invalid_params(format!(
+                "Invalid epochs; must be between 1 and {MAX_STAKE_ACTIVATION_PROJECTION_EPOCHS}"
+            )));
+        }
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
+
+        let stake_account = bank.get_account(pubkey).ok_or_else(|| {
+            Error::invalid_params("Invalid param: could not find account".to_string())
+        })?;
+        if stake_account.owner() != &solana_stake_interface::program::id() {
+            return Err(Error::invalid_params(
+                "Invalid param: not a stake account".to_string(),
+            ));
+        }
+        let delegation = match bincode::deserialize::<StakeStateV2>(stake_account.data()) {
+            Ok(StakeStateV2::Stake(_meta, stake, _flags)) => stake.delegation,
+            Ok(_) => {
+                return Err(Error::invalid_params(
+                    "Invalid param: stake account is not delegated".to_string(),
+                ))
+            }
+            Err(_) => {
+                return Err(Error::invalid_params(
+                    "Invalid param: not a stake account".to_string(),
+                ))
+            }
+        };
+
+        let stake_history_account = bank
+            .get_account(&stake_history::id())
+            .ok_or_else(Error::internal_error)?;
+        let stake_history: StakeHistory = bincode::deserialize(stake_history_account.data())
+            .map_err(|_| Error::internal_error())?;
+
+        let projection = project_stake_activation(
+            &delegation,
+            &stake_history,
+            bank.epoch(),
+            epochs,
+            bank.new_warmup_cooldown_rate_epoch(),
+        )
+        .into_iter()
+        .map(|(epoch, status)| RpcStakeActivationProjection {
+            epoch,
+            effective: status.effective,
+            activating: status.activating,
+            deactivating: status.deactivating,
+        })
+        .collect();
+        Ok(new_response(&bank, projection))
+    }
+
     fn get_recent_prioritization_fees(
         &self,
         pubkeys: Vec<Pubkey>,
@@ -2514,6 +3174,57 @@ fn get_encoded_account(
     }
 }
 
+/// Encodes the elements of `lt_hash` as little-endian bytes, the order the bank hash hashes them in
+fn encode_lt_hash(lt_hash: &LtHash) -> String {
+    let bytes: Vec<_> = lt_hash
+        .0
+        .iter()
+        .flat_map(|element| element.to_le_bytes())
+        .collect();
+    BASE64_STANDARD.encode(bytes)
+}
+
+/// Fails with the accounts of `response` kept so far when they were `truncated` to stay within
+/// `max_bytes`
+fn limit_accounts_response<T: Serialize>(
+    method: &str,
+    max_bytes: Option<usize>,
+    truncated: bool,
+    response: T,
+) -> Result<T> {
+    match max_bytes.filter(|_| truncated) {
+        Some(max_bytes) => Err(response_too_large(method, max_bytes, &response, None)),
+        None => Ok(response),
+    }
+}
+
+/// Encodes `keyed_accounts` for a response, as long as the list stays within `max_bytes`
+fn encode_keyed_accounts(
+    bank: &Arc<Bank>,
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    is_token_program: bool,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+    max_bytes: Option<usize>,
+) -> Result<LimitedItems<RpcKeyedAccount>> {
+    if is_token_program && encoding == UiAccountEncoding::JsonParsed {
+        collect_within_limit(
+            get_parsed_token_accounts(Arc::clone(bank), keyed_accounts.into_iter()).map(Ok),
+            max_bytes,
+        )
+    } else {
+        collect_within_limit(
+            keyed_accounts.into_iter().map(|(pubkey, account)| {
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode_account(&account, &pubkey, encoding, data_slice)?,
+                })
+            }),
+            max_bytes,
+        )
+    }
+}
+
 fn encode_account<T: ReadableAccount>(
     account: &T,
     pubkey: &Pubkey,
@@ -2727,6 +3438,9 @@ pub mod rpc_minimal {
         #[rpc(meta, name = "getHealth")]
         fn get_health(&self, meta: Self::Metadata) -> Result<String>;
 
+        #[rpc(meta, name = "getHealthDetailed")]
+        fn get_health_detailed(&self, meta: Self::Metadata) -> Result<RpcHealthDetailed>;
+
         #[rpc(meta, name = "getIdentity")]
         fn get_identity(&self, meta: Self::Metadata) -> Result<RpcIdentity>;
 
@@ -2817,6 +3531,11 @@ pub mod rpc_minimal {
             }
         }
 
+        fn get_health_detailed(&self, meta: Self::Metadata) -> Result<RpcHealthDetailed> {
+            debug!("get_health_detailed rpc request received");
+            Ok(meta.get_health_detailed())
+        }
+
         fn get_identity(&self, meta: Self::Metadata) -> Result<RpcIdentity> {
             debug!("get_identity rpc request received");
             Ok(RpcIdentity {
@@ -3170,6 +3889,22 @@ pub mod rpc_accounts {
             config: Option<RpcAccountInfoConfig>,
         ) -> BoxFuture<Result<RpcResponse<Vec<Option<UiAccount>>>>>;
 
+        #[rpc(meta, name = "getMultipleAccountsAtSlot")]
+        fn get_multiple_accounts_at_slot(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Vec<String>,
+            config: Option<RpcMultipleAccountsAtSlotConfig>,
+        ) -> BoxFuture<Result<RpcResponse<Vec<Option<UiAccount>>>>>;
+
+        #[rpc(meta, name = "getAccountWithBankHash")]
+        fn get_account_with_bank_hash(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            config: Option<RpcAccountWithBankHashConfig>,
+        ) -> BoxFuture<Result<RpcResponse<RpcAccountWithBankHash>>>;
+
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
             &self,
@@ -3245,6 +3980,49 @@ pub mod rpc_accounts {
             .boxed()
         }
 
+        fn get_multiple_accounts_at_slot(
+            &self,
+            meta: Self::Metadata,
+            pubkey_strs: Vec<String>,
+            config: Option<RpcMultipleAccountsAtSlotConfig>,
+        ) -> BoxFuture<Result<RpcResponse<Vec<Option<UiAccount>>>>> {
+            debug!(
+                "get_multiple_accounts_at_slot rpc request received: {:?}",
+                pubkey_strs.len()
+            );
+            async move {
+                let max_multiple_accounts = meta
+                    .config
+                    .max_multiple_accounts
+                    .unwrap_or(MAX_MULTIPLE_ACCOUNTS);
+                if pubkey_strs.len() > max_multiple_accounts {
+                    return Err(Error::invalid_params(format!(
+                        "Too many inputs provided; max {max_multiple_accounts}"
+                    )));
+                }
+                let pubkeys = pubkey_strs
+                    .into_iter()
+                    .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                    .collect::<Result<Vec<_>>>()?;
+                meta.get_multiple_accounts_at_slot(pubkeys, config).await
+            }
+            .boxed()
+        }
+
+        fn get_account_with_bank_hash(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            config: Option<RpcAccountWithBankHashConfig>,
+        ) -> BoxFuture<Result<RpcResponse<RpcAccountWithBankHash>>> {
+            debug!("get_account_with_bank_hash rpc request received: {pubkey_str:?}");
+            async move {
+                let pubkey = verify_pubkey(&pubkey_str)?;
+                meta.get_account_with_bank_hash(pubkey, config).await
+            }
+            .boxed()
+        }
+
         fn get_block_commitment(
             &self,
             meta: Self::Metadata,
@@ -3461,6 +4239,13 @@ pub mod rpc_full {
         #[rpc(meta, name = "getClusterNodes")]
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>>;
 
+        #[rpc(meta, name = "getClusterVersionBreakdown")]
+        fn get_cluster_version_breakdown(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcContextConfig>,
+        ) -> Result<RpcResponse<RpcClusterVersionBreakdown>>;
+
         #[rpc(meta, name = "getRecentPerformanceSamples")]
         fn get_recent_performance_samples(
             &self,
@@ -3468,6 +4253,9 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getForkStats")]
+        fn get_fork_stats(&self, meta: Self::Metadata) -> Result<Option<RpcForkStats>>;
+
         #[rpc(meta, name = "getSignatureStatuses")]
         fn get_signature_statuses(
             &self,
@@ -3507,6 +4295,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "previewTransactionPriority")]
+        fn preview_transaction_priority(
+            &self,
+            meta: Self::Metadata,
+            data: String,
+            config: Option<RpcPreviewTransactionPriorityConfig>,
+        ) -> Result<RpcResponse<RpcTransactionPriorityPreview>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3559,9 +4355,32 @@ pub mod rpc_full {
             config: Option<RpcSignaturesForAddressConfig>,
         ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
 
+        #[rpc(meta, name = "getSignaturesForAddressByTime")]
+        fn get_signatures_for_address_by_time(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressByTimeConfig>,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>>;
 
+        #[rpc(meta, name = "getDuplicateSlots")]
+        fn get_duplicate_slots(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            limit: Option<usize>,
+        ) -> Result<Vec<Slot>>;
+
+        #[rpc(meta, name = "getDuplicateSlotProof")]
+        fn get_duplicate_slot_proof(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+        ) -> Result<Option<RpcDuplicateSlotProof>>;
+
         #[rpc(meta, name = "getLatestBlockhash")]
         fn get_latest_blockhash(
             &self,
@@ -3592,6 +4411,14 @@ pub mod rpc_full {
             config: Option<RpcContextConfig>,
         ) -> Result<RpcResponse<u64>>;
 
+        #[rpc(meta, name = "getStakeActivationProjection")]
+        fn get_stake_activation_projection(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            config: Option<RpcStakeActivationProjectionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcStakeActivationProjection>>>;
+
         #[rpc(meta, name = "getRecentPrioritizationFees")]
         fn get_recent_prioritization_fees(
             &self,
@@ -3631,6 +4458,23 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_fork_stats(&self, meta: Self::Metadata) -> Result<Option<RpcForkStats>> {
+            debug!("get_fork_stats rpc request received");
+            Ok(meta
+                .blockstore
+                .fork_stats
+                .summary()
+                .map(|summary| RpcForkStats {
+                    first_slot: summary.first_slot,
+                    last_slot: summary.last_slot,
+                    num_blocks: summary.num_blocks,
+                    num_skipped_slots: summary.num_skipped_slots,
+                    num_forks: summary.num_forks,
+                    skip_rate: summary.skip_rate(),
+                    forks_per_thousand_slots: summary.forks_per_thousand_slots(),
+                }))
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -3694,6 +4538,15 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_cluster_version_breakdown(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcContextConfig>,
+        ) -> Result<RpcResponse<RpcClusterVersionBreakdown>> {
+            debug!("get_cluster_version_breakdown rpc request received");
+            meta.get_cluster_version_breakdown(config.unwrap_or_default())
+        }
+
         fn get_signature_statuses(
             &self,
             meta: Self::Metadata,
@@ -3746,6 +4599,9 @@ pub mod rpc_full {
                 &config
             );
 
+            if meta.config.read_only {
+                return Err(RpcCustomError::ReadOnlyNode.into());
+            }
             let faucet_addr = meta.config.faucet_addr.ok_or_else(Error::invalid_request)?;
             let pubkey = verify_pubkey(&pubkey_str)?;
 
@@ -3800,6 +4656,13 @@ pub mod rpc_full {
             config: Option<RpcSendTransactionConfig>,
         ) -> Result<String> {
             debug!("send_transaction rpc request received");
+            if meta.config.read_only {
+                return Err(RpcCustomError::ReadOnlyNode.into());
+            }
+            check_banking_queue_depth(
+                &meta.config.backpressure,
+                meta.prioritization_fee_cache.banking_queue_depth(),
+            )?;
             let RpcSendTransactionConfig {
                 skip_preflight,
                 preflight_commitment,
@@ -4003,9 +4866,14 @@ pub mod rpc_full {
                 None
             };
 
+            if let Some(max_compute_units) = meta.config.simulation.max_compute_units {
+                check_compute_unit_limit(&transaction, &bank.feature_set, max_compute_units)?;
+            }
+
             let simulation_result = if let Some(err) = verification_error {
                 TransactionSimulationResult::new_error(err)
             } else {
+                let _permit = meta.simulation_quota.try_acquire()?;
                 bank.simulate_transaction(&transaction, enable_cpi_recording)
             };
 
@@ -4104,6 +4972,42 @@ pub mod rpc_full {
             ))
         }
 
+        fn preview_transaction_priority(
+            &self,
+            meta: Self::Metadata,
+            data: String,
+            config: Option<RpcPreviewTransactionPriorityConfig>,
+        ) -> Result<RpcResponse<RpcTransactionPriorityPreview>> {
+            debug!("preview_transaction_priority rpc request received");
+            let RpcPreviewTransactionPriorityConfig {
+                commitment,
+                encoding,
+                min_context_slot,
+            } = config.unwrap_or_default();
+            let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+            let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+                Error::invalid_params(format!(
+                    "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+                ))
+            })?;
+            let (_, unsanitized_tx) =
+                decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
+
+            let bank = &*meta.get_bank_with_config(RpcContextConfig {
+                commitment,
+                min_context_slot,
+            })?;
+            let transaction = sanitize_transaction(
+                unsanitized_tx,
+                bank,
+                bank.get_reserved_account_keys(),
+                bank.feature_set
+                    .is_active(&agave_feature_set::static_instruction_limit::id()),
+            )?;
+            let preview = meta.preview_transaction_priority(&transaction, bank)?;
+            Ok(new_response(bank, preview))
+        }
+
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("minimum_ledger_slot rpc request received");
             meta.minimum_ledger_slot()
@@ -4202,11 +5106,67 @@ pub mod rpc_full {
             }
         }
 
+        fn get_signatures_for_address_by_time(
+            &self,
+            meta: Self::Metadata,
+            address: String,
+            config: Option<RpcSignaturesForAddressByTimeConfig>,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+            debug!("get_signatures_for_address_by_time rpc request received: {address:?}");
+            let RpcSignaturesForAddressByTimeConfig {
+                start_time,
+                end_time,
+                after,
+                limit,
+                commitment,
+                min_context_slot,
+            } = config.unwrap_or_default();
+            let (address, after, _, limit) =
+                verify_and_parse_signatures_for_address_params(address, after, None, limit)?;
+            let start_time = start_time.unwrap_or(UnixTimestamp::MIN);
+            let end_time = end_time.unwrap_or(UnixTimestamp::MAX);
+            if start_time >= end_time {
+                return Err(Error::invalid_params(
+                    "Invalid time range; startTime must be before endTime",
+                ));
+            }
+            meta.get_signatures_for_address_by_time(
+                address,
+                start_time,
+                end_time,
+                after,
+                limit,
+                RpcContextConfig {
+                    commitment,
+                    min_context_slot,
+                },
+            )
+        }
+
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>> {
             debug!("get_first_available_block rpc request received");
             Box::pin(async move { Ok(meta.get_first_available_block().await) })
         }
 
+        fn get_duplicate_slots(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            limit: Option<usize>,
+        ) -> Result<Vec<Slot>> {
+            debug!("get_duplicate_slots rpc request received: {start_slot}");
+            meta.get_duplicate_slots(start_slot, limit)
+        }
+
+        fn get_duplicate_slot_proof(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+        ) -> Result<Option<RpcDuplicateSlotProof>> {
+            debug!("get_duplicate_slot_proof rpc request received: {slot}");
+            Ok(meta.get_duplicate_slot_proof(slot))
+        }
+
         fn get_inflation_reward(
             &self,
             meta: Self::Metadata,
@@ -4286,6 +5246,17 @@ pub mod rpc_full {
             meta.get_stake_minimum_delegation(config.unwrap_or_default())
         }
 
+        fn get_stake_activation_projection(
+            &self,
+            meta: Self::Metadata,
+            pubkey_str: String,
+            config: Option<RpcStakeActivationProjectionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcStakeActivationProjection>>> {
+            debug!("get_stake_activation_projection rpc request received: {pubkey_str:?}");
+            let pubkey = verify_pubkey(&pubkey_str)?;
+            meta.get_stake_activation_projection(&pubkey, config.unwrap_or_default())
+        }
+
         fn get_recent_prioritization_fees(
             &self,
             meta: Self::Metadata,
@@ -4480,10 +5451,9 @@ pub fn populate_blockstore_for_tests(
         crate::transaction_status_service::TransactionStatusService::new(
             transaction_status_receiver,
             max_complete_transaction_status_slot,
-            true,
+            Arc::new(crate::transaction_status_service::TransactionHistorySwitch::new(true, false)),
             None,
             blockstore,
-            false,
             None,
             tss_exit.clone(),
         );
@@ -4527,7 +5497,10 @@ pub mod tests {
         jsonrpc_core_client::transports::local,
         serde::de::DeserializeOwned,
         solana_account::{state_traits::StateMut, Account, WritableAccount},
-        solana_accounts_db::accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+        solana_accounts_db::{
+            accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+            accounts_hash::AccountsLtHash,
+        },
         solana_address_lookup_table_interface::{
             self as address_lookup_table,
             state::{AddressLookupTable, LookupTableMeta},
@@ -4557,14 +5530,20 @@ pub mod tests {
         },
         solana_rpc_client_api::{
             custom_error::{
-                JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+                ResponseTooLargeErrorData, JSON_RPC_SERVER_ERROR_ADDRESS_TIME_INDEX_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE, JSON_RPC_SERVER_ERROR_NODE_CONGESTED,
+                JSON_RPC_SERVER_ERROR_READ_ONLY_NODE, JSON_RPC_SERVER_ERROR_RESPONSE_TOO_LARGE,
+                JSON_RPC_SERVER_ERROR_SIMULATION_COMPUTE_UNIT_LIMIT_EXCEEDED,
+                JSON_RPC_SERVER_ERROR_SIMULATION_QUOTA_EXCEEDED,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
             },
             filter::MemcmpEncodedBytes,
         },
         solana_runtime::{
-            bank::BankTestConfig,
+            bank::{BankHashComponents, BankTestConfig},
+            banking_queue_snapshot::{BankingQueueSnapshot, LockingTransaction, QueuedTransaction},
             commitment::{BlockCommitment, CommitmentSlots},
             non_circulating_supply::non_circulating_accounts,
         },
@@ -4575,6 +5554,10 @@ pub mod tests {
         },
         solana_sha256_hasher::hash,
         solana_signer::Signer,
+        solana_stake_interface::{
+            stake_flags::StakeFlags,
+            state::{Delegation, Meta, Stake},
+        },
         solana_svm::account_loader::TRANSACTION_ACCOUNT_BASE_SIZE,
         solana_svm_log_collector::ic_logger_msg,
         solana_system_interface::{instruction as system_instruction, program as system_program},
@@ -4601,7 +5584,11 @@ pub mod tests {
             },
             state::{AccountState as TokenAccountState, Mint},
         },
-        std::{borrow::Cow, collections::HashMap, net::Ipv4Addr},
+        std::{
+            borrow::Cow,
+            collections::HashMap,
+            net::{IpAddr, Ipv4Addr},
+        },
         test_case::test_case,
     };
 
@@ -4814,8 +5801,11 @@ pub mod tests {
                 rpc_threads,
                 rpc_blocking_threads,
                 rpc_niceness_adj,
+                enable_transaction_priority_preview,
                 ..
             } = config;
+            let banking_queue_snapshot = enable_transaction_priority_preview
+                .then(Arc::<SharedBankingQueueSnapshot>::default);
             let meta = JsonRpcRequestProcessor::new(
                 config,
                 None,
@@ -4833,6 +5823,7 @@ pub mod tests {
                 Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
                 max_complete_transaction_status_slot.clone(),
                 Arc::new(PrioritizationFeeCache::default()),
+                banking_queue_snapshot,
                 service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj),
             )
             .0;
@@ -5221,6 +6212,52 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_get_cluster_version_breakdown() {
+        let rpc = RpcHandler::start();
+        let version = solana_version::Version::default();
+        let staked_nodes = rpc.working_bank().current_epoch_staked_nodes();
+        let total_activated_stake: u64 = staked_nodes.values().sum();
+        assert_eq!(
+            staked_nodes.get(&rpc.leader_pubkey()),
+            Some(&total_activated_stake)
+        );
+
+        let request = create_test_request("getClusterVersionBreakdown", None);
+        let result: RpcResponse<RpcClusterVersionBreakdown> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result.value,
+            RpcClusterVersionBreakdown {
+                epoch: 0,
+                total_activated_stake,
+                versions: vec![RpcClusterVersionShare {
+                    version: Some(version.to_string()),
+                    feature_set: Some(version.feature_set),
+                    node_count: 2,
+                    activated_stake: total_activated_stake,
+                }],
+            }
+        );
+
+        // unstaked nodes only add to the node count
+        rpc.meta
+            .cluster_info
+            .insert_info(ContactInfo::new_localhost(
+                &Pubkey::new_unique(),
+                solana_time_utils::timestamp(),
+            ));
+        let request = create_test_request("getClusterVersionBreakdown", None);
+        let result: RpcResponse<RpcClusterVersionBreakdown> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.versions.len(), 1);
+        assert_eq!(result.value.versions[0].node_count, 3);
+        assert_eq!(
+            result.value.versions[0].activated_stake,
+            total_activated_stake
+        );
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples() {
         let rpc = RpcHandler::start();
@@ -5254,6 +6291,30 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_get_fork_stats() {
+        let rpc = RpcHandler::start();
+
+        let request = create_test_request("getForkStats", None);
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result, Value::Null);
+
+        rpc.blockstore.fork_stats.record_rooted_block(1, 0, 1);
+        rpc.blockstore.fork_stats.record_rooted_block(3, 1, 2);
+        let request = create_test_request("getForkStats", None);
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+        let expected = json!({
+            "firstSlot": 1,
+            "lastSlot": 3,
+            "numBlocks": 2,
+            "numSkippedSlots": 1,
+            "numForks": 1,
+            "skipRate": 100.0 / 3.0,
+            "forksPerThousandSlots": 1_000.0 / 3.0,
+        });
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples_invalid_limit() {
         let rpc = RpcHandler::start();
@@ -5828,6 +6889,148 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_rpc_get_account_with_bank_hash() {
+        let rpc = RpcHandler::start();
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::new(42, 8, &Pubkey::new_unique());
+        let bank0 = rpc.working_bank();
+        bank0.store_account(&pubkey, &account);
+
+        let request = |pubkey: &Pubkey, config: Value| {
+            create_test_request(
+                "getAccountWithBankHash",
+                Some(json!([pubkey.to_string(), config])),
+            )
+        };
+        let decode_lt_hash = |lt_hash: &str| {
+            let bytes = BASE64_STANDARD.decode(lt_hash).unwrap();
+            let mut elements = [0u16; LtHash::NUM_ELEMENTS];
+            for (element, bytes) in elements.iter_mut().zip(bytes.chunks_exact(2)) {
+                *element = u16::from_le_bytes([bytes[0], bytes[1]]);
+            }
+            LtHash(elements)
+        };
+
+        // Bank 0 is still being replayed
+        let (code, _) =
+            parse_failure_response(rpc.handle_request_sync(request(&pubkey, json!({}))));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE);
+        bank0.freeze();
+
+        let result: RpcResponse<RpcAccountWithBankHash> =
+            parse_success_result(rpc.handle_request_sync(request(&pubkey, json!({}))));
+        assert_eq!(result.context.slot, 0);
+        let account_with_bank_hash = result.value;
+        assert_eq!(account_with_bank_hash.account.unwrap().lamports, 42);
+        assert_eq!(account_with_bank_hash.bank_hash, bank0.hash().to_string());
+
+        // The components hash to the bank hash
+        let hash_components = BankHashComponents {
+            parent_hash: Hash::from_str(&account_with_bank_hash.parent_bank_hash).unwrap(),
+            signature_count: account_with_bank_hash.signature_count,
+            last_blockhash: Hash::from_str(&account_with_bank_hash.last_blockhash).unwrap(),
+            accounts_lt_hash: AccountsLtHash(decode_lt_hash(
+                &account_with_bank_hash.accounts_lt_hash,
+            )),
+            hard_fork_data: None,
+        };
+        assert_eq!(
+            hash_components.hash().to_string(),
+            account_with_bank_hash.bank_hash
+        );
+
+        let result: RpcResponse<RpcAccountWithBankHash> = parse_success_result(
+            rpc.handle_request_sync(request(&Pubkey::new_unique(), json!({"slot": 0}))),
+        );
+        assert_eq!(result.value.account, None);
+        assert_eq!(
+            result.value.accounts_lt_hash,
+            account_with_bank_hash.accounts_lt_hash
+        );
+
+        let (code, _) = parse_failure_response(
+            rpc.handle_request_sync(request(&pubkey, json!({"encoding": "jsonParsed"}))),
+        );
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_get_multiple_accounts_at_slot() {
+        let rpc = RpcHandler::start();
+        let pubkey = Pubkey::new_unique();
+        let non_existent_pubkey = Pubkey::new_unique();
+        let bank0 = rpc.working_bank();
+        bank0.store_account(&pubkey, &AccountSharedData::new(42, 0, &Pubkey::default()));
+        bank0.freeze();
+        let bank1 = rpc.advance_bank_to_confirmed_slot(1);
+        bank1.store_account(&pubkey, &AccountSharedData::new(43, 0, &Pubkey::default()));
+
+        let request = |config: Value| {
+            create_test_request(
+                "getMultipleAccountsAtSlot",
+                Some(json!([
+                    [pubkey.to_string(), non_existent_pubkey.to_string()],
+                    config
+                ])),
+            )
+        };
+        let lamports = |result: RpcResponse<Value>| {
+            (
+                result.context.slot,
+                result.value[0]["lamports"].as_u64().unwrap(),
+                result.value[1].clone(),
+            )
+        };
+
+        // Bank 1 is still being replayed
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request(json!({}))));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE);
+
+        let result = parse_success_result(rpc.handle_request_sync(request(json!({"slot": 0}))));
+        assert_eq!(lamports(result), (0, 42, Value::Null));
+
+        bank1.freeze();
+        let result = parse_success_result(rpc.handle_request_sync(request(json!({}))));
+        assert_eq!(lamports(result), (1, 43, Value::Null));
+        let result = parse_success_result(rpc.handle_request_sync(request(json!({"slot": 1}))));
+        assert_eq!(lamports(result), (1, 43, Value::Null));
+
+        // Slot 2 is not an ancestor of the processed bank
+        let (code, _) =
+            parse_failure_response(rpc.handle_request_sync(request(json!({"slot": 2}))));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE);
+
+        // Slot 1 is not an ancestor of the finalized bank
+        let (code, _) = parse_failure_response(
+            rpc.handle_request_sync(request(json!({"slot": 1, "commitment": "finalized"}))),
+        );
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_BANK_NOT_AVAILABLE);
+    }
+
+    #[test]
+    fn test_rpc_get_signatures_for_address_by_time() {
+        let rpc = RpcHandler::start();
+        let address = Pubkey::new_unique().to_string();
+        let request = |config: Value| {
+            create_test_request(
+                "getSignaturesForAddressByTime",
+                Some(json!([address, config])),
+            )
+        };
+
+        let (code, _) = parse_failure_response(
+            rpc.handle_request_sync(request(json!({"startTime": 10, "endTime": 10}))),
+        );
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+
+        // The blockstore of the test handler does not index addresses by time
+        let (code, _) = parse_failure_response(
+            rpc.handle_request_sync(request(json!({"startTime": 10, "endTime": 20}))),
+        );
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_ADDRESS_TIME_INDEX_NOT_AVAILABLE);
+    }
+
     #[test]
     fn test_rpc_get_program_accounts() {
         let rpc = RpcHandler::start();
@@ -5982,6 +7185,175 @@ pub mod tests {
         assert_eq!(result.len(), 0);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts_response_too_large() {
+        let mut rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+
+        let program_id = Pubkey::new_unique();
+        for _ in 0..3 {
+            bank.store_account(
+                &Pubkey::new_unique(),
+                &AccountSharedData::new(42, 100, &program_id),
+            );
+        }
+        let request = create_test_request(
+            "getProgramAccounts",
+            Some(json!([program_id.to_string(), {"encoding": "base64"}])),
+        );
+        let accounts: Vec<RpcKeyedAccount> =
+            parse_success_result(rpc.handle_request_sync(request.clone()));
+        assert_eq!(accounts.len(), 3);
+
+        // One byte short of the whole response
+        let max_bytes = serde_json::to_vec(&accounts).unwrap().len() - 1;
+        rpc.meta.config.response_limits = RpcResponseLimits {
+            default_max_response_bytes: None,
+            max_response_bytes: HashMap::from([("getProgramAccounts".to_string(), max_bytes)]),
+        };
+        let Response::Single(Output::Failure(failure)) = rpc.handle_request_sync(request) else {
+            panic!("Expected failure");
+        };
+        assert_eq!(
+            failure.error.code.code(),
+            JSON_RPC_SERVER_ERROR_RESPONSE_TOO_LARGE
+        );
+        let data: ResponseTooLargeErrorData =
+            serde_json::from_value(failure.error.data.unwrap()).unwrap();
+        assert_eq!(data.max_bytes, max_bytes);
+        let partial_result: Vec<RpcKeyedAccount> =
+            serde_json::from_value(data.partial_result).unwrap();
+        assert_eq!(partial_result, accounts[..2]);
+        assert_eq!(data.cursor, None);
+    }
+
+    #[test]
+    fn test_rpc_get_multiple_accounts_response_too_large() {
+        let mut rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+
+        let pubkeys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for pubkey in &pubkeys {
+            bank.store_account(pubkey, &AccountSharedData::new(42, 100, &Pubkey::default()));
+        }
+        let request = create_test_request(
+            "getMultipleAccounts",
+            Some(json!([pubkeys
+                .iter()
+                .map(|pubkey| pubkey.to_string())
+                .collect::<Vec<_>>()])),
+        );
+        let response: RpcResponse<Vec<Option<UiAccount>>> =
+            parse_success_result(rpc.handle_request_sync(request.clone()));
+        assert_eq!(response.value.len(), 3);
+
+        // The context counts toward the limit, one byte short of the whole response
+        let max_bytes = serde_json::to_vec(&response).unwrap().len() - 1;
+        rpc.meta.config.response_limits = RpcResponseLimits {
+            default_max_response_bytes: Some(max_bytes),
+            max_response_bytes: HashMap::new(),
+        };
+        let Response::Single(Output::Failure(failure)) = rpc.handle_request_sync(request) else {
+            panic!("Expected failure");
+        };
+        assert_eq!(
+            failure.error.code.code(),
+            JSON_RPC_SERVER_ERROR_RESPONSE_TOO_LARGE
+        );
+        let data: ResponseTooLargeErrorData =
+            serde_json::from_value(failure.error.data.unwrap()).unwrap();
+        let partial_result: RpcResponse<Vec<Option<UiAccount>>> =
+            serde_json::from_value(data.partial_result).unwrap();
+        assert_eq!(partial_result.value, response.value[..2]);
+    }
+
+    #[test]
+    fn test_rpc_preview_transaction_priority() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            enable_transaction_priority_preview: true,
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &Pubkey::new_unique(),
+            1,
+            bank.confirmed_last_blockhash(),
+        );
+        let request = create_test_request(
+            "previewTransactionPriority",
+            Some(json!([bs58::encode(serialize(&tx).unwrap()).into_string()])),
+        );
+
+        // Only served when enabled
+        let (code, _) =
+            parse_failure_response(RpcHandler::start().handle_request_sync(request.clone()));
+        assert_eq!(code, ErrorCode::MethodNotFound.code());
+
+        // Nothing is buffered
+        let result: RpcResponse<RpcTransactionPriorityPreview> =
+            parse_success_result(rpc.handle_request_sync(request.clone()));
+        let preview = result.value;
+        assert!(preview.priority > 0);
+        assert_eq!(preview.queue_size, 0);
+        assert_eq!(preview.rank, 0);
+        assert_eq!(preview.fee_percentile, 100);
+        assert_eq!(preview.estimated_inclusion_slot, bank.slot());
+        let priority = preview.priority;
+
+        // A higher priority transaction writes the payer, a lower priority one does not matter
+        let block_cost_limit = bank.read_cost_tracker().unwrap().get_block_limit();
+        let mint = rpc.mint_keypair.pubkey();
+        rpc.meta
+            .banking_queue_snapshot
+            .as_ref()
+            .unwrap()
+            .update(BankingQueueSnapshot {
+                slot: bank.slot(),
+                transactions: vec![
+                    QueuedTransaction {
+                        priority: priority + 1,
+                        cost: block_cost_limit,
+                    },
+                    QueuedTransaction {
+                        priority: priority - 1,
+                        cost: 1_000,
+                    },
+                ],
+                locking_transactions: vec![
+                    LockingTransaction {
+                        priority: priority + 1,
+                        writable_accounts: vec![mint],
+                        readonly_accounts: vec![],
+                    },
+                    LockingTransaction {
+                        priority: priority - 1,
+                        writable_accounts: vec![mint],
+                        readonly_accounts: vec![],
+                    },
+                ],
+            });
+        let result: RpcResponse<RpcTransactionPriorityPreview> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result.value,
+            RpcTransactionPriorityPreview {
+                priority,
+                cost: preview.cost,
+                queue_slot: bank.slot(),
+                queue_size: 2,
+                rank: 1,
+                fee_percentile: 50,
+                compute_units_ahead: block_cost_limit,
+                conflicting_locks: vec![RpcConflictingLock {
+                    account: mint.to_string(),
+                    transactions: 1,
+                }],
+                estimated_inclusion_slot: bank.slot() + 1,
+            }
+        );
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let rpc = RpcHandler::start();
@@ -6713,6 +8085,146 @@ pub mod tests {
         let _ = io.handle_request_sync(&req, meta);
     }
 
+    #[test]
+    fn test_rpc_simulate_transaction_limits() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            simulation: RpcSimulationConfig {
+                max_compute_units: Some(1_000),
+                max_concurrent: NonZeroUsize::new(1),
+            },
+            skip_preflight_health_check: true,
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+        let request = |tx: &Transaction| {
+            let tx_serialized_encoded = bs58::encode(serialize(tx).unwrap()).into_string();
+            create_test_request("simulateTransaction", Some(json!([tx_serialized_encoded])))
+        };
+
+        // A transfer requests the default limit of a builtin instruction, 3_000 compute units
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &Pubkey::new_unique(),
+            1234,
+            recent_blockhash,
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request(&tx)));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_SIMULATION_COMPUTE_UNIT_LIMIT_EXCEEDED
+        );
+
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(1_000),
+                system_instruction::transfer(
+                    &rpc.mint_keypair.pubkey(),
+                    &Pubkey::new_unique(),
+                    1234,
+                ),
+            ],
+            Some(&rpc.mint_keypair.pubkey()),
+            &[&rpc.mint_keypair],
+            recent_blockhash,
+        );
+        let result: RpcResponse<RpcSimulateTransactionResult> =
+            parse_success_result(rpc.handle_request_sync(request(&tx)));
+        assert_eq!(result.value.err, None);
+
+        // Another simulation is already running
+        let _permit = rpc.meta.simulation_quota.try_acquire().unwrap();
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request(&tx)));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_SIMULATION_QUOTA_EXCEEDED);
+
+        // The preflight checks of sendTransaction are not limited
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+        let request = create_test_request("sendTransaction", Some(json!([tx_serialized_encoded])));
+        let signature: String = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(signature, tx.signatures[0].to_string());
+    }
+
+    #[test]
+    fn test_rpc_read_only() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            faucet_addr: Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 9900)),
+            read_only: true,
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &Pubkey::new_unique(),
+            1234,
+            recent_blockhash,
+        );
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+        let request = create_test_request("sendTransaction", Some(json!([tx_serialized_encoded])));
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_READ_ONLY_NODE);
+
+        let request = create_test_request(
+            "requestAirdrop",
+            Some(json!([Pubkey::new_unique().to_string(), 1234])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_READ_ONLY_NODE);
+
+        // Reads are still served
+        let request = create_test_request(
+            "getBalance",
+            Some(json!([rpc.mint_keypair.pubkey().to_string()])),
+        );
+        let result: RpcResponse<u64> = parse_success_result(rpc.handle_request_sync(request));
+        assert!(result.value > 0);
+    }
+
+    #[test]
+    fn test_rpc_send_transaction_congested() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            backpressure: RpcBackpressureConfig {
+                max_banking_queue_depth: NonZeroUsize::new(10),
+                retry_after: Duration::from_millis(800),
+            },
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &Pubkey::new_unique(),
+            1234,
+            recent_blockhash,
+        );
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+        let request = create_test_request(
+            "sendTransaction",
+            Some(json!([tx_serialized_encoded, {"skipPreflight": true}])),
+        );
+
+        rpc.meta
+            .prioritization_fee_cache
+            .update_banking_queue_depth(10);
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request.clone()));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_NODE_CONGESTED);
+        assert_eq!(
+            message,
+            "Node is congested with 10 transactions queued, retry after 800ms"
+        );
+
+        rpc.meta
+            .prioritization_fee_cache
+            .update_banking_queue_depth(9);
+        let result: String = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result, tx.signatures[0].to_string());
+    }
+
     #[test]
     fn test_rpc_get_signature_statuses() {
         let rpc = RpcHandler::start();
@@ -6887,6 +8399,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             runtime.clone(),
         );
 
@@ -7214,6 +8727,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             runtime,
         );
 
@@ -7644,6 +9158,104 @@ pub mod tests {
         assert_eq!(result, Vec::<Slot>::new());
     }
 
+    #[test]
+    fn test_get_duplicate_slots() {
+        let rpc = RpcHandler::start();
+        for slot in [2, 5, 9] {
+            rpc.blockstore
+                .store_duplicate_slot(slot, vec![slot as u8; 8], vec![slot as u8 + 1; 8])
+                .unwrap();
+        }
+
+        let request = create_test_request("getDuplicateSlots", Some(json!([0u64])));
+        let result: Vec<Slot> = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result, vec![2, 5, 9]);
+
+        let request = create_test_request("getDuplicateSlots", Some(json!([3u64, 1])));
+        let result: Vec<Slot> = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result, vec![5]);
+
+        let request = create_test_request(
+            "getDuplicateSlots",
+            Some(json!([0u64, MAX_GET_DUPLICATE_SLOTS_LIMIT + 1])),
+        );
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        let expected = (
+            ErrorCode::InvalidParams.code(),
+            String::from("Invalid limit; max 1000"),
+        );
+        assert_eq!(response, expected);
+
+        let request = create_test_request("getDuplicateSlotProof", Some(json!([5u64])));
+        let result: Option<RpcDuplicateSlotProof> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result,
+            Some(RpcDuplicateSlotProof {
+                slot: 5,
+                shred1: BASE64_STANDARD.encode([5; 8]),
+                shred2: BASE64_STANDARD.encode([6; 8]),
+            })
+        );
+
+        let request = create_test_request("getDuplicateSlotProof", Some(json!([6u64])));
+        let result: Option<RpcDuplicateSlotProof> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_get_health_detailed() {
+        let mut rpc = RpcHandler::start();
+        rpc.meta
+            .health
+            .stub_set_health_status(Some(RpcHealthStatus::Ok));
+
+        let request = create_test_request("getHealthDetailed", None);
+        let result: RpcHealthDetailed = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result,
+            RpcHealthDetailed {
+                healthy: true,
+                causes: vec![],
+            }
+        );
+
+        // Generating snapshots without any full snapshot archive
+        rpc.meta.snapshot_config = Some(SnapshotConfig {
+            full_snapshot_archives_dir: rpc.blockstore.ledger_path().to_path_buf(),
+            incremental_snapshot_archives_dir: rpc.blockstore.ledger_path().to_path_buf(),
+            ..SnapshotConfig::default()
+        });
+        rpc.meta
+            .health
+            .stub_set_health_status(Some(RpcHealthStatus::Behind { num_slots: 50 }));
+        let request = create_test_request("getHealthDetailed", None);
+        let result: RpcHealthDetailed = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            result,
+            RpcHealthDetailed {
+                healthy: false,
+                causes: vec![
+                    RpcUnhealthyCause::Behind {
+                        num_slots: 50,
+                        max_slot_distance: 42,
+                    },
+                    RpcUnhealthyCause::SnapshotMissing,
+                ],
+            }
+        );
+
+        // Not generating snapshots, so a missing archive doesn't matter
+        rpc.meta.snapshot_config = Some(SnapshotConfig::new_load_only());
+        rpc.meta
+            .health
+            .stub_set_health_status(Some(RpcHealthStatus::Unknown));
+        let request = create_test_request("getHealthDetailed", None);
+        let result: RpcHealthDetailed = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.causes, vec![RpcUnhealthyCause::Unknown]);
+    }
+
     #[test]
     fn test_get_block_time() {
         let rpc = RpcHandler::start();
@@ -8966,6 +10578,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             max_complete_transaction_status_slot,
             Arc::new(PrioritizationFeeCache::default()),
+            None,
             service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj),
         );
 
@@ -9257,6 +10870,84 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_rpc_get_stake_activation_projection() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let stake_amount = 1_000_000_000;
+        let stake_state = StakeStateV2::Stake(
+            Meta::default(),
+            Stake {
+                delegation: Delegation::new(&Pubkey::new_unique(), stake_amount, bank.epoch()),
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        );
+        let mut stake_account = AccountSharedData::new(
+            stake_amount,
+            StakeStateV2::size_of(),
+            &solana_stake_interface::program::id(),
+        );
+        stake_account.set_data_from_slice(&serialize(&stake_state).unwrap());
+        let stake_pubkey = Pubkey::new_unique();
+        bank.store_account(&stake_pubkey, &stake_account);
+
+        // Stake delegated in the first epoch has no history to warm up against, so it is fully
+        // effective in the next epoch
+        let request = create_test_request(
+            "getStakeActivationProjection",
+            Some(json!([stake_pubkey.to_string()])),
+        );
+        let response: RpcResponse<Vec<RpcStakeActivationProjection>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            response.value,
+            vec![
+                RpcStakeActivationProjection {
+                    epoch: bank.epoch(),
+                    effective: 0,
+                    activating: stake_amount,
+                    deactivating: 0,
+                },
+                RpcStakeActivationProjection {
+                    epoch: bank.epoch() + 1,
+                    effective: stake_amount,
+                    activating: 0,
+                    deactivating: 0,
+                },
+            ]
+        );
+
+        let request = create_test_request(
+            "getStakeActivationProjection",
+            Some(json!([stake_pubkey.to_string(), {"epochs": 1}])),
+        );
+        let response: RpcResponse<Vec<RpcStakeActivationProjection>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(response.value.len(), 1);
+
+        let request = create_test_request(
+            "getStakeActivationProjection",
+            Some(json!([stake_pubkey.to_string(), {"epochs": 0}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+
+        // An account that isn't owned by the stake program
+        let other_pubkey = Pubkey::new_unique();
+        bank.store_account(
+            &other_pubkey,
+            &AccountSharedData::new(42, 0, &Pubkey::default()),
+        );
+        let request = create_test_request(
+            "getStakeActivationProjection",
+            Some(json!([other_pubkey.to_string()])),
+        );
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        assert_eq!(message, "Invalid param: not a stake account");
+    }
+
     #[test]
     fn test_get_fee_for_message() {
         let rpc = RpcHandler::start();
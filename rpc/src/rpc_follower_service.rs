@@ -0,0 +1,446 @@
+//! The `rpc_follower_service` module serves JSON RPC from a bank loaded from the latest local
+//! snapshot archives, without joining gossip or replaying. The node answers at the slot of the
+//! snapshot and, if enabled, moves to newer snapshot archives as they appear, which is useful for
+//! analytics, inspecting the state of a halted cluster and air-gapped hosts.
+
+use {
+    crate::{
+        max_slots::MaxSlots,
+        optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        rpc::{create_validator_exit, JsonRpcConfig},
+        rpc_service::JsonRpcService,
+    },
+    agave_snapshots::{
+        paths as snapshot_paths, snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_config::SnapshotConfig,
+    },
+    solana_accounts_db::{
+        accounts_db::AccountsDbConfig, utils::create_accounts_run_and_snapshot_dirs,
+    },
+    solana_client::connection_cache::ConnectionCache,
+    solana_clock::Slot,
+    solana_genesis_config::GenesisConfig,
+    solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
+    solana_keypair::Keypair,
+    solana_ledger::{blockstore::Blockstore, leader_schedule_cache::LeaderScheduleCache},
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache,
+        prioritization_fee_cache::PrioritizationFeeCache, runtime_config::RuntimeConfig,
+        snapshot_bank_utils,
+    },
+    solana_send_transaction_service::send_transaction_service,
+    solana_signer::Signer,
+    solana_streamer::socket::SocketAddrSpace,
+    solana_time_utils::timestamp,
+    std::{
+        fs,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct RpcFollowerConfig {
+    pub rpc_addr: SocketAddr,
+    pub rpc_config: JsonRpcConfig,
+    /// Where the snapshot archives are read from
+    pub snapshot_config: SnapshotConfig,
+    pub ledger_path: PathBuf,
+    /// Directory owned by the follower, where the snapshot archives are unpacked. Its content is
+    /// removed at startup.
+    pub working_dir: PathBuf,
+    pub accounts_db_config: AccountsDbConfig,
+    /// How often to check for newer snapshot archives, never if `None`
+    pub refresh_interval: Option<Duration>,
+}
+
+pub struct RpcFollowerService {
+    json_rpc_service: JsonRpcService,
+    bank_forks: Arc<RwLock<BankForks>>,
+    refresh_thread_hdl: Option<JoinHandle<()>>,
+    exit: Arc<AtomicBool>,
+}
+
+impl RpcFollowerService {
+    pub fn new(
+        config: RpcFollowerConfig,
+        genesis_config: GenesisConfig,
+        blockstore: Arc<Blockstore>,
+        exit: Arc<AtomicBool>,
+    ) -> Result<Self, String> {
+        let RpcFollowerConfig {
+            rpc_addr,
+            mut rpc_config,
+            snapshot_config,
+            ledger_path,
+            working_dir,
+            accounts_db_config,
+            refresh_interval,
+        } = config;
+        rpc_config.read_only = true;
+
+        if working_dir.exists() {
+            fs::remove_dir_all(&working_dir).map_err(|err| {
+                format!(
+                    "failed to clean up working directory {}: {err}",
+                    working_dir.display()
+                )
+            })?;
+        }
+        let loader = SnapshotLoader {
+            snapshot_config: snapshot_config.clone(),
+            working_dir,
+            genesis_config,
+            accounts_db_config,
+            exit: exit.clone(),
+        };
+        let (bank, accounts_dir) = loader
+            .load_latest(None)?
+            .ok_or_else(|| "no snapshot archive found".to_string())?;
+        info!("rpc follower serving slot {}", bank.slot());
+
+        let slot = bank.slot();
+        let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let mut block_commitment_cache = BlockCommitmentCache::default();
+        block_commitment_cache.set_all_slots(slot, slot);
+        let block_commitment_cache = Arc::new(RwLock::new(block_commitment_cache));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
+
+        // Gossip is never started, the contact info only gives the RPC service the addresses it
+        // expects. Transactions are rejected before they would be sent to the TPU address.
+        let identity = Arc::new(Keypair::new());
+        let mut contact_info = ContactInfo::new(identity.pubkey(), timestamp(), 0);
+        contact_info
+            .set_rpc(rpc_addr)
+            .and_then(|()| contact_info.set_tpu(rpc_addr))
+            .map_err(|err| format!("invalid RPC address {rpc_addr}: {err}"))?;
+        let cluster_info = Arc::new(ClusterInfo::new(
+            contact_info,
+            identity,
+            SocketAddrSpace::Unspecified,
+        ));
+
+        let json_rpc_service = JsonRpcService::new(
+            rpc_addr,
+            rpc_config,
+            Some(snapshot_config),
+            bank_forks.clone(),
+            block_commitment_cache.clone(),
+            blockstore,
+            cluster_info,
+            None,
+            loader.genesis_config.hash(),
+            &ledger_path,
+            create_validator_exit(exit.clone()),
+            exit.clone(),
+            // The follower does not keep up with the cluster, so it is never reported behind
+            Arc::new(AtomicBool::new(true)),
+            optimistically_confirmed_bank.clone(),
+            send_transaction_service::Config::default(),
+            Arc::new(MaxSlots::default()),
+            leader_schedule_cache.clone(),
+            Arc::new(ConnectionCache::with_udp(
+                "connection_cache_rpc_follower",
+                1,
+            )),
+            max_complete_transaction_status_slot,
+            Arc::new(PrioritizationFeeCache::default()),
+            // The follower has no banking stage
+            None,
+        )?;
+
+        let refresh_thread_hdl = refresh_interval.map(|refresh_interval| {
+            let follower = Follower {
+                loader,
+                bank_forks: bank_forks.clone(),
+                block_commitment_cache,
+                optimistically_confirmed_bank,
+                leader_schedule_cache,
+                accounts_dir,
+            };
+            Builder::new()
+                .name("solRpcFollower".to_string())
+                .spawn(move || follower.run(refresh_interval))
+                .unwrap()
+        });
+
+        Ok(Self {
+            json_rpc_service,
+            bank_forks,
+            refresh_thread_hdl,
+            exit,
+        })
+    }
+
+    /// Slot of the snapshot being served
+    pub fn slot(&self) -> Slot {
+        self.bank_forks.read().unwrap().root()
+    }
+
+    /// Serves until `exit` is set
+    pub fn join(self) -> thread::Result<()> {
+        while !self.exit.load(Ordering::Relaxed) {
+            sleep(EXIT_CHECK_INTERVAL);
+        }
+        if let Some(refresh_thread_hdl) = self.refresh_thread_hdl {
+            refresh_thread_hdl.join()?;
+        }
+        self.json_rpc_service.join()
+    }
+}
+
+struct SnapshotLoader {
+    snapshot_config: SnapshotConfig,
+    working_dir: PathBuf,
+    genesis_config: GenesisConfig,
+    accounts_db_config: AccountsDbConfig,
+    exit: Arc<AtomicBool>,
+}
+
+impl SnapshotLoader {
+    /// Loads the bank of the latest snapshot archives if they are newer than `slot`, and returns
+    /// it with the directory its accounts were unpacked to
+    fn load_latest(&self, slot: Option<Slot>) -> Result<Option<(Bank, PathBuf)>, String> {
+        let Some(full_snapshot_archive_info) =
+            snapshot_paths::get_highest_full_snapshot_archive_info(
+                &self.snapshot_config.full_snapshot_archives_dir,
+            )
+        else {
+            return Ok(None);
+        };
+        let incremental_snapshot_archive_info =
+            snapshot_paths::get_highest_incremental_snapshot_archive_info(
+                &self.snapshot_config.incremental_snapshot_archives_dir,
+                full_snapshot_archive_info.slot(),
+            );
+        let snapshot_slot = incremental_snapshot_archive_info
+            .as_ref()
+            .map(|info| info.slot())
+            .unwrap_or(full_snapshot_archive_info.slot());
+        if slot.is_some_and(|slot| snapshot_slot <= slot) {
+            return Ok(None);
+        }
+
+        // Each bank gets its own directories, so that the bank being served keeps its accounts
+        // while the next one is loaded
+        let accounts_dir = self.working_dir.join(format!("accounts-{snapshot_slot}"));
+        let bank_snapshots_dir = self.working_dir.join("bank-snapshots");
+        let (account_run_dir, _) = create_accounts_run_and_snapshot_dirs(&accounts_dir)
+            .and_then(|dirs| fs::create_dir_all(&bank_snapshots_dir).map(|()| dirs))
+            .map_err(|err| format!("failed to create accounts directories: {err}"))?;
+        let start = Instant::now();
+        let bank = snapshot_bank_utils::bank_from_snapshot_archives(
+            &[account_run_dir],
+            &bank_snapshots_dir,
+            &full_snapshot_archive_info,
+            incremental_snapshot_archive_info.as_ref(),
+            &self.genesis_config,
+            &RuntimeConfig::default(),
+            None,
+            None,
+            false,
+            false,
+            false,
+            self.accounts_db_config.clone(),
+            None,
+            self.exit.clone(),
+        )
+        .map_err(|err| {
+            remove_accounts_dir(&accounts_dir);
+            format!("failed to load snapshot of slot {snapshot_slot}: {err}")
+        })?;
+        info!(
+            "rpc follower loaded the snapshot of slot {} in {:?}",
+            bank.slot(),
+            start.elapsed()
+        );
+        Ok(Some((bank, accounts_dir)))
+    }
+}
+
+struct Follower {
+    loader: SnapshotLoader,
+    bank_forks: Arc<RwLock<BankForks>>,
+    block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
+    optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
+    leader_schedule_cache: Arc<LeaderScheduleCache>,
+    /// Where the accounts of the bank being served were unpacked
+    accounts_dir: PathBuf,
+}
+
+impl Follower {
+    fn run(mut self, refresh_interval: Duration) {
+        let mut last_refresh = Instant::now();
+        while !self.loader.exit.load(Ordering::Relaxed) {
+            sleep(EXIT_CHECK_INTERVAL);
+            if last_refresh.elapsed() < refresh_interval {
+                continue;
+            }
+            if let Err(err) = self.refresh() {
+                warn!("rpc follower failed to refresh: {err}");
+            }
+            last_refresh = Instant::now();
+        }
+        remove_accounts_dir(&self.accounts_dir);
+    }
+
+    /// Serves the latest snapshot archives if they are newer than the bank being served, and
+    /// returns the slot being served
+    fn refresh(&mut self) -> Result<Slot, String> {
+        let slot = self.bank_forks.read().unwrap().root();
+        let Some((bank, accounts_dir)) = self.loader.load_latest(Some(slot))? else {
+            return Ok(slot);
+        };
+        let bank = BankForks::reset_root_bank(&self.bank_forks, bank);
+        self.block_commitment_cache
+            .write()
+            .unwrap()
+            .set_all_slots(bank.slot(), bank.slot());
+        self.optimistically_confirmed_bank.write().unwrap().bank = bank.clone();
+        self.leader_schedule_cache.set_root(&bank);
+        info!("rpc follower moved from slot {slot} to {}", bank.slot());
+
+        // Requests still holding the previous bank read from files already opened, which stay
+        // readable once removed
+        remove_accounts_dir(&std::mem::replace(&mut self.accounts_dir, accounts_dir));
+        Ok(bank.slot())
+    }
+}
+
+fn remove_accounts_dir(accounts_dir: &Path) {
+    if let Err(err) = fs::remove_dir_all(accounts_dir) {
+        warn!(
+            "failed to remove accounts directory {}: {err}",
+            accounts_dir.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        agave_snapshots::ArchiveFormat,
+        solana_accounts_db::accounts_db::ACCOUNTS_DB_CONFIG_FOR_TESTING,
+        solana_ledger::get_tmp_ledger_path_auto_delete,
+        solana_net_utils::sockets::localhost_port_range_for_tests,
+        solana_pubkey::Pubkey,
+        solana_runtime::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        std::net::{IpAddr, Ipv4Addr},
+    };
+
+    fn archive_bank(bank: &Bank, snapshot_config: &SnapshotConfig) {
+        snapshot_bank_utils::bank_to_full_snapshot_archive(
+            &snapshot_config.bank_snapshots_dir,
+            bank,
+            None,
+            &snapshot_config.full_snapshot_archives_dir,
+            &snapshot_config.incremental_snapshot_archives_dir,
+            ArchiveFormat::Tar,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rpc_follower_refresh() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let snapshots_dir = ledger_path.path().join("snapshots");
+        let snapshot_config = SnapshotConfig {
+            bank_snapshots_dir: snapshots_dir.join("bank"),
+            full_snapshot_archives_dir: snapshots_dir.clone(),
+            incremental_snapshot_archives_dir: snapshots_dir.clone(),
+            ..SnapshotConfig::new_load_only()
+        };
+        fs::create_dir_all(&snapshot_config.bank_snapshots_dir).unwrap();
+
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        bank0.fill_bank_with_ticks_for_tests();
+        archive_bank(&bank0, &snapshot_config);
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let rpc_addr = SocketAddr::new(
+            ip_addr,
+            solana_net_utils::find_available_port_in_range(
+                ip_addr,
+                localhost_port_range_for_tests(),
+            )
+            .unwrap(),
+        );
+        let working_dir = ledger_path.path().join("rpc-follower");
+        let service = RpcFollowerService::new(
+            RpcFollowerConfig {
+                rpc_addr,
+                rpc_config: JsonRpcConfig::default_for_test(),
+                snapshot_config: snapshot_config.clone(),
+                ledger_path: ledger_path.path().to_path_buf(),
+                working_dir: working_dir.clone(),
+                accounts_db_config: ACCOUNTS_DB_CONFIG_FOR_TESTING,
+                refresh_interval: None,
+            },
+            genesis_config.clone(),
+            Arc::new(Blockstore::open(ledger_path.path()).unwrap()),
+            exit.clone(),
+        )
+        .unwrap();
+        assert_eq!(service.slot(), 0);
+
+        let pubkey = Pubkey::new_unique();
+        let bank3 = Bank::new_from_parent(bank0, &Pubkey::default(), 3);
+        bank3.transfer(1_000, &mint_keypair, &pubkey).unwrap();
+        bank3.fill_bank_with_ticks_for_tests();
+        archive_bank(&bank3, &snapshot_config);
+
+        let mut follower = Follower {
+            loader: SnapshotLoader {
+                snapshot_config,
+                working_dir: working_dir.clone(),
+                genesis_config: genesis_config.clone(),
+                accounts_db_config: ACCOUNTS_DB_CONFIG_FOR_TESTING,
+                exit: exit.clone(),
+            },
+            bank_forks: service.bank_forks.clone(),
+            block_commitment_cache: Arc::new(RwLock::new(BlockCommitmentCache::default())),
+            optimistically_confirmed_bank: OptimisticallyConfirmedBank::locked_from_bank_forks_root(
+                &service.bank_forks,
+            ),
+            leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(&bank3)),
+            accounts_dir: working_dir.join("accounts-0"),
+        };
+        assert_eq!(follower.refresh(), Ok(3));
+        assert_eq!(service.slot(), 3);
+        assert_eq!(
+            service
+                .bank_forks
+                .read()
+                .unwrap()
+                .root_bank()
+                .get_balance(&pubkey),
+            1_000
+        );
+        assert!(!working_dir.join("accounts-0").exists());
+        assert!(working_dir.join("accounts-3").exists());
+
+        // Nothing newer to load
+        assert_eq!(follower.refresh(), Ok(3));
+        assert!(working_dir.join("accounts-3").exists());
+
+        exit.store(true, Ordering::Relaxed);
+        service.join().unwrap();
+    }
+}
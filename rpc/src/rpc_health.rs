@@ -1,7 +1,9 @@
 use {
     crate::optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-    solana_clock::Slot,
+    solana_clock::{Slot, UnixTimestamp},
     solana_ledger::blockstore::Blockstore,
+    solana_rpc_client_api::response::RpcUnhealthyCause,
+    solana_time_utils::timestamp,
     std::sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
@@ -15,10 +17,19 @@ pub enum RpcHealthStatus {
     Unknown,
 }
 
+/// The latest optimistically confirmed slots as seen by this node and by the cluster
+struct OptimisticSlots {
+    my_latest: Slot,
+    cluster_latest: Slot,
+    /// When the cluster's latest optimistically confirmed slot was recorded, in milliseconds
+    cluster_latest_timestamp: UnixTimestamp,
+}
+
 pub struct RpcHealth {
     optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     blockstore: Arc<Blockstore>,
     health_check_slot_distance: u64,
+    health_check_max_vote_age_secs: Option<u64>,
     override_health_check: Arc<AtomicBool>,
     #[cfg(test)]
     stub_health_status: std::sync::RwLock<Option<RpcHealthStatus>>,
@@ -29,12 +40,14 @@ impl RpcHealth {
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
         blockstore: Arc<Blockstore>,
         health_check_slot_distance: u64,
+        health_check_max_vote_age_secs: Option<u64>,
         override_health_check: Arc<AtomicBool>,
     ) -> Self {
         Self {
             optimistically_confirmed_bank,
             blockstore,
             health_check_slot_distance,
+            health_check_max_vote_age_secs,
             override_health_check,
             #[cfg(test)]
             stub_health_status: std::sync::RwLock::new(None),
@@ -53,6 +66,88 @@ impl RpcHealth {
             return RpcHealthStatus::Ok;
         }
 
+        let Some(OptimisticSlots {
+            my_latest: my_latest_optimistically_confirmed_slot,
+            cluster_latest: cluster_latest_optimistically_confirmed_slot,
+            ..
+        }) = self.optimistic_slots()
+        else {
+            return RpcHealthStatus::Unknown;
+        };
+
+        if my_latest_optimistically_confirmed_slot
+            >= cluster_latest_optimistically_confirmed_slot
+                .saturating_sub(self.health_check_slot_distance)
+        {
+            RpcHealthStatus::Ok
+        } else {
+            let num_slots = cluster_latest_optimistically_confirmed_slot
+                .saturating_sub(my_latest_optimistically_confirmed_slot);
+            warn!(
+                "health check: behind by {num_slots} slots: \
+                 me={my_latest_optimistically_confirmed_slot}, latest \
+                 cluster={cluster_latest_optimistically_confirmed_slot}",
+            );
+            RpcHealthStatus::Behind { num_slots }
+        }
+    }
+
+    /// Like [`Self::check`], but returns every reason the node is unhealthy
+    ///
+    /// An empty result means the node is healthy. In addition to the slot distance checked by
+    /// [`Self::check`], this reports a node that has not seen a slot optimistically confirmed
+    /// within the configured maximum vote age.
+    pub fn check_detailed(&self) -> Vec<RpcUnhealthyCause> {
+        #[cfg(test)]
+        {
+            if let Some(stub_health_status) = *self.stub_health_status.read().unwrap() {
+                return match stub_health_status {
+                    RpcHealthStatus::Ok => vec![],
+                    RpcHealthStatus::Behind { num_slots } => vec![RpcUnhealthyCause::Behind {
+                        num_slots,
+                        max_slot_distance: self.health_check_slot_distance,
+                    }],
+                    RpcHealthStatus::Unknown => vec![RpcUnhealthyCause::Unknown],
+                };
+            }
+        }
+
+        if self.override_health_check.load(Ordering::Relaxed) {
+            return vec![];
+        }
+
+        let Some(OptimisticSlots {
+            my_latest,
+            cluster_latest,
+            cluster_latest_timestamp,
+        }) = self.optimistic_slots()
+        else {
+            return vec![RpcUnhealthyCause::Unknown];
+        };
+
+        let mut causes = vec![];
+        let num_slots = cluster_latest.saturating_sub(my_latest);
+        if num_slots > self.health_check_slot_distance {
+            causes.push(RpcUnhealthyCause::Behind {
+                num_slots,
+                max_slot_distance: self.health_check_slot_distance,
+            });
+        }
+        if let Some(max_vote_age_secs) = self.health_check_max_vote_age_secs {
+            let secs_since_last_vote = timestamp()
+                .saturating_sub(u64::try_from(cluster_latest_timestamp).unwrap_or_default())
+                / 1000;
+            if secs_since_last_vote > max_vote_age_secs {
+                causes.push(RpcUnhealthyCause::NoRecentVotes {
+                    secs_since_last_vote,
+                    max_vote_age_secs,
+                });
+            }
+        }
+        causes
+    }
+
+    fn optimistic_slots(&self) -> Option<OptimisticSlots> {
         // A node can observe votes by both replaying blocks and observing gossip.
         //
         // ClusterInfoVoteListener receives votes from both of these sources and then records
@@ -68,7 +163,7 @@ impl RpcHealth {
         // So, comparing the latest optimistic slot from the Blockstore vs. the slot from the
         // OptimisticallyConfirmedBank bank allows a node to see where it stands in relation to the
         // tip of the cluster.
-        let my_latest_optimistically_confirmed_slot = self
+        let my_latest = self
             .optimistically_confirmed_bank
             .read()
             .unwrap()
@@ -79,31 +174,20 @@ impl RpcHealth {
             Ok(infos) => infos,
             Err(err) => {
                 warn!("health check: blockstore error: {err}");
-                return RpcHealthStatus::Unknown;
+                return None;
             }
         };
-        let Some((cluster_latest_optimistically_confirmed_slot, _, _)) =
-            optimistic_slot_infos.pop()
+        let Some((cluster_latest, _, cluster_latest_timestamp)) = optimistic_slot_infos.pop()
         else {
             warn!("health check: blockstore does not contain any optimistically confirmed slots");
-            return RpcHealthStatus::Unknown;
+            return None;
         };
 
-        if my_latest_optimistically_confirmed_slot
-            >= cluster_latest_optimistically_confirmed_slot
-                .saturating_sub(self.health_check_slot_distance)
-        {
-            RpcHealthStatus::Ok
-        } else {
-            let num_slots = cluster_latest_optimistically_confirmed_slot
-                .saturating_sub(my_latest_optimistically_confirmed_slot);
-            warn!(
-                "health check: behind by {num_slots} slots: \
-                 me={my_latest_optimistically_confirmed_slot}, latest \
-                 cluster={cluster_latest_optimistically_confirmed_slot}",
-            );
-            RpcHealthStatus::Behind { num_slots }
-        }
+        Some(OptimisticSlots {
+            my_latest,
+            cluster_latest,
+            cluster_latest_timestamp,
+        })
     }
 
     #[cfg(test)]
@@ -115,6 +199,7 @@ impl RpcHealth {
             optimistically_confirmed_bank,
             blockstore,
             42,
+            None,
             Arc::new(AtomicBool::new(false)),
         ))
     }
@@ -129,7 +214,6 @@ impl RpcHealth {
 pub mod tests {
     use {
         super::*,
-        solana_clock::UnixTimestamp,
         solana_hash::Hash,
         solana_ledger::{
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
@@ -157,6 +241,7 @@ pub mod tests {
             optimistically_confirmed_bank.clone(),
             blockstore.clone(),
             health_check_slot_distance,
+            None,
             override_health_check.clone(),
         );
 
@@ -200,4 +285,56 @@ pub mod tests {
         optimistically_confirmed_bank.write().unwrap().bank = bank16.clone();
         assert_eq!(health.check(), RpcHealthStatus::Ok);
     }
+
+    #[test]
+    fn test_check_detailed() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+
+        let health = RpcHealth::new(
+            optimistically_confirmed_bank.clone(),
+            blockstore.clone(),
+            10,
+            Some(60),
+            Arc::new(AtomicBool::new(false)),
+        );
+        assert_eq!(health.check_detailed(), vec![RpcUnhealthyCause::Unknown]);
+
+        // Recently confirmed by the cluster, but not yet replayed by this node
+        blockstore
+            .insert_optimistic_slot(15, &Hash::default(), timestamp() as UnixTimestamp)
+            .unwrap();
+        assert_eq!(
+            health.check_detailed(),
+            vec![RpcUnhealthyCause::Behind {
+                num_slots: 15,
+                max_slot_distance: 10,
+            }]
+        );
+
+        // Caught up, but the cluster's latest optimistically confirmed slot is two minutes old
+        let bank0 = bank_forks.read().unwrap().root_bank();
+        let bank16 = Arc::new(Bank::new_from_parent(bank0, &Pubkey::default(), 16));
+        optimistically_confirmed_bank.write().unwrap().bank = bank16;
+        blockstore
+            .insert_optimistic_slot(
+                16,
+                &Hash::default(),
+                (timestamp() - 120_000) as UnixTimestamp,
+            )
+            .unwrap();
+        let causes = health.check_detailed();
+        assert!(matches!(
+            causes.as_slice(),
+            [RpcUnhealthyCause::NoRecentVotes {
+                secs_since_last_vote: 119..=121,
+                max_vote_age_secs: 60,
+            }]
+        ));
+    }
 }
@@ -0,0 +1,211 @@
+//! The HTTP server of the JSON RPC service
+//!
+//! The server of `jsonrpc_http_server` does not tell the request middleware or the meta extractor
+//! which peer a request was received from. This one serves requests with the same handler, and
+//! adds the address of the peer to the extensions of every request as a [`PeerAddr`].
+
+use {
+    crossbeam_channel::{bounded, Receiver},
+    jsonrpc_core::{Metadata, Middleware},
+    jsonrpc_http_server::{
+        hyper::{
+            self,
+            server::conn::{AddrIncoming, AddrStream},
+            service::{make_service_fn, service_fn, Service},
+        },
+        AccessControlAllowHeaders, AccessControlAllowOrigin, RequestMiddleware, RestApi, Rpc,
+        ServerHandler,
+    },
+    std::{
+        convert::Infallible,
+        future, io,
+        net::{IpAddr, SocketAddr},
+        sync::{Arc, Mutex},
+    },
+    tokio::{net::TcpSocket, runtime::Handle, sync::oneshot},
+};
+
+const LISTEN_BACKLOG: u32 = 1024;
+const CORS_MAX_AGE_SECS: u32 = 86400;
+
+/// Address of the peer a request was received from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PeerAddr(pub(crate) SocketAddr);
+
+/// Returns the address of the client that sent `request`
+///
+/// That is the address of the peer, unless the peer is one of the `trusted_proxies`. Each proxy
+/// appends the address it received the request from to the `X-Forwarded-For` header, so the
+/// header is read from the right for as long as the addresses are of trusted proxies. Addresses
+/// further to the left may have been made up by the client and are never used.
+pub(crate) fn client_ip(
+    request: &hyper::Request<hyper::Body>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut client_ip = request.extensions().get::<PeerAddr>()?.0.ip();
+    let mut forwarded = request
+        .headers()
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .rev();
+    while trusted_proxies.contains(&client_ip) {
+        match forwarded.next().and_then(|ip| ip.trim().parse().ok()) {
+            Some(ip) => client_ip = ip,
+            None => break,
+        }
+    }
+    Some(client_ip)
+}
+
+/// Stops a [`Server`] from accepting connections, leaving the requests in flight to finish
+#[derive(Clone)]
+pub(crate) struct CloseHandle(Arc<Mutex<Option<oneshot::Sender<()>>>>);
+
+impl CloseHandle {
+    pub(crate) fn close(&self) {
+        if let Some(close_sender) = self.0.lock().unwrap().take() {
+            let _ = close_sender.send(());
+        }
+    }
+}
+
+pub(crate) struct Server {
+    close_handle: CloseHandle,
+    done_receiver: Receiver<()>,
+}
+
+impl Server {
+    pub(crate) fn close_handle(&self) -> CloseHandle {
+        self.close_handle.clone()
+    }
+
+    /// Blocks until the server is closed, or its runtime shut down
+    pub(crate) fn wait(self) {
+        let _ = self.done_receiver.recv();
+    }
+}
+
+/// Starts serving `rpc` at `addr` on `runtime`, allowing cross-origin requests from anywhere
+pub(crate) fn start_http<M, S>(
+    runtime: &Handle,
+    addr: &SocketAddr,
+    rpc: Rpc<M, S>,
+    request_middleware: impl RequestMiddleware,
+    max_request_body_size: usize,
+) -> io::Result<Server>
+where
+    M: Metadata + Unpin,
+    S: Middleware<M>,
+    S::Future: Unpin,
+    S::CallFuture: Unpin,
+{
+    let _guard = runtime.enter();
+    let socket = match addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.set_reuseaddr(true)?;
+    socket.bind(*addr)?;
+    let mut incoming =
+        AddrIncoming::from_listener(socket.listen(LISTEN_BACKLOG)?).map_err(io::Error::other)?;
+    incoming.set_nodelay(true);
+    // Recover from accept errors, such as running out of file descriptors, instead of exiting
+    incoming.set_sleep_on_errors(true);
+
+    let request_middleware: Arc<dyn RequestMiddleware> = Arc::new(request_middleware);
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let peer_addr = PeerAddr(conn.remote_addr());
+        let mut handler = ServerHandler::new(
+            rpc.downgrade(),
+            Some(vec![AccessControlAllowOrigin::Any]),
+            Some(CORS_MAX_AGE_SECS),
+            AccessControlAllowHeaders::Any,
+            None,
+            request_middleware.clone(),
+            RestApi::Disabled,
+            None,
+            max_request_body_size,
+            true,
+        );
+        let service = service_fn(move |mut request: hyper::Request<hyper::Body>| {
+            request.extensions_mut().insert(peer_addr);
+            handler.call(request)
+        });
+        future::ready(Ok::<_, Infallible>(service))
+    });
+    let server = hyper::Server::builder(incoming)
+        .http1_keepalive(true)
+        .serve(make_service);
+
+    let (close_sender, close_receiver) = oneshot::channel();
+    let (done_sender, done_receiver) = bounded(1);
+    runtime.spawn(async move {
+        // Only the accepting stops, the connections run in tasks of their own
+        tokio::select! {
+            result = server => {
+                if let Err(err) = result {
+                    warn!("JSON RPC server error: {err}");
+                }
+            }
+            _ = close_receiver => {}
+        }
+        let _ = done_sender.send(());
+    });
+    Ok(Server {
+        close_handle: CloseHandle(Arc::new(Mutex::new(Some(close_sender)))),
+        done_receiver,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_client_ip() {
+        let client = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let inner_proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let request = |peer: IpAddr, forwarded: Option<&str>| {
+            let mut request = hyper::Request::new(hyper::Body::empty());
+            request
+                .extensions_mut()
+                .insert(PeerAddr(SocketAddr::new(peer, 40_000)));
+            if let Some(forwarded) = forwarded {
+                request
+                    .headers_mut()
+                    .insert("x-forwarded-for", forwarded.parse().unwrap());
+            }
+            request
+        };
+
+        assert_eq!(
+            client_ip(&hyper::Request::new(hyper::Body::empty()), &[proxy]),
+            None
+        );
+        // the header of an untrusted peer is ignored
+        assert_eq!(
+            client_ip(&request(client, Some("198.51.100.1")), &[]),
+            Some(client)
+        );
+        assert_eq!(
+            client_ip(&request(proxy, Some("198.51.100.1, 192.0.2.1")), &[proxy]),
+            Some(client)
+        );
+        assert_eq!(
+            client_ip(
+                &request(proxy, Some("192.0.2.1, 10.0.0.2")),
+                &[proxy, inner_proxy]
+            ),
+            Some(client)
+        );
+        // requests of a trusted proxy itself, or with a header it did not understand
+        assert_eq!(client_ip(&request(proxy, None), &[proxy]), Some(proxy));
+        assert_eq!(
+            client_ip(&request(proxy, Some("unknown")), &[proxy]),
+            Some(proxy)
+        );
+    }
+}
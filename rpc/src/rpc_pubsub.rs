@@ -21,9 +21,9 @@ use {
     solana_pubkey::Pubkey,
     solana_rpc_client_api::{
         config::{
-            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcBlockSubscribeConfig,
+            RpcBlockSubscribeFilter, RpcProgramSubscribeConfig, RpcSignatureSubscribeConfig,
+            RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
@@ -32,9 +32,23 @@ use {
     },
     solana_signature::Signature,
     solana_transaction_status::UiTransactionEncoding,
-    std::{str::FromStr, sync::Arc},
+    std::{
+        str::FromStr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
 };
 
+/// Distinguishes subscriptions that only receive changed account data, see
+/// [`AccountSubscriptionParams::changed_data_only`]
+static NEXT_CHANGED_DATA_SUBSCRIPTION: AtomicU64 = AtomicU64::new(0);
+
+/// Longest coalescing window a subscription can ask for
+pub const MAX_COALESCE_MS: u64 = 60_000;
+
 // We have to keep both of the following traits to not break backwards compatibility.
 // `RpcSolPubSubInternal` is actually used by the current PubSub API implementation.
 // `RpcSolPubSub` and the corresponding `gen_client` module are preserved
@@ -90,7 +104,7 @@ pub trait RpcSolPubSub {
         meta: Self::Metadata,
         subscriber: Subscriber<RpcResponse<RpcKeyedAccount>>,
         pubkey_str: String,
-        config: Option<RpcProgramAccountsConfig>,
+        config: Option<RpcProgramSubscribeConfig>,
     );
 
     // Unsubscribe from account notification subscription.
@@ -263,7 +277,7 @@ mod internal {
         fn account_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -276,7 +290,7 @@ mod internal {
         fn program_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcProgramAccountsConfig>,
+            config: Option<RpcProgramSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -421,23 +435,54 @@ fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     })
 }
 
+fn coalesce_window(coalesce_ms: Option<u64>) -> Result<Option<Duration>> {
+    match coalesce_ms {
+        None | Some(0) => Ok(None),
+        Some(coalesce_ms) if coalesce_ms <= MAX_COALESCE_MS => {
+            Ok(Some(Duration::from_millis(coalesce_ms)))
+        }
+        Some(_) => Err(Error {
+            code: ErrorCode::InvalidParams,
+            message: format!("Invalid Request: coalesceMs must be at most {MAX_COALESCE_MS}"),
+            data: None,
+        }),
+    }
+}
+
 impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let RpcAccountInfoConfig {
-            encoding,
-            data_slice,
-            commitment,
-            min_context_slot: _, // ignored
+        let RpcAccountSubscribeConfig {
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice,
+                    commitment,
+                    min_context_slot: _, // ignored
+                },
+            changed_data_only,
+            coalesce_ms,
         } = config.unwrap_or_default();
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        let changed_data_only = changed_data_only.unwrap_or_default();
+        if changed_data_only && encoding == UiAccountEncoding::JsonParsed {
+            return Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "changedDataOnly is not supported with jsonParsed encoding".to_string(),
+                data: None,
+            });
+        }
         let params = AccountSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             commitment: commitment.unwrap_or_default(),
             data_slice,
-            encoding: encoding.unwrap_or(UiAccountEncoding::Binary),
+            encoding,
+            changed_data_only: changed_data_only
+                .then(|| NEXT_CHANGED_DATA_SUBSCRIPTION.fetch_add(1, Ordering::Relaxed)),
+            coalesce_window: coalesce_window(coalesce_ms)?,
         };
         self.subscribe(SubscriptionParams::Account(params))
     }
@@ -449,9 +494,12 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn program_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcProgramAccountsConfig>,
+        config: Option<RpcProgramSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let config = config.unwrap_or_default();
+        let RpcProgramSubscribeConfig {
+            program_config: config,
+            coalesce_ms,
+        } = config.unwrap_or_default();
         let mut filters = config.filters.unwrap_or_default();
         if let Err(error) = verify_filters(&filters) {
             return Err(Error {
@@ -471,6 +519,7 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
             data_slice: config.account_config.data_slice,
             commitment: config.account_config.commitment.unwrap_or_default(),
             with_context: config.with_context.unwrap_or_default(),
+            coalesce_window: coalesce_window(coalesce_ms)?,
         };
         self.subscribe(SubscriptionParams::Program(params))
     }
@@ -900,11 +949,15 @@ mod tests {
 
         rpc.account_subscribe(
             vote_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                changed_data_only: None,
+                coalesce_ms: None,
             }),
         )
         .unwrap();
@@ -1014,11 +1067,15 @@ mod tests {
 
         rpc.account_subscribe(
             nonce_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(UiAccountEncoding::JsonParsed),
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                changed_data_only: None,
+                coalesce_ms: None,
             }),
         )
         .unwrap();
@@ -1145,11 +1202,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                changed_data_only: None,
+                coalesce_ms: None,
             }),
         )
         .unwrap();
@@ -1169,6 +1230,46 @@ mod tests {
         let _panic = receiver.recv();
     }
 
+    #[test]
+    fn test_account_subscribe_coalesce_window() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let rpc_subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            Arc::new(AtomicBool::new(false)),
+            max_complete_transaction_status_slot,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, _receiver) = rpc_pubsub_service::test_connection(&rpc_subscriptions);
+        let config = |coalesce_ms| {
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig::default(),
+                changed_data_only: None,
+                coalesce_ms: Some(coalesce_ms),
+            })
+        };
+
+        assert!(rpc
+            .account_subscribe(
+                Pubkey::new_unique().to_string(),
+                config(MAX_COALESCE_MS + 1)
+            )
+            .is_err());
+        assert!(rpc
+            .account_subscribe(Pubkey::new_unique().to_string(), config(MAX_COALESCE_MS))
+            .is_ok());
+
+        assert_eq!(coalesce_window(None).unwrap(), None);
+        assert_eq!(coalesce_window(Some(0)).unwrap(), None);
+        assert_eq!(
+            coalesce_window(Some(250)).unwrap(),
+            Some(Duration::from_millis(250))
+        );
+    }
+
     #[test]
     fn test_account_commitment() {
         let GenesisConfigInfo {
@@ -1198,11 +1299,15 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                changed_data_only: None,
+                coalesce_ms: None,
             }),
         )
         .unwrap();
@@ -9,12 +9,20 @@ use {
         rpc_subscriptions::{RpcNotification, RpcSubscriptions},
     },
     dashmap::{mapref::entry::Entry, DashMap},
+    hyper::{
+        ext::Protocol, server::conn::Http, service::service_fn, Body, Method, Request, Response,
+        StatusCode,
+    },
     jsonrpc_core::IoHandler,
-    soketto::handshake::{server, Server},
+    soketto::{
+        connection::{self, Mode},
+        handshake::{server, Server},
+    },
     solana_metrics::TokenCounter,
     solana_rayon_threadlimit::get_thread_count,
     solana_time_utils::AtomicInterval,
     std::{
+        convert::Infallible,
         io,
         net::SocketAddr,
         num::NonZeroUsize,
@@ -27,8 +35,13 @@ use {
     },
     stream_cancel::{Trigger, Tripwire},
     thiserror::Error,
-    tokio::{net::TcpStream, pin, select, sync::broadcast},
-    tokio_util::compat::TokioAsyncReadCompatExt,
+    tokio::{
+        io::{AsyncRead, AsyncWrite},
+        net::TcpStream,
+        pin, select,
+        sync::broadcast,
+    },
+    tokio_util::compat::{Compat, TokioAsyncReadCompatExt},
 };
 
 pub const MAX_ACTIVE_SUBSCRIPTIONS: usize = 1_000_000;
@@ -37,6 +50,10 @@ pub const DEFAULT_TEST_QUEUE_CAPACITY_ITEMS: usize = 100;
 pub const DEFAULT_QUEUE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
 const DEFAULT_TEST_QUEUE_CAPACITY_BYTES: usize = 16 * 1024 * 1024;
 pub const DEFAULT_WORKER_THREADS: usize = 1;
+pub const DEFAULT_HTTP2_MAX_CONCURRENT_STREAMS: u32 = 1_000;
+
+/// Every HTTP/2 connection starts with this preface from the client, see RFC 9113 section 3.4
+const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PubSubConfig {
@@ -47,6 +64,10 @@ pub struct PubSubConfig {
     pub queue_capacity_bytes: usize,
     pub worker_threads: usize,
     pub notification_threads: Option<NonZeroUsize>,
+    /// Also accept HTTP/2 connections, with a WebSocket on every stream (RFC 8441)
+    pub enable_http2: bool,
+    /// The maximum number of WebSocket streams a single HTTP/2 connection may have open
+    pub http2_max_concurrent_streams: u32,
 }
 
 impl Default for PubSubConfig {
@@ -59,6 +80,8 @@ impl Default for PubSubConfig {
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(get_thread_count()),
+            enable_http2: false,
+            http2_max_concurrent_streams: DEFAULT_HTTP2_MAX_CONCURRENT_STREAMS,
         }
     }
 }
@@ -73,6 +96,8 @@ impl PubSubConfig {
             queue_capacity_bytes: DEFAULT_TEST_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(2),
+            enable_http2: false,
+            http2_max_concurrent_streams: DEFAULT_HTTP2_MAX_CONCURRENT_STREAMS,
         }
     }
 }
@@ -356,6 +381,10 @@ enum Error {
     Handshake(#[from] soketto::handshake::Error),
     #[error("connection error: {0}")]
     Connection(#[from] soketto::connection::Error),
+    #[error("http/2 error: {0}")]
+    Http2(#[from] hyper::Error),
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
     #[error("broadcast queue error: {0}")]
     Broadcast(#[from] broadcast::error::RecvError),
     #[error("client has lagged behind (notification is gone)")]
@@ -366,8 +395,20 @@ async fn handle_connection(
     socket: TcpStream,
     subscription_control: SubscriptionControl,
     config: PubSubConfig,
-    mut tripwire: Tripwire,
+    tripwire: Tripwire,
+    stream_counter: Arc<TokenCounter>,
 ) -> Result<(), Error> {
+    if config.enable_http2 && is_http2(&socket).await? {
+        return handle_http2_connection(
+            socket,
+            subscription_control,
+            config,
+            tripwire,
+            stream_counter,
+        )
+        .await;
+    }
+
     let mut server = Server::new(socket.compat());
     let request = server.receive_request().await?;
     let accept = server::Response::Accept {
@@ -375,7 +416,95 @@ async fn handle_connection(
         protocol: None,
     };
     server.send_response(&accept).await?;
-    let mut builder = server.into_builder();
+    handle_websocket(
+        server.into_builder(),
+        subscription_control,
+        config,
+        tripwire,
+    )
+    .await
+}
+
+/// Whether the client opened the connection with the HTTP/2 preface rather than an HTTP/1.1
+/// WebSocket handshake
+async fn is_http2(socket: &TcpStream) -> io::Result<bool> {
+    let mut buf = [0; HTTP2_PREFACE.len()];
+    let len = socket.peek(&mut buf).await?;
+    Ok(len > 0 && buf[..len] == HTTP2_PREFACE[..len])
+}
+
+/// Serves an HTTP/2 connection on which every stream is a separate WebSocket
+///
+/// Clients open a stream with an extended CONNECT request (RFC 8441) and then use it exactly like
+/// a WebSocket connection of its own, with its own subscriptions. HTTP/2 flow control applies to
+/// each stream separately, so a slow subscription does not hold up the others on the connection.
+async fn handle_http2_connection(
+    socket: TcpStream,
+    subscription_control: SubscriptionControl,
+    config: PubSubConfig,
+    mut tripwire: Tripwire,
+    stream_counter: Arc<TokenCounter>,
+) -> Result<(), Error> {
+    let mut http = Http::new();
+    http.http2_only(true)
+        .http2_enable_connect_protocol()
+        .http2_max_concurrent_streams(config.http2_max_concurrent_streams);
+
+    let stream_tripwire = tripwire.clone();
+    let service = service_fn(move |mut request: Request<Body>| {
+        let is_websocket = request.method() == Method::CONNECT
+            && request
+                .extensions()
+                .get::<Protocol>()
+                .is_some_and(|protocol| protocol.as_str() == "websocket");
+        let mut response = Response::new(Body::empty());
+        if is_websocket {
+            let on_upgrade = hyper::upgrade::on(&mut request);
+            let subscription_control = subscription_control.clone();
+            let config = config.clone();
+            let tripwire = stream_tripwire.clone();
+            let counter_token = stream_counter.create_token();
+            tokio::spawn(async move {
+                let result = match on_upgrade.await {
+                    Ok(upgraded) => {
+                        handle_websocket(
+                            connection::Builder::new(upgraded.compat(), Mode::Server),
+                            subscription_control,
+                            config,
+                            tripwire,
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err.into()),
+                };
+                if let Err(err) = result {
+                    warn!("http/2 stream handler error: {err}");
+                }
+                drop(counter_token); // Force moving token into the task.
+            });
+        } else {
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+        }
+        async { Ok::<_, Infallible>(response) }
+    });
+
+    let connection = http.serve_connection(socket, service);
+    pin!(connection);
+    select! {
+        result = &mut connection => Ok(result?),
+        _ = &mut tripwire => {
+            warn!("disconnecting http/2 client: shutting down");
+            Ok(())
+        },
+    }
+}
+
+async fn handle_websocket<S: AsyncRead + AsyncWrite + Unpin>(
+    mut builder: connection::Builder<Compat<S>>,
+    subscription_control: SubscriptionControl,
+    config: PubSubConfig,
+    mut tripwire: Tripwire,
+) -> Result<(), Error> {
     builder.set_max_message_size(4_096);
     builder.set_max_frame_size(4_096);
     let (mut sender, mut receiver) = builder.finish();
@@ -462,6 +591,7 @@ async fn listen(
         }
     };
     let counter = TokenCounter::new("rpc_pubsub_connections");
+    let stream_counter = Arc::new(TokenCounter::new("rpc_pubsub_http2_streams"));
     loop {
         select! {
             result = listener.accept() => match result {
@@ -471,9 +601,10 @@ async fn listen(
                     let config = config.clone();
                     let tripwire = tripwire.clone();
                     let counter_token = counter.create_token();
+                    let stream_counter = Arc::clone(&stream_counter);
                     tokio::spawn(async move {
                         let handle = handle_connection(
-                            socket, subscription_control, config, tripwire
+                            socket, subscription_control, config, tripwire, stream_counter
                         );
                         match handle.await {
                             Ok(()) => debug!("connection closed ({addr:?})"),
@@ -494,6 +625,7 @@ mod tests {
     use {
         super::*,
         crate::optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        solana_rpc_client_api::response::SlotInfo,
         solana_runtime::{
             bank::Bank,
             bank_forks::BankForks,
@@ -507,6 +639,7 @@ mod tests {
                 RwLock,
             },
         },
+        tokio::io::AsyncWriteExt,
     };
 
     #[test]
@@ -531,4 +664,108 @@ mod tests {
         let thread = pubsub_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solRpcPubSub");
     }
+
+    #[test]
+    fn test_is_http2() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .await
+                .unwrap();
+            let addr = listener.local_addr().unwrap();
+            let websocket_handshake: &[u8] = b"GET / HTTP/1.1\r\nUpgrade: websocket\r\n\r\n";
+            for (request, expected) in [
+                (HTTP2_PREFACE, true),
+                (&HTTP2_PREFACE[..4], true),
+                (websocket_handshake, false),
+            ] {
+                let mut client = TcpStream::connect(addr).await.unwrap();
+                client.write_all(request).await.unwrap();
+                let (server, _) = listener.accept().await.unwrap();
+                assert_eq!(is_http2(&server).await.unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_http2_websocket_subscription() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let subscriptions = Arc::new(RpcSubscriptions::default_with_bank_forks(
+            max_complete_transaction_status_slot,
+            bank_forks,
+        ));
+        let config = PubSubConfig {
+            enable_http2: true,
+            ..PubSubConfig::default_for_tests()
+        };
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .await
+                .unwrap();
+            let addr = listener.local_addr().unwrap();
+            let (_trigger, tripwire) = Tripwire::new();
+            let subscription_control = subscriptions.control().clone();
+            tokio::spawn(async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let stream_counter = Arc::new(TokenCounter::new("test_http2_streams"));
+                handle_connection(socket, subscription_control, config, tripwire, stream_counter)
+                    .await
+            });
+
+            let socket = TcpStream::connect(addr).await.unwrap();
+            let (mut send_request, connection) = hyper::client::conn::Builder::new()
+                .http2_only(true)
+                .handshake::<_, Body>(socket)
+                .await
+                .unwrap();
+            tokio::spawn(connection);
+
+            // Only extended CONNECT requests for the websocket protocol open a stream
+            let request = Request::get("http://localhost/")
+                .body(Body::empty())
+                .unwrap();
+            let response = send_request.send_request(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let mut request = Request::connect("http://localhost/")
+                .body(Body::empty())
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(Protocol::from_static("websocket"));
+            let mut response = send_request.send_request(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let upgraded = hyper::upgrade::on(&mut response).await.unwrap();
+            let (mut sender, mut receiver) =
+                connection::Builder::new(upgraded.compat(), Mode::Client).finish();
+
+            sender
+                .send_text(r#"{"jsonrpc":"2.0","id":1,"method":"slotSubscribe"}"#)
+                .await
+                .unwrap();
+            let mut data = Vec::new();
+            receiver.receive_data(&mut data).await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&data).unwrap();
+            let subscription = response["result"].as_u64().unwrap();
+
+            subscriptions.notify_slot(1, 0, 0);
+            data.clear();
+            receiver.receive_data(&mut data).await.unwrap();
+            let expected_res_str = serde_json::to_string(&SlotInfo {
+                slot: 1,
+                parent: 0,
+                root: 0,
+            })
+            .unwrap();
+            let expected = format!(
+                r#"{{"jsonrpc":"2.0","method":"slotNotification","params":{{"result":{expected_res_str},"subscription":{subscription}}}}}"#
+            );
+            assert_eq!(str::from_utf8(&data).unwrap(), expected);
+        });
+    }
 }
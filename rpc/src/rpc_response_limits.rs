@@ -0,0 +1,376 @@
+//! Limits on the size of RPC responses.
+//!
+//! A few methods return as much as the request matches: every account of a program, the whole
+//! history of a busy address. Building such a response can take more memory than the node can
+//! spare. With a limit set for a method, the response is only built up to the limit. The request
+//! then fails with the part built so far, plus a cursor to continue from for the methods that can
+//! continue.
+//!
+//! The accounts scanned for a response are only kept while the least they can take in the response
+//! fits in the limit, so that the accounts past it are not loaded in memory at all.
+
+use {
+    jsonrpc_core::Error,
+    serde::{Deserialize, Serialize},
+    solana_account::{AccountSharedData, ReadableAccount},
+    solana_account_decoder::{encode_ui_account, UiAccountEncoding, UiDataSliceConfig},
+    solana_pubkey::Pubkey,
+    solana_rpc_client_api::custom_error::RpcCustomError,
+    std::{
+        collections::HashMap,
+        io,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+/// Least number of bytes a keyed account takes in a response besides its account: the shortest
+/// base58 address and the JSON around it
+const MIN_KEYED_ACCOUNT_OVERHEAD: usize = r#"{"pubkey":"","account":}"#.len() + 32;
+
+/// Methods whose responses can be limited
+pub const LIMITED_METHODS: &[&str] = &[
+    "getMultipleAccounts",
+    "getProgramAccounts",
+    "getSignaturesForAddress",
+    "getTokenAccountsByDelegate",
+    "getTokenAccountsByOwner",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RpcResponseLimits {
+    /// Limit of the methods without a limit in `methods`, in bytes
+    pub default_max_response_bytes: Option<usize>,
+    /// Limit of each method, in bytes, by method name
+    #[serde(rename = "methods")]
+    pub max_response_bytes: HashMap<String, usize>,
+}
+
+impl RpcResponseLimits {
+    pub fn verify(&self) -> Result<(), String> {
+        match self
+            .max_response_bytes
+            .keys()
+            .find(|method| !LIMITED_METHODS.contains(&method.as_str()))
+        {
+            Some(method) => Err(format!(
+                "the response size of {method} can not be limited, only that of {}",
+                LIMITED_METHODS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn max_response_bytes(&self, method: &str) -> Option<usize> {
+        self.max_response_bytes
+            .get(method)
+            .copied()
+            .or(self.default_max_response_bytes)
+    }
+}
+
+/// Bytes left for the items of a list when the response wraps the list in `empty_response`, built
+/// with an empty list, for example in a `Context`
+pub(crate) fn max_list_bytes<T: Serialize>(
+    max_bytes: Option<usize>,
+    empty_response: &T,
+) -> Option<usize> {
+    // The empty list is counted with the items
+    max_bytes.map(|max_bytes| (max_bytes + 2).saturating_sub(serialized_len(empty_response)))
+}
+
+/// Size of a JSON list built item by item, against a limit
+pub(crate) struct ListSize {
+    num_bytes: usize,
+    max_bytes: Option<usize>,
+}
+
+impl ListSize {
+    pub(crate) fn new(max_bytes: Option<usize>) -> Self {
+        // The brackets of the list
+        Self {
+            num_bytes: 2,
+            max_bytes,
+        }
+    }
+
+    /// Counts `item` and returns true if the list stays within the limit with it, or returns false
+    /// without counting it
+    pub(crate) fn push<T: Serialize>(&mut self, item: &T) -> bool {
+        let Some(max_bytes) = self.max_bytes else {
+            return true;
+        };
+        // The item and the comma separating it from the previous one
+        let num_bytes = self.num_bytes + serialized_len(item) + usize::from(self.num_bytes > 2);
+        if num_bytes > max_bytes {
+            return false;
+        }
+        self.num_bytes = num_bytes;
+        true
+    }
+}
+
+/// Bounds the accounts a scan keeps for a list of keyed accounts limited in size. Each account is
+/// counted for the least it can take in the list, so that the scan keeps at least all the accounts
+/// that fit, and then only those are encoded.
+pub(crate) struct ScanBudget {
+    max_bytes: usize,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+    num_bytes: AtomicUsize,
+    exceeded: AtomicBool,
+}
+
+impl ScanBudget {
+    pub(crate) fn new(
+        max_bytes: Option<usize>,
+        encoding: UiAccountEncoding,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Option<Self> {
+        max_bytes.map(|max_bytes| Self {
+            max_bytes,
+            encoding,
+            data_slice,
+            // The brackets of the list, less the comma not needed before the first account
+            num_bytes: AtomicUsize::new(1),
+            exceeded: AtomicBool::new(false),
+        })
+    }
+
+    /// Counts `account` and returns true if it may still fit, or returns false once the budget is
+    /// exceeded
+    pub(crate) fn keep(&self, account: &AccountSharedData) -> bool {
+        if self.exceeded.load(Ordering::Relaxed) {
+            return false;
+        }
+        // The account and the comma separating it from the previous one
+        let len = self.min_keyed_account_len(account) + 1;
+        if self.num_bytes.fetch_add(len, Ordering::Relaxed) + len > self.max_bytes {
+            self.exceeded.store(true, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    /// Whether accounts were left out of the scan
+    pub(crate) fn exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed)
+    }
+
+    fn min_keyed_account_len(&self, account: &AccountSharedData) -> usize {
+        // The address is not part of the account unless it is parsed. Parsing also needs other
+        // accounts, so the parsed data is counted as if it was empty.
+        let ui_account = match self.encoding {
+            UiAccountEncoding::JsonParsed => encode_ui_account(
+                &Pubkey::default(),
+                account,
+                UiAccountEncoding::Base64,
+                None,
+                Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                }),
+            ),
+            encoding => {
+                encode_ui_account(&Pubkey::default(), account, encoding, None, self.data_slice)
+            }
+        };
+        MIN_KEYED_ACCOUNT_OVERHEAD + serialized_len(&ui_account)
+    }
+}
+
+/// Items of a response collected up to a size limit
+pub(crate) struct LimitedItems<T> {
+    pub items: Vec<T>,
+    /// The limit, if items were left out to stay within it
+    pub truncated_at: Option<usize>,
+}
+
+/// Collects `items` until the next one would take the serialized list past `max_bytes`
+pub(crate) fn collect_within_limit<T: Serialize, E>(
+    items: impl IntoIterator<Item = Result<T, E>>,
+    max_bytes: Option<usize>,
+) -> Result<LimitedItems<T>, E> {
+    let items = items.into_iter();
+    let Some(max_bytes) = max_bytes else {
+        return Ok(LimitedItems {
+            items: items.collect::<Result<_, _>>()?,
+            truncated_at: None,
+        });
+    };
+
+    let mut limited = Vec::new();
+    let mut list_size = ListSize::new(Some(max_bytes));
+    for item in items {
+        let item = item?;
+        if !list_size.push(&item) {
+            return Ok(LimitedItems {
+                items: limited,
+                truncated_at: Some(max_bytes),
+            });
+        }
+        limited.push(item);
+    }
+    Ok(LimitedItems {
+        items: limited,
+        truncated_at: None,
+    })
+}
+
+/// The error returned in place of a response of `method` cut short at `max_bytes`
+pub(crate) fn response_too_large<T: Serialize>(
+    method: &str,
+    max_bytes: usize,
+    partial_result: &T,
+    cursor: Option<String>,
+) -> Error {
+    datapoint_info!(
+        "rpc-response-too-large",
+        "method" => method.to_string(),
+        ("max_bytes", max_bytes, i64)
+    );
+    RpcCustomError::ResponseTooLarge {
+        max_bytes,
+        partial_result: serde_json::to_value(partial_result).unwrap_or_default(),
+        cursor,
+    }
+    .into()
+}
+
+/// Length of `value` serialized to JSON, without keeping the serialized bytes around
+fn serialized_len<T: Serialize>(value: &T) -> usize {
+    let mut counter = ByteCounter(0);
+    // Writing to the counter never fails, neither does serializing the RPC response types
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(items: &[&str], max_bytes: Option<usize>) -> (Vec<String>, bool) {
+        let LimitedItems {
+            items,
+            truncated_at,
+        } = collect_within_limit(
+            items.iter().map(|item| Ok::<_, ()>(item.to_string())),
+            max_bytes,
+        )
+        .unwrap();
+        (items, truncated_at.is_some())
+    }
+
+    #[test]
+    fn test_collect_within_limit() {
+        let items = ["aaaa", "bbbb", "cccc"];
+        assert_eq!(
+            serde_json::to_vec(&items).unwrap().len(),
+            serialized_len(&items)
+        );
+
+        assert_eq!(
+            collect(&items, None),
+            (items.map(String::from).to_vec(), false)
+        );
+        // The whole list is 22 bytes
+        assert_eq!(
+            collect(&items, Some(22)),
+            (items.map(String::from).to_vec(), false)
+        );
+        assert_eq!(
+            collect(&items, Some(21)),
+            (vec!["aaaa".to_string(), "bbbb".to_string()], true)
+        );
+        assert_eq!(collect(&items, Some(14)), (vec!["aaaa".to_string()], true));
+        assert_eq!(collect(&items, Some(7)), (vec![], true));
+
+        assert_eq!(
+            collect_within_limit([Ok("aaaa"), Err(())], Some(100)).err(),
+            Some(())
+        );
+    }
+
+    #[test]
+    fn test_scan_budget() {
+        assert!(ScanBudget::new(None, UiAccountEncoding::Base64, None).is_none());
+
+        let account = AccountSharedData::new(42, 100, &Pubkey::new_unique());
+        let keyed_account = serde_json::json!({
+            "pubkey": Pubkey::new_unique().to_string(),
+            "account": encode_ui_account(
+                &Pubkey::default(),
+                &account,
+                UiAccountEncoding::Base64,
+                None,
+                None,
+            ),
+        });
+        // The scan keeps at least the accounts that fit in the response
+        let max_bytes = serialized_len(&[&keyed_account, &keyed_account]);
+        let scan_budget =
+            ScanBudget::new(Some(max_bytes), UiAccountEncoding::Base64, None).unwrap();
+        assert!(scan_budget.keep(&account));
+        assert!(scan_budget.keep(&account));
+        assert!(!scan_budget.exceeded());
+        // And not many more
+        let mut num_kept = 2;
+        while scan_budget.keep(&account) {
+            num_kept += 1;
+        }
+        assert!(num_kept < 4);
+        assert!(scan_budget.exceeded());
+        assert!(!scan_budget.keep(&AccountSharedData::default()));
+
+        // Only the sliced data counts
+        let scan_budget = ScanBudget::new(
+            Some(max_bytes),
+            UiAccountEncoding::Base64,
+            Some(UiDataSliceConfig {
+                offset: 0,
+                length: 0,
+            }),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            assert!(scan_budget.keep(&account));
+        }
+    }
+
+    #[test]
+    fn test_max_response_bytes() {
+        let limits = RpcResponseLimits {
+            default_max_response_bytes: Some(1_000),
+            max_response_bytes: HashMap::from([("getProgramAccounts".to_string(), 100)]),
+        };
+        assert!(limits.verify().is_ok());
+        assert_eq!(limits.max_response_bytes("getProgramAccounts"), Some(100));
+        assert_eq!(
+            limits.max_response_bytes("getMultipleAccounts"),
+            Some(1_000)
+        );
+        assert_eq!(
+            RpcResponseLimits::default().max_response_bytes("getProgramAccounts"),
+            None
+        );
+
+        let limits = RpcResponseLimits {
+            default_max_response_bytes: None,
+            max_response_bytes: HashMap::from([("getBalance".to_string(), 100)]),
+        };
+        assert!(limits.verify().is_err());
+    }
+}
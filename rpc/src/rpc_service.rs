@@ -3,23 +3,24 @@
 use {
     crate::{
         cluster_tpu_info::ClusterTpuInfo,
+        file_serving::{FileServingConfig, FileServingLimiter, ThrottledStream},
         max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         rpc::{rpc_accounts::*, rpc_accounts_scan::*, rpc_bank::*, rpc_full::*, rpc_minimal::*, *},
         rpc_cache::LargestAccountsCache,
         rpc_health::*,
+        rpc_http_server::{self, client_ip, CloseHandle},
+        rpc_usage::{self, RpcUsageMiddleware, RpcUsageTracker},
     },
     agave_snapshots::{
         paths as snapshot_paths, snapshot_archive_info::SnapshotArchiveInfoGetter,
         snapshot_config::SnapshotConfig, SnapshotInterval,
     },
-    crossbeam_channel::unbounded,
+    crossbeam_channel::{bounded, unbounded, Sender},
     jsonrpc_core::{futures::prelude::*, MetaIoHandler},
-    jsonrpc_http_server::{
-        hyper, AccessControlAllowOrigin, CloseHandle, DomainsValidation, RequestMiddleware,
-        RequestMiddlewareAction, ServerBuilder,
-    },
+    jsonrpc_http_server::{hyper, RequestMiddleware, RequestMiddlewareAction, Rpc},
     regex::Regex,
+    serde::{Deserialize, Serialize},
     solana_cli_output::display::build_balance_message,
     solana_client::{
         client_option::ClientOption,
@@ -38,8 +39,8 @@ use {
     solana_poh::poh_recorder::PohRecorder,
     solana_quic_definitions::NotifyKeyUpdate,
     solana_runtime::{
-        bank::Bank, bank_forks::BankForks, commitment::BlockCommitmentCache,
-        non_circulating_supply::calculate_non_circulating_supply,
+        bank::Bank, bank_forks::BankForks, banking_queue_snapshot::SharedBankingQueueSnapshot,
+        commitment::BlockCommitmentCache, non_circulating_supply::calculate_non_circulating_supply,
         prioritization_fee_cache::PrioritizationFeeCache,
     },
     solana_send_transaction_service::{
@@ -49,12 +50,12 @@ use {
     solana_storage_bigtable::CredentialType,
     solana_validator_exit::Exit,
     std::{
-        net::SocketAddr,
+        net::{IpAddr, SocketAddr},
         path::{Path, PathBuf},
         pin::Pin,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
-            Arc, RwLock,
+            Arc, Mutex, RwLock,
         },
         task::{Context, Poll},
         thread::{self, Builder, JoinHandle},
@@ -76,6 +77,11 @@ const LARGEST_ACCOUNTS_CACHE_DURATION: u64 = 60 * 60 * 2;
 const FALLBACK_FULL_SNAPSHOT_TIMEOUT_SECS: Duration = Duration::from_secs(12_000);
 /// Incremental: 2.5 GB / 10 MB/s = 250 seconds -> ~625 slots
 const FALLBACK_INCREMENTAL_SNAPSHOT_TIMEOUT_SECS: Duration = Duration::from_secs(250);
+/// How long the requests in flight on a replaced runtime are given to finish
+const RPC_RUNTIME_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+const RPC_RESTART_TIMEOUT: Duration = Duration::from_secs(10);
+/// Worker threads of the runtime the BigTable connections and uploads run on
+const BIGTABLE_RUNTIME_THREADS: usize = 4;
 
 enum SnapshotKind {
     Full,
@@ -119,9 +125,106 @@ pub struct JsonRpcService {
     #[cfg(test)]
     pub request_processor: JsonRpcRequestProcessor, // Used only by test_rpc_new()...
 
-    close_handle: Option<CloseHandle>,
+    server_close_handle: Arc<ServerCloseHandle>,
 
     client_updater: Arc<dyn NotifyKeyUpdate + Send + Sync>,
+
+    usage_tracker: Option<Arc<RpcUsageTracker>>,
+    usage_report_thread_hdl: Option<JoinHandle<()>>,
+
+    thread_pool_resizer: Arc<RpcThreadPoolResizer>,
+}
+
+/// Close handle of the HTTP server, which changes whenever the server restarts
+#[derive(Default)]
+struct ServerCloseHandle {
+    close_handle: Mutex<Option<CloseHandle>>,
+    closed: AtomicBool,
+}
+
+impl ServerCloseHandle {
+    fn set(&self, close_handle: CloseHandle) {
+        let mut current = self.close_handle.lock().unwrap();
+        if self.closed.load(Ordering::Relaxed) {
+            // Closed while the server was restarting
+            close_handle.close();
+        } else {
+            *current = Some(close_handle);
+        }
+    }
+
+    fn restart(&self) {
+        if let Some(close_handle) = self.close_handle.lock().unwrap().as_ref() {
+            close_handle.close();
+        }
+    }
+
+    fn close(&self) {
+        let mut current = self.close_handle.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        if let Some(close_handle) = current.take() {
+            close_handle.close();
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcThreadPoolSizes {
+    pub rpc_threads: usize,
+    pub rpc_blocking_threads: usize,
+}
+
+impl RpcThreadPoolSizes {
+    pub fn new(rpc_threads: usize, rpc_blocking_threads: usize) -> Self {
+        Self {
+            rpc_threads: rpc_threads.max(1),
+            rpc_blocking_threads: rpc_blocking_threads.max(1),
+        }
+    }
+}
+
+/// Resizes the thread pools of a running [`JsonRpcService`]
+///
+/// The pools belong to the tokio runtime the HTTP server runs on, which cannot be resized. A new
+/// runtime is built instead and the server restarted on it, while the requests in flight on the
+/// previous runtime are left to finish before it is shut down. Connections attempted during the
+/// restart are refused. The PubSub service has a runtime of its own, so websocket subscriptions
+/// are not affected.
+pub struct RpcThreadPoolResizer {
+    /// Current sizes, locked for the whole of a resize
+    sizes: Mutex<RpcThreadPoolSizes>,
+    resize_sender: Sender<(RpcThreadPoolSizes, Sender<Result<(), String>>)>,
+    server_close_handle: Arc<ServerCloseHandle>,
+}
+
+impl RpcThreadPoolResizer {
+    pub fn sizes(&self) -> RpcThreadPoolSizes {
+        *self.sizes.lock().unwrap()
+    }
+
+    /// Restarts the service with thread pools of `sizes`, returning the sizes it runs with
+    pub fn resize(&self, sizes: RpcThreadPoolSizes) -> Result<RpcThreadPoolSizes, String> {
+        let sizes = RpcThreadPoolSizes::new(sizes.rpc_threads, sizes.rpc_blocking_threads);
+        let mut current_sizes = self.sizes.lock().unwrap();
+        if *current_sizes == sizes {
+            return Ok(sizes);
+        }
+        let (result_sender, result_receiver) = bounded(1);
+        self.resize_sender
+            .send((sizes, result_sender))
+            .map_err(|_| "the JSON RPC service has exited".to_string())?;
+        self.server_close_handle.restart();
+        result_receiver
+            .recv_timeout(RPC_RESTART_TIMEOUT)
+            .map_err(|_| "the JSON RPC service did not restart".to_string())??;
+        *current_sizes = sizes;
+        Ok(sizes)
+    }
 }
 
 struct RpcRequestMiddleware {
@@ -129,6 +232,9 @@ struct RpcRequestMiddleware {
     full_snapshot_archive_path_regex: Regex,
     incremental_snapshot_archive_path_regex: Regex,
     snapshot_config: Option<SnapshotConfig>,
+    file_serving_limiter: Arc<FileServingLimiter>,
+    /// Reverse proxies whose `X-Forwarded-For` header tells which client sent a request
+    trusted_proxies: Vec<IpAddr>,
     bank_forks: Arc<RwLock<BankForks>>,
     health: Arc<RpcHealth>,
 }
@@ -137,6 +243,8 @@ impl RpcRequestMiddleware {
     pub fn new(
         ledger_path: PathBuf,
         snapshot_config: Option<SnapshotConfig>,
+        file_serving_config: FileServingConfig,
+        trusted_proxies: Vec<IpAddr>,
         bank_forks: Arc<RwLock<BankForks>>,
         health: Arc<RpcHealth>,
     ) -> Self {
@@ -151,6 +259,8 @@ impl RpcRequestMiddleware {
             )
             .unwrap(),
             snapshot_config,
+            file_serving_limiter: FileServingLimiter::new(file_serving_config),
+            trusted_proxies,
             bank_forks,
             health,
         }
@@ -253,7 +363,14 @@ impl RpcRequestMiddleware {
         )
     }
 
-    fn process_file_get(&self, path: &str) -> RequestMiddlewareAction {
+    fn process_file_get(&self, path: &str, client_ip: Option<IpAddr>) -> RequestMiddlewareAction {
+        let download_guard = match self.file_serving_limiter.try_start(client_ip) {
+            Ok(download_guard) => download_guard,
+            Err(rejection) => {
+                info!("get {path} rejected: {rejection:?}");
+                return rejection.response().into();
+            }
+        };
         let (filename, snapshot_type) = {
             let stem = Self::strip_leading_slash(path).expect("path already verified");
             match path {
@@ -312,8 +429,10 @@ impl RpcRequestMiddleware {
                         Self::internal_server_error()
                     }),
                     Ok(file) => {
-                        let stream =
-                            FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze());
+                        let stream = ThrottledStream::new(
+                            FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze()),
+                            download_guard,
+                        );
                         let body = if let Some(timeout) = snapshot_timeout {
                             hyper::Body::wrap_stream(TimeoutStream::new(stream, timeout))
                         } else {
@@ -348,6 +467,9 @@ impl RequestMiddleware for RpcRequestMiddleware {
             if request.uri().path() == FULL_SNAPSHOT_REQUEST_PATH
                 || request.uri().path() == INCREMENTAL_SNAPSHOT_REQUEST_PATH
             {
+                if let Err(rejection) = self.file_serving_limiter.check_enabled() {
+                    return rejection.response().into();
+                }
                 // Convenience redirect to the latest snapshot
                 let full_snapshot_archive_info =
                     snapshot_paths::get_highest_full_snapshot_archive_info(
@@ -391,7 +513,10 @@ impl RequestMiddleware for RpcRequestMiddleware {
         if let Some(path) = match_supply_path(request.uri().path()) {
             process_rest(&self.bank_forks, path)
         } else if self.is_file_get_path(request.uri().path()) {
-            self.process_file_get(request.uri().path())
+            self.process_file_get(
+                request.uri().path(),
+                client_ip(&request, &self.trusted_proxies),
+            )
         } else if request.uri().path() == "/health" {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
@@ -489,6 +614,8 @@ pub struct JsonRpcServiceConfig<'a> {
     pub leader_schedule_cache: Arc<LeaderScheduleCache>,
     pub max_complete_transaction_status_slot: Arc<AtomicU64>,
     pub prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    /// Snapshots of the banking stage queue, if `previewTransactionPriority` is enabled
+    pub banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
     pub client_option: ClientOption<'a>,
 }
 
@@ -540,6 +667,7 @@ impl JsonRpcService {
                     client.clone(),
                     config.max_complete_transaction_status_slot,
                     config.prioritization_fee_cache,
+                    config.banking_queue_snapshot,
                     runtime,
                 )?;
                 Ok(json_rpc_service)
@@ -589,6 +717,7 @@ impl JsonRpcService {
                     client,
                     config.max_complete_transaction_status_slot,
                     config.prioritization_fee_cache,
+                    config.banking_queue_snapshot,
                     runtime,
                 )?;
                 Ok(json_rpc_service)
@@ -618,6 +747,7 @@ impl JsonRpcService {
         connection_cache: Arc<ConnectionCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
     ) -> Result<Self, String> {
         let runtime = service_runtime(
             config.rpc_threads,
@@ -664,6 +794,7 @@ impl JsonRpcService {
             client.clone(),
             max_complete_transaction_status_slot,
             prioritization_fee_cache,
+            banking_queue_snapshot,
             runtime,
         )?;
         Ok(json_rpc_service)
@@ -697,6 +828,7 @@ impl JsonRpcService {
         client: Client,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        banking_queue_snapshot: Option<Arc<SharedBankingQueueSnapshot>>,
         runtime: Arc<TokioRuntime>,
     ) -> Result<Self, String> {
         info!("rpc bound to {rpc_addr:?}");
@@ -707,6 +839,7 @@ impl JsonRpcService {
             Arc::clone(&optimistically_confirmed_bank),
             Arc::clone(&blockstore),
             config.health_check_slot_distance,
+            config.health_check_max_vote_age_secs,
             override_health_check,
         ));
 
@@ -716,24 +849,31 @@ impl JsonRpcService {
 
         let exit_bigtable_ledger_upload_service = Arc::new(AtomicBool::new(false));
 
-        let (bigtable_ledger_storage, _bigtable_ledger_upload_service) =
+        let (bigtable_ledger_storage, _bigtable_ledger_upload_service, bigtable_runtime) =
             if let Some(RpcBigtableConfig {
                 enable_bigtable_ledger_upload,
                 ref bigtable_instance_name,
                 ref bigtable_app_profile_id,
                 timeout,
                 max_message_size,
+                ref credential_path,
+                connection_pool_size,
+                required,
             }) = config.rpc_bigtable_config
             {
                 let bigtable_config = solana_storage_bigtable::LedgerStorageConfig {
                     read_only: !enable_bigtable_ledger_upload,
                     timeout,
-                    credential_type: CredentialType::Filepath(None),
+                    credential_type: CredentialType::Filepath(credential_path.clone()),
                     instance_name: bigtable_instance_name.clone(),
                     app_profile_id: bigtable_app_profile_id.clone(),
                     max_message_size,
+                    connection_pool_size,
                 };
-                runtime
+                // The BigTable client keeps its connections on the runtime it connected on, so it
+                // gets a runtime of its own rather than the RPC runtime, which is replaced on resize
+                let bigtable_runtime = bigtable_service_runtime(rpc_niceness_adj);
+                bigtable_runtime
                     .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
                         bigtable_config,
                     ))
@@ -742,7 +882,7 @@ impl JsonRpcService {
 
                         let bigtable_ledger_upload_service = if enable_bigtable_ledger_upload {
                             Some(Arc::new(BigTableUploadService::new_with_config(
-                                runtime.clone(),
+                                bigtable_runtime.clone(),
                                 bigtable_ledger_storage.clone(),
                                 blockstore.clone(),
                                 block_commitment_cache.clone(),
@@ -757,17 +897,39 @@ impl JsonRpcService {
                         (
                             Some(bigtable_ledger_storage),
                             bigtable_ledger_upload_service,
+                            Some(bigtable_runtime.clone()),
                         )
                     })
-                    .unwrap_or_else(|err| {
+                    .or_else(|err| {
+                        if required {
+                            return Err(format!(
+                                "Failed to initialize BigTable ledger storage: {err:?}"
+                            ));
+                        }
                         error!("Failed to initialize BigTable ledger storage: {err:?}");
-                        (None, None)
-                    })
+                        Ok((None, None, None))
+                    })?
             } else {
-                (None, None)
+                (None, None, None)
             };
 
         let full_api = config.full_api;
+        let file_serving_config = config.file_serving.clone();
+        let trusted_proxies = config.trusted_proxies.clone();
+        let rpc_threads = config.rpc_threads;
+        let rpc_blocking_threads = config.rpc_blocking_threads;
+        let usage_tracker = config
+            .usage
+            .clone()
+            .map(|usage_config| Arc::new(RpcUsageTracker::new(usage_config)));
+        let usage_report_thread_hdl = usage_tracker.as_ref().and_then(|usage_tracker| {
+            let report_dir = usage_tracker.config().report_dir.clone()?;
+            Some(rpc_usage::spawn_report_thread(
+                usage_tracker.clone(),
+                report_dir,
+                exit.clone(),
+            ))
+        });
         let max_request_body_size = config
             .max_request_body_size
             .unwrap_or(MAX_REQUEST_BODY_SIZE);
@@ -788,6 +950,7 @@ impl JsonRpcService {
             leader_schedule_cache,
             max_complete_transaction_status_slot,
             prioritization_fee_cache,
+            banking_queue_snapshot,
             Arc::clone(&runtime),
         );
 
@@ -804,13 +967,12 @@ impl JsonRpcService {
 
         let ledger_path = ledger_path.to_path_buf();
 
-        let (close_handle_sender, close_handle_receiver) = unbounded();
-        let thread_hdl = Builder::new()
-            .name("solJsonRpcSvc".to_string())
-            .spawn(move || {
-                renice_this_thread(rpc_niceness_adj).unwrap();
-
-                let mut io = MetaIoHandler::default();
+        let meta_usage_tracker = usage_tracker.clone();
+        let start_server =
+            move |runtime: &Arc<TokioRuntime>, request_processor: JsonRpcRequestProcessor| {
+                let mut io = MetaIoHandler::with_middleware(RpcUsageMiddleware::new(
+                    meta_usage_tracker.clone(),
+                ));
 
                 io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
                 if full_api {
@@ -821,80 +983,199 @@ impl JsonRpcService {
                 }
 
                 let request_middleware = RpcRequestMiddleware::new(
-                    ledger_path,
-                    snapshot_config,
+                    ledger_path.clone(),
+                    snapshot_config.clone(),
+                    file_serving_config.clone(),
+                    trusted_proxies.clone(),
                     bank_forks.clone(),
                     health.clone(),
                 );
-                let server = ServerBuilder::with_meta_extractor(
-                    io,
-                    move |req: &hyper::Request<hyper::Body>| {
+                let meta_usage_tracker = meta_usage_tracker.clone();
+                let trusted_proxies = trusted_proxies.clone();
+                let rpc = Rpc {
+                    handler: Arc::new(io),
+                    extractor: Arc::new(move |req: &hyper::Request<hyper::Body>| {
                         let xbigtable = req.headers().get("x-bigtable");
-                        if xbigtable.is_some_and(|v| v == "disabled") {
+                        let request_processor = if xbigtable.is_some_and(|v| v == "disabled") {
                             request_processor.clone_without_bigtable()
                         } else {
                             request_processor.clone()
+                        };
+                        match &meta_usage_tracker {
+                            Some(usage_tracker) => request_processor.clone_with_usage_client(
+                                usage_tracker
+                                    .client_key(req.headers(), client_ip(req, &trusted_proxies)),
+                            ),
+                            None => request_processor,
                         }
-                    },
+                    }),
+                };
+                rpc_http_server::start_http(
+                    runtime.handle(),
+                    &rpc_addr,
+                    rpc,
+                    request_middleware,
+                    max_request_body_size,
                 )
-                .event_loop_executor(runtime.handle().clone())
-                .threads(1)
-                .cors(DomainsValidation::AllowOnly(vec![
-                    AccessControlAllowOrigin::Any,
-                ]))
-                .cors_max_age(86400)
-                .request_middleware(request_middleware)
-                .max_request_body_size(max_request_body_size)
-                .start_http(&rpc_addr);
-
-                if let Err(e) = server {
-                    warn!(
-                        "JSON RPC service unavailable error: {e:?}. Also, check that port {} is \
-                         not already in use by another application",
-                        rpc_addr.port()
-                    );
-                    close_handle_sender.send(Err(e.to_string())).unwrap();
-                    return;
-                }
+            };
 
-                let server = server.unwrap();
-                close_handle_sender.send(Ok(server.close_handle())).unwrap();
-                server.wait();
+        let server_close_handle = Arc::new(ServerCloseHandle::default());
+        let (resize_sender, resize_receiver) = unbounded();
+        let thread_pool_resizer = Arc::new(RpcThreadPoolResizer {
+            sizes: Mutex::new(RpcThreadPoolSizes::new(rpc_threads, rpc_blocking_threads)),
+            resize_sender,
+            server_close_handle: server_close_handle.clone(),
+        });
+
+        let (start_result_sender, start_result_receiver) = unbounded();
+        let thread_server_close_handle = server_close_handle.clone();
+        let thread_hdl = Builder::new()
+            .name("solJsonRpcSvc".to_string())
+            .spawn(move || {
+                renice_this_thread(rpc_niceness_adj).unwrap();
+                let _bigtable_runtime = bigtable_runtime;
+
+                let mut server = match start_server(&runtime, request_processor.clone()) {
+                    Ok(server) => server,
+                    Err(e) => {
+                        warn!(
+                            "JSON RPC service unavailable error: {e:?}. Also, check that port {} \
+                             is not already in use by another application",
+                            rpc_addr.port()
+                        );
+                        start_result_sender.send(Err(e.to_string())).unwrap();
+                        return;
+                    }
+                };
+                thread_server_close_handle.set(server.close_handle());
+                start_result_sender.send(Ok(())).unwrap();
+
+                let mut runtime = runtime;
+                let mut request_processor = request_processor;
+                loop {
+                    server.wait();
+                    if thread_server_close_handle.is_closed() {
+                        break;
+                    }
+                    let Ok((sizes, result_sender)) = resize_receiver.try_recv() else {
+                        break;
+                    };
+                    let RpcThreadPoolSizes {
+                        rpc_threads,
+                        rpc_blocking_threads,
+                    } = sizes;
+                    let new_runtime =
+                        service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj);
+                    let new_request_processor =
+                        request_processor.clone_with_runtime(new_runtime.clone());
+                    let result = match start_server(&new_runtime, new_request_processor.clone()) {
+                        Ok(new_server) => {
+                            info!(
+                                "rpc thread pools resized to {rpc_threads} threads and \
+                                 {rpc_blocking_threads} blocking threads"
+                            );
+                            server = new_server;
+                            request_processor = new_request_processor;
+                            drain_runtime(std::mem::replace(&mut runtime, new_runtime));
+                            Ok(())
+                        }
+                        Err(err) => {
+                            let err = format!("failed to restart the JSON RPC service: {err}");
+                            error!("{err}, restarting it with the previous thread pools");
+                            drop(new_request_processor);
+                            drop(new_runtime);
+                            match start_server(&runtime, request_processor.clone()) {
+                                Ok(previous_server) => server = previous_server,
+                                Err(err) => {
+                                    error!("Failed to restart the JSON RPC service: {err}");
+                                    let _ = result_sender.send(Err(err.to_string()));
+                                    break;
+                                }
+                            }
+                            Err(err)
+                        }
+                    };
+                    thread_server_close_handle.set(server.close_handle());
+                    let _ = result_sender.send(result);
+                }
                 exit_bigtable_ledger_upload_service.store(true, Ordering::Relaxed);
             })
             .unwrap();
 
-        let close_handle = close_handle_receiver.recv().unwrap()?;
-        let close_handle_ = close_handle.clone();
+        start_result_receiver.recv().unwrap()?;
+        let validator_server_close_handle = server_close_handle.clone();
         validator_exit
             .write()
             .unwrap()
             .register_exit(Box::new(move || {
-                close_handle_.close();
+                validator_server_close_handle.close();
             }));
         Ok(Self {
             thread_hdl,
             #[cfg(test)]
             request_processor: test_request_processor,
-            close_handle: Some(close_handle),
+            server_close_handle,
             client_updater: Arc::new(client) as Arc<dyn NotifyKeyUpdate + Send + Sync>,
+            usage_tracker,
+            usage_report_thread_hdl,
+            thread_pool_resizer,
         })
     }
 
     pub fn exit(&mut self) {
-        if let Some(c) = self.close_handle.take() {
-            c.close()
-        }
+        self.server_close_handle.close();
     }
 
     pub fn join(mut self) -> thread::Result<()> {
         self.exit();
-        self.thread_hdl.join()
+        self.thread_hdl.join()?;
+        if let Some(usage_report_thread_hdl) = self.usage_report_thread_hdl {
+            usage_report_thread_hdl.join()?;
+        }
+        Ok(())
     }
 
     pub fn get_client_key_updater(&self) -> Arc<dyn NotifyKeyUpdate + Send + Sync> {
         self.client_updater.clone()
     }
+
+    /// Returns the per-client usage accounting, if enabled
+    pub fn usage_tracker(&self) -> Option<Arc<RpcUsageTracker>> {
+        self.usage_tracker.clone()
+    }
+
+    pub fn thread_pool_resizer(&self) -> Arc<RpcThreadPoolResizer> {
+        self.thread_pool_resizer.clone()
+    }
+}
+
+/// Shuts down a runtime the HTTP server was moved off of, once the requests in flight on it
+/// finished or [`RPC_RUNTIME_DRAIN_TIMEOUT`] passed
+///
+/// Requests still in flight hold a reference to the runtime, so it is only shut down once the
+/// last of them released it, which aborts whatever is left running on it.
+fn drain_runtime(mut runtime: Arc<TokioRuntime>) {
+    Builder::new()
+        .name("solRpcElDrain".to_string())
+        .spawn(move || {
+            let deadline = Instant::now() + RPC_RUNTIME_DRAIN_TIMEOUT;
+            while runtime.metrics().num_alive_tasks() > 0 && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(100));
+            }
+            loop {
+                match Arc::try_unwrap(runtime) {
+                    Ok(runtime) => {
+                        runtime.shutdown_timeout(Duration::from_secs(1));
+                        break;
+                    }
+                    Err(still_shared) => {
+                        runtime = still_shared;
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                }
+            }
+        })
+        .unwrap();
 }
 
 pub fn service_runtime(
@@ -933,13 +1214,26 @@ pub fn service_runtime(
     runtime
 }
 
+/// Runtime the BigTable client connects and uploads blocks on, which lives as long as the service
+fn bigtable_service_runtime(rpc_niceness_adj: i8) -> Arc<TokioRuntime> {
+    Arc::new(
+        TokioBuilder::new_multi_thread()
+            .worker_threads(BIGTABLE_RUNTIME_THREADS)
+            .on_thread_start(move || renice_this_thread(rpc_niceness_adj).unwrap())
+            .thread_name("solRpcBigtable")
+            .enable_all()
+            .build()
+            .expect("Runtime"),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
         crate::rpc::{create_validator_exit, tests::new_test_cluster_info},
         solana_cluster_type::ClusterType,
-        solana_genesis_config::DEFAULT_GENESIS_ARCHIVE,
+        solana_genesis_config::{GenesisConfig, DEFAULT_GENESIS_ARCHIVE},
         solana_ledger::{
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
             get_tmp_ledger_path_auto_delete,
@@ -948,22 +1242,20 @@ mod tests {
         solana_runtime::bank::Bank,
         solana_signer::Signer,
         std::{
-            io::Write,
-            net::{IpAddr, Ipv4Addr},
+            io::{Read, Write},
+            net::{IpAddr, Ipv4Addr, TcpStream},
+            num::NonZeroUsize,
         },
         tokio::runtime::Runtime,
     };
 
-    #[test]
-    fn test_rpc_new() {
-        let GenesisConfigInfo {
-            genesis_config,
-            mint_keypair,
-            ..
-        } = create_genesis_config(10_000);
+    fn start_test_rpc_service(
+        genesis_config: &GenesisConfig,
+        ledger_path: &Path,
+    ) -> (JsonRpcService, SocketAddr) {
         let exit = Arc::new(AtomicBool::new(false));
         let validator_exit = create_validator_exit(exit.clone());
-        let bank = Bank::new_for_tests(&genesis_config);
+        let bank = Bank::new_for_tests(genesis_config);
         let cluster_info = Arc::new(new_test_cluster_info());
         let ip_addr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
         let port_range = solana_net_utils::sockets::localhost_port_range_for_tests();
@@ -972,13 +1264,12 @@ mod tests {
             solana_net_utils::find_available_port_in_range(ip_addr, port_range).unwrap(),
         );
         let bank_forks = BankForks::new_rw_arc(bank);
-        let ledger_path = get_tmp_ledger_path_auto_delete!();
-        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let blockstore = Arc::new(Blockstore::open(ledger_path).unwrap());
         let block_commitment_cache = Arc::new(RwLock::new(BlockCommitmentCache::default()));
         let optimistically_confirmed_bank =
             OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
         let connection_cache = Arc::new(ConnectionCache::new("connection_cache_test"));
-        let mut rpc_service = JsonRpcService::new(
+        let rpc_service = JsonRpcService::new(
             rpc_addr,
             JsonRpcConfig::default(),
             None,
@@ -1003,8 +1294,21 @@ mod tests {
             connection_cache,
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
         )
         .expect("assume successful JsonRpcService start");
+        (rpc_service, rpc_addr)
+    }
+
+    #[test]
+    fn test_rpc_new() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let (mut rpc_service, _) = start_test_rpc_service(&genesis_config, ledger_path.path());
         let thread = rpc_service.thread_hdl.thread();
         assert_eq!(thread.name().unwrap(), "solJsonRpcSvc");
 
@@ -1020,6 +1324,44 @@ mod tests {
         rpc_service.join().unwrap();
     }
 
+    fn get_slot_over_http(rpc_addr: SocketAddr) -> String {
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"getSlot"}"#;
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, rpc_addr.port())).unwrap();
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_rpc_thread_pool_resize() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let (rpc_service, rpc_addr) = start_test_rpc_service(&genesis_config, ledger_path.path());
+        assert!(get_slot_over_http(rpc_addr).contains(r#""result":0"#));
+
+        let resizer = rpc_service.thread_pool_resizer();
+        let sizes = RpcThreadPoolSizes::new(3, 0);
+        assert_eq!(
+            resizer.resize(sizes).unwrap(),
+            RpcThreadPoolSizes {
+                rpc_threads: 3,
+                rpc_blocking_threads: 1,
+            }
+        );
+        assert_eq!(resizer.sizes(), RpcThreadPoolSizes::new(3, 1));
+        assert!(get_slot_over_http(rpc_addr).contains(r#""result":0"#));
+
+        rpc_service.join().unwrap();
+        assert!(resizer.resize(RpcThreadPoolSizes::new(4, 4)).is_err());
+    }
+
     fn create_bank_forks() -> Arc<RwLock<BankForks>> {
         let GenesisConfigInfo {
             mut genesis_config, ..
@@ -1095,12 +1437,16 @@ mod tests {
         let rrm = RpcRequestMiddleware::new(
             ledger_path.path().to_path_buf(),
             None,
+            FileServingConfig::default(),
+            Vec::new(),
             bank_forks.clone(),
             health.clone(),
         );
         let rrm_with_snapshot_config = RpcRequestMiddleware::new(
             ledger_path.path().to_path_buf(),
             Some(SnapshotConfig::default()),
+            FileServingConfig::default(),
+            Vec::new(),
             bank_forks,
             health,
         );
@@ -1214,12 +1560,14 @@ mod tests {
         let rrm = RpcRequestMiddleware::new(
             ledger_path.path().to_path_buf(),
             None,
+            FileServingConfig::default(),
+            Vec::new(),
             bank_forks,
             RpcHealth::stub(optimistically_confirmed_bank, blockstore),
         );
 
         // File does not exist => request should fail.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -1234,7 +1582,7 @@ mod tests {
         }
 
         // Normal file exist => request should succeed.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -1251,7 +1599,7 @@ mod tests {
         symlink::symlink_file("wrong", &genesis_path).unwrap();
 
         // File is a symbolic link => request should fail.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -1260,4 +1608,63 @@ mod tests {
             panic!("Unexpected RequestMiddlewareAction variant");
         }
     }
+
+    #[test]
+    fn test_process_file_get_limits() {
+        let runtime = Runtime::new().unwrap();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        std::fs::write(ledger_path.path().join(DEFAULT_GENESIS_ARCHIVE), b"genesis").unwrap();
+        let bank_forks = create_bank_forks();
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let health = RpcHealth::stub(optimistically_confirmed_bank, blockstore);
+        let status = |rrm: &RpcRequestMiddleware, client: &str| {
+            let RequestMiddlewareAction::Respond { response, .. } =
+                rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some(client.parse().unwrap()))
+            else {
+                panic!("Unexpected RequestMiddlewareAction variant");
+            };
+            runtime.block_on(response).unwrap()
+        };
+
+        let rrm = RpcRequestMiddleware::new(
+            ledger_path.path().to_path_buf(),
+            None,
+            FileServingConfig {
+                enabled: false,
+                ..FileServingConfig::default()
+            },
+            Vec::new(),
+            bank_forks.clone(),
+            health.clone(),
+        );
+        assert_eq!(
+            status(&rrm, "192.0.2.1").status(),
+            hyper::StatusCode::FORBIDDEN
+        );
+
+        let rrm = RpcRequestMiddleware::new(
+            ledger_path.path().to_path_buf(),
+            None,
+            FileServingConfig {
+                max_downloads_per_ip: NonZeroUsize::new(1),
+                ..FileServingConfig::default()
+            },
+            Vec::new(),
+            bank_forks,
+            health,
+        );
+        // the download lasts as long as its body
+        let response = status(&rrm, "192.0.2.1");
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        assert_eq!(
+            status(&rrm, "192.0.2.1").status(),
+            hyper::StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(status(&rrm, "192.0.2.2").status(), hyper::StatusCode::OK);
+        drop(response);
+        assert_eq!(status(&rrm, "192.0.2.1").status(), hyper::StatusCode::OK);
+    }
 }
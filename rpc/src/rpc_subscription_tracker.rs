@@ -2,6 +2,7 @@ use {
     crate::rpc_subscriptions::{NotificationEntry, RpcNotification, TimestampedNotificationEntry},
     dashmap::{mapref::entry::Entry as DashEntry, DashMap},
     serde::{Deserialize, Serialize},
+    solana_account::{AccountSharedData, ReadableAccount},
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_clock::Slot,
     solana_commitment_config::CommitmentConfig,
@@ -19,13 +20,21 @@ use {
         fmt,
         sync::{
             atomic::{AtomicU64, Ordering},
-            Arc, RwLock, Weak,
+            Arc, Mutex, RwLock, Weak,
         },
+        time::{Duration, Instant},
     },
     thiserror::Error,
     tokio::sync::broadcast,
 };
 
+/// Most accounts a coalescing window holds back before the notification is sent without waiting
+/// for the window to pass
+pub const MAX_HELD_BACK_ACCOUNTS: usize = 10_000;
+/// Most account data a coalescing window holds back before the notification is sent without
+/// waiting for the window to pass
+pub const MAX_HELD_BACK_BYTES: usize = 64 * 1024 * 1024;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SubscriptionId(u64);
 
@@ -113,6 +122,14 @@ impl SubscriptionParams {
         commitment.is_confirmed()
     }
 
+    fn coalesce_window(&self) -> Option<Duration> {
+        match self {
+            SubscriptionParams::Account(params) => params.coalesce_window,
+            SubscriptionParams::Program(params) => params.coalesce_window,
+            _ => None,
+        }
+    }
+
     fn is_node_progress_watcher(&self) -> bool {
         matches!(
             self,
@@ -130,6 +147,12 @@ pub struct AccountSubscriptionParams {
     pub encoding: UiAccountEncoding,
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    /// Set for subscriptions that only receive the changed parts of the account data. The value is
+    /// unique to every such subscription: each subscriber needs its own notifications to start
+    /// from the complete data, so these subscriptions are never shared.
+    pub changed_data_only: Option<u64>,
+    /// Minimum time between two notifications
+    pub coalesce_window: Option<Duration>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -169,6 +192,9 @@ pub struct ProgramSubscriptionParams {
     pub data_slice: Option<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
     pub with_context: bool,
+    /// Minimum time between two notifications, which carry the latest state of every account
+    /// modified in between
+    pub coalesce_window: Option<Duration>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -322,6 +348,10 @@ pub struct SubscriptionInfo {
     params: SubscriptionParams,
     method: &'static str,
     pub last_notified_slot: RwLock<Slot>,
+    /// Account data sent with the last notification, for subscriptions that only receive changes
+    pub last_notified_data: RwLock<Option<Vec<u8>>>,
+    /// Notifications held back by the coalescing window of the subscription, if it has one
+    pub coalescing: Option<Coalescing>,
     commitment: Option<CommitmentConfig>,
 }
 
@@ -343,6 +373,87 @@ impl SubscriptionInfo {
     }
 }
 
+/// Notifications held back until a coalescing window has passed since the previous one
+#[derive(Debug)]
+pub struct Coalescing {
+    window: Duration,
+    state: Mutex<CoalescingState>,
+}
+
+#[derive(Debug, Default)]
+struct CoalescingState {
+    last_notified_at: Option<Instant>,
+    /// Whether a notification was held back since the last one was sent
+    held_back: bool,
+    /// Latest state of the program accounts modified since the last notification
+    accounts: HashMap<Pubkey, AccountSharedData>,
+    /// Data bytes of `accounts`
+    accounts_bytes: usize,
+    /// Whether `accounts` reached the limits, so the notification is not held back any longer
+    full: bool,
+}
+
+impl Coalescing {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns true, and remembers it, if a notification must be held back at `now`
+    pub fn hold_back(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let hold_back = !state.full
+            && state
+                .last_notified_at
+                .is_some_and(|last_notified_at| now.duration_since(last_notified_at) < self.window);
+        state.held_back |= hold_back;
+        hold_back
+    }
+
+    /// Keeps the latest state of `accounts` for the next notification
+    ///
+    /// Once [`MAX_HELD_BACK_ACCOUNTS`] or [`MAX_HELD_BACK_BYTES`] are held back, the notification
+    /// is due without waiting for the window to pass.
+    pub fn hold_back_accounts(&self, accounts: Vec<(Pubkey, AccountSharedData)>) {
+        let mut state = self.state.lock().unwrap();
+        for (pubkey, account) in accounts {
+            state.accounts_bytes = state.accounts_bytes.saturating_add(account.data().len());
+            if let Some(previous) = state.accounts.insert(pubkey, account) {
+                state.accounts_bytes = state.accounts_bytes.saturating_sub(previous.data().len());
+            }
+        }
+        state.full = state.accounts.len() >= MAX_HELD_BACK_ACCOUNTS
+            || state.accounts_bytes >= MAX_HELD_BACK_BYTES;
+    }
+
+    /// Returns the accounts held back for this notification, which is no longer held back
+    pub fn release(&self) -> HashMap<Pubkey, AccountSharedData> {
+        let mut state = self.state.lock().unwrap();
+        state.held_back = false;
+        state.accounts_bytes = 0;
+        state.full = false;
+        std::mem::take(&mut state.accounts)
+    }
+
+    /// Starts a new window after a notification was sent at `now`
+    pub fn notified(&self, now: Instant) {
+        self.state.lock().unwrap().last_notified_at = Some(now);
+    }
+
+    /// Returns true if a notification was held back and its window has passed at `now`, or it
+    /// holds back too many accounts to wait any longer
+    pub fn is_due(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap();
+        state.held_back
+            && (state.full
+                || state.last_notified_at.is_none_or(|last_notified_at| {
+                    now.duration_since(last_notified_at) >= self.window
+                }))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("node subscription limit reached")]
@@ -425,6 +536,8 @@ pub struct SubscriptionsTracker {
     gossip_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
     // Slots, slots updates, roots, votes.
     node_progress_watchers: HashMap<SubscriptionParams, Arc<SubscriptionInfo>>,
+    // Accounts, programs with a coalescing window (gossip or not)
+    coalescing_watchers: HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
 }
 
 impl SubscriptionsTracker {
@@ -440,6 +553,7 @@ impl SubscriptionsTracker {
             commitment_watchers: HashMap::new(),
             gossip_watchers: HashMap::new(),
             node_progress_watchers: HashMap::new(),
+            coalescing_watchers: HashMap::new(),
         }
     }
 
@@ -451,6 +565,8 @@ impl SubscriptionsTracker {
     ) {
         let info = Arc::new(SubscriptionInfo {
             last_notified_slot: RwLock::new(last_notified_slot()),
+            last_notified_data: RwLock::default(),
+            coalescing: params.coalesce_window().map(Coalescing::new),
             id,
             commitment: params.commitment(),
             method: params.method(),
@@ -478,6 +594,9 @@ impl SubscriptionsTracker {
             self.node_progress_watchers
                 .insert(info.params.clone(), Arc::clone(&info));
         }
+        if info.coalescing.is_some() {
+            self.coalescing_watchers.insert(id, Arc::clone(&info));
+        }
     }
 
     #[allow(clippy::collapsible_if)]
@@ -515,6 +634,11 @@ impl SubscriptionsTracker {
                 warn!("Subscriptions inconsistency (missing entry in node_progress_watchers)");
             }
         }
+        if params.coalesce_window().is_some() {
+            if self.coalescing_watchers.remove(&id).is_none() {
+                warn!("Subscriptions inconsistency (missing entry in coalescing_watchers)");
+            }
+        }
     }
 
     pub fn by_signature(
@@ -534,6 +658,10 @@ impl SubscriptionsTracker {
     pub fn node_progress_watchers(&self) -> &HashMap<SubscriptionParams, Arc<SubscriptionInfo>> {
         &self.node_progress_watchers
     }
+
+    pub fn coalescing_watchers(&self) -> &HashMap<SubscriptionId, Arc<SubscriptionInfo>> {
+        &self.coalescing_watchers
+    }
 }
 
 struct SubscriptionTokenInner {
@@ -598,6 +726,7 @@ mod tests {
     use {
         super::*,
         crate::rpc_pubsub_service::PubSubConfig,
+        solana_account::ReadableAccount,
         solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
         solana_runtime::bank::Bank,
     };
@@ -723,6 +852,8 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: None,
+            coalesce_window: None,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 42);
 
@@ -763,6 +894,8 @@ mod tests {
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: None,
+            coalesce_window: None,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 0);
         assert_eq!(counts(&tracker), (0, 1, 0, 0));
@@ -774,6 +907,8 @@ mod tests {
             commitment: CommitmentConfig::confirmed(),
             encoding: UiAccountEncoding::Base64Zstd,
             data_slice: None,
+            changed_data_only: None,
+            coalesce_window: None,
         });
         tracker.subscribe(account_params2.clone(), 2.into(), || 0);
         assert_eq!(counts(&tracker), (0, 0, 1, 0));
@@ -790,4 +925,66 @@ mod tests {
         tracker.unsubscribe(signature_params, 3.into());
         assert_eq!(counts(&tracker), (0, 0, 0, 0));
     }
+
+    #[test]
+    fn coalescing_window() {
+        let coalescing = Coalescing::new(Duration::from_millis(100));
+        let start = Instant::now();
+        // The first notification is never held back
+        assert!(!coalescing.hold_back(start));
+        assert!(!coalescing.is_due(start));
+        coalescing.notified(start);
+
+        let pubkey = Pubkey::new_unique();
+        let within_window = start + Duration::from_millis(50);
+        assert!(coalescing.hold_back(within_window));
+        coalescing.hold_back_accounts(vec![(pubkey, AccountSharedData::new(1, 0, &pubkey))]);
+        coalescing.hold_back_accounts(vec![(pubkey, AccountSharedData::new(2, 0, &pubkey))]);
+        assert!(!coalescing.is_due(within_window));
+
+        let after_window = start + Duration::from_millis(100);
+        assert!(coalescing.is_due(after_window));
+        assert!(!coalescing.hold_back(after_window));
+        let accounts = coalescing.release();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[&pubkey].lamports(), 2);
+        assert!(!coalescing.is_due(after_window));
+    }
+
+    #[test]
+    fn coalescing_limit() {
+        let coalescing = Coalescing::new(Duration::from_secs(60));
+        let start = Instant::now();
+        coalescing.notified(start);
+
+        let within_window = start + Duration::from_secs(1);
+        assert!(coalescing.hold_back(within_window));
+        let accounts = (0..MAX_HELD_BACK_ACCOUNTS - 1)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                (pubkey, AccountSharedData::new(1, 0, &pubkey))
+            })
+            .collect::<Vec<_>>();
+        coalescing.hold_back_accounts(accounts.clone());
+        // Updates of accounts already held back take no more room
+        coalescing.hold_back_accounts(accounts);
+        assert!(!coalescing.is_due(within_window));
+        assert!(coalescing.hold_back(within_window));
+
+        let pubkey = Pubkey::new_unique();
+        coalescing.hold_back_accounts(vec![(pubkey, AccountSharedData::new(1, 0, &pubkey))]);
+        assert!(coalescing.is_due(within_window));
+        assert!(!coalescing.hold_back(within_window));
+        assert_eq!(coalescing.release().len(), MAX_HELD_BACK_ACCOUNTS);
+
+        // Large accounts reach the byte limit first
+        coalescing.notified(within_window);
+        assert!(coalescing.hold_back(within_window));
+        let pubkey = Pubkey::new_unique();
+        coalescing.hold_back_accounts(vec![(
+            pubkey,
+            AccountSharedData::new(1, MAX_HELD_BACK_BYTES, &pubkey),
+        )]);
+        assert!(coalescing.is_due(within_window));
+    }
 }
@@ -20,15 +20,17 @@ use {
     solana_account::{AccountSharedData, ReadableAccount},
     solana_account_decoder::{
         encode_ui_account, parse_token::is_known_spl_token_id, UiAccount, UiAccountEncoding,
+        UiDataSliceConfig,
     },
     solana_clock::Slot,
     solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path},
     solana_measure::measure::Measure,
     solana_pubkey::Pubkey,
     solana_rpc_client_api::response::{
-        ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse, RpcBlockUpdate,
-        RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcResponseContext,
-        RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+        ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse,
+        RpcAccountChanges, RpcAccountDataChange, RpcBlockUpdate, RpcBlockUpdateError,
+        RpcKeyedAccount, RpcLogsResponse, RpcResponseContext, RpcSignatureResult, RpcVote,
+        SlotInfo, SlotUpdate,
     },
     solana_runtime::{
         bank::{Bank, TransactionLogInfo},
@@ -45,6 +47,7 @@ use {
         cell::RefCell,
         collections::{HashMap, VecDeque},
         io::Cursor,
+        ops::Range,
         str,
         sync::{
             atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
@@ -62,6 +65,9 @@ mod transaction {
 
 const RECEIVE_DELAY_MILLIS: u64 = 100;
 
+/// Granularity at which account data is compared for subscriptions that only receive changes
+const DATA_CHANGE_CHUNK_BYTES: usize = 32;
+
 fn get_transaction_logs(
     bank: &Bank,
     params: &LogsSubscriptionParams,
@@ -388,12 +394,95 @@ fn filter_account_result(
         {
             get_parsed_token_account(&bank, &params.pubkey, account, None)
         } else {
-            encode_ui_account(&params.pubkey, &account, params.encoding, None, None)
+            encode_ui_account(
+                &params.pubkey,
+                &account,
+                params.encoding,
+                None,
+                params.data_slice,
+            )
         }
     });
     (account, last_modified_slot)
 }
 
+fn filter_account_changes(
+    result: Option<(AccountSharedData, Slot)>,
+    params: &AccountSubscriptionParams,
+    last_notified_slot: Slot,
+    last_notified_data: &RwLock<Option<Vec<u8>>>,
+) -> (Option<RpcAccountChanges>, Slot) {
+    let (account, last_modified_slot) = result.unwrap_or_default();
+    let changes = (last_modified_slot != last_notified_slot).then(|| {
+        let data = account.data();
+        let window = match params.data_slice {
+            Some(UiDataSliceConfig { offset, length }) => {
+                offset.min(data.len())..offset.saturating_add(length).min(data.len())
+            }
+            None => 0..data.len(),
+        };
+        let data = &data[window.clone()];
+
+        let mut last_notified_data = last_notified_data.write().unwrap();
+        let data_changes = changed_ranges(last_notified_data.as_deref(), data)
+            .into_iter()
+            .map(|range| {
+                let offset = window.start + range.start;
+                let data_slice = UiDataSliceConfig {
+                    offset,
+                    length: range.len(),
+                };
+                RpcAccountDataChange {
+                    offset,
+                    data: encode_ui_account(
+                        &params.pubkey,
+                        &account,
+                        params.encoding,
+                        None,
+                        Some(data_slice),
+                    )
+                    .data,
+                }
+            })
+            .collect();
+        *last_notified_data = Some(data.to_vec());
+
+        RpcAccountChanges {
+            lamports: account.lamports(),
+            owner: account.owner().to_string(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            space: account.data().len() as u64,
+            data_changes,
+        }
+    });
+    (changes, last_modified_slot)
+}
+
+/// Ranges of `current` that differ from `previous`, compared in chunks of
+/// `DATA_CHANGE_CHUNK_BYTES`. Everything is changed if there is no previous data.
+fn changed_ranges(previous: Option<&[u8]>, current: &[u8]) -> Vec<Range<usize>> {
+    let Some(previous) = previous else {
+        return if current.is_empty() {
+            vec![]
+        } else {
+            vec![0..current.len()]
+        };
+    };
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for start in (0..current.len()).step_by(DATA_CHANGE_CHUNK_BYTES) {
+        let end = current.len().min(start + DATA_CHANGE_CHUNK_BYTES);
+        if previous.get(start..end) == Some(&current[start..end]) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == start => range.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
 fn filter_signature_result(
     result: Option<transaction::Result<()>>,
     _params: &SignatureSubscriptionParams,
@@ -418,6 +507,7 @@ fn filter_program_results(
 ) -> (impl Iterator<Item = RpcKeyedAccount>, Slot) {
     let accounts_is_empty = accounts.is_empty();
     let encoding = params.encoding;
+    let data_slice = params.data_slice;
     let filters = params.filters.clone();
     let keyed_accounts = accounts.into_iter().filter(move |(_, account)| {
         filters
@@ -433,7 +523,7 @@ fn filter_program_results(
     } else {
         let accounts = keyed_accounts.map(move |(pubkey, account)| RpcKeyedAccount {
             pubkey: pubkey.to_string(),
-            account: encode_ui_account(&pubkey, &account, encoding, None, None),
+            account: encode_ui_account(&pubkey, &account, encoding, None, data_slice),
         });
         Either::Right(accounts)
     };
@@ -766,6 +856,9 @@ impl RpcSubscriptions {
         optimistically_confirmed_bank: Arc<RwLock<OptimisticallyConfirmedBank>>,
     ) {
         let mut stats = PubsubNotificationStats::default();
+        // Slots of the last notifications, at which held back notifications are sent
+        let mut last_commitment_slots = None;
+        let mut last_gossip_slot = None;
 
         loop {
             if exit.load(Ordering::Relaxed) {
@@ -849,6 +942,7 @@ impl RpcSubscriptions {
                                 &notifier,
                                 SOURCE,
                             );
+                            last_commitment_slots = Some(commitment_slots);
                         }
                         NotificationEntry::Gossip(slot) => {
                             let commitment_slots = CommitmentSlots {
@@ -865,6 +959,7 @@ impl RpcSubscriptions {
                                 &notifier,
                                 SOURCE,
                             );
+                            last_gossip_slot = Some(slot);
                         }
                         NotificationEntry::SignaturesReceived((slot, slot_signatures)) => {
                             for slot_signature in &slot_signatures {
@@ -906,10 +1001,80 @@ impl RpcSubscriptions {
                     break;
                 }
             }
+            RpcSubscriptions::notify_coalesced_watchers(
+                &max_complete_transaction_status_slot,
+                &subscriptions,
+                &bank_forks,
+                &blockstore,
+                last_commitment_slots.as_ref(),
+                last_gossip_slot,
+                &notifier,
+            );
             stats.maybe_submit();
         }
     }
 
+    /// Sends the notifications held back by coalescing windows that have passed, at the slots of
+    /// the last notifications
+    fn notify_coalesced_watchers(
+        max_complete_transaction_status_slot: &Arc<AtomicU64>,
+        subscriptions: &SubscriptionsTracker,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        blockstore: &Blockstore,
+        commitment_slots: Option<&CommitmentSlots>,
+        gossip_slot: Option<Slot>,
+        notifier: &RpcNotifier,
+    ) {
+        const SOURCE: &str = "coalesced";
+        let now = Instant::now();
+        let (gossip_watchers, commitment_watchers): (HashMap<_, _>, HashMap<_, _>) = subscriptions
+            .coalescing_watchers()
+            .iter()
+            .filter(|(_, subscription)| {
+                subscription
+                    .coalescing
+                    .as_ref()
+                    .is_some_and(|coalescing| coalescing.is_due(now))
+            })
+            .map(|(id, subscription)| (*id, Arc::clone(subscription)))
+            .partition(|(_, subscription)| {
+                subscription
+                    .commitment()
+                    .is_some_and(|commitment| commitment.is_confirmed())
+            });
+
+        if let Some(commitment_slots) = commitment_slots {
+            if !commitment_watchers.is_empty() {
+                RpcSubscriptions::notify_watchers(
+                    max_complete_transaction_status_slot.clone(),
+                    &commitment_watchers,
+                    bank_forks,
+                    blockstore,
+                    commitment_slots,
+                    notifier,
+                    SOURCE,
+                );
+            }
+        }
+        if let Some(slot) = gossip_slot {
+            if !gossip_watchers.is_empty() {
+                let commitment_slots = CommitmentSlots {
+                    highest_confirmed_slot: slot,
+                    ..CommitmentSlots::default()
+                };
+                RpcSubscriptions::notify_watchers(
+                    max_complete_transaction_status_slot.clone(),
+                    &gossip_watchers,
+                    bank_forks,
+                    blockstore,
+                    &commitment_slots,
+                    notifier,
+                    SOURCE,
+                );
+            }
+        }
+    }
+
     fn notify_watchers(
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         subscriptions: &HashMap<SubscriptionId, Arc<SubscriptionInfo>>,
@@ -954,19 +1119,50 @@ impl RpcSubscriptions {
                 SubscriptionParams::Account(params) => {
                     num_accounts_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
-                        let notified = check_commitment_and_notify(
-                            params,
-                            subscription,
-                            bank_forks,
-                            slot,
-                            |bank, params| bank.get_account_modified_slot(&params.pubkey),
-                            filter_account_result,
-                            notifier,
-                            false,
-                        );
+                        // The account is read again once the window has passed, so only its
+                        // latest state is sent
+                        if let Some(coalescing) = &subscription.coalescing {
+                            if coalescing.hold_back(Instant::now()) {
+                                return;
+                            }
+                            coalescing.release();
+                        }
+                        let notified = if params.changed_data_only.is_some() {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                |bank, params| bank.get_account_modified_slot(&params.pubkey),
+                                |result, params, last_notified_slot, _bank| {
+                                    filter_account_changes(
+                                        result,
+                                        params,
+                                        last_notified_slot,
+                                        &subscription.last_notified_data,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            )
+                        } else {
+                            check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                |bank, params| bank.get_account_modified_slot(&params.pubkey),
+                                filter_account_result,
+                                notifier,
+                                false,
+                            )
+                        };
 
                         if notified {
                             num_accounts_notified.fetch_add(1, Ordering::Relaxed);
+                            if let Some(coalescing) = &subscription.coalescing {
+                                coalescing.notified(Instant::now());
+                            }
                         }
                     }
                 }
@@ -1077,13 +1273,38 @@ impl RpcSubscriptions {
                 SubscriptionParams::Program(params) => {
                     num_programs_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
+                        // Only the accounts modified in the bank are notified, so the accounts
+                        // of the banks held back are kept until the window has passed
+                        let held_back_accounts = match &subscription.coalescing {
+                            Some(coalescing) => {
+                                if coalescing.hold_back(Instant::now()) {
+                                    if let Some(bank) = bank_forks.read().unwrap().get(slot) {
+                                        coalescing.hold_back_accounts(
+                                            bank.get_program_accounts_modified_since_parent(
+                                                &params.pubkey,
+                                            ),
+                                        );
+                                    }
+                                    return;
+                                }
+                                coalescing.release()
+                            }
+                            None => HashMap::new(),
+                        };
                         let notified = check_commitment_and_notify(
                             params,
                             subscription,
                             bank_forks,
                             slot,
                             |bank, params| {
-                                bank.get_program_accounts_modified_since_parent(&params.pubkey)
+                                let accounts =
+                                    bank.get_program_accounts_modified_since_parent(&params.pubkey);
+                                if held_back_accounts.is_empty() {
+                                    return accounts;
+                                }
+                                let mut held_back_accounts = held_back_accounts.clone();
+                                held_back_accounts.extend(accounts);
+                                held_back_accounts.into_iter().collect()
                             },
                             filter_program_results,
                             notifier,
@@ -1092,6 +1313,9 @@ impl RpcSubscriptions {
 
                         if notified {
                             num_programs_notified.fetch_add(1, Ordering::Relaxed);
+                            if let Some(coalescing) = &subscription.coalescing {
+                                coalescing.notified(Instant::now());
+                            }
                         }
                     }
                 }
@@ -1220,15 +1444,17 @@ pub(crate) mod tests {
             rpc_pubsub::RpcSolPubSubInternal,
             rpc_pubsub_service,
         },
+        base64::{prelude::BASE64_STANDARD, Engine},
         serial_test::serial,
+        solana_account_decoder::UiAccountData,
         solana_commitment_config::CommitmentConfig,
         solana_keypair::Keypair,
         solana_ledger::get_tmp_ledger_path_auto_delete,
         solana_message::Message,
         solana_rpc_client_api::config::{
-            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcBlockSubscribeConfig,
+            RpcBlockSubscribeFilter, RpcProgramAccountsConfig, RpcProgramSubscribeConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         solana_runtime::{
             commitment::BlockCommitment,
@@ -1367,11 +1593,15 @@ pub(crate) mod tests {
             let sub_id = rpc
                 .account_subscribe(
                     pubkey.to_string(),
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        encoding: None,
-                        data_slice: None,
-                        min_context_slot: None,
+                    Some(RpcAccountSubscribeConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            encoding: None,
+                            data_slice: None,
+                            min_context_slot: None,
+                        },
+                        changed_data_only: None,
+                        coalesce_ms: None,
                     }),
                 )
                 .unwrap();
@@ -1383,6 +1613,8 @@ pub(crate) mod tests {
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
+                    changed_data_only: None,
+                    coalesce_window: None,
                 }));
 
             rpc.block_until_processed(&subscriptions);
@@ -1414,10 +1646,101 @@ pub(crate) mod tests {
                     commitment: CommitmentConfig::processed(),
                     data_slice: None,
                     encoding: UiAccountEncoding::Binary,
+                    changed_data_only: None,
+                    coalesce_window: None,
                 }));
         }
     }
 
+    #[test]
+    fn test_changed_ranges() {
+        let chunk = DATA_CHANGE_CHUNK_BYTES;
+        let previous = vec![0; 4 * chunk + 5];
+
+        assert_eq!(changed_ranges(None, &previous), vec![0..previous.len()]);
+        assert!(changed_ranges(None, &[]).is_empty());
+        assert!(changed_ranges(Some(&previous), &previous).is_empty());
+
+        // adjacent changed chunks are merged
+        let mut current = previous.clone();
+        current[1] = 1;
+        current[chunk] = 1;
+        current[3 * chunk] = 1;
+        assert_eq!(
+            changed_ranges(Some(&previous), &current),
+            vec![0..2 * chunk, 3 * chunk..4 * chunk]
+        );
+
+        // the partial last chunk and any growth are reported
+        let mut current = previous.clone();
+        current[4 * chunk + 4] = 1;
+        current.push(1);
+        assert_eq!(
+            changed_ranges(Some(&previous), &current),
+            vec![4 * chunk..current.len()]
+        );
+    }
+
+    #[test]
+    fn test_filter_account_changes() {
+        let pubkey = solana_pubkey::new_rand();
+        let params = AccountSubscriptionParams {
+            pubkey,
+            commitment: CommitmentConfig::processed(),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 8,
+                length: 100,
+            }),
+            encoding: UiAccountEncoding::Base64,
+            changed_data_only: Some(0),
+            coalesce_window: None,
+        };
+        let last_notified_data = RwLock::default();
+        let mut data = vec![0; 200];
+        let account = AccountSharedData::create(1, data.clone(), Pubkey::default(), false, 0);
+
+        // the first notification carries the whole slice
+        let (changes, slot) =
+            filter_account_changes(Some((account, 1)), &params, 0, &last_notified_data);
+        assert_eq!(slot, 1);
+        let changes = changes.unwrap();
+        assert_eq!(changes.space, 200);
+        assert_eq!(changes.data_changes.len(), 1);
+        assert_eq!(changes.data_changes[0].offset, 8);
+
+        // unchanged slot
+        let (changes, _) = filter_account_changes(
+            Some((
+                AccountSharedData::create(1, data.clone(), Pubkey::default(), false, 0),
+                1,
+            )),
+            &params,
+            1,
+            &last_notified_data,
+        );
+        assert!(changes.is_none());
+
+        // only the modified chunk inside the slice is sent
+        data[8 + DATA_CHANGE_CHUNK_BYTES] = 1;
+        data[150] = 1;
+        let account = AccountSharedData::create(2, data, Pubkey::default(), false, 0);
+        let (changes, _) =
+            filter_account_changes(Some((account, 2)), &params, 1, &last_notified_data);
+        let changes = changes.unwrap();
+        let mut expected_chunk = vec![0; DATA_CHANGE_CHUNK_BYTES];
+        expected_chunk[0] = 1;
+        assert_eq!(changes.lamports, 2);
+        assert_eq!(changes.data_changes.len(), 1);
+        assert_eq!(changes.data_changes[0].offset, 8 + DATA_CHANGE_CHUNK_BYTES);
+        assert_eq!(
+            changes.data_changes[0].data,
+            UiAccountData::Binary(
+                BASE64_STANDARD.encode(expected_chunk),
+                UiAccountEncoding::Base64
+            )
+        );
+    }
+
     #[test]
     #[serial]
     fn test_check_confirmed_block_subscribe() {
@@ -1812,12 +2135,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -1831,6 +2157,7 @@ pub(crate) mod tests {
                 data_slice: None,
                 encoding: UiAccountEncoding::Binary,
                 with_context: false,
+                coalesce_window: None,
             }));
 
         subscriptions.notify_subscribers(CommitmentSlots::default());
@@ -1871,9 +2198,101 @@ pub(crate) mod tests {
                 data_slice: None,
                 encoding: UiAccountEncoding::Binary,
                 with_context: false,
+                coalesce_window: None,
             }));
     }
 
+    #[test]
+    #[serial]
+    fn test_check_program_subscribe_coalesced() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let blockhash = bank.last_blockhash();
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let create_stake_account = |slot: Slot, lamports: u64| {
+            let account = Keypair::new();
+            let tx = system_transaction::create_account(
+                &mint_keypair,
+                &account,
+                blockhash,
+                lamports,
+                16,
+                &stake::program::id(),
+            );
+            let bank = bank_forks.read().unwrap().get(slot).unwrap();
+            bank.process_transaction(&tx).unwrap();
+            account.pubkey()
+        };
+        let alice = create_stake_account(0, 1);
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            exit,
+            max_complete_transaction_status_slot,
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        rpc.program_subscribe(
+            stake::program::id().to_string(),
+            Some(RpcProgramSubscribeConfig {
+                program_config: RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::processed()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+                coalesce_ms: Some(1_000),
+            }),
+        )
+        .unwrap();
+        let notified_pubkey = |response: String| {
+            let response = serde_json::from_str::<serde_json::Value>(&response).unwrap();
+            let slot = response["params"]["result"]["context"]["slot"]
+                .as_u64()
+                .unwrap();
+            let pubkey = response["params"]["result"]["value"]["pubkey"]
+                .as_str()
+                .unwrap()
+                .parse::<Pubkey>()
+                .unwrap();
+            (slot, pubkey)
+        };
+
+        // The first notification is sent right away and starts the window
+        subscriptions.notify_subscribers(CommitmentSlots::default());
+        assert_eq!(notified_pubkey(receiver.recv()), (0, alice));
+
+        // Accounts modified within the window are held back
+        let mut held_back = HashSet::new();
+        for slot in 1..=2 {
+            let parent = bank_forks.read().unwrap().get(slot - 1).unwrap();
+            let bank = Bank::new_from_parent(parent, &Pubkey::default(), slot);
+            bank_forks.write().unwrap().insert(bank);
+            held_back.insert((2, create_stake_account(slot, slot)));
+            subscriptions.notify_subscribers(CommitmentSlots {
+                slot,
+                ..CommitmentSlots::default()
+            });
+        }
+        assert!(receiver.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // Once the window passed, they are sent together at the last slot
+        let notified = (0..held_back.len())
+            .map(|_| notified_pubkey(receiver.recv()))
+            .collect::<HashSet<_>>();
+        assert_eq!(notified, held_back);
+    }
+
     #[test]
     #[serial]
     fn test_check_program_subscribe_for_missing_optimistically_confirmed_slot() {
@@ -1963,12 +2382,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -1982,6 +2404,7 @@ pub(crate) mod tests {
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
+                coalesce_window: None,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -2143,12 +2566,15 @@ pub(crate) mod tests {
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
         rpc.program_subscribe(
             stake::program::id().to_string(),
-            Some(RpcProgramAccountsConfig {
-                account_config: RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    ..RpcAccountInfoConfig::default()
+            Some(RpcProgramSubscribeConfig {
+                program_config: RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
                 },
-                ..RpcProgramAccountsConfig::default()
+                ..RpcProgramSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -2162,6 +2588,7 @@ pub(crate) mod tests {
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
+                coalesce_window: None,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -2261,12 +2688,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -2280,6 +2710,7 @@ pub(crate) mod tests {
                 data_slice: None,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
+                coalesce_window: None,
             }));
 
         let mut highest_confirmed_slot: Slot = 0;
@@ -2742,11 +3173,15 @@ pub(crate) mod tests {
         let sub_id0 = rpc0
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    changed_data_only: None,
+                    coalesce_ms: None,
                 }),
             )
             .unwrap();
@@ -2843,11 +3278,15 @@ pub(crate) mod tests {
         let sub_id1 = rpc1
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    changed_data_only: None,
+                    coalesce_ms: None,
                 }),
             )
             .unwrap();
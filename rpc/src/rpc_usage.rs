@@ -0,0 +1,395 @@
+//! Per-client accounting of the requests served by the JSON RPC service
+//!
+//! Operators of private RPC nodes meter their downstream users by the number of requests they
+//! send, the size of the responses they receive and the time spent handling their requests. An
+//! [`RpcUsageTracker`] accumulates those figures per client over a reporting period, and the
+//! RPC service writes one JSON report per period when a report directory is configured.
+//!
+//! A client is identified by the API token it sends in a configurable request header, when that is
+//! one of the configured tokens, and otherwise by its address. Requests with any other token are
+//! accounted together as `invalid-token`, and since clients can use many addresses, at most
+//! [`MAX_RPC_USAGE_CLIENTS`] clients are tracked per period and any further clients are accounted
+//! together as `other`.
+
+use {
+    crate::rpc::JsonRpcRequestProcessor,
+    jsonrpc_core::{
+        futures::future::Either, FutureOutput, FutureResponse, Middleware, Request, Response,
+    },
+    jsonrpc_http_server::hyper::HeaderMap,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{BTreeMap, HashMap},
+        fs::File,
+        future::Future,
+        io::{self, BufWriter, Write},
+        net::IpAddr,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const DEFAULT_RPC_USAGE_REPORT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Maximum number of clients tracked per reporting period
+pub const MAX_RPC_USAGE_CLIENTS: usize = 10_000;
+const UNKNOWN_CLIENT: &str = "unknown";
+const INVALID_TOKEN_CLIENTS: &str = "invalid-token";
+const OTHER_CLIENTS: &str = "other";
+const REPORT_THREAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcUsageConfig {
+    /// Request header with the API token identifying the client, instead of its address
+    pub client_header: Option<String>,
+    /// API tokens of the known clients, mapped to the names they are accounted to
+    pub client_tokens: HashMap<String, String>,
+    /// Directory the periodic usage reports are written to
+    pub report_dir: Option<PathBuf>,
+    pub report_interval: Duration,
+}
+
+impl Default for RpcUsageConfig {
+    fn default() -> Self {
+        Self {
+            client_header: None,
+            client_tokens: HashMap::new(),
+            report_dir: None,
+            report_interval: DEFAULT_RPC_USAGE_REPORT_INTERVAL,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClientUsage {
+    /// Number of calls, where each call of a batch counts separately
+    pub num_requests: u64,
+    /// Size of the serialized responses
+    pub response_bytes: u64,
+    /// Time spent handling the requests
+    pub compute_micros: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUsageReport {
+    /// Start of the reporting period, in seconds since the Unix epoch
+    pub start_time: u64,
+    /// End of the reporting period, in seconds since the Unix epoch
+    pub end_time: u64,
+    pub clients: BTreeMap<String, RpcClientUsage>,
+}
+
+#[derive(Debug)]
+struct UsagePeriod {
+    start_time: u64,
+    clients: BTreeMap<String, RpcClientUsage>,
+}
+
+impl UsagePeriod {
+    fn new() -> Self {
+        Self {
+            start_time: unix_timestamp(),
+            clients: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RpcUsageTracker {
+    config: RpcUsageConfig,
+    period: Mutex<UsagePeriod>,
+}
+
+impl RpcUsageTracker {
+    pub fn new(config: RpcUsageConfig) -> Self {
+        Self {
+            config,
+            period: Mutex::new(UsagePeriod::new()),
+        }
+    }
+
+    pub fn config(&self) -> &RpcUsageConfig {
+        &self.config
+    }
+
+    /// Returns the name a request with `headers` from `client_ip` is accounted to
+    pub fn client_key(&self, headers: &HeaderMap, client_ip: Option<IpAddr>) -> String {
+        if let Some(token) = self
+            .config
+            .client_header
+            .as_ref()
+            .and_then(|header| headers.get(header.as_str()))
+        {
+            return token
+                .to_str()
+                .ok()
+                .and_then(|token| self.config.client_tokens.get(token))
+                .cloned()
+                .unwrap_or_else(|| INVALID_TOKEN_CLIENTS.to_string());
+        }
+        client_ip
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| UNKNOWN_CLIENT.to_string())
+    }
+
+    pub fn record(&self, client: &str, usage: RpcClientUsage) {
+        let mut period = self.period.lock().unwrap();
+        let client = if period.clients.contains_key(client) {
+            client
+        } else if period.clients.len() < MAX_RPC_USAGE_CLIENTS {
+            period
+                .clients
+                .insert(client.to_string(), RpcClientUsage::default());
+            client
+        } else {
+            period.clients.entry(OTHER_CLIENTS.to_string()).or_default();
+            OTHER_CLIENTS
+        };
+        let client_usage = period.clients.get_mut(client).unwrap();
+        client_usage.num_requests = client_usage.num_requests.saturating_add(usage.num_requests);
+        client_usage.response_bytes = client_usage
+            .response_bytes
+            .saturating_add(usage.response_bytes);
+        client_usage.compute_micros = client_usage
+            .compute_micros
+            .saturating_add(usage.compute_micros);
+    }
+
+    /// Returns the usage of the current period so far
+    pub fn snapshot(&self) -> RpcUsageReport {
+        let period = self.period.lock().unwrap();
+        RpcUsageReport {
+            start_time: period.start_time,
+            end_time: unix_timestamp(),
+            clients: period.clients.clone(),
+        }
+    }
+
+    /// Ends the current period and returns its usage
+    pub fn take_report(&self) -> RpcUsageReport {
+        let period = std::mem::replace(&mut *self.period.lock().unwrap(), UsagePeriod::new());
+        RpcUsageReport {
+            start_time: period.start_time,
+            end_time: unix_timestamp(),
+            clients: period.clients,
+        }
+    }
+
+    /// Ends the current period and writes its usage to a file in `report_dir`
+    pub fn write_report(&self, report_dir: &Path) -> io::Result<PathBuf> {
+        let report = self.take_report();
+        let path = report_dir.join(format!(
+            "rpc-usage-{}-{}.json",
+            report.start_time, report.end_time
+        ));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(&mut writer, &report)?;
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+/// Writes a usage report to `report_dir` every reporting period, and a last one on exit
+pub(crate) fn spawn_report_thread(
+    tracker: Arc<RpcUsageTracker>,
+    report_dir: PathBuf,
+    exit: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    Builder::new()
+        .name("solRpcUsageRpt".to_string())
+        .spawn(move || {
+            let report_interval = tracker.config().report_interval;
+            let mut period_start = Instant::now();
+            loop {
+                let exiting = exit.load(Ordering::Relaxed);
+                if exiting || period_start.elapsed() >= report_interval {
+                    match tracker.write_report(&report_dir) {
+                        Ok(path) => info!("wrote rpc usage report to {}", path.display()),
+                        Err(err) => warn!(
+                            "failed to write rpc usage report to {}: {err}",
+                            report_dir.display()
+                        ),
+                    }
+                    period_start = Instant::now();
+                }
+                if exiting {
+                    break;
+                }
+                thread::sleep(REPORT_THREAD_POLL_INTERVAL);
+            }
+        })
+        .unwrap()
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Counts the bytes written to it, to size responses without buffering them
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 = self.0.saturating_add(buf.len() as u64);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn response_size(response: &Response) -> u64 {
+    let mut counter = ByteCounter::default();
+    match serde_json::to_writer(&mut counter, response) {
+        Ok(()) => counter.0,
+        Err(_) => 0,
+    }
+}
+
+/// Accounts every request to the client the meta extractor identified
+#[derive(Default)]
+pub struct RpcUsageMiddleware {
+    tracker: Option<Arc<RpcUsageTracker>>,
+}
+
+impl RpcUsageMiddleware {
+    pub fn new(tracker: Option<Arc<RpcUsageTracker>>) -> Self {
+        Self { tracker }
+    }
+}
+
+impl Middleware<JsonRpcRequestProcessor> for RpcUsageMiddleware {
+    type Future = FutureResponse;
+    type CallFuture = FutureOutput;
+
+    fn on_request<F, X>(
+        &self,
+        request: Request,
+        meta: JsonRpcRequestProcessor,
+        next: F,
+    ) -> Either<Self::Future, X>
+    where
+        F: Fn(Request, JsonRpcRequestProcessor) -> X + Send + Sync,
+        X: Future<Output = Option<Response>> + Send + 'static,
+    {
+        let (Some(tracker), Some(client)) = (self.tracker.clone(), meta.usage_client().cloned())
+        else {
+            return Either::Right(next(request, meta));
+        };
+        let num_requests = match &request {
+            Request::Single(_) => 1,
+            Request::Batch(calls) => calls.len() as u64,
+        };
+        let start = Instant::now();
+        let response = next(request, meta);
+        Either::Left(Box::pin(async move {
+            let response = response.await;
+            let compute_micros = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+            let response_bytes = response.as_ref().map(response_size).unwrap_or_default();
+            tracker.record(
+                &client,
+                RpcClientUsage {
+                    num_requests,
+                    response_bytes,
+                    compute_micros,
+                },
+            );
+            response
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_ledger::get_tmp_ledger_path_auto_delete, std::net::Ipv4Addr};
+
+    #[test]
+    fn test_client_key() {
+        let client_ip = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+        let tracker = RpcUsageTracker::new(RpcUsageConfig {
+            client_header: Some("x-api-key".to_string()),
+            client_tokens: HashMap::from([("secret-a".to_string(), "customer-a".to_string())]),
+            ..RpcUsageConfig::default()
+        });
+        let mut headers = HeaderMap::new();
+        assert_eq!(tracker.client_key(&headers, None), UNKNOWN_CLIENT);
+        assert_eq!(tracker.client_key(&headers, client_ip), "192.0.2.1");
+        headers.insert("x-api-key", "secret-a".parse().unwrap());
+        assert_eq!(tracker.client_key(&headers, client_ip), "customer-a");
+        // made up tokens are accounted together
+        headers.insert("x-api-key", "secret-b".parse().unwrap());
+        assert_eq!(
+            tracker.client_key(&headers, client_ip),
+            INVALID_TOKEN_CLIENTS
+        );
+
+        let tracker = RpcUsageTracker::new(RpcUsageConfig::default());
+        assert_eq!(tracker.client_key(&headers, client_ip), "192.0.2.1");
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let tracker = RpcUsageTracker::new(RpcUsageConfig::default());
+        let usage = RpcClientUsage {
+            num_requests: 2,
+            response_bytes: 100,
+            compute_micros: 30,
+        };
+        tracker.record("a", usage);
+        tracker.record("a", usage);
+        tracker.record("b", usage);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.clients.len(), 2);
+        assert_eq!(
+            snapshot.clients["a"],
+            RpcClientUsage {
+                num_requests: 4,
+                response_bytes: 200,
+                compute_micros: 60,
+            }
+        );
+        assert_eq!(snapshot.clients["b"], usage);
+
+        let report_dir = get_tmp_ledger_path_auto_delete!();
+        let path = tracker.write_report(report_dir.path()).unwrap();
+        let report: RpcUsageReport = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        assert_eq!(report.clients, snapshot.clients);
+        assert!(tracker.snapshot().clients.is_empty());
+    }
+
+    #[test]
+    fn test_usage_client_limit() {
+        let tracker = RpcUsageTracker::new(RpcUsageConfig::default());
+        let usage = RpcClientUsage {
+            num_requests: 1,
+            response_bytes: 10,
+            compute_micros: 3,
+        };
+        for i in 0..MAX_RPC_USAGE_CLIENTS {
+            tracker.record(&i.to_string(), usage);
+        }
+        // clients beyond the limit are accounted together
+        tracker.record("late-1", usage);
+        tracker.record("late-2", usage);
+        // known clients are still accounted separately
+        tracker.record("0", usage);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.clients.len(), MAX_RPC_USAGE_CLIENTS + 1);
+        assert!(!snapshot.clients.contains_key("late-1"));
+        assert_eq!(snapshot.clients[OTHER_CLIENTS].num_requests, 2);
+        assert_eq!(snapshot.clients["0"].num_requests, 2);
+    }
+}
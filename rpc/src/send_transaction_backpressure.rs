@@ -0,0 +1,73 @@
+//! Backpressure from the banking stage on `sendTransaction`.
+//!
+//! Once the banking stage has buffered as many transactions as it can, it drops the lowest
+//! priority ones. Accepting more transactions at that point only has them dropped after the
+//! client was told they were sent. Past a queue depth, `sendTransaction` is rejected with the
+//! delay after which the client should retry instead.
+
+use {
+    solana_clock::DEFAULT_MS_PER_SLOT,
+    solana_rpc_client_api::custom_error::RpcCustomError,
+    std::{num::NonZeroUsize, time::Duration},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcBackpressureConfig {
+    /// Number of transactions queued by the banking stage from which `sendTransaction` is
+    /// rejected, never if `None`
+    pub max_banking_queue_depth: Option<NonZeroUsize>,
+    /// How long rejected clients are told to wait before retrying
+    pub retry_after: Duration,
+}
+
+impl Default for RpcBackpressureConfig {
+    fn default() -> Self {
+        Self {
+            max_banking_queue_depth: None,
+            retry_after: Duration::from_millis(DEFAULT_MS_PER_SLOT),
+        }
+    }
+}
+
+/// Rejects a transaction if the banking stage has `queue_depth` transactions queued, more than
+/// the configured maximum
+pub(crate) fn check_banking_queue_depth(
+    config: &RpcBackpressureConfig,
+    queue_depth: usize,
+) -> Result<(), RpcCustomError> {
+    match config.max_banking_queue_depth {
+        Some(max_queue_depth) if queue_depth >= max_queue_depth.get() => {
+            inc_new_counter_info!("rpc-send-tx_rejected_congested", 1);
+            Err(RpcCustomError::NodeCongested {
+                queue_depth,
+                retry_after_ms: config.retry_after.as_millis() as u64,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_banking_queue_depth() {
+        let config = RpcBackpressureConfig {
+            max_banking_queue_depth: NonZeroUsize::new(100),
+            retry_after: Duration::from_millis(800),
+        };
+        assert!(check_banking_queue_depth(&config, 0).is_ok());
+        assert!(check_banking_queue_depth(&config, 99).is_ok());
+        assert!(matches!(
+            check_banking_queue_depth(&config, 100),
+            Err(RpcCustomError::NodeCongested {
+                queue_depth: 100,
+                retry_after_ms: 800,
+            })
+        ));
+
+        let config = RpcBackpressureConfig::default();
+        assert!(check_banking_queue_depth(&config, usize::MAX).is_ok());
+    }
+}
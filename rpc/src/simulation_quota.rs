@@ -0,0 +1,156 @@
+//! Limits on the transaction simulations the JSON RPC service runs.
+//!
+//! Simulations execute on the same SVM as replay. On a node that both votes and serves RPC, a
+//! client simulating many expensive transactions at once takes the cores replay needs to keep up
+//! with the cluster. Each simulated transaction may be held to a compute unit limit, and the
+//! number of simulations running at once across all clients may be capped; simulations beyond
+//! the cap are rejected rather than queued, so that clients back off.
+
+use {
+    agave_feature_set::FeatureSet,
+    solana_rpc_client_api::custom_error::RpcCustomError,
+    solana_runtime_transaction::transaction_meta::StaticMeta,
+    std::{
+        num::NonZeroUsize,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RpcSimulationConfig {
+    /// Largest compute unit limit a transaction passed to `simulateTransaction` may request
+    pub max_compute_units: Option<u32>,
+    /// Number of `simulateTransaction` requests that may run at once. The preflight checks of
+    /// `sendTransaction` are exempt, so that a flood of simulations cannot keep transactions from
+    /// being submitted
+    pub max_concurrent: Option<NonZeroUsize>,
+}
+
+/// Counts the simulations running, shared by all clones of the request processor
+#[derive(Debug, Default)]
+pub(crate) struct SimulationQuota {
+    max_concurrent: Option<NonZeroUsize>,
+    running: AtomicUsize,
+}
+
+impl SimulationQuota {
+    pub(crate) fn new(max_concurrent: Option<NonZeroUsize>) -> Self {
+        Self {
+            max_concurrent,
+            running: AtomicUsize::default(),
+        }
+    }
+
+    /// Reserves a slot for a simulation, until the returned permit is dropped
+    pub(crate) fn try_acquire(&self) -> Result<SimulationPermit<'_>, RpcCustomError> {
+        let running = self.running.fetch_add(1, Ordering::AcqRel);
+        let permit = SimulationPermit {
+            running: &self.running,
+        };
+        match self.max_concurrent {
+            Some(max_concurrent) if running >= max_concurrent.get() => {
+                drop(permit);
+                inc_new_counter_info!("rpc-simulation-rejected_quota", 1);
+                Err(RpcCustomError::SimulationQuotaExceeded {
+                    max_concurrent: max_concurrent.get(),
+                })
+            }
+            _ => Ok(permit),
+        }
+    }
+}
+
+pub(crate) struct SimulationPermit<'a> {
+    running: &'a AtomicUsize,
+}
+
+impl Drop for SimulationPermit<'_> {
+    fn drop(&mut self) {
+        self.running.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Rejects `transaction` if it requests more than `max_compute_units`
+///
+/// Transactions without a compute unit limit instruction request the default limit of their
+/// instructions. Transactions with invalid compute budget instructions are left to fail in the
+/// simulation itself.
+pub(crate) fn check_compute_unit_limit(
+    transaction: &impl StaticMeta,
+    feature_set: &FeatureSet,
+    max_compute_units: u32,
+) -> Result<(), RpcCustomError> {
+    let Ok(limits) = transaction
+        .compute_budget_instruction_details()
+        .sanitize_and_convert_to_compute_budget_limits(feature_set)
+    else {
+        return Ok(());
+    };
+    if limits.compute_unit_limit > max_compute_units {
+        return Err(RpcCustomError::SimulationComputeUnitLimitExceeded {
+            requested: limits.compute_unit_limit,
+            max: max_compute_units,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_compute_budget_interface::ComputeBudgetInstruction, solana_hash::Hash,
+        solana_instruction::Instruction, solana_keypair::Keypair, solana_message::Message,
+        solana_pubkey::Pubkey, solana_runtime_transaction::runtime_transaction::RuntimeTransaction,
+        solana_signer::Signer, solana_transaction::Transaction,
+    };
+
+    #[test]
+    fn test_simulation_quota() {
+        let quota = SimulationQuota::new(NonZeroUsize::new(2));
+        let first = quota.try_acquire().unwrap();
+        let second = quota.try_acquire().unwrap();
+        assert!(matches!(
+            quota.try_acquire(),
+            Err(RpcCustomError::SimulationQuotaExceeded { max_concurrent: 2 })
+        ));
+        drop(first);
+        let _third = quota.try_acquire().unwrap();
+        drop(second);
+
+        let quota = SimulationQuota::new(None);
+        let _permits: Vec<_> = (0..100).map(|_| quota.try_acquire().unwrap()).collect();
+    }
+
+    #[test]
+    fn test_check_compute_unit_limit() {
+        let payer = Keypair::new();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let transaction = |instructions: &[Instruction]| {
+            let message = Message::new(instructions, Some(&payer.pubkey()));
+            RuntimeTransaction::from_transaction_for_tests(Transaction::new(
+                &[&payer],
+                message,
+                Hash::default(),
+            ))
+        };
+        let feature_set = FeatureSet::all_enabled();
+
+        let requested = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+            instruction.clone(),
+        ]);
+        assert!(check_compute_unit_limit(&requested, &feature_set, 300_000).is_ok());
+        assert!(matches!(
+            check_compute_unit_limit(&requested, &feature_set, 250_000),
+            Err(RpcCustomError::SimulationComputeUnitLimitExceeded {
+                requested: 300_000,
+                max: 250_000,
+            })
+        ));
+
+        // Without a limit instruction, the default limit of the instructions is requested
+        let default = transaction(&[instruction.clone(), instruction]);
+        assert!(check_compute_unit_limit(&default, &feature_set, 400_000).is_ok());
+        assert!(check_compute_unit_limit(&default, &feature_set, 300_000).is_err());
+    }
+}
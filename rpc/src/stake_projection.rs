@@ -0,0 +1,171 @@
+//! Projects the activation and deactivation of a stake delegation over the coming epochs.
+//!
+//! The stake of a delegation that warms up or cools down in an epoch depends on the stake of
+//! the whole cluster that was effective, activating and deactivating in the previous epoch. The
+//! stake history only holds those totals for past epochs, so the totals of the coming epochs are
+//! projected from the latest entry, assuming that no other stake is delegated or undelegated in
+//! the meantime. The delegation itself is then run through the same activation logic as the
+//! stake program, against the projected history.
+
+use {
+    solana_clock::Epoch,
+    solana_stake_interface::{
+        stake_history::{StakeHistory, StakeHistoryEntry, StakeHistoryGetEntry},
+        state::{warmup_cooldown_rate, Delegation, StakeActivationStatus},
+    },
+};
+
+/// Returns the status of `delegation` in each epoch from `current_epoch` on, until it is fully
+/// active or inactive, or for at most `max_epochs` epochs
+///
+/// `stake_history` is the stake history of `current_epoch`, holding the totals of the epochs
+/// before it.
+pub(crate) fn project_stake_activation(
+    delegation: &Delegation,
+    stake_history: &StakeHistory,
+    current_epoch: Epoch,
+    max_epochs: u64,
+    new_rate_activation_epoch: Option<Epoch>,
+) -> Vec<(Epoch, StakeActivationStatus)> {
+    let mut stake_history = stake_history.clone();
+    let mut projection = vec![];
+    for epoch in (current_epoch..).take(max_epochs as usize) {
+        let status = delegation.stake_activating_and_deactivating(
+            epoch,
+            &stake_history,
+            new_rate_activation_epoch,
+        );
+        projection.push((epoch, status));
+        if status.activating == 0 && status.deactivating == 0 {
+            break;
+        }
+
+        if stake_history.get_entry(epoch).is_none() {
+            let Some(previous_entry) = epoch
+                .checked_sub(1)
+                .and_then(|epoch| stake_history.get_entry(epoch))
+            else {
+                // Without history, stake is treated as fully effective from the next epoch on
+                continue;
+            };
+            let mut entry =
+                project_cluster_entry(&previous_entry, epoch, new_rate_activation_epoch);
+            // Stake delegated or undelegated in this epoch is not part of the previous totals
+            if delegation.activation_epoch == epoch {
+                entry.activating = entry.activating.saturating_add(status.activating);
+            }
+            if delegation.deactivation_epoch == epoch {
+                entry.deactivating = entry.deactivating.saturating_add(status.deactivating);
+            }
+            stake_history.add(epoch, entry);
+        }
+    }
+    projection
+}
+
+/// Projects the stake totals of the cluster in `epoch` from those of the epoch before it
+fn project_cluster_entry(
+    previous_entry: &StakeHistoryEntry,
+    epoch: Epoch,
+    new_rate_activation_epoch: Option<Epoch>,
+) -> StakeHistoryEntry {
+    let rate = warmup_cooldown_rate(epoch, new_rate_activation_epoch);
+    let max_change = (previous_entry.effective as f64 * rate) as u64;
+    let activated = previous_entry.activating.min(max_change);
+    let deactivated = previous_entry.deactivating.min(max_change);
+    StakeHistoryEntry {
+        effective: previous_entry
+            .effective
+            .saturating_add(activated)
+            .saturating_sub(deactivated),
+        activating: previous_entry.activating.saturating_sub(activated),
+        deactivating: previous_entry.deactivating.saturating_sub(deactivated),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_pubkey::Pubkey};
+
+    fn stake_history(entries: &[(Epoch, u64, u64, u64)]) -> StakeHistory {
+        let mut stake_history = StakeHistory::default();
+        for (epoch, effective, activating, deactivating) in entries {
+            stake_history.add(
+                *epoch,
+                StakeHistoryEntry {
+                    effective: *effective,
+                    activating: *activating,
+                    deactivating: *deactivating,
+                },
+            );
+        }
+        stake_history
+    }
+
+    #[test]
+    fn test_project_stake_activation() {
+        // The delegation is all of the stake activating in the cluster, and a quarter of the
+        // effective stake warms up in each epoch
+        let delegation = Delegation::new(&Pubkey::new_unique(), 1_000, 9);
+        let stake_history = stake_history(&[(9, 2_000, 1_000, 0)]);
+
+        let projection = project_stake_activation(&delegation, &stake_history, 10, 10, None);
+        let effective: Vec<_> = projection
+            .iter()
+            .map(|(epoch, status)| (*epoch, status.effective, status.activating))
+            .collect();
+        assert_eq!(effective, vec![(10, 500, 500), (11, 1_000, 0)]);
+
+        // The projection stops after `max_epochs`
+        let projection = project_stake_activation(&delegation, &stake_history, 10, 1, None);
+        assert_eq!(projection.len(), 1);
+
+        // A delegation that is fully active is already settled
+        let stake_history =
+            stake_history(&[(9, 2_000, 1_000, 0), (10, 2_500, 500, 0), (11, 3_000, 0, 0)]);
+        let projection = project_stake_activation(&delegation, &stake_history, 12, 10, None);
+        assert_eq!(projection.len(), 1);
+        assert_eq!(projection[0].1.effective, 1_000);
+    }
+
+    #[test]
+    fn test_project_stake_deactivation_delegated_in_current_epoch() {
+        // Deactivated in the current epoch, which the stake history does not cover yet. As the
+        // cluster's effective stake shrinks, less of the delegation cools down each epoch
+        let mut delegation = Delegation::new(&Pubkey::new_unique(), 1_000, 0);
+        delegation.deactivation_epoch = 10;
+        let stake_history = stake_history(&[(8, 2_000, 0, 0), (9, 2_000, 0, 0)]);
+
+        let projection = project_stake_activation(&delegation, &stake_history, 10, 10, None);
+        let deactivating: Vec<_> = projection
+            .iter()
+            .map(|(epoch, status)| (*epoch, status.effective, status.deactivating))
+            .collect();
+        assert_eq!(
+            deactivating,
+            vec![
+                (10, 1_000, 1_000),
+                (11, 500, 500),
+                (12, 125, 125),
+                (13, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_cluster_entry() {
+        let previous_entry = StakeHistoryEntry {
+            effective: 1_000,
+            activating: 100,
+            deactivating: 1_000,
+        };
+        assert_eq!(
+            project_cluster_entry(&previous_entry, 10, None),
+            StakeHistoryEntry {
+                effective: 850,
+                activating: 0,
+                deactivating: 750,
+            }
+        );
+    }
+}
@@ -2,11 +2,18 @@
 //! transaction metadata objects to persist into the Blockstore and optionally
 //! broadcast over geyser. The service also records block metadata for any
 //! frozen banks it receives.
+//!
+//! Whether transaction history and extended metadata are written can be changed while the
+//! service runs, through a [`TransactionHistorySwitch`]. The service reads the switch once for
+//! each slot, when it receives the first transactions of that slot, so the history of a slot is
+//! either complete or missing. Slots received while the history is off leave a gap that is never
+//! filled in.
 
 use {
     crate::transaction_notifier_interface::TransactionNotifierArc,
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::izip,
+    serde::{Deserialize, Serialize},
     solana_clock::Slot,
     solana_ledger::{
         blockstore::{Blockstore, BlockstoreError},
@@ -22,9 +29,10 @@ use {
         TransactionStatusMeta,
     },
     std::{
+        collections::HashMap,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
-            Arc,
+            Arc, RwLock,
         },
         thread::{self, Builder, JoinHandle},
         time::Duration,
@@ -48,6 +56,120 @@ const TSS_TEST_QUIESCE_NUM_RETRIES: usize = 100;
 #[cfg(feature = "dev-context-only-utils")]
 const TSS_TEST_QUIESCE_SLEEP_TIME_MS: u64 = 50;
 
+// Slots that are this far behind the latest slot the service received transactions for are
+// assumed to have been abandoned without being frozen.
+const MAX_UNFROZEN_SLOT_DISTANCE: Slot = 1024;
+
+#[derive(Debug, Default)]
+pub struct TransactionHistorySwitch {
+    enable_rpc_transaction_history: AtomicBool,
+    enable_extended_tx_metadata_storage: AtomicBool,
+    /// First slot of the current run of slots whose history is recorded
+    recording_since: RwLock<Option<Slot>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionHistoryStatus {
+    pub enable_rpc_transaction_history: bool,
+    pub enable_extended_tx_metadata_storage: bool,
+    /// The history is complete from this slot on, if it is recorded
+    pub recording_since: Option<Slot>,
+}
+
+impl TransactionHistorySwitch {
+    pub fn new(
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    ) -> Self {
+        Self {
+            enable_rpc_transaction_history: AtomicBool::new(enable_rpc_transaction_history),
+            enable_extended_tx_metadata_storage: AtomicBool::new(
+                enable_extended_tx_metadata_storage,
+            ),
+            recording_since: RwLock::default(),
+        }
+    }
+
+    /// Applies from the next slot the service receives transactions for
+    pub fn set(
+        &self,
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    ) {
+        self.enable_rpc_transaction_history
+            .store(enable_rpc_transaction_history, Ordering::Relaxed);
+        self.enable_extended_tx_metadata_storage
+            .store(enable_extended_tx_metadata_storage, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> TransactionHistoryStatus {
+        let SlotHistorySettings {
+            enable_rpc_transaction_history,
+            enable_extended_tx_metadata_storage,
+        } = self.settings();
+        TransactionHistoryStatus {
+            enable_rpc_transaction_history,
+            enable_extended_tx_metadata_storage,
+            recording_since: *self.recording_since.read().unwrap(),
+        }
+    }
+
+    fn settings(&self) -> SlotHistorySettings {
+        SlotHistorySettings {
+            enable_rpc_transaction_history: self
+                .enable_rpc_transaction_history
+                .load(Ordering::Relaxed),
+            enable_extended_tx_metadata_storage: self
+                .enable_extended_tx_metadata_storage
+                .load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SlotHistorySettings {
+    enable_rpc_transaction_history: bool,
+    enable_extended_tx_metadata_storage: bool,
+}
+
+/// The settings in effect for the slots that have not been frozen yet
+#[derive(Default)]
+struct SlotHistory {
+    settings: HashMap<Slot, SlotHistorySettings>,
+}
+
+impl SlotHistory {
+    fn settings(&mut self, slot: Slot, switch: &TransactionHistorySwitch) -> SlotHistorySettings {
+        if let Some(settings) = self.settings.get(&slot) {
+            return *settings;
+        }
+        let settings = switch.settings();
+        self.settings.retain(|pending_slot, _| {
+            pending_slot.saturating_add(MAX_UNFROZEN_SLOT_DISTANCE) > slot
+        });
+        self.settings.insert(slot, settings);
+
+        let mut recording_since = switch.recording_since.write().unwrap();
+        match (settings.enable_rpc_transaction_history, *recording_since) {
+            (true, None) => {
+                info!("Recording transaction history from slot {slot}");
+                *recording_since = Some(slot);
+            }
+            (false, Some(since)) => {
+                info!("Stopped recording transaction history at slot {slot}, recorded since slot {since}");
+                *recording_since = None;
+            }
+            _ => {}
+        }
+        settings
+    }
+
+    fn freeze(&mut self, slot: Slot) {
+        self.settings.remove(&slot);
+    }
+}
+
 pub struct TransactionStatusService {
     thread_hdl: JoinHandle<()>,
     #[cfg(feature = "dev-context-only-utils")]
@@ -60,10 +182,9 @@ impl TransactionStatusService {
     pub fn new(
         transaction_status_receiver: Receiver<TransactionStatusMessage>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
-        enable_rpc_transaction_history: bool,
+        transaction_history: Arc<TransactionHistorySwitch>,
         transaction_notifier: Option<TransactionNotifierArc>,
         blockstore: Arc<Blockstore>,
-        enable_extended_tx_metadata_storage: bool,
         depenency_tracker: Option<Arc<DependencyTracker>>,
         exit: Arc<AtomicBool>,
     ) -> Self {
@@ -73,6 +194,7 @@ impl TransactionStatusService {
                 let transaction_status_receiver = transaction_status_receiver.clone();
                 move || {
                     info!("{} has started", Self::SERVICE_NAME);
+                    let mut slot_history = SlotHistory::default();
                     loop {
                         if exit.load(Ordering::Relaxed) {
                             break;
@@ -94,10 +216,10 @@ impl TransactionStatusService {
                         match Self::write_transaction_status_batch(
                             message,
                             &max_complete_transaction_status_slot,
-                            enable_rpc_transaction_history,
+                            &transaction_history,
+                            &mut slot_history,
                             transaction_notifier.clone(),
                             &blockstore,
-                            enable_extended_tx_metadata_storage,
                             depenency_tracker.clone(),
                         ) {
                             Ok(_) => {}
@@ -122,16 +244,17 @@ impl TransactionStatusService {
     fn write_transaction_status_batch(
         transaction_status_message: TransactionStatusMessage,
         max_complete_transaction_status_slot: &Arc<AtomicU64>,
-        enable_rpc_transaction_history: bool,
+        transaction_history: &TransactionHistorySwitch,
+        slot_history: &mut SlotHistory,
         transaction_notifier: Option<TransactionNotifierArc>,
         blockstore: &Blockstore,
-        enable_extended_tx_metadata_storage: bool,
         dependency_tracker: Option<Arc<DependencyTracker>>,
     ) -> Result<()> {
         match transaction_status_message {
             TransactionStatusMessage::Batch((
                 TransactionStatusBatch {
                     slot,
+                    block_time,
                     transactions,
                     commit_results,
                     balances,
@@ -141,11 +264,16 @@ impl TransactionStatusService {
                 },
                 work_id,
             )) => {
+                let SlotHistorySettings {
+                    enable_rpc_transaction_history,
+                    enable_extended_tx_metadata_storage,
+                } = slot_history.settings(slot, transaction_history);
                 let mut status_and_memos_batch = if enable_rpc_transaction_history {
                     Some(blockstore.get_write_batch()?)
                 } else {
                     None
                 };
+                let enable_address_time_index = blockstore.is_address_time_index_enabled();
 
                 for (
                     transaction,
@@ -238,16 +366,30 @@ impl TransactionStatusService {
                         }
 
                         let message = transaction.message();
-                        let keys_with_writable = message
-                            .account_keys()
-                            .iter()
-                            .enumerate()
-                            .map(|(index, key)| (key, message.is_writable(index)));
+                        let keys_with_writable = || {
+                            message
+                                .account_keys()
+                                .iter()
+                                .enumerate()
+                                .map(|(index, key)| (key, message.is_writable(index)))
+                        };
+
+                        if enable_address_time_index {
+                            blockstore.add_address_transactions_to_batch(
+                                slot,
+                                block_time,
+                                *transaction.signature(),
+                                keys_with_writable(),
+                                transaction_status_meta.status.clone().err(),
+                                transaction_index,
+                                batch,
+                            )?;
+                        }
 
                         blockstore.add_transaction_status_to_batch(
                             slot,
                             *transaction.signature(),
-                            keys_with_writable,
+                            keys_with_writable(),
                             transaction_status_meta,
                             transaction_index,
                             batch,
@@ -269,6 +411,7 @@ impl TransactionStatusService {
                 if !bank.is_frozen() {
                     return Err(Error::NonFrozenBank(bank.slot()));
                 }
+                slot_history.freeze(bank.slot());
                 Self::write_block_meta(&bank, blockstore)?;
                 max_complete_transaction_status_slot.fetch_max(bank.slot(), Ordering::SeqCst);
             }
@@ -509,6 +652,7 @@ pub(crate) mod tests {
         let transaction_index: usize = bank.transaction_count().try_into().unwrap();
         let transaction_status_batch = TransactionStatusBatch {
             slot,
+            block_time: bank.clock().unix_timestamp,
             transactions: vec![transaction],
             commit_results: vec![commit_result],
             balances,
@@ -523,10 +667,9 @@ pub(crate) mod tests {
         let transaction_status_service = TransactionStatusService::new(
             transaction_status_receiver,
             Arc::new(AtomicU64::default()),
-            false,
+            Arc::new(TransactionHistorySwitch::new(false, false)),
             Some(test_notifier.clone()),
             blockstore,
-            false,
             None, // No work dependency tracker
             exit.clone(),
         );
@@ -617,6 +760,7 @@ pub(crate) mod tests {
 
         let transaction_status_batch = TransactionStatusBatch {
             slot,
+            block_time: bank.clock().unix_timestamp,
             transactions: vec![transaction1, transaction2],
             commit_results: vec![commit_result.clone(), commit_result],
             balances: balances.clone(),
@@ -632,10 +776,9 @@ pub(crate) mod tests {
         let transaction_status_service = TransactionStatusService::new(
             transaction_status_receiver,
             Arc::new(AtomicU64::default()),
-            true,
+            Arc::new(TransactionHistorySwitch::new(true, false)),
             Some(test_notifier.clone()),
             blockstore,
-            false,
             Some(dependency_tracker.clone()),
             exit.clone(),
         );
@@ -683,4 +826,48 @@ pub(crate) mod tests {
             &result2.transaction.message.hash(),
         );
     }
+    #[test]
+    fn test_slot_history() {
+        let switch = TransactionHistorySwitch::new(false, false);
+        let mut slot_history = SlotHistory::default();
+        let off = SlotHistorySettings {
+            enable_rpc_transaction_history: false,
+            enable_extended_tx_metadata_storage: false,
+        };
+        let on = SlotHistorySettings {
+            enable_rpc_transaction_history: true,
+            enable_extended_tx_metadata_storage: true,
+        };
+
+        assert_eq!(slot_history.settings(1, &switch), off);
+        assert_eq!(switch.status().recording_since, None);
+
+        // a slot keeps the settings it started with until it is frozen
+        switch.set(true, true);
+        assert_eq!(slot_history.settings(1, &switch), off);
+        assert_eq!(slot_history.settings(2, &switch), on);
+        assert_eq!(
+            switch.status(),
+            TransactionHistoryStatus {
+                enable_rpc_transaction_history: true,
+                enable_extended_tx_metadata_storage: true,
+                recording_since: Some(2),
+            }
+        );
+        slot_history.freeze(1);
+        assert_eq!(slot_history.settings(1, &switch), on);
+        assert_eq!(switch.status().recording_since, Some(2));
+
+        switch.set(false, false);
+        assert_eq!(slot_history.settings(2, &switch), on);
+        assert_eq!(slot_history.settings(3, &switch), off);
+        assert_eq!(switch.status().recording_since, None);
+
+        // slots that were never frozen are forgotten eventually
+        assert_eq!(
+            slot_history.settings(2 + MAX_UNFROZEN_SLOT_DISTANCE, &switch),
+            off
+        );
+        assert_eq!(slot_history.settings.len(), 2);
+    }
 }
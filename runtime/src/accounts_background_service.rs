@@ -569,6 +569,8 @@ impl AccountsBackgroundService {
                                 bank.shrink_candidate_slots();
                                 previous_shrink_time = Instant::now();
                             }
+
+                            bank.rc.accounts.accounts_db.maybe_write_storage_manifest();
                         }
                         stats.record_and_maybe_submit(start_time.elapsed());
                         sleep(Duration::from_millis(INTERVAL_MS));
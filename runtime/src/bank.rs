@@ -568,6 +568,7 @@ impl PartialEq for Bank {
             collector_fee_details: _,
             compute_budget: _,
             transaction_account_lock_limit: _,
+            replay_determinism_check: _,
             fee_structure: _,
             cache_for_accounts_lt_hash: _,
             stats_for_accounts_lt_hash: _,
@@ -884,6 +885,9 @@ pub struct Bank {
     /// The max number of accounts that a transaction may lock.
     transaction_account_lock_limit: Option<usize>,
 
+    /// Whether replayed transaction batches are re-executed to check for nondeterminism.
+    replay_determinism_check: bool,
+
     /// Fee structure to use for assessing transaction fees.
     fee_structure: FeeStructure,
 
@@ -1006,6 +1010,41 @@ impl BankHashStats {
     }
 }
 
+/// Everything the hash of a bank is computed from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BankHashComponents {
+    pub parent_hash: Hash,
+    pub signature_count: u64,
+    pub last_blockhash: Hash,
+    pub accounts_lt_hash: AccountsLtHash,
+    /// Hard fork data mixed into the hash, if a hard fork is registered between the parent slot
+    /// and the slot of the bank
+    pub hard_fork_data: Option<[u8; 8]>,
+}
+
+impl BankHashComponents {
+    pub fn hash(&self) -> Hash {
+        let hash = self.hash_before_hard_fork();
+        match &self.hard_fork_data {
+            Some(hard_fork_data) => hashv(&[hash.as_ref(), hard_fork_data]),
+            None => hash,
+        }
+    }
+
+    /// The hash without the hard fork data mixed in
+    fn hash_before_hard_fork(&self) -> Hash {
+        let hash = hashv(&[
+            self.parent_hash.as_ref(),
+            &self.signature_count.to_le_bytes(),
+            self.last_blockhash.as_ref(),
+        ]);
+        hashv(&[
+            hash.as_ref(),
+            bytemuck::must_cast_slice(&self.accounts_lt_hash.0 .0),
+        ])
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct AtomicBankHashStats {
     pub num_updated_accounts: AtomicU64,
@@ -1116,6 +1155,7 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: None,
             transaction_account_lock_limit: None,
+            replay_determinism_check: false,
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -1159,6 +1199,7 @@ impl Bank {
                 .set_execution_cost(compute_budget.to_cost());
         }
         bank.transaction_account_lock_limit = runtime_config.transaction_account_lock_limit;
+        bank.replay_determinism_check = runtime_config.replay_determinism_check;
         bank.transaction_debug_keys = debug_keys;
         bank.cluster_type = Some(genesis_config.cluster_type);
 
@@ -1363,6 +1404,7 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: parent.compute_budget,
             transaction_account_lock_limit: parent.transaction_account_lock_limit,
+            replay_determinism_check: parent.replay_determinism_check,
             fee_structure: parent.fee_structure.clone(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: parent.hash_overrides.clone(),
@@ -1856,6 +1898,7 @@ impl Bank {
             collector_fee_details: RwLock::new(CollectorFeeDetails::default()),
             compute_budget: runtime_config.compute_budget,
             transaction_account_lock_limit: runtime_config.transaction_account_lock_limit,
+            replay_determinism_check: runtime_config.replay_determinism_check,
             fee_structure: FeeStructure::default(),
             #[cfg(feature = "dev-context-only-utils")]
             hash_overrides: Arc::new(Mutex::new(HashOverrides::default())),
@@ -2932,6 +2975,11 @@ impl Bank {
         tick_height == self.max_tick_height
     }
 
+    /// Whether replayed transaction batches should be re-executed to check for nondeterminism
+    pub fn replay_determinism_check(&self) -> bool {
+        self.replay_determinism_check
+    }
+
     /// Get the max number of accounts that a transaction may lock in this block
     pub fn get_transaction_account_lock_limit(&self) -> usize {
         if let Some(transaction_account_lock_limit) = self.transaction_account_lock_limit {
@@ -4535,34 +4583,36 @@ impl Bank {
         self.get_signature_status_slot(signature).is_some()
     }
 
+    /// Returns the components of the bank hash, which are final once the bank is frozen
+    pub fn hash_components(&self) -> BankHashComponents {
+        BankHashComponents {
+            parent_hash: self.parent_hash,
+            signature_count: self.signature_count(),
+            last_blockhash: self.last_blockhash(),
+            accounts_lt_hash: self.accounts_lt_hash.lock().unwrap().clone(),
+            hard_fork_data: self
+                .hard_forks
+                .read()
+                .unwrap()
+                .get_hash_data(self.slot(), self.parent_slot()),
+        }
+    }
+
     /// Hash the `accounts` HashMap. This represents a validator's interpretation
     ///  of the delta of the ledger since the last vote and up to now
     fn hash_internal_state(&self) -> Hash {
         let measure_total = Measure::start("");
         let slot = self.slot();
 
-        let mut hash = hashv(&[
-            self.parent_hash.as_ref(),
-            &self.signature_count().to_le_bytes(),
-            self.last_blockhash().as_ref(),
-        ]);
-
-        let accounts_lt_hash_checksum = {
-            let accounts_lt_hash = &*self.accounts_lt_hash.lock().unwrap();
-            let lt_hash_bytes = bytemuck::must_cast_slice(&accounts_lt_hash.0 .0);
-            hash = hashv(&[hash.as_ref(), lt_hash_bytes]);
-            accounts_lt_hash.0.checksum()
-        };
-
-        let buf = self
-            .hard_forks
-            .read()
-            .unwrap()
-            .get_hash_data(slot, self.parent_slot());
-        if let Some(buf) = buf {
-            let hard_forked_hash = hashv(&[hash.as_ref(), &buf]);
-            warn!("hard fork at slot {slot} by hashing {buf:?}: {hash} => {hard_forked_hash}");
-            hash = hard_forked_hash;
+        let hash_components = self.hash_components();
+        let hash = hash_components.hash();
+        let accounts_lt_hash_checksum = hash_components.accounts_lt_hash.0.checksum();
+        if let Some(hard_fork_data) = hash_components.hard_fork_data {
+            let hash_before_hard_fork = hash_components.hash_before_hard_fork();
+            warn!(
+                "hard fork at slot {slot} by hashing {hard_fork_data:?}: {hash_before_hard_fork} \
+                 => {hash}"
+            );
         }
 
         #[cfg(feature = "dev-context-only-utils")]
@@ -3819,6 +3819,25 @@ fn test_hash_internal_state_unchanged() {
     assert_ne!(bank0_hash, bank1_hash);
 }
 
+#[test]
+fn test_hash_components() {
+    let (genesis_config, _) = create_genesis_config(500);
+    let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+    bank0.freeze();
+    let hash_components = bank0.hash_components();
+    assert_eq!(hash_components.hard_fork_data, None);
+    assert_eq!(hash_components.hash(), bank0.hash());
+
+    let bank1 = Bank::new_from_parent(bank0.clone(), &Pubkey::default(), 1);
+    bank1.register_hard_fork(1);
+    bank1.freeze();
+    let hash_components = bank1.hash_components();
+    assert_eq!(hash_components.parent_hash, bank0.hash());
+    assert_eq!(hash_components.last_blockhash, bank1.last_blockhash());
+    assert!(hash_components.hard_fork_data.is_some());
+    assert_eq!(hash_components.hash(), bank1.hash());
+}
+
 #[test]
 fn test_hash_internal_state_unchanged_with_ticks() {
     let (genesis_config, _) = create_genesis_config(500);
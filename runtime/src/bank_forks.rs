@@ -72,6 +72,45 @@ pub struct BankPair {
     pub working_bank: Arc<Bank>,
 }
 
+/// What replay does with the forks over the [`BankForksLimits`]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BankForksEviction {
+    /// Report the forks over the limits and keep them
+    #[default]
+    Report,
+    /// Remove the forks over the limits, which are replayed again if the cluster confirms them
+    Prune,
+}
+
+/// Limits on the unrooted banks kept while the root does not advance, such as during long forky
+/// periods
+///
+/// The limits only apply to the forks that branch off the forks replay keeps, those of the
+/// heaviest bank, the last vote and the PoH reset bank.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct BankForksLimits {
+    /// Most unrooted banks to keep, the oldest forks being over the limit first
+    pub max_unrooted_banks: Option<usize>,
+    /// Most slots the newest bank of a fork may be behind the highest bank
+    pub max_fork_age: Option<Slot>,
+    pub eviction: BankForksEviction,
+}
+
+impl BankForksLimits {
+    pub fn is_enabled(&self) -> bool {
+        self.max_unrooted_banks.is_some() || self.max_fork_age.is_some()
+    }
+}
+
+/// A fork over the [`BankForksLimits`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkOverLimits {
+    /// The first bank of the fork, whose parent is kept
+    pub slot: Slot,
+    /// `slot` and its descendants, in ascending order
+    pub slots: Vec<Slot>,
+}
+
 #[derive(Debug, Default, Copy, Clone)]
 struct SetRootMetrics {
     timings: SetRootTimings,
@@ -114,6 +153,62 @@ impl BankForks {
     pub fn new_rw_arc(root_bank: Bank) -> Arc<RwLock<Self>> {
         let root_bank = Arc::new(root_bank);
         let root_slot = root_bank.slot();
+        let (banks, descendants) = Self::banks_and_descendants_of_root(&root_bank);
+
+        let bank_forks = Arc::new(RwLock::new(Self {
+            root: Arc::new(AtomicSlot::new(root_slot)),
+            working_slot: root_slot,
+            sharable_banks: SharableBanks {
+                root_bank: Arc::new(ArcSwap::from(root_bank.clone())),
+                // working bank is initially the same as root - all banks are either the root
+                // or its ancestors.
+                working_bank: Arc::new(ArcSwap::from(root_bank.clone())),
+            },
+            banks,
+            descendants,
+            in_vote_only_mode: Arc::new(AtomicBool::new(false)),
+            highest_slot_at_startup: 0,
+            scheduler_pool: None,
+            dumped_slot_subscribers: vec![],
+        }));
+
+        root_bank.set_fork_graph_in_program_cache(Arc::downgrade(&bank_forks));
+        bank_forks
+    }
+
+    /// Replaces all the banks with `root_bank`, which does not need to descend from the current
+    /// root, e.g. a bank loaded from a newer snapshot. Banks held elsewhere stay usable.
+    pub fn reset_root_bank(bank_forks: &Arc<RwLock<Self>>, root_bank: Bank) -> Arc<Bank> {
+        let root_bank = Arc::new(root_bank);
+        let root_slot = root_bank.slot();
+        let (banks, descendants) = Self::banks_and_descendants_of_root(&root_bank);
+
+        let mut w_bank_forks = bank_forks.write().unwrap();
+        w_bank_forks.banks = banks;
+        w_bank_forks.descendants = descendants;
+        w_bank_forks.working_slot = root_slot;
+        w_bank_forks.root.store(root_slot, Ordering::Release);
+        w_bank_forks
+            .sharable_banks
+            .root_bank
+            .store(root_bank.clone());
+        w_bank_forks
+            .sharable_banks
+            .working_bank
+            .store(root_bank.clone());
+        drop(w_bank_forks);
+
+        root_bank.set_fork_graph_in_program_cache(Arc::downgrade(bank_forks));
+        root_bank
+    }
+
+    fn banks_and_descendants_of_root(
+        root_bank: &Arc<Bank>,
+    ) -> (
+        HashMap<Slot, BankWithScheduler>,
+        HashMap<Slot, HashSet<Slot>>,
+    ) {
+        let root_slot = root_bank.slot();
 
         let mut banks = HashMap::new();
         banks.insert(
@@ -140,26 +235,7 @@ impl BankForks {
         for parent in root_bank.proper_ancestors() {
             descendants.entry(parent).or_default().insert(root_slot);
         }
-
-        let bank_forks = Arc::new(RwLock::new(Self {
-            root: Arc::new(AtomicSlot::new(root_slot)),
-            working_slot: root_slot,
-            sharable_banks: SharableBanks {
-                root_bank: Arc::new(ArcSwap::from(root_bank.clone())),
-                // working bank is initially the same as root - all banks are either the root
-                // or its ancestors.
-                working_bank: Arc::new(ArcSwap::from(root_bank.clone())),
-            },
-            banks,
-            descendants,
-            in_vote_only_mode: Arc::new(AtomicBool::new(false)),
-            highest_slot_at_startup: 0,
-            scheduler_pool: None,
-            dumped_slot_subscribers: vec![],
-        }));
-
-        root_bank.set_fork_graph_in_program_cache(Arc::downgrade(&bank_forks));
-        bank_forks
+        (banks, descendants)
     }
 
     pub fn banks(&self) -> &HashMap<Slot, BankWithScheduler> {
@@ -360,13 +436,7 @@ impl BankForks {
     where
         I: Iterator<Item = &'a Slot>,
     {
-        // Notify subscribers. It is fine that the lock is immediately released, since the bank_forks
-        // lock is held until the end of this function, so subscribers will not be able to interact
-        // with bank_forks anyway.
-        for subscriber in &self.dumped_slot_subscribers {
-            let mut lock = subscriber.lock().unwrap();
-            *lock = true;
-        }
+        self.notify_dumped_slot_subscribers();
 
         slots
             .map(|slot| {
@@ -384,6 +454,90 @@ impl BankForks {
             .unzip()
     }
 
+    /// Clears the banks of forks over the [`BankForksLimits`] from BankForks
+    ///
+    /// Unlike [`Self::dump_slots`], the banks are not suspected to be wrong, so no bank hash
+    /// details are written for them.
+    pub fn evict_slots<'a, I>(&mut self, slots: I) -> (Vec<(Slot, BankId)>, Vec<BankWithScheduler>)
+    where
+        I: Iterator<Item = &'a Slot>,
+    {
+        self.notify_dumped_slot_subscribers();
+
+        slots
+            .filter_map(|slot| {
+                let bank = self.remove(*slot)?;
+                Some(((*slot, bank.bank_id()), bank))
+            })
+            .unzip()
+    }
+
+    fn notify_dumped_slot_subscribers(&self) {
+        // Notify subscribers. It is fine that the lock is immediately released, since the bank_forks
+        // lock is held until the end of the caller, so subscribers will not be able to interact
+        // with bank_forks anyway.
+        for subscriber in &self.dumped_slot_subscribers {
+            let mut lock = subscriber.lock().unwrap();
+            *lock = true;
+        }
+    }
+
+    /// Returns the forks over `limits`, oldest first
+    ///
+    /// A fork is a bank that is not one of `keep`, nor one of their ancestors or descendants,
+    /// but whose parent is, along with all its descendants. Forks are over the limits when their
+    /// newest bank is more than `max_fork_age` slots behind the highest bank, and then from the
+    /// oldest one while there are more than `max_unrooted_banks` unrooted banks.
+    pub fn forks_over_limits(
+        &self,
+        limits: &BankForksLimits,
+        keep: &[Slot],
+    ) -> Vec<ForkOverLimits> {
+        if !limits.is_enabled() {
+            return Vec::new();
+        }
+        let mut kept = HashSet::from([self.root()]);
+        for slot in keep {
+            let Some(bank) = self.banks.get(slot) else {
+                continue;
+            };
+            kept.insert(*slot);
+            kept.extend(bank.proper_ancestors());
+            kept.extend(self.descendants.get(slot).into_iter().flatten());
+        }
+
+        let mut forks: Vec<_> = self
+            .banks
+            .iter()
+            .filter(|(slot, bank)| !kept.contains(slot) && kept.contains(&bank.parent_slot()))
+            .map(|(slot, _)| {
+                let mut slots: Vec<_> = std::iter::once(*slot)
+                    .chain(self.descendants.get(slot).into_iter().flatten().copied())
+                    .collect();
+                slots.sort_unstable();
+                ForkOverLimits { slot: *slot, slots }
+            })
+            .collect();
+        let newest_slot = |fork: &ForkOverLimits| *fork.slots.last().unwrap();
+        forks.sort_unstable_by_key(|fork| (newest_slot(fork), fork.slot));
+
+        let highest_slot = self.highest_slot();
+        let mut num_unrooted_banks = self.banks.len().saturating_sub(1);
+        forks.retain(|fork| {
+            let too_old = limits
+                .max_fork_age
+                .is_some_and(|max_fork_age| highest_slot - newest_slot(fork) > max_fork_age);
+            let too_many = limits
+                .max_unrooted_banks
+                .is_some_and(|max_unrooted_banks| num_unrooted_banks > max_unrooted_banks);
+            if too_old || too_many {
+                num_unrooted_banks = num_unrooted_banks.saturating_sub(fork.slots.len());
+            }
+            too_old || too_many
+        });
+        forks
+    }
+
     fn do_set_root_return_metrics(
         &mut self,
         root: Slot,
@@ -781,6 +935,59 @@ mod tests {
         assert!(descendants[&2].is_empty());
     }
 
+    #[test]
+    fn test_forks_over_limits() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let mut bank_forks = bank_forks.write().unwrap();
+        // 0 - 1 - 2 - 4
+        //   \ 3 - 5
+        //   \ 6
+        for (slot, parent) in [(1, 0), (2, 1), (3, 0), (4, 2), (5, 3), (6, 0)] {
+            let parent = bank_forks[parent].clone();
+            bank_forks.insert(Bank::new_from_parent(parent, &Pubkey::default(), slot));
+        }
+        let fork_3 = ForkOverLimits {
+            slot: 3,
+            slots: vec![3, 5],
+        };
+        let fork_6 = ForkOverLimits {
+            slot: 6,
+            slots: vec![6],
+        };
+        let forks_over_limits = |max_unrooted_banks, max_fork_age, keep: &[Slot]| {
+            let limits = BankForksLimits {
+                max_unrooted_banks,
+                max_fork_age,
+                eviction: BankForksEviction::Prune,
+            };
+            bank_forks.forks_over_limits(&limits, keep)
+        };
+
+        assert!(forks_over_limits(None, None, &[4]).is_empty());
+        assert_eq!(forks_over_limits(None, Some(0), &[4]), vec![fork_3.clone()]);
+        assert!(forks_over_limits(None, Some(1), &[4]).is_empty());
+        assert_eq!(forks_over_limits(Some(5), None, &[4]), vec![fork_3.clone()]);
+        assert_eq!(
+            forks_over_limits(Some(2), None, &[4]),
+            vec![fork_3.clone(), fork_6.clone()]
+        );
+        // The forks of the kept banks are never over the limits
+        assert_eq!(forks_over_limits(Some(0), None, &[4, 5]), vec![fork_6]);
+        assert_eq!(forks_over_limits(Some(0), None, &[2, 5, 6]), vec![]);
+
+        let (slots, banks) = bank_forks.evict_slots(fork_3.slots.iter());
+        assert_eq!(
+            slots.iter().map(|(slot, _)| *slot).collect::<Vec<_>>(),
+            vec![3, 5]
+        );
+        assert_eq!(banks.len(), 2);
+        assert!(bank_forks.get(3).is_none());
+        assert_eq!(bank_forks.len(), 5);
+        assert!(!bank_forks.descendants()[&0].contains(&3));
+    }
+
     #[test]
     fn test_bank_forks_ancestors() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
@@ -1043,6 +1250,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reset_root_bank() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
+        extend_bank_forks(bank_forks.clone(), &[(0, 1), (1, 2)]);
+        let old_working_bank = bank_forks.read().unwrap().working_bank();
+
+        // A bank on an unrelated fork, as if loaded from a snapshot
+        let parent = Arc::new(Bank::new_for_tests(&genesis_config));
+        let bank = Bank::new_from_parent(parent, &Pubkey::default(), 5);
+        let root_bank = BankForks::reset_root_bank(&bank_forks, bank);
+
+        let bank_forks = bank_forks.read().unwrap();
+        assert_eq!(bank_forks.root(), 5);
+        assert_eq!(bank_forks.working_bank().slot(), 5);
+        assert!(Arc::ptr_eq(&bank_forks.root_bank(), &root_bank));
+        assert!(Arc::ptr_eq(
+            &bank_forks.sharable_banks().working(),
+            &root_bank
+        ));
+        assert!(bank_forks.get(1).is_none());
+        assert!(bank_forks.get(2).is_none());
+        assert_eq!(
+            bank_forks.descendants(),
+            make_hash_map(vec![(0, vec![5]), (5, vec![])])
+        );
+        assert_eq!(old_working_bank.slot(), 2);
+    }
+
     #[test]
     fn test_fork_graph() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
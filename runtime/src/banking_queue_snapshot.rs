@@ -0,0 +1,241 @@
+//! Snapshots of the transactions buffered by the banking stage.
+//!
+//! The banking stage scheduler orders the transactions it buffers by priority. When the node
+//! opts in, the scheduler periodically publishes a snapshot of its queue: the priority and cost
+//! of every buffered transaction, and the accounts locked by the highest priority ones. Senders
+//! can then see where a transaction would rank in this node's queue before submitting it.
+
+use {
+    crate::bank::Bank,
+    solana_clock::Slot,
+    solana_cost_model::cost_model::CostModel,
+    solana_fee_structure::FeeBudgetLimits,
+    solana_pubkey::Pubkey,
+    solana_runtime_transaction::transaction_with_meta::TransactionWithMeta,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, RwLock},
+    },
+};
+
+/// Number of the highest priority buffered transactions whose account locks are kept in a
+/// snapshot
+pub const MAX_SNAPSHOT_LOCKING_TRANSACTIONS: usize = 4096;
+
+/// Calculate priority and cost for a transaction:
+///
+/// Cost is calculated through the `CostModel`,
+/// and priority is calculated through a formula here that attempts to sell
+/// blockspace to the highest bidder.
+///
+/// The priority is calculated as:
+/// P = R / (1 + C)
+/// where P is the priority, R is the reward,
+/// and C is the cost towards block-limits.
+///
+/// Current minimum costs are on the order of several hundred,
+/// so the denominator is effectively C, and the +1 is simply
+/// to avoid any division by zero due to a bug - these costs
+/// are calculated by the cost-model and are not direct
+/// from user input. They should never be zero.
+/// Any difference in the prioritization is negligible for
+/// the current transaction costs.
+pub fn calculate_priority_and_cost(
+    transaction: &impl TransactionWithMeta,
+    fee_budget_limits: &FeeBudgetLimits,
+    bank: &Bank,
+) -> (u64, u64) {
+    let cost = CostModel::calculate_cost(transaction, &bank.feature_set).sum();
+    let reward = bank.calculate_reward_for_transaction(transaction, fee_budget_limits);
+
+    // We need a multiplier here to avoid rounding down too aggressively.
+    // For many transactions, the cost will be greater than the fees in terms of raw lamports.
+    // For the purposes of calculating prioritization, we multiply the fees by a large number so that
+    // the cost is a small fraction.
+    // An offset of 1 is used in the denominator to explicitly avoid division by zero.
+    const MULTIPLIER: u64 = 1_000_000;
+    (
+        reward
+            .saturating_mul(MULTIPLIER)
+            .saturating_div(cost.saturating_add(1)),
+        cost,
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueuedTransaction {
+    pub priority: u64,
+    pub cost: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockingTransaction {
+    pub priority: u64,
+    pub writable_accounts: Vec<Pubkey>,
+    pub readonly_accounts: Vec<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BankingQueueSnapshot {
+    /// Slot of the working bank when the snapshot was taken
+    pub slot: Slot,
+    /// Buffered transactions, in no particular order
+    pub transactions: Vec<QueuedTransaction>,
+    /// Account locks of the highest priority buffered transactions, highest priority first
+    pub locking_transactions: Vec<LockingTransaction>,
+}
+
+/// Latest snapshot of the banking stage queue, published by the scheduler and read by the RPC
+/// service
+#[derive(Debug, Default)]
+pub struct SharedBankingQueueSnapshot(RwLock<Arc<BankingQueueSnapshot>>);
+
+impl SharedBankingQueueSnapshot {
+    /// Replaces the snapshot
+    pub fn update(&self, snapshot: BankingQueueSnapshot) {
+        *self.0.write().unwrap() = Arc::new(snapshot);
+    }
+
+    /// Returns the latest snapshot
+    pub fn load(&self) -> Arc<BankingQueueSnapshot> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+/// Where a transaction would rank among the buffered transactions of a snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PriorityPreview {
+    /// Number of buffered transactions of a higher priority
+    pub rank: usize,
+    /// Percentage of the buffered transactions with a lower priority
+    pub percentile: u8,
+    /// Compute units of the buffered transactions of a higher priority
+    pub cost_ahead: u64,
+    /// Accounts of the transaction locked by buffered transactions of a higher priority, with the
+    /// number of those transactions, most conflicting first
+    pub conflicting_locks: Vec<(Pubkey, usize)>,
+}
+
+impl BankingQueueSnapshot {
+    /// Returns where a transaction of `priority` locking `writable_accounts` and
+    /// `readonly_accounts` would rank
+    pub fn preview(
+        &self,
+        priority: u64,
+        writable_accounts: &[Pubkey],
+        readonly_accounts: &[Pubkey],
+    ) -> PriorityPreview {
+        // Transactions of the same priority are scheduled newest first
+        let (rank, cost_ahead) = self
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.priority > priority)
+            .fold((0, 0u64), |(rank, cost_ahead), transaction| {
+                (rank + 1, cost_ahead.saturating_add(transaction.cost))
+            });
+        let lower = self
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.priority < priority)
+            .count();
+        let percentile = if self.transactions.is_empty() {
+            100
+        } else {
+            (lower * 100 / self.transactions.len()) as u8
+        };
+
+        let writable: HashSet<_> = writable_accounts.iter().collect();
+        let readonly: HashSet<_> = readonly_accounts.iter().collect();
+        let mut conflicts = HashMap::<Pubkey, usize>::new();
+        for transaction in self
+            .locking_transactions
+            .iter()
+            .take_while(|transaction| transaction.priority > priority)
+        {
+            let write_conflicts = transaction
+                .writable_accounts
+                .iter()
+                .filter(|pubkey| writable.contains(pubkey) || readonly.contains(pubkey));
+            let read_conflicts = transaction
+                .readonly_accounts
+                .iter()
+                .filter(|pubkey| writable.contains(pubkey));
+            for pubkey in write_conflicts.chain(read_conflicts) {
+                *conflicts.entry(*pubkey).or_default() += 1;
+            }
+        }
+        let mut conflicting_locks: Vec<_> = conflicts.into_iter().collect();
+        conflicting_locks.sort_unstable_by(|(a_pubkey, a_count), (b_pubkey, b_count)| {
+            b_count.cmp(a_count).then(a_pubkey.cmp(b_pubkey))
+        });
+
+        PriorityPreview {
+            rank,
+            percentile,
+            cost_ahead,
+            conflicting_locks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        let hot = Pubkey::new_unique();
+        let read = Pubkey::new_unique();
+        let snapshot = BankingQueueSnapshot {
+            slot: 10,
+            transactions: [300, 100, 400, 200, 300]
+                .into_iter()
+                .map(|priority| QueuedTransaction {
+                    priority,
+                    cost: 1_000,
+                })
+                .collect(),
+            locking_transactions: vec![
+                LockingTransaction {
+                    priority: 400,
+                    writable_accounts: vec![hot],
+                    readonly_accounts: vec![read],
+                },
+                LockingTransaction {
+                    priority: 300,
+                    writable_accounts: vec![hot, Pubkey::new_unique()],
+                    readonly_accounts: vec![],
+                },
+                LockingTransaction {
+                    priority: 300,
+                    writable_accounts: vec![Pubkey::new_unique()],
+                    readonly_accounts: vec![read],
+                },
+            ],
+        };
+
+        // Reading an account read by others does not conflict
+        let preview = snapshot.preview(300, &[], &[hot, read]);
+        assert_eq!(preview.rank, 1);
+        assert_eq!(preview.percentile, 40);
+        assert_eq!(preview.cost_ahead, 1_000);
+        assert_eq!(preview.conflicting_locks, vec![(hot, 1)]);
+
+        let preview = snapshot.preview(250, &[hot, read], &[]);
+        assert_eq!(preview.rank, 3);
+        assert_eq!(preview.percentile, 40);
+        assert_eq!(preview.cost_ahead, 3_000);
+        let mut expected = vec![(hot, 2), (read, 2)];
+        expected.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(preview.conflicting_locks, expected);
+
+        let preview = snapshot.preview(500, &[hot], &[]);
+        assert_eq!(preview.rank, 0);
+        assert_eq!(preview.percentile, 100);
+        assert!(preview.conflicting_locks.is_empty());
+
+        let preview = BankingQueueSnapshot::default().preview(0, &[hot], &[]);
+        assert_eq!(preview.rank, 0);
+        assert_eq!(preview.percentile, 100);
+    }
+}
@@ -17,6 +17,7 @@ pub mod bank_client;
 pub mod bank_forks;
 pub mod bank_hash_cache;
 pub mod bank_utils;
+pub mod banking_queue_snapshot;
 pub mod commitment;
 pub mod dependency_tracker;
 pub mod epoch_stakes;
@@ -10,7 +10,7 @@ use {
     std::{
         collections::{BTreeMap, HashMap},
         sync::{
-            atomic::{AtomicU64, Ordering},
+            atomic::{AtomicU64, AtomicUsize, Ordering},
             Arc, RwLock,
         },
         thread::{sleep, Builder, JoinHandle},
@@ -160,6 +160,8 @@ pub struct PrioritizationFeeCache {
     service_thread: Option<JoinHandle<()>>,
     sender: Sender<CacheServiceUpdate>,
     metrics: Arc<PrioritizationFeeCacheMetrics>,
+    /// Number of transactions queued by the banking stage, updated on every scheduler iteration
+    banking_queue_depth: AtomicUsize,
 }
 
 impl Default for PrioritizationFeeCache {
@@ -201,9 +203,21 @@ impl PrioritizationFeeCache {
             service_thread,
             sender,
             metrics,
+            banking_queue_depth: AtomicUsize::default(),
         }
     }
 
+    /// Sets the number of transactions queued by the banking stage
+    pub fn update_banking_queue_depth(&self, queue_depth: usize) {
+        self.banking_queue_depth
+            .store(queue_depth, Ordering::Relaxed);
+    }
+
+    /// Returns the number of transactions queued by the banking stage
+    pub fn banking_queue_depth(&self) -> usize {
+        self.banking_queue_depth.load(Ordering::Relaxed)
+    }
+
     /// Update with a list of non-vote transactions' compute_budget_details and account_locks; Only
     /// transactions have both valid compute_budget_details and account_locks will be used to update
     /// fee_cache asynchronously.
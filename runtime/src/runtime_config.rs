@@ -14,4 +14,7 @@ pub struct RuntimeConfig {
     pub compute_budget: Option<ComputeBudget>,
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
+    /// Re-execute replayed transaction batches with different scheduling and report any
+    /// difference in results. Expensive, only meant for debugging.
+    pub replay_determinism_check: bool,
 }
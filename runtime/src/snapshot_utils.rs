@@ -14,7 +14,7 @@ use {
         },
     },
     agave_snapshots::{
-        archive_snapshot,
+        archive_snapshot, default_num_unpack_threads,
         error::{
             AddBankSnapshotError, GetSnapshotAccountsHardLinkDirError,
             HardLinkStoragesToSnapshotError, SnapshotError, SnapshotFastbootError,
@@ -493,6 +493,7 @@ pub fn serialize_and_archive_snapshot_package(
         &bank_snapshot_info.snapshot_dir,
         snapshot_archive_path,
         snapshot_config.archive_format,
+        snapshot_config.archive_upload.as_ref(),
     )?;
 
     Ok(snapshot_archive_info)
@@ -1301,6 +1302,9 @@ fn unarchive_snapshot(
         snapshot_archive_path.as_ref().to_path_buf(),
         archive_format,
         accounts_db_config.memlock_budget_size,
+        accounts_db_config
+            .num_snapshot_unpack_threads
+            .unwrap_or_else(default_num_unpack_threads),
     );
 
     let num_rebuilder_threads = num_cpus::get_physical().saturating_sub(1).max(1);
@@ -1,9 +1,15 @@
 use {
     crate::{
-        error::ArchiveSnapshotPackageError, paths, snapshot_archive_info::SnapshotArchiveInfo,
-        snapshot_hash::SnapshotHash, ArchiveFormat, Result, SnapshotKind,
+        archive_upload::{ArchiveSink, ArchiveUpload},
+        error::ArchiveSnapshotPackageError,
+        paths,
+        seekable_zstd::SeekableZstdEncoder,
+        snapshot_archive_info::SnapshotArchiveInfo,
+        snapshot_config::ArchiveUploadConfig,
+        snapshot_hash::SnapshotHash,
+        ArchiveFormat, Result, SnapshotKind,
     },
-    log::info,
+    log::{error, info},
     solana_accounts_db::{
         account_storage::AccountStoragesOrderer, account_storage_reader::AccountStorageReader,
         accounts_db::AccountStorageEntry, accounts_file::AccountsFile,
@@ -20,6 +26,11 @@ use {
 const INTERLEAVE_TAR_ENTRIES_SMALL_TO_LARGE_RATIO: (usize, usize) = (4, 1);
 
 /// Archives a snapshot into `archive_path`
+///
+/// With `archive_upload`, the archive is also streamed to the upload command while it is being
+/// created, and is only written to `archive_path` if a local copy should be kept. A failed upload
+/// is reported but does not fail archiving.
+#[allow(clippy::too_many_arguments)]
 pub fn archive_snapshot(
     snapshot_kind: SnapshotKind,
     snapshot_slot: Slot,
@@ -28,6 +39,7 @@ pub fn archive_snapshot(
     bank_snapshot_dir: impl AsRef<Path>,
     archive_path: impl AsRef<Path>,
     archive_format: ArchiveFormat,
+    archive_upload: Option<&ArchiveUploadConfig>,
 ) -> Result<SnapshotArchiveInfo> {
     use ArchiveSnapshotPackageError as E;
     const ACCOUNTS_DIR: &str = "accounts";
@@ -86,9 +98,23 @@ pub fn archive_snapshot(
         archive_format.extension(),
     ));
 
-    {
-        let archive_file = fs::File::create(&staging_archive_path)
+    let keep_local_archive = archive_upload.is_none_or(|upload| upload.keep_local_archive);
+    let archive_name = archive_path
+        .as_ref()
+        .file_name()
+        .expect("archive path has a file name")
+        .to_string_lossy();
+    let (archive_size, upload_result) = {
+        let archive_file = keep_local_archive
+            .then(|| fs::File::create(&staging_archive_path))
+            .transpose()
             .map_err(|err| E::CreateArchiveFile(err, staging_archive_path.clone()))?;
+        let upload = archive_upload.and_then(|upload| {
+            ArchiveUpload::spawn(&upload.command, &archive_name)
+                .inspect_err(|err| error!("Failed to start upload of {archive_name}: {err}"))
+                .ok()
+        });
+        let archive_sink = ArchiveSink::new(archive_file, upload);
 
         let do_archive_files = |encoder: &mut dyn Write| -> std::result::Result<(), E> {
             let mut archive = tar::Builder::new(encoder);
@@ -137,32 +163,44 @@ pub fn archive_snapshot(
             Ok(())
         };
 
-        match archive_format {
+        let archive_sink = match archive_format {
+            ArchiveFormat::TarZstd { config } if config.seekable => {
+                let mut encoder = SeekableZstdEncoder::new(archive_sink, config.compression_level);
+                do_archive_files(&mut encoder)?;
+                encoder.finish().map_err(E::FinishEncoder)?
+            }
             ArchiveFormat::TarZstd { config } => {
                 let mut encoder =
-                    zstd::stream::Encoder::new(archive_file, config.compression_level)
+                    zstd::stream::Encoder::new(archive_sink, config.compression_level)
                         .map_err(E::CreateEncoder)?;
                 do_archive_files(&mut encoder)?;
-                encoder.finish().map_err(E::FinishEncoder)?;
+                encoder.finish().map_err(E::FinishEncoder)?
             }
             ArchiveFormat::TarLz4 => {
                 let mut encoder = lz4::EncoderBuilder::new()
                     .level(1)
-                    .build(archive_file)
+                    .build(archive_sink)
                     .map_err(E::CreateEncoder)?;
                 do_archive_files(&mut encoder)?;
-                let (_output, result) = encoder.finish();
+                let (archive_sink, result) = encoder.finish();
                 result.map_err(E::FinishEncoder)?;
+                archive_sink
             }
         };
-    }
+        archive_sink.finish()
+    };
 
-    // Atomically move the archive into position for other validators to find
-    let metadata = fs::metadata(&staging_archive_path)
-        .map_err(|err| E::QueryArchiveMetadata(err, staging_archive_path.clone()))?;
     let archive_path = archive_path.as_ref().to_path_buf();
-    fs::rename(&staging_archive_path, &archive_path)
-        .map_err(|err| E::MoveArchive(err, staging_archive_path, archive_path.clone()))?;
+    if keep_local_archive {
+        // Atomically move the archive into position for other validators to find
+        fs::rename(&staging_archive_path, &archive_path)
+            .map_err(|err| E::MoveArchive(err, staging_archive_path, archive_path.clone()))?;
+    }
+    match &upload_result {
+        Some(Ok(())) => info!("Uploaded {archive_name}"),
+        Some(Err(err)) => error!("Failed to upload {archive_name}: {err}"),
+        None => {}
+    }
 
     timer.stop();
     info!(
@@ -170,7 +208,7 @@ pub fn archive_snapshot(
         archive_path.display(),
         snapshot_slot,
         timer.as_ms(),
-        metadata.len()
+        archive_size
     );
 
     datapoint_info!(
@@ -184,9 +222,10 @@ pub fn archive_snapshot(
             } else {
                 "incremental-snapshot-archive-size"
             },
-            metadata.len(),
+            archive_size,
             i64
         ),
+        ("uploaded", upload_result.as_ref().map(|result| result.is_ok()), Option<bool>),
     );
     Ok(SnapshotArchiveInfo {
         path: archive_path,
@@ -5,7 +5,7 @@ use {
 
 // SUPPORTED_ARCHIVE_COMPRESSION lists the compression types that can be
 // specified on the command line.
-pub const SUPPORTED_ARCHIVE_COMPRESSION: &[&str] = &["zstd", "lz4"];
+pub const SUPPORTED_ARCHIVE_COMPRESSION: &[&str] = &["zstd", "lz4", "zstd-seekable"];
 pub const DEFAULT_ARCHIVE_COMPRESSION: &str = "zstd";
 
 pub const TAR_ZSTD_EXTENSION: &str = "tar.zst";
@@ -33,6 +33,12 @@ impl ArchiveFormat {
                 config: ZstdConfig::default(),
             }),
             "lz4" => Some(ArchiveFormat::TarLz4),
+            "zstd-seekable" => Some(ArchiveFormat::TarZstd {
+                config: ZstdConfig {
+                    seekable: true,
+                    ..ZstdConfig::default()
+                },
+            }),
             _ => None,
         }
     }
@@ -109,6 +115,10 @@ impl fmt::Display for ParseError {
 pub struct ZstdConfig {
     /// The compression level to use when archiving with zstd
     pub compression_level: i32,
+    /// Compress into independent frames with a seek table, see [`crate::seekable_zstd`]
+    ///
+    /// Archives are unpacked the same way either way, so this is not reflected in the extension.
+    pub seekable: bool,
 }
 
 #[cfg(test)]
@@ -171,6 +181,12 @@ mod tests {
                 config: ZstdConfig::default(),
             }),
             Some(ArchiveFormat::TarLz4),
+            Some(ArchiveFormat::TarZstd {
+                config: ZstdConfig {
+                    compression_level: 0,
+                    seekable: true,
+                },
+            }),
         ];
 
         for (arg, expected) in zip(SUPPORTED_ARCHIVE_COMPRESSION.iter(), golden.into_iter()) {
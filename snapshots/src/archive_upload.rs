@@ -0,0 +1,209 @@
+//! Streaming of snapshot archives to an external uploader
+//!
+//! The archive bytes are piped into a user supplied shell command while the archive is being
+//! created, e.g. `aws s3 cp - s3://bucket/$SNAPSHOT_ARCHIVE_NAME`. This makes the archive available
+//! remotely as soon as it is finished and, if no local copy is kept, avoids needing the disk space
+//! for it at all.
+
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::{
+    fs::File,
+    io::{self, Write},
+    process::{Child, ChildStdin, Command, ExitStatus, Stdio},
+};
+
+/// Environment variable holding the file name of the archive being uploaded
+pub const ARCHIVE_NAME_ENV_VAR: &str = "SNAPSHOT_ARCHIVE_NAME";
+
+/// A running upload command
+///
+/// If dropped without a successful `finish()`, e.g. because creating the archive failed, the
+/// upload is aborted: the command is killed before its standard input is closed, so a truncated
+/// archive is never completed remotely.
+pub(crate) struct ArchiveUpload {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    error: Option<io::Error>,
+    finished: bool,
+}
+
+impl ArchiveUpload {
+    pub(crate) fn spawn(command: &str, archive_name: &str) -> io::Result<Self> {
+        let mut command_builder = Command::new("sh");
+        command_builder
+            .arg("-c")
+            .arg(command)
+            .env(ARCHIVE_NAME_ENV_VAR, archive_name)
+            .stdin(Stdio::piped());
+        // Run the command in its own process group, so aborting the upload also kills the
+        // processes the shell started
+        #[cfg(unix)]
+        command_builder.process_group(0);
+        let mut child = command_builder.spawn()?;
+        let stdin = child.stdin.take();
+        Ok(Self {
+            child,
+            stdin,
+            error: None,
+            finished: false,
+        })
+    }
+
+    /// Writes `buf` to the upload command
+    ///
+    /// A failed upload must not fail the archive, so the first error is kept for `finish()` and
+    /// nothing more is written.
+    fn write_all(&mut self, buf: &[u8]) {
+        if let Some(stdin) = &mut self.stdin {
+            if let Err(err) = stdin.write_all(buf) {
+                self.error = Some(err);
+                self.stdin = None;
+            }
+        }
+    }
+
+    /// Closes the standard input of the upload command and waits for it to exit
+    fn finish(mut self) -> io::Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        self.finished = true;
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        check_exit_status(status)
+    }
+
+    /// Kills the upload command and everything it started
+    fn kill(&mut self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            let pgid = libc::pid_t::try_from(self.child.id()).map_err(io::Error::other)?;
+            // SAFETY: `killpg` has no memory safety requirements. The process group is the one
+            // `spawn()` created for the command, which has not been reaped yet, so its id cannot
+            // have been reused.
+            if unsafe { libc::killpg(pgid, libc::SIGKILL) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        self.child.kill()
+    }
+}
+
+impl Drop for ArchiveUpload {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Err(err) = self.kill() {
+            log::warn!("Failed to abort archive upload: {err}");
+        }
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+fn check_exit_status(status: ExitStatus) -> io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("upload command failed: {status}")))
+    }
+}
+
+/// Writes the encoded archive to a local file, an upload command, or both
+///
+/// Dropping the sink without calling `finish()` aborts the upload.
+pub(crate) struct ArchiveSink {
+    file: Option<File>,
+    upload: Option<ArchiveUpload>,
+    bytes_written: u64,
+}
+
+impl ArchiveSink {
+    pub(crate) fn new(file: Option<File>, upload: Option<ArchiveUpload>) -> Self {
+        Self {
+            file,
+            upload,
+            bytes_written: 0,
+        }
+    }
+
+    /// Returns the archive size and, if there was an upload, its result
+    pub(crate) fn finish(self) -> (u64, Option<io::Result<()>>) {
+        (self.bytes_written, self.upload.map(ArchiveUpload::finish))
+    }
+}
+
+impl Write for ArchiveSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(file) = &mut self.file {
+            file.write_all(buf)?;
+        }
+        if let Some(upload) = &mut self.upload {
+            upload.write_all(buf);
+        }
+        self.bytes_written = self.bytes_written.saturating_add(buf.len() as u64);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::fs};
+
+    #[test]
+    fn test_archive_sink() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let local_path = dir.path().join("local");
+        let upload_dir = dir.path().join("upload");
+        fs::create_dir(&upload_dir).unwrap();
+        let command = format!("cat > {}/\"${ARCHIVE_NAME_ENV_VAR}\"", upload_dir.display());
+
+        let mut sink = ArchiveSink::new(
+            Some(File::create(&local_path).unwrap()),
+            Some(ArchiveUpload::spawn(&command, "snapshot.tar.zst").unwrap()),
+        );
+        sink.write_all(b"archive bytes").unwrap();
+        let (bytes_written, upload_result) = sink.finish();
+        assert_eq!(bytes_written, 13);
+        upload_result.unwrap().unwrap();
+        assert_eq!(fs::read(&local_path).unwrap(), b"archive bytes");
+        assert_eq!(
+            fs::read(upload_dir.join("snapshot.tar.zst")).unwrap(),
+            b"archive bytes"
+        );
+
+        // a failing upload does not fail the archive
+        let mut sink = ArchiveSink::new(None, Some(ArchiveUpload::spawn("exit 3", "a").unwrap()));
+        sink.write_all(&[0; 1024 * 1024]).unwrap();
+        let (bytes_written, upload_result) = sink.finish();
+        assert_eq!(bytes_written, 1024 * 1024);
+        assert!(upload_result.unwrap().is_err());
+    }
+
+    #[test]
+    fn test_archive_upload_aborted_on_drop() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let upload_path = dir.path().join("upload");
+        // The upload is only complete once the command sees the end of the archive
+        let command = format!(
+            "cat > {0}.partial && mv {0}.partial {0}",
+            upload_path.display()
+        );
+
+        let mut sink = ArchiveSink::new(None, Some(ArchiveUpload::spawn(&command, "a").unwrap()));
+        sink.write_all(b"truncated archive").unwrap();
+        drop(sink);
+        assert!(!upload_path.exists());
+    }
+}
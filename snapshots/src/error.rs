@@ -246,9 +246,6 @@ pub enum ArchiveSnapshotPackageError {
     #[error("failed to encode archive: {0}")]
     FinishEncoder(#[source] io::Error),
 
-    #[error("failed to query archive metadata '{1}': {0}")]
-    QueryArchiveMetadata(#[source] io::Error, PathBuf),
-
     #[error("failed to move archive from '{1}' to '{2}': {0}")]
     MoveArchive(#[source] io::Error, PathBuf, PathBuf),
 
@@ -17,24 +17,29 @@
 
 mod archive;
 mod archive_format;
+mod archive_upload;
 pub mod error;
 pub mod hardened_unpack;
 mod kind;
 pub mod paths;
+pub mod seekable_zstd;
 pub mod snapshot_archive_info;
 pub mod snapshot_config;
 pub mod snapshot_hash;
 mod snapshot_interval;
 mod snapshot_version;
 mod unarchive;
+mod unpack_pipeline;
 
 pub type Result<T> = std::result::Result<T, error::SnapshotError>;
 
 pub use {
     archive::archive_snapshot,
     archive_format::*,
+    archive_upload::ARCHIVE_NAME_ENV_VAR,
     kind::SnapshotKind,
     snapshot_interval::SnapshotInterval,
     snapshot_version::SnapshotVersion,
     unarchive::{streaming_unarchive_snapshot, unpack_genesis_archive},
+    unpack_pipeline::default_num_unpack_threads,
 };
@@ -0,0 +1,422 @@
+//! Snapshot archives made of independently compressed zstd frames
+//!
+//! An archive in the zstd seekable format is a series of zstd frames that each hold a fixed
+//! amount of the tar stream, followed by a seek table in a skippable frame. Decoders that do not
+//! know about the format decompress the frames one after another and skip the seek table, so
+//! these archives keep the `tar.zst` extension and unpack like any other zstd archive.
+//!
+//! The seek table lets [`SeekableZstdReader`] decompress only the frames around a given position
+//! of the tar stream. That is enough to list the files of the archive by reading their headers,
+//! and to extract a single account storage or the bank snapshot without decompressing the rest.
+//!
+//! The seek table layout follows the zstd seekable format specification:
+//! <https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md>
+
+use std::{
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+/// Amount of the tar stream compressed into each frame
+pub const SEEKABLE_FRAME_SIZE: usize = 2 * 1024 * 1024;
+
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184D2A5E;
+const SEEKABLE_MAGIC: u32 = 0x8F92EAB1;
+const SKIPPABLE_FRAME_HEADER_SIZE: u64 = 8;
+const SEEK_TABLE_FOOTER_SIZE: u64 = 9;
+const SEEK_TABLE_CHECKSUM_FLAG: u8 = 0x80;
+const TAR_BLOCK_SIZE: u64 = 512;
+
+/// Compresses the written data into frames of [`SEEKABLE_FRAME_SIZE`] bytes
+pub struct SeekableZstdEncoder<W: Write> {
+    writer: W,
+    compression_level: i32,
+    frame: Vec<u8>,
+    /// Compressed and decompressed size of each frame written so far
+    seek_table: Vec<(u32, u32)>,
+}
+
+impl<W: Write> SeekableZstdEncoder<W> {
+    pub fn new(writer: W, compression_level: i32) -> Self {
+        Self {
+            writer,
+            compression_level,
+            frame: Vec::with_capacity(SEEKABLE_FRAME_SIZE),
+            seek_table: Vec::new(),
+        }
+    }
+
+    /// Writes the last frame and the seek table, and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_frame()?;
+
+        let num_frames = u32::try_from(self.seek_table.len()).map_err(io::Error::other)?;
+        let table_size = self
+            .seek_table
+            .len()
+            .checked_mul(8)
+            .and_then(|size| size.checked_add(SEEK_TABLE_FOOTER_SIZE as usize))
+            .and_then(|size| u32::try_from(size).ok())
+            .ok_or_else(|| io::Error::other("seek table is too large"))?;
+        let mut table = Vec::with_capacity((table_size as usize).saturating_add(8));
+        table.extend_from_slice(&SKIPPABLE_FRAME_MAGIC.to_le_bytes());
+        table.extend_from_slice(&table_size.to_le_bytes());
+        for (compressed_size, decompressed_size) in &self.seek_table {
+            table.extend_from_slice(&compressed_size.to_le_bytes());
+            table.extend_from_slice(&decompressed_size.to_le_bytes());
+        }
+        table.extend_from_slice(&num_frames.to_le_bytes());
+        table.push(0); // no checksums
+        table.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+        self.writer.write_all(&table)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        if self.frame.is_empty() {
+            return Ok(());
+        }
+        let compressed = zstd::bulk::compress(&self.frame, self.compression_level)?;
+        self.writer.write_all(&compressed)?;
+        self.seek_table.push((
+            u32::try_from(compressed.len()).map_err(io::Error::other)?,
+            u32::try_from(self.frame.len()).map_err(io::Error::other)?,
+        ));
+        self.frame.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SeekableZstdEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf
+            .len()
+            .min(SEEKABLE_FRAME_SIZE.saturating_sub(self.frame.len()));
+        self.frame.extend_from_slice(&buf[..len]);
+        if self.frame.len() >= SEEKABLE_FRAME_SIZE {
+            self.write_frame()?;
+        }
+        Ok(len)
+    }
+
+    /// Flushes the inner writer; the current frame is only written once it is full
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    compressed_offset: u64,
+    compressed_size: u32,
+    decompressed_offset: u64,
+    decompressed_size: u32,
+}
+
+impl Frame {
+    fn decompressed_end(&self) -> u64 {
+        self.decompressed_offset
+            .saturating_add(u64::from(self.decompressed_size))
+    }
+}
+
+/// A file in the tar stream of a seekable archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeekableArchiveEntry {
+    pub path: PathBuf,
+    /// Position of the file contents in the tar stream
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Reads the tar stream of a seekable archive at any position
+pub struct SeekableZstdReader<R> {
+    reader: R,
+    frames: Vec<Frame>,
+    position: u64,
+    /// Index and contents of the most recently decompressed frame
+    cached_frame: Option<(usize, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> SeekableZstdReader<R> {
+    /// Reads the seek table of the archive, or returns `None` if it does not have one
+    pub fn open(mut reader: R) -> io::Result<Option<Self>> {
+        let archive_size = reader.seek(SeekFrom::End(0))?;
+        if archive_size < SKIPPABLE_FRAME_HEADER_SIZE + SEEK_TABLE_FOOTER_SIZE {
+            return Ok(None);
+        }
+        let mut footer = [0u8; SEEK_TABLE_FOOTER_SIZE as usize];
+        reader.seek(SeekFrom::End(-(SEEK_TABLE_FOOTER_SIZE as i64)))?;
+        reader.read_exact(&mut footer)?;
+        if u32::from_le_bytes(footer[5..9].try_into().unwrap()) != SEEKABLE_MAGIC {
+            return Ok(None);
+        }
+        let num_frames = u32::from_le_bytes(footer[0..4].try_into().unwrap()) as u64;
+        let entry_size = if footer[4] & SEEK_TABLE_CHECKSUM_FLAG != 0 {
+            12
+        } else {
+            8
+        };
+
+        let invalid_seek_table = || io::Error::new(io::ErrorKind::InvalidData, "bad seek table");
+        let entries_size = num_frames
+            .checked_mul(entry_size)
+            .ok_or_else(invalid_seek_table)?;
+        let table_size = entries_size
+            .checked_add(SKIPPABLE_FRAME_HEADER_SIZE + SEEK_TABLE_FOOTER_SIZE)
+            .filter(|table_size| *table_size <= archive_size)
+            .ok_or_else(invalid_seek_table)?;
+        let mut table = vec![0u8; table_size as usize];
+        reader.seek(SeekFrom::End(-(table_size as i64)))?;
+        reader.read_exact(&mut table)?;
+        if u32::from_le_bytes(table[0..4].try_into().unwrap()) != SKIPPABLE_FRAME_MAGIC {
+            return Err(invalid_seek_table());
+        }
+
+        let mut frames = Vec::with_capacity(num_frames as usize);
+        let mut compressed_offset = 0u64;
+        let mut decompressed_offset = 0u64;
+        let entries = &table[SKIPPABLE_FRAME_HEADER_SIZE as usize..][..entries_size as usize];
+        for entry in entries.chunks_exact(entry_size as usize) {
+            let compressed_size = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+            let decompressed_size = u32::from_le_bytes(entry[4..8].try_into().unwrap());
+            frames.push(Frame {
+                compressed_offset,
+                compressed_size,
+                decompressed_offset,
+                decompressed_size,
+            });
+            compressed_offset = compressed_offset
+                .checked_add(u64::from(compressed_size))
+                .ok_or_else(invalid_seek_table)?;
+            decompressed_offset = decompressed_offset
+                .checked_add(u64::from(decompressed_size))
+                .ok_or_else(invalid_seek_table)?;
+        }
+        if compressed_offset.saturating_add(table_size) != archive_size {
+            return Err(invalid_seek_table());
+        }
+
+        Ok(Some(Self {
+            reader,
+            frames,
+            position: 0,
+            cached_frame: None,
+        }))
+    }
+
+    /// Size of the tar stream
+    pub fn len(&self) -> u64 {
+        self.frames.last().map_or(0, Frame::decompressed_end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lists the files of the tar stream, decompressing only the frames that hold their headers
+    pub fn entries(&mut self) -> io::Result<Vec<SeekableArchiveEntry>> {
+        let mut entries = Vec::new();
+        let mut offset = 0u64;
+        let mut long_name = None;
+        let mut header = tar::Header::new_old();
+        while offset.saturating_add(TAR_BLOCK_SIZE) <= self.len() {
+            self.seek(SeekFrom::Start(offset))?;
+            self.read_exact(header.as_mut_bytes())?;
+            if header.as_bytes().iter().all(|byte| *byte == 0) {
+                break;
+            }
+            let size = header.entry_size()?;
+            let contents_offset = offset.saturating_add(TAR_BLOCK_SIZE);
+            if header.entry_type().is_gnu_longname() {
+                let mut name = Vec::new();
+                self.by_ref().take(size).read_to_end(&mut name)?;
+                while name.last() == Some(&0) {
+                    name.pop();
+                }
+                long_name = Some(PathBuf::from(String::from_utf8_lossy(&name).into_owned()));
+            } else {
+                let path = match long_name.take() {
+                    Some(path) => path,
+                    None => header.path()?.into_owned(),
+                };
+                if header.entry_type().is_file() {
+                    entries.push(SeekableArchiveEntry {
+                        path,
+                        offset: contents_offset,
+                        size,
+                    });
+                }
+            }
+            offset = size
+                .checked_next_multiple_of(TAR_BLOCK_SIZE)
+                .and_then(|size| contents_offset.checked_add(size))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad tar header"))?;
+        }
+        Ok(entries)
+    }
+
+    /// Returns a reader of the contents of `entry`
+    pub fn entry_reader(&mut self, entry: &SeekableArchiveEntry) -> io::Result<impl Read + '_> {
+        self.seek(SeekFrom::Start(entry.offset))?;
+        Ok(self.take(entry.size))
+    }
+
+    fn load_frame(&mut self, index: usize) -> io::Result<&[u8]> {
+        if self
+            .cached_frame
+            .as_ref()
+            .is_none_or(|(cached_index, _)| *cached_index != index)
+        {
+            let frame = self.frames[index];
+            let mut compressed = vec![0u8; frame.compressed_size as usize];
+            self.reader.seek(SeekFrom::Start(frame.compressed_offset))?;
+            self.reader.read_exact(&mut compressed)?;
+            let decompressed =
+                zstd::bulk::decompress(&compressed, frame.decompressed_size as usize)?;
+            if decompressed.len() != frame.decompressed_size as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame size does not match the seek table",
+                ));
+            }
+            self.cached_frame = Some((index, decompressed));
+        }
+        Ok(&self.cached_frame.as_ref().unwrap().1)
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableZstdReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.position;
+        let index = self
+            .frames
+            .partition_point(|frame| frame.decompressed_end() <= position);
+        let Some(frame) = self.frames.get(index).copied() else {
+            return Ok(0);
+        };
+        let frame_data = self.load_frame(index)?;
+        let start = position.saturating_sub(frame.decompressed_offset) as usize;
+        let len = buf.len().min(frame_data.len().saturating_sub(start));
+        buf[..len].copy_from_slice(&frame_data[start..][..len]);
+        self.position = position.saturating_add(len as u64);
+        Ok(len)
+    }
+}
+
+impl<R: Read + Seek> Seek for SeekableZstdReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Cursor};
+
+    fn tar_archive(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = SeekableZstdEncoder::new(Vec::new(), 0);
+        // write in odd sized chunks to cross frame boundaries
+        for chunk in data.chunks(100_003) {
+            encoder.write_all(chunk).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_seekable_zstd_round_trip() {
+        let data: Vec<u8> = (0..(2 * SEEKABLE_FRAME_SIZE + 12_345))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let archive = compress(&data);
+
+        // the archive can be decompressed as a regular zstd stream
+        assert_eq!(zstd::stream::decode_all(archive.as_slice()).unwrap(), data);
+
+        let mut reader = SeekableZstdReader::open(Cursor::new(archive))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reader.frames.len(), 3);
+        assert_eq!(reader.len(), data.len() as u64);
+
+        let position = SEEKABLE_FRAME_SIZE as u64 - 10;
+        reader.seek(SeekFrom::Start(position)).unwrap();
+        let mut buf = vec![0u8; 20];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, data[position as usize..][..20]);
+
+        reader.seek(SeekFrom::End(-5)).unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, data[data.len() - 5..]);
+
+        // archives without a seek table are not seekable
+        let archive = zstd::stream::encode_all(data.as_slice(), 0).unwrap();
+        assert!(SeekableZstdReader::open(Cursor::new(archive))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_seekable_zstd_entries() {
+        let long_path = format!("accounts/{}", "x".repeat(120));
+        let files = [
+            ("version", b"1.2.0".to_vec()),
+            ("accounts/1.2", vec![7u8; SEEKABLE_FRAME_SIZE + 1]),
+            (long_path.as_str(), vec![3u8; 1_000]),
+            ("snapshots/5/5", vec![5u8; 600]),
+        ];
+        let archive = compress(&tar_archive(&files));
+
+        let mut reader = SeekableZstdReader::open(Cursor::new(archive))
+            .unwrap()
+            .unwrap();
+        let entries = reader.entries().unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.path.to_str().unwrap(), entry.size))
+                .collect::<Vec<_>>(),
+            files
+                .iter()
+                .map(|(path, contents)| (*path, contents.len() as u64))
+                .collect::<Vec<_>>(),
+        );
+        for (entry, (_, contents)) in entries.iter().zip(&files).rev() {
+            let mut buf = Vec::new();
+            reader
+                .entry_reader(entry)
+                .unwrap()
+                .read_to_end(&mut buf)
+                .unwrap();
+            assert_eq!(&buf, contents);
+        }
+    }
+}
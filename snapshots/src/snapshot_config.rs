@@ -51,6 +51,9 @@ pub struct SnapshotConfig {
 
     // Thread niceness adjustment for snapshot packager service
     pub packager_thread_niceness_adj: i8,
+
+    /// Stream snapshot archives to an upload command while they are created
+    pub archive_upload: Option<ArchiveUploadConfig>,
 }
 
 impl Default for SnapshotConfig {
@@ -74,6 +77,7 @@ impl Default for SnapshotConfig {
             maximum_incremental_snapshot_archives_to_retain:
                 DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             packager_thread_niceness_adj: 0,
+            archive_upload: None,
         }
     }
 }
@@ -111,6 +115,19 @@ impl SnapshotConfig {
     }
 }
 
+/// Configuration for streaming snapshot archives to an upload command
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveUploadConfig {
+    /// Shell command that reads the archive from its standard input
+    ///
+    /// The file name of the archive is available in the `SNAPSHOT_ARCHIVE_NAME` environment
+    /// variable.
+    pub command: String,
+
+    /// Also write the archive to the local snapshot archives directory
+    pub keep_local_archive: bool,
+}
+
 /// Specify the ways that snapshots are allowed to be used
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum SnapshotUsage {
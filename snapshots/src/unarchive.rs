@@ -1,14 +1,18 @@
 use {
     crate::{
         hardened_unpack::{self, UnpackError},
+        unpack_pipeline::PipelinedFileCreator,
         ArchiveFormat, ArchiveFormatDecompressor,
     },
     agave_fs::{buffered_reader, file_io::file_creator},
     bzip2::bufread::BzDecoder,
     crossbeam_channel::Sender,
+    solana_measure::measure_us,
+    solana_metrics::datapoint_info,
     std::{
         fs,
         io::{self, BufRead, BufReader},
+        num::NonZeroUsize,
         path::{Path, PathBuf},
         thread::{self, JoinHandle},
         time::Instant,
@@ -25,6 +29,9 @@ const MAX_SNAPSHOT_READER_BUF_SIZE: u64 = 128 * 1024 * 1024;
 const MAX_UNPACK_WRITE_BUF_SIZE: usize = 512 * 1024 * 1024;
 
 /// Streams unpacked files across channel
+///
+/// The archive is decoded on a single thread, while the unpacked files are checksummed and
+/// written by `num_unpack_threads` writer threads.
 pub fn streaming_unarchive_snapshot(
     file_sender: Sender<PathBuf>,
     account_paths: Vec<PathBuf>,
@@ -32,8 +39,9 @@ pub fn streaming_unarchive_snapshot(
     snapshot_archive_path: PathBuf,
     archive_format: ArchiveFormat,
     memlock_budget_size: usize,
+    num_unpack_threads: NonZeroUsize,
 ) -> JoinHandle<Result<(), UnpackError>> {
-    let do_unpack = move |archive_path: &Path| {
+    let do_unpack = move |archive_path: &Path| -> Result<(), UnpackError> {
         let archive_size = fs::metadata(archive_path)?.len() as usize;
         // Bound the buffer based on available memlock budget (reader and writer might use it to
         // register buffer in kernel) and input archive size (decompression multiplies content size,
@@ -43,22 +51,40 @@ pub fn streaming_unarchive_snapshot(
         let decompressor = decompressed_tar_reader(archive_format, archive_path, read_buf_size)?;
 
         let write_buf_size = MAX_UNPACK_WRITE_BUF_SIZE.min(read_write_budget_size);
-        let file_creator = file_creator(write_buf_size, move |file_path| {
-            let result = file_sender.send(file_path);
-            if let Err(err) = result {
-                panic!(
-                    "failed to send path '{}' from unpacker to rebuilder: {err}",
-                    err.0.display(),
-                );
-            }
-        })?;
+        let file_creator =
+            PipelinedFileCreator::new(num_unpack_threads, write_buf_size, move |file_path| {
+                let result = file_sender.send(file_path);
+                if let Err(err) = result {
+                    panic!(
+                        "failed to send path '{}' from unpacker to rebuilder: {err}",
+                        err.0.display(),
+                    );
+                }
+            })?;
+        let checksum = file_creator.checksum();
 
-        hardened_unpack::streaming_unpack_snapshot(
+        let (result, unpack_us) = measure_us!(hardened_unpack::streaming_unpack_snapshot(
             decompressor,
-            file_creator,
+            Box::new(file_creator),
             ledger_dir.as_path(),
             &account_paths,
-        )
+        ));
+        result?;
+
+        let checksum = checksum.get().map(ToString::to_string).unwrap_or_default();
+        log::info!(
+            "Unpacked {} with {num_unpack_threads} writer threads in {unpack_us} us, checksum: \
+             {checksum}",
+            archive_path.display(),
+        );
+        datapoint_info!(
+            "snapshot_unpack",
+            ("num_unpack_threads", num_unpack_threads.get(), i64),
+            ("archive_size", archive_size, i64),
+            ("unpack_us", unpack_us, i64),
+            ("checksum", checksum, String),
+        );
+        Ok(())
     };
 
     thread::Builder::new()
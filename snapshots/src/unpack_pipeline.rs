@@ -0,0 +1,350 @@
+//! Pipelined writing of files unpacked from a snapshot archive
+//!
+//! Decompressing and parsing the tar stream is inherently sequential, so the unpack thread only
+//! reads each entry's contents and hands them off in chunks.  A pool of writer threads then
+//! checksums and writes the files, each through its own [`FileCreator`], so hashing and creating
+//! earlier files overlaps with decoding later ones.
+
+use {
+    agave_fs::file_io::{self, FileCreator},
+    crossbeam_channel::{bounded, Receiver, Sender},
+    solana_lattice_hash::lt_hash::{Checksum, LtHash},
+    std::{
+        fs::File,
+        io::{self, Read},
+        num::NonZeroUsize,
+        path::{Path, PathBuf},
+        sync::{Arc, OnceLock},
+        thread::{self, JoinHandle},
+    },
+};
+
+/// Number of decoded files that may be queued per writer thread
+const MAX_QUEUED_FILES_PER_WRITER: usize = 2;
+
+/// Size of the chunks file contents are handed to the writers in
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Number of chunks of a single file that may be queued
+///
+/// Together with `MAX_QUEUED_FILES_PER_WRITER`, this bounds how far decoding can run ahead of
+/// writing to `num_writers * MAX_QUEUED_FILES_PER_WRITER * MAX_QUEUED_CHUNKS_PER_FILE` chunks,
+/// however large the files are.
+const MAX_QUEUED_CHUNKS_PER_FILE: usize = 4;
+
+/// Returns the default number of threads used to write unpacked snapshot files
+pub fn default_num_unpack_threads() -> NonZeroUsize {
+    let num_cpus = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    NonZeroUsize::new((num_cpus / 4).clamp(1, 8)).unwrap()
+}
+
+struct WriteJob {
+    path: PathBuf,
+    mode: u32,
+    parent_dir_handle: Arc<File>,
+    contents: ChunkReader,
+}
+
+/// Reads the contents of a file as the unpack thread sends them
+///
+/// Reaches end of file once the sender is dropped and all sent chunks have been read.
+struct ChunkReader {
+    chunks: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Hashes everything read through it, along with the file name
+struct HashingReader<R> {
+    inner: R,
+    hasher: blake3::Hasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// A [`FileCreator`] that distributes file writes over a pool of writer threads
+///
+/// Each writer also mixes the name and contents of the files it writes into an [`LtHash`]. Since
+/// mixing is commutative, the combined checksum does not depend on how files were assigned to
+/// writers, so unpacking the same archive yields the same checksum regardless of thread count.
+pub(crate) struct PipelinedFileCreator {
+    job_sender: Option<Sender<WriteJob>>,
+    writers: Vec<JoinHandle<io::Result<LtHash>>>,
+    file_complete: Box<dyn FnMut(PathBuf)>,
+    checksum: Arc<OnceLock<Checksum>>,
+}
+
+impl PipelinedFileCreator {
+    /// Spawns `num_writers` writer threads that share `write_buf_size` bytes of write buffers
+    ///
+    /// `file_complete` is called for every created file, from whichever thread created it.
+    pub(crate) fn new(
+        num_writers: NonZeroUsize,
+        write_buf_size: usize,
+        file_complete: impl FnMut(PathBuf) + Clone + Send + 'static,
+    ) -> io::Result<Self> {
+        let num_writers = num_writers.get();
+        let (job_sender, job_receiver) =
+            bounded(num_writers.saturating_mul(MAX_QUEUED_FILES_PER_WRITER));
+        let writer_buf_size = write_buf_size.saturating_div(num_writers);
+        let writers = (0..num_writers)
+            .map(|i| {
+                let job_receiver = job_receiver.clone();
+                let file_complete = file_complete.clone();
+                thread::Builder::new()
+                    .name(format!("solUnpackWr{i:02}"))
+                    .spawn(move || write_files(job_receiver, writer_buf_size, file_complete))
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self {
+            job_sender: Some(job_sender),
+            writers,
+            file_complete: Box::new(file_complete),
+            checksum: Arc::default(),
+        })
+    }
+
+    /// Checksum of the names and contents of all files written, set by `drain()`
+    pub(crate) fn checksum(&self) -> Arc<OnceLock<Checksum>> {
+        self.checksum.clone()
+    }
+}
+
+impl FileCreator for PipelinedFileCreator {
+    fn schedule_create_at_dir(
+        &mut self,
+        path: PathBuf,
+        mode: u32,
+        parent_dir_handle: Arc<File>,
+        contents: &mut dyn io::Read,
+    ) -> io::Result<()> {
+        let job_sender = self
+            .job_sender
+            .as_ref()
+            .ok_or_else(|| io::Error::other("file creator has already been drained"))?;
+        // All writers have exited, which only happens on error; drain() reports it.
+        let writers_exited = |path: &Path| {
+            io::Error::other(format!(
+                "failed to queue '{}' for writing: all writers exited",
+                path.display(),
+            ))
+        };
+
+        // The job is queued before its contents are read, so a writer can start on a file while
+        // the rest of it is still being decoded. Writers only wait on chunks of files whose jobs
+        // were queued earlier, and those are always sent in full first, so this cannot deadlock.
+        let (chunk_sender, chunk_receiver) = bounded(MAX_QUEUED_CHUNKS_PER_FILE);
+        job_sender
+            .send(WriteJob {
+                path: path.clone(),
+                mode,
+                parent_dir_handle,
+                contents: ChunkReader {
+                    chunks: chunk_receiver,
+                    chunk: Vec::new(),
+                    pos: 0,
+                },
+            })
+            .map_err(|_| writers_exited(&path))?;
+        loop {
+            let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+            contents.take(CHUNK_SIZE as u64).read_to_end(&mut chunk)?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            chunk_sender
+                .send(chunk)
+                .map_err(|_| writers_exited(&path))?;
+        }
+    }
+
+    fn file_complete(&mut self, path: PathBuf) {
+        (self.file_complete)(path)
+    }
+
+    fn drain(&mut self) -> io::Result<()> {
+        drop(self.job_sender.take());
+        let mut lt_hash = LtHash::identity();
+        for writer in self.writers.drain(..) {
+            let writer_lt_hash = writer
+                .join()
+                .map_err(|_| io::Error::other("snapshot unpack writer thread panicked"))??;
+            lt_hash.mix_in(&writer_lt_hash);
+        }
+        let _ = self.checksum.set(lt_hash.checksum());
+        Ok(())
+    }
+}
+
+impl Drop for PipelinedFileCreator {
+    fn drop(&mut self) {
+        // If unpacking failed before drain(), make sure writers have stopped touching the
+        // destination directories before the caller starts cleaning them up.
+        drop(self.job_sender.take());
+        for writer in self.writers.drain(..) {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn write_files(
+    job_receiver: Receiver<WriteJob>,
+    write_buf_size: usize,
+    file_complete: impl FnMut(PathBuf),
+) -> io::Result<LtHash> {
+    let mut file_creator = file_io::file_creator(write_buf_size, file_complete)?;
+    let mut lt_hash = LtHash::identity();
+    for job in job_receiver {
+        // Only the file name is hashed since account storage files are spread randomly over the
+        // account paths.
+        let mut hasher = blake3::Hasher::new();
+        if let Some(file_name) = job.path.file_name() {
+            hasher.update(file_name.as_encoded_bytes());
+        }
+        let mut contents = HashingReader {
+            inner: job.contents,
+            hasher,
+        };
+        file_creator.schedule_create_at_dir(
+            job.path,
+            job.mode,
+            job.parent_dir_handle,
+            &mut contents,
+        )?;
+        // Make sure the checksum covers the whole file, even if the file creator stopped reading
+        // early
+        io::copy(&mut contents, &mut io::sink())?;
+        lt_hash.mix_in(&LtHash::with(&contents.hasher));
+    }
+    file_creator.drain()?;
+    Ok(lt_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{fs, sync::Mutex},
+        tempfile::TempDir,
+    };
+
+    fn unpack_files(
+        dst: &Path,
+        files: &[(&str, &[u8])],
+        num_writers: usize,
+    ) -> (Checksum, Vec<PathBuf>) {
+        let parent_dir_handle = Arc::new(File::open(dst).unwrap());
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let mut file_creator =
+            PipelinedFileCreator::new(NonZeroUsize::new(num_writers).unwrap(), 0, {
+                let completed = completed.clone();
+                move |path| completed.lock().unwrap().push(path)
+            })
+            .unwrap();
+        for (name, contents) in files {
+            file_creator
+                .schedule_create_at_dir(
+                    dst.join(name),
+                    0o644,
+                    parent_dir_handle.clone(),
+                    &mut &contents[..],
+                )
+                .unwrap();
+        }
+        let checksum = file_creator.checksum();
+        file_creator.drain().unwrap();
+        drop(file_creator);
+        let checksum = Arc::into_inner(checksum).unwrap().into_inner().unwrap();
+
+        let mut completed = Arc::into_inner(completed).unwrap().into_inner().unwrap();
+        completed.sort();
+        (checksum, completed)
+    }
+
+    #[test]
+    fn test_pipelined_file_creator_writes_files() {
+        let files: Vec<(String, Vec<u8>)> = (0u8..32)
+            .map(|i| (format!("{i}.{i}"), vec![i; usize::from(i)]))
+            .collect();
+        let files: Vec<_> = files
+            .iter()
+            .map(|(name, contents)| (name.as_str(), contents.as_slice()))
+            .collect();
+
+        let dst = TempDir::new().unwrap();
+        let (_, completed) = unpack_files(dst.path(), &files, 4);
+
+        let mut expected: Vec<_> = files
+            .iter()
+            .map(|(name, _)| dst.path().join(name))
+            .collect();
+        expected.sort();
+        assert_eq!(completed, expected);
+        for (name, contents) in files {
+            assert_eq!(fs::read(dst.path().join(name)).unwrap(), contents);
+        }
+    }
+
+    #[test]
+    fn test_pipelined_file_creator_streams_large_files() {
+        let contents: Vec<u8> = (0..3 * CHUNK_SIZE + 17).map(|i| i as u8).collect();
+        let files = [("big.0", contents.as_slice()), ("empty.1", &[][..])];
+
+        let dst = TempDir::new().unwrap();
+        let (streamed, _) = unpack_files(dst.path(), &files, 2);
+        assert_eq!(fs::read(dst.path().join("big.0")).unwrap(), contents);
+        assert!(fs::read(dst.path().join("empty.1")).unwrap().is_empty());
+
+        let dst = TempDir::new().unwrap();
+        let (single_writer, _) = unpack_files(dst.path(), &files, 1);
+        assert_eq!(streamed, single_writer);
+    }
+
+    #[test]
+    fn test_pipelined_file_creator_checksum_independent_of_num_writers() {
+        let files: [(&str, &[u8]); 3] = [("1.1", b"one"), ("2.2", b"two"), ("3.3", b"three")];
+
+        let dst = TempDir::new().unwrap();
+        let (single_writer, _) = unpack_files(dst.path(), &files, 1);
+        let dst = TempDir::new().unwrap();
+        let (many_writers, _) = unpack_files(dst.path(), &files, 3);
+        assert_eq!(single_writer, many_writers);
+
+        // the checksum covers the file contents...
+        let files: [(&str, &[u8]); 3] = [("1.1", b"one"), ("2.2", b"two"), ("3.3", b"thr3e")];
+        let dst = TempDir::new().unwrap();
+        let (modified, _) = unpack_files(dst.path(), &files, 3);
+        assert_ne!(single_writer, modified);
+
+        // ...and the file names
+        let files: [(&str, &[u8]); 3] = [("1.1", b"one"), ("2.2", b"two"), ("3.4", b"three")];
+        let dst = TempDir::new().unwrap();
+        let (renamed, _) = unpack_files(dst.path(), &files, 3);
+        assert_ne!(single_writer, renamed);
+    }
+}
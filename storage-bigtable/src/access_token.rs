@@ -8,34 +8,63 @@ use {
     },
     log::*,
     smpl_jwt::Jwt,
+    solana_time_utils::AtomicInterval,
     std::{
+        fs,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, Ordering},
             {Arc, RwLock},
         },
-        time::Instant,
+        time::{Instant, SystemTime},
     },
     tokio::time,
 };
 
-fn load_credentials(filepath: Option<String>) -> Result<Credentials, String> {
-    let path = match filepath {
-        Some(f) => f,
+/// How often the credentials file is checked for modifications
+const CREDENTIALS_FILE_CHECK_INTERVAL_MS: u64 = 30_000;
+
+fn credentials_path(filepath: Option<String>) -> Result<String, String> {
+    match filepath {
+        Some(f) => Ok(f),
         None => std::env::var("GOOGLE_APPLICATION_CREDENTIALS").map_err(|_| {
             "GOOGLE_APPLICATION_CREDENTIALS environment variable not found".to_string()
-        })?,
-    };
-    Credentials::from_file(&path)
-        .map_err(|err| format!("Failed to read GCP credentials from {path}: {err}"))
+        }),
+    }
+}
+
+fn load_credentials(path: &str) -> Result<Credentials, String> {
+    let credentials = Credentials::from_file(path)
+        .map_err(|err| format!("Failed to read GCP credentials from {path}: {err}"))?;
+    credentials
+        .rsa_key()
+        .map_err(|err| format!("Invalid rsa key: {err}"))?;
+    Ok(credentials)
+}
+
+fn file_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
 }
 
 fn load_stringified_credentials(credential: String) -> Result<Credentials, String> {
     Credentials::from_str(&credential).map_err(|err| format!("{err}"))
 }
 
+/// A credentials file that is watched for modifications, so that rotated credentials take effect
+/// without restarting
+struct CredentialsFile {
+    path: String,
+    modified: RwLock<Option<SystemTime>>,
+    last_check: AtomicInterval,
+}
+
 pub struct AccessTokenInner {
-    credentials: Credentials,
+    credentials: RwLock<Arc<Credentials>>,
+    credentials_file: Option<CredentialsFile>,
+    /// Set when the credentials were reloaded and the token has not been refreshed with them yet
+    credentials_reloaded: AtomicBool,
     scope: Scope,
     token: RwLock<(Token, Instant)>,
     refresh_active: AtomicBool,
@@ -56,30 +85,87 @@ impl std::ops::Deref for AccessToken {
 
 impl AccessToken {
     pub async fn new(scope: Scope, credential_type: CredentialType) -> Result<Self, String> {
-        let credentials = match credential_type {
-            CredentialType::Filepath(fp) => load_credentials(fp)?,
-            CredentialType::Stringified(s) => load_stringified_credentials(s)?,
+        let (credentials, credentials_file) = match credential_type {
+            CredentialType::Filepath(fp) => {
+                let path = credentials_path(fp)?;
+                let modified = file_modified(&path);
+                let credentials = load_credentials(&path)?;
+                let credentials_file = CredentialsFile {
+                    path,
+                    modified: RwLock::new(modified),
+                    last_check: AtomicInterval::default(),
+                };
+                (credentials, Some(credentials_file))
+            }
+            CredentialType::Stringified(s) => {
+                let credentials = load_stringified_credentials(s)?;
+                if let Err(err) = credentials.rsa_key() {
+                    return Err(format!("Invalid rsa key: {err}"));
+                }
+                (credentials, None)
+            }
         };
 
-        if let Err(err) = credentials.rsa_key() {
-            Err(format!("Invalid rsa key: {err}"))
-        } else {
-            let token = RwLock::new(Self::get_token(&credentials, &scope).await?);
-            let access_token = Self {
-                inner: Arc::new(AccessTokenInner {
-                    credentials,
-                    scope,
-                    token,
-                    refresh_active: AtomicBool::new(false),
-                }),
-            };
-            Ok(access_token)
-        }
+        let token = RwLock::new(Self::get_token(&credentials, &scope).await?);
+        let access_token = Self {
+            inner: Arc::new(AccessTokenInner {
+                credentials: RwLock::new(Arc::new(credentials)),
+                credentials_file,
+                credentials_reloaded: AtomicBool::new(false),
+                scope,
+                token,
+                refresh_active: AtomicBool::new(false),
+            }),
+        };
+        Ok(access_token)
     }
 
     /// The project that this token grants access to
     pub fn project(&self) -> String {
-        self.credentials.project()
+        self.credentials.read().unwrap().project()
+    }
+
+    /// Reloads the credentials if the file they were read from has been modified since
+    ///
+    /// Credentials for a different project are rejected, since the project is part of every
+    /// table name used by the connection.
+    fn reload_credentials_if_modified(&self) {
+        let Some(credentials_file) = &self.credentials_file else {
+            return;
+        };
+        if !credentials_file
+            .last_check
+            .should_update(CREDENTIALS_FILE_CHECK_INTERVAL_MS)
+        {
+            return;
+        }
+
+        let modified = file_modified(&credentials_file.path);
+        if modified.is_none() || *credentials_file.modified.read().unwrap() == modified {
+            return;
+        }
+        *credentials_file.modified.write().unwrap() = modified;
+
+        let credentials = match load_credentials(&credentials_file.path) {
+            Ok(credentials) => credentials,
+            Err(err) => {
+                warn!("Keeping previous GCP credentials: {err}");
+                return;
+            }
+        };
+        let project = self.project();
+        if credentials.project() != project {
+            warn!(
+                "Ignoring GCP credentials from {} for project {}, expected project {project}",
+                credentials_file.path,
+                credentials.project(),
+            );
+            return;
+        }
+
+        info!("Reloaded GCP credentials from {}", credentials_file.path);
+        *self.credentials.write().unwrap() = Arc::new(credentials);
+        self.credentials_reloaded.store(true, Ordering::Relaxed);
     }
 
     async fn get_token(
@@ -106,9 +192,13 @@ impl AccessToken {
 
     /// Call this function regularly to ensure the access token does not expire
     pub fn refresh(&self) {
+        self.reload_credentials_if_modified();
+
         // Check if it's time to try a token refresh
         let token_r = self.token.read().unwrap();
-        if token_r.1.elapsed().as_secs() < token_r.0.expires_in() as u64 / 2 {
+        if !self.credentials_reloaded.load(Ordering::Relaxed)
+            && token_r.1.elapsed().as_secs() < token_r.0.expires_in() as u64 / 2
+        {
             debug!("Token is not expired yet");
             return;
         }
@@ -123,11 +213,15 @@ impl AccessToken {
             return;
         }
 
+        // Clear the flag before reading the credentials, so a reload that races with this refresh
+        // triggers another one
+        self.credentials_reloaded.store(false, Ordering::Relaxed);
+        let credentials = self.credentials.read().unwrap().clone();
         let this = self.clone();
         tokio::spawn(async move {
             match time::timeout(
                 time::Duration::from_secs(5),
-                Self::get_token(&this.credentials, &this.scope),
+                Self::get_token(&credentials, &this.scope),
             )
             .await
             {
@@ -9,7 +9,12 @@ use {
     backoff::{future::retry, Error as BackoffError, ExponentialBackoff},
     log::*,
     std::{
+        num::NonZeroUsize,
         str::FromStr,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
         time::{Duration, Instant},
     },
     thiserror::Error,
@@ -117,7 +122,10 @@ type InterceptedRequestResult = std::result::Result<Request<()>, Status>;
 #[derive(Clone)]
 pub struct BigTableConnection {
     access_token: Option<AccessToken>,
-    channel: tonic::transport::Channel,
+    /// Clients are handed out round-robin over these channels, each of which is a separate HTTP/2
+    /// connection
+    channels: Arc<[tonic::transport::Channel]>,
+    next_channel: Arc<AtomicUsize>,
     table_prefix: String,
     app_profile_id: String,
     timeout: Option<Duration>,
@@ -136,6 +144,9 @@ impl BigTableConnection {
     /// The BIGTABLE_PROXY environment variable is used to configure the gRPC connection through a
     /// forward proxy (see HTTP_PROXY).
     ///
+    /// `connection_pool_size` connections are opened to BigTable, except for the emulator which
+    /// always uses a single connection.
+    ///
     pub async fn new(
         instance_name: &str,
         app_profile_id: &str,
@@ -143,6 +154,7 @@ impl BigTableConnection {
         timeout: Option<Duration>,
         credential_type: CredentialType,
         max_message_size: usize,
+        connection_pool_size: NonZeroUsize,
     ) -> Result<Self> {
         match std::env::var("BIGTABLE_EMULATOR_HOST") {
             Ok(endpoint) => {
@@ -195,26 +207,36 @@ impl BigTableConnection {
                 let mut http = hyper::client::HttpConnector::new();
                 http.enforce_http(false);
                 http.set_nodelay(true);
-                let channel = match std::env::var("BIGTABLE_PROXY") {
-                    Ok(proxy_uri) => {
-                        let proxy = hyper_proxy::Proxy::new(
-                            hyper_proxy::Intercept::All,
-                            proxy_uri
-                                .parse::<http::Uri>()
-                                .map_err(|err| Error::InvalidUri(proxy_uri, err.to_string()))?,
-                        );
-                        let mut proxy_connector =
-                            hyper_proxy::ProxyConnector::from_proxy(http, proxy)?;
-                        // tonic handles TLS as a separate layer
-                        proxy_connector.set_tls(None);
-                        endpoint.connect_with_connector_lazy(proxy_connector)
-                    }
-                    _ => endpoint.connect_with_connector_lazy(http),
-                };
+                let proxy = std::env::var("BIGTABLE_PROXY")
+                    .ok()
+                    .map(|proxy_uri| {
+                        proxy_uri
+                            .parse::<http::Uri>()
+                            .map(|uri| hyper_proxy::Proxy::new(hyper_proxy::Intercept::All, uri))
+                            .map_err(|err| Error::InvalidUri(proxy_uri, err.to_string()))
+                    })
+                    .transpose()?;
+                let channels = (0..connection_pool_size.get())
+                    .map(|_| match &proxy {
+                        Some(proxy) => {
+                            let mut proxy_connector = hyper_proxy::ProxyConnector::from_proxy(
+                                http.clone(),
+                                proxy.clone(),
+                            )?;
+                            // tonic handles TLS as a separate layer
+                            proxy_connector.set_tls(None);
+                            Ok(endpoint
+                                .clone()
+                                .connect_with_connector_lazy(proxy_connector))
+                        }
+                        None => Ok(endpoint.clone().connect_with_connector_lazy(http.clone())),
+                    })
+                    .collect::<Result<_>>()?;
 
                 Ok(Self {
                     access_token: Some(access_token),
-                    channel,
+                    channels,
+                    next_channel: Arc::default(),
                     table_prefix,
                     app_profile_id: app_profile_id.to_string(),
                     timeout,
@@ -231,11 +253,13 @@ impl BigTableConnection {
         timeout: Option<Duration>,
         max_message_size: usize,
     ) -> Result<Self> {
+        let channel = tonic::transport::Channel::from_shared(format!("http://{endpoint}"))
+            .map_err(|err| Error::InvalidUri(String::from(endpoint), err.to_string()))?
+            .connect_lazy();
         Ok(Self {
             access_token: None,
-            channel: tonic::transport::Channel::from_shared(format!("http://{endpoint}"))
-                .map_err(|err| Error::InvalidUri(String::from(endpoint), err.to_string()))?
-                .connect_lazy(),
+            channels: Arc::new([channel]),
+            next_channel: Arc::default(),
             table_prefix: format!("projects/emulator/instances/{instance_name}/tables/"),
             app_profile_id: app_profile_id.to_string(),
             timeout,
@@ -249,8 +273,9 @@ impl BigTableConnection {
     /// creating new clients is cheap and thus can be used as a work around for ease of use.
     pub fn client(&self) -> BigTable<impl FnMut(Request<()>) -> InterceptedRequestResult + use<>> {
         let access_token = self.access_token.clone();
+        let channel_index = self.next_channel.fetch_add(1, Ordering::Relaxed) % self.channels.len();
         let client = bigtable_client::BigtableClient::with_interceptor(
-            self.channel.clone(),
+            self.channels[channel_index].clone(),
             move |mut req: Request<()>| {
                 if let Some(access_token) = &access_token {
                     match FromStr::from_str(&access_token.get()) {
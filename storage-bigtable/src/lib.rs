@@ -35,6 +35,7 @@ use {
         collections::{HashMap, HashSet},
         convert::TryInto,
         fmt::Debug,
+        num::NonZeroUsize,
         sync::{
             atomic::{AtomicUsize, Ordering},
             Arc,
@@ -391,6 +392,7 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
 pub const DEFAULT_INSTANCE_NAME: &str = "solana-ledger";
 pub const DEFAULT_APP_PROFILE_ID: &str = "default";
 pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; // 64MB
+pub const DEFAULT_CONNECTION_POOL_SIZE: NonZeroUsize = NonZeroUsize::new(1).unwrap();
 
 #[derive(Debug)]
 pub enum CredentialType {
@@ -406,6 +408,7 @@ pub struct LedgerStorageConfig {
     pub instance_name: String,
     pub app_profile_id: String,
     pub max_message_size: usize,
+    pub connection_pool_size: NonZeroUsize,
 }
 
 impl Default for LedgerStorageConfig {
@@ -417,6 +420,7 @@ impl Default for LedgerStorageConfig {
             instance_name: DEFAULT_INSTANCE_NAME.to_string(),
             app_profile_id: DEFAULT_APP_PROFILE_ID.to_string(),
             max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            connection_pool_size: DEFAULT_CONNECTION_POOL_SIZE,
         }
     }
 }
@@ -498,6 +502,7 @@ impl LedgerStorage {
             app_profile_id,
             credential_type,
             max_message_size,
+            connection_pool_size,
         } = config;
         let connection = bigtable::BigTableConnection::new(
             instance_name.as_str(),
@@ -506,6 +511,7 @@ impl LedgerStorage {
             timeout,
             credential_type,
             max_message_size,
+            connection_pool_size,
         )
         .await?;
         Ok(Self { stats, connection })
@@ -4,6 +4,7 @@ use {
             connection_rate_limiter::ConnectionRateLimiter,
             qos::{ConnectionContext, QosController},
             stream_throttle::ConnectionStreamCounter,
+            tpu_ack::{RecentSignatures, TpuAck, TpuAckStatus, ALPN_TPU_ACK_PROTOCOL_ID},
         },
         quic::{configure_server, QuicServerError, QuicStreamerConfig, StreamerStats},
         streamer::StakedNodes,
@@ -12,7 +13,10 @@ use {
     crossbeam_channel::{Sender, TrySendError},
     futures::{stream::FuturesUnordered, Future, StreamExt as _},
     indexmap::map::{Entry, IndexMap},
-    quinn::{Accept, Connecting, Connection, Endpoint, EndpointConfig, TokioRuntime},
+    quinn::{
+        crypto::rustls::HandshakeData, Accept, Connecting, Connection, Endpoint, EndpointConfig,
+        TokioRuntime, VarInt,
+    },
     rand::{thread_rng, Rng},
     smallvec::SmallVec,
     solana_keypair::Keypair,
@@ -23,7 +27,9 @@ use {
     solana_pubkey::Pubkey,
     solana_signature::Signature,
     solana_tls_utils::get_pubkey_from_tls_certificate,
-    solana_transaction_metrics_tracker::signature_if_should_track_packet,
+    solana_transaction_metrics_tracker::{
+        get_signature_from_packet, signature_if_should_track_packet,
+    },
     std::{
         array, fmt,
         iter::repeat_with,
@@ -142,7 +148,7 @@ where
 {
     let sockets: Vec<_> = sockets.into_iter().collect();
     info!("Start {name} quic server on {sockets:?}");
-    let (config, _) = configure_server(keypair)?;
+    let (config, _) = configure_server(keypair, quic_server_params.enable_transaction_acks)?;
 
     let endpoints = sockets
         .into_iter()
@@ -503,6 +509,7 @@ async fn setup_connection<Q, C>(
                         new_connection,
                         stats,
                         server_params.wait_for_chunk_timeout,
+                        server_params.enable_transaction_acks,
                         conn_context.clone(),
                         qos,
                         cancel_connection,
@@ -592,6 +599,7 @@ async fn handle_connection<Q, C>(
     connection: Connection,
     stats: Arc<StreamerStats>,
     wait_for_chunk_timeout: Duration,
+    enable_transaction_acks: bool,
     context: C,
     qos: Arc<Q>,
     cancel: CancellationToken,
@@ -609,6 +617,12 @@ async fn handle_connection<Q, C>(
     );
     stats.total_connections.fetch_add(1, Ordering::Relaxed);
 
+    // Duplicates are only detected for connections that get acks, so that they learn why a
+    // transaction was discarded
+    let mut recent_signatures =
+        (enable_transaction_acks && peer_type.is_staked() && negotiated_ack_protocol(&connection))
+            .then(RecentSignatures::default);
+
     'conn: loop {
         // Wait for new streams. If the peer is disconnected we get a cancellation signal and stop
         // the connection task.
@@ -682,9 +696,13 @@ async fn handle_connection<Q, C>(
                 &packet_sender,
                 &stats,
                 peer_type,
+                recent_signatures.as_mut(),
             ) {
                 // The stream is finished, break out of the loop and close the stream.
-                Ok(StreamState::Finished) => {
+                Ok(StreamState::Finished(status)) => {
+                    if recent_signatures.is_some() {
+                        send_ack(&connection, stream.id().into(), status, &stats);
+                    }
                     qos.on_stream_finished(&context);
                     break;
                 }
@@ -720,15 +738,48 @@ async fn handle_connection<Q, C>(
     stats.total_connections.fetch_sub(1, Ordering::Relaxed);
 }
 
+/// Whether the client chose the acknowledged TPU protocol during the handshake
+fn negotiated_ack_protocol(connection: &Connection) -> bool {
+    connection
+        .handshake_data()
+        .and_then(|data| data.downcast::<HandshakeData>().ok())
+        .and_then(|data| data.protocol)
+        .is_some_and(|protocol| protocol == ALPN_TPU_ACK_PROTOCOL_ID)
+}
+
+fn send_ack(
+    connection: &Connection,
+    stream_id: VarInt,
+    status: TpuAckStatus,
+    stats: &StreamerStats,
+) {
+    let ack = TpuAck {
+        stream_id: stream_id.into_inner(),
+        status,
+    };
+    match connection.send_datagram(ack.encode()) {
+        Ok(()) => {
+            stats.total_tpu_acks_sent.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(err) => {
+            trace!("failed to send tpu ack: {err:?}");
+            stats
+                .total_tpu_ack_send_errors
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
 enum StreamState {
     // Stream is not finished, keep receiving chunks
     Receiving,
-    // Stream is finished
-    Finished,
+    // Stream is finished, with what happened to its packet
+    Finished(TpuAckStatus),
 }
 
 // Handle the chunks received from the stream. If the stream is finished, send the packet to the
-// packet sender.
+// packet sender. If `recent_signatures` is given, packets with one of those signatures are
+// discarded as duplicates, and the signatures of packets that were sent are added to it.
 //
 // Returns Err(()) if the stream is invalid.
 fn handle_chunks(
@@ -737,6 +788,7 @@ fn handle_chunks(
     packet_sender: &Sender<PacketBatch>,
     stats: &StreamerStats,
     peer_type: ConnectionPeerType,
+    recent_signatures: Option<&mut RecentSignatures>,
 ) -> Result<StreamState, ()> {
     let n_chunks = chunks.len();
     for chunk in chunks {
@@ -800,6 +852,20 @@ fn handle_chunks(
 
     let packet_size = packet.meta().size;
 
+    // Only remembered once the packet was sent, so a client can retry a dropped transaction
+    let mut signature = None;
+    if let Some(recent_signatures) = recent_signatures.as_deref() {
+        if let Ok(packet_signature) = get_signature_from_packet(&packet) {
+            if recent_signatures.contains(packet_signature) {
+                stats
+                    .total_duplicate_packets
+                    .fetch_add(1, Ordering::Relaxed);
+                return Ok(StreamState::Finished(TpuAckStatus::Duplicate));
+            }
+            signature = Some(*packet_signature);
+        }
+    }
+
     let mut packet_perf_measure = None;
     if let Some(signature) = signature_if_should_track_packet(&packet).ok().flatten() {
         packet_perf_measure = Some((*signature, accum.start_time));
@@ -825,7 +891,11 @@ fn handle_chunks(
             }
         }
         trace!("packet batch send error {err:?}");
+        return Ok(StreamState::Finished(TpuAckStatus::Dropped));
     } else {
+        if let (Some(recent_signatures), Some(signature)) = (recent_signatures, signature) {
+            recent_signatures.insert(&signature);
+        }
         if let Some(ppm) = &packet_perf_measure {
             track_streamer_fetch_packet_performance(core::array::from_ref(ppm), stats);
         }
@@ -853,7 +923,7 @@ fn handle_chunks(
         trace!("sent {bytes_sent} byte packet for batching");
     }
 
-    Ok(StreamState::Finished)
+    Ok(StreamState::Finished(TpuAckStatus::Accepted))
 }
 
 #[derive(Debug)]
@@ -1114,19 +1184,20 @@ pub mod test {
     use {
         super::*,
         crate::nonblocking::{
-            swqos::SwQosConfig,
+            swqos::{QosExemptSenders, SwQosConfig},
             testing_utilities::{
-                check_multiple_streams, get_client_config, make_client_endpoint, setup_quic_server,
-                spawn_stake_weighted_qos_server, SpawnTestServerResult,
+                check_multiple_streams, get_client_config, get_client_config_with_alpn,
+                make_client_endpoint, setup_quic_server, spawn_stake_weighted_qos_server,
+                SpawnTestServerResult,
             },
         },
         assert_matches::assert_matches,
-        crossbeam_channel::{unbounded, Receiver},
+        crossbeam_channel::{bounded, unbounded, Receiver},
         quinn::{ApplicationClose, ConnectionError},
         solana_keypair::Keypair,
         solana_net_utils::sockets::bind_to_localhost_unique,
         solana_signer::Signer,
-        std::collections::HashMap,
+        std::collections::{HashMap, HashSet},
         tokio::time::sleep,
     };
 
@@ -1497,6 +1568,96 @@ pub mod test {
         assert_eq!(stats.connection_remove_failed.load(Ordering::Relaxed), 0);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quic_server_transaction_acks() {
+        agave_logger::setup();
+
+        let client_keypair = Keypair::new();
+        let stakes = HashMap::from([(client_keypair.pubkey(), 100_000)]);
+        let staked_nodes = StakedNodes::new(Arc::new(stakes), HashMap::default());
+        let SpawnTestServerResult {
+            join_handle,
+            receiver,
+            server_address,
+            stats,
+            cancel,
+        } = setup_quic_server(
+            Some(staked_nodes),
+            QuicStreamerConfig {
+                enable_transaction_acks: true,
+                ..QuicStreamerConfig::default_for_tests()
+            },
+            SwQosConfig::default(),
+        );
+
+        let mut endpoint = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        endpoint.set_default_client_config(get_client_config_with_alpn(
+            &client_keypair,
+            vec![
+                ALPN_TPU_ACK_PROTOCOL_ID.to_vec(),
+                ALPN_TPU_PROTOCOL_ID.to_vec(),
+            ],
+        ));
+        let connection = endpoint
+            .connect(server_address, "localhost")
+            .unwrap()
+            .await
+            .unwrap();
+        assert!(negotiated_ack_protocol(&connection));
+
+        // a single signature followed by the rest of the "transaction"
+        let mut transaction = vec![1u8];
+        transaction.extend_from_slice(&[7; 64]);
+        transaction.extend_from_slice(&[0; 10]);
+
+        let mut expected_acks = vec![];
+        for status in [TpuAckStatus::Accepted, TpuAckStatus::Duplicate] {
+            let mut stream = connection.open_uni().await.unwrap();
+            stream.write_all(&transaction).await.unwrap();
+            stream.finish().unwrap();
+            expected_acks.push(TpuAck {
+                stream_id: VarInt::from(stream.id()).into_inner(),
+                status,
+            });
+            let datagram = timeout(Duration::from_secs(5), connection.read_datagram())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(TpuAck::decode(&datagram), expected_acks.last().copied());
+        }
+        assert_eq!(receiver.try_iter().count(), 1);
+        assert_eq!(stats.total_duplicate_packets.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.total_tpu_acks_sent.load(Ordering::Relaxed), 2);
+
+        // unstaked clients get no acks
+        let unstaked_keypair = Keypair::new();
+        endpoint.set_default_client_config(get_client_config_with_alpn(
+            &unstaked_keypair,
+            vec![ALPN_TPU_ACK_PROTOCOL_ID.to_vec()],
+        ));
+        let connection = endpoint
+            .connect(server_address, "localhost")
+            .unwrap()
+            .await
+            .unwrap();
+        let mut stream = connection.open_uni().await.unwrap();
+        stream.write_all(&transaction).await.unwrap();
+        stream.finish().unwrap();
+        assert!(
+            timeout(Duration::from_millis(500), connection.read_datagram())
+                .await
+                .is_err()
+        );
+        assert_eq!(stats.total_tpu_acks_sent.load(Ordering::Relaxed), 2);
+
+        // clients that only offer the plain protocol don't negotiate acks
+        let connection = make_client_endpoint(&server_address, None).await;
+        assert!(!negotiated_ack_protocol(&connection));
+
+        cancel.cancel();
+        join_handle.await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_quic_server_zero_staked_connection_removal() {
         // In this test, the client has a pubkey, but is not in stake table.
@@ -1561,6 +1722,85 @@ pub mod test {
         assert_eq!(stats.connection_remove_failed.load(Ordering::Relaxed), 0);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quic_server_exempt_connection_ignores_limits() {
+        agave_logger::setup();
+
+        // The client is unstaked and the server has no room for any connection, but the client
+        // is exempt from QoS.
+        let client_keypair = Keypair::new();
+        let SpawnTestServerResult {
+            join_handle,
+            receiver,
+            server_address,
+            stats,
+            cancel,
+        } = setup_quic_server(
+            None,
+            QuicStreamerConfig {
+                max_staked_connections: 0,
+                max_unstaked_connections: 0,
+                ..QuicStreamerConfig::default_for_tests()
+            },
+            SwQosConfig {
+                exempt_senders: QosExemptSenders {
+                    pubkeys: HashSet::from([client_keypair.pubkey()]),
+                    ..QosExemptSenders::default()
+                },
+                ..SwQosConfig::default()
+            },
+        );
+        check_multiple_writes(receiver, server_address, Some(&client_keypair)).await;
+        cancel.cancel();
+        join_handle.await.unwrap();
+
+        assert_eq!(
+            stats
+                .connection_added_from_exempt_peer
+                .load(Ordering::Relaxed),
+            1
+        );
+        assert_eq!(stats.connection_removed.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.connection_remove_failed.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_handle_chunks_dropped_transaction_is_not_duplicate() {
+        let (packet_sender, packet_receiver) = bounded(1);
+        let stats = StreamerStats::default();
+        let mut recent_signatures = RecentSignatures::default();
+        let mut send_transaction = |signature: u8| {
+            let mut transaction = vec![1u8];
+            transaction.extend_from_slice(&[signature; 64]);
+            transaction.extend_from_slice(&[0; 10]);
+            let mut accum = PacketAccumulator::new(Meta::default());
+            for chunks in [vec![Bytes::from(transaction)], vec![]] {
+                let state = handle_chunks(
+                    chunks.into_iter(),
+                    &mut accum,
+                    &packet_sender,
+                    &stats,
+                    ConnectionPeerType::Staked(1),
+                    Some(&mut recent_signatures),
+                )
+                .unwrap();
+                if let StreamState::Finished(status) = state {
+                    return status;
+                }
+            }
+            unreachable!("stream was finished");
+        };
+
+        assert_eq!(send_transaction(1), TpuAckStatus::Accepted);
+        assert_eq!(send_transaction(1), TpuAckStatus::Duplicate);
+        // the channel is full, so the next transaction is dropped...
+        assert_eq!(send_transaction(2), TpuAckStatus::Dropped);
+        // ...and can be retried once there is room again
+        assert_eq!(packet_receiver.try_iter().count(), 1);
+        assert_eq!(send_transaction(2), TpuAckStatus::Accepted);
+        assert_eq!(send_transaction(2), TpuAckStatus::Duplicate);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_quic_server_unstaked_node_connect_failure() {
         agave_logger::setup();
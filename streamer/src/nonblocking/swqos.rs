@@ -3,10 +3,11 @@ use {
         nonblocking::{
             qos::{ConnectionContext, QosController},
             quic::{
-                get_connection_stake, update_open_connections_stat, ClientConnectionTracker,
-                ConnectionHandlerError, ConnectionPeerType, ConnectionTable, ConnectionTableKey,
-                ConnectionTableType, CONNECTION_CLOSE_CODE_DISALLOWED,
-                CONNECTION_CLOSE_CODE_EXCEED_MAX_STREAM_COUNT, CONNECTION_CLOSE_REASON_DISALLOWED,
+                get_connection_stake, get_remote_pubkey, update_open_connections_stat,
+                ClientConnectionTracker, ConnectionHandlerError, ConnectionPeerType,
+                ConnectionTable, ConnectionTableKey, ConnectionTableType,
+                CONNECTION_CLOSE_CODE_DISALLOWED, CONNECTION_CLOSE_CODE_EXCEED_MAX_STREAM_COUNT,
+                CONNECTION_CLOSE_REASON_DISALLOWED,
                 CONNECTION_CLOSE_REASON_EXCEED_MAX_STREAM_COUNT,
             },
             stream_throttle::{
@@ -20,6 +21,7 @@ use {
     percentage::Percentage,
     quinn::{Connection, VarInt, VarIntBoundsExceeded},
     solana_packet::PACKET_DATA_SIZE,
+    solana_pubkey::Pubkey,
     solana_quic_definitions::{
         QUIC_MAX_STAKED_CONCURRENT_STREAMS, QUIC_MAX_STAKED_RECEIVE_WINDOW_RATIO,
         QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS, QUIC_MIN_STAKED_CONCURRENT_STREAMS,
@@ -28,9 +30,11 @@ use {
     },
     solana_time_utils as timing,
     std::{
+        collections::HashSet,
         future::Future,
+        net::IpAddr,
         sync::{
-            atomic::{AtomicU64, Ordering},
+            atomic::{AtomicU64, AtomicUsize, Ordering},
             Arc, RwLock,
         },
     },
@@ -41,16 +45,44 @@ use {
 #[derive(Clone)]
 pub struct SwQosConfig {
     pub max_streams_per_ms: u64,
+    pub exempt_senders: QosExemptSenders,
 }
 
 impl Default for SwQosConfig {
     fn default() -> Self {
         SwQosConfig {
             max_streams_per_ms: DEFAULT_MAX_STREAMS_PER_MS,
+            exempt_senders: QosExemptSenders::default(),
         }
     }
 }
 
+/// Maximum number of open connections that are exempt from stake-weighted QoS
+///
+/// Connections from exempt senders beyond this limit are treated according to their stake, so a
+/// misconfigured exemption, e.g. of a shared IP address, cannot crowd out staked peers.
+pub const MAX_EXEMPT_CONNECTIONS: usize = 256;
+
+/// Senders that are exempt from stake-weighted QoS, typically the operator's own RPC nodes
+///
+/// Connections from these senders are always admitted to the staked connection table, are never
+/// evicted by staked peers, get the maximum number of concurrent streams and are not throttled,
+/// regardless of their stake, up to [`MAX_EXEMPT_CONNECTIONS`] connections.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QosExemptSenders {
+    pub pubkeys: HashSet<Pubkey>,
+    pub ip_addrs: HashSet<IpAddr>,
+}
+
+impl QosExemptSenders {
+    fn contains(&self, connection: &Connection) -> bool {
+        self.ip_addrs.contains(&connection.remote_address().ip())
+            || (!self.pubkeys.is_empty()
+                && get_remote_pubkey(connection)
+                    .is_some_and(|pubkey| self.pubkeys.contains(&pubkey)))
+    }
+}
+
 pub struct SwQos {
     max_staked_connections: usize,
     max_unstaked_connections: usize,
@@ -59,6 +91,8 @@ pub struct SwQos {
     staked_stream_load_ema: Arc<StakedStreamLoadEMA>,
     stats: Arc<StreamerStats>,
     staked_nodes: Arc<RwLock<StakedNodes>>,
+    exempt_senders: QosExemptSenders,
+    num_exempt_connections: AtomicUsize,
     unstaked_connection_table: Arc<Mutex<ConnectionTable>>,
     staked_connection_table: Arc<Mutex<ConnectionTable>>,
 }
@@ -71,6 +105,7 @@ pub struct SwQosConnectionContext {
     min_stake: u64,
     remote_pubkey: Option<solana_pubkey::Pubkey>,
     total_stake: u64,
+    exempt: bool,
     in_staked_table: bool,
     last_update: Arc<AtomicU64>,
     remote_address: std::net::SocketAddr,
@@ -110,6 +145,8 @@ impl SwQos {
             )),
             stats,
             staked_nodes,
+            exempt_senders: qos_config.exempt_senders,
+            num_exempt_connections: AtomicUsize::new(0),
             unstaked_connection_table: Arc::new(Mutex::new(ConnectionTable::new(
                 ConnectionTableType::Unstaked,
                 cancel.clone(),
@@ -120,6 +157,27 @@ impl SwQos {
             ))),
         }
     }
+
+    /// Reserves one of the [`MAX_EXEMPT_CONNECTIONS`] exempt connections, released by
+    /// `release_exempt_connection()` once the connection is removed or fails to be added
+    fn try_reserve_exempt_connection(&self) -> bool {
+        let reserved = self
+            .num_exempt_connections
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |num| {
+                (num < MAX_EXEMPT_CONNECTIONS).then_some(num.saturating_add(1))
+            })
+            .is_ok();
+        if !reserved {
+            self.stats
+                .exempt_connection_limit_reached
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        reserved
+    }
+
+    fn release_exempt_connection(&self) {
+        self.num_exempt_connections.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 /// Calculate the ratio for per connection receive window from a staked peer
@@ -207,11 +265,12 @@ impl SwQos {
         ),
         ConnectionHandlerError,
     > {
-        if let Ok(max_uni_streams) = VarInt::from_u64(compute_max_allowed_uni_streams(
-            conn_context.peer_type(),
-            conn_context.total_stake,
-        ) as u64)
-        {
+        let max_uni_streams = if conn_context.exempt {
+            QUIC_MAX_STAKED_CONCURRENT_STREAMS
+        } else {
+            compute_max_allowed_uni_streams(conn_context.peer_type(), conn_context.total_stake)
+        };
+        if let Ok(max_uni_streams) = VarInt::from_u64(max_uni_streams as u64) {
             let remote_addr = connection.remote_address();
             let receive_window = compute_recieve_window(
                 conn_context.max_stake,
@@ -323,6 +382,9 @@ impl SwQos {
     }
 
     fn max_streams_per_throttling_interval(&self, conn_context: &SwQosConnectionContext) -> u64 {
+        if conn_context.exempt {
+            return u64::MAX;
+        }
         self.staked_stream_load_ema
             .available_load_capacity_in_throttling_duration(
                 conn_context.peer_type,
@@ -333,13 +395,14 @@ impl SwQos {
 
 impl QosController<SwQosConnectionContext> for SwQos {
     fn build_connection_context(&self, connection: &Connection) -> SwQosConnectionContext {
-        get_connection_stake(connection, &self.staked_nodes).map_or(
+        let mut conn_context = get_connection_stake(connection, &self.staked_nodes).map_or(
             SwQosConnectionContext {
                 peer_type: ConnectionPeerType::Unstaked,
                 max_stake: 0,
                 min_stake: 0,
                 total_stake: 0,
                 remote_pubkey: None,
+                exempt: false,
                 in_staked_table: false,
                 remote_address: connection.remote_address(),
                 stream_counter: None,
@@ -368,13 +431,24 @@ impl QosController<SwQosConnectionContext> for SwQos {
                     min_stake,
                     total_stake,
                     remote_pubkey: Some(pubkey),
+                    exempt: false,
                     in_staked_table: false,
                     remote_address: connection.remote_address(),
                     last_update: Arc::new(AtomicU64::new(timing::timestamp())),
                     stream_counter: None,
                 }
             },
-        )
+        );
+
+        if self.exempt_senders.contains(connection) && self.try_reserve_exempt_connection() {
+            // The maximum stake keeps the connection from being evicted by any staked peer
+            conn_context.exempt = true;
+            conn_context.peer_type = ConnectionPeerType::Staked(u64::MAX);
+            conn_context.remote_pubkey = conn_context
+                .remote_pubkey
+                .or_else(|| get_remote_pubkey(connection));
+        }
+        conn_context
     }
 
     #[allow(clippy::manual_async_fn)]
@@ -388,6 +462,27 @@ impl QosController<SwQosConnectionContext> for SwQos {
             const PRUNE_RANDOM_SAMPLE_SIZE: usize = 2;
 
             match conn_context.peer_type() {
+                ConnectionPeerType::Staked(_) if conn_context.exempt => {
+                    // Exempt connections are admitted even if the staked table is full
+                    let connection_table_l = self.staked_connection_table.lock().await;
+                    if let Ok((last_update, cancel_connection, stream_counter)) = self
+                        .cache_new_connection(
+                            client_connection_tracker,
+                            connection,
+                            connection_table_l,
+                            conn_context,
+                        )
+                    {
+                        self.stats
+                            .connection_added_from_exempt_peer
+                            .fetch_add(1, Ordering::Relaxed);
+                        conn_context.in_staked_table = true;
+                        conn_context.last_update = last_update;
+                        conn_context.stream_counter = Some(stream_counter);
+                        return Some(cancel_connection);
+                    }
+                    self.release_exempt_connection();
+                }
                 ConnectionPeerType::Staked(stake) => {
                     let mut connection_table_l = self.staked_connection_table.lock().await;
 
@@ -519,6 +614,9 @@ impl QosController<SwQosConnectionContext> for SwQos {
                 stable_id,
             );
             update_open_connections_stat(&self.stats, &lock);
+            if conn_context.exempt {
+                self.release_exempt_connection();
+            }
             removed_count
         }
     }
@@ -617,4 +715,32 @@ pub mod test {
             QUIC_MAX_UNSTAKED_CONCURRENT_STREAMS
         );
     }
+
+    #[test]
+    fn test_exempt_connection_limit() {
+        let stats = Arc::new(StreamerStats::default());
+        let swqos = SwQos::new(
+            SwQosConfig::default(),
+            1,
+            1,
+            1,
+            1,
+            stats.clone(),
+            Arc::new(RwLock::new(StakedNodes::default())),
+            CancellationToken::new(),
+        );
+        for _ in 0..MAX_EXEMPT_CONNECTIONS {
+            assert!(swqos.try_reserve_exempt_connection());
+        }
+        assert!(!swqos.try_reserve_exempt_connection());
+        assert_eq!(
+            stats
+                .exempt_connection_limit_reached
+                .load(Ordering::Relaxed),
+            1
+        );
+
+        swqos.release_exempt_connection();
+        assert!(swqos.try_reserve_exempt_connection());
+    }
 }
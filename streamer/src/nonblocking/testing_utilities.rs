@@ -70,6 +70,13 @@ where
 }
 
 pub fn get_client_config(keypair: &Keypair) -> ClientConfig {
+    get_client_config_with_alpn(keypair, vec![ALPN_TPU_PROTOCOL_ID.to_vec()])
+}
+
+pub fn get_client_config_with_alpn(
+    keypair: &Keypair,
+    alpn_protocols: Vec<Vec<u8>>,
+) -> ClientConfig {
     let (cert, key) = new_dummy_x509_certificate(keypair);
 
     let mut crypto = tls_client_config_builder()
@@ -77,7 +84,7 @@ pub fn get_client_config(keypair: &Keypair) -> ClientConfig {
         .expect("Failed to use client certificate");
 
     crypto.enable_early_data = true;
-    crypto.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
+    crypto.alpn_protocols = alpn_protocols;
 
     let mut config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(crypto).unwrap()));
 
@@ -0,0 +1,150 @@
+//! Acknowledgements of transactions received over the QUIC TPU
+//!
+//! A client that offers [`ALPN_TPU_ACK_PROTOCOL_ID`] during the handshake, and is staked, gets a
+//! [`TpuAck`] for every stream it sends once the server has decided what to do with the
+//! transaction. Acks are sent as QUIC datagrams, so the client must accept datagrams and be
+//! prepared for an ack to be lost. Clients that only offer
+//! [`ALPN_TPU_PROTOCOL_ID`](crate::nonblocking::quic::ALPN_TPU_PROTOCOL_ID) see no change.
+
+use {
+    bytes::Bytes,
+    solana_signature::SIGNATURE_BYTES,
+    std::collections::{HashSet, VecDeque},
+};
+
+/// ALPN of version 1 of the acknowledged TPU protocol
+pub const ALPN_TPU_ACK_PROTOCOL_ID: &[u8] = b"solana-tpu-ack/1";
+
+/// Size of an encoded [`TpuAck`]
+pub const TPU_ACK_SIZE: usize = 9;
+
+/// Number of signatures remembered per connection to detect duplicates
+const RECENT_SIGNATURES_CAPACITY: usize = 1024;
+
+/// What the server did with a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TpuAckStatus {
+    /// Passed on to signature verification
+    Accepted = 0,
+    /// The same transaction was recently received on this connection and was discarded
+    Duplicate = 1,
+    /// Discarded because the server is overloaded, the client should retry later
+    Dropped = 2,
+}
+
+impl TryFrom<u8> for TpuAckStatus {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Accepted),
+            1 => Ok(Self::Duplicate),
+            2 => Ok(Self::Dropped),
+            value => Err(value),
+        }
+    }
+}
+
+/// Acknowledgement of the transaction sent on a unidirectional stream
+///
+/// Encoded as the little-endian stream id followed by the status byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TpuAck {
+    pub stream_id: u64,
+    pub status: TpuAckStatus,
+}
+
+impl TpuAck {
+    pub fn encode(&self) -> Bytes {
+        let mut buf = [0; TPU_ACK_SIZE];
+        buf[..8].copy_from_slice(&self.stream_id.to_le_bytes());
+        buf[8] = self.status as u8;
+        Bytes::copy_from_slice(&buf)
+    }
+
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        let buf: &[u8; TPU_ACK_SIZE] = buf.try_into().ok()?;
+        let (stream_id, status) = buf.split_at(8);
+        Some(Self {
+            stream_id: u64::from_le_bytes(stream_id.try_into().unwrap()),
+            status: TpuAckStatus::try_from(status[0]).ok()?,
+        })
+    }
+}
+
+/// Signatures of the transactions most recently received on a connection
+#[derive(Default)]
+pub(crate) struct RecentSignatures {
+    signatures: HashSet<[u8; SIGNATURE_BYTES]>,
+    order: VecDeque<[u8; SIGNATURE_BYTES]>,
+}
+
+impl RecentSignatures {
+    pub(crate) fn contains(&self, signature: &[u8; SIGNATURE_BYTES]) -> bool {
+        self.signatures.contains(signature)
+    }
+
+    /// Remembers `signature`, returns false if it was already known
+    pub(crate) fn insert(&mut self, signature: &[u8; SIGNATURE_BYTES]) -> bool {
+        if !self.signatures.insert(*signature) {
+            return false;
+        }
+        if self.order.len() == RECENT_SIGNATURES_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.signatures.remove(&oldest);
+            }
+        }
+        self.order.push_back(*signature);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tpu_ack_encoding() {
+        for status in [
+            TpuAckStatus::Accepted,
+            TpuAckStatus::Duplicate,
+            TpuAckStatus::Dropped,
+        ] {
+            let ack = TpuAck {
+                stream_id: 0x0102_0304_0506_0708,
+                status,
+            };
+            let buf = ack.encode();
+            assert_eq!(buf.len(), TPU_ACK_SIZE);
+            assert_eq!(TpuAck::decode(&buf), Some(ack));
+        }
+
+        assert_eq!(TpuAck::decode(&[0; TPU_ACK_SIZE - 1]), None);
+        assert_eq!(TpuAck::decode(&[0, 0, 0, 0, 0, 0, 0, 0, 3]), None);
+    }
+
+    #[test]
+    fn test_recent_signatures() {
+        let mut recent = RecentSignatures::default();
+        let signature = |i: usize| {
+            let mut signature = [0; SIGNATURE_BYTES];
+            signature[..8].copy_from_slice(&i.to_le_bytes());
+            signature
+        };
+
+        assert!(!recent.contains(&signature(0)));
+        assert!(recent.insert(&signature(0)));
+        assert!(recent.contains(&signature(0)));
+        assert!(!recent.insert(&signature(0)));
+        for i in 1..RECENT_SIGNATURES_CAPACITY {
+            assert!(recent.insert(&signature(i)));
+        }
+        assert!(!recent.insert(&signature(0)));
+
+        // the oldest signature is forgotten once the capacity is exceeded
+        assert!(recent.insert(&signature(RECENT_SIGNATURES_CAPACITY)));
+        assert!(recent.insert(&signature(0)));
+        assert!(!recent.insert(&signature(RECENT_SIGNATURES_CAPACITY)));
+    }
+}
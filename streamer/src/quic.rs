@@ -5,6 +5,7 @@ use {
             quic::{ALPN_TPU_PROTOCOL_ID, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT},
             simple_qos::{SimpleQos, SimpleQosConfig},
             swqos::{SwQos, SwQosConfig},
+            tpu_ack::ALPN_TPU_ACK_PROTOCOL_ID,
         },
         streamer::StakedNodes,
     },
@@ -76,9 +77,13 @@ pub struct SpawnServerResult {
 }
 
 /// Returns default server configuration along with its PEM certificate chain.
+///
+/// With `enable_transaction_acks` the server also offers the acknowledged TPU protocol, see
+/// [`crate::nonblocking::tpu_ack`].
 #[allow(clippy::field_reassign_with_default)] // https://github.com/rust-lang/rust-clippy/issues/6527
 pub(crate) fn configure_server(
     identity_keypair: &Keypair,
+    enable_transaction_acks: bool,
 ) -> Result<(ServerConfig, String), QuicServerError> {
     let (cert, priv_key) = new_dummy_x509_certificate(identity_keypair);
     let cert_chain_pem_parts = vec![Pem {
@@ -89,7 +94,15 @@ pub(crate) fn configure_server(
 
     let mut server_tls_config =
         tls_server_config_builder().with_single_cert(vec![cert], priv_key)?;
-    server_tls_config.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
+    // the server's order decides, so clients offering both protocols get acks
+    server_tls_config.alpn_protocols = if enable_transaction_acks {
+        vec![
+            ALPN_TPU_ACK_PROTOCOL_ID.to_vec(),
+            ALPN_TPU_PROTOCOL_ID.to_vec(),
+        ]
+    } else {
+        vec![ALPN_TPU_PROTOCOL_ID.to_vec()]
+    };
     server_tls_config.key_log = Arc::new(KeyLogFile::new());
     let quic_server_config = QuicServerConfig::try_from(server_tls_config)?;
 
@@ -144,11 +157,12 @@ pub enum QuicServerError {
 
 pub struct EndpointKeyUpdater {
     endpoints: Vec<Endpoint>,
+    enable_transaction_acks: bool,
 }
 
 impl NotifyKeyUpdate for EndpointKeyUpdater {
     fn update_key(&self, key: &Keypair) -> Result<(), Box<dyn std::error::Error>> {
-        let (config, _) = configure_server(key)?;
+        let (config, _) = configure_server(key, self.enable_transaction_acks)?;
         for endpoint in &self.endpoints {
             endpoint.set_server_config(Some(config.clone()));
         }
@@ -169,6 +183,9 @@ pub struct StreamerStats {
     pub(crate) total_handle_chunk_to_packet_send_full_err: AtomicUsize,
     pub(crate) total_handle_chunk_to_packet_send_disconnected_err: AtomicUsize,
     pub(crate) total_packet_batches_none: AtomicUsize,
+    pub(crate) total_duplicate_packets: AtomicUsize,
+    pub(crate) total_tpu_acks_sent: AtomicUsize,
+    pub(crate) total_tpu_ack_send_errors: AtomicUsize,
     pub(crate) total_packets_sent_to_consumer: AtomicUsize,
     pub(crate) total_bytes_sent_to_consumer: AtomicUsize,
     pub(crate) total_chunks_processed_by_batcher: AtomicUsize,
@@ -178,6 +195,8 @@ pub struct StreamerStats {
     pub(crate) num_evictions_unstaked: AtomicUsize,
     pub(crate) connection_added_from_staked_peer: AtomicUsize,
     pub(crate) connection_added_from_unstaked_peer: AtomicUsize,
+    pub(crate) connection_added_from_exempt_peer: AtomicUsize,
+    pub(crate) exempt_connection_limit_reached: AtomicUsize,
     pub(crate) connection_add_failed: AtomicUsize,
     pub(crate) connection_add_failed_invalid_stream_count: AtomicUsize,
     pub(crate) connection_add_failed_staked_node: AtomicUsize,
@@ -274,6 +293,18 @@ impl StreamerStats {
                     .swap(0, Ordering::Relaxed),
                 i64
             ),
+            (
+                "connection_added_from_exempt_peer",
+                self.connection_added_from_exempt_peer
+                    .swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "exempt_connection_limit_reached",
+                self.exempt_connection_limit_reached
+                    .swap(0, Ordering::Relaxed),
+                i64
+            ),
             (
                 "connection_add_failed",
                 self.connection_add_failed.swap(0, Ordering::Relaxed),
@@ -438,6 +469,21 @@ impl StreamerStats {
                 self.total_packet_batches_none.swap(0, Ordering::Relaxed),
                 i64
             ),
+            (
+                "duplicate_packets",
+                self.total_duplicate_packets.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "tpu_acks_sent",
+                self.total_tpu_acks_sent.swap(0, Ordering::Relaxed),
+                i64
+            ),
+            (
+                "tpu_ack_send_errors",
+                self.total_tpu_ack_send_errors.swap(0, Ordering::Relaxed),
+                i64
+            ),
             (
                 "stream_read_errors",
                 self.total_stream_read_errors.swap(0, Ordering::Relaxed),
@@ -567,6 +613,9 @@ pub struct QuicStreamerConfig {
     pub max_connections_per_ipaddr_per_min: u64,
     pub wait_for_chunk_timeout: Duration,
     pub num_threads: NonZeroUsize,
+    /// Acknowledge the transactions of staked clients that negotiate the acknowledged TPU
+    /// protocol
+    pub enable_transaction_acks: bool,
 }
 
 #[derive(Clone)]
@@ -591,6 +640,7 @@ impl Default for QuicStreamerConfig {
             max_connections_per_ipaddr_per_min: DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             wait_for_chunk_timeout: DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             num_threads: NonZeroUsize::new(num_cpus::get().min(1)).expect("1 is non-zero"),
+            enable_transaction_acks: false,
         }
     }
 }
@@ -633,6 +683,7 @@ where
     C: ConnectionContext + Send + Sync + 'static,
 {
     let runtime = rt(format!("{thread_name}Rt"), quic_server_params.num_threads);
+    let enable_transaction_acks = quic_server_params.enable_transaction_acks;
     let result = {
         let _guard = runtime.enter();
         crate::nonblocking::quic::spawn_server(
@@ -656,6 +707,7 @@ where
         .unwrap();
     let updater = EndpointKeyUpdater {
         endpoints: result.endpoints.clone(),
+        enable_transaction_acks,
     };
     Ok(SpawnServerResult {
         endpoints: result.endpoints,
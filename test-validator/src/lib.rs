@@ -15,7 +15,7 @@ use {
         accounts_db::AccountsDbConfig, accounts_index::AccountsIndexConfig,
         utils::create_accounts_run_and_snapshot_dirs,
     },
-    solana_cli_output::CliAccount,
+    solana_cli_output::{accounts_export::CliAccountsExport, CliAccount},
     solana_clock::{Slot, DEFAULT_MS_PER_SLOT},
     solana_commitment_config::CommitmentConfig,
     solana_compute_budget::compute_budget::ComputeBudget,
@@ -67,7 +67,7 @@ use {
         ffi::OsStr,
         fmt::Display,
         fs::{self, remove_dir_all, File},
-        io::Read,
+        io::{BufReader, Read},
         net::{IpAddr, Ipv4Addr, SocketAddr},
         num::{NonZero, NonZeroU64},
         path::{Path, PathBuf},
@@ -558,6 +558,31 @@ impl TestValidatorGenesis {
         Ok(self)
     }
 
+    /// Add the accounts of exports written by `agave-ledger-tool accounts export`, after checking
+    /// them against their hashes
+    pub fn add_accounts_from_exports<T, P>(&mut self, exports: T) -> Result<&mut Self, String>
+    where
+        T: IntoIterator<Item = P>,
+        P: AsRef<Path> + Display,
+    {
+        for export in exports {
+            let file =
+                File::open(&export).map_err(|err| format!("Unable to open {export}: {err}"))?;
+            let accounts_export: CliAccountsExport = serde_json::from_reader(BufReader::new(file))
+                .map_err(|err| format!("Unable to deserialize {export}: {err}"))?;
+            let accounts = accounts_export
+                .decode()
+                .map_err(|err| format!("Invalid accounts export {export}: {err}"))?;
+            info!(
+                "Loading {} accounts exported at slot {} from {export}",
+                accounts.len(),
+                accounts_export.slot
+            );
+            self.add_accounts(accounts);
+        }
+        Ok(self)
+    }
+
     pub fn add_accounts_from_directories<T, P>(&mut self, dirs: T) -> Result<&mut Self, String>
     where
         T: IntoIterator<Item = P>,
@@ -1099,6 +1124,7 @@ impl TestValidator {
                 }),
             log_messages_bytes_limit: config.log_messages_bytes_limit,
             transaction_account_lock_limit: config.transaction_account_lock_limit,
+            ..RuntimeConfig::default()
         };
 
         let mut validator_config = ValidatorConfig {
@@ -18,7 +18,7 @@ use {
     solana_clock::Slot,
     solana_gossip::{
         cluster_info::{ClusterInfo, ClusterInfoError},
-        contact_info::Protocol,
+        contact_info::{Protocol, CAPABILITY_ENCRYPTED_SHRED_TRANSPORT},
     },
     solana_keypair::Keypair,
     solana_ledger::{blockstore::Blockstore, shred::Shred},
@@ -504,6 +504,9 @@ pub fn broadcast_shreds(
         let bank_forks = bank_forks.read().unwrap();
         (bank_forks.root_bank(), bank_forks.working_bank())
     };
+    let encrypted_transport = cluster_info
+        .my_contact_info()
+        .has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
     let (packets, quic_packets): (Vec<_>, Vec<_>) = shreds
         .iter()
         .chunk_by(|shred| shred.slot())
@@ -514,10 +517,19 @@ pub fn broadcast_shreds(
             update_peer_stats(&cluster_nodes, last_datapoint_submit);
             shreds.filter_map(move |shred| {
                 let key = shred.id();
-                let protocol = cluster_nodes::get_broadcast_protocol(&key);
-                cluster_nodes
-                    .get_broadcast_peer(&key)?
-                    .tvu(protocol)
+                let node = cluster_nodes.get_broadcast_peer(&key)?;
+                // Peers which both declared the encrypted shred transport
+                // only take shreds from each other over QUIC.
+                let protocol = match cluster_nodes::get_broadcast_protocol(&key) {
+                    Protocol::UDP
+                        if encrypted_transport
+                            && node.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT) =>
+                    {
+                        Protocol::QUIC
+                    }
+                    protocol => protocol,
+                };
+                node.tvu(protocol)
                     .filter(|addr| socket_addr_space.check(addr))
                     .map(|addr| {
                         (match protocol {
@@ -610,27 +610,83 @@ impl<T: 'static> ClusterNodesCache<T> {
         });
         // Initialize if needed by only a single thread outside locks.
         let (_, nodes) = entry.get_or_init(|| {
-            let epoch_staked_nodes = [root_bank, working_bank]
-                .iter()
-                .find_map(|bank| bank.epoch_staked_nodes(epoch))
-                .unwrap_or_else(|| {
-                    error!(
-                        "ClusterNodesCache::get: unknown Bank::epoch_staked_nodes for epoch: \
-                         {epoch}, slot: {shred_slot}"
-                    );
-                    inc_new_counter_error!("cluster_nodes-unknown_epoch_staked_nodes", 1);
-                    Arc::<HashMap<Pubkey, /*stake:*/ u64>>::default()
-                });
-            let nodes = new_cluster_nodes::<T>(
+            let nodes = Self::compute(
+                epoch,
+                shred_slot,
+                root_bank,
+                working_bank,
                 cluster_info,
-                root_bank.cluster_type(),
-                &epoch_staked_nodes,
                 use_cha_cha_8,
             );
             (Instant::now(), Arc::new(nodes))
         });
         nodes.clone()
     }
+
+    /// Recomputes the entry for the epoch of `shred_slot` if it is missing or past half of its
+    /// time-to-live, so that it is still fresh when shreds of that epoch start arriving and
+    /// [`Self::get`] does not have to compute it on the shred processing path.
+    pub fn refresh(
+        &self,
+        shred_slot: Slot,
+        root_bank: &Bank,
+        working_bank: &Bank,
+        cluster_info: &ClusterInfo,
+    ) {
+        let epoch = root_bank.epoch_schedule().get_epoch(shred_slot);
+        let is_fresh = {
+            let cache = self.cache.read().unwrap();
+            cache
+                .get(&epoch)
+                .and_then(|entry| entry.get())
+                .is_some_and(|(asof, _)| asof.elapsed() < self.ttl / 2)
+        };
+        if is_fresh {
+            return;
+        }
+        let use_cha_cha_8 = check_feature_activation(
+            &feature_set::switch_to_chacha8_turbine::ID,
+            shred_slot,
+            root_bank,
+        );
+        let nodes = Self::compute(
+            epoch,
+            shred_slot,
+            root_bank,
+            working_bank,
+            cluster_info,
+            use_cha_cha_8,
+        );
+        let entry = OnceLock::from((Instant::now(), Arc::new(nodes)));
+        self.cache.write().unwrap().put(epoch, Arc::new(entry));
+    }
+
+    fn compute(
+        epoch: Epoch,
+        shred_slot: Slot,
+        root_bank: &Bank,
+        working_bank: &Bank,
+        cluster_info: &ClusterInfo,
+        use_cha_cha_8: bool,
+    ) -> ClusterNodes<T> {
+        let epoch_staked_nodes = [root_bank, working_bank]
+            .iter()
+            .find_map(|bank| bank.epoch_staked_nodes(epoch))
+            .unwrap_or_else(|| {
+                error!(
+                    "ClusterNodesCache::get: unknown Bank::epoch_staked_nodes for epoch: \
+                     {epoch}, slot: {shred_slot}"
+                );
+                inc_new_counter_error!("cluster_nodes-unknown_epoch_staked_nodes", 1);
+                Arc::<HashMap<Pubkey, /*stake:*/ u64>>::default()
+            });
+        new_cluster_nodes::<T>(
+            cluster_info,
+            root_bank.cluster_type(),
+            &epoch_staked_nodes,
+            use_cha_cha_8,
+        )
+    }
 }
 
 impl From<ContactInfo> for NodeId {
@@ -1008,6 +1064,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cluster_nodes_cache_refresh() {
+        let mut rng = rand::thread_rng();
+        let (_nodes, _stakes, cluster_info) = make_test_cluster(&mut rng, 20, None);
+        let genesis_config =
+            solana_runtime::genesis_utils::create_genesis_config(100).genesis_config;
+        let bank = Bank::new_for_tests(&genesis_config);
+        let next_epoch_slot = bank
+            .epoch_schedule()
+            .get_first_slot_in_epoch(bank.epoch() + 1);
+
+        // A fresh entry is left alone
+        let cache = ClusterNodesCache::<RetransmitStage>::new(2, Duration::from_secs(60));
+        cache.refresh(next_epoch_slot, &bank, &bank, &cluster_info);
+        let nodes = cache.get(next_epoch_slot, &bank, &bank, &cluster_info);
+        cache.refresh(next_epoch_slot, &bank, &bank, &cluster_info);
+        assert!(Arc::ptr_eq(
+            &nodes,
+            &cache.get(next_epoch_slot, &bank, &bank, &cluster_info)
+        ));
+
+        // An entry past half of its time-to-live is recomputed
+        let cache = ClusterNodesCache::<RetransmitStage>::new(2, Duration::ZERO);
+        let nodes = cache.get(next_epoch_slot, &bank, &bank, &cluster_info);
+        cache.refresh(next_epoch_slot, &bank, &bank, &cluster_info);
+        let refreshed = cache
+            .cache
+            .read()
+            .unwrap()
+            .get(&bank.epoch().saturating_add(1))
+            .cloned();
+        let (_, refreshed) = refreshed.unwrap().get().cloned().unwrap();
+        assert!(!Arc::ptr_eq(&nodes, &refreshed));
+    }
+
     #[test]
     fn test_get_retransmit_nodes() {
         // fanout 2
@@ -0,0 +1,277 @@
+//! Repair and turbine traffic between peers which both declared
+//! `CAPABILITY_ENCRYPTED_SHRED_TRANSPORT` goes over QUIC instead of UDP. QUIC authenticates both
+//! ends by their identity and encrypts the traffic, and the turbine QUIC endpoints of such peers
+//! add an authenticity tag to every shred they exchange. A shred which cannot be queued for QUIC
+//! is still sent over UDP, and is checked against the leader's signature like any other UDP shred.
+
+use {
+    bytes::Bytes,
+    solana_gossip::{
+        cluster_info::ClusterInfo,
+        contact_info::{Protocol, CAPABILITY_ENCRYPTED_SHRED_TRANSPORT},
+    },
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        net::SocketAddr,
+        time::{Duration, Instant},
+    },
+    tokio::sync::mpsc::Sender as AsyncSender,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The peers which declared CAPABILITY_ENCRYPTED_SHRED_TRANSPORT, if this node declared it too
+#[derive(Default)]
+pub struct EncryptedTransportPeers {
+    asof: Option<Instant>,
+    /// QUIC TVU addresses of the peers, by their UDP TVU address
+    quic_addrs: HashMap<SocketAddr, SocketAddr>,
+}
+
+/// Output of [`EncryptedTransportPeers::send`]
+pub(crate) struct EncryptedTransportSend<'a> {
+    /// Addresses left to send the shred to over UDP
+    pub(crate) udp_addrs: Cow<'a, [SocketAddr]>,
+    /// Number of peers the shred was sent to over QUIC
+    pub(crate) num_nodes: usize,
+    /// Number of peers among `udp_addrs` which take encrypted shreds, but could not be sent the
+    /// shred over QUIC
+    pub(crate) num_fallbacks: usize,
+}
+
+impl EncryptedTransportPeers {
+    pub fn maybe_refresh(&mut self, cluster_info: &ClusterInfo) {
+        if self
+            .asof
+            .is_some_and(|asof| asof.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.asof = Some(Instant::now());
+        self.quic_addrs.clear();
+        if !cluster_info
+            .my_contact_info()
+            .has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT)
+        {
+            return;
+        }
+        let peers = cluster_info.tvu_peers(|node| {
+            node.has_capability(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT)
+                .then(|| node.tvu(Protocol::UDP).zip(node.tvu(Protocol::QUIC)))
+                .flatten()
+        });
+        self.quic_addrs.extend(peers.into_iter().flatten());
+    }
+
+    /// Sends `shred` over QUIC to the peers among `addrs` which take encrypted shreds, and leaves
+    /// the other peers to send it to over UDP. A peer is left to UDP too if the QUIC endpoint
+    /// does not take the shred, rather than losing it.
+    pub(crate) fn send<'a>(
+        &self,
+        shred: &Bytes,
+        addrs: &'a [SocketAddr],
+        quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
+    ) -> EncryptedTransportSend<'a> {
+        if self.quic_addrs.is_empty() {
+            return EncryptedTransportSend {
+                udp_addrs: Cow::Borrowed(addrs),
+                num_nodes: 0,
+                num_fallbacks: 0,
+            };
+        }
+        let mut udp_addrs = Vec::with_capacity(addrs.len());
+        let mut num_nodes = 0;
+        let mut num_fallbacks = 0;
+        for addr in addrs {
+            match self.quic_addrs.get(addr) {
+                None => udp_addrs.push(*addr),
+                Some(&quic_addr) => {
+                    if quic_endpoint_sender
+                        .try_send((quic_addr, shred.clone()))
+                        .is_ok()
+                    {
+                        num_nodes += 1;
+                    } else {
+                        udp_addrs.push(*addr);
+                        num_fallbacks += 1;
+                    }
+                }
+            }
+        }
+        EncryptedTransportSend {
+            udp_addrs: Cow::Owned(udp_addrs),
+            num_nodes,
+            num_fallbacks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::quic_endpoint::{close_quic_endpoint, new_quic_endpoint},
+        solana_gossip::contact_info::ContactInfo,
+        solana_keypair::Keypair,
+        solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_net_utils::sockets::{bind_to, localhost_port_range_for_tests},
+        solana_runtime::{bank::Bank, bank_forks::BankForks},
+        solana_signer::Signer,
+        solana_streamer::socket::SocketAddrSpace,
+        solana_time_utils::timestamp,
+        std::{
+            net::{IpAddr, Ipv4Addr},
+            sync::Arc,
+        },
+    };
+
+    fn new_contact_info(keypair: &Keypair, ip: Ipv4Addr, encrypted: bool) -> ContactInfo {
+        let mut node = ContactInfo::new_localhost(&keypair.pubkey(), timestamp());
+        node.set_gossip((ip, 8000)).unwrap();
+        node.set_tvu(Protocol::UDP, (ip, 8001)).unwrap();
+        node.set_tvu(Protocol::QUIC, (ip, 8002)).unwrap();
+        node.set_serve_repair(Protocol::UDP, (ip, 8003)).unwrap();
+        if encrypted {
+            node.set_capabilities(CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+        }
+        node
+    }
+
+    #[test]
+    fn test_encrypted_transport_peers_send() {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+        let addrs: Vec<SocketAddr> = (0..4)
+            .map(|k| SocketAddr::from(([127, 0, 0, 1], 8000 + k)))
+            .collect();
+        let shred = Bytes::from_static(b"shred");
+        let mut peers = EncryptedTransportPeers::default();
+        let out = peers.send(&shred, &addrs, &sender);
+        assert_eq!(&*out.udp_addrs, &addrs[..]);
+        assert_eq!((out.num_nodes, out.num_fallbacks), (0, 0));
+
+        let quic_addr = SocketAddr::from(([127, 0, 0, 1], 9001));
+        peers.quic_addrs.insert(addrs[1], quic_addr);
+        peers.quic_addrs.insert(addrs[2], quic_addr);
+        // The QUIC channel only takes one shred, the other encrypted peer falls back to UDP
+        let out = peers.send(&shred, &addrs, &sender);
+        assert_eq!(&*out.udp_addrs, &[addrs[0], addrs[2], addrs[3]]);
+        assert_eq!((out.num_nodes, out.num_fallbacks), (1, 1));
+        assert_eq!(receiver.try_recv().unwrap(), (quic_addr, shred));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_encrypted_transport_peers_refresh() {
+        let keypair = Arc::new(Keypair::new());
+        let encrypted = Keypair::new();
+        let plain = Keypair::new();
+        for self_encrypted in [false, true] {
+            let cluster_info = ClusterInfo::new(
+                new_contact_info(&keypair, Ipv4Addr::new(10, 0, 0, 3), self_encrypted),
+                keypair.clone(),
+                SocketAddrSpace::Unspecified,
+            );
+            let encrypted = new_contact_info(&encrypted, Ipv4Addr::new(10, 0, 0, 1), true);
+            // Behind the same address as the encrypted peer, but on other ports
+            let mut plain = new_contact_info(&plain, Ipv4Addr::new(10, 0, 0, 1), false);
+            plain
+                .set_tvu(Protocol::UDP, (Ipv4Addr::new(10, 0, 0, 1), 9001))
+                .unwrap();
+            plain
+                .set_tvu(Protocol::QUIC, (Ipv4Addr::new(10, 0, 0, 1), 9002))
+                .unwrap();
+            cluster_info.insert_info(encrypted.clone());
+            cluster_info.insert_info(plain.clone());
+            let mut peers = EncryptedTransportPeers::default();
+            peers.maybe_refresh(&cluster_info);
+            assert_eq!(
+                peers
+                    .quic_addrs
+                    .get(&encrypted.tvu(Protocol::UDP).unwrap())
+                    .copied(),
+                self_encrypted.then(|| encrypted.tvu(Protocol::QUIC).unwrap()),
+            );
+            assert!(!peers
+                .quic_addrs
+                .contains_key(&plain.tvu(Protocol::UDP).unwrap()));
+        }
+    }
+
+    // From the capabilities declared in gossip to the shred arriving at the peer: over QUIC,
+    // with the authenticity tag checked and stripped, to the peer which takes encrypted shreds,
+    // and left to UDP for the other one
+    #[test]
+    fn test_encrypted_transport_end_to_end() {
+        const RECV_TIMEOUT: Duration = Duration::from_secs(60);
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .unwrap();
+        let bank_forks = {
+            let GenesisConfigInfo { genesis_config, .. } =
+                create_genesis_config(/*mint_lamports:*/ 100_000);
+            BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config))
+        };
+        let port_range = localhost_port_range_for_tests();
+        let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let mut sockets = (port_range.0..port_range.1).map(|port| bind_to(ip_addr, port).unwrap());
+        let (sender_socket, peer_socket) = (sockets.next().unwrap(), sockets.next().unwrap());
+        let (keypair, peer_keypair) = (Arc::new(Keypair::new()), Keypair::new());
+        let sender_addr = sender_socket.local_addr().unwrap();
+        let peer_quic_addr = peer_socket.local_addr().unwrap();
+        let (sender_receiver_sender, _sender_receiver) = crossbeam_channel::unbounded();
+        let (peer_receiver_sender, peer_receiver) = crossbeam_channel::unbounded();
+        let (sender_endpoint, quic_endpoint_sender, sender_task) = new_quic_endpoint(
+            runtime.handle(),
+            &keypair,
+            sender_socket,
+            sender_receiver_sender,
+            bank_forks.clone(),
+            /*authenticity_tags:*/ true,
+        )
+        .unwrap();
+        let (peer_endpoint, peer_sender, peer_task) = new_quic_endpoint(
+            runtime.handle(),
+            &peer_keypair,
+            peer_socket,
+            peer_receiver_sender,
+            bank_forks,
+            /*authenticity_tags:*/ true,
+        )
+        .unwrap();
+
+        let cluster_info = ClusterInfo::new(
+            new_contact_info(&keypair, Ipv4Addr::LOCALHOST, true),
+            keypair.clone(),
+            SocketAddrSpace::Unspecified,
+        );
+        let mut peer = new_contact_info(&peer_keypair, Ipv4Addr::new(10, 0, 0, 1), true);
+        peer.set_tvu(Protocol::QUIC, peer_quic_addr).unwrap();
+        let plain = new_contact_info(&Keypair::new(), Ipv4Addr::new(10, 0, 0, 2), false);
+        cluster_info.insert_info(peer.clone());
+        cluster_info.insert_info(plain.clone());
+        let mut peers = EncryptedTransportPeers::default();
+        peers.maybe_refresh(&cluster_info);
+
+        let shred = Bytes::from(vec![7u8; 1228]);
+        let addrs = [
+            peer.tvu(Protocol::UDP).unwrap(),
+            plain.tvu(Protocol::UDP).unwrap(),
+        ];
+        let out = peers.send(&shred, &addrs, &quic_endpoint_sender);
+        assert_eq!(&*out.udp_addrs, &[plain.tvu(Protocol::UDP).unwrap()]);
+        assert_eq!((out.num_nodes, out.num_fallbacks), (1, 0));
+        assert_eq!(
+            peer_receiver.recv_timeout(RECV_TIMEOUT).unwrap(),
+            (keypair.pubkey(), sender_addr, shred)
+        );
+
+        drop((quic_endpoint_sender, peer_sender));
+        close_quic_endpoint(&sender_endpoint);
+        close_quic_endpoint(&peer_endpoint);
+        runtime.block_on(sender_task).unwrap();
+        runtime.block_on(peer_task).unwrap();
+    }
+}
@@ -15,6 +15,8 @@ pub mod broadcast_stage;
 
 pub mod cluster_nodes;
 
+pub mod encrypted_transport;
+
 pub mod quic_endpoint;
 
 pub mod retransmit_stage;
@@ -1,10 +1,10 @@
 use {
-    bytes::Bytes,
+    bytes::{Bytes, BytesMut},
     crossbeam_channel::Sender,
     futures::future::TryJoin,
     log::error,
     quinn::{
-        crypto::rustls::{QuicClientConfig, QuicServerConfig},
+        crypto::rustls::{HandshakeData, QuicClientConfig, QuicServerConfig},
         ClientConfig, ConnectError, Connecting, Connection, ConnectionError, Endpoint,
         EndpointConfig, IdleTimeout, SendDatagramError, ServerConfig, TokioRuntime,
         TransportConfig, VarInt,
@@ -31,6 +31,7 @@ use {
         },
         time::Duration,
     },
+    subtle::ConstantTimeEq,
     thiserror::Error,
     tokio::{
         sync::{
@@ -45,6 +46,12 @@ const CLIENT_CHANNEL_BUFFER: usize = 1 << 14;
 const ROUTER_CHANNEL_BUFFER: usize = 64;
 const CONNECTION_CACHE_CAPACITY: usize = 3072;
 const ALPN_TURBINE_PROTOCOL_ID: &[u8] = b"solana-turbine";
+// Datagrams carry an authenticity tag, see new_quic_endpoint.
+const ALPN_TURBINE_TAGGED_PROTOCOL_ID: &[u8] = b"solana-turbine-tagged";
+
+// Authenticity tags.
+const AUTHENTICITY_KEY_LABEL: &[u8] = b"EXPORTER-solana-turbine-authenticity-tag";
+const AUTHENTICITY_TAG_SIZE: usize = 8;
 
 // Transport config.
 const DATAGRAM_RECEIVE_BUFFER_SIZE: usize = 256 * 1024 * 1024;
@@ -68,6 +75,8 @@ const CONNECTION_CLOSE_REASON_PRUNED: &[u8] = b"PRUNED";
 
 pub type AsyncTryJoinHandle = TryJoin<JoinHandle<()>, JoinHandle<()>>;
 
+type AuthenticityKey = [u8; blake3::KEY_LEN];
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Channel Send Error")]
@@ -76,6 +85,8 @@ pub enum Error {
     ConnectError(#[from] ConnectError),
     #[error(transparent)]
     ConnectionError(#[from] ConnectionError),
+    #[error("Export Keying Material Error")]
+    ExportKeyingMaterialError,
     #[error("Invalid Identity: {0:?}")]
     InvalidIdentity(SocketAddr),
     #[error(transparent)]
@@ -92,6 +103,9 @@ macro_rules! add_metric {
     }};
 }
 
+/// With `authenticity_tags`, the endpoint prefers connections where each datagram carries a tag,
+/// a keyed hash of the shred under a key exported from the TLS session, and drops datagrams with
+/// an invalid tag. Connections to peers which do not support the tags carry plain datagrams.
 #[allow(clippy::type_complexity)]
 pub fn new_quic_endpoint(
     runtime: &tokio::runtime::Handle,
@@ -99,6 +113,7 @@ pub fn new_quic_endpoint(
     socket: UdpSocket,
     sender: Sender<(Pubkey, SocketAddr, Bytes)>,
     bank_forks: Arc<RwLock<BankForks>>,
+    authenticity_tags: bool,
 ) -> Result<
     (
         Endpoint,
@@ -108,8 +123,8 @@ pub fn new_quic_endpoint(
     Error,
 > {
     let (cert, key) = new_dummy_x509_certificate(keypair);
-    let server_config = new_server_config(cert.clone(), key.clone_key())?;
-    let client_config = new_client_config(cert, key)?;
+    let server_config = new_server_config(cert.clone(), key.clone_key(), authenticity_tags)?;
+    let client_config = new_client_config(cert, key, authenticity_tags)?;
     let mut endpoint = {
         // Endpoint::new requires entering the runtime context,
         // otherwise the code below will panic.
@@ -154,12 +169,25 @@ pub fn close_quic_endpoint(endpoint: &Endpoint) {
     );
 }
 
+// The server picks the first of its protocols which the client offers as well.
+fn alpn_protocols(authenticity_tags: bool) -> Vec<Vec<u8>> {
+    if authenticity_tags {
+        vec![
+            ALPN_TURBINE_TAGGED_PROTOCOL_ID.to_vec(),
+            ALPN_TURBINE_PROTOCOL_ID.to_vec(),
+        ]
+    } else {
+        vec![ALPN_TURBINE_PROTOCOL_ID.to_vec()]
+    }
+}
+
 fn new_server_config(
     cert: CertificateDer<'static>,
     key: PrivateKeyDer<'static>,
+    authenticity_tags: bool,
 ) -> Result<ServerConfig, rustls::Error> {
     let mut config = tls_server_config_builder().with_single_cert(vec![cert], key)?;
-    config.alpn_protocols = vec![ALPN_TURBINE_PROTOCOL_ID.to_vec()];
+    config.alpn_protocols = alpn_protocols(authenticity_tags);
     config.key_log = Arc::new(KeyLogFile::new());
     let quic_server_config = QuicServerConfig::try_from(config)
         .map_err(|_err| rustls::Error::InvalidCertificate(CertificateError::BadSignature))?;
@@ -174,10 +202,11 @@ fn new_server_config(
 fn new_client_config(
     cert: CertificateDer<'static>,
     key: PrivateKeyDer<'static>,
+    authenticity_tags: bool,
 ) -> Result<ClientConfig, rustls::Error> {
     let mut config = tls_client_config_builder().with_client_auth_cert(vec![cert], key)?;
     config.enable_early_data = true;
-    config.alpn_protocols = vec![ALPN_TURBINE_PROTOCOL_ID.to_vec()];
+    config.alpn_protocols = alpn_protocols(authenticity_tags);
     let mut config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(config).unwrap()));
     config.transport_config(Arc::new(new_transport_config()));
     Ok(config)
@@ -339,6 +368,7 @@ async fn handle_connecting(
     let connection = connecting.await?;
     let remote_address = connection.remote_address();
     let remote_pubkey = get_remote_pubkey(&connection)?;
+    let authenticity_key = get_authenticity_key(&connection)?;
     let receiver = {
         let (sender, receiver) = tokio::sync::mpsc::channel(ROUTER_CHANNEL_BUFFER);
         router.write().await.insert(remote_address, sender);
@@ -348,6 +378,7 @@ async fn handle_connecting(
         endpoint,
         remote_address,
         remote_pubkey,
+        authenticity_key,
         connection,
         sender,
         receiver,
@@ -366,6 +397,7 @@ async fn handle_connection(
     endpoint: Endpoint,
     remote_address: SocketAddr,
     remote_pubkey: Pubkey,
+    authenticity_key: Option<AuthenticityKey>,
     connection: Connection,
     sender: Sender<(Pubkey, SocketAddr, Bytes)>,
     receiver: AsyncReceiver<Bytes>,
@@ -384,11 +416,16 @@ async fn handle_connection(
         cache.clone(),
     )
     .await;
-    let send_datagram_task = tokio::task::spawn(send_datagram_task(connection.clone(), receiver));
+    let send_datagram_task = tokio::task::spawn(send_datagram_task(
+        connection.clone(),
+        authenticity_key,
+        receiver,
+    ));
     let read_datagram_task = tokio::task::spawn(read_datagram_task(
         endpoint,
         remote_address,
         remote_pubkey,
+        authenticity_key,
         connection.clone(),
         sender,
         stats.clone(),
@@ -418,6 +455,7 @@ async fn read_datagram_task(
     endpoint: Endpoint,
     remote_address: SocketAddr,
     remote_pubkey: Pubkey,
+    authenticity_key: Option<AuthenticityKey>,
     connection: Connection,
     sender: Sender<(Pubkey, SocketAddr, Bytes)>,
     stats: Arc<TurbineQuicStats>,
@@ -427,6 +465,11 @@ async fn read_datagram_task(
     loop {
         match connection.read_datagram().await {
             Ok(bytes) => {
+                let Some(bytes) = strip_authenticity_tag(bytes, authenticity_key.as_ref()) else {
+                    debug!("invalid authenticity tag: {remote_pubkey}, {remote_address}");
+                    add_metric!(stats.invalid_authenticity_tag);
+                    continue;
+                };
                 if let Err(err) = sender.send((remote_pubkey, remote_address, bytes)) {
                     close_quic_endpoint(&endpoint);
                     return Err(Error::from(err));
@@ -445,6 +488,7 @@ async fn read_datagram_task(
 
 async fn send_datagram_task(
     connection: Connection,
+    authenticity_key: Option<AuthenticityKey>,
     mut receiver: AsyncReceiver<Bytes>,
 ) -> Result<(), Error> {
     tokio::pin! {
@@ -456,7 +500,8 @@ async fn send_datagram_task(
             bytes = receiver.recv() => {
                 match bytes {
                     None => return Ok(()),
-                    Some(bytes) => connection.send_datagram(bytes)?,
+                    Some(bytes) => connection
+                        .send_datagram(add_authenticity_tag(bytes, authenticity_key.as_ref()))?,
                 }
             }
             err = &mut connection_closed => return Err(Error::from(err)),
@@ -510,6 +555,7 @@ async fn make_connection(
         endpoint,
         connection.remote_address(),
         get_remote_pubkey(&connection)?,
+        get_authenticity_key(&connection)?,
         connection,
         sender,
         receiver,
@@ -536,6 +582,49 @@ fn get_remote_pubkey(connection: &Connection) -> Result<Pubkey, Error> {
     }
 }
 
+// Returns the key for the authenticity tags if the connection negotiated them.
+fn get_authenticity_key(connection: &Connection) -> Result<Option<AuthenticityKey>, Error> {
+    let protocol = connection
+        .handshake_data()
+        .and_then(|data| data.downcast::<HandshakeData>().ok())
+        .and_then(|data| data.protocol);
+    if protocol.as_deref() != Some(ALPN_TURBINE_TAGGED_PROTOCOL_ID) {
+        return Ok(None);
+    }
+    let mut key = AuthenticityKey::default();
+    connection
+        .export_keying_material(&mut key, AUTHENTICITY_KEY_LABEL, /*context:*/ &[])
+        .map_err(|_| Error::ExportKeyingMaterialError)?;
+    Ok(Some(key))
+}
+
+fn authenticity_tag(key: &AuthenticityKey, bytes: &[u8]) -> [u8; AUTHENTICITY_TAG_SIZE] {
+    let hash = blake3::keyed_hash(key, bytes);
+    let mut tag = [0u8; AUTHENTICITY_TAG_SIZE];
+    tag.copy_from_slice(&hash.as_bytes()[..AUTHENTICITY_TAG_SIZE]);
+    tag
+}
+
+fn add_authenticity_tag(bytes: Bytes, key: Option<&AuthenticityKey>) -> Bytes {
+    let Some(key) = key else {
+        return bytes;
+    };
+    let mut buffer = BytesMut::with_capacity(bytes.len() + AUTHENTICITY_TAG_SIZE);
+    buffer.extend_from_slice(&bytes);
+    buffer.extend_from_slice(&authenticity_tag(key, &bytes));
+    buffer.freeze()
+}
+
+// Returns None if the tag is missing or invalid.
+fn strip_authenticity_tag(mut bytes: Bytes, key: Option<&AuthenticityKey>) -> Option<Bytes> {
+    let Some(key) = key else {
+        return Some(bytes);
+    };
+    let offset = bytes.len().checked_sub(AUTHENTICITY_TAG_SIZE)?;
+    let tag = bytes.split_off(offset);
+    bool::from(authenticity_tag(key, &bytes)[..].ct_eq(&tag[..])).then_some(bytes)
+}
+
 async fn cache_connection(
     remote_pubkey: Pubkey,
     connection: Connection,
@@ -646,6 +735,7 @@ struct TurbineQuicStats {
     connection_error_transport_error: AtomicU64,
     connection_error_version_mismatch: AtomicU64,
     connection_error_connection_limit_exceeded: AtomicU64,
+    invalid_authenticity_tag: AtomicU64,
     invalid_identity: AtomicU64,
     router_try_send_error_full: AtomicU64,
     send_datagram_error_connection_lost: AtomicU64,
@@ -697,6 +787,7 @@ fn record_error(err: &Error, stats: &TurbineQuicStats) {
         Error::ConnectionError(ConnectionError::LocallyClosed) => {
             add_metric!(stats.connection_error_locally_closed)
         }
+        Error::ExportKeyingMaterialError => (),
         Error::InvalidIdentity(_) => add_metric!(stats.invalid_identity),
         Error::IoError(_) => (),
         Error::SendDatagramError(SendDatagramError::UnsupportedByPeer) => {
@@ -785,6 +876,11 @@ fn report_metrics(name: &'static str, stats: &TurbineQuicStats) {
             reset_metric!(stats.connection_error_connection_limit_exceeded),
             i64
         ),
+        (
+            "invalid_authenticity_tag",
+            reset_metric!(stats.invalid_authenticity_tag),
+            i64
+        ),
         (
             "invalid_identity",
             reset_metric!(stats.invalid_identity),
@@ -827,10 +923,46 @@ mod tests {
             net::{IpAddr, Ipv4Addr},
             time::Duration,
         },
+        test_case::test_case,
     };
 
     #[test]
-    fn test_quic_endpoint() {
+    fn test_authenticity_tag() {
+        let key: AuthenticityKey = rand::random();
+        let bytes = Bytes::from_static(b"shred");
+        assert_eq!(add_authenticity_tag(bytes.clone(), None), bytes);
+        assert_eq!(
+            strip_authenticity_tag(bytes.clone(), None),
+            Some(bytes.clone())
+        );
+        let tagged = add_authenticity_tag(bytes.clone(), Some(&key));
+        assert_eq!(tagged.len(), bytes.len() + AUTHENTICITY_TAG_SIZE);
+        assert_eq!(&tagged[..bytes.len()], &bytes[..]);
+        assert_eq!(
+            strip_authenticity_tag(tagged.clone(), Some(&key)),
+            Some(bytes.clone())
+        );
+        // Tampered payload, tampered tag, another key or a missing tag.
+        let mut tampered = tagged.to_vec();
+        tampered[0] ^= 1;
+        assert_eq!(strip_authenticity_tag(tampered.into(), Some(&key)), None);
+        let mut tampered = tagged.to_vec();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(strip_authenticity_tag(tampered.into(), Some(&key)), None);
+        let other_key: AuthenticityKey = rand::random();
+        assert_eq!(strip_authenticity_tag(tagged, Some(&other_key)), None);
+        assert_eq!(strip_authenticity_tag(bytes, Some(&key)), None);
+        assert_eq!(
+            strip_authenticity_tag(Bytes::from_static(b"tag"), Some(&key)),
+            None
+        );
+    }
+
+    // Endpoints with and without authenticity tags talk to each other.
+    #[test_case([false, false, false])]
+    #[test_case([true, true, true])]
+    #[test_case([true, false, true])]
+    fn test_quic_endpoint(authenticity_tags: [bool; 3]) {
         const NUM_ENDPOINTS: usize = 3;
         const RECV_TIMEOUT: Duration = Duration::from_secs(60);
         let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -861,14 +993,15 @@ mod tests {
             BankForks::new_rw_arc(bank)
         };
         let (endpoints, senders, tasks): (Vec<_>, Vec<_>, Vec<_>) =
-            multiunzip(keypairs.iter().zip(sockets).zip(senders).map(
-                |((keypair, socket), sender)| {
+            multiunzip(izip!(&keypairs, sockets, senders, authenticity_tags).map(
+                |(keypair, socket, sender, authenticity_tags)| {
                     new_quic_endpoint(
                         runtime.handle(),
                         keypair,
                         socket,
                         sender,
                         bank_forks.clone(),
+                        authenticity_tags,
                     )
                     .unwrap()
                 },
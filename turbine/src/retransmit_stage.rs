@@ -4,6 +4,7 @@ use {
     crate::{
         addr_cache::AddrCache,
         cluster_nodes::{self, ClusterNodes, ClusterNodesCache, Error, MAX_NUM_TURBINE_HOPS},
+        encrypted_transport::{EncryptedTransportPeers, EncryptedTransportSend},
         xdp::XdpSender,
     },
     agave_votor::event::VotorEvent,
@@ -104,6 +105,8 @@ struct RetransmitStats {
     addr_cache_hit: AtomicUsize,
     addr_cache_miss: AtomicUsize,
     num_nodes: AtomicUsize,
+    num_encrypted_nodes: AtomicUsize,
+    num_encrypted_fallbacks: AtomicUsize,
     num_addrs_failed: AtomicUsize,
     num_shreds_dropped_xdp_full: AtomicUsize,
     num_loopback_errs: AtomicUsize,
@@ -145,6 +148,16 @@ impl RetransmitStats {
             ("total_batches", self.total_batches, i64),
             ("num_small_batches", self.num_small_batches, i64),
             ("num_nodes", *self.num_nodes.get_mut(), i64),
+            (
+                "num_encrypted_nodes",
+                *self.num_encrypted_nodes.get_mut(),
+                i64
+            ),
+            (
+                "num_encrypted_fallbacks",
+                *self.num_encrypted_fallbacks.get_mut(),
+                i64
+            ),
             ("num_addrs_failed", *self.num_addrs_failed.get_mut(), i64),
             (
                 "num_shreds_dropped_xdp_full",
@@ -298,6 +311,7 @@ fn retransmit(
     stats: &mut RetransmitStats,
     cluster_nodes_cache: &ClusterNodesCache<RetransmitStage>,
     addr_cache: &mut AddrCache,
+    encrypted_transport_peers: &mut EncryptedTransportPeers,
     shred_deduper: &mut ShredDeduper,
     max_slots: &MaxSlots,
     rpc_subscriptions: Option<&RpcSubscriptions>,
@@ -352,6 +366,7 @@ fn retransmit(
     stats.epoch_fetch += epoch_fetch.as_us();
 
     let mut epoch_cache_update = Measure::start("retransmit_epoch_cache_update");
+    encrypted_transport_peers.maybe_refresh(cluster_info);
     shred_deduper.maybe_reset(
         &mut rand::thread_rng(),
         DEDUPER_FALSE_POSITIVE_RATE,
@@ -397,6 +412,7 @@ fn retransmit(
             shred_deduper,
             &cache,
             addr_cache,
+            encrypted_transport_peers,
             socket_addr_space,
             socket,
             quic_endpoint_sender,
@@ -459,6 +475,7 @@ fn retransmit_shred(
     shred_deduper: &ShredDeduper,
     cache: &HashMap<Slot, (/*leader:*/ Pubkey, Arc<ClusterNodes<RetransmitStage>>)>,
     addr_cache: &AddrCache,
+    encrypted_transport_peers: &EncryptedTransportPeers,
     socket_addr_space: &SocketAddrSpace,
     socket: RetransmitSocket<'_>,
     quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
@@ -491,34 +508,51 @@ fn retransmit_shred(
                 .filter_map(|&addr| quic_endpoint_sender.try_send((addr, shred.clone())).ok())
                 .count()
         }
-        Protocol::UDP => match socket {
-            RetransmitSocket::Xdp(sender) => {
-                let mut sent = num_addrs;
-                if num_addrs > 0 {
-                    if let Err(e) = sender.try_send(key.index() as usize, addrs.to_vec(), shred) {
-                        log::warn!("xdp channel full: {e:?}");
-                        stats
-                            .num_shreds_dropped_xdp_full
-                            .fetch_add(num_addrs, Ordering::Relaxed);
-                        sent = 0;
+        Protocol::UDP => {
+            let EncryptedTransportSend {
+                udp_addrs: addrs,
+                num_nodes: num_encrypted_nodes,
+                num_fallbacks,
+            } = encrypted_transport_peers.send(&shred.bytes, &addrs, quic_endpoint_sender);
+            stats
+                .num_encrypted_nodes
+                .fetch_add(num_encrypted_nodes, Ordering::Relaxed);
+            stats
+                .num_encrypted_fallbacks
+                .fetch_add(num_fallbacks, Ordering::Relaxed);
+            let num_addrs = addrs.len();
+            num_encrypted_nodes
+                + match socket {
+                    RetransmitSocket::Xdp(sender) => {
+                        let mut sent = num_addrs;
+                        if num_addrs > 0 {
+                            if let Err(e) =
+                                sender.try_send(key.index() as usize, addrs.to_vec(), shred)
+                            {
+                                log::warn!("xdp channel full: {e:?}");
+                                stats
+                                    .num_shreds_dropped_xdp_full
+                                    .fetch_add(num_addrs, Ordering::Relaxed);
+                                sent = 0;
+                            }
+                        }
+                        sent
                     }
-                }
-                sent
-            }
-            RetransmitSocket::Socket(_) | RetransmitSocket::Multihomed { .. } => {
-                let socket = socket.get_socket();
-                match multi_target_send(socket, shred, &addrs) {
-                    Ok(()) => num_addrs,
-                    Err(SendPktsError::IoError(ioerr, num_failed)) => {
-                        error!(
-                            "retransmit_to multi_target_send error: {ioerr:?}, \
-                             {num_failed}/{num_addrs} packets failed"
-                        );
-                        num_addrs - num_failed
+                    RetransmitSocket::Socket(_) | RetransmitSocket::Multihomed { .. } => {
+                        let socket = socket.get_socket();
+                        match multi_target_send(socket, shred, &addrs) {
+                            Ok(()) => num_addrs,
+                            Err(SendPktsError::IoError(ioerr, num_failed)) => {
+                                error!(
+                                    "retransmit_to multi_target_send error: {ioerr:?}, \
+                                     {num_failed}/{num_addrs} packets failed"
+                                );
+                                num_addrs - num_failed
+                            }
+                        }
                     }
                 }
-            }
-        },
+        }
     };
     retransmit_time.stop();
     stats
@@ -633,6 +667,7 @@ fn cache_retransmit_addrs(
 /// Service to retransmit messages received from other peers in turbine.
 pub struct RetransmitStage {
     retransmit_thread_handle: JoinHandle<()>,
+    cluster_nodes_cache: Arc<ClusterNodesCache<RetransmitStage>>,
 }
 
 impl RetransmitStage {
@@ -659,13 +694,14 @@ impl RetransmitStage {
         xdp_sender: Option<XdpSender>,
         votor_event_sender: Option<Sender<VotorEvent>>,
     ) -> Self {
-        let cluster_nodes_cache = ClusterNodesCache::<RetransmitStage>::new(
+        let cluster_nodes_cache = Arc::new(ClusterNodesCache::<RetransmitStage>::new(
             CLUSTER_NODES_CACHE_NUM_EPOCH_CAP,
             CLUSTER_NODES_CACHE_TTL,
-        );
+        ));
         let mut rng = rand::thread_rng();
         let mut stats = RetransmitStats::new(Instant::now());
         let mut addr_cache = AddrCache::with_capacity(/*capacity:*/ 4);
+        let mut encrypted_transport_peers = EncryptedTransportPeers::default();
         let mut shred_deduper = ShredDeduper::new(&mut rng, DEDUPER_NUM_BITS);
 
         let thread_pool = {
@@ -680,6 +716,7 @@ impl RetransmitStage {
         let retransmit_thread_handle = Builder::new()
             .name("solRetransmittr".to_string())
             .spawn({
+                let cluster_nodes_cache = cluster_nodes_cache.clone();
                 move || {
                     let mut shred_buf = Vec::with_capacity(RETRANSMIT_BATCH_SIZE);
                     while retransmit(
@@ -694,6 +731,7 @@ impl RetransmitStage {
                         &mut stats,
                         &cluster_nodes_cache,
                         &mut addr_cache,
+                        &mut encrypted_transport_peers,
                         &mut shred_deduper,
                         &max_slots,
                         rpc_subscriptions.as_deref(),
@@ -709,9 +747,15 @@ impl RetransmitStage {
 
         Self {
             retransmit_thread_handle,
+            cluster_nodes_cache,
         }
     }
 
+    /// The turbine trees the stage retransmits shreds along, cached by epoch
+    pub fn cluster_nodes_cache(&self) -> &Arc<ClusterNodesCache<RetransmitStage>> {
+        &self.cluster_nodes_cache
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.retransmit_thread_handle.join()
     }
@@ -758,6 +802,8 @@ impl RetransmitStats {
             addr_cache_hit: AtomicUsize::default(),
             addr_cache_miss: AtomicUsize::default(),
             num_nodes: AtomicUsize::default(),
+            num_encrypted_nodes: AtomicUsize::default(),
+            num_encrypted_fallbacks: AtomicUsize::default(),
             num_addrs_failed: AtomicUsize::default(),
             num_shreds_dropped_xdp_full: AtomicUsize::default(),
             num_loopback_errs: AtomicUsize::default(),
@@ -17,7 +17,7 @@ use {
             layout::{get_shred, resign_packet},
             wire::is_retransmitter_signed_variant,
         },
-        sigverify_shreds::{verify_shreds_gpu, LruCache, SlotPubkeys},
+        sigverify_shreds::{verify_shreds_cpu, verify_shreds_gpu, LruCache, SlotPubkeys},
     },
     solana_perf::{
         self,
@@ -31,6 +31,7 @@ use {
     solana_streamer::{evicting_sender::EvictingSender, streamer::ChannelSend},
     std::{
         num::NonZeroUsize,
+        str::FromStr,
         sync::{
             atomic::{AtomicUsize, Ordering},
             Arc, RwLock,
@@ -57,7 +58,56 @@ const CLUSTER_NODES_CACHE_NUM_EPOCH_CAP: usize = 2;
 const CLUSTER_NODES_CACHE_TTL: Duration = Duration::from_secs(30);
 
 /// Maximum number of packet batches to process in a single sigverify iteration.
-const SIGVERIFY_SHRED_BATCH_SIZE: usize = 1024;
+pub const DEFAULT_SHRED_SIGVERIFY_BATCH_SIZE: usize = 1024;
+/// Number of packet batches waiting for sigverify, beyond which the oldest are dropped. Also the
+/// ingress limit of the turbine and repair shred fetch channels.
+///
+/// The general case sees shred and repair ingress in the hundreds of packet batches per second.
+/// However, in the case of catch-up, we may see upwards of 8k packet batches per second, which would
+/// suggest a roughly 16k packet batch limit for ample headroom. We're setting it to 4x that amount
+/// to future proof for increases of CU limits (e.g., a future 100k CU limit).
+pub const DEFAULT_SHRED_SIGVERIFY_QUEUE_DEPTH: usize = 1024 * 64;
+
+/// Where shred signatures are verified
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShredSigverifyBackend {
+    /// On the GPU if the perf libs are loaded, on the CPU otherwise
+    #[default]
+    Gpu,
+    /// On the CPU, with the SIMD instructions it supports, even if the GPU is available
+    Cpu,
+}
+
+impl FromStr for ShredSigverifyBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gpu" => Ok(Self::Gpu),
+            "cpu" => Ok(Self::Cpu),
+            _ => Err(format!("invalid shred sigverify backend: {s}")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShredSigverifyConfig {
+    /// Maximum number of packet batches verified together
+    pub batch_size: NonZeroUsize,
+    /// Number of packet batches waiting for verification, beyond which the oldest are dropped
+    pub queue_depth: NonZeroUsize,
+    pub backend: ShredSigverifyBackend,
+}
+
+impl Default for ShredSigverifyConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: NonZeroUsize::new(DEFAULT_SHRED_SIGVERIFY_BATCH_SIZE).unwrap(),
+            queue_depth: NonZeroUsize::new(DEFAULT_SHRED_SIGVERIFY_QUEUE_DEPTH).unwrap(),
+            backend: ShredSigverifyBackend::default(),
+        }
+    }
+}
 
 #[allow(clippy::enum_variant_names)]
 enum ShredSigverifyError {
@@ -82,6 +132,7 @@ pub fn spawn_shred_sigverify(
     retransmit_sender: EvictingSender<Vec<shred::Payload>>,
     verified_sender: Sender<Vec<(shred::Payload, /*is_repaired:*/ bool)>>,
     num_sigverify_threads: NonZeroUsize,
+    config: ShredSigverifyConfig,
 ) -> JoinHandle<()> {
     let recycler_cache = RecyclerCache::warmed();
     let mut stats = ShredSigVerifyStats::new(Instant::now());
@@ -98,7 +149,7 @@ pub fn spawn_shred_sigverify(
     let run_shred_sigverify = move || {
         let mut rng = rand::thread_rng();
         let mut deduper = Deduper::<2, [u8]>::new(&mut rng, DEDUPER_NUM_BITS);
-        let mut shred_buffer = Vec::with_capacity(SIGVERIFY_SHRED_BATCH_SIZE);
+        let mut shred_buffer = Vec::with_capacity(config.batch_size.get());
         loop {
             if deduper.maybe_reset(&mut rng, DEDUPER_FALSE_POSITIVE_RATE, DEDUPER_RESET_CYCLE) {
                 stats.num_deduper_saturations += 1;
@@ -119,6 +170,7 @@ pub fn spawn_shred_sigverify(
                 &verified_sender,
                 &cluster_nodes_cache,
                 &cache,
+                &config,
                 &mut stats,
                 &mut shred_buffer,
             ) {
@@ -150,6 +202,7 @@ fn run_shred_sigverify<const K: usize>(
     verified_sender: &Sender<Vec<(shred::Payload, /*is_repaired:*/ bool)>>,
     cluster_nodes_cache: &ClusterNodesCache<RetransmitStage>,
     cache: &RwLock<LruCache>,
+    config: &ShredSigverifyConfig,
     stats: &mut ShredSigVerifyStats,
     shred_buffer: &mut Vec<PacketBatch>,
 ) -> Result<(), ShredSigverifyError> {
@@ -159,7 +212,7 @@ fn run_shred_sigverify<const K: usize>(
     shred_buffer.push(packets);
     for packets in shred_fetch_receiver
         .try_iter()
-        .take(SIGVERIFY_SHRED_BATCH_SIZE - 1)
+        .take(config.batch_size.get() - 1)
     {
         stats.num_packets += packets.len();
         shred_buffer.push(packets);
@@ -200,8 +253,10 @@ fn run_shred_sigverify<const K: usize>(
         let bank_forks = bank_forks.read().unwrap();
         (bank_forks.working_bank(), bank_forks.root_bank())
     };
+    let verify_start = Instant::now();
     verify_packets(
         thread_pool,
+        config.backend,
         &keypair.pubkey(),
         &working_bank,
         leader_schedule_cache,
@@ -209,6 +264,7 @@ fn run_shred_sigverify<const K: usize>(
         shred_buffer,
         cache,
     );
+    stats.record_verify_time(verify_start.elapsed(), shred_buffer);
     stats.num_discards_post += count_discards(shred_buffer);
     // Verify retransmitter's signature, and resign shreds
     // Merkle root as the retransmitter node.
@@ -392,6 +448,7 @@ fn verify_retransmitter_signature(
 
 fn verify_packets(
     thread_pool: &ThreadPool,
+    backend: ShredSigverifyBackend,
     self_pubkey: &Pubkey,
     working_bank: &Bank,
     leader_schedule_cache: &LeaderScheduleCache,
@@ -404,7 +461,12 @@ fn verify_packets(
             .filter_map(|(slot, pubkey)| Some((slot, pubkey?)))
             .chain(std::iter::once((Slot::MAX, Pubkey::default())))
             .collect();
-    let out = verify_shreds_gpu(thread_pool, packets, &leader_slots, recycler_cache, cache);
+    let out = match backend {
+        ShredSigverifyBackend::Gpu => {
+            verify_shreds_gpu(thread_pool, packets, &leader_slots, recycler_cache, cache)
+        }
+        ShredSigverifyBackend::Cpu => verify_shreds_cpu(thread_pool, packets, &leader_slots, cache),
+    };
     solana_perf::sigverify::mark_disabled(packets, &out);
 }
 
@@ -477,6 +539,9 @@ struct ShredSigVerifyStats {
     num_unknown_turbine_parent: AtomicUsize,
     elapsed_micros: u64,
     resign_micros: u64,
+    // Time to verify the signatures of a batch, and that time per packet
+    verify_batch_micros_hist: histogram::Histogram,
+    verify_batch_packet_nanos_hist: histogram::Histogram,
 }
 
 impl ShredSigVerifyStats {
@@ -501,6 +566,22 @@ impl ShredSigVerifyStats {
             num_unknown_turbine_parent: AtomicUsize::default(),
             elapsed_micros: 0u64,
             resign_micros: 0u64,
+            verify_batch_micros_hist: histogram::Histogram::default(),
+            verify_batch_packet_nanos_hist: histogram::Histogram::default(),
+        }
+    }
+
+    fn record_verify_time(&mut self, elapsed: Duration, batches: &[PacketBatch]) {
+        let num_packets = batches.iter().map(PacketBatch::len).sum::<usize>();
+        // Recording fails only for values beyond the range of the histogram
+        let _ = self
+            .verify_batch_micros_hist
+            .increment(elapsed.as_micros() as u64);
+        if let Some(nanos_per_packet) = (elapsed.as_nanos() as u64).checked_div(num_packets as u64)
+        {
+            let _ = self
+                .verify_batch_packet_nanos_hist
+                .increment(nanos_per_packet);
         }
     }
 
@@ -552,6 +633,35 @@ impl ShredSigVerifyStats {
             ),
             ("elapsed_micros", self.elapsed_micros, i64),
             ("resign_micros", self.resign_micros, i64),
+            (
+                "verify_batch_micros_50pct",
+                self.verify_batch_micros_hist.percentile(50.0).unwrap_or(0),
+                i64
+            ),
+            (
+                "verify_batch_micros_90pct",
+                self.verify_batch_micros_hist.percentile(90.0).unwrap_or(0),
+                i64
+            ),
+            (
+                "verify_batch_micros_max",
+                self.verify_batch_micros_hist.maximum().unwrap_or(0),
+                i64
+            ),
+            (
+                "verify_batch_packet_nanos_50pct",
+                self.verify_batch_packet_nanos_hist
+                    .percentile(50.0)
+                    .unwrap_or(0),
+                i64
+            ),
+            (
+                "verify_batch_packet_nanos_90pct",
+                self.verify_batch_packet_nanos_hist
+                    .percentile(90.0)
+                    .unwrap_or(0),
+                i64
+            ),
         );
         *self = Self::new(Instant::now());
     }
@@ -575,11 +685,12 @@ mod tests {
         solana_signer::Signer,
         solana_streamer::socket::SocketAddrSpace,
         solana_time_utils::timestamp,
-        test_case::test_matrix,
+        test_case::{test_case, test_matrix},
     };
 
-    #[test]
-    fn test_sigverify_shreds_verify_batches() {
+    #[test_case(ShredSigverifyBackend::Gpu)]
+    #[test_case(ShredSigverifyBackend::Cpu)]
+    fn test_sigverify_shreds_verify_batches(backend: ShredSigverifyBackend) {
         let leader_keypair = Arc::new(Keypair::new());
         let wrong_keypair = Keypair::new();
         let leader_pubkey = leader_keypair.pubkey();
@@ -633,6 +744,7 @@ mod tests {
             .collect::<Vec<_>>();
         verify_packets(
             &thread_pool,
+            backend,
             &Pubkey::new_unique(), // self_pubkey
             &working_bank,
             &leader_schedule_cache,
@@ -0,0 +1,414 @@
+//! The admin RPC over a local HTTP endpoint
+//!
+//! Serves the same JSON-RPC methods as the IPC interface of [`admin_rpc_service`] to clients
+//! presenting the configured bearer token, so that tooling can manage the validator without
+//! linking its Rust client. `GET /openapi.json` describes the methods, and needs no token.
+
+use {
+    crate::admin_rpc_service::{self, AdminRpcRequestMetadata},
+    jsonrpc_http_server::{
+        hyper, DomainsValidation, RequestMiddleware, RequestMiddlewareAction, ServerBuilder,
+    },
+    log::*,
+    serde_json::{json, Map, Value},
+    std::{collections::BTreeSet, net::SocketAddr, thread::Builder},
+};
+
+pub const OPENAPI_PATH: &str = "/openapi.json";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdminRpcHttpConfig {
+    /// Address to listen on, which must be a loopback address
+    pub address: SocketAddr,
+    /// The bearer token requests must present in their `Authorization` header
+    pub auth_token: String,
+}
+
+/// The parameters of every admin RPC method, in order. A test checks that every method of the
+/// `AdminRpc` trait is listed here with as many parameters as the trait declares.
+const METHOD_PARAMS: &[(&str, &[&str])] = &[
+    ("addAuthorizedVoter", &["keypairFile"]),
+    ("addAuthorizedVoterFromBytes", &["keypair"]),
+    ("applyConfigTransaction", &["changes"]),
+    ("cancelRestart", &[]),
+    ("contactInfo", &[]),
+    ("diagnosticsBundle", &["logLines"]),
+    ("exit", &[]),
+    ("getSecondaryIndexKeySize", &["pubkey"]),
+    ("listPlugins", &[]),
+    ("loadPlugin", &["configFile"]),
+    (
+        "manageBlockProduction",
+        &[
+            "blockProductionMethod",
+            "transactionStruct",
+            "numWorkers",
+            "schedulerPacing",
+        ],
+    ),
+    ("pid", &[]),
+    ("pohTiming", &[]),
+    ("profileCpu", &["threadNames", "numSlots", "frequency"]),
+    ("reloadPlugin", &["name", "configFile"]),
+    ("removeAllAuthorizedVoters", &[]),
+    ("repairShredFromPeer", &["pubkey", "slot", "shredIndex"]),
+    ("repairWhitelist", &[]),
+    ("restart", &["config"]),
+    ("restartProgress", &[]),
+    ("rpcAddress", &[]),
+    ("rpcThreadPools", &[]),
+    ("rpcUsage", &[]),
+    ("selectActiveInterface", &["interface"]),
+    ("setIdentity", &["keypairFile", "requireTower"]),
+    ("setIdentityFromBytes", &["identityKeypair", "requireTower"]),
+    ("setLogFilter", &["filter"]),
+    ("setPublicTpuAddress", &["publicTpuAddr"]),
+    ("setPublicTpuForwardsAddress", &["publicTpuForwardsAddr"]),
+    ("setPublicTvuAddress", &["publicTvuAddr"]),
+    ("setRepairWhitelist", &["whitelist"]),
+    ("setRpcThreadPools", &["rpcThreads", "rpcBlockingThreads"]),
+    ("setStakedNodesOverrides", &["path"]),
+    (
+        "setTransactionHistory",
+        &[
+            "enableRpcTransactionHistory",
+            "enableExtendedTxMetadataStorage",
+        ],
+    ),
+    ("startProgress", &[]),
+    ("startTime", &[]),
+    ("transactionHistory", &[]),
+    ("unloadPlugin", &["name"]),
+    ("wenRestartStatus", &[]),
+    ("writeLockContention", &["limit"]),
+];
+
+// Start the admin RPC HTTP endpoint
+pub fn run(config: AdminRpcHttpConfig, metadata: AdminRpcRequestMetadata) {
+    let io = admin_rpc_service::io_handler();
+    let methods: BTreeSet<_> = io.iter().map(|(name, _)| name.clone()).collect();
+    let middleware = AdminRpcHttpMiddleware {
+        auth_token: config.auth_token,
+        openapi: openapi_spec(methods.iter().map(String::as_str)).to_string(),
+    };
+    let address = config.address;
+
+    Builder::new()
+        .name("solAdminRpcHttp".to_string())
+        .spawn(move || {
+            let validator_exit = metadata.validator_exit.clone();
+            let server = ServerBuilder::with_meta_extractor(
+                io,
+                move |_req: &hyper::Request<hyper::Body>| metadata.clone(),
+            )
+            .threads(1)
+            .cors(DomainsValidation::Disabled)
+            .request_middleware(middleware)
+            .start_http(&address);
+
+            match server {
+                Err(err) => {
+                    warn!("Unable to start the admin rpc http service at {address}: {err:?}");
+                }
+                Ok(server) => {
+                    info!("started admin rpc http service at {address}");
+                    let close_handle = server.close_handle();
+                    validator_exit
+                        .write()
+                        .unwrap()
+                        .register_exit(Box::new(move || {
+                            close_handle.close();
+                        }));
+
+                    server.wait();
+                }
+            }
+        })
+        .unwrap();
+}
+
+struct AdminRpcHttpMiddleware {
+    auth_token: String,
+    openapi: String,
+}
+
+impl AdminRpcHttpMiddleware {
+    fn is_authorized(&self, headers: &hyper::HeaderMap) -> bool {
+        let Some(token) = headers
+            .get(hyper::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        // Compare every byte, so the time taken does not reveal the length of a correct prefix
+        token.len() == self.auth_token.len()
+            && token
+                .bytes()
+                .zip(self.auth_token.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl RequestMiddleware for AdminRpcHttpMiddleware {
+    fn on_request(&self, request: hyper::Request<hyper::Body>) -> RequestMiddlewareAction {
+        if request.method() == hyper::Method::GET && request.uri().path() == OPENAPI_PATH {
+            return hyper::Response::builder()
+                .status(hyper::StatusCode::OK)
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .body(hyper::Body::from(self.openapi.clone()))
+                .unwrap()
+                .into();
+        }
+        if !self.is_authorized(request.headers()) {
+            return hyper::Response::builder()
+                .status(hyper::StatusCode::UNAUTHORIZED)
+                .header(hyper::header::WWW_AUTHENTICATE, "Bearer")
+                .body(hyper::Body::empty())
+                .unwrap()
+                .into();
+        }
+        request.into()
+    }
+}
+
+/// Describes the JSON-RPC `methods` as an OpenAPI document with a single `POST /` operation
+/// whose request is one of the method calls
+fn openapi_spec<'a>(methods: impl IntoIterator<Item = &'a str>) -> Value {
+    let mut schemas = Map::new();
+    let mut requests = Vec::new();
+    for method in methods {
+        let params = METHOD_PARAMS
+            .iter()
+            .find(|(name, _)| *name == method)
+            .map(|(_, params)| *params)
+            .unwrap_or_default();
+        let schema_name = format!("{method}Request");
+        requests.push(json!({ "$ref": format!("#/components/schemas/{schema_name}") }));
+        schemas.insert(
+            schema_name,
+            json!({
+                "type": "object",
+                "required": ["jsonrpc", "id", "method"],
+                "properties": {
+                    "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                    "id": { "oneOf": [{ "type": "integer" }, { "type": "string" }] },
+                    "method": { "type": "string", "enum": [method] },
+                    "params": {
+                        "type": "array",
+                        "maxItems": params.len(),
+                        "description": format!("[{}]", params.join(", ")),
+                    },
+                },
+            }),
+        );
+    }
+    schemas.insert(
+        "Response".to_string(),
+        json!({
+            "type": "object",
+            "required": ["jsonrpc", "id"],
+            "properties": {
+                "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                "id": { "oneOf": [{ "type": "integer" }, { "type": "string" }] },
+                "result": {},
+                "error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": { "type": "integer" },
+                        "message": { "type": "string" },
+                        "data": {},
+                    },
+                },
+            },
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Agave validator admin RPC",
+            "version": solana_version::version!(),
+        },
+        "paths": {
+            "/": {
+                "post": {
+                    "summary": "Call an admin RPC method",
+                    "security": [{ "bearerAuth": [] }],
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": { "schema": { "oneOf": requests } },
+                        },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "The JSON-RPC response",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Response" },
+                                },
+                            },
+                        },
+                        "401": { "description": "Missing or incorrect bearer token" },
+                    },
+                },
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+            },
+            "schemas": schemas,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::BTreeMap};
+
+    fn middleware() -> AdminRpcHttpMiddleware {
+        AdminRpcHttpMiddleware {
+            auth_token: "secret".to_string(),
+            openapi: "{}".to_string(),
+        }
+    }
+
+    fn request(
+        method: hyper::Method,
+        path: &str,
+        token: Option<&str>,
+    ) -> hyper::Request<hyper::Body> {
+        let mut builder = hyper::Request::builder().method(method).uri(path);
+        if let Some(token) = token {
+            builder = builder.header(hyper::header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        builder.body(hyper::Body::empty()).unwrap()
+    }
+
+    fn response_status(action: RequestMiddlewareAction) -> Option<hyper::StatusCode> {
+        match action {
+            RequestMiddlewareAction::Proceed { .. } => None,
+            RequestMiddlewareAction::Respond { response, .. } => {
+                let runtime = tokio::runtime::Runtime::new().unwrap();
+                Some(runtime.block_on(response).unwrap().status())
+            }
+        }
+    }
+
+    #[test]
+    fn test_admin_rpc_http_middleware() {
+        let middleware = middleware();
+        assert_eq!(
+            response_status(middleware.on_request(request(hyper::Method::POST, "/", None))),
+            Some(hyper::StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            response_status(middleware.on_request(request(
+                hyper::Method::POST,
+                "/",
+                Some("secreT")
+            ))),
+            Some(hyper::StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            response_status(middleware.on_request(request(
+                hyper::Method::POST,
+                "/",
+                Some("secret")
+            ))),
+            None
+        );
+        assert_eq!(
+            response_status(middleware.on_request(request(hyper::Method::GET, OPENAPI_PATH, None))),
+            Some(hyper::StatusCode::OK)
+        );
+    }
+
+    #[test]
+    fn test_openapi_spec_describes_every_method() {
+        let io = admin_rpc_service::io_handler();
+        let methods: BTreeSet<_> = io.iter().map(|(name, _)| name.as_str()).collect();
+        let documented: BTreeSet<_> = METHOD_PARAMS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(methods, documented);
+
+        let spec = openapi_spec(methods.iter().copied());
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        assert_eq!(
+            spec["paths"]["/"]["post"]["requestBody"]["content"]["application/json"]["schema"]
+                ["oneOf"]
+                .as_array()
+                .unwrap()
+                .len(),
+            methods.len()
+        );
+        assert_eq!(
+            schemas["setIdentityRequest"]["properties"]["params"]["description"],
+            "[keypairFile, requireTower]"
+        );
+        assert_eq!(
+            schemas["setIdentityRequest"]["properties"]["method"]["enum"][0],
+            "setIdentity"
+        );
+    }
+
+    /// Returns the number of parameters of each method of the `AdminRpc` trait, read from its
+    /// source, without `&self` and the metadata
+    fn admin_rpc_trait_params() -> BTreeMap<&'static str, usize> {
+        let source = include_str!("admin_rpc_service.rs");
+        let start = source.find("pub trait AdminRpc {").unwrap();
+        let end = start + source[start..].find("\n}\n").unwrap();
+        let mut methods = BTreeMap::new();
+        let mut rest = &source[start..end];
+        while let Some(offset) = rest.find("name = \"") {
+            rest = &rest[offset + "name = \"".len()..];
+            let name = &rest[..rest.find('"').unwrap()];
+            rest = &rest[rest.find("fn ").unwrap()..];
+            rest = &rest[rest.find('(').unwrap() + 1..];
+            // Split the arguments on the commas that are not inside generic arguments
+            let mut depth = 0;
+            let mut args = vec![];
+            let mut arg_start = 0;
+            for (index, c) in rest.char_indices() {
+                match c {
+                    '(' | '<' | '[' => depth += 1,
+                    ')' | '>' | ']' if depth > 0 => depth -= 1,
+                    ',' | ')' if depth == 0 => {
+                        args.push(rest[arg_start..index].trim());
+                        arg_start = index + 1;
+                        if c == ')' {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let num_params = args
+                .iter()
+                .filter(|arg| !arg.is_empty() && **arg != "&self" && !arg.starts_with("meta:"))
+                .count();
+            methods.insert(name, num_params);
+        }
+        methods
+    }
+
+    #[test]
+    fn test_method_params_match_admin_rpc_trait() {
+        let trait_params = admin_rpc_trait_params();
+        let io = admin_rpc_service::io_handler();
+        assert_eq!(
+            trait_params.keys().copied().collect::<BTreeSet<_>>(),
+            io.iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<BTreeSet<_>>()
+        );
+        let documented_params: BTreeMap<_, _> = METHOD_PARAMS
+            .iter()
+            .map(|(name, params)| (*name, params.len()))
+            .collect();
+        assert_eq!(documented_params, trait_params);
+    }
+}
@@ -1,4 +1,13 @@
 use {
+    crate::{
+        cpu_profiler::{
+            self, CpuProfile, ProfileGuard, DEFAULT_PROFILE_FREQUENCY, MAX_PROFILE_DURATION,
+            MAX_PROFILE_FREQUENCY,
+        },
+        diagnostics_bundle::{DiagnosticsBundler, DEFAULT_LOG_LINES},
+        intent_log::{IntentLog, IntentOperation},
+        restart_window::{RestartProgress, RestartStatus, RestartWindow, RestartWindowConfig},
+    },
     crossbeam_channel::Sender,
     jsonrpc_core::{BoxFuture, ErrorCode, MetaIoHandler, Metadata, Result},
     jsonrpc_core_client::{transports::ipc, RpcError},
@@ -9,11 +18,15 @@ use {
     log::*,
     serde::{de::Deserializer, Deserialize, Serialize},
     solana_accounts_db::accounts_index::AccountIndex,
+    solana_clock::{Slot, DEFAULT_MS_PER_SLOT},
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
         banking_stage::{
-            transaction_scheduler::scheduler_controller::SchedulerConfig, BankingControlMsg,
-            BankingStage,
+            transaction_scheduler::{
+                scheduler_controller::SchedulerConfig,
+                write_lock_contention::WriteLockContentionSummary,
+            },
+            BankingControlMsg, BankingStage,
         },
         consensus::{tower_storage::TowerStorage, Tower},
         repair::repair_service,
@@ -24,11 +37,21 @@ use {
     solana_geyser_plugin_manager::GeyserPluginManagerRequest,
     solana_gossip::contact_info::{ContactInfo, Protocol, SOCKET_ADDR_UNSPECIFIED},
     solana_keypair::{read_keypair_file, Keypair},
+    solana_poh::poh_timing_report::{PohTimingReport, PohTimingSample},
     solana_pubkey::Pubkey,
-    solana_rpc::rpc::verify_pubkey,
+    solana_rpc::{
+        rpc::verify_pubkey,
+        rpc_service::{RpcThreadPoolResizer, RpcThreadPoolSizes},
+        rpc_usage::RpcUsageReport,
+        transaction_status_service::{TransactionHistoryStatus, TransactionHistorySwitch},
+    },
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
     solana_signer::Signer,
     solana_validator_exit::Exit,
+    solana_wen_restart::{
+        cluster_halt_detector::ClusterHalt,
+        wen_restart_status::{read_wen_restart_status, RestartFork, WenRestartStatus},
+    },
     std::{
         collections::{HashMap, HashSet},
         env, error,
@@ -38,7 +61,7 @@ use {
         path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, Ordering},
-            Arc, RwLock,
+            Arc, Mutex, RwLock,
         },
         thread::{self, Builder},
         time::{Duration, SystemTime},
@@ -58,6 +81,15 @@ pub struct AdminRpcRequestMetadata {
     pub staked_nodes_overrides: Arc<RwLock<HashMap<Pubkey, u64>>>,
     pub post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
     pub rpc_to_plugin_manager_sender: Option<Sender<GeyserPluginManagerRequest>>,
+    pub restart_window: Arc<RestartWindow>,
+    /// Records identity and authorized voter changes, so they can be checked after a crash
+    pub intent_log: Option<Arc<IntentLog>>,
+    pub diagnostics: Option<Arc<DiagnosticsBundler>>,
+    /// Progress file of the wen restart, if the validator was started with --wen-restart
+    pub wen_restart_path: Option<PathBuf>,
+    /// Held while the runtime config is changed, so that a concurrent change can not land in the
+    /// middle of a config transaction and be overwritten by its rollback
+    pub config_lock: Arc<Mutex<()>>,
 }
 
 impl Metadata for AdminRpcRequestMetadata {}
@@ -146,6 +178,381 @@ impl Display for AdminRpcContactInfo {
 impl solana_cli_output::VerboseDisplay for AdminRpcContactInfo {}
 impl solana_cli_output::QuietDisplay for AdminRpcContactInfo {}
 
+/// Number of accounts returned by `writeLockContention` if no limit is given
+pub const DEFAULT_WRITE_LOCK_CONTENTION_LIMIT: usize = 20;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcWriteLockContention {
+    /// Leader slots covered by the report, oldest first
+    pub slots: Vec<u64>,
+    pub accounts: Vec<AdminRpcAccountContention>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcAccountContention {
+    pub pubkey: String,
+    pub conflicting_transactions: u64,
+    pub lock_wait_us: u64,
+}
+
+impl From<WriteLockContentionSummary> for AdminRpcWriteLockContention {
+    fn from(summary: WriteLockContentionSummary) -> Self {
+        Self {
+            slots: summary.slots,
+            accounts: summary
+                .accounts
+                .into_iter()
+                .map(|(pubkey, contention)| AdminRpcAccountContention {
+                    pubkey: pubkey.to_string(),
+                    conflicting_transactions: contention.conflicting_transactions,
+                    lock_wait_us: contention.lock_wait_us,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for AdminRpcWriteLockContention {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.slots.first(), self.slots.last()) {
+            (Some(first), Some(last)) => writeln!(
+                f,
+                "Write lock contention over {} leader slots ({first} to {last})",
+                self.slots.len()
+            )?,
+            _ => return writeln!(f, "No leader slots recorded yet"),
+        }
+        writeln!(
+            f,
+            "{:<44}  {:>12}  {:>14}",
+            "Account", "Conflicting", "Lock Wait (ms)"
+        )?;
+        for account in &self.accounts {
+            writeln!(
+                f,
+                "{:<44}  {:>12}  {:>14.3}",
+                account.pubkey,
+                account.conflicting_transactions,
+                account.lock_wait_us as f64 / 1_000.0
+            )?;
+        }
+        Ok(())
+    }
+}
+impl solana_cli_output::VerboseDisplay for AdminRpcWriteLockContention {}
+impl solana_cli_output::QuietDisplay for AdminRpcWriteLockContention {}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcPohTiming {
+    /// Duration of a tick PoH paces itself to
+    pub target_tick_duration_us: u64,
+    pub ticks_per_slot: u64,
+    pub alert_max_slot_drift_us: Option<u64>,
+    pub alert_min_hash_rate: Option<u64>,
+    /// Number of reporting periods that exceeded an alert threshold since the node started
+    pub alerts: u64,
+    /// Timing of the recent reporting periods, oldest first
+    pub samples: Vec<AdminRpcPohTimingSample>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcPohTimingSample {
+    pub timestamp_ms: u64,
+    pub ticks: u64,
+    pub tick_duration_us: u64,
+    pub hashes_per_second: u64,
+    pub max_hashes_per_second: u64,
+    pub slot_drift_us: i64,
+}
+
+impl From<&PohTimingReport> for AdminRpcPohTiming {
+    fn from(report: &PohTimingReport) -> Self {
+        let alert_thresholds = report.alert_thresholds();
+        Self {
+            target_tick_duration_us: report.target_tick_duration().as_micros() as u64,
+            ticks_per_slot: report.ticks_per_slot(),
+            alert_max_slot_drift_us: alert_thresholds
+                .max_slot_drift
+                .map(|max_slot_drift| max_slot_drift.as_micros() as u64),
+            alert_min_hash_rate: alert_thresholds.min_hash_rate,
+            alerts: report.num_alerts(),
+            samples: report
+                .samples()
+                .into_iter()
+                .map(AdminRpcPohTimingSample::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<PohTimingSample> for AdminRpcPohTimingSample {
+    fn from(sample: PohTimingSample) -> Self {
+        Self {
+            timestamp_ms: sample.timestamp_ms,
+            ticks: sample.ticks,
+            tick_duration_us: sample.tick_duration_us,
+            hashes_per_second: sample.hashes_per_second,
+            max_hashes_per_second: sample.max_hashes_per_second,
+            slot_drift_us: sample.slot_drift_us,
+        }
+    }
+}
+
+impl Display for AdminRpcPohTiming {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Target tick duration: {}us, {} ticks per slot",
+            self.target_tick_duration_us, self.ticks_per_slot
+        )?;
+        let max_slot_drift = self
+            .alert_max_slot_drift_us
+            .map(|max_slot_drift_us| format!("{:.3}ms", max_slot_drift_us as f64 / 1_000.0))
+            .unwrap_or_else(|| "none".to_string());
+        let min_hash_rate = self
+            .alert_min_hash_rate
+            .map(|min_hash_rate| min_hash_rate.to_string())
+            .unwrap_or_else(|| "none".to_string());
+        writeln!(
+            f,
+            "Alert thresholds: slot drift {max_slot_drift}, hash rate {min_hash_rate}"
+        )?;
+        writeln!(f, "Alerts: {}", self.alerts)?;
+        let Some(last_sample) = self.samples.last() else {
+            return writeln!(f, "No PoH timing recorded yet");
+        };
+        writeln!(
+            f,
+            "{:>16}  {:>6}  {:>14}  {:>12}  {:>16}  {:>15}",
+            "Timestamp (ms)",
+            "Ticks",
+            "Tick Time (us)",
+            "Hashes/s",
+            "Max Hashes/s",
+            "Slot Drift (ms)"
+        )?;
+        for sample in &self.samples {
+            writeln!(
+                f,
+                "{:>16}  {:>6}  {:>14}  {:>12}  {:>16}  {:>15.3}",
+                sample.timestamp_ms,
+                sample.ticks,
+                sample.tick_duration_us,
+                sample.hashes_per_second,
+                sample.max_hashes_per_second,
+                sample.slot_drift_us as f64 / 1_000.0
+            )?;
+        }
+        let min_max_hashes_per_second = self
+            .samples
+            .iter()
+            .map(|sample| sample.max_hashes_per_second)
+            .min()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "Latest slot drift: {:.3}ms, lowest sustained hash rate: {min_max_hashes_per_second}",
+            last_sample.slot_drift_us as f64 / 1_000.0
+        )
+    }
+}
+impl solana_cli_output::VerboseDisplay for AdminRpcPohTiming {}
+impl solana_cli_output::QuietDisplay for AdminRpcPohTiming {}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcWenRestartStatus {
+    /// Set while the root of the node has not advanced for --cluster-halt-detection-secs
+    pub cluster_halt: Option<AdminRpcClusterHalt>,
+    /// Progress of the wen restart, if the validator was started with --wen-restart
+    pub wen_restart: Option<AdminRpcWenRestartProgress>,
+    /// What the operator should do next, if anything
+    pub next_step: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcClusterHalt {
+    pub root_slot: Slot,
+    pub highest_slot: Slot,
+    /// When the root last advanced, in milliseconds since the UNIX epoch
+    pub root_timestamp_ms: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcWenRestartProgress {
+    pub step: String,
+    pub my_last_voted_slot: Option<Slot>,
+    pub last_voted_fork_slots_received: usize,
+    pub my_heaviest_fork: Option<AdminRpcRestartFork>,
+    pub coordinator_heaviest_fork: Option<AdminRpcRestartFork>,
+    pub heaviest_fork_received: usize,
+    /// Number of the received heaviest forks with the slot and bank hash of the coordinator
+    pub heaviest_fork_agreed: usize,
+    pub conflicts: usize,
+    pub snapshot_path: Option<String>,
+    /// Arguments to restart the validator with, once the restart is done
+    pub restart_arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRpcRestartFork {
+    pub slot: Slot,
+    pub bankhash: String,
+}
+
+impl AdminRpcWenRestartStatus {
+    fn new(cluster_halt: Option<ClusterHalt>, wen_restart: Option<WenRestartStatus>) -> Self {
+        let next_step = match (&wen_restart, &cluster_halt) {
+            (Some(wen_restart), _) => Some(wen_restart.next_step()),
+            (None, Some(cluster_halt)) => Some(cluster_halt.next_step()),
+            (None, None) => None,
+        };
+        Self {
+            cluster_halt: cluster_halt.map(|cluster_halt| AdminRpcClusterHalt {
+                root_slot: cluster_halt.root,
+                highest_slot: cluster_halt.highest_slot,
+                root_timestamp_ms: cluster_halt.root_timestamp_ms,
+            }),
+            wen_restart: wen_restart.map(|wen_restart| AdminRpcWenRestartProgress {
+                step: wen_restart.step.to_string(),
+                my_last_voted_slot: wen_restart.my_last_voted_slot,
+                last_voted_fork_slots_received: wen_restart.last_voted_fork_slots_received,
+                my_heaviest_fork: wen_restart.my_heaviest_fork.clone().map(Into::into),
+                coordinator_heaviest_fork: wen_restart
+                    .coordinator_heaviest_fork
+                    .clone()
+                    .map(Into::into),
+                heaviest_fork_received: wen_restart.heaviest_fork_received,
+                heaviest_fork_agreed: wen_restart.heaviest_fork_agreed,
+                conflicts: wen_restart.conflicts,
+                snapshot_path: wen_restart
+                    .snapshot
+                    .as_ref()
+                    .map(|snapshot| snapshot.path.clone()),
+                restart_arguments: wen_restart.restart_arguments(),
+            }),
+            next_step,
+        }
+    }
+}
+
+impl From<RestartFork> for AdminRpcRestartFork {
+    fn from(fork: RestartFork) -> Self {
+        Self {
+            slot: fork.slot,
+            bankhash: fork.bankhash,
+        }
+    }
+}
+
+impl Display for AdminRpcWenRestartStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.cluster_halt {
+            Some(cluster_halt) => writeln!(
+                f,
+                "Cluster halted: root {}, highest slot {}",
+                cluster_halt.root_slot, cluster_halt.highest_slot
+            )?,
+            None => writeln!(f, "Cluster halted: no")?,
+        }
+        if let Some(wen_restart) = &self.wen_restart {
+            let format_fork = |fork: &Option<AdminRpcRestartFork>| {
+                fork.as_ref()
+                    .map(|fork| format!("{} {}", fork.slot, fork.bankhash))
+                    .unwrap_or_else(|| "none".to_string())
+            };
+            writeln!(f, "Wen restart step: {}", wen_restart.step)?;
+            if let Some(my_last_voted_slot) = wen_restart.my_last_voted_slot {
+                writeln!(f, "Last voted slot: {my_last_voted_slot}")?;
+            }
+            writeln!(
+                f,
+                "Last voted fork slots received from {} validators",
+                wen_restart.last_voted_fork_slots_received
+            )?;
+            writeln!(
+                f,
+                "Heaviest fork: {}",
+                format_fork(&wen_restart.my_heaviest_fork)
+            )?;
+            writeln!(
+                f,
+                "Coordinator heaviest fork: {}",
+                format_fork(&wen_restart.coordinator_heaviest_fork)
+            )?;
+            writeln!(
+                f,
+                "Heaviest forks received: {}, agreeing: {}",
+                wen_restart.heaviest_fork_received, wen_restart.heaviest_fork_agreed
+            )?;
+            if wen_restart.conflicts > 0 {
+                writeln!(
+                    f,
+                    "Conflicting messages from {} validators",
+                    wen_restart.conflicts
+                )?;
+            }
+            if let Some(snapshot_path) = &wen_restart.snapshot_path {
+                writeln!(f, "Snapshot: {snapshot_path}")?;
+            }
+        }
+        if let Some(next_step) = &self.next_step {
+            writeln!(f, "Next step: {next_step}")?;
+        }
+        Ok(())
+    }
+}
+
+impl solana_cli_output::VerboseDisplay for AdminRpcWenRestartStatus {}
+impl solana_cli_output::QuietDisplay for AdminRpcWenRestartStatus {}
+
+/// A runtime config change, applied together with the other changes of an
+/// `applyConfigTransaction` request or not at all
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(
+    tag = "field",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AdminRpcConfigChange {
+    /// Replaces the staked nodes overrides with those of the file at `path`
+    StakedNodesOverrides {
+        path: String,
+    },
+    RepairWhitelist {
+        whitelist: Vec<String>,
+    },
+    TransactionHistory {
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    },
+    /// Resizes the thread pools of the JSON RPC service, keeping the current size of the pools
+    /// left out
+    RpcThreadPools {
+        rpc_threads: Option<usize>,
+        rpc_blocking_threads: Option<usize>,
+    },
+}
+
+/// The value a config change sets its field to
+#[derive(Debug, Clone, PartialEq)]
+enum ConfigValue {
+    StakedNodesOverrides(HashMap<Pubkey, u64>),
+    RepairWhitelist(HashSet<Pubkey>),
+    TransactionHistory {
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    },
+    RpcThreadPools(RpcThreadPoolSizes),
+}
+
 impl Display for AdminRpcRepairWhitelist {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Repair whitelist: {:?}", &self.whitelist)
@@ -274,6 +681,16 @@ pub trait AdminRpc {
         public_tvu_addr: SocketAddr,
     ) -> Result<()>;
 
+    #[rpc(meta, name = "writeLockContention")]
+    fn write_lock_contention(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<AdminRpcWriteLockContention>;
+
+    #[rpc(meta, name = "pohTiming")]
+    fn poh_timing(&self, meta: Self::Metadata) -> Result<AdminRpcPohTiming>;
+
     #[rpc(meta, name = "manageBlockProduction")]
     fn manage_block_production(
         &self,
@@ -283,6 +700,73 @@ pub trait AdminRpc {
         num_workers: NonZeroUsize,
         scheduler_pacing: SchedulerPacing,
     ) -> Result<()>;
+
+    /// Exits the validator once it is not leader for `config.min_idle_slots` slots, so that its
+    /// supervisor can start it again
+    #[rpc(meta, name = "restart")]
+    fn restart(&self, meta: Self::Metadata, config: RestartWindowConfig) -> Result<()>;
+
+    #[rpc(meta, name = "cancelRestart")]
+    fn cancel_restart(&self, meta: Self::Metadata) -> Result<bool>;
+
+    #[rpc(meta, name = "restartProgress")]
+    fn restart_progress(&self, meta: Self::Metadata) -> Result<Option<RestartProgress>>;
+
+    /// Reports whether the cluster appears halted, and the progress of the wen restart
+    #[rpc(meta, name = "wenRestartStatus")]
+    fn wen_restart_status(&self, meta: Self::Metadata) -> Result<AdminRpcWenRestartStatus>;
+
+    /// Starts or stops writing the RPC transaction history from the next slot on
+    #[rpc(meta, name = "setTransactionHistory")]
+    fn set_transaction_history(
+        &self,
+        meta: Self::Metadata,
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    ) -> Result<TransactionHistoryStatus>;
+
+    #[rpc(meta, name = "transactionHistory")]
+    fn transaction_history(&self, meta: Self::Metadata) -> Result<TransactionHistoryStatus>;
+
+    /// Returns the JSON RPC usage per client in the current reporting period
+    #[rpc(meta, name = "rpcUsage")]
+    fn rpc_usage(&self, meta: Self::Metadata) -> Result<RpcUsageReport>;
+
+    #[rpc(meta, name = "rpcThreadPools")]
+    fn rpc_thread_pools(&self, meta: Self::Metadata) -> Result<RpcThreadPoolSizes>;
+
+    /// Restarts the JSON RPC service on thread pools of the given sizes, keeping the current size
+    /// of the pools left out
+    #[rpc(meta, name = "setRpcThreadPools")]
+    fn set_rpc_thread_pools(
+        &self,
+        meta: Self::Metadata,
+        rpc_threads: Option<usize>,
+        rpc_blocking_threads: Option<usize>,
+    ) -> Result<RpcThreadPoolSizes>;
+
+    /// Applies all of `changes`, or none of them if any is invalid or fails to apply
+    #[rpc(meta, name = "applyConfigTransaction")]
+    fn apply_config_transaction(
+        &self,
+        meta: Self::Metadata,
+        changes: Vec<AdminRpcConfigChange>,
+    ) -> Result<()>;
+
+    /// Writes a diagnostics bundle to the ledger directory and returns its path
+    #[rpc(meta, name = "diagnosticsBundle")]
+    fn diagnostics_bundle(&self, meta: Self::Metadata, log_lines: Option<usize>) -> Result<String>;
+
+    /// Samples the threads whose names start with one of `thread_names`, or all threads if there
+    /// are none, for `num_slots` slots and returns their stacks in the collapsed format. Only one
+    /// profile is taken at a time
+    #[rpc(name = "profileCpu")]
+    fn profile_cpu(
+        &self,
+        thread_names: Vec<String>,
+        num_slots: u64,
+        frequency: Option<u64>,
+    ) -> BoxFuture<Result<CpuProfile>>;
 }
 
 pub struct AdminRpcImpl;
@@ -511,8 +995,15 @@ impl AdminRpc for AdminRpcImpl {
 
     fn remove_all_authorized_voters(&self, meta: Self::Metadata) -> Result<()> {
         debug!("remove_all_authorized_voters received");
-        meta.authorized_voter_keypairs.write().unwrap().clear();
-        Ok(())
+        AdminRpcImpl::with_intent(
+            &meta,
+            IntentOperation::RemoveAllAuthorizedVoters,
+            || {
+                meta.authorized_voter_keypairs.write().unwrap().clear();
+                Ok(())
+            },
+            || meta.authorized_voter_keypairs.read().unwrap().is_empty(),
+        )
     }
 
     fn set_identity(
@@ -559,6 +1050,7 @@ impl AdminRpc for AdminRpcImpl {
                 jsonrpc_core::error::Error::internal_error()
             })?
             .staked_map_id;
+        let _config_lock = meta.config_lock.lock().unwrap();
         let mut write_staked_nodes = meta.staked_nodes_overrides.write().unwrap();
         write_staked_nodes.clear();
         write_staked_nodes.extend(loaded_config);
@@ -631,6 +1123,7 @@ impl AdminRpc for AdminRpcImpl {
         debug!("set_repair_whitelist request received");
 
         let whitelist: HashSet<Pubkey> = whitelist.into_iter().collect();
+        let _config_lock = meta.config_lock.lock().unwrap();
         meta.with_post_init(|post_init| {
             *post_init.repair_whitelist.write().unwrap() = whitelist;
             warn!(
@@ -800,6 +1293,27 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn write_lock_contention(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<AdminRpcWriteLockContention> {
+        debug!("write_lock_contention rpc request received");
+
+        meta.with_post_init(|post_init| {
+            Ok(post_init
+                .write_lock_contention_report
+                .summary(limit.unwrap_or(DEFAULT_WRITE_LOCK_CONTENTION_LIMIT))
+                .into())
+        })
+    }
+
+    fn poh_timing(&self, meta: Self::Metadata) -> Result<AdminRpcPohTiming> {
+        debug!("poh_timing rpc request received");
+
+        meta.with_post_init(|post_init| Ok(post_init.poh_timing_report.as_ref().into()))
+    }
+
     fn manage_block_production(
         &self,
         meta: Self::Metadata,
@@ -840,26 +1354,518 @@ impl AdminRpc for AdminRpcImpl {
             Ok(())
         })
     }
+
+    fn restart(&self, meta: Self::Metadata, config: RestartWindowConfig) -> Result<()> {
+        debug!("restart rpc request received: {config:?}");
+
+        let (bank_forks, cluster_info, leader_schedule_cache) =
+            meta.with_post_init(|post_init| {
+                Ok((
+                    post_init.bank_forks.clone(),
+                    post_init.cluster_info.clone(),
+                    post_init.leader_schedule_cache.clone(),
+                ))
+            })?;
+        let Some(id) = meta.restart_window.request(config) else {
+            info!("Pending restart updated: {config:?}");
+            return Ok(());
+        };
+        info!("Restart requested: {config:?}");
+
+        thread::Builder::new()
+            .name("solRestartWin".into())
+            .spawn(move || loop {
+                let bank = bank_forks.read().unwrap().working_bank();
+                match meta.restart_window.check(
+                    id,
+                    &bank,
+                    &cluster_info.id(),
+                    &leader_schedule_cache,
+                ) {
+                    Some(RestartStatus::Exiting) => {
+                        info!("Restart window reached at slot {}", bank.slot());
+                        if let Err(err) = AdminRpcImpl.exit(meta) {
+                            error!("Failed to exit at the restart window: {err}");
+                        }
+                        break;
+                    }
+                    Some(RestartStatus::Cancelled) | None => {
+                        info!("Restart cancelled");
+                        break;
+                    }
+                    Some(
+                        RestartStatus::WaitingForIdleWindow | RestartStatus::DelinquencyTooHigh,
+                    ) => {
+                        drop(bank);
+                        thread::sleep(Duration::from_millis(DEFAULT_MS_PER_SLOT));
+                    }
+                }
+            })
+            .unwrap();
+
+        Ok(())
+    }
+
+    fn cancel_restart(&self, meta: Self::Metadata) -> Result<bool> {
+        debug!("cancel_restart rpc request received");
+        Ok(meta.restart_window.cancel())
+    }
+
+    fn restart_progress(&self, meta: Self::Metadata) -> Result<Option<RestartProgress>> {
+        debug!("restart_progress rpc request received");
+        Ok(meta.restart_window.progress())
+    }
+
+    fn wen_restart_status(&self, meta: Self::Metadata) -> Result<AdminRpcWenRestartStatus> {
+        debug!("wen_restart_status rpc request received");
+        // The validator does not finish starting up while in wen restart
+        let cluster_halt = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|post_init| post_init.cluster_halt_status.halt());
+        let wen_restart = meta
+            .wen_restart_path
+            .as_ref()
+            .map(|wen_restart_path| {
+                read_wen_restart_status(wen_restart_path).map_err(|err| {
+                    jsonrpc_core::error::Error::invalid_params(format!(
+                        "Unable to read the wen restart progress at {}: {err}",
+                        wen_restart_path.display()
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(AdminRpcWenRestartStatus::new(cluster_halt, wen_restart))
+    }
+
+    fn set_transaction_history(
+        &self,
+        meta: Self::Metadata,
+        enable_rpc_transaction_history: bool,
+        enable_extended_tx_metadata_storage: bool,
+    ) -> Result<TransactionHistoryStatus> {
+        debug!(
+            "set_transaction_history rpc request received: {enable_rpc_transaction_history} \
+             {enable_extended_tx_metadata_storage}"
+        );
+        let _config_lock = meta.config_lock.lock().unwrap();
+        meta.with_post_init(|post_init| {
+            let transaction_history = Self::transaction_history_switch(post_init)?;
+            transaction_history.set(
+                enable_rpc_transaction_history,
+                enable_extended_tx_metadata_storage,
+            );
+            info!(
+                "Transaction history set to {enable_rpc_transaction_history}, extended metadata \
+                 to {enable_extended_tx_metadata_storage}"
+            );
+            Ok(transaction_history.status())
+        })
+    }
+
+    fn transaction_history(&self, meta: Self::Metadata) -> Result<TransactionHistoryStatus> {
+        debug!("transaction_history rpc request received");
+        meta.with_post_init(|post_init| Ok(Self::transaction_history_switch(post_init)?.status()))
+    }
+
+    fn rpc_usage(&self, meta: Self::Metadata) -> Result<RpcUsageReport> {
+        debug!("rpc_usage rpc request received");
+        meta.with_post_init(|post_init| {
+            post_init
+                .rpc_usage
+                .as_ref()
+                .map(|rpc_usage| rpc_usage.snapshot())
+                .ok_or_else(|| {
+                    jsonrpc_core::error::Error::invalid_params(
+                        "the validator was started without --rpc-usage-tracking",
+                    )
+                })
+        })
+    }
+
+    fn rpc_thread_pools(&self, meta: Self::Metadata) -> Result<RpcThreadPoolSizes> {
+        debug!("rpc_thread_pools rpc request received");
+        meta.with_post_init(|post_init| Ok(Self::rpc_thread_pool_resizer(post_init)?.sizes()))
+    }
+
+    fn set_rpc_thread_pools(
+        &self,
+        meta: Self::Metadata,
+        rpc_threads: Option<usize>,
+        rpc_blocking_threads: Option<usize>,
+    ) -> Result<RpcThreadPoolSizes> {
+        debug!(
+            "set_rpc_thread_pools rpc request received: {rpc_threads:?} {rpc_blocking_threads:?}"
+        );
+        // Not resized under the post init lock, as the RPC service takes a while to restart
+        let resizer =
+            meta.with_post_init(|post_init| Ok(Self::rpc_thread_pool_resizer(post_init)?.clone()))?;
+        let _config_lock = meta.config_lock.lock().unwrap();
+        let sizes = resizer.sizes();
+        let sizes = RpcThreadPoolSizes::new(
+            rpc_threads.unwrap_or(sizes.rpc_threads),
+            rpc_blocking_threads.unwrap_or(sizes.rpc_blocking_threads),
+        );
+        resizer.resize(sizes).map_err(|err| {
+            error!("Failed to resize the RPC thread pools: {err}");
+            jsonrpc_core::Error {
+                code: ErrorCode::InternalError,
+                message: err,
+                data: None,
+            }
+        })
+    }
+
+    fn apply_config_transaction(
+        &self,
+        meta: Self::Metadata,
+        changes: Vec<AdminRpcConfigChange>,
+    ) -> Result<()> {
+        debug!("apply_config_transaction rpc request received: {changes:?}");
+        // Not applied under the post init lock, as the RPC service takes a while to restart
+        let post_init = meta.with_post_init(|post_init| Ok(post_init.clone()))?;
+        let values = Self::verify_config_changes(&post_init, changes)?;
+        // Held from reading the values to roll back to until the last change or rollback
+        let _config_lock = meta.config_lock.lock().unwrap();
+
+        let num_changes = values.len();
+        let mut previous_values = Vec::with_capacity(num_changes);
+        for value in values {
+            let previous_value = Self::config_value(&meta, &post_init, &value)?;
+            if let Err(err) = Self::set_config_value(&meta, &post_init, value.clone()) {
+                error!(
+                    "Failed to apply {value:?}, rolling back the {} changes applied before it: {}",
+                    previous_values.len(),
+                    err.message
+                );
+                for previous_value in previous_values.into_iter().rev() {
+                    if let Err(rollback_err) =
+                        Self::set_config_value(&meta, &post_init, previous_value)
+                    {
+                        error!(
+                            "Failed to roll back a config change: {}",
+                            rollback_err.message
+                        );
+                    }
+                }
+                return Err(err);
+            }
+            previous_values.push(previous_value);
+        }
+        info!("Config transaction of {num_changes} changes applied");
+        Ok(())
+    }
+
+    fn diagnostics_bundle(&self, meta: Self::Metadata, log_lines: Option<usize>) -> Result<String> {
+        debug!("diagnostics_bundle rpc request received: {log_lines:?}");
+        let Some(diagnostics) = &meta.diagnostics else {
+            return Err(jsonrpc_core::Error {
+                code: ErrorCode::InvalidRequest,
+                message: "Diagnostics bundles are not available".to_string(),
+                data: None,
+            });
+        };
+        let path = diagnostics
+            .write_bundle(log_lines.unwrap_or(DEFAULT_LOG_LINES))
+            .map_err(|err| jsonrpc_core::Error {
+                code: ErrorCode::InternalError,
+                message: format!("failed to write the diagnostics bundle: {err}"),
+                data: None,
+            })?;
+        Ok(path.display().to_string())
+    }
+
+    fn profile_cpu(
+        &self,
+        thread_names: Vec<String>,
+        num_slots: u64,
+        frequency: Option<u64>,
+    ) -> BoxFuture<Result<CpuProfile>> {
+        Box::pin(async move {
+            debug!("profile_cpu rpc request received: {thread_names:?} {num_slots} {frequency:?}");
+            let duration = Duration::from_millis(num_slots.saturating_mul(DEFAULT_MS_PER_SLOT));
+            if duration.is_zero() || duration > MAX_PROFILE_DURATION {
+                return Err(jsonrpc_core::error::Error::invalid_params(format!(
+                    "the number of slots must be between 1 and {}",
+                    MAX_PROFILE_DURATION.as_millis() as u64 / DEFAULT_MS_PER_SLOT
+                )));
+            }
+            let frequency = frequency.unwrap_or(DEFAULT_PROFILE_FREQUENCY);
+            if !(1..=MAX_PROFILE_FREQUENCY).contains(&frequency) {
+                return Err(jsonrpc_core::error::Error::invalid_params(format!(
+                    "the frequency must be between 1 and {MAX_PROFILE_FREQUENCY} Hz"
+                )));
+            }
+            let Some(profile_guard) = ProfileGuard::try_acquire() else {
+                return Err(jsonrpc_core::Error {
+                    code: ErrorCode::InvalidRequest,
+                    message: "A CPU profile is already being taken".to_string(),
+                    data: None,
+                });
+            };
+
+            // Sampling sleeps for the whole duration, so it is kept off the event loop workers
+            tokio::task::spawn_blocking(move || {
+                let profile = cpu_profiler::profile_cpu(&thread_names, duration, frequency);
+                drop(profile_guard);
+                profile
+            })
+            .await
+            .map_err(|err| format!("the CPU profiler panicked: {err}"))
+            .and_then(|profile| profile)
+            .map_err(|err| jsonrpc_core::Error {
+                code: ErrorCode::InternalError,
+                message: err,
+                data: None,
+            })
+        })
+    }
 }
 
 impl AdminRpcImpl {
+    fn rpc_thread_pool_resizer(
+        post_init: &AdminRpcRequestMetadataPostInit,
+    ) -> Result<&Arc<RpcThreadPoolResizer>> {
+        post_init.rpc_thread_pools.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params("the validator was started without RPC")
+        })
+    }
+
+    fn transaction_history_switch(
+        post_init: &AdminRpcRequestMetadataPostInit,
+    ) -> Result<&TransactionHistorySwitch> {
+        post_init.transaction_history.as_deref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "the validator was started without --enable-rpc-transaction-history",
+            )
+        })
+    }
+
+    /// Checks each of `changes` and the config they result in together, returning the value each
+    /// change sets its field to
+    fn verify_config_changes(
+        post_init: &AdminRpcRequestMetadataPostInit,
+        changes: Vec<AdminRpcConfigChange>,
+    ) -> Result<Vec<ConfigValue>> {
+        let mut changed_fields = HashSet::new();
+        let mut values = Vec::with_capacity(changes.len());
+        for change in changes {
+            if !changed_fields.insert(std::mem::discriminant(&change)) {
+                return Err(jsonrpc_core::error::Error::invalid_params(format!(
+                    "{change:?} changes a field already changed by the transaction"
+                )));
+            }
+            let value = match change {
+                AdminRpcConfigChange::StakedNodesOverrides { path } => {
+                    let overrides = load_staked_nodes_overrides(&path).map_err(|err| {
+                        jsonrpc_core::error::Error::invalid_params(format!(
+                            "Failed to load staked nodes overrides from {path}: {err}"
+                        ))
+                    })?;
+                    ConfigValue::StakedNodesOverrides(overrides.staked_map_id)
+                }
+                AdminRpcConfigChange::RepairWhitelist { whitelist } => {
+                    ConfigValue::RepairWhitelist(
+                        whitelist
+                            .iter()
+                            .map(|pubkey| verify_pubkey(pubkey))
+                            .collect::<Result<_>>()?,
+                    )
+                }
+                AdminRpcConfigChange::TransactionHistory {
+                    enable_rpc_transaction_history,
+                    enable_extended_tx_metadata_storage,
+                } => {
+                    Self::transaction_history_switch(post_init)?;
+                    ConfigValue::TransactionHistory {
+                        enable_rpc_transaction_history,
+                        enable_extended_tx_metadata_storage,
+                    }
+                }
+                AdminRpcConfigChange::RpcThreadPools {
+                    rpc_threads,
+                    rpc_blocking_threads,
+                } => {
+                    if rpc_threads == Some(0) || rpc_blocking_threads == Some(0) {
+                        return Err(jsonrpc_core::error::Error::invalid_params(
+                            "the RPC thread pools need at least one thread",
+                        ));
+                    }
+                    let sizes = Self::rpc_thread_pool_resizer(post_init)?.sizes();
+                    ConfigValue::RpcThreadPools(RpcThreadPoolSizes::new(
+                        rpc_threads.unwrap_or(sizes.rpc_threads),
+                        rpc_blocking_threads.unwrap_or(sizes.rpc_blocking_threads),
+                    ))
+                }
+            };
+            values.push(value);
+        }
+
+        // The fields left unchanged keep their current value in the resulting config
+        let (enable_rpc_transaction_history, enable_extended_tx_metadata_storage) = values
+            .iter()
+            .find_map(|value| match value {
+                ConfigValue::TransactionHistory {
+                    enable_rpc_transaction_history,
+                    enable_extended_tx_metadata_storage,
+                } => Some((
+                    *enable_rpc_transaction_history,
+                    *enable_extended_tx_metadata_storage,
+                )),
+                _ => None,
+            })
+            .or_else(|| {
+                post_init.transaction_history.as_ref().map(|switch| {
+                    let status = switch.status();
+                    (
+                        status.enable_rpc_transaction_history,
+                        status.enable_extended_tx_metadata_storage,
+                    )
+                })
+            })
+            .unwrap_or_default();
+        if enable_extended_tx_metadata_storage && !enable_rpc_transaction_history {
+            return Err(jsonrpc_core::error::Error::invalid_params(
+                "the extended transaction metadata is only stored along with the transaction \
+                 history",
+            ));
+        }
+        Ok(values)
+    }
+
+    /// The current value of the field `value` is for
+    fn config_value(
+        meta: &AdminRpcRequestMetadata,
+        post_init: &AdminRpcRequestMetadataPostInit,
+        value: &ConfigValue,
+    ) -> Result<ConfigValue> {
+        Ok(match value {
+            ConfigValue::StakedNodesOverrides(_) => ConfigValue::StakedNodesOverrides(
+                meta.staked_nodes_overrides.read().unwrap().clone(),
+            ),
+            ConfigValue::RepairWhitelist(_) => {
+                ConfigValue::RepairWhitelist(post_init.repair_whitelist.read().unwrap().clone())
+            }
+            ConfigValue::TransactionHistory { .. } => {
+                let status = Self::transaction_history_switch(post_init)?.status();
+                ConfigValue::TransactionHistory {
+                    enable_rpc_transaction_history: status.enable_rpc_transaction_history,
+                    enable_extended_tx_metadata_storage: status.enable_extended_tx_metadata_storage,
+                }
+            }
+            ConfigValue::RpcThreadPools(_) => {
+                ConfigValue::RpcThreadPools(Self::rpc_thread_pool_resizer(post_init)?.sizes())
+            }
+        })
+    }
+
+    fn set_config_value(
+        meta: &AdminRpcRequestMetadata,
+        post_init: &AdminRpcRequestMetadataPostInit,
+        value: ConfigValue,
+    ) -> Result<()> {
+        match value {
+            ConfigValue::StakedNodesOverrides(overrides) => {
+                *meta.staked_nodes_overrides.write().unwrap() = overrides;
+                info!("Staked nodes overrides set");
+            }
+            ConfigValue::RepairWhitelist(whitelist) => {
+                warn!("Repair whitelist set to {whitelist:?}");
+                *post_init.repair_whitelist.write().unwrap() = whitelist;
+            }
+            ConfigValue::TransactionHistory {
+                enable_rpc_transaction_history,
+                enable_extended_tx_metadata_storage,
+            } => {
+                Self::transaction_history_switch(post_init)?.set(
+                    enable_rpc_transaction_history,
+                    enable_extended_tx_metadata_storage,
+                );
+                info!(
+                    "Transaction history set to {enable_rpc_transaction_history}, extended \
+                     metadata to {enable_extended_tx_metadata_storage}"
+                );
+            }
+            ConfigValue::RpcThreadPools(sizes) => {
+                Self::rpc_thread_pool_resizer(post_init)?
+                    .resize(sizes)
+                    .map_err(|err| jsonrpc_core::Error {
+                        code: ErrorCode::InternalError,
+                        message: format!("failed to resize the RPC thread pools: {err}"),
+                        data: None,
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `operation` between an intent and a completion entry of the intent log, if there is
+    /// one, and fails it if `verify` finds that it did not take effect
+    fn with_intent<R>(
+        meta: &AdminRpcRequestMetadata,
+        operation: IntentOperation,
+        run: impl FnOnce() -> Result<R>,
+        verify: impl FnOnce() -> bool,
+    ) -> Result<R> {
+        let Some(intent_log) = &meta.intent_log else {
+            return run();
+        };
+        let description = format!("{operation:?}");
+        let id = intent_log.begin(operation).map_err(|err| {
+            error!(
+                "Not starting {description}, failed to record the intent in {}: {err}",
+                intent_log.path().display()
+            );
+            jsonrpc_core::error::Error::internal_error()
+        })?;
+        let result = run();
+        let succeeded = result.is_ok() && verify();
+        if let Err(err) = intent_log.finish(id, succeeded) {
+            error!(
+                "Failed to record the outcome of {description} in {}: {err}",
+                intent_log.path().display()
+            );
+        }
+        if result.is_ok() && !succeeded {
+            error!("{description} did not take effect");
+            return Err(jsonrpc_core::error::Error::internal_error());
+        }
+        result
+    }
+
     fn add_authorized_voter_keypair(
         meta: AdminRpcRequestMetadata,
         authorized_voter: Keypair,
     ) -> Result<()> {
-        let mut authorized_voter_keypairs = meta.authorized_voter_keypairs.write().unwrap();
-
-        if authorized_voter_keypairs
-            .iter()
-            .any(|x| x.pubkey() == authorized_voter.pubkey())
-        {
-            Err(jsonrpc_core::error::Error::invalid_params(
-                "Authorized voter already present",
-            ))
-        } else {
-            authorized_voter_keypairs.push(Arc::new(authorized_voter));
-            Ok(())
-        }
+        let voter = authorized_voter.pubkey();
+        Self::with_intent(
+            &meta,
+            IntentOperation::AddAuthorizedVoter { voter },
+            || {
+                let mut authorized_voter_keypairs = meta.authorized_voter_keypairs.write().unwrap();
+
+                if authorized_voter_keypairs
+                    .iter()
+                    .any(|x| x.pubkey() == authorized_voter.pubkey())
+                {
+                    Err(jsonrpc_core::error::Error::invalid_params(
+                        "Authorized voter already present",
+                    ))
+                } else {
+                    authorized_voter_keypairs.push(Arc::new(authorized_voter));
+                    Ok(())
+                }
+            },
+            || {
+                meta.authorized_voter_keypairs
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .any(|x| x.pubkey() == voter)
+            },
+        )
     }
 
     fn set_identity_keypair(
@@ -868,29 +1874,45 @@ impl AdminRpcImpl {
         require_tower: bool,
     ) -> Result<()> {
         meta.with_post_init(|post_init| {
-            if require_tower {
-                let _ = Tower::restore(meta.tower_storage.as_ref(), &identity_keypair.pubkey())
-                    .map_err(|err| {
-                        jsonrpc_core::error::Error::invalid_params(format!(
-                            "Unable to load tower file for identity {}: {}",
-                            identity_keypair.pubkey(),
-                            err
-                        ))
-                    })?;
-            }
+            let to = identity_keypair.pubkey();
+            let operation = IntentOperation::SetIdentity {
+                from: post_init.cluster_info.id(),
+                to,
+                require_tower,
+            };
+            Self::with_intent(
+                &meta,
+                operation,
+                || {
+                    if require_tower {
+                        let _ =
+                            Tower::restore(meta.tower_storage.as_ref(), &identity_keypair.pubkey())
+                                .map_err(|err| {
+                                    jsonrpc_core::error::Error::invalid_params(format!(
+                                        "Unable to load tower file for identity {}: {}",
+                                        identity_keypair.pubkey(),
+                                        err
+                                    ))
+                                })?;
+                    }
 
-            for (key, notifier) in &*post_init.notifies.read().unwrap() {
-                if let Err(err) = notifier.update_key(&identity_keypair) {
-                    error!("Error updating network layer keypair: {err} on {key:?}");
-                }
-            }
+                    for (key, notifier) in &*post_init.notifies.read().unwrap() {
+                        if let Err(err) = notifier.update_key(&identity_keypair) {
+                            error!("Error updating network layer keypair: {err} on {key:?}");
+                        }
+                    }
 
-            solana_metrics::set_host_id(identity_keypair.pubkey().to_string());
-            post_init
-                .cluster_info
-                .set_keypair(Arc::new(identity_keypair));
-            warn!("Identity set to {}", post_init.cluster_info.id());
-            Ok(())
+                    solana_metrics::set_host_id(identity_keypair.pubkey().to_string());
+                    let identity_keypair = Arc::new(identity_keypair);
+                    post_init.cluster_info.set_keypair(identity_keypair.clone());
+                    if let Some(intent_log) = &meta.intent_log {
+                        intent_log.set_signer(identity_keypair);
+                    }
+                    warn!("Identity set to {}", post_init.cluster_info.id());
+                    Ok(())
+                },
+                || post_init.cluster_info.id() == to,
+            )
         })
     }
 }
@@ -903,6 +1925,13 @@ fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> RpcAcco
     }
 }
 
+/// The handler of the admin RPC methods, shared by the IPC and HTTP interfaces
+pub(crate) fn io_handler() -> MetaIoHandler<AdminRpcRequestMetadata> {
+    let mut io = MetaIoHandler::default();
+    io.extend_with(AdminRpcImpl.to_delegate());
+    io
+}
+
 // Start the Admin RPC interface
 pub fn run(ledger_path: &Path, metadata: AdminRpcRequestMetadata) {
     let admin_rpc_path = admin_rpc_path(ledger_path);
@@ -917,8 +1946,7 @@ pub fn run(ledger_path: &Path, metadata: AdminRpcRequestMetadata) {
     Builder::new()
         .name("solAdminRpc".to_string())
         .spawn(move || {
-            let mut io = MetaIoHandler::default();
-            io.extend_with(AdminRpcImpl.to_delegate());
+            let io = io_handler();
 
             let validator_exit = metadata.validator_exit.clone();
             let server = ServerBuilder::with_meta_extractor(io, move |_req: &RequestContext| {
@@ -1029,7 +2057,8 @@ pub fn load_staked_nodes_overrides(
 mod tests {
     use {
         super::*,
-        serde_json::Value,
+        crate::intent_log::INTENT_LOG_FILE,
+        serde_json::{json, Value},
         solana_account::{Account, AccountSharedData},
         solana_accounts_db::{
             accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
@@ -1046,12 +2075,17 @@ mod tests {
             genesis_utils::{
                 create_genesis_config, create_genesis_config_with_leader, GenesisConfigInfo,
             },
+            leader_schedule_cache::LeaderScheduleCache,
         },
         solana_net_utils::sockets::bind_to_localhost_unique,
+        solana_poh::poh_timing_report::PohAlertThresholds,
         solana_program_option::COption,
         solana_program_pack::Pack,
         solana_pubkey::Pubkey,
-        solana_rpc::rpc::create_validator_exit,
+        solana_rpc::{
+            rpc::create_validator_exit,
+            rpc_usage::{RpcClientUsage, RpcUsageConfig, RpcUsageTracker},
+        },
         solana_runtime::{
             bank::{Bank, BankTestConfig},
             bank_forks::BankForks,
@@ -1128,9 +2162,30 @@ mod tests {
                     ),
                     node: None,
                     banking_control_sender: mpsc::channel(1).0,
+                    write_lock_contention_report: Arc::default(),
+                    in_flight_batches_report: Arc::default(),
+                    leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(
+                        &bank_forks.read().unwrap().root_bank(),
+                    )),
+                    poh_timing_report: Arc::new(PohTimingReport::new(
+                        PohAlertThresholds::default(),
+                        0,
+                        0,
+                    )),
+                    cluster_halt_status: Arc::default(),
+                    transaction_history: Some(Arc::new(TransactionHistorySwitch::new(
+                        true, false,
+                    ))),
+                    rpc_usage: Some(Arc::new(RpcUsageTracker::new(RpcUsageConfig::default()))),
+                    rpc_thread_pools: None,
                 }))),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
+                restart_window: Arc::default(),
+                intent_log: None,
+                diagnostics: None,
+                wen_restart_path: None,
+                config_lock: Arc::default(),
             };
             let mut io = MetaIoHandler::default();
             io.extend_with(AdminRpcImpl.to_delegate());
@@ -1160,6 +2215,296 @@ mod tests {
         (BankForks::new_rw_arc(bank), Arc::new(voting_keypair))
     }
 
+    #[test]
+    fn test_transaction_history() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        let status = |res: Option<String>| -> TransactionHistoryStatus {
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            serde_json::from_value(result["result"].clone()).unwrap()
+        };
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"transactionHistory"}"#;
+        assert_eq!(
+            status(io.handle_request_sync(req, meta.clone())),
+            TransactionHistoryStatus {
+                enable_rpc_transaction_history: true,
+                enable_extended_tx_metadata_storage: false,
+                recording_since: None,
+            }
+        );
+
+        let req =
+            r#"{"jsonrpc":"2.0","id":1,"method":"setTransactionHistory","params":[false,true]}"#;
+        assert_eq!(
+            status(io.handle_request_sync(req, meta.clone())),
+            TransactionHistoryStatus {
+                enable_rpc_transaction_history: false,
+                enable_extended_tx_metadata_storage: true,
+                recording_since: None,
+            }
+        );
+
+        meta.post_init
+            .write()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .transaction_history = None;
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_object());
+    }
+
+    #[test]
+    fn test_profile_cpu_rejects_invalid_params() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        for params in [
+            json!([["solReplay"], 0, null]),
+            json!([["solReplay"], 1_000, null]),
+            json!([["solReplay"], 1, 0]),
+            json!([["solReplay"], 1, MAX_PROFILE_FREQUENCY + 1]),
+        ] {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "profileCpu",
+                "params": params,
+            });
+            let res = io
+                .handle_request_sync(&req.to_string(), meta.clone())
+                .expect("actual response");
+            let result: Value =
+                serde_json::from_str(&res).expect("actual response deserialization");
+            assert_eq!(result["error"]["code"], json!(-32602), "{params}");
+        }
+    }
+
+    #[test]
+    fn test_profile_cpu_rejects_concurrent_profiles() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "profileCpu",
+            "params": [["solReplay"], 1, null],
+        });
+        let profile_guard = ProfileGuard::try_acquire().unwrap();
+        let res = io
+            .handle_request_sync(&req.to_string(), meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert_eq!(result["error"]["code"], json!(-32600));
+        drop(profile_guard);
+    }
+
+    #[test]
+    fn test_apply_config_transaction() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        let apply = |changes: Value| -> Value {
+            let req = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "applyConfigTransaction",
+                "params": [changes],
+            });
+            let res = io
+                .handle_request_sync(&req.to_string(), meta.clone())
+                .expect("actual response");
+            serde_json::from_str(&res).expect("actual response deserialization")
+        };
+        let repair_whitelist = || {
+            meta.post_init
+                .read()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .repair_whitelist
+                .read()
+                .unwrap()
+                .clone()
+        };
+        let transaction_history = || {
+            meta.post_init
+                .read()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .transaction_history
+                .as_ref()
+                .unwrap()
+                .status()
+        };
+
+        let overrides_dir = tempfile::TempDir::new().unwrap();
+        let overrides_path = overrides_dir.path().join("overrides.yml");
+        let overridden_node = Pubkey::new_unique();
+        std::fs::write(
+            &overrides_path,
+            format!("staked_map_id:\n  {overridden_node}: 42\n"),
+        )
+        .unwrap();
+        let whitelisted_node = Pubkey::new_unique();
+        let result = apply(json!([
+            {"field": "stakedNodesOverrides", "path": overrides_path},
+            {"field": "repairWhitelist", "whitelist": [whitelisted_node.to_string()]},
+            {
+                "field": "transactionHistory",
+                "enableRpcTransactionHistory": true,
+                "enableExtendedTxMetadataStorage": true,
+            },
+        ]));
+        assert!(result["error"].is_null(), "{result}");
+        assert_eq!(
+            *meta.staked_nodes_overrides.read().unwrap(),
+            HashMap::from([(overridden_node, 42)])
+        );
+        assert_eq!(repair_whitelist(), HashSet::from([whitelisted_node]));
+        assert!(transaction_history().enable_extended_tx_metadata_storage);
+
+        // Nothing applies when one of the changes is invalid
+        for changes in [
+            // No RPC service to resize
+            json!([
+                {"field": "repairWhitelist", "whitelist": []},
+                {"field": "rpcThreadPools", "rpcThreads": 4},
+            ]),
+            // Extended metadata without the transaction history
+            json!([
+                {"field": "repairWhitelist", "whitelist": []},
+                {
+                    "field": "transactionHistory",
+                    "enableRpcTransactionHistory": false,
+                    "enableExtendedTxMetadataStorage": true,
+                },
+            ]),
+            // The same field changed twice
+            json!([
+                {"field": "repairWhitelist", "whitelist": []},
+                {"field": "repairWhitelist", "whitelist": []},
+            ]),
+            json!([
+                {"field": "repairWhitelist", "whitelist": ["not a pubkey"]},
+            ]),
+        ] {
+            assert!(apply(changes)["error"].is_object());
+            assert_eq!(repair_whitelist(), HashSet::from([whitelisted_node]));
+            assert!(transaction_history().enable_rpc_transaction_history);
+        }
+    }
+
+    #[test]
+    fn test_authorized_voter_intents() {
+        let RpcHandler { io, mut meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        let ledger_path = tempfile::TempDir::new().unwrap();
+        let identity = Arc::new(Keypair::new());
+        let (intent_log, _) = IntentLog::open(ledger_path.path(), identity.clone()).unwrap();
+        meta.intent_log = Some(Arc::new(intent_log));
+
+        let voter_bytes = format!("{:?}", Keypair::new().to_bytes());
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"addAuthorizedVoterFromBytes","params":[{voter_bytes}]}}"#,
+        );
+        let res = io
+            .handle_request_sync(&req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_null());
+        // Adding the same voter again fails, and is recorded as failed
+        let res = io
+            .handle_request_sync(&req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_object());
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"removeAllAuthorizedVoters"}"#;
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_null());
+        assert!(meta.authorized_voter_keypairs.read().unwrap().is_empty());
+
+        // Every intent was completed, so there is nothing to resolve
+        meta.intent_log = None;
+        let log = std::fs::read_to_string(ledger_path.path().join(INTENT_LOG_FILE)).unwrap();
+        assert_eq!(log.lines().count(), 6);
+        let (_intent_log, resolved) = IntentLog::open(ledger_path.path(), identity).unwrap();
+        assert!(resolved.is_empty());
+    }
+
+    #[test]
+    fn test_rpc_usage() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+        let usage = RpcClientUsage {
+            num_requests: 3,
+            response_bytes: 512,
+            compute_micros: 40,
+        };
+        meta.post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .rpc_usage
+            .as_ref()
+            .unwrap()
+            .record("customer-a", usage);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"rpcUsage"}"#;
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        let report: RpcUsageReport = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(report.clients.len(), 1);
+        assert_eq!(report.clients["customer-a"], usage);
+
+        meta.post_init.write().unwrap().as_mut().unwrap().rpc_usage = None;
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_object());
+    }
+
+    #[test]
+    fn test_wen_restart_status() {
+        let RpcHandler { io, mut meta, .. } = RpcHandler::start_with_config(TestConfig::default());
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"wenRestartStatus"}"#;
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        let status: AdminRpcWenRestartStatus =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert!(status.cluster_halt.is_none());
+        assert!(status.wen_restart.is_none());
+        assert!(status.next_step.is_none());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        meta.wen_restart_path = Some(temp_dir.path().join("wen_restart_progress.proto"));
+        let res = io
+            .handle_request_sync(req, meta.clone())
+            .expect("actual response");
+        let result: Value = serde_json::from_str(&res).expect("actual response deserialization");
+        assert!(result["error"].is_object());
+
+        let status = AdminRpcWenRestartStatus::new(
+            Some(ClusterHalt {
+                root: 10,
+                highest_slot: 14,
+                root_timestamp_ms: 0,
+            }),
+            None,
+        );
+        assert_eq!(status.cluster_halt.as_ref().unwrap().root_slot, 10);
+        assert!(status.next_step.unwrap().contains("--wen-restart"));
+    }
+
     #[test]
     fn test_secondary_index_key_sizes() {
         for secondary_index_enabled in [true, false] {
@@ -1551,6 +2896,11 @@ mod tests {
                 post_init: post_init.clone(),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
+                restart_window: Arc::default(),
+                intent_log: None,
+                diagnostics: None,
+                wen_restart_path: None,
+                config_lock: Arc::default(),
             };
 
             let _validator = Validator::new(
@@ -278,6 +278,11 @@ fn main() {
         .unwrap_or_default()
         .collect();
 
+    let accounts_exports: Vec<_> = matches
+        .values_of("accounts_export")
+        .unwrap_or_default()
+        .collect();
+
     let accounts_to_clone: HashSet<_> = pubkeys_of(&matches, "clone_account")
         .map(|v| v.into_iter().collect())
         .unwrap_or_default();
@@ -432,6 +437,11 @@ fn main() {
             post_init: admin_service_post_init,
             tower_storage: tower_storage.clone(),
             rpc_to_plugin_manager_sender,
+            restart_window: Arc::default(),
+            intent_log: None,
+            diagnostics: None,
+            wen_restart_path: None,
+            config_lock: Arc::default(),
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -482,6 +492,11 @@ fn main() {
             println!("Error: add_accounts_from_directories failed: {e}");
             exit(1);
         })
+        .add_accounts_from_exports(&accounts_exports)
+        .unwrap_or_else(|e| {
+            println!("Error: add_accounts_from_exports failed: {e}");
+            exit(1);
+        })
         .deactivate_features(&features_to_deactivate);
 
     genesis.rpc_config(JsonRpcConfig {
@@ -19,6 +19,7 @@ use {
         crds_data,
         gossip_service::GossipService,
         node::Node,
+        peer_preference::PeerPreference,
     },
     solana_hash::Hash,
     solana_keypair::Keypair,
@@ -141,6 +142,7 @@ fn is_known_validator(id: &Pubkey, known_validators: &Option<HashSet<Pubkey>>) -
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn start_gossip_node(
     identity_keypair: Arc<Keypair>,
     cluster_entrypoints: &[ContactInfo],
@@ -149,15 +151,27 @@ fn start_gossip_node(
     gossip_sockets: Arc<[UdpSocket]>,
     expected_shred_version: u16,
     gossip_validators: Option<HashSet<Pubkey>>,
+    known_validators: Option<&HashSet<Pubkey>>,
+    region: Option<&str>,
+    peer_preference: PeerPreference,
     should_check_duplicate_instance: bool,
     socket_addr_space: SocketAddrSpace,
 ) -> (Arc<ClusterInfo>, Arc<AtomicBool>, GossipService) {
-    let contact_info = ClusterInfo::gossip_contact_info(
+    let mut contact_info = ClusterInfo::gossip_contact_info(
         identity_keypair.pubkey(),
         *gossip_addr,
         expected_shred_version,
     );
+    if let Some(region) = region {
+        contact_info
+            .set_region(region.to_string())
+            .expect("region should have been validated");
+    }
     let mut cluster_info = ClusterInfo::new(contact_info, identity_keypair, socket_addr_space);
+    cluster_info.set_peer_preference(peer_preference);
+    if let Some(known_validators) = known_validators {
+        cluster_info.set_region_peers(known_validators.clone());
+    }
     cluster_info.set_entrypoints(cluster_entrypoints.to_vec());
     cluster_info.restore_contact_info(ledger_path, 0);
     let cluster_info = Arc::new(cluster_info);
@@ -175,6 +189,13 @@ fn start_gossip_node(
     (cluster_info, gossip_exit_flag, gossip_service)
 }
 
+/// Returns how strongly the peer preference favors `rpc_contact_info`, 1 if it does not
+fn peer_preference_weight(cluster_info: &ClusterInfo, rpc_contact_info: &ContactInfo) -> u64 {
+    let mut weights = [1];
+    cluster_info.apply_peer_preference(std::slice::from_ref(rpc_contact_info), &mut weights);
+    weights[0]
+}
+
 fn get_rpc_peers(
     cluster_info: &ClusterInfo,
     validator_config: &ValidatorConfig,
@@ -538,7 +559,13 @@ fn get_vetted_rpc_nodes(
                     Option<Duration>,
                 )>>()
                 .into_iter()
-                .sorted_by_key(|(_, _, _, ping_time)| ping_time.unwrap())
+                // The last node is tried first, so preferred nodes are sorted last
+                .sorted_by_key(|(rpc_contact_info, _, _, ping_time)| {
+                    (
+                        peer_preference_weight(cluster_info, rpc_contact_info),
+                        ping_time.unwrap(),
+                    )
+                })
                 .map(|(rpc_contact_info, snapshot_hash, rpc_client, _)| {
                     (rpc_contact_info, snapshot_hash, rpc_client)
                 })
@@ -610,6 +637,9 @@ pub fn rpc_bootstrap(
                     .expected_shred_version
                     .expect("expected_shred_version should not be None"),
                 validator_config.gossip_validators.clone(),
+                validator_config.known_validators.as_ref(),
+                node.info.region(),
+                validator_config.peer_preference,
                 should_check_duplicate_instance,
                 socket_addr_space,
             ));
@@ -734,7 +764,7 @@ fn get_rpc_nodes(
         } else {
             KnownValidatorsToWaitFor::Any
         };
-        let peer_snapshot_hashes = get_peer_snapshot_hashes(
+        let mut peer_snapshot_hashes = get_peer_snapshot_hashes(
             cluster_info,
             &rpc_peers,
             validator_config.known_validators.as_ref(),
@@ -753,6 +783,13 @@ fn get_rpc_nodes(
             retry_reason = Some("No snapshots available".to_owned());
             continue;
         } else {
+            // Evaluate the preferred peers first, keeping the order of the others
+            peer_snapshot_hashes.sort_by_key(|peer_snapshot_hash| {
+                std::cmp::Reverse(peer_preference_weight(
+                    cluster_info,
+                    &peer_snapshot_hash.rpc_contact_info,
+                ))
+            });
             let rpc_peers = peer_snapshot_hashes
                 .iter()
                 .map(|peer_snapshot_hash| peer_snapshot_hash.rpc_contact_info.pubkey())
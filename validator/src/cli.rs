@@ -1,5 +1,8 @@
 use {
-    crate::{commands, commands::run::args::pub_sub_config},
+    crate::{
+        commands,
+        commands::run::args::{is_valid_cpu_core_index, pub_sub_config},
+    },
     agave_snapshots::{
         snapshot_config::{
             DEFAULT_FULL_SNAPSHOT_ARCHIVE_INTERVAL_SLOTS,
@@ -21,11 +24,17 @@ use {
     },
     solana_clock::Slot,
     solana_core::{
-        banking_trace::BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT, validator::TransactionStructure,
+        banking_trace::BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT,
+        epoch_precompute_service::DEFAULT_EPOCH_PRECOMPUTE_SLOTS,
+        repair::serve_repair::DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND,
+        validator::TransactionStructure,
     },
     solana_epoch_schedule::MINIMUM_SLOTS_PER_EPOCH,
     solana_faucet::faucet::{self, FAUCET_PORT},
     solana_hash::Hash,
+    solana_ledger::blockstore_cleanup_service::{
+        DEFAULT_CLEANUP_PAUSE_LEADER_SLOT_WINDOW, DEFAULT_CLEANUP_PAUSE_REPAIR_SERVING_WINDOW,
+    },
     solana_net_utils::{MINIMUM_VALIDATOR_PORT_RANGE_WIDTH, VALIDATOR_PORT_RANGE},
     solana_quic_definitions::QUIC_PORT_OFFSET,
     solana_send_transaction_service::send_transaction_service::{self},
@@ -36,6 +45,9 @@ use {
         DEFAULT_MAX_STREAMS_PER_MS, DEFAULT_MAX_UNSTAKED_CONNECTIONS, DEFAULT_QUIC_ENDPOINTS,
     },
     solana_tpu_client::tpu_client::{DEFAULT_TPU_CONNECTION_POOL_SIZE, DEFAULT_VOTE_USE_QUIC},
+    solana_turbine::sigverify_shreds::{
+        DEFAULT_SHRED_SIGVERIFY_BATCH_SIZE, DEFAULT_SHRED_SIGVERIFY_QUEUE_DEPTH,
+    },
     std::{cmp::Ordering, path::PathBuf, str::FromStr},
 };
 
@@ -64,6 +76,10 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
         .subcommand(commands::exit::command())
         .subcommand(commands::authorized_voter::command())
         .subcommand(commands::contact_info::command())
+        .subcommand(commands::apply_config::command())
+        .subcommand(commands::diagnostics_bundle::command())
+        .subcommand(commands::doctor::command())
+        .subcommand(commands::generate_service::command())
         .subcommand(commands::repair_shred_from_peer::command())
         .subcommand(commands::repair_whitelist::command())
         .subcommand(
@@ -72,10 +88,20 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
         .subcommand(commands::monitor::command())
         .subcommand(SubCommand::with_name("run").about("Run the validator"))
         .subcommand(commands::plugin::command())
+        .subcommand(commands::poh_timing::command())
+        .subcommand(commands::profile_cpu::command())
+        .subcommand(commands::rpc_follower::command())
+        .subcommand(commands::rpc_threads::command())
+        .subcommand(commands::rpc_usage::command())
+        .subcommand(commands::self_test::command())
         .subcommand(commands::set_identity::command())
         .subcommand(commands::set_log_filter::command())
         .subcommand(commands::staked_nodes_overrides::command())
+        .subcommand(commands::transaction_history::command())
+        .subcommand(commands::restart::command())
         .subcommand(commands::wait_for_restart_window::command())
+        .subcommand(commands::wen_restart_status::command())
+        .subcommand(commands::write_lock_contention::command())
         .subcommand(commands::set_public_address::command())
         .subcommand(commands::manage_block_production::command(default_args));
 
@@ -149,6 +175,17 @@ fn deprecated_arguments() -> Vec<DeprecatedArg> {
             .help("Milliseconds to wait in the TPU receiver for packet coalescing."),
             usage_warning:"tpu_coalesce will be dropped (currently ignored)",
     );
+    add_arg!(
+        // deprecated in v4.0.0
+        Arg::with_name("experimental_poh_pinned_cpu_core")
+            .long("experimental-poh-pinned-cpu-core")
+            .takes_value(true)
+            .value_name("CPU_CORE_INDEX")
+            .validator(is_valid_cpu_core_index)
+            .conflicts_with("poh_pinned_cpu_core")
+            .help("EXPERIMENTAL: Specify which CPU core PoH is pinned to"),
+            replaced_by: "poh-pinned-cpu-core",
+    );
     add_arg!(
         // deprecated in v3.0.0
         Arg::with_name("tpu_disable_quic")
@@ -239,6 +276,11 @@ pub struct DefaultArgs {
     pub snapshot_archive_format: String,
     pub snapshot_zstd_compression_level: String,
 
+    pub ledger_cleanup_pause_leader_slots: String,
+    pub ledger_cleanup_pause_repair_serving_secs: String,
+    pub serve_repair_max_bytes_per_second: String,
+    pub epoch_precompute_slots: String,
+
     pub rocksdb_shred_compaction: String,
     pub rocksdb_ledger_compression: String,
     pub rocksdb_perf_sample_interval: String,
@@ -264,6 +306,9 @@ pub struct DefaultArgs {
 
     pub wen_restart_path: String,
 
+    pub tvu_shred_sigverify_batch_size: String,
+    pub tvu_shred_sigverify_queue_depth: String,
+
     pub thread_args: DefaultThreadArgs,
 }
 
@@ -294,6 +339,13 @@ impl DefaultArgs {
             snapshot_zstd_compression_level: "1".to_string(), // level 1 is optimized for speed
             contact_debug_interval: "120000".to_string(),
             snapshot_version: SnapshotVersion::default(),
+            ledger_cleanup_pause_leader_slots: DEFAULT_CLEANUP_PAUSE_LEADER_SLOT_WINDOW.to_string(),
+            ledger_cleanup_pause_repair_serving_secs: DEFAULT_CLEANUP_PAUSE_REPAIR_SERVING_WINDOW
+                .as_secs()
+                .to_string(),
+            serve_repair_max_bytes_per_second: DEFAULT_SERVE_REPAIR_MAX_BYTES_PER_SECOND
+                .to_string(),
+            epoch_precompute_slots: DEFAULT_EPOCH_PRECOMPUTE_SLOTS.to_string(),
             rocksdb_shred_compaction: "level".to_string(),
             rocksdb_ledger_compression: "none".to_string(),
             rocksdb_perf_sample_interval: "0".to_string(),
@@ -320,6 +372,8 @@ impl DefaultArgs {
             block_production_pacing_fill_time_millis: BankingStage::default_fill_time_millis()
                 .to_string(),
             wen_restart_path: "wen_restart_progress.proto".to_string(),
+            tvu_shred_sigverify_batch_size: DEFAULT_SHRED_SIGVERIFY_BATCH_SIZE.to_string(),
+            tvu_shred_sigverify_queue_depth: DEFAULT_SHRED_SIGVERIFY_QUEUE_DEPTH.to_string(),
             thread_args: DefaultThreadArgs::default(),
         }
     }
@@ -569,6 +623,18 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                      parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("accounts_export")
+                .long("accounts-export")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .help(
+                    "Load the accounts of an export written by `agave-ledger-tool accounts \
+                     export`, rejecting it if the accounts do not match their hashes. If the \
+                     ledger already exists then this parameter is silently ignored",
+                ),
+        )
         .arg(
             Arg::with_name("ticks_per_slot")
                 .long("ticks-per-slot")
@@ -27,6 +27,7 @@ pub struct DefaultThreadArgs {
     pub tvu_receive_threads: String,
     pub tvu_retransmit_threads: String,
     pub tvu_sigverify_threads: String,
+    pub unpack_threads: String,
 }
 
 impl Default for DefaultThreadArgs {
@@ -55,6 +56,7 @@ impl Default for DefaultThreadArgs {
             tvu_receive_threads: TvuReceiveThreadsArg::bounded_default().to_string(),
             tvu_retransmit_threads: TvuRetransmitThreadsArg::bounded_default().to_string(),
             tvu_sigverify_threads: TvuShredSigverifyThreadsArg::bounded_default().to_string(),
+            unpack_threads: UnpackThreadsArg::bounded_default().to_string(),
         }
     }
 }
@@ -81,6 +83,7 @@ pub fn thread_args<'a>(defaults: &DefaultThreadArgs) -> Vec<Arg<'_, 'a>> {
         new_thread_arg::<TvuReceiveThreadsArg>(&defaults.tvu_receive_threads),
         new_thread_arg::<TvuRetransmitThreadsArg>(&defaults.tvu_retransmit_threads),
         new_thread_arg::<TvuShredSigverifyThreadsArg>(&defaults.tvu_sigverify_threads),
+        new_thread_arg::<UnpackThreadsArg>(&defaults.unpack_threads),
     ]
 }
 
@@ -110,6 +113,7 @@ pub struct NumThreadConfig {
     pub tvu_receive_threads: NonZeroUsize,
     pub tvu_retransmit_threads: NonZeroUsize,
     pub tvu_sigverify_threads: NonZeroUsize,
+    pub unpack_threads: NonZeroUsize,
 }
 
 pub fn parse_num_threads_args(matches: &ArgMatches) -> NumThreadConfig {
@@ -172,6 +176,7 @@ pub fn parse_num_threads_args(matches: &ArgMatches) -> NumThreadConfig {
             TvuShredSigverifyThreadsArg::NAME,
             NonZeroUsize
         ),
+        unpack_threads: value_t_or_exit!(matches, UnpackThreadsArg::NAME, NonZeroUsize),
     }
 }
 
@@ -412,3 +417,16 @@ impl ThreadArg for TvuShredSigverifyThreadsArg {
         get_thread_count()
     }
 }
+
+struct UnpackThreadsArg;
+impl ThreadArg for UnpackThreadsArg {
+    const NAME: &'static str = "unpack_threads";
+    const LONG_NAME: &'static str = "unpack-threads";
+    const HELP: &'static str =
+        "Number of threads to use for checksumming and writing files unpacked from snapshot \
+         archives";
+
+    fn default() -> usize {
+        agave_snapshots::default_num_unpack_threads().get()
+    }
+}
@@ -0,0 +1,109 @@
+use {
+    crate::{
+        admin_rpc_service::{self, AdminRpcConfigChange},
+        commands::{Error, FromClapArgMatches, Result},
+    },
+    clap::{App, Arg, ArgMatches, SubCommand},
+    std::{fs, path::Path},
+};
+
+const COMMAND: &str = "apply-config";
+
+#[derive(Debug, PartialEq)]
+pub struct ApplyConfigArgs {
+    pub changes: Vec<AdminRpcConfigChange>,
+}
+
+impl FromClapArgMatches for ApplyConfigArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        let path = matches.value_of("path").expect("path is required");
+        let contents = fs::read_to_string(path)
+            .map_err(|err| Error::Dynamic(format!("unable to read {path}: {err}").into()))?;
+        let changes = serde_json::from_str(&contents)
+            .map_err(|err| Error::Dynamic(format!("unable to parse {path}: {err}").into()))?;
+        Ok(ApplyConfigArgs { changes })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Apply several runtime config changes at once, or none of them")
+        .arg(
+            Arg::with_name("path")
+                .value_name("PATH")
+                .takes_value(true)
+                .required(true)
+                .help("JSON file with the list of changes to apply"),
+        )
+        .after_help(
+            "Each change of the list sets one field, given by its \"field\": \
+             \"stakedNodesOverrides\" (with \"path\"), \"repairWhitelist\" (with \"whitelist\"), \
+             \"transactionHistory\" (with \"enableRpcTransactionHistory\" and \
+             \"enableExtendedTxMetadataStorage\") or \"rpcThreadPools\" (with \"rpcThreads\" and \
+             \"rpcBlockingThreads\"). The changes are checked one by one and together before any \
+             is applied. If one fails to apply, those applied before it are rolled back. The \
+             changes only apply to the currently running validator instance",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let ApplyConfigArgs { changes } = ApplyConfigArgs::from_clap_arg_match(matches)?;
+    let num_changes = changes.len();
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    admin_rpc_service::runtime()
+        .block_on(async move { admin_client.await?.apply_config_transaction(changes).await })?;
+    println!("Applied {num_changes} config changes");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Write, tempfile::NamedTempFile};
+
+    #[test]
+    fn verify_args_struct_by_command_apply_config() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"[
+                {{"field": "stakedNodesOverrides", "path": "overrides.yml"}},
+                {{"field": "rpcThreadPools", "rpcThreads": 8}},
+                {{
+                    "field": "transactionHistory",
+                    "enableRpcTransactionHistory": true,
+                    "enableExtendedTxMetadataStorage": false
+                }}
+            ]"#
+        )
+        .unwrap();
+        let matches = command().get_matches_from(vec![COMMAND, file.path().to_str().unwrap()]);
+        assert_eq!(
+            ApplyConfigArgs::from_clap_arg_match(&matches).unwrap(),
+            ApplyConfigArgs {
+                changes: vec![
+                    AdminRpcConfigChange::StakedNodesOverrides {
+                        path: "overrides.yml".to_string(),
+                    },
+                    AdminRpcConfigChange::RpcThreadPools {
+                        rpc_threads: Some(8),
+                        rpc_blocking_threads: None,
+                    },
+                    AdminRpcConfigChange::TransactionHistory {
+                        enable_rpc_transaction_history: true,
+                        enable_extended_tx_metadata_storage: false,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_config_unknown_field() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, r#"[{{"field": "identity", "path": "id.json"}}]"#).unwrap();
+        let matches = command().get_matches_from(vec![COMMAND, file.path().to_str().unwrap()]);
+        assert!(ApplyConfigArgs::from_clap_arg_match(&matches).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    std::path::Path,
+};
+
+const COMMAND: &str = "diagnostics-bundle";
+
+#[derive(Debug, PartialEq)]
+pub struct DiagnosticsBundleArgs {
+    pub log_lines: Option<usize>,
+}
+
+impl FromClapArgMatches for DiagnosticsBundleArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(DiagnosticsBundleArgs {
+            log_lines: value_t!(matches, "log_lines", usize).ok(),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Write a diagnostics bundle of the running validator to the ledger directory")
+        .arg(
+            Arg::with_name("log_lines")
+                .long("log-lines")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .help("Number of lines of the log file to include [default: 10000]"),
+        )
+        .after_help(
+            "The bundle is a tar archive of the threads of the validator, the metrics not \
+             submitted yet, the configuration, blockstore and accounts statistics and the end of \
+             the log file. The validator also writes a bundle when it receives SIGUSR2",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let DiagnosticsBundleArgs { log_lines } = DiagnosticsBundleArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let path = admin_rpc_service::runtime()
+        .block_on(async move { admin_client.await?.diagnostics_bundle(log_lines).await })?;
+    println!("{path}");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_args_struct_by_command_diagnostics_bundle() {
+        for (args, expected) in [
+            (vec![COMMAND], DiagnosticsBundleArgs { log_lines: None }),
+            (
+                vec![COMMAND, "--log-lines", "500"],
+                DiagnosticsBundleArgs {
+                    log_lines: Some(500),
+                },
+            ),
+        ] {
+            let matches = command().get_matches_from(args);
+            assert_eq!(
+                DiagnosticsBundleArgs::from_clap_arg_match(&matches).unwrap(),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,478 @@
+//! Host checks performed by `agave-validator doctor`
+//!
+//! Each check reads the relevant host setting and hands it to a side-effect free `evaluate_*`
+//! function that compares it against the recommended value.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+pub const CPU_FEATURES: &str = "cpu features";
+pub const CLOCK_SOURCE: &str = "clock source";
+pub const TIME_SYNC: &str = "time sync";
+pub const NOFILE_LIMIT: &str = "open file limit";
+pub const MAX_MAP_COUNT: &str = "vm.max_map_count";
+pub const DISK_THROUGHPUT: &str = "disk throughput";
+pub const NETWORK_MTU: &str = "network mtu";
+
+const RECOMMENDED_CLOCK_SOURCE: &str = "tsc";
+/// Largest clock error estimate the kernel may report before the check warns
+const MAX_TIME_ERROR: Duration = Duration::from_millis(100);
+// Should be kept in sync with the limit requested by `adjust_nofile_limit()`
+pub(crate) const RECOMMENDED_NOFILE_LIMIT: u64 = 1_000_000;
+pub(crate) const RECOMMENDED_MAX_MAP_COUNT: u64 = 1_000_000;
+const RECOMMENDED_DISK_WRITE_MIB_PER_SEC: f64 = 500.0;
+/// Smallest MTU that fits a full sized packet, plus IPv6 and UDP headers
+const MIN_MTU: u32 = 1280;
+
+const DISK_TEST_FILE_NAME: &str = "doctor-disk-throughput-test";
+const DISK_TEST_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        };
+        write!(f, "{status}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    pub(crate) fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn from_io_error(name: &'static str, err: io::Error) -> Self {
+        Self::new(name, CheckStatus::Warn, format!("unable to check: {err}"))
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.status, self.name, self.detail)
+    }
+}
+
+pub fn check_cpu_features() -> CheckResult {
+    #[cfg(target_arch = "x86_64")]
+    {
+        evaluate_cpu_features(
+            is_x86_feature_detected!("avx2"),
+            is_x86_feature_detected!("sha"),
+            is_x86_feature_detected!("avx512f"),
+        )
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        CheckResult::new(CPU_FEATURES, CheckStatus::Skip, "only checked on x86_64")
+    }
+}
+
+#[cfg_attr(not(target_arch = "x86_64"), allow(dead_code))]
+fn evaluate_cpu_features(avx2: bool, sha: bool, avx512f: bool) -> CheckResult {
+    let detected = [("avx2", avx2), ("sha", sha), ("avx512f", avx512f)]
+        .into_iter()
+        .filter_map(|(feature, detected)| detected.then_some(feature))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (status, note) = if !avx2 {
+        (CheckStatus::Fail, "avx2 is required")
+    } else if !sha {
+        (
+            CheckStatus::Warn,
+            "sha extensions are missing, PoH hashing will be slower",
+        )
+    } else {
+        (CheckStatus::Pass, "")
+    };
+    let detail = if note.is_empty() {
+        format!("detected [{detected}]")
+    } else {
+        format!("detected [{detected}], {note}")
+    };
+    CheckResult::new(CPU_FEATURES, status, detail)
+}
+
+pub fn check_clock_source() -> CheckResult {
+    if !cfg!(target_os = "linux") {
+        return CheckResult::new(CLOCK_SOURCE, CheckStatus::Skip, "only checked on linux");
+    }
+    match fs::read_to_string("/sys/devices/system/clocksource/clocksource0/current_clocksource") {
+        Ok(clock_source) => evaluate_clock_source(clock_source.trim()),
+        Err(err) => CheckResult::from_io_error(CLOCK_SOURCE, err),
+    }
+}
+
+fn evaluate_clock_source(clock_source: &str) -> CheckResult {
+    if clock_source == RECOMMENDED_CLOCK_SOURCE {
+        CheckResult::new(CLOCK_SOURCE, CheckStatus::Pass, clock_source)
+    } else {
+        CheckResult::new(
+            CLOCK_SOURCE,
+            CheckStatus::Warn,
+            format!("{clock_source} (recommended: {RECOMMENDED_CLOCK_SOURCE})"),
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn check_time_sync() -> CheckResult {
+    // SAFETY: timex is plain old data, for which all zeroes is a valid value
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+    // SAFETY: timex is a valid, exclusively borrowed timex, and modes == 0 makes adjtimex()
+    // read-only
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    if state == -1 {
+        return CheckResult::from_io_error(TIME_SYNC, io::Error::last_os_error());
+    }
+    let max_error = Duration::from_micros(u64::try_from(timex.maxerror).unwrap_or(u64::MAX));
+    evaluate_time_sync(state != libc::TIME_ERROR, max_error)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn check_time_sync() -> CheckResult {
+    CheckResult::new(TIME_SYNC, CheckStatus::Skip, "only checked on linux")
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn evaluate_time_sync(synchronized: bool, max_error: Duration) -> CheckResult {
+    if !synchronized {
+        CheckResult::new(
+            TIME_SYNC,
+            CheckStatus::Fail,
+            "system clock is not synchronized, is an NTP daemon running?",
+        )
+    } else if max_error > MAX_TIME_ERROR {
+        CheckResult::new(
+            TIME_SYNC,
+            CheckStatus::Warn,
+            format!(
+                "synchronized, but max error is {max_error:?} (recommended: <= \
+                 {MAX_TIME_ERROR:?})"
+            ),
+        )
+    } else {
+        CheckResult::new(
+            TIME_SYNC,
+            CheckStatus::Pass,
+            format!("synchronized, max error {max_error:?}"),
+        )
+    }
+}
+
+#[cfg(unix)]
+pub fn check_nofile_limit() -> CheckResult {
+    let mut nofile = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: nofile is a valid, exclusively borrowed rlimit for getrlimit() to fill in
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut nofile) } != 0 {
+        return CheckResult::from_io_error(NOFILE_LIMIT, io::Error::last_os_error());
+    }
+    // rlim_t is not u64 on every platform
+    #[allow(clippy::useless_conversion)]
+    let hard_limit = u64::from(nofile.rlim_max);
+    evaluate_nofile_limit(hard_limit)
+}
+
+#[cfg(not(unix))]
+pub fn check_nofile_limit() -> CheckResult {
+    CheckResult::new(NOFILE_LIMIT, CheckStatus::Skip, "only checked on unix")
+}
+
+#[cfg_attr(not(unix), allow(dead_code))]
+fn evaluate_nofile_limit(hard_limit: u64) -> CheckResult {
+    if hard_limit >= RECOMMENDED_NOFILE_LIMIT {
+        CheckResult::new(
+            NOFILE_LIMIT,
+            CheckStatus::Pass,
+            format!("hard limit {hard_limit}"),
+        )
+    } else {
+        CheckResult::new(
+            NOFILE_LIMIT,
+            CheckStatus::Fail,
+            format!("hard limit {hard_limit} (recommended: >= {RECOMMENDED_NOFILE_LIMIT})"),
+        )
+    }
+}
+
+pub fn check_max_map_count() -> CheckResult {
+    if !cfg!(target_os = "linux") {
+        return CheckResult::new(MAX_MAP_COUNT, CheckStatus::Skip, "only checked on linux");
+    }
+    fs::read_to_string("/proc/sys/vm/max_map_count")
+        .and_then(|value| value.trim().parse().map_err(io::Error::other))
+        .map_or_else(
+            |err| CheckResult::from_io_error(MAX_MAP_COUNT, err),
+            evaluate_max_map_count,
+        )
+}
+
+fn evaluate_max_map_count(max_map_count: u64) -> CheckResult {
+    if max_map_count >= RECOMMENDED_MAX_MAP_COUNT {
+        CheckResult::new(MAX_MAP_COUNT, CheckStatus::Pass, max_map_count.to_string())
+    } else {
+        CheckResult::new(
+            MAX_MAP_COUNT,
+            CheckStatus::Fail,
+            format!("{max_map_count} (recommended: >= {RECOMMENDED_MAX_MAP_COUNT})"),
+        )
+    }
+}
+
+/// Measures sequential write throughput by writing and syncing `size` bytes below `ledger_path`
+///
+/// The ledger directory may not exist yet on first boot, in which case the closest existing
+/// ancestor is used since it is on the same file system the ledger will most likely be on.
+pub fn check_disk_throughput(ledger_path: &Path, size: usize) -> CheckResult {
+    let Some(test_dir) = ledger_path.ancestors().find(|path| path.is_dir()) else {
+        return CheckResult::new(
+            DISK_THROUGHPUT,
+            CheckStatus::Warn,
+            format!(
+                "unable to find a directory to test at {}",
+                ledger_path.display()
+            ),
+        );
+    };
+    let test_file = test_dir.join(DISK_TEST_FILE_NAME);
+    let result = write_test_file(&test_file, size);
+    let _ = fs::remove_file(&test_file);
+    match result {
+        Ok(elapsed) => evaluate_disk_throughput(test_dir, size, elapsed),
+        Err(err) => CheckResult::from_io_error(DISK_THROUGHPUT, err),
+    }
+}
+
+fn write_test_file(path: &Path, size: usize) -> io::Result<Duration> {
+    let chunk = vec![0xa5u8; DISK_TEST_CHUNK_SIZE];
+    let mut file = fs::File::create(path)?;
+    let start = Instant::now();
+    let mut remaining = size;
+    while remaining > 0 {
+        let len = remaining.min(chunk.len());
+        file.write_all(&chunk[..len])?;
+        remaining -= len;
+    }
+    file.sync_all()?;
+    Ok(start.elapsed())
+}
+
+fn evaluate_disk_throughput(test_dir: &Path, size: usize, elapsed: Duration) -> CheckResult {
+    let mib_per_sec = size as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64().max(f64::EPSILON);
+    let detail = format!(
+        "{mib_per_sec:.0} MiB/s sequential write at {}",
+        test_dir.display()
+    );
+    if mib_per_sec >= RECOMMENDED_DISK_WRITE_MIB_PER_SEC {
+        CheckResult::new(DISK_THROUGHPUT, CheckStatus::Pass, detail)
+    } else {
+        CheckResult::new(
+            DISK_THROUGHPUT,
+            CheckStatus::Warn,
+            format!("{detail} (recommended: >= {RECOMMENDED_DISK_WRITE_MIB_PER_SEC:.0} MiB/s)"),
+        )
+    }
+}
+
+pub fn check_network_mtu() -> CheckResult {
+    if !cfg!(target_os = "linux") {
+        return CheckResult::new(NETWORK_MTU, CheckStatus::Skip, "only checked on linux");
+    }
+    match read_interface_mtus(Path::new("/sys/class/net")) {
+        Ok(mtus) => evaluate_network_mtu(&mtus),
+        Err(err) => CheckResult::from_io_error(NETWORK_MTU, err),
+    }
+}
+
+/// Returns the name and MTU of every non-loopback interface that is up
+fn read_interface_mtus(sys_class_net: &Path) -> io::Result<Vec<(String, u32)>> {
+    let mut mtus = vec![];
+    for entry in fs::read_dir(sys_class_net)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let is_up = fs::read_to_string(path.join("operstate"))
+            .is_ok_and(|operstate| operstate.trim() == "up");
+        if name == "lo" || !is_up {
+            continue;
+        }
+        let mtu = fs::read_to_string(path.join("mtu"))?
+            .trim()
+            .parse()
+            .map_err(io::Error::other)?;
+        mtus.push((name.to_string(), mtu));
+    }
+    mtus.sort();
+    Ok(mtus)
+}
+
+fn evaluate_network_mtu(mtus: &[(String, u32)]) -> CheckResult {
+    if mtus.is_empty() {
+        return CheckResult::new(
+            NETWORK_MTU,
+            CheckStatus::Warn,
+            "no network interfaces are up",
+        );
+    }
+    let detail = mtus
+        .iter()
+        .map(|(name, mtu)| format!("{name}={mtu}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if mtus.iter().all(|(_, mtu)| *mtu >= MIN_MTU) {
+        CheckResult::new(NETWORK_MTU, CheckStatus::Pass, detail)
+    } else {
+        CheckResult::new(
+            NETWORK_MTU,
+            CheckStatus::Fail,
+            format!("{detail} (required: >= {MIN_MTU})"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_cpu_features() {
+        assert_eq!(
+            evaluate_cpu_features(true, true, true),
+            CheckResult::new(
+                CPU_FEATURES,
+                CheckStatus::Pass,
+                "detected [avx2 sha avx512f]"
+            ),
+        );
+        assert_eq!(
+            evaluate_cpu_features(true, false, false).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            evaluate_cpu_features(false, true, true).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_evaluate_clock_source() {
+        assert_eq!(evaluate_clock_source("tsc").status, CheckStatus::Pass);
+        assert_eq!(evaluate_clock_source("hpet").status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_evaluate_time_sync() {
+        assert_eq!(
+            evaluate_time_sync(true, Duration::from_millis(1)).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            evaluate_time_sync(true, Duration::from_secs(1)).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            evaluate_time_sync(false, Duration::from_millis(1)).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_evaluate_limits() {
+        assert_eq!(
+            evaluate_nofile_limit(RECOMMENDED_NOFILE_LIMIT).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(evaluate_nofile_limit(1024).status, CheckStatus::Fail);
+        assert_eq!(
+            evaluate_max_map_count(RECOMMENDED_MAX_MAP_COUNT).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(evaluate_max_map_count(65530).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_evaluate_disk_throughput() {
+        let size = 1024 * 1024 * 1024;
+        assert_eq!(
+            evaluate_disk_throughput(Path::new("/"), size, Duration::from_secs(1)).status,
+            CheckStatus::Pass
+        );
+        assert_eq!(
+            evaluate_disk_throughput(Path::new("/"), size, Duration::from_secs(10)).status,
+            CheckStatus::Warn
+        );
+    }
+
+    #[test]
+    fn test_evaluate_network_mtu() {
+        assert_eq!(evaluate_network_mtu(&[]).status, CheckStatus::Warn);
+        assert_eq!(
+            evaluate_network_mtu(&[("eth0".to_string(), 1500)]),
+            CheckResult::new(NETWORK_MTU, CheckStatus::Pass, "eth0=1500"),
+        );
+        assert_eq!(
+            evaluate_network_mtu(&[("eth0".to_string(), 1500), ("wg0".to_string(), 1200)]).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_read_interface_mtus() {
+        let sys_class_net = tempfile::tempdir().unwrap();
+        for (name, operstate, mtu) in [
+            ("lo", "unknown", "65536"),
+            ("eth0", "up", "9000"),
+            ("eth1", "down", "1500"),
+        ] {
+            let dir = sys_class_net.path().join(name);
+            fs::create_dir(&dir).unwrap();
+            fs::write(dir.join("operstate"), format!("{operstate}\n")).unwrap();
+            fs::write(dir.join("mtu"), format!("{mtu}\n")).unwrap();
+        }
+        assert_eq!(
+            read_interface_mtus(sys_class_net.path()).unwrap(),
+            vec![("eth0".to_string(), 9000)],
+        );
+    }
+
+    #[test]
+    fn test_check_disk_throughput_removes_test_file() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger_path = ledger_dir.path().join("not-created-yet");
+        let result = check_disk_throughput(&ledger_path, 1024 * 1024);
+        assert_ne!(result.status, CheckStatus::Skip);
+        assert!(result
+            .detail
+            .contains(&ledger_dir.path().display().to_string()));
+        assert!(!ledger_dir.path().join(DISK_TEST_FILE_NAME).exists());
+    }
+}
@@ -0,0 +1,172 @@
+use {
+    crate::commands::{Error, FromClapArgMatches, Result},
+    checks::{CheckResult, CheckStatus},
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    log::*,
+    solana_clap_utils::input_validators::is_parsable,
+    solana_metrics::datapoint_info,
+    std::path::Path,
+};
+
+pub(crate) mod checks;
+
+const COMMAND: &str = "doctor";
+
+const DEFAULT_DISK_TEST_SIZE_MB: &str = "1024";
+
+#[derive(Debug, PartialEq)]
+pub struct DoctorArgs {
+    pub skip_disk_throughput_test: bool,
+    pub disk_test_size_mb: usize,
+}
+
+impl FromClapArgMatches for DoctorArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(DoctorArgs {
+            skip_disk_throughput_test: matches.is_present("skip_disk_throughput_test"),
+            disk_test_size_mb: value_t!(matches, "disk_test_size_mb", usize)?,
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Check the host hardware and OS configuration against recommended values")
+        .arg(
+            Arg::with_name("skip_disk_throughput_test")
+                .long("skip-disk-throughput-test")
+                .takes_value(false)
+                .help("Skip measuring write throughput of the ledger disk"),
+        )
+        .arg(
+            Arg::with_name("disk_test_size_mb")
+                .long("disk-test-size-mb")
+                .takes_value(true)
+                .value_name("MEGABYTES")
+                .validator(is_parsable::<usize>)
+                .default_value(DEFAULT_DISK_TEST_SIZE_MB)
+                .help("Amount of data to write when measuring ledger disk throughput"),
+        )
+        .after_help(
+            "An abbreviated version of these checks, without the disk throughput test, is also \
+             run every time the validator starts",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let doctor_args = DoctorArgs::from_clap_arg_match(matches)?;
+
+    let mut results = run_checks();
+    results.push(if doctor_args.skip_disk_throughput_test {
+        CheckResult {
+            name: checks::DISK_THROUGHPUT,
+            status: CheckStatus::Skip,
+            detail: "skipped by --skip-disk-throughput-test".to_string(),
+        }
+    } else {
+        checks::check_disk_throughput(
+            ledger_path,
+            doctor_args.disk_test_size_mb.saturating_mul(1024 * 1024),
+        )
+    });
+
+    for result in &results {
+        println!("{result}");
+    }
+    let num_failed = count_status(&results, CheckStatus::Fail);
+    let num_warned = count_status(&results, CheckStatus::Warn);
+    println!(
+        "\n{} checks: {} passed, {num_warned} warnings, {num_failed} failed",
+        results.len(),
+        count_status(&results, CheckStatus::Pass),
+    );
+
+    if num_failed > 0 {
+        return Err(Error::Dynamic(
+            format!(
+                "{num_failed} checks failed. See \
+                 https://docs.anza.xyz/operations/guides/validator-start#system-tuning"
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Runs the checks that are quick enough to be performed on every startup, and reports any that
+/// did not pass to the log and metrics. Unlike the `doctor` command, failures do not abort.
+pub fn report_startup_checks() {
+    for result in run_checks() {
+        match result.status {
+            CheckStatus::Pass | CheckStatus::Skip => info!("Host check {result}"),
+            CheckStatus::Warn | CheckStatus::Fail => warn!("Host check {result}"),
+        }
+        datapoint_info!(
+            "validator-host-check",
+            ("check", result.name, String),
+            ("status", result.status.to_string(), String),
+            ("detail", result.detail, String),
+        );
+    }
+}
+
+/// Runs all checks that do not touch the disk
+fn run_checks() -> Vec<CheckResult> {
+    vec![
+        checks::check_cpu_features(),
+        checks::check_clock_source(),
+        checks::check_time_sync(),
+        checks::check_nofile_limit(),
+        checks::check_max_map_count(),
+        checks::check_network_mtu(),
+    ]
+}
+
+fn count_status(results: &[CheckResult], status: CheckStatus) -> usize {
+    results
+        .iter()
+        .filter(|result| result.status == status)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    impl Default for DoctorArgs {
+        fn default() -> Self {
+            DoctorArgs {
+                skip_disk_throughput_test: false,
+                disk_test_size_mb: DEFAULT_DISK_TEST_SIZE_MB
+                    .parse()
+                    .expect("invalid DEFAULT_DISK_TEST_SIZE_MB"),
+            }
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_doctor_default() {
+        verify_args_struct_by_command(command(), vec![COMMAND], DoctorArgs::default());
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_doctor_with_disk_test_args() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--skip-disk-throughput-test"],
+            DoctorArgs {
+                skip_disk_throughput_test: true,
+                ..DoctorArgs::default()
+            },
+        );
+
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--disk-test-size-mb", "64"],
+            DoctorArgs {
+                disk_test_size_mb: 64,
+                ..DoctorArgs::default()
+            },
+        );
+    }
+}
@@ -188,7 +188,7 @@ pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
 }
 
 #[cfg(target_os = "linux")]
-fn poll_until_pid_terminates(pid: u32) -> Result<()> {
+pub(crate) fn poll_until_pid_terminates(pid: u32) -> Result<()> {
     let pid = i32::try_from(pid)?;
 
     println!("Waiting for agave-validator process {pid} to terminate");
@@ -237,7 +237,7 @@ fn poll_until_pid_terminates(pid: u32) -> Result<()> {
 }
 
 #[cfg(not(target_os = "linux"))]
-fn poll_until_pid_terminates(_pid: u32) -> Result<()> {
+pub(crate) fn poll_until_pid_terminates(_pid: u32) -> Result<()> {
     Err(Error::Dynamic(
         "Unable to wait for agave-validator process termination on this platform".into(),
     ))
@@ -0,0 +1,398 @@
+use {
+    crate::{
+        cli::{app, DefaultArgs},
+        commands::{doctor::checks, Error, FromClapArgMatches, Result},
+        fleet_defaults,
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_net_utils::PortRange,
+    std::{
+        ffi::OsString,
+        fmt::Write,
+        fs, iter,
+        path::{Path, PathBuf},
+    },
+};
+
+const COMMAND: &str = "generate-service";
+
+const DEFAULT_USER: &str = "sol";
+const DEFAULT_SERVICE_NAME: &str = "agave-validator";
+/// Locked memory the validator needs for io_uring
+const MEMLOCK_LIMIT: u64 = 2_000_000_000;
+/// Socket buffer size recommended for the validator's UDP traffic
+const RECOMMENDED_SOCKET_BUFFER_SIZE: u64 = 134_217_728;
+const LOG_ROTATE_COUNT: usize = 7;
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateServiceArgs {
+    /// Validator arguments file, in the format of the fleet defaults
+    pub config: PathBuf,
+    pub user: String,
+    pub service_name: String,
+    pub validator_binary: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+}
+
+impl FromClapArgMatches for GenerateServiceArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(GenerateServiceArgs {
+            config: value_t!(matches, "config", PathBuf)?,
+            user: value_t!(matches, "user", String)?,
+            service_name: value_t!(matches, "service_name", String)?,
+            validator_binary: matches.value_of("validator_binary").map(PathBuf::from),
+            output_dir: matches.value_of("output_dir").map(PathBuf::from),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Generate a systemd unit, logrotate config and sysctl settings for the validator")
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("FILE")
+                .takes_value(true)
+                .required(true)
+                .help(
+                    "Validator arguments, in the TOML format of --fleet-defaults. The service \
+                     starts the validator with --fleet-defaults FILE",
+                ),
+        )
+        .arg(
+            Arg::with_name("user")
+                .long("user")
+                .value_name("USER")
+                .takes_value(true)
+                .default_value(DEFAULT_USER)
+                .help("User the validator runs as"),
+        )
+        .arg(
+            Arg::with_name("service_name")
+                .long("service-name")
+                .value_name("NAME")
+                .takes_value(true)
+                .default_value(DEFAULT_SERVICE_NAME)
+                .help("Name of the systemd service"),
+        )
+        .arg(
+            Arg::with_name("validator_binary")
+                .long("validator-binary")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Path of the agave-validator binary [default: this binary]"),
+        )
+        .arg(
+            Arg::with_name("output_dir")
+                .long("output-dir")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Write the files to DIR instead of printing them"),
+        )
+        .after_help(
+            "The files are derived from the effective configuration: the defaults of the \
+             validator with the arguments from --config applied. Review them before installing",
+        )
+}
+
+pub fn execute(matches: &ArgMatches) -> Result<()> {
+    let args = GenerateServiceArgs::from_clap_arg_match(matches)?;
+
+    let config = fs::canonicalize(&args.config)?;
+    let validator_args =
+        fleet_defaults::load_fleet_args(&config).map_err(|err| Error::Dynamic(Box::from(err)))?;
+    let default_args = DefaultArgs::new();
+    let validator_matches = app(solana_version::version!(), &default_args).get_matches_from_safe(
+        iter::once(OsString::from("agave-validator")).chain(validator_args),
+    )?;
+    let validator_binary = match args.validator_binary {
+        Some(validator_binary) => validator_binary,
+        None => std::env::current_exe()?,
+    };
+    let service = ServiceConfig::new(&validator_matches, config, validator_binary, &args)?;
+
+    let files = [
+        (
+            format!("{}.service", args.service_name),
+            Some(service.unit()),
+        ),
+        (
+            format!("{}.logrotate", args.service_name),
+            service.logrotate(),
+        ),
+        (format!("21-{}.conf", args.service_name), Some(sysctl())),
+    ];
+    for (name, contents) in files {
+        let Some(contents) = contents else {
+            continue;
+        };
+        match &args.output_dir {
+            Some(output_dir) => {
+                let path = output_dir.join(&name);
+                fs::write(&path, contents)?;
+                println!("Wrote {}", path.display());
+            }
+            None => println!("### {name}\n{contents}"),
+        }
+    }
+    println!(
+        "Install {0}.service in /etc/systemd/system, {0}.logrotate as \
+         /etc/logrotate.d/{0} and 21-{0}.conf in /etc/sysctl.d",
+        args.service_name
+    );
+
+    Ok(())
+}
+
+/// Settings of the validator that the generated files depend on
+#[derive(Debug)]
+struct ServiceConfig {
+    config: PathBuf,
+    validator_binary: PathBuf,
+    user: String,
+    service_name: String,
+    /// Directories that must be mounted before the validator starts
+    data_paths: Vec<PathBuf>,
+    log_path: Option<PathBuf>,
+    gossip_port: Option<u16>,
+    dynamic_port_range: PortRange,
+    rpc_port: Option<u16>,
+    private_rpc: bool,
+}
+
+impl ServiceConfig {
+    fn new(
+        matches: &ArgMatches,
+        config: PathBuf,
+        validator_binary: PathBuf,
+        args: &GenerateServiceArgs,
+    ) -> Result<Self> {
+        let ledger_path = value_t!(matches, "ledger_path", PathBuf)?;
+        let data_paths: Vec<_> = iter::once(ledger_path)
+            .chain(
+                [
+                    "account_paths",
+                    "snapshots",
+                    "incremental_snapshot_archive_path",
+                ]
+                .into_iter()
+                .filter_map(|name| matches.values_of(name))
+                .flatten()
+                .flat_map(|paths| paths.split(','))
+                .map(PathBuf::from),
+            )
+            .collect();
+        // The working directory of a service is `/`, which the default ledger path of the
+        // command line is relative to
+        if let Some(path) = data_paths.iter().find(|path| path.is_relative()) {
+            return Err(Error::Dynamic(Box::from(format!(
+                "{} is a relative path; set the ledger, accounts and snapshot paths to absolute \
+                 paths in the config",
+                path.display()
+            ))));
+        }
+        // Without `--log`, the validator logs to a file in its working directory
+        let log_path = match matches.value_of("logfile") {
+            Some("-") => None,
+            Some(log) if Path::new(log).is_absolute() => Some(PathBuf::from(log)),
+            _ => {
+                return Err(Error::Dynamic(Box::from(
+                    "set `log` in the config to an absolute path, or to \"-\" to log to the \
+                     journal",
+                )))
+            }
+        };
+        let dynamic_port_range = matches
+            .value_of("dynamic_port_range")
+            .and_then(solana_net_utils::parse_port_range)
+            .expect("dynamic_port_range has a validated default");
+
+        Ok(Self {
+            config,
+            validator_binary,
+            user: args.user.clone(),
+            service_name: args.service_name.clone(),
+            data_paths,
+            log_path,
+            gossip_port: value_t!(matches, "gossip_port", u16).ok(),
+            dynamic_port_range,
+            rpc_port: value_t!(matches, "rpc_port", u16).ok(),
+            private_rpc: matches.is_present("private_rpc"),
+        })
+    }
+
+    fn unit(&self) -> String {
+        let mut unit = format!(
+            "# Generated by `agave-validator {COMMAND}` from {}\n#\n# Ports to allow through \
+             the firewall:\n",
+            self.config.display()
+        );
+        match self.gossip_port {
+            Some(gossip_port) => {
+                writeln!(unit, "#   {gossip_port}: gossip, TCP and UDP").unwrap();
+            }
+            None => writeln!(unit, "#   gossip is bound in the dynamic port range").unwrap(),
+        }
+        writeln!(
+            unit,
+            "#   {}-{}: dynamic port range, TCP and UDP",
+            self.dynamic_port_range.0, self.dynamic_port_range.1
+        )
+        .unwrap();
+        if let Some(rpc_port) = self.rpc_port.filter(|_| !self.private_rpc) {
+            writeln!(
+                unit,
+                "#   {rpc_port}-{}: JSON RPC and PubSub, TCP, if clients connect directly",
+                rpc_port.saturating_add(1)
+            )
+            .unwrap();
+        }
+        write!(
+            unit,
+            "\n[Unit]\nDescription=Agave validator\nAfter=network-online.target\n\
+             Wants=network-online.target\nStartLimitIntervalSec=0\nRequiresMountsFor={}\n\n\
+             [Service]\nType=simple\nUser={}\nRestart=always\nRestartSec=1\nLimitNOFILE={}\n\
+             LimitMEMLOCK={MEMLOCK_LIMIT}\nLogRateLimitIntervalSec=0\n\
+             ExecStart={} --{} {}\n\n[Install]\nWantedBy=multi-user.target\n",
+            self.data_paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            self.user,
+            checks::RECOMMENDED_NOFILE_LIMIT,
+            self.validator_binary.display(),
+            fleet_defaults::FLEET_DEFAULTS_ARG,
+            self.config.display(),
+        )
+        .unwrap();
+        unit
+    }
+
+    /// Returns the logrotate config, unless the validator logs to standard error, which the
+    /// journal collects
+    fn logrotate(&self) -> Option<String> {
+        let log_path = self.log_path.as_ref()?;
+        Some(format!(
+            "{} {{\n  su {1} {1}\n  daily\n  rotate {LOG_ROTATE_COUNT}\n  missingok\n  \
+             postrotate\n    systemctl kill -s USR1 {2}.service\n  endscript\n}}\n",
+            log_path.display(),
+            self.user,
+            self.service_name,
+        ))
+    }
+}
+
+fn sysctl() -> String {
+    format!(
+        "# Recommended kernel settings for the validator\n\n# UDP socket buffers\n\
+         net.core.rmem_default = {RECOMMENDED_SOCKET_BUFFER_SIZE}\n\
+         net.core.rmem_max = {RECOMMENDED_SOCKET_BUFFER_SIZE}\n\
+         net.core.wmem_default = {RECOMMENDED_SOCKET_BUFFER_SIZE}\n\
+         net.core.wmem_max = {RECOMMENDED_SOCKET_BUFFER_SIZE}\n\n# Memory mapped account \
+         files\nvm.max_map_count = {}\n\n# Open files\nfs.nr_open = {}\n",
+        checks::RECOMMENDED_MAX_MAP_COUNT,
+        checks::RECOMMENDED_NOFILE_LIMIT,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    fn service_config(validator_args: &[&str]) -> Result<ServiceConfig> {
+        let default_args = DefaultArgs::new();
+        let matches = app("test", &default_args)
+            .get_matches_from_safe(iter::once("agave-validator").chain(validator_args.to_vec()))
+            .unwrap();
+        let args = GenerateServiceArgs {
+            config: PathBuf::from("/etc/agave/validator.toml"),
+            user: DEFAULT_USER.to_string(),
+            service_name: DEFAULT_SERVICE_NAME.to_string(),
+            validator_binary: None,
+            output_dir: None,
+        };
+        ServiceConfig::new(
+            &matches,
+            args.config.clone(),
+            PathBuf::from("/usr/local/bin/agave-validator"),
+            &args,
+        )
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_generate_service_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--config", "validator.toml"],
+            GenerateServiceArgs {
+                config: PathBuf::from("validator.toml"),
+                user: DEFAULT_USER.to_string(),
+                service_name: DEFAULT_SERVICE_NAME.to_string(),
+                validator_binary: None,
+                output_dir: None,
+            },
+        );
+    }
+
+    #[test]
+    fn test_service_files() {
+        let service = service_config(&[
+            "--ledger",
+            "/mnt/ledger",
+            "--accounts",
+            "/mnt/accounts",
+            "--log",
+            "/home/sol/validator.log",
+            "--gossip-port",
+            "8001",
+            "--dynamic-port-range",
+            "8002-8020",
+            "--rpc-port",
+            "8899",
+        ])
+        .unwrap();
+
+        let unit = service.unit();
+        assert!(unit.contains("#   8001: gossip, TCP and UDP\n"));
+        assert!(unit.contains("#   8002-8020: dynamic port range, TCP and UDP\n"));
+        assert!(unit.contains("#   8899-8900: JSON RPC and PubSub"));
+        assert!(unit.contains("RequiresMountsFor=/mnt/ledger /mnt/accounts\n"));
+        assert!(unit.contains("User=sol\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/local/bin/agave-validator --fleet-defaults \
+             /etc/agave/validator.toml\n"
+        ));
+
+        let logrotate = service.logrotate().unwrap();
+        assert!(logrotate.starts_with("/home/sol/validator.log {\n"));
+        assert!(logrotate.contains("systemctl kill -s USR1 agave-validator.service\n"));
+
+        // Logs on standard error go to the journal
+        let service = service_config(&["--ledger", "/mnt/ledger", "--log", "-"]).unwrap();
+        assert_eq!(service.logrotate(), None);
+        assert!(service
+            .unit()
+            .contains("#   gossip is bound in the dynamic port range\n"));
+    }
+
+    #[test]
+    fn test_service_relative_paths() {
+        // The default ledger path is relative
+        assert!(service_config(&["--log", "-"]).is_err());
+        assert!(service_config(&[
+            "--ledger",
+            "/mnt/ledger",
+            "--snapshots",
+            "snapshots",
+            "--log",
+            "-"
+        ])
+        .is_err());
+
+        // So is the default log file
+        assert!(service_config(&["--ledger", "/mnt/ledger"]).is_err());
+        assert!(service_config(&["--ledger", "/mnt/ledger", "--log", "validator.log"]).is_err());
+    }
+}
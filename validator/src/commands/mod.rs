@@ -1,17 +1,31 @@
+pub mod apply_config;
 pub mod authorized_voter;
 pub mod contact_info;
+pub mod diagnostics_bundle;
+pub mod doctor;
 pub mod exit;
+pub mod generate_service;
 pub mod manage_block_production;
 pub mod monitor;
 pub mod plugin;
+pub mod poh_timing;
+pub mod profile_cpu;
 pub mod repair_shred_from_peer;
 pub mod repair_whitelist;
+pub mod restart;
+pub mod rpc_follower;
+pub mod rpc_threads;
+pub mod rpc_usage;
 pub mod run;
+pub mod self_test;
 pub mod set_identity;
 pub mod set_log_filter;
 pub mod set_public_address;
 pub mod staked_nodes_overrides;
+pub mod transaction_history;
 pub mod wait_for_restart_window;
+pub mod wen_restart_status;
+pub mod write_lock_contention;
 
 use thiserror::Error;
 
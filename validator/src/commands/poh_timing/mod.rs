@@ -0,0 +1,81 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{App, Arg, ArgMatches, SubCommand},
+    solana_cli_output::OutputFormat,
+    std::path::Path,
+};
+
+const COMMAND: &str = "poh-timing";
+
+#[derive(Debug, PartialEq)]
+pub struct PohTimingArgs {
+    pub output: OutputFormat,
+}
+
+impl FromClapArgMatches for PohTimingArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(PohTimingArgs {
+            output: OutputFormat::from_matches(matches, "output", false),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Display the recent tick timing and hash rate of PoH")
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+        .after_help(
+            "A PoH core that slows down first sustains fewer hashes per second, and then makes \
+             slots take longer than their target. The alert thresholds are set with \
+             --poh-alert-max-slot-drift-ms and --poh-alert-min-hash-rate",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let poh_timing_args = PohTimingArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let poh_timing = admin_rpc_service::runtime()
+        .block_on(async move { admin_client.await?.poh_timing().await })?;
+
+    println!("{}", poh_timing_args.output.formatted_string(&poh_timing));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    #[test]
+    fn verify_args_struct_by_command_poh_timing_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND],
+            PohTimingArgs {
+                output: OutputFormat::Display,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_poh_timing_with_output() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--output", "json"],
+            PohTimingArgs {
+                output: OutputFormat::Json,
+            },
+        );
+    }
+}
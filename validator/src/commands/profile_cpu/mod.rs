@@ -0,0 +1,159 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+        cpu_profiler::{CpuProfile, DEFAULT_PROFILE_FREQUENCY},
+    },
+    clap::{value_t, value_t_or_exit, values_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    std::{fs, path::Path},
+};
+
+const COMMAND: &str = "profile-cpu";
+const DEFAULT_PROFILE_SLOTS: &str = "25";
+
+#[derive(Debug, PartialEq)]
+pub struct ProfileCpuArgs {
+    pub thread_names: Vec<String>,
+    pub num_slots: u64,
+    pub frequency: Option<u64>,
+    pub output: Option<String>,
+}
+
+impl FromClapArgMatches for ProfileCpuArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(ProfileCpuArgs {
+            thread_names: values_t!(matches, "thread", String).unwrap_or_default(),
+            num_slots: value_t_or_exit!(matches, "slots", u64),
+            frequency: value_t!(matches, "frequency", u64).ok(),
+            output: value_t!(matches, "output", String).ok(),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Sample the threads of the running validator and print their stacks")
+        .arg(
+            Arg::with_name("thread")
+                .long("thread")
+                .takes_value(true)
+                .value_name("NAME")
+                .multiple(true)
+                .number_of_values(1)
+                .help(
+                    "Sample the threads whose names start with NAME, such as solReplay. May be \
+                     specified multiple times [default: all threads]",
+                ),
+        )
+        .arg(
+            Arg::with_name("slots")
+                .long("slots")
+                .takes_value(true)
+                .value_name("SLOTS")
+                .default_value(DEFAULT_PROFILE_SLOTS)
+                .validator(is_parsable::<u64>)
+                .help("Sample for this many slots' worth of time"),
+        )
+        .arg(
+            Arg::with_name("frequency")
+                .long("frequency")
+                .takes_value(true)
+                .value_name("HZ")
+                .validator(is_parsable::<u64>)
+                .help("Samples per second of CPU time of each thread [default: 99]"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Write the stacks to FILE instead of stdout"),
+        )
+        .after_help(
+            "The stacks are printed in the collapsed format, one line per distinct stack, which \
+             flamegraph.pl or inferno-flamegraph turn into a flame graph. The stacks are only \
+             complete if the validator was built with RUSTFLAGS=\"-C \
+             force-frame-pointers=yes\". Sampling needs /proc/sys/kernel/perf_event_paranoid to \
+             be 2 or lower, or CAP_PERFMON",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let ProfileCpuArgs {
+        thread_names,
+        num_slots,
+        frequency,
+        output,
+    } = ProfileCpuArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let frequency = frequency.unwrap_or(DEFAULT_PROFILE_FREQUENCY);
+    let CpuProfile {
+        threads,
+        samples,
+        lost_samples,
+        collapsed_stacks,
+    } = admin_rpc_service::runtime().block_on(async move {
+        admin_client
+            .await?
+            .profile_cpu(thread_names, num_slots, Some(frequency))
+            .await
+    })?;
+
+    match output {
+        Some(output) => fs::write(output, collapsed_stacks)?,
+        None => print!("{collapsed_stacks}"),
+    }
+    eprintln!("{samples} samples of {threads} threads, {lost_samples} lost, at {frequency} Hz");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_args_struct_by_command_profile_cpu() {
+        for (args, expected) in [
+            (
+                vec![COMMAND],
+                ProfileCpuArgs {
+                    thread_names: vec![],
+                    num_slots: 25,
+                    frequency: None,
+                    output: None,
+                },
+            ),
+            (
+                vec![
+                    COMMAND,
+                    "--thread",
+                    "solReplay",
+                    "--thread",
+                    "solBanknStgTx",
+                    "--slots",
+                    "10",
+                    "--frequency",
+                    "499",
+                    "--output",
+                    "stacks.txt",
+                ],
+                ProfileCpuArgs {
+                    thread_names: vec!["solReplay".to_string(), "solBanknStgTx".to_string()],
+                    num_slots: 10,
+                    frequency: Some(499),
+                    output: Some("stacks.txt".to_string()),
+                },
+            ),
+        ] {
+            let matches = command().get_matches_from(args);
+            assert_eq!(
+                ProfileCpuArgs::from_clap_arg_match(&matches).unwrap(),
+                expected
+            );
+        }
+    }
+}
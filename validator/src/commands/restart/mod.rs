@@ -0,0 +1,233 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{exit, Error, FromClapArgMatches, Result},
+        new_spinner_progress_bar,
+        restart_window::{RestartProgress, RestartStatus, RestartWindowConfig},
+    },
+    clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::{is_parsable, is_valid_percentage},
+    std::{path::Path, time::Duration},
+};
+
+const COMMAND: &str = "restart";
+
+/// About 10 minutes
+const DEFAULT_MIN_IDLE_SLOTS: &str = "1500";
+const DEFAULT_MAX_DELINQUENT_STAKE: &str = "5";
+
+#[derive(Debug, PartialEq)]
+pub struct RestartArgs {
+    pub config: RestartWindowConfig,
+    pub cancel: bool,
+    pub wait_for_exit: bool,
+}
+
+impl FromClapArgMatches for RestartArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(RestartArgs {
+            config: RestartWindowConfig {
+                min_idle_slots: value_t_or_exit!(matches, "min_idle_slots", u64),
+                max_delinquent_stake_percent: value_t_or_exit!(matches, "max_delinquent_stake", u8),
+            },
+            cancel: matches.is_present("cancel"),
+            wait_for_exit: !matches.is_present("no_wait_for_exit"),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Have the validator exit at the next good time to restart")
+        .arg(
+            Arg::with_name("min_idle_slots")
+                .long("min-idle-slots")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .value_name("SLOTS")
+                .default_value(DEFAULT_MIN_IDLE_SLOTS)
+                .help("Number of slots after the exit in which the validator must not be leader"),
+        )
+        .arg(
+            Arg::with_name("max_delinquent_stake")
+                .long("max-delinquent-stake")
+                .takes_value(true)
+                .validator(is_valid_percentage)
+                .value_name("PERCENT")
+                .default_value(DEFAULT_MAX_DELINQUENT_STAKE)
+                .help("The maximum delinquent stake % permitted for a restart"),
+        )
+        .arg(
+            Arg::with_name("cancel")
+                .long("cancel")
+                .takes_value(false)
+                .conflicts_with_all(&["min_idle_slots", "max_delinquent_stake"])
+                .help("Cancel a pending restart"),
+        )
+        .arg(
+            Arg::with_name("no_wait_for_exit")
+                .long("no-wait-for-exit")
+                .takes_value(false)
+                .help("Do not wait for the validator to exit after requesting the restart"),
+        )
+        .after_help(
+            "The validator checks its leader schedule every slot and exits as soon as it will not \
+             be leader for the given number of slots. Starting it again is left to its \
+             supervisor, such as systemd. Running the command again while a restart is pending \
+             updates the pending restart",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let restart_args = RestartArgs::from_clap_arg_match(matches)?;
+
+    if restart_args.cancel {
+        let cancelled = admin_rpc_service::runtime().block_on(async move {
+            admin_rpc_service::connect(ledger_path)
+                .await?
+                .cancel_restart()
+                .await
+        })?;
+        if cancelled {
+            println!("Restart cancelled");
+        } else {
+            println!("No restart is pending");
+        }
+        return Ok(());
+    }
+
+    let config = restart_args.config;
+    let validator_pid = admin_rpc_service::runtime().block_on(async move {
+        let admin_client = admin_rpc_service::connect(ledger_path).await?;
+        let validator_pid = admin_client.pid().await?;
+        admin_client.restart(config).await?;
+        Ok::<u32, Error>(validator_pid)
+    })?;
+    println!(
+        "Restart requested, the validator exits once it is not leader for the next {} slots",
+        config.min_idle_slots
+    );
+    if !restart_args.wait_for_exit {
+        return Ok(());
+    }
+
+    let progress_bar = new_spinner_progress_bar();
+    let status = admin_rpc_service::runtime().block_on(async move {
+        let admin_client = admin_rpc_service::connect(ledger_path).await?;
+        loop {
+            // The admin RPC service stops answering while the validator shuts down
+            let Ok(progress) = admin_client.restart_progress().await else {
+                return Ok(RestartStatus::Exiting);
+            };
+            let Some(progress) = progress else {
+                return Err(Error::Dynamic("no restart is pending".into()));
+            };
+            match progress.status {
+                RestartStatus::Exiting | RestartStatus::Cancelled => return Ok(progress.status),
+                RestartStatus::WaitingForIdleWindow | RestartStatus::DelinquencyTooHigh => {
+                    progress_bar.set_message(progress_message(&progress));
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    })?;
+
+    if status == RestartStatus::Cancelled {
+        return Err(Error::Dynamic("the restart was cancelled".into()));
+    }
+    println!("Restart window reached, the validator is exiting");
+    exit::poll_until_pid_terminates(validator_pid)?;
+    println!("The validator has exited and is expected to be started again by its supervisor");
+    Ok(())
+}
+
+fn progress_message(progress: &RestartProgress) -> String {
+    let elapsed = progress
+        .requested_at
+        .elapsed()
+        .unwrap_or_default()
+        .as_secs();
+    let status = match progress.status {
+        RestartStatus::WaitingForIdleWindow => match progress.next_leader_slot {
+            Some(next_leader_slot) => {
+                format!("Waiting for an idle window, next leader slot is {next_leader_slot}")
+            }
+            None => "Waiting for an idle window".to_string(),
+        },
+        RestartStatus::DelinquencyTooHigh => "Delinquency too high".to_string(),
+        RestartStatus::Exiting => "Exiting".to_string(),
+        RestartStatus::Cancelled => "Cancelled".to_string(),
+    };
+    format!(
+        "{:02}:{:02}:{:02} | Slot: {} | {:.2}% delinquent stake | {status}",
+        elapsed / 3600,
+        elapsed / 60 % 60,
+        elapsed % 60,
+        progress
+            .slot
+            .map_or_else(|| "-".to_string(), |slot| slot.to_string()),
+        progress.delinquent_stake_percent,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    impl Default for RestartArgs {
+        fn default() -> Self {
+            RestartArgs {
+                config: RestartWindowConfig {
+                    min_idle_slots: DEFAULT_MIN_IDLE_SLOTS
+                        .parse()
+                        .expect("invalid DEFAULT_MIN_IDLE_SLOTS"),
+                    max_delinquent_stake_percent: DEFAULT_MAX_DELINQUENT_STAKE
+                        .parse()
+                        .expect("invalid DEFAULT_MAX_DELINQUENT_STAKE"),
+                },
+                cancel: false,
+                wait_for_exit: true,
+            }
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_restart_default() {
+        verify_args_struct_by_command(command(), vec![COMMAND], RestartArgs::default());
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_restart_with_config() {
+        verify_args_struct_by_command(
+            command(),
+            vec![
+                COMMAND,
+                "--min-idle-slots",
+                "300",
+                "--max-delinquent-stake",
+                "10",
+                "--no-wait-for-exit",
+            ],
+            RestartArgs {
+                config: RestartWindowConfig {
+                    min_idle_slots: 300,
+                    max_delinquent_stake_percent: 10,
+                },
+                wait_for_exit: false,
+                ..RestartArgs::default()
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_restart_cancel() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--cancel"],
+            RestartArgs {
+                cancel: true,
+                ..RestartArgs::default()
+            },
+        );
+    }
+}
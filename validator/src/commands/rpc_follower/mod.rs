@@ -0,0 +1,174 @@
+use {
+    crate::commands::{Error, FromClapArgMatches, Result},
+    agave_logger::redirect_stderr_to_file,
+    agave_snapshots::{paths::BANK_SNAPSHOTS_DIR, snapshot_config::SnapshotConfig},
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    log::*,
+    solana_accounts_db::accounts_db::AccountsDbConfig,
+    solana_clap_utils::input_validators::is_parsable,
+    solana_genesis_utils::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+    solana_ledger::{
+        blockstore::Blockstore,
+        blockstore_options::{AccessType, BlockstoreOptions},
+    },
+    solana_rpc::{
+        rpc::JsonRpcConfig,
+        rpc_follower_service::{RpcFollowerConfig, RpcFollowerService},
+    },
+    std::{
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        path::{Path, PathBuf},
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    },
+};
+
+const COMMAND: &str = "rpc-follower";
+
+const DEFAULT_REFRESH_INTERVAL_SECS: &str = "0";
+
+#[derive(Debug, PartialEq)]
+pub struct RpcFollowerArgs {
+    pub refresh_interval: Option<Duration>,
+}
+
+impl FromClapArgMatches for RpcFollowerArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        let refresh_interval_secs = value_t!(matches, "refresh_interval", u64)?;
+        Ok(RpcFollowerArgs {
+            refresh_interval: (refresh_interval_secs > 0)
+                .then(|| Duration::from_secs(refresh_interval_secs)),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Serve read-only JSON RPC from the latest local snapshot archives")
+        .arg(
+            Arg::with_name("refresh_interval")
+                .long("refresh-interval")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .default_value(DEFAULT_REFRESH_INTERVAL_SECS)
+                .help(
+                    "How often to look for newer snapshot archives and switch to them. 0 keeps \
+                     serving the snapshot loaded at startup",
+                ),
+        )
+        .after_help(
+            "The node does not join gossip and does not replay. The RPC port is set with \
+             --rpc-port, the bind address with --rpc-bind-address [default: 127.0.0.1], and the \
+             other RPC and snapshot arguments of the validator apply. Transaction submission and \
+             airdrops are rejected.",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, subcommand_matches: &ArgMatches) -> Result<()> {
+    let RpcFollowerArgs { refresh_interval } =
+        RpcFollowerArgs::from_clap_arg_match(subcommand_matches)?;
+
+    let logfile = matches
+        .value_of("logfile")
+        .filter(|logfile| *logfile != "-")
+        .map(PathBuf::from);
+    let _logger_thread = redirect_stderr_to_file(logfile);
+
+    let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
+    let rpc_port = value_t!(matches, "rpc_port", u16)
+        .map_err(|_| Error::Dynamic("--rpc-port is required to run the rpc follower".into()))?;
+    let rpc_bind_address = matches
+        .value_of("rpc_bind_address")
+        .map(solana_net_utils::parse_host)
+        .transpose()
+        .map_err(|err| Error::Dynamic(err.into()))?
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    let rpc_config = JsonRpcConfig::from_clap_arg_match(matches)?;
+
+    let snapshots_dir = matches
+        .value_of("snapshots")
+        .map(Path::new)
+        .unwrap_or(ledger_path.as_path());
+    let snapshot_config = SnapshotConfig {
+        full_snapshot_archives_dir: matches
+            .value_of("full_snapshot_archive_path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| snapshots_dir.to_path_buf()),
+        incremental_snapshot_archives_dir: matches
+            .value_of("incremental_snapshot_archive_path")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| snapshots_dir.to_path_buf()),
+        bank_snapshots_dir: snapshots_dir.join(BANK_SNAPSHOTS_DIR),
+        ..SnapshotConfig::new_load_only()
+    };
+
+    let genesis_config = open_genesis_config(&ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE)
+        .map_err(|err| Error::Dynamic(format!("failed to open genesis config: {err}").into()))?;
+
+    // A validator may be running on the same ledger, in which case only secondary access is
+    // possible. An air-gapped copy of the ledger is opened as usual.
+    let blockstore = Blockstore::open_with_options(
+        &ledger_path,
+        BlockstoreOptions {
+            access_type: AccessType::Secondary,
+            ..BlockstoreOptions::default()
+        },
+    )
+    .or_else(|err| {
+        info!("opening the blockstore as secondary failed: {err}, opening it as primary");
+        Blockstore::open(&ledger_path)
+    })
+    .map_err(|err| Error::Dynamic(format!("failed to open blockstore: {err}").into()))?;
+
+    let exit = Arc::new(AtomicBool::new(false));
+    let config = RpcFollowerConfig {
+        rpc_addr: SocketAddr::new(rpc_bind_address, rpc_port),
+        accounts_db_config: AccountsDbConfig {
+            account_indexes: Some(rpc_config.account_indexes.clone()),
+            ..AccountsDbConfig::default()
+        },
+        rpc_config,
+        snapshot_config,
+        working_dir: ledger_path.join(COMMAND),
+        ledger_path,
+        refresh_interval,
+    };
+    let service = RpcFollowerService::new(config, genesis_config, Arc::new(blockstore), exit)
+        .map_err(|err| Error::Dynamic(err.into()))?;
+    println!(
+        "Serving slot {} on http://{}",
+        service.slot(),
+        SocketAddr::new(rpc_bind_address, rpc_port),
+    );
+    service
+        .join()
+        .map_err(|_| Error::Dynamic("rpc follower service panicked".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    #[test]
+    fn verify_args_struct_by_command_rpc_follower_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND],
+            RpcFollowerArgs {
+                refresh_interval: None,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_rpc_follower_with_refresh_interval() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--refresh-interval", "60"],
+            RpcFollowerArgs {
+                refresh_interval: Some(Duration::from_secs(60)),
+            },
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_within_range,
+    std::path::Path,
+};
+
+const COMMAND: &str = "rpc-threads";
+
+#[derive(Debug, PartialEq)]
+pub struct RpcThreadsArgs {
+    pub rpc_threads: Option<usize>,
+    pub rpc_blocking_threads: Option<usize>,
+}
+
+impl FromClapArgMatches for RpcThreadsArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(RpcThreadsArgs {
+            rpc_threads: value_t!(matches, "rpc_threads", usize).ok(),
+            rpc_blocking_threads: value_t!(matches, "rpc_blocking_threads", usize).ok(),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Display or resize the thread pools of the JSON RPC service")
+        .arg(
+            Arg::with_name("rpc_threads")
+                .long("rpc-threads")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(|value| is_within_range(value, 1usize..))
+                .help("Number of threads to use for servicing RPC requests"),
+        )
+        .arg(
+            Arg::with_name("rpc_blocking_threads")
+                .long("rpc-blocking-threads")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(|value| is_within_range(value, 1usize..))
+                .help("Number of blocking threads to use for servicing CPU bound RPC requests"),
+        )
+        .after_help(
+            "Without arguments, the current sizes are displayed. Resizing restarts the RPC HTTP \
+             server on new thread pools, so connections attempted in the meantime are refused. \
+             Requests in flight are given 30 seconds to complete on the previous thread pools. \
+             PubSub websocket subscriptions are not affected. The new sizes last until the \
+             validator restarts, so also update --rpc-threads and --rpc-blocking-threads to keep \
+             them",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let RpcThreadsArgs {
+        rpc_threads,
+        rpc_blocking_threads,
+    } = RpcThreadsArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let sizes = if rpc_threads.is_none() && rpc_blocking_threads.is_none() {
+        admin_rpc_service::runtime()
+            .block_on(async move { admin_client.await?.rpc_thread_pools().await })?
+    } else {
+        admin_rpc_service::runtime().block_on(async move {
+            admin_client
+                .await?
+                .set_rpc_thread_pools(rpc_threads, rpc_blocking_threads)
+                .await
+        })?
+    };
+    println!("RPC threads: {}", sizes.rpc_threads);
+    println!("RPC blocking threads: {}", sizes.rpc_blocking_threads);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_args_struct_by_command_rpc_threads() {
+        for (args, expected) in [
+            (
+                vec![COMMAND],
+                RpcThreadsArgs {
+                    rpc_threads: None,
+                    rpc_blocking_threads: None,
+                },
+            ),
+            (
+                vec![COMMAND, "--rpc-threads", "16"],
+                RpcThreadsArgs {
+                    rpc_threads: Some(16),
+                    rpc_blocking_threads: None,
+                },
+            ),
+            (
+                vec![
+                    COMMAND,
+                    "--rpc-threads",
+                    "16",
+                    "--rpc-blocking-threads",
+                    "4",
+                ],
+                RpcThreadsArgs {
+                    rpc_threads: Some(16),
+                    rpc_blocking_threads: Some(4),
+                },
+            ),
+        ] {
+            let matches = command().get_matches_from(args);
+            assert_eq!(
+                RpcThreadsArgs::from_clap_arg_match(&matches).unwrap(),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use {
+    crate::{admin_rpc_service, commands::Result},
+    clap::{App, ArgMatches, SubCommand},
+    std::path::Path,
+};
+
+const COMMAND: &str = "rpc-usage";
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Display the JSON RPC usage per client in the current reporting period")
+        .after_help(
+            "Requires the validator to be started with --rpc-usage-tracking. The usage is \
+             printed in the same JSON format as the reports written to --rpc-usage-report-dir",
+        )
+}
+
+pub fn execute(_matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let rpc_usage = admin_rpc_service::runtime()
+        .block_on(async move { admin_client.await?.rpc_usage().await })?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&rpc_usage).map_err(std::io::Error::from)?
+    );
+
+    Ok(())
+}
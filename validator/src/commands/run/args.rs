@@ -2,7 +2,8 @@ use {
     crate::{
         bootstrap::RpcBootstrapConfig,
         cli::{hash_validator, port_range_validator, port_validator, DefaultArgs},
-        commands::{FromClapArgMatches, Result},
+        commands::{run::preflight, FromClapArgMatches, Result},
+        fleet_defaults,
     },
     agave_snapshots::{SnapshotVersion, SUPPORTED_ARCHIVE_COMPRESSION},
     clap::{values_t, App, Arg, ArgMatches},
@@ -12,16 +13,19 @@ use {
         input_parsers::keypair_of,
         input_validators::{
             is_keypair_or_ask_keyword, is_non_zero, is_parsable, is_pow2, is_pubkey,
-            is_pubkey_or_keypair, is_slot, is_within_range, validate_cpu_ranges,
+            is_pubkey_or_keypair, is_slot, is_url, is_within_range, validate_cpu_ranges,
             validate_maximum_full_snapshot_archives_to_retain,
             validate_maximum_incremental_snapshot_archives_to_retain,
         },
         keypair::SKIP_SEED_PHRASE_VALIDATION_ARG,
     },
     solana_core::{
+        banking_stage::reserved_capacity::MAX_RESERVED_BLOCK_CU_PERCENT,
         banking_trace::DirByteLimit,
+        repair::serve_repair::MAX_REPAIR_SERVED_SLOT_AGE,
         validator::{BlockProductionMethod, BlockVerificationMethod},
     },
+    solana_gossip::{contact_info::MAX_REGION_LEN, peer_preference::PeerPreference},
     solana_keypair::Keypair,
     solana_ledger::{blockstore_options::BlockstoreOptions, use_snapshot_archives_at_startup},
     solana_pubkey::Pubkey,
@@ -30,7 +34,13 @@ use {
     solana_signer::Signer,
     solana_streamer::socket::SocketAddrSpace,
     solana_unified_scheduler_pool::DefaultSchedulerPool,
-    std::{collections::HashSet, net::SocketAddr, path::PathBuf, str::FromStr},
+    std::{
+        collections::HashSet,
+        net::{IpAddr, SocketAddr},
+        num::NonZeroUsize,
+        path::PathBuf,
+        str::FromStr,
+    },
 };
 
 const EXCLUDE_KEY: &str = "account-index-exclude-key";
@@ -57,10 +67,15 @@ const WEN_RESTART_HELP: &str =
 
 pub mod account_secondary_indexes;
 pub mod blockstore_options;
+pub mod file_serving_config;
 pub mod json_rpc_config;
 pub mod pub_sub_config;
+pub mod rpc_backpressure_config;
 pub mod rpc_bigtable_config;
 pub mod rpc_bootstrap_config;
+pub mod rpc_response_limits;
+pub mod rpc_simulation_config;
+pub mod rpc_usage_config;
 pub mod send_transaction_config;
 
 #[derive(Debug, PartialEq)]
@@ -214,6 +229,23 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .default_value(&default_args.ledger_path)
             .help("Use DIR as ledger location"),
     )
+    .arg(
+        Arg::with_name(fleet_defaults::FLEET_DEFAULTS_ARG)
+            .long(fleet_defaults::FLEET_DEFAULTS_ARG)
+            .value_name("FILE")
+            .takes_value(true)
+            .help(
+                "Read machine-wide argument defaults from FILE. Arguments given on the command \
+                 line take precedence over the values in FILE \
+                 [default: /etc/agave/fleet.toml, if it exists]",
+            ),
+    )
+    .arg(
+        Arg::with_name(fleet_defaults::NO_FLEET_DEFAULTS_ARG)
+            .long(fleet_defaults::NO_FLEET_DEFAULTS_ARG)
+            .takes_value(false)
+            .help("Do not read machine-wide argument defaults, even if --fleet-defaults is given"),
+    )
     .arg(
         Arg::with_name("entrypoint")
             .short("n")
@@ -248,6 +280,17 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .takes_value(true)
             .help("Halt the validator when it reaches the given slot"),
     )
+    .arg(
+        Arg::with_name("throttle_profile")
+            .long("throttle-profile")
+            .value_name("FILE")
+            .takes_value(true)
+            .hidden(hidden_unless_forced())
+            .help(
+                "Cap the cores, thread pools, blockstore write bandwidth and accounts-db memory \
+                 to the limits in the TOML profile FILE, to simulate running on smaller hardware",
+            ),
+    )
     .arg(
         Arg::with_name("rpc_port")
             .long("rpc-port")
@@ -387,6 +430,63 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  forwarded.",
             ),
     )
+    .arg(
+        Arg::with_name("tpu_mirror_address")
+            .long("tpu-mirror-address")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .validator(solana_net_utils::is_host_port)
+            .help(
+                "Send a copy of the verified transactions received over the TPU to this address, \
+                 for testing another validator or banking stage against live traffic. \
+                 Transactions are dropped rather than slowing down the TPU",
+            ),
+    )
+    .arg(
+        Arg::with_name("tpu_mirror_protocol")
+            .long("tpu-mirror-protocol")
+            .value_name("PROTOCOL")
+            .takes_value(true)
+            .possible_values(&["quic", "udp"])
+            .default_value("quic")
+            .help("Protocol to send the transactions of --tpu-mirror-address over"),
+    )
+    .arg(
+        Arg::with_name("tpu_mirror_sample_rate")
+            .long("tpu-mirror-sample-rate")
+            .value_name("RATE")
+            .takes_value(true)
+            .validator(|value| match value.parse::<f64>() {
+                Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(()),
+                _ => Err(format!("{value} is not a number between 0 and 1")),
+            })
+            .default_value("1.0")
+            .help("Fraction of the transactions to send to --tpu-mirror-address"),
+    )
+    .arg(
+        Arg::with_name("forwarding_deny_delinquent_slots")
+            .long("forwarding-deny-delinquent-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .conflicts_with("no_voting")
+            .help(
+                "Stop forwarding transactions, and stop advertising the TPU forwards address in \
+                 gossip, while the last vote of the vote account is more than SLOTS behind the \
+                 working bank",
+            ),
+    )
+    .arg(
+        Arg::with_name("forwarding_deny_identity_mismatch")
+            .long("forwarding-deny-identity-mismatch")
+            .takes_value(false)
+            .conflicts_with("no_voting")
+            .help(
+                "Stop forwarding transactions, and stop advertising the TPU forwards address in \
+                 gossip, while the vote account does not exist or names another validator \
+                 identity",
+            ),
+    )
     .arg(
         Arg::with_name("public_rpc_addr")
             .long("public-rpc-address")
@@ -485,6 +585,22 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  older snapshots.",
             ),
     )
+    .arg(
+        Arg::with_name("snapshot_on_exit_max_age")
+            .long("snapshot-on-exit-max-age")
+            .value_name("NUMBER_OF_SLOTS")
+            .takes_value(true)
+            .conflicts_with("no_snapshots")
+            .validator(is_parsable::<u64>)
+            .help("Create a snapshot on graceful exit if the latest one is older than this")
+            .long_help(
+                "Create a snapshot of the root bank on graceful exit if the latest snapshot \
+                 archive is more than this many slots behind it, so the next start does not have \
+                 to replay those slots. An incremental snapshot is created if incremental \
+                 snapshots are enabled and a full snapshot archive exists, otherwise a full \
+                 snapshot. Exit is delayed until the snapshot has been archived.",
+            ),
+    )
     .arg(
         Arg::with_name("snapshot_packager_niceness_adj")
             .long("snapshot-packager-niceness-adjustment")
@@ -583,6 +699,72 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             /* .default_value() intentionally not used here! */
             .help("Keep this amount of shreds in root slots."),
     )
+    .arg(
+        Arg::with_name("ledger_cleanup_pause_leader_slots")
+            .long("ledger-cleanup-pause-leader-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .default_value(&default_args.ledger_cleanup_pause_leader_slots)
+            .validator(is_parsable::<u64>)
+            .help(
+                "Don't purge old ledger data while the node is within this many slots of one of \
+                 its leader slots. 0 disables the pause",
+            ),
+    )
+    .arg(
+        Arg::with_name("ledger_cleanup_pause_repair_serving_secs")
+            .long("ledger-cleanup-pause-repair-serving-secs")
+            .value_name("SECONDS")
+            .takes_value(true)
+            .default_value(&default_args.ledger_cleanup_pause_repair_serving_secs)
+            .validator(|value| {
+                is_within_range(value, 0..=MAX_REPAIR_SERVED_SLOT_AGE.as_secs() as usize)
+            })
+            .help(
+                "Don't purge old ledger data while slots that would be purged were served to \
+                 repair peers within this many seconds. 0 disables the pause",
+            ),
+    )
+    .arg(
+        Arg::with_name("rpc_transaction_history_retention_slots")
+            .long("rpc-transaction-history-retention-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .requires_all(&["enable_rpc_transaction_history", "limit_ledger_size"])
+            .help(
+                "Purge the RPC transaction history once it is this many slots older than the \
+                 latest root, ahead of the rest of the ledger. The history of transactions that \
+                 involve a --rpc-transaction-history-retained-address is exempt",
+            ),
+    )
+    .arg(
+        Arg::with_name("rpc_transaction_history_retained_address")
+            .long("rpc-transaction-history-retained-address")
+            .value_name("PUBKEY")
+            .takes_value(true)
+            .multiple(true)
+            .validator(is_pubkey)
+            .requires_all(&["enable_rpc_transaction_history", "limit_ledger_size"])
+            .help(
+                "Keep the RPC transaction history of transactions involving this address for \
+                 --rpc-transaction-history-retained-address-slots, or as long as the rest of the \
+                 ledger. May be specified multiple times",
+            ),
+    )
+    .arg(
+        Arg::with_name("rpc_transaction_history_retained_address_slots")
+            .long("rpc-transaction-history-retained-address-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .requires("rpc_transaction_history_retained_address")
+            .help(
+                "Purge the RPC transaction history of transactions involving a \
+                 --rpc-transaction-history-retained-address once it is this many slots older \
+                 than the latest root",
+            ),
+    )
     .arg(
         Arg::with_name("rocksdb_shred_compaction")
             .long("rocksdb-shred-compaction")
@@ -621,6 +803,42 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  samples are collected in 1 / ROCKS_PERF_SAMPLE_INTERVAL sampling rate.",
             ),
     )
+    .arg(
+        Arg::with_name("rocksdb_wal_flush_interval_ms")
+            .long("rocksdb-wal-flush-interval-ms")
+            .value_name("MILLISECONDS")
+            .takes_value(true)
+            .validator(|value| is_within_range(value, 1u64..))
+            .help(
+                "Buffer RocksDB write-ahead log entries in memory and flush them from a dedicated \
+                 thread at this interval, committing the writes of concurrent shred insertions \
+                 together. Writes made since the last flush are lost if the validator crashes. \
+                 [default: every write is flushed to the write-ahead log]",
+            ),
+    )
+    .arg(
+        Arg::with_name("rocksdb_wal_bytes_per_sync")
+            .long("rocksdb-wal-bytes-per-sync")
+            .value_name("BYTES")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .help(
+                "Sync the RocksDB write-ahead log to disk in the background every time this many \
+                 bytes are written to it, rather than leaving large syncs to the OS \
+                 [default: 0, disabled]",
+            ),
+    )
+    .arg(
+        Arg::with_name("rocksdb_bytes_per_sync")
+            .long("rocksdb-bytes-per-sync")
+            .value_name("BYTES")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .help(
+                "Sync RocksDB data files to disk in the background every time this many bytes \
+                 are written to them by flushes and compactions [default: 0, disabled]",
+            ),
+    )
     .arg(
         Arg::with_name("skip_startup_ledger_verification")
             .long("skip-startup-ledger-verification")
@@ -674,6 +892,17 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  SIGUSR1 signal to the validator process will cause it to re-open the log file",
             ),
     )
+    .arg(
+        Arg::with_name("crash_report_webhook")
+            .long("crash-report-webhook")
+            .value_name("URL")
+            .takes_value(true)
+            .validator(is_url)
+            .help(
+                "POST the JSON crash report written to <LEDGER>/crash-reports to this URL when \
+                 the validator panics",
+            ),
+    )
     .arg(
         Arg::with_name("wait_for_supermajority")
             .long("wait-for-supermajority")
@@ -719,6 +948,22 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  accepted",
             ),
     )
+    .arg(
+        Arg::with_name("known_validators_min_stake_percent")
+            .long("known-validators-min-stake-percent")
+            .value_name("PERCENT")
+            .takes_value(true)
+            .validator(|value| match value.parse::<f64>() {
+                Ok(percent) if percent > 0.0 && percent <= 100.0 => Ok(()),
+                _ => Err(format!("{value} is not a percentage between 0 and 100")),
+            })
+            .help(
+                "Also treat every validator holding at least this percentage of the current \
+                 epoch's stake as a known validator. The set is rederived at every epoch and \
+                 kept in the ledger, so that a restart bootstraps from the validators of the \
+                 last epoch the node saw",
+            ),
+    )
     .arg(
         Arg::with_name("debug_key")
             .long("debug-key")
@@ -754,6 +999,41 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  validators. [default: all validators]",
             ),
     )
+    .arg(
+        Arg::with_name("serve_repair_max_bytes_per_second")
+            .long("serve-repair-max-bytes-per-second")
+            .value_name("BYTES")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .default_value(&default_args.serve_repair_max_bytes_per_second)
+            .help(
+                "Upload bandwidth to spend on responses to the repair requests of other nodes. \
+                 Requests of validators in --repair-whitelist are served first, then the others \
+                 in an order weighted by their stake",
+            ),
+    )
+    .arg(
+        Arg::with_name("serve_repair_max_requests_per_second")
+            .long("serve-repair-max-requests-per-second")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .help(
+                "Number of repair requests of other nodes to handle per second, bounding the CPU \
+                 and blockstore reads they cost [default: unlimited]",
+            ),
+    )
+    .arg(
+        Arg::with_name("serve_repair_max_peer_bytes_per_second")
+            .long("serve-repair-max-peer-bytes-per-second")
+            .value_name("BYTES")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .help(
+                "Upload bandwidth to spend on responses to the repair requests of a single node \
+                 outside of --repair-whitelist [default: unlimited]",
+            ),
+    )
     .arg(
         Arg::with_name("gossip_validators")
             .long("gossip-validator")
@@ -766,6 +1046,50 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  from from validators outside this set. [default: all validators]",
             ),
     )
+    .arg(
+        Arg::with_name("region")
+            .long("region")
+            .value_name("REGION")
+            .takes_value(true)
+            .validator(|value| {
+                if value.len() > MAX_REGION_LEN {
+                    Err(format!("region is longer than {MAX_REGION_LEN} bytes"))
+                } else {
+                    Ok(())
+                }
+            })
+            .help(
+                "Region this validator runs in, such as a cloud region or a city code. The region \
+                 is published in gossip so that peers in the same region can prefer this node",
+            ),
+    )
+    .arg(
+        Arg::with_name("peer_preference")
+            .long("peer-preference")
+            .value_name("PREFERENCE")
+            .takes_value(true)
+            .possible_values(PeerPreference::cli_names())
+            .default_value("stake")
+            .requires_if("region", "region")
+            .help(
+                "How to pick gossip entrypoints, repair peers and snapshot peers. \"stake\" samples \
+                 them by stake only. \"region\" favors the --known-validator peers that declared \
+                 the same --region as this validator. \"latency\" favors peers with a short round trip time, \
+                 measured continuously with gossip pings",
+            ),
+    )
+    .arg(
+        Arg::with_name("encrypt_shred_transport")
+            .long("encrypt-shred-transport")
+            .takes_value(false)
+            .help(
+                "Send repair requests, broadcast and retransmitted shreds over QUIC, which \
+                 authenticates both ends by their identity and encrypts the traffic, to the \
+                 peers which also enable this option, and drop the shreds they send over UDP. \
+                 Repair responses come back over the same connections. Traffic with the other \
+                 peers still goes over UDP",
+            ),
+    )
     .arg(
         Arg::with_name("tpu_connection_pool_size")
             .long("tpu-connection-pool-size")
@@ -783,6 +1107,16 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .hidden(hidden_unless_forced())
             .help("Controls the rate of the clients connections per IpAddr per minute."),
     )
+    .arg(
+        Arg::with_name("tpu_enable_transaction_acks")
+            .long("tpu-enable-transaction-acks")
+            .takes_value(false)
+            .help(
+                "Acknowledge transactions received over QUIC from staked clients that negotiate \
+                 the acknowledged TPU protocol, telling them whether each transaction was \
+                 accepted, discarded as a duplicate or dropped",
+            ),
+    )
     .arg(
         Arg::with_name("vote_use_quic")
             .long("vote-use-quic")
@@ -865,6 +1199,33 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .hidden(hidden_unless_forced())
             .help("Controls the max number of streams for a TPU service."),
     )
+    .arg(
+        Arg::with_name("tpu_qos_exempt_pubkey")
+            .long("tpu-qos-exempt-pubkey")
+            .validator(is_pubkey)
+            .value_name("PUBKEY")
+            .multiple(true)
+            .takes_value(true)
+            .help(
+                "Exempt TPU and TPU-forward connections authenticated with this identity from \
+                 stake-weighted QoS, so they are always accepted and never throttled regardless \
+                 of stake. Intended for the operator's own RPC nodes. May be specified multiple \
+                 times",
+            ),
+    )
+    .arg(
+        Arg::with_name("tpu_qos_exempt_ip_addr")
+            .long("tpu-qos-exempt-ip-addr")
+            .validator(is_parsable::<IpAddr>)
+            .value_name("IP_ADDRESS")
+            .multiple(true)
+            .takes_value(true)
+            .help(
+                "Exempt TPU and TPU-forward connections from this IP address from stake-weighted \
+                 QoS. The per IP address connection rate limit still applies. May be specified \
+                 multiple times",
+            ),
+    )
     .arg(
         Arg::with_name("num_quic_endpoints")
             .long("num-quic-endpoints")
@@ -915,6 +1276,31 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  otherwise use --bind-address]",
             ),
     )
+    .arg(
+        Arg::with_name("admin_rpc_http_address")
+            .long("admin-rpc-http-address")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .validator(|value| match value.parse::<SocketAddr>() {
+                Ok(address) if address.ip().is_loopback() => Ok(()),
+                Ok(_) => Err(format!("{value} is not a loopback address")),
+                Err(err) => Err(format!("invalid address {value}: {err}")),
+            })
+            .requires("admin_rpc_http_token_file")
+            .help(
+                "Also serve the admin RPC over HTTP at this loopback address, to requests \
+                 presenting the token of --admin-rpc-http-token-file as a bearer token. \
+                 GET /openapi.json describes the methods",
+            ),
+    )
+    .arg(
+        Arg::with_name("admin_rpc_http_token_file")
+            .long("admin-rpc-http-token-file")
+            .value_name("FILE")
+            .takes_value(true)
+            .requires("admin_rpc_http_address")
+            .help("File containing the bearer token of the admin RPC HTTP endpoint"),
+    )
     .arg(
         Arg::with_name("geyser_plugin_config")
             .long("geyser-plugin-config")
@@ -954,6 +1340,30 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  See the zstd manpage for more information.",
             ),
     )
+    .arg(
+        Arg::with_name("snapshot_archive_upload_command")
+            .long("snapshot-archive-upload-command")
+            .value_name("COMMAND")
+            .takes_value(true)
+            .help(
+                "Stream each snapshot archive into the standard input of COMMAND while the \
+                 archive is being created. COMMAND is run with `sh -c` and the archive file name \
+                 is available in the SNAPSHOT_ARCHIVE_NAME environment variable, e.g. \
+                 'aws s3 cp - s3://bucket/$SNAPSHOT_ARCHIVE_NAME'. A failed upload is logged \
+                 and does not affect the validator",
+            ),
+    )
+    .arg(
+        Arg::with_name("snapshot_archive_upload_only")
+            .long("snapshot-archive-upload-only")
+            .takes_value(false)
+            .requires("snapshot_archive_upload_command")
+            .help(
+                "Do not keep snapshot archives in the local snapshot archive directories, only \
+                 stream them to --snapshot-archive-upload-command. Snapshots created this way \
+                 are not advertised to the cluster",
+            ),
+    )
     .arg(
         Arg::with_name("wal_recovery_mode")
             .long("wal-recovery-mode")
@@ -969,21 +1379,15 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
     )
     .arg(
         Arg::with_name("poh_pinned_cpu_core")
-            .hidden(hidden_unless_forced())
-            .long("experimental-poh-pinned-cpu-core")
+            .long("poh-pinned-cpu-core")
             .takes_value(true)
             .value_name("CPU_CORE_INDEX")
-            .validator(|s| {
-                let core_index = usize::from_str(&s).map_err(|e| e.to_string())?;
-                let max_index = core_affinity::get_core_ids()
-                    .map(|cids| cids.len() - 1)
-                    .unwrap_or(0);
-                if core_index > max_index {
-                    return Err(format!("core index must be in the range [0, {max_index}]"));
-                }
-                Ok(())
-            })
-            .help("EXPERIMENTAL: Specify which CPU core PoH is pinned to"),
+            .validator(is_valid_cpu_core_index)
+            .help(
+                "Specify which CPU core PoH is pinned to. The startup PoH speed check is \
+                 measured on the same core. For best results, isolate this core from the \
+                 scheduler (e.g. with the isolcpus kernel parameter)",
+            ),
     )
     .arg(
         Arg::with_name("poh_hashes_per_batch")
@@ -993,6 +1397,25 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .value_name("NUM")
             .help("Specify hashes per batch in PoH service"),
     )
+    .arg(
+        Arg::with_name("poh_alert_max_slot_drift_ms")
+            .long("poh-alert-max-slot-drift-ms")
+            .takes_value(true)
+            .value_name("MILLISECONDS")
+            .validator(is_parsable::<u64>)
+            .help(
+                "Warn when PoH ticks slowly enough that a slot takes this much longer than its \
+                 target duration",
+            ),
+    )
+    .arg(
+        Arg::with_name("poh_alert_min_hash_rate")
+            .long("poh-alert-min-hash-rate")
+            .takes_value(true)
+            .value_name("HASHES_PER_SECOND")
+            .validator(is_parsable::<u64>)
+            .help("Warn when the PoH core computes fewer hashes per second than this"),
+    )
     .arg(
         Arg::with_name("process_ledger_before_services")
             .long("process-ledger-before-services")
@@ -1141,6 +1564,18 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             )
             .hidden(hidden_unless_forced()),
     )
+    .arg(
+        Arg::with_name("accounts_db_storage_manifest")
+            .long("accounts-db-storage-manifest")
+            .takes_value(false)
+            .help("Let other processes on this host read the accounts storages")
+            .long_help(
+                "Periodically write a manifest of the accounts storage files to \
+                 <LEDGER_DIR>/accounts_storage_manifest, so that tools such as \
+                 agave-ledger-tool can read the accounts of the running validator without \
+                 copying its storages.",
+            ),
+    )
     .arg(
         Arg::with_name("accounts_index_scan_results_limit_mb")
             .long("accounts-index-scan-results-limit-mb")
@@ -1169,6 +1604,22 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .help("Pre-allocate the accounts index, assuming this many accounts")
             .hidden(hidden_unless_forced()),
     )
+    .arg(
+        Arg::with_name("accounts_index_memory_check")
+            .long("accounts-index-memory-check")
+            .value_name("MODE")
+            .takes_value(true)
+            .possible_values(preflight::ACCOUNTS_INDEX_MEMORY_CHECK_NAMES)
+            .default_value(preflight::DEFAULT_ACCOUNTS_INDEX_MEMORY_CHECK)
+            .help("What to do if the accounts index is not expected to fit in memory")
+            .long_help(
+                "Before loading the snapshot, estimate the memory needed to generate the accounts \
+                 index from the snapshot's account count and the accounts index mode. \
+                 \"enforce\" refuses to start when the estimate exceeds the total memory of the \
+                 host and warns when it exceeds the available memory. \"warn\" only warns. \
+                 \"skip\" disables the check.",
+            ),
+    )
     .arg(
         Arg::with_name("accounts_index_path")
             .long("accounts-index-path")
@@ -1230,6 +1681,17 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
             .value_name("BYTES")
             .help("Maximum number of bytes written to the program log before truncation"),
     )
+    .arg(
+        Arg::with_name("replay_determinism_check")
+            .long("replay-determinism-check")
+            .takes_value(false)
+            .hidden(hidden_unless_forced())
+            .help(
+                "Debug mode that re-executes every replayed transaction batch one transaction at \
+                 a time and reports transactions whose results differ from the batched \
+                 execution. Greatly slows down replay",
+            ),
+    )
     .arg(
         Arg::with_name("banking_trace_dir_byte_limit")
             // expose friendly alternative name to cli than internal
@@ -1271,6 +1733,57 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  started creating ours.",
             ),
     )
+    .arg(
+        Arg::with_name("max_unrooted_banks")
+            .long("max-unrooted-banks")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .help(
+                "Most unrooted banks to keep in memory. When there are more, the oldest forks \
+                 that do not contain the heaviest bank, the last vote or the PoH reset bank are \
+                 over the limit. See --unrooted-fork-eviction",
+            ),
+    )
+    .arg(
+        Arg::with_name("max_unrooted_fork_age")
+            .long("max-unrooted-fork-age")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .help(
+                "Most slots the newest bank of an unrooted fork may be behind the highest bank. \
+                 Older forks that do not contain the heaviest bank, the last vote or the PoH \
+                 reset bank are over the limit. See --unrooted-fork-eviction",
+            ),
+    )
+    .arg(
+        Arg::with_name("unrooted_fork_eviction")
+            .long("unrooted-fork-eviction")
+            .value_name("BEHAVIOR")
+            .takes_value(true)
+            .possible_values(&["report", "prune"])
+            .default_value("report")
+            .help(
+                "What to do with the forks over --max-unrooted-banks or --max-unrooted-fork-age. \
+                 \"report\" only reports them in the logs and metrics. \"prune\" also removes \
+                 their banks, which are replayed again if the cluster confirms one of their \
+                 slots",
+            ),
+    )
+    .arg(
+        Arg::with_name("epoch_precompute_slots")
+            .long("epoch-precompute-slots")
+            .value_name("SLOTS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .default_value(&default_args.epoch_precompute_slots)
+            .help(
+                "Start computing the turbine tree of the next epoch this many slots before the \
+                 epoch boundary, and compute leader schedules as soon as their stakes are known, \
+                 on a dedicated thread rather than in replay and retransmit. 0 disables it",
+            ),
+    )
     .arg(
         Arg::with_name("block_verification_method")
             .long("block-verification-method")
@@ -1300,6 +1813,44 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  method",
             ),
     )
+    .arg(
+        Arg::with_name("reserved_block_cu_percent")
+            .long("reserved-block-cu-percent")
+            .value_name("PERCENT")
+            .takes_value(true)
+            .validator(|value| {
+                is_within_range(value, 0..=usize::from(MAX_RESERVED_BLOCK_CU_PERCENT))
+            })
+            .help(
+                "Percentage of the block compute units that only vote transactions and \
+                 transactions of the --critical-program programs can use when producing blocks",
+            ),
+    )
+    .arg(
+        Arg::with_name("reserved_banking_workers")
+            .long("reserved-banking-workers")
+            .value_name("COUNT")
+            .takes_value(true)
+            .validator(is_parsable::<usize>)
+            .help(
+                "Number of the central-scheduler banking workers that only transactions of the \
+                 --critical-program programs are scheduled on. Vote transactions are processed by \
+                 the vote worker and are not affected. Must be less than \
+                 --block-production-num-workers",
+            ),
+    )
+    .arg(
+        Arg::with_name("critical_program")
+            .long("critical-program")
+            .value_name("PUBKEY")
+            .takes_value(true)
+            .multiple(true)
+            .validator(is_pubkey)
+            .help(
+                "Let the transactions that invoke this program use the block compute units and \
+                 banking workers reserved for vote transactions. May be specified multiple times",
+            ),
+    )
     .arg(
         Arg::with_name("enable_scheduler_bindings")
             .long("enable-scheduler-bindings")
@@ -1338,6 +1889,56 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
                  leader used is different from others.",
             ),
     )
+    .arg(
+        Arg::with_name("cluster_halt_detection_secs")
+            .long("cluster-halt-detection-secs")
+            .value_name("SECONDS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .help(
+                "Report the cluster as halted, with how to join a coordinated restart, when \
+                 neither the root of this validator nor the roots voted by staked peers in \
+                 gossip have advanced for this many seconds [default: disabled]",
+            ),
+    )
+    .arg(
+        Arg::with_name("tvu_shred_sigverify_batch_size")
+            .long("tvu-shred-sigverify-batch-size")
+            .value_name("BATCHES")
+            .takes_value(true)
+            .default_value(&default_args.tvu_shred_sigverify_batch_size)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Maximum number of received packet batches whose shred signatures are verified \
+                 together",
+            ),
+    )
+    .arg(
+        Arg::with_name("tvu_shred_sigverify_queue_depth")
+            .long("tvu-shred-sigverify-queue-depth")
+            .value_name("BATCHES")
+            .takes_value(true)
+            .default_value(&default_args.tvu_shred_sigverify_queue_depth)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Number of received packet batches waiting for shred signature verification, \
+                 beyond which the oldest are dropped. Also bounds the packet batches received \
+                 from turbine and repair waiting to be filtered",
+            ),
+    )
+    .arg(
+        Arg::with_name("tvu_shred_sigverify_backend")
+            .long("tvu-shred-sigverify-backend")
+            .value_name("BACKEND")
+            .takes_value(true)
+            .possible_values(&["gpu", "cpu"])
+            .default_value("gpu")
+            .help(
+                "Where to verify shred signatures. gpu uses the GPU when --cuda loaded the perf \
+                 libs and the CPU otherwise. cpu always uses the CPU, with the SIMD instructions \
+                 it supports, leaving the GPU to transaction signatures",
+            ),
+    )
     .arg(
         Arg::with_name("retransmit_xdp_interface")
             .hidden(hidden_unless_forced())
@@ -1377,11 +1978,27 @@ pub fn add_args<'a>(app: App<'a, 'a>, default_args: &'a DefaultArgs) -> App<'a,
     )
     .args(&pub_sub_config::args(/*test_validator:*/ false))
     .args(&json_rpc_config::args())
+    .args(&file_serving_config::args())
+    .args(&rpc_usage_config::args())
+    .args(&rpc_simulation_config::args())
+    .args(&rpc_backpressure_config::args())
+    .args(&rpc_response_limits::args())
     .args(&rpc_bigtable_config::args())
     .args(&send_transaction_config::args())
     .args(&rpc_bootstrap_config::args())
 }
 
+pub(crate) fn is_valid_cpu_core_index(value: String) -> std::result::Result<(), String> {
+    let core_index = usize::from_str(&value).map_err(|e| e.to_string())?;
+    let max_index = core_affinity::get_core_ids()
+        .map(|cids| cids.len() - 1)
+        .unwrap_or(0);
+    if core_index > max_index {
+        return Err(format!("core index must be in the range [0, {max_index}]"));
+    }
+    Ok(())
+}
+
 fn validators_set(
     identity_pubkey: &Pubkey,
     matches: &ArgMatches<'_>,
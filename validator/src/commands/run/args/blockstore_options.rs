@@ -8,7 +8,7 @@ use {
         AccessType, BlockstoreCompressionType, BlockstoreOptions, BlockstoreRecoveryMode,
         LedgerColumnOptions,
     },
-    std::num::NonZeroUsize,
+    std::{num::NonZeroUsize, time::Duration},
 };
 
 impl FromClapArgMatches for BlockstoreOptions {
@@ -42,6 +42,10 @@ impl FromClapArgMatches for BlockstoreOptions {
 
         let rocksdb_flush_threads = value_t!(matches, RocksdbFlushThreadsArg::NAME, NonZeroUsize)?;
 
+        let wal_flush_interval = value_t!(matches, "rocksdb_wal_flush_interval_ms", u64)
+            .ok()
+            .map(Duration::from_millis);
+
         Ok(BlockstoreOptions {
             recovery_mode,
             column_options,
@@ -49,6 +53,11 @@ impl FromClapArgMatches for BlockstoreOptions {
             access_type: AccessType::Primary,
             num_rocksdb_compaction_threads: rocksdb_compaction_threads,
             num_rocksdb_flush_threads: rocksdb_flush_threads,
+            write_rate_limit_bytes_per_second: None,
+            wal_flush_interval,
+            wal_bytes_per_sync: value_t!(matches, "rocksdb_wal_bytes_per_sync", u64).unwrap_or(0),
+            bytes_per_sync: value_t!(matches, "rocksdb_bytes_per_sync", u64).unwrap_or(0),
+            enable_address_time_index: matches.is_present("enable_rpc_address_time_index"),
         })
     }
 }
@@ -199,4 +208,39 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rocksdb_wal_options() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        let expected_args = RunArgs {
+            blockstore_options: BlockstoreOptions {
+                wal_flush_interval: Some(Duration::from_millis(5)),
+                wal_bytes_per_sync: 1048576,
+                bytes_per_sync: 2097152,
+                ..default_run_args.blockstore_options.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rocksdb-wal-flush-interval-ms",
+                "5",
+                "--rocksdb-wal-bytes-per-sync",
+                "1048576",
+                "--rocksdb-bytes-per-sync",
+                "2097152",
+            ],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rocksdb_wal_flush_interval_invalid() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        verify_args_struct_by_command_run_is_error_with_identity_setup(
+            default_run_args,
+            vec!["--rocksdb-wal-flush-interval-ms", "0"],
+        );
+    }
 }
@@ -0,0 +1,131 @@
+use {
+    crate::commands::{FromClapArgMatches, Result},
+    clap::{value_t, Arg, ArgMatches},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_rpc::file_serving::FileServingConfig,
+    std::num::{NonZeroU64, NonZeroUsize},
+};
+
+const DEFAULT_SNAPSHOT_SERVING: &str = "enabled";
+const MB: u64 = 1_024 * 1_024;
+
+impl FromClapArgMatches for FileServingConfig {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(FileServingConfig {
+            enabled: matches.value_of("snapshot_serving") == Some("enabled"),
+            max_downloads: value_t!(matches, "snapshot_serving_max_downloads", NonZeroUsize).ok(),
+            max_downloads_per_ip: value_t!(
+                matches,
+                "snapshot_serving_max_downloads_per_ip",
+                NonZeroUsize
+            )
+            .ok(),
+            max_bytes_per_second: value_t!(
+                matches,
+                "snapshot_serving_max_mb_per_second",
+                NonZeroU64
+            )
+            .ok()
+            .map(|mb| mb.saturating_mul(NonZeroU64::new(MB).unwrap())),
+        })
+    }
+}
+
+pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("snapshot_serving")
+            .long("snapshot-serving")
+            .value_name("MODE")
+            .takes_value(true)
+            .possible_values(&["enabled", "disabled"])
+            .default_value(DEFAULT_SNAPSHOT_SERVING)
+            .help(
+                "Serve the genesis and snapshot archives of this node to other nodes over the \
+                 RPC port, so they can use it to join the cluster",
+            ),
+        Arg::with_name("snapshot_serving_max_downloads")
+            .long("snapshot-serving-max-downloads")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Maximum number of genesis and snapshot downloads served at the same time. \
+                 Further requests are answered with 429 Too Many Requests",
+            ),
+        Arg::with_name("snapshot_serving_max_downloads_per_ip")
+            .long("snapshot-serving-max-downloads-per-ip")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Maximum number of genesis and snapshot downloads served at the same time to a \
+                 single client address. Behind a reverse proxy, see --rpc-trusted-proxy",
+            ),
+        Arg::with_name("snapshot_serving_max_mb_per_second")
+            .long("snapshot-serving-max-mb-per-second")
+            .value_name("MEGABYTES")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroU64>)
+            .help("Bandwidth shared by all genesis and snapshot downloads"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::run::args::{
+            tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
+        },
+        solana_rpc::rpc::JsonRpcConfig,
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_snapshot_serving_disabled() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                file_serving: FileServingConfig {
+                    enabled: false,
+                    ..FileServingConfig::default()
+                },
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec!["--snapshot-serving", "disabled"],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_snapshot_serving_limits() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                file_serving: FileServingConfig {
+                    enabled: true,
+                    max_downloads: NonZeroUsize::new(8),
+                    max_downloads_per_ip: NonZeroUsize::new(2),
+                    max_bytes_per_second: NonZeroU64::new(100 * MB),
+                },
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--snapshot-serving-max-downloads",
+                "8",
+                "--snapshot-serving-max-downloads-per-ip",
+                "2",
+                "--snapshot-serving-max-mb-per-second",
+                "100",
+            ],
+            expected_args,
+        );
+    }
+}
@@ -1,15 +1,23 @@
 use {
     crate::commands::{FromClapArgMatches, Result},
-    clap::{value_t, Arg, ArgMatches},
+    clap::{value_t, values_t, Arg, ArgMatches},
     solana_accounts_db::accounts_index::AccountSecondaryIndexes,
     solana_clap_utils::input_validators::is_parsable,
-    solana_rpc::rpc::{JsonRpcConfig, RpcBigtableConfig},
-    std::sync::LazyLock,
+    solana_rpc::{
+        file_serving::FileServingConfig,
+        rpc::{JsonRpcConfig, RpcBigtableConfig},
+        rpc_response_limits::RpcResponseLimits,
+        rpc_usage::RpcUsageConfig,
+        send_transaction_backpressure::RpcBackpressureConfig,
+        simulation_quota::RpcSimulationConfig,
+    },
+    std::{net::IpAddr, sync::LazyLock},
 };
 
 static DEFAULT_HEALTH_CHECK_SLOT_DISTANCE: LazyLock<String> = LazyLock::new(|| {
     solana_rpc_client_api::request::DELINQUENT_VALIDATOR_SLOT_DISTANCE.to_string()
 });
+const DEFAULT_HEALTH_CHECK_MAX_VOTE_AGE_SECS: &str = "60";
 static DEFAULT_MAX_MULTIPLE_ACCOUNTS: LazyLock<String> =
     LazyLock::new(|| solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS.to_string());
 static DEFAULT_RPC_THREADS: LazyLock<String> = LazyLock::new(|| num_cpus::get().to_string());
@@ -29,10 +37,17 @@ impl FromClapArgMatches for JsonRpcConfig {
             None
         };
 
+        let usage = if matches.is_present("rpc_usage_tracking") {
+            Some(RpcUsageConfig::from_clap_arg_match(matches)?)
+        } else {
+            None
+        };
+
         Ok(JsonRpcConfig {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_extended_tx_metadata_storage: matches
                 .is_present("enable_extended_tx_metadata_storage"),
+            transaction_history_paused: matches.is_present("rpc_transaction_history_paused"),
             faucet_addr: matches
                 .value_of("rpc_faucet_addr")
                 .map(|address| {
@@ -44,6 +59,12 @@ impl FromClapArgMatches for JsonRpcConfig {
                 })
                 .transpose()?,
             health_check_slot_distance: value_t!(matches, "health_check_slot_distance", u64)?,
+            health_check_max_vote_age_secs: Some(value_t!(
+                matches,
+                "health_check_max_vote_age",
+                u64
+            )?),
+            known_validators: None,
             skip_preflight_health_check: matches.is_present("skip_preflight_health_check"),
             rpc_bigtable_config,
             max_multiple_accounts: Some(value_t!(matches, "rpc_max_multiple_accounts", usize)?),
@@ -55,6 +76,15 @@ impl FromClapArgMatches for JsonRpcConfig {
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
             max_request_body_size: Some(value_t!(matches, "rpc_max_request_body_size", usize)?),
             disable_health_check: false,
+            file_serving: FileServingConfig::from_clap_arg_match(matches)?,
+            trusted_proxies: values_t!(matches, "rpc_trusted_proxies", IpAddr).unwrap_or_default(),
+            usage,
+            simulation: RpcSimulationConfig::from_clap_arg_match(matches)?,
+            backpressure: RpcBackpressureConfig::from_clap_arg_match(matches)?,
+            response_limits: RpcResponseLimits::from_clap_arg_match(matches)?,
+            read_only: false,
+            enable_transaction_priority_preview: matches
+                .is_present("enable_rpc_transaction_priority_preview"),
         })
     }
 }
@@ -84,6 +114,23 @@ pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
                 "Include CPI inner instructions, logs, and return data in the historical \
                  transaction info stored",
             ),
+        Arg::with_name("rpc_transaction_history_paused")
+            .long("rpc-transaction-history-paused")
+            .requires("enable_rpc_transaction_history")
+            .takes_value(false)
+            .help(
+                "Start without writing historical transaction info. Writing can be started and \
+                 stopped with `agave-validator transaction-history` while the validator runs",
+            ),
+        Arg::with_name("enable_rpc_address_time_index")
+            .long("enable-rpc-address-time-index")
+            .requires("enable_rpc_transaction_history")
+            .takes_value(false)
+            .help(
+                "Also index the historical transactions of each address by block time, enabling \
+                 the 'getSignaturesForAddressByTime' API. Only transactions stored while enabled \
+                 are indexed",
+            ),
         Arg::with_name("rpc_faucet_addr")
             .long("rpc-faucet-address")
             .value_name("HOST:PORT")
@@ -100,6 +147,16 @@ pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
                  slot is within the specified number of slots from the cluster's latest \
                  optimistically confirmed slot",
             ),
+        Arg::with_name("health_check_max_vote_age")
+            .long("health-check-max-vote-age")
+            .value_name("SECONDS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .default_value(DEFAULT_HEALTH_CHECK_MAX_VOTE_AGE_SECS)
+            .help(
+                "Report this validator as unhealthy in the 'getHealthDetailed' RPC method if the \
+                 cluster's latest optimistically confirmed slot was observed longer ago than this",
+            ),
         Arg::with_name("skip_preflight_health_check")
             .long("skip-preflight-health-check")
             .takes_value(false)
@@ -161,6 +218,14 @@ pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .takes_value(false)
             .requires("enable_rpc_transaction_history")
             .help("Verifies blockstore roots on boot and fixes any gaps"),
+        Arg::with_name("enable_rpc_transaction_priority_preview")
+            .long("enable-rpc-transaction-priority-preview")
+            .takes_value(false)
+            .requires("full_rpc_api")
+            .help(
+                "Enable the JSON RPC 'previewTransactionPriority' API. The banking stage then \
+                 takes a snapshot of the transactions it buffers every second",
+            ),
         Arg::with_name("rpc_max_request_body_size")
             .long("rpc-max-request-body-size")
             .value_name("BYTES")
@@ -168,6 +233,17 @@ pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .validator(is_parsable::<usize>)
             .default_value(&DEFAULT_RPC_MAX_REQUEST_BODY_SIZE)
             .help("The maximum request body size accepted by rpc service"),
+        Arg::with_name("rpc_trusted_proxies")
+            .long("rpc-trusted-proxy")
+            .value_name("IP_ADDRESS")
+            .takes_value(true)
+            .multiple(true)
+            .validator(is_parsable::<IpAddr>)
+            .help(
+                "Address of a reverse proxy in front of the RPC port. The clients of requests \
+                 from it are told apart by the X-Forwarded-For header it sets, instead of by the \
+                 address of the proxy",
+            ),
     ]
 }
 
@@ -181,6 +257,7 @@ pub(super) mod tests {
             pub_sub_config::DEFAULT_RPC_PUBSUB_NUM_NOTIFICATION_THREADS,
             tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
         },
+        solana_ledger::blockstore_options::BlockstoreOptions,
         solana_rpc::rpc_pubsub_service::PubSubConfig,
         std::{
             net::{Ipv4Addr, SocketAddr},
@@ -191,6 +268,9 @@ pub(super) mod tests {
     pub fn default_json_rpc_config() -> JsonRpcConfig {
         JsonRpcConfig {
             health_check_slot_distance: DEFAULT_HEALTH_CHECK_SLOT_DISTANCE.parse().unwrap(),
+            health_check_max_vote_age_secs: Some(
+                DEFAULT_HEALTH_CHECK_MAX_VOTE_AGE_SECS.parse().unwrap(),
+            ),
             max_multiple_accounts: Some(DEFAULT_MAX_MULTIPLE_ACCOUNTS.parse().unwrap()),
             rpc_threads: DEFAULT_RPC_THREADS.parse().unwrap(),
             rpc_blocking_threads: DEFAULT_RPC_BLOCKING_THREADS.parse().unwrap(),
@@ -242,6 +322,78 @@ pub(super) mod tests {
         }
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_transaction_history_paused() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    enable_rpc_transaction_history: true,
+                    transaction_history_paused: true,
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--enable-rpc-transaction-history", // required by rpc_transaction_history_paused
+                    "--rpc-transaction-history-paused",
+                ],
+                expected_args,
+            );
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_enable_rpc_address_time_index() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    enable_rpc_transaction_history: true,
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                blockstore_options: BlockstoreOptions {
+                    enable_address_time_index: true,
+                    ..default_run_args.blockstore_options.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--enable-rpc-transaction-history", // required by enable_rpc_address_time_index
+                    "--enable-rpc-address-time-index",
+                ],
+                expected_args,
+            );
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_enable_rpc_transaction_priority_preview() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    full_api: true,
+                    enable_transaction_priority_preview: true,
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec![
+                    "--full-rpc-api", // required by enable_rpc_transaction_priority_preview
+                    "--enable-rpc-transaction-priority-preview",
+                ],
+                expected_args,
+            );
+        }
+    }
+
     #[test]
     fn verify_args_struct_by_command_run_with_rpc_faucet_addr() {
         {
@@ -280,6 +432,25 @@ pub(super) mod tests {
         }
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_health_check_max_vote_age() {
+        {
+            let default_run_args = crate::commands::run::args::RunArgs::default();
+            let expected_args = RunArgs {
+                json_rpc_config: JsonRpcConfig {
+                    health_check_max_vote_age_secs: Some(30),
+                    ..default_run_args.json_rpc_config.clone()
+                },
+                ..default_run_args.clone()
+            };
+            verify_args_struct_by_command_run_with_identity_setup(
+                default_run_args,
+                vec!["--health-check-max-vote-age", "30"],
+                expected_args,
+            );
+        }
+    }
+
     #[test]
     fn verify_args_struct_by_command_run_with_skip_preflight_health_check() {
         {
@@ -458,6 +629,28 @@ pub(super) mod tests {
         }
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_trusted_proxies() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                trusted_proxies: vec!["10.0.0.1".parse().unwrap(), "2001:db8::1".parse().unwrap()],
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rpc-trusted-proxy",
+                "10.0.0.1",
+                "--rpc-trusted-proxy",
+                "2001:db8::1",
+            ],
+            expected_args,
+        );
+    }
+
     #[test]
     fn test_default_health_check_slot_distance_unchanged() {
         assert_eq!(*DEFAULT_HEALTH_CHECK_SLOT_DISTANCE, "128");
@@ -33,6 +33,12 @@ static DEFAULT_TEST_RPC_PUBSUB_QUEUE_CAPACITY_BYTES: LazyLock<String> = LazyLock
         .to_string()
 });
 
+static DEFAULT_RPC_PUBSUB_HTTP2_MAX_CONCURRENT_STREAMS: LazyLock<String> = LazyLock::new(|| {
+    PubSubConfig::default()
+        .http2_max_concurrent_streams
+        .to_string()
+});
+
 const DEFAULT_RPC_PUBSUB_WORKER_THREADS: &str = "4";
 static DEFAULT_TEST_RPC_PUBSUB_WORKER_THREADS: LazyLock<String> =
     LazyLock::new(|| PubSubConfig::default_for_tests().worker_threads.to_string());
@@ -126,6 +132,23 @@ pub(crate) fn args<'a, 'b>(test_validator: bool) -> Vec<Arg<'a, 'b>> {
             .default_value(DEFAULT_RPC_PUBSUB_WORKER_THREADS)
             .help("PubSub worker threads"),
         rpc_pubsub_notification_threads,
+        Arg::with_name("rpc_pubsub_enable_http2")
+            .long("rpc-pubsub-enable-http2")
+            .takes_value(false)
+            .help(
+                "Also accept HTTP/2 connections on the RPC PubSub port. Every stream of an HTTP/2 \
+                 connection is a separate WebSocket (RFC 8441), so clients can spread their \
+                 subscriptions over many streams without opening more connections",
+            ),
+        Arg::with_name("rpc_pubsub_http2_max_concurrent_streams")
+            .long("rpc-pubsub-http2-max-concurrent-streams")
+            .takes_value(true)
+            .value_name("NUMBER")
+            .validator(is_parsable::<u32>)
+            .default_value(&DEFAULT_RPC_PUBSUB_HTTP2_MAX_CONCURRENT_STREAMS)
+            .help(
+                "The maximum number of WebSocket streams on a single RPC PubSub HTTP/2 connection",
+            ),
     ]
 }
 
@@ -145,6 +168,12 @@ impl FromClapArgMatches for PubSubConfig {
             notification_threads: value_t!(matches, "rpc_pubsub_notification_threads", usize)
                 .ok()
                 .and_then(NonZeroUsize::new),
+            enable_http2: matches.is_present("rpc_pubsub_enable_http2"),
+            http2_max_concurrent_streams: value_t!(
+                matches,
+                "rpc_pubsub_http2_max_concurrent_streams",
+                u32
+            )?,
         })
     }
 }
@@ -268,6 +297,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_http2() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        let expected_args = RunArgs {
+            pub_sub_config: PubSubConfig {
+                enable_http2: true,
+                http2_max_concurrent_streams: 50,
+                ..default_run_args.pub_sub_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rpc-pubsub-enable-http2",
+                "--rpc-pubsub-http2-max-concurrent-streams",
+                "50",
+            ],
+            expected_args,
+        );
+    }
+
     #[test]
     fn verify_args_struct_by_command_run_with_notification_threads() {
         let default_run_args = crate::commands::run::args::RunArgs::default();
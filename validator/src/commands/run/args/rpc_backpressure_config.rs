@@ -0,0 +1,90 @@
+use {
+    crate::commands::{FromClapArgMatches, Result},
+    clap::{value_t, Arg, ArgMatches},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_rpc::send_transaction_backpressure::RpcBackpressureConfig,
+    std::{num::NonZeroUsize, sync::LazyLock, time::Duration},
+};
+
+static DEFAULT_RPC_SEND_TRANSACTION_RETRY_AFTER_MS: LazyLock<String> = LazyLock::new(|| {
+    RpcBackpressureConfig::default()
+        .retry_after
+        .as_millis()
+        .to_string()
+});
+
+impl FromClapArgMatches for RpcBackpressureConfig {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(RpcBackpressureConfig {
+            max_banking_queue_depth: value_t!(
+                matches,
+                "rpc_send_transaction_max_banking_queue_depth",
+                NonZeroUsize
+            )
+            .ok(),
+            retry_after: Duration::from_millis(value_t!(
+                matches,
+                "rpc_send_transaction_retry_after_ms",
+                u64
+            )?),
+        })
+    }
+}
+
+pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("rpc_send_transaction_max_banking_queue_depth")
+            .long("rpc-send-transaction-max-banking-queue-depth")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Reject sendTransaction requests while the banking stage has this many \
+                 transactions queued, telling clients to retry later instead of accepting \
+                 transactions the node would drop",
+            ),
+        Arg::with_name("rpc_send_transaction_retry_after_ms")
+            .long("rpc-send-transaction-retry-after-ms")
+            .value_name("MILLISECONDS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .default_value(&DEFAULT_RPC_SEND_TRANSACTION_RETRY_AFTER_MS)
+            .help("Delay after which clients rejected for congestion are told to retry"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::run::args::{
+            tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
+        },
+        solana_rpc::rpc::JsonRpcConfig,
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_backpressure() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                backpressure: RpcBackpressureConfig {
+                    max_banking_queue_depth: NonZeroUsize::new(50_000),
+                    retry_after: Duration::from_millis(1000),
+                },
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rpc-send-transaction-max-banking-queue-depth",
+                "50000",
+                "--rpc-send-transaction-retry-after-ms",
+                "1000",
+            ],
+            expected_args,
+        );
+    }
+}
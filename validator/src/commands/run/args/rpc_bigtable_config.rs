@@ -3,7 +3,7 @@ use {
     clap::{value_t, Arg, ArgMatches},
     solana_clap_utils::{hidden_unless_forced, input_validators::is_parsable},
     solana_rpc::rpc::RpcBigtableConfig,
-    std::{sync::LazyLock, time::Duration},
+    std::{num::NonZeroUsize, sync::LazyLock, time::Duration},
 };
 
 const DEFAULT_BIGTABLE_INSTANCE_NAME: &str = solana_storage_bigtable::DEFAULT_INSTANCE_NAME;
@@ -11,6 +11,8 @@ const DEFAULT_BIGTABLE_APP_PROFILE_ID: &str = solana_storage_bigtable::DEFAULT_A
 const DEFAULT_BIGTABLE_TIMEOUT: &str = "30";
 static DEFAULT_BIGTABLE_MAX_MESSAGE_SIZE: LazyLock<String> =
     LazyLock::new(|| solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE.to_string());
+static DEFAULT_BIGTABLE_CONNECTION_POOL_SIZE: LazyLock<String> =
+    LazyLock::new(|| solana_storage_bigtable::DEFAULT_CONNECTION_POOL_SIZE.to_string());
 
 impl FromClapArgMatches for RpcBigtableConfig {
     fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
@@ -22,6 +24,15 @@ impl FromClapArgMatches for RpcBigtableConfig {
                 .ok()
                 .map(Duration::from_secs),
             max_message_size: value_t!(matches, "rpc_bigtable_max_message_size", usize)?,
+            credential_path: matches
+                .value_of("rpc_bigtable_credential_path")
+                .map(String::from),
+            connection_pool_size: value_t!(
+                matches,
+                "rpc_bigtable_connection_pool_size",
+                NonZeroUsize
+            )?,
+            required: matches.is_present("rpc_bigtable_required"),
         })
     }
 }
@@ -59,6 +70,30 @@ pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .takes_value(true)
             .default_value(&DEFAULT_BIGTABLE_MAX_MESSAGE_SIZE)
             .help("Max encoding and decoding message size used in Bigtable Grpc client"),
+        Arg::with_name("rpc_bigtable_credential_path")
+            .long("rpc-bigtable-credential-path")
+            .value_name("FILEPATH")
+            .takes_value(true)
+            .help(
+                "Path to the Google Cloud service account credentials used to access Bigtable. \
+                 Defaults to the GOOGLE_APPLICATION_CREDENTIALS environment variable. The file \
+                 is periodically checked for changes, so credentials can be rotated without a \
+                 restart",
+            ),
+        Arg::with_name("rpc_bigtable_connection_pool_size")
+            .long("rpc-bigtable-connection-pool-size")
+            .value_name("NUMBER")
+            .validator(is_parsable::<NonZeroUsize>)
+            .takes_value(true)
+            .default_value(&DEFAULT_BIGTABLE_CONNECTION_POOL_SIZE)
+            .help("Number of connections to open to Bigtable, requests are spread across them"),
+        Arg::with_name("rpc_bigtable_required")
+            .long("rpc-bigtable-required")
+            .takes_value(false)
+            .help(
+                "Exit if the Bigtable connection cannot be initialized at startup, rather than \
+                 serving RPC from the local ledger only",
+            ),
     ]
 }
 
@@ -234,6 +269,86 @@ mod tests {
         );
     }
 
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_bigtable_credential_path() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                enable_rpc_transaction_history: true,
+                rpc_bigtable_config: Some(RpcBigtableConfig {
+                    enable_bigtable_ledger_upload: true,
+                    credential_path: Some("/path/to/credentials.json".to_string()),
+                    ..default_rpc_bigtable_config()
+                }),
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--enable-rpc-transaction-history", // required by enable-bigtable-ledger-upload
+                "--enable-bigtable-ledger-upload",  // required by all rpc_bigtable_config
+                "--rpc-bigtable-credential-path",
+                "/path/to/credentials.json",
+            ],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_bigtable_connection_pool_size() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                enable_rpc_transaction_history: true,
+                rpc_bigtable_config: Some(RpcBigtableConfig {
+                    enable_bigtable_ledger_upload: true,
+                    connection_pool_size: NonZeroUsize::new(8).unwrap(),
+                    ..default_rpc_bigtable_config()
+                }),
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--enable-rpc-transaction-history", // required by enable-bigtable-ledger-upload
+                "--enable-bigtable-ledger-upload",  // required by all rpc_bigtable_config
+                "--rpc-bigtable-connection-pool-size",
+                "8",
+            ],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_bigtable_required() {
+        let default_run_args = crate::commands::run::args::RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                enable_rpc_transaction_history: true,
+                rpc_bigtable_config: Some(RpcBigtableConfig {
+                    enable_bigtable_ledger_upload: true,
+                    required: true,
+                    ..default_rpc_bigtable_config()
+                }),
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--enable-rpc-transaction-history", // required by enable-bigtable-ledger-upload
+                "--enable-bigtable-ledger-upload",  // required by all rpc_bigtable_config
+                "--rpc-bigtable-required",
+            ],
+            expected_args,
+        );
+    }
+
     #[test]
     fn test_default_bigtable_instance_name_unchanged() {
         assert_eq!(DEFAULT_BIGTABLE_INSTANCE_NAME, "solana-ledger");
@@ -253,4 +368,9 @@ mod tests {
     fn test_default_bigtable_max_message_size_unchanged() {
         assert_eq!(*DEFAULT_BIGTABLE_MAX_MESSAGE_SIZE, "67108864");
     }
+
+    #[test]
+    fn test_default_bigtable_connection_pool_size_unchanged() {
+        assert_eq!(*DEFAULT_BIGTABLE_CONNECTION_POOL_SIZE, "1");
+    }
 }
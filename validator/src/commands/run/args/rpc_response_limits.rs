@@ -0,0 +1,128 @@
+use {
+    crate::commands::{Error, FromClapArgMatches, Result},
+    clap::{Arg, ArgMatches},
+    serde::Deserialize,
+    solana_rpc::rpc_response_limits::RpcResponseLimits,
+    std::fs,
+};
+
+/// The file given with `--rpc-limits`:
+///
+/// ```toml
+/// [rpc.limits]
+/// default-max-response-bytes = 104857600
+///
+/// [rpc.limits.methods]
+/// getProgramAccounts = 52428800
+/// getSignaturesForAddress = 1048576
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RpcLimitsFile {
+    rpc: RpcLimitsSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RpcLimitsSection {
+    limits: RpcResponseLimits,
+}
+
+impl FromClapArgMatches for RpcResponseLimits {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        let Some(path) = matches.value_of("rpc_limits") else {
+            return Ok(RpcResponseLimits::default());
+        };
+        let contents = fs::read_to_string(path)
+            .map_err(|err| Error::Dynamic(format!("unable to read {path}: {err}").into()))?;
+        let RpcLimitsFile {
+            rpc: RpcLimitsSection { limits },
+        } = toml::from_str(&contents)
+            .map_err(|err| Error::Dynamic(format!("unable to parse {path}: {err}").into()))?;
+        limits
+            .verify()
+            .map_err(|err| Error::Dynamic(format!("{path}: {err}").into()))?;
+        Ok(limits)
+    }
+}
+
+pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![Arg::with_name("rpc_limits")
+        .long("rpc-limits")
+        .value_name("FILE")
+        .takes_value(true)
+        .help(
+            "TOML file with the maximum response size of RPC methods, in its [rpc.limits] \
+             section. A request whose response would be larger fails with the part of the \
+             response within the limit, and where the method can continue, a cursor to request \
+             the rest from",
+        )]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::run::args::{
+            tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
+        },
+        solana_rpc::rpc::JsonRpcConfig,
+        std::{collections::HashMap, io::Write},
+        tempfile::NamedTempFile,
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_limits() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+            [rpc.limits]
+            default-max-response-bytes = 1000000
+
+            [rpc.limits.methods]
+            getProgramAccounts = 500000
+            "#
+        )
+        .unwrap();
+
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                response_limits: RpcResponseLimits {
+                    default_max_response_bytes: Some(1_000_000),
+                    max_response_bytes: HashMap::from([(
+                        "getProgramAccounts".to_string(),
+                        500_000,
+                    )]),
+                },
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec!["--rpc-limits", file.path().to_str().unwrap()],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn test_rpc_limits_unknown_method() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+            [rpc.limits.methods]
+            getBalance = 100
+            "#
+        )
+        .unwrap();
+        let matches = clap::App::new("test").args(&args()).get_matches_from(vec![
+            "test",
+            "--rpc-limits",
+            file.path().to_str().unwrap(),
+        ]);
+        assert!(RpcResponseLimits::from_clap_arg_match(&matches).is_err());
+    }
+}
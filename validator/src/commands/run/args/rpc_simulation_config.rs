@@ -0,0 +1,79 @@
+use {
+    crate::commands::{FromClapArgMatches, Result},
+    clap::{value_t, Arg, ArgMatches},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_rpc::simulation_quota::RpcSimulationConfig,
+    std::num::{NonZeroU32, NonZeroUsize},
+};
+
+impl FromClapArgMatches for RpcSimulationConfig {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(RpcSimulationConfig {
+            max_compute_units: value_t!(matches, "rpc_simulation_max_compute_units", NonZeroU32)
+                .ok()
+                .map(NonZeroU32::get),
+            max_concurrent: value_t!(matches, "rpc_simulation_max_concurrent", NonZeroUsize).ok(),
+        })
+    }
+}
+
+pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("rpc_simulation_max_compute_units")
+            .long("rpc-simulation-max-compute-units")
+            .value_name("UNITS")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroU32>)
+            .help(
+                "Reject simulateTransaction requests for transactions requesting more compute \
+                 units than this. Transactions without a compute unit limit instruction request \
+                 the default limit of their instructions",
+            ),
+        Arg::with_name("rpc_simulation_max_concurrent")
+            .long("rpc-simulation-max-concurrent")
+            .value_name("NUMBER")
+            .takes_value(true)
+            .validator(is_parsable::<NonZeroUsize>)
+            .help(
+                "Maximum number of simulateTransaction requests run at the same time. Further \
+                 requests are rejected so that simulations do not take the cores replay needs. \
+                 The preflight checks of sendTransaction are not limited",
+            ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::run::args::{
+            tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
+        },
+        solana_rpc::rpc::JsonRpcConfig,
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_simulation_limits() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                simulation: RpcSimulationConfig {
+                    max_compute_units: Some(400_000),
+                    max_concurrent: NonZeroUsize::new(4),
+                },
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rpc-simulation-max-compute-units",
+                "400000",
+                "--rpc-simulation-max-concurrent",
+                "4",
+            ],
+            expected_args,
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use {
+    crate::commands::{Error, FromClapArgMatches, Result},
+    clap::{value_t, Arg, ArgMatches},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_rpc::rpc_usage::RpcUsageConfig,
+    std::{collections::HashMap, fs, path::PathBuf, sync::LazyLock, time::Duration},
+};
+
+static DEFAULT_RPC_USAGE_REPORT_INTERVAL: LazyLock<String> = LazyLock::new(|| {
+    solana_rpc::rpc_usage::DEFAULT_RPC_USAGE_REPORT_INTERVAL
+        .as_secs()
+        .to_string()
+});
+
+impl FromClapArgMatches for RpcUsageConfig {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(RpcUsageConfig {
+            client_header: matches
+                .value_of("rpc_usage_client_header")
+                .map(str::to_ascii_lowercase),
+            client_tokens: matches
+                .value_of("rpc_usage_client_tokens")
+                .map(read_client_tokens)
+                .transpose()?
+                .unwrap_or_default(),
+            report_dir: matches.value_of("rpc_usage_report_dir").map(PathBuf::from),
+            report_interval: Duration::from_secs(value_t!(
+                matches,
+                "rpc_usage_report_interval",
+                u64
+            )?),
+        })
+    }
+}
+
+/// Reads a file with a `NAME TOKEN` line per client, skipping empty lines and `#` comments
+fn read_client_tokens(path: &str) -> Result<HashMap<String, String>> {
+    let mut client_tokens = HashMap::new();
+    for (line_number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(token), None) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(Error::Dynamic(Box::<dyn std::error::Error>::from(format!(
+                "{path}:{}: expected a client name and token",
+                line_number + 1
+            ))));
+        };
+        client_tokens.insert(token.to_string(), name.to_string());
+    }
+    Ok(client_tokens)
+}
+
+pub(crate) fn args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("rpc_usage_tracking")
+            .long("rpc-usage-tracking")
+            .takes_value(false)
+            .help(
+                "Account the JSON RPC requests, response bytes and handling time per client. \
+                 The usage is available over the admin RPC and can be written to periodic \
+                 reports with --rpc-usage-report-dir",
+            ),
+        Arg::with_name("rpc_usage_client_header")
+            .long("rpc-usage-client-header")
+            .value_name("HEADER")
+            .takes_value(true)
+            .requires("rpc_usage_tracking")
+            .requires("rpc_usage_client_tokens")
+            .help(
+                "Request header with the API token identifying the client. Requests without it \
+                 are accounted to the client address, and requests with a token not in \
+                 --rpc-usage-client-tokens are accounted together as 'invalid-token'",
+            ),
+        Arg::with_name("rpc_usage_client_tokens")
+            .long("rpc-usage-client-tokens")
+            .value_name("FILE")
+            .takes_value(true)
+            .requires("rpc_usage_client_header")
+            .help(
+                "File with a 'NAME TOKEN' line for every client identified by an API token, \
+                 whose usage is accounted to NAME",
+            ),
+        Arg::with_name("rpc_usage_report_dir")
+            .long("rpc-usage-report-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .requires("rpc_usage_tracking")
+            .help("Write a JSON usage report to this directory at the end of every period"),
+        Arg::with_name("rpc_usage_report_interval")
+            .long("rpc-usage-report-interval")
+            .value_name("SECONDS")
+            .takes_value(true)
+            .validator(is_parsable::<u64>)
+            .default_value(&DEFAULT_RPC_USAGE_REPORT_INTERVAL)
+            .help("Length of the usage reporting period"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::run::args::{
+            tests::verify_args_struct_by_command_run_with_identity_setup, RunArgs,
+        },
+        solana_rpc::rpc::JsonRpcConfig,
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_usage_tracking() {
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                usage: Some(RpcUsageConfig::default()),
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec!["--rpc-usage-tracking"],
+            expected_args,
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_run_with_rpc_usage_reports() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let client_tokens_path = tmp_dir.path().join("client-tokens");
+        fs::write(
+            &client_tokens_path,
+            "# name token\ncustomer-a secret-a\n\ncustomer-b secret-b\n",
+        )
+        .unwrap();
+        let default_run_args = RunArgs::default();
+        let expected_args = RunArgs {
+            json_rpc_config: JsonRpcConfig {
+                usage: Some(RpcUsageConfig {
+                    client_header: Some("x-api-key".to_string()),
+                    client_tokens: HashMap::from([
+                        ("secret-a".to_string(), "customer-a".to_string()),
+                        ("secret-b".to_string(), "customer-b".to_string()),
+                    ]),
+                    report_dir: Some(PathBuf::from("/tmp/rpc-usage")),
+                    report_interval: Duration::from_secs(600),
+                }),
+                ..default_run_args.json_rpc_config.clone()
+            },
+            ..default_run_args.clone()
+        };
+        verify_args_struct_by_command_run_with_identity_setup(
+            default_run_args,
+            vec![
+                "--rpc-usage-tracking",
+                "--rpc-usage-client-header",
+                "X-Api-Key",
+                "--rpc-usage-client-tokens",
+                client_tokens_path.to_str().unwrap(),
+                "--rpc-usage-report-dir",
+                "/tmp/rpc-usage",
+                "--rpc-usage-report-interval",
+                "600",
+            ],
+            expected_args,
+        );
+    }
+}
@@ -1,15 +1,27 @@
 use {
     crate::{
+        admin_rpc_http_service::{self, AdminRpcHttpConfig},
         admin_rpc_service::{self, load_staked_nodes_overrides, StakedNodesOverrides},
         bootstrap,
         cli::{self},
-        commands::{run::args::RunArgs, FromClapArgMatches},
+        commands::{
+            doctor,
+            run::{
+                args::RunArgs,
+                preflight::{self, AccountsIndexMemoryCheck},
+            },
+            FromClapArgMatches,
+        },
+        crash_report::CrashReporter,
+        diagnostics_bundle::{self, DiagnosticsBundler},
+        intent_log::IntentLog,
         ledger_lockfile, lock_ledger,
+        throttle_profile::ThrottleProfile,
     },
     agave_logger::redirect_stderr_to_file,
     agave_snapshots::{
         paths::BANK_SNAPSHOTS_DIR,
-        snapshot_config::{SnapshotConfig, SnapshotUsage},
+        snapshot_config::{ArchiveUploadConfig, SnapshotConfig, SnapshotUsage},
         ArchiveFormat, SnapshotInterval, SnapshotVersion,
     },
     clap::{crate_name, value_t, value_t_or_exit, values_t, values_t_or_exit, ArgMatches},
@@ -20,6 +32,7 @@ use {
         accounts_db::{AccountShrinkThreshold, AccountsDbConfig, MarkObsoleteAccounts},
         accounts_file::StorageAccess,
         accounts_index::{AccountSecondaryIndexes, AccountsIndexConfig, IndexLimitMb, ScanFilter},
+        storage_manifest::STORAGE_MANIFEST_FILE_NAME,
         utils::{
             create_all_accounts_run_and_snapshot_dirs, create_and_canonicalize_directories,
             create_and_canonicalize_directory,
@@ -30,56 +43,77 @@ use {
     },
     solana_clock::{Slot, DEFAULT_SLOTS_PER_EPOCH},
     solana_core::{
-        banking_stage::transaction_scheduler::scheduler_controller::SchedulerConfig,
+        banking_stage::{
+            reserved_capacity::ReservedCapacity,
+            transaction_scheduler::scheduler_controller::SchedulerConfig,
+        },
         banking_trace::DISABLED_BAKING_TRACE_DIR,
         consensus::tower_storage,
-        repair::repair_handler::RepairHandlerType,
+        forwarding_stage::forwarding_deny::ForwardingDenyConfig,
+        known_validators_service::{load_known_validators_from_stake, KnownValidatorsFromStake},
+        repair::{repair_handler::RepairHandlerType, serve_repair::ServeRepairConfig},
         snapshot_packager_service::SnapshotPackagerService,
         system_monitor_service::SystemMonitorService,
         tpu::MAX_VOTES_PER_SECOND,
+        tpu_mirror_stage::TpuMirrorConfig,
         validator::{
             is_snapshot_config_valid, BlockProductionMethod, BlockVerificationMethod,
             SchedulerPacing, Validator, ValidatorConfig, ValidatorError, ValidatorStartProgress,
-            ValidatorTpuConfig,
+            ValidatorTpuConfig, EXIT_SNAPSHOT_NAME,
         },
     },
     solana_genesis_utils::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
     solana_gossip::{
         cluster_info::{NodeConfig, DEFAULT_CONTACT_SAVE_INTERVAL_MILLIS},
-        contact_info::ContactInfo,
+        contact_info::{ContactInfo, Protocol, CAPABILITY_ENCRYPTED_SHRED_TRANSPORT},
         node::Node,
+        peer_preference::PeerPreference,
     },
     solana_hash::Hash,
     solana_keypair::Keypair,
     solana_ledger::{
-        blockstore_cleanup_service::{DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS},
+        blockstore_cleanup_service::{
+            BlockstoreCleanupPauseConfig, TransactionHistoryRetentionConfig,
+            DEFAULT_MAX_LEDGER_SHREDS, DEFAULT_MIN_MAX_LEDGER_SHREDS,
+        },
         use_snapshot_archives_at_startup::{self, UseSnapshotArchivesAtStartup},
     },
     solana_net_utils::multihomed_sockets::BindIpAddrs,
     solana_perf::recycler::enable_recycler_warming,
-    solana_poh::poh_service,
+    solana_poh::{poh_service, poh_timing_report::PohAlertThresholds},
     solana_pubkey::Pubkey,
-    solana_runtime::{runtime_config::RuntimeConfig, snapshot_utils},
+    solana_rpc::{known_validators::KnownValidators, rpc::JsonRpcConfig},
+    solana_runtime::{
+        bank_forks::{BankForksEviction, BankForksLimits},
+        runtime_config::RuntimeConfig,
+        snapshot_utils,
+    },
     solana_signer::Signer,
     solana_streamer::{
-        nonblocking::{simple_qos::SimpleQosConfig, swqos::SwQosConfig},
+        nonblocking::{
+            simple_qos::SimpleQosConfig,
+            swqos::{QosExemptSenders, SwQosConfig},
+        },
         quic::{QuicStreamerConfig, SimpleQosQuicStreamerConfig, SwQosQuicStreamerConfig},
     },
     solana_tpu_client::tpu_client::DEFAULT_TPU_ENABLE_UDP,
     solana_turbine::{
         broadcast_stage::BroadcastStageType,
+        sigverify_shreds::{ShredSigverifyBackend, ShredSigverifyConfig},
         xdp::{set_cpu_affinity, XdpConfig},
     },
     solana_validator_exit::Exit,
     std::{
         collections::HashSet,
         fs::{self, File},
+        iter,
         net::{IpAddr, Ipv4Addr, SocketAddr},
         num::{NonZeroU64, NonZeroUsize},
         path::{Path, PathBuf},
         process::exit,
         str::{self, FromStr},
         sync::{atomic::AtomicBool, Arc, RwLock},
+        time::Duration,
     },
 };
 
@@ -94,8 +128,17 @@ pub fn execute(
     solana_version: &str,
     operation: Operation,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let run_args = RunArgs::from_clap_arg_match(matches)?;
+    let mut run_args = RunArgs::from_clap_arg_match(matches)?;
 
+    let throttle_profile = value_t!(matches, "throttle_profile", PathBuf)
+        .ok()
+        .map(|path| ThrottleProfile::load(&path))
+        .transpose()?;
+    let mut num_thread_config = cli::thread_args::parse_num_threads_args(matches);
+    if let Some(throttle_profile) = &throttle_profile {
+        throttle_profile.cap_threads(&mut num_thread_config);
+        throttle_profile.cap_blockstore(&mut run_args.blockstore_options);
+    }
     let cli::thread_args::NumThreadConfig {
         accounts_db_background_threads,
         accounts_db_foreground_threads,
@@ -111,8 +154,10 @@ pub fn execute(
         tvu_receive_threads,
         tvu_retransmit_threads,
         tvu_sigverify_threads,
-    } = cli::thread_args::parse_num_threads_args(matches);
+        unpack_threads,
+    } = num_thread_config;
 
+    let effective_config = diagnostics_bundle::describe_effective_config(&run_args, solana_version);
     let identity_keypair = Arc::new(run_args.identity_keypair);
 
     let logfile = run_args.logfile;
@@ -120,9 +165,13 @@ pub fn execute(
         println!("log file: {}", logfile.display());
     }
     let use_progress_bar = logfile.is_none();
+    let diagnostics_logfile = logfile.clone();
     let _logger_thread = redirect_stderr_to_file(logfile);
 
     info!("{} {}", crate_name!(), solana_version);
+    if throttle_profile.is_some() {
+        warn!("Running with a throttle profile, performance is deliberately limited");
+    }
     info!("Starting validator with: {:#?}", std::env::args_os());
 
     let cuda = matches.is_present("cuda");
@@ -178,6 +227,37 @@ pub fn execute(
     } else {
         None
     };
+    let blockstore_cleanup_pause_config = BlockstoreCleanupPauseConfig {
+        leader_slot_window: value_t_or_exit!(matches, "ledger_cleanup_pause_leader_slots", u64),
+        repair_serving_window: Duration::from_secs(value_t_or_exit!(
+            matches,
+            "ledger_cleanup_pause_repair_serving_secs",
+            u64
+        )),
+    };
+    let transaction_history_retention = TransactionHistoryRetentionConfig {
+        slots: value_t!(matches, "rpc_transaction_history_retention_slots", u64).ok(),
+        retained_addresses: values_t!(matches, "rpc_transaction_history_retained_address", Pubkey)
+            .map(|addresses| addresses.into_iter().collect())
+            .unwrap_or_default(),
+        retained_address_slots: value_t!(
+            matches,
+            "rpc_transaction_history_retained_address_slots",
+            u64
+        )
+        .ok(),
+    };
+    if let (Some(slots), Some(retained_address_slots)) = (
+        transaction_history_retention.slots,
+        transaction_history_retention.retained_address_slots,
+    ) {
+        if retained_address_slots <= slots {
+            Err(
+                "--rpc-transaction-history-retained-address-slots must be greater than \
+                 --rpc-transaction-history-retention-slots",
+            )?;
+        }
+    }
 
     let debug_keys: Option<Arc<HashSet<_>>> = if matches.is_present("debug_key") {
         Some(Arc::new(
@@ -426,7 +506,7 @@ pub fn execute(
         })
         .unwrap_or_default();
 
-    let accounts_db_config = AccountsDbConfig {
+    let mut accounts_db_config = AccountsDbConfig {
         index: Some(accounts_index_config),
         account_indexes: Some(account_indexes.clone()),
         base_working_path: Some(ledger_path.clone()),
@@ -449,10 +529,17 @@ pub fn execute(
         scan_filter_for_shrinking,
         num_background_threads: Some(accounts_db_background_threads),
         num_foreground_threads: Some(accounts_db_foreground_threads),
+        num_snapshot_unpack_threads: Some(unpack_threads),
         mark_obsolete_accounts,
         memlock_budget_size: solana_accounts_db::accounts_db::DEFAULT_MEMLOCK_BUDGET_SIZE,
+        storage_manifest_path: matches
+            .is_present("accounts_db_storage_manifest")
+            .then(|| ledger_path.join(STORAGE_MANIFEST_FILE_NAME)),
         ..AccountsDbConfig::default()
     };
+    if let Some(throttle_profile) = &throttle_profile {
+        throttle_profile.cap_accounts_db(&mut accounts_db_config);
+    }
 
     let on_start_geyser_plugin_config_files = if matches.is_present("geyser_plugin_config") {
         Some(
@@ -512,6 +599,7 @@ pub fn execute(
         &account_paths,
         run_args.rpc_bootstrap_config.incremental_snapshot_fetch,
     )?;
+    let exit_snapshot_max_age_slots = value_t!(matches, "snapshot_on_exit_max_age", Slot).ok();
 
     let use_snapshot_archives_at_startup = value_t_or_exit!(
         matches,
@@ -519,6 +607,63 @@ pub fn execute(
         UseSnapshotArchivesAtStartup
     );
 
+    let tpu_mirror_config = matches
+        .value_of("tpu_mirror_address")
+        .map(|tpu_mirror_address| {
+            let address = solana_net_utils::parse_host_port(tpu_mirror_address)
+                .map_err(|err| format!("failed to parse --tpu-mirror-address: {err}"))?;
+            Ok::<_, String>(TpuMirrorConfig {
+                address,
+                protocol: match matches.value_of("tpu_mirror_protocol") {
+                    Some("udp") => Protocol::UDP,
+                    _ => Protocol::QUIC,
+                },
+                sample_rate: value_t_or_exit!(matches, "tpu_mirror_sample_rate", f64),
+            })
+        })
+        .transpose()?;
+
+    let block_production_reserved_capacity = ReservedCapacity {
+        block_cu_percent: value_t!(matches, "reserved_block_cu_percent", u8).unwrap_or_default(),
+        num_workers: value_t!(matches, "reserved_banking_workers", usize).unwrap_or_default(),
+        critical_programs: values_t!(matches, "critical_program", Pubkey)
+            .map(HashSet::from_iter)
+            .unwrap_or_default(),
+    };
+    if block_production_reserved_capacity.num_workers >= block_production_num_workers.get() {
+        Err(format!(
+            "--reserved-banking-workers must be less than --block-production-num-workers \
+             ({block_production_num_workers})"
+        ))?;
+    }
+
+    let forwarding_deny_config = ForwardingDenyConfig {
+        max_delinquent_slots: value_t!(matches, "forwarding_deny_delinquent_slots", u64).ok(),
+        deny_on_identity_mismatch: matches.is_present("forwarding_deny_identity_mismatch"),
+    };
+    let forwarding_deny_config = (forwarding_deny_config != ForwardingDenyConfig::default())
+        .then_some(forwarding_deny_config);
+
+    let known_validators_from_stake = value_t!(matches, "known_validators_min_stake_percent", f64)
+        .ok()
+        .map(|min_stake_percent| KnownValidatorsFromStake {
+            min_stake_percent,
+            static_known_validators: run_args.known_validators.clone().unwrap_or_default(),
+        });
+    // Bootstrap from the validators derived from stake at the last epoch this ledger saw
+    let known_validators = match (
+        run_args.known_validators,
+        known_validators_from_stake
+            .as_ref()
+            .and_then(|_| load_known_validators_from_stake(&ledger_path)),
+    ) {
+        (Some(mut known_validators), Some(from_stake)) => {
+            known_validators.extend(from_stake);
+            Some(known_validators)
+        }
+        (known_validators, from_stake) => known_validators.or(from_stake),
+    };
+
     let mut validator_config = ValidatorConfig {
         require_tower: matches.is_present("require_tower"),
         tower_storage,
@@ -533,7 +678,14 @@ pub fn execute(
             .map(|s| Hash::from_str(s).unwrap()),
         expected_shred_version,
         new_hard_forks: hardforks_of(matches, "hard_forks"),
-        rpc_config: run_args.json_rpc_config,
+        rpc_config: JsonRpcConfig {
+            // The known validators service updates the set in place, so it must exist
+            known_validators: known_validators
+                .clone()
+                .or_else(|| known_validators_from_stake.as_ref().map(|_| HashSet::new()))
+                .map(KnownValidators::new),
+            ..run_args.json_rpc_config
+        },
         on_start_geyser_plugin_config_files,
         geyser_plugin_always_enabled: matches.is_present("geyser_plugin_always_enabled"),
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
@@ -548,12 +700,37 @@ pub fn execute(
         pubsub_config: run_args.pub_sub_config,
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
-        known_validators: run_args.known_validators,
+        known_validators,
+        known_validators_from_stake,
         repair_validators,
         repair_whitelist,
         repair_handler_type: RepairHandlerType::default(),
+        serve_repair_config: ServeRepairConfig {
+            max_bytes_per_second: value_t_or_exit!(
+                matches,
+                "serve_repair_max_bytes_per_second",
+                usize
+            ),
+            max_requests_per_second: value_t!(
+                matches,
+                "serve_repair_max_requests_per_second",
+                usize
+            )
+            .ok(),
+            max_peer_bytes_per_second: value_t!(
+                matches,
+                "serve_repair_max_peer_bytes_per_second",
+                usize
+            )
+            .ok(),
+        },
+        tpu_mirror_config,
+        forwarding_deny_config,
         gossip_validators,
+        peer_preference: value_t_or_exit!(matches, "peer_preference", PeerPreference),
         max_ledger_shreds,
+        blockstore_cleanup_pause_config,
+        transaction_history_retention,
         blockstore_options: run_args.blockstore_options,
         run_verification: !matches.is_present("skip_startup_ledger_verification"),
         debug_keys,
@@ -571,9 +748,15 @@ pub fn execute(
         // permission to do so in order to fail quickly and give a direct error
         enforce_ulimit_nofile: true,
         poh_pinned_cpu_core: value_of(matches, "poh_pinned_cpu_core")
+            .or_else(|| value_of(matches, "experimental_poh_pinned_cpu_core"))
             .unwrap_or(poh_service::DEFAULT_PINNED_CPU_CORE),
         poh_hashes_per_batch: value_of(matches, "poh_hashes_per_batch")
             .unwrap_or(poh_service::DEFAULT_HASHES_PER_BATCH),
+        poh_alert_thresholds: PohAlertThresholds {
+            max_slot_drift: value_of(matches, "poh_alert_max_slot_drift_ms")
+                .map(Duration::from_millis),
+            min_hash_rate: value_of(matches, "poh_alert_min_hash_rate"),
+        },
         process_ledger_before_services: matches.is_present("process_ledger_before_services"),
         account_paths: account_run_paths,
         account_snapshot_paths,
@@ -581,10 +764,12 @@ pub fn execute(
         accounts_db_skip_shrink: true,
         accounts_db_force_initial_clean: matches.is_present("no_skip_initial_accounts_db_clean"),
         snapshot_config,
+        exit_snapshot_max_age_slots,
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
         wait_to_vote_slot: None,
         runtime_config: RuntimeConfig {
             log_messages_bytes_limit: value_of(matches, "log_messages_bytes_limit"),
+            replay_determinism_check: matches.is_present("replay_determinism_check"),
             ..RuntimeConfig::default()
         },
         staked_nodes_overrides: staked_nodes_overrides.clone(),
@@ -594,10 +779,33 @@ pub fn execute(
         replay_forks_threads,
         replay_transactions_threads,
         tvu_shred_sigverify_threads: tvu_sigverify_threads,
+        tvu_shred_sigverify: ShredSigverifyConfig {
+            batch_size: value_t_or_exit!(matches, "tvu_shred_sigverify_batch_size", NonZeroUsize),
+            queue_depth: value_t_or_exit!(matches, "tvu_shred_sigverify_queue_depth", NonZeroUsize),
+            backend: value_t_or_exit!(
+                matches,
+                "tvu_shred_sigverify_backend",
+                ShredSigverifyBackend
+            ),
+        },
         delay_leader_block_for_pending_fork: matches
             .is_present("delay_leader_block_for_pending_fork"),
+        bank_forks_limits: BankForksLimits {
+            max_unrooted_banks: value_t!(matches, "max_unrooted_banks", usize).ok(),
+            max_fork_age: value_t!(matches, "max_unrooted_fork_age", Slot).ok(),
+            eviction: match matches.value_of("unrooted_fork_eviction") {
+                Some("prune") => BankForksEviction::Prune,
+                _ => BankForksEviction::Report,
+            },
+        },
+        epoch_precompute_slots: Some(value_t_or_exit!(matches, "epoch_precompute_slots", Slot))
+            .filter(|slots| *slots > 0),
         wen_restart_proto_path: value_t!(matches, "wen_restart", PathBuf).ok(),
         wen_restart_coordinator: value_t!(matches, "wen_restart_coordinator", Pubkey).ok(),
+        cluster_halt_detection_timeout: value_t!(matches, "cluster_halt_detection_secs", u64)
+            .ok()
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs),
         turbine_disabled: Arc::<AtomicBool>::default(),
         retransmit_xdp,
         broadcast_stage_type: BroadcastStageType::Standard,
@@ -626,15 +834,15 @@ pub fn execute(
                 SchedulerPacing
             ),
         },
+        block_production_reserved_capacity,
         enable_block_production_forwarding: staked_nodes_overrides_path.is_some(),
         enable_scheduler_bindings: matches.is_present("enable_scheduler_bindings"),
         banking_trace_dir_byte_limit: parse_banking_trace_dir_byte_limit(matches),
         validator_exit: Arc::new(RwLock::new(Exit::default())),
-        validator_exit_backpressure: [(
-            SnapshotPackagerService::NAME.to_string(),
-            Arc::new(AtomicBool::new(false)),
-        )]
-        .into(),
+        validator_exit_backpressure: iter::once(SnapshotPackagerService::NAME)
+            .chain(exit_snapshot_max_age_slots.map(|_| EXIT_SNAPSHOT_NAME))
+            .map(|name| (name.to_string(), Arc::new(AtomicBool::new(false))))
+            .collect(),
     };
 
     let reserved = validator_config
@@ -645,14 +853,17 @@ pub fn execute(
         .iter()
         .cloned()
         .collect::<HashSet<_>>();
-    if !reserved.is_empty() {
+    let throttled_cores = throttle_profile
+        .as_ref()
+        .and_then(|throttle_profile| throttle_profile.cpu.cores);
+    if !reserved.is_empty() || throttled_cores.is_some() {
         let available = core_affinity::get_core_ids()
             .unwrap_or_default()
             .into_iter()
             .map(|core_id| core_id.id)
-            .collect::<HashSet<_>>();
-        let available = available.difference(&reserved);
-        set_cpu_affinity(available.into_iter().copied()).unwrap();
+            .filter(|id| !reserved.contains(id))
+            .take(throttled_cores.map_or(usize::MAX, NonZeroUsize::get));
+        set_cpu_affinity(available).unwrap();
     }
 
     let vote_account = pubkey_of(matches, "vote_account").unwrap_or_else(|| {
@@ -702,10 +913,20 @@ pub fn execute(
                 .to_string())?;
         }
     }
+    doctor::report_startup_checks();
 
     let mut ledger_lock = ledger_lockfile(&ledger_path);
     let _ledger_write_guard = lock_ledger(&ledger_path, &mut ledger_lock);
 
+    let (intent_log, resolved_intents) = IntentLog::open(&ledger_path, identity_keypair.clone())
+        .map_err(|err| format!("failed to open the admin intent log: {err}"))?;
+    for intent in resolved_intents {
+        warn!(
+            "Admin operation {} of the previous run did not complete: {:?}, resolved as {:?}",
+            intent.id, intent.operation, intent.resolution
+        );
+    }
+
     let start_progress = Arc::new(RwLock::new(ValidatorStartProgress::default()));
     let admin_service_post_init = Arc::new(RwLock::new(None));
     let (rpc_to_plugin_manager_sender, rpc_to_plugin_manager_receiver) =
@@ -715,21 +936,62 @@ pub fn execute(
         } else {
             (None, None)
         };
-    admin_rpc_service::run(
-        &ledger_path,
-        admin_rpc_service::AdminRpcRequestMetadata {
-            rpc_addr: validator_config.rpc_addrs.map(|(rpc_addr, _)| rpc_addr),
-            start_time: std::time::SystemTime::now(),
-            validator_exit: validator_config.validator_exit.clone(),
-            validator_exit_backpressure: validator_config.validator_exit_backpressure.clone(),
-            start_progress: start_progress.clone(),
-            authorized_voter_keypairs: authorized_voter_keypairs.clone(),
-            post_init: admin_service_post_init.clone(),
-            tower_storage: validator_config.tower_storage.clone(),
-            staked_nodes_overrides,
-            rpc_to_plugin_manager_sender,
-        },
-    );
+    Arc::new(CrashReporter::new(
+        ledger_path.clone(),
+        solana_version.to_string(),
+        &effective_config,
+        admin_service_post_init.clone(),
+        matches.value_of("crash_report_webhook").map(str::to_string),
+    ))
+    .install();
+    let diagnostics = Arc::new(DiagnosticsBundler::new(
+        ledger_path.clone(),
+        diagnostics_logfile,
+        effective_config,
+        admin_service_post_init.clone(),
+    ));
+    #[cfg(unix)]
+    if let Err(err) = diagnostics_bundle::spawn_signal_handler(diagnostics.clone()) {
+        warn!("Failed to install the SIGUSR2 diagnostics bundle handler: {err}");
+    }
+    let admin_rpc_http_config = value_t!(matches, "admin_rpc_http_address", SocketAddr)
+        .ok()
+        .map(|address| {
+            let token_file = value_t_or_exit!(matches, "admin_rpc_http_token_file", PathBuf);
+            let auth_token = fs::read_to_string(&token_file)
+                .map_err(|err| format!("Unable to read {}: {err}", token_file.display()))?
+                .trim()
+                .to_string();
+            if auth_token.is_empty() {
+                return Err(format!("{} contains no token", token_file.display()));
+            }
+            Ok(AdminRpcHttpConfig {
+                address,
+                auth_token,
+            })
+        })
+        .transpose()?;
+    let admin_rpc_metadata = admin_rpc_service::AdminRpcRequestMetadata {
+        rpc_addr: validator_config.rpc_addrs.map(|(rpc_addr, _)| rpc_addr),
+        start_time: std::time::SystemTime::now(),
+        validator_exit: validator_config.validator_exit.clone(),
+        validator_exit_backpressure: validator_config.validator_exit_backpressure.clone(),
+        start_progress: start_progress.clone(),
+        authorized_voter_keypairs: authorized_voter_keypairs.clone(),
+        post_init: admin_service_post_init.clone(),
+        tower_storage: validator_config.tower_storage.clone(),
+        staked_nodes_overrides,
+        rpc_to_plugin_manager_sender,
+        restart_window: Arc::default(),
+        intent_log: Some(Arc::new(intent_log)),
+        diagnostics: Some(diagnostics.clone()),
+        wen_restart_path: validator_config.wen_restart_proto_path.clone(),
+        config_lock: Arc::default(),
+    };
+    if let Some(admin_rpc_http_config) = admin_rpc_http_config {
+        admin_rpc_http_service::run(admin_rpc_http_config, admin_rpc_metadata.clone());
+    }
+    admin_rpc_service::run(&ledger_path, admin_rpc_metadata);
 
     let gossip_host = matches
         .value_of("gossip_host")
@@ -853,6 +1115,15 @@ pub fn execute(
     let tpu_max_connections_per_ipaddr_per_minute: u64 =
         value_t_or_exit!(matches, "tpu_max_connections_per_ipaddr_per_minute", u64);
     let max_streams_per_ms = value_t_or_exit!(matches, "tpu_max_streams_per_ms", u64);
+    let tpu_enable_transaction_acks = matches.is_present("tpu_enable_transaction_acks");
+    let tpu_qos_exempt_senders = QosExemptSenders {
+        pubkeys: values_t!(matches, "tpu_qos_exempt_pubkey", Pubkey)
+            .map(HashSet::from_iter)
+            .unwrap_or_default(),
+        ip_addrs: values_t!(matches, "tpu_qos_exempt_ip_addr", IpAddr)
+            .map(HashSet::from_iter)
+            .unwrap_or_default(),
+    };
 
     let node_config = NodeConfig {
         advertised_ip,
@@ -874,6 +1145,15 @@ pub fn execute(
         .collect::<Vec<_>>();
 
     let mut node = Node::new_with_external_ip(&identity_keypair.pubkey(), node_config);
+    if let Some(region) = matches.value_of("region") {
+        node.info
+            .set_region(region.to_string())
+            .map_err(|err| format!("invalid --region: {err}"))?;
+    }
+    if matches.is_present("encrypt_shred_transport") {
+        node.info
+            .set_capabilities(node.info.capabilities() | CAPABILITY_ENCRYPTED_SHRED_TRANSPORT);
+    }
 
     if restricted_repair_only_mode {
         if validator_config.wen_restart_proto_path.is_some() {
@@ -951,6 +1231,20 @@ pub fn execute(
         *start_progress.write().unwrap() = ValidatorStartProgress::Initializing;
     }
 
+    preflight::check_accounts_index_memory(
+        value_t_or_exit!(
+            matches,
+            "accounts_index_memory_check",
+            AccountsIndexMemoryCheck
+        ),
+        &validator_config.snapshot_config,
+        validator_config
+            .accounts_db_config
+            .index
+            .as_ref()
+            .expect("accounts index config is always set"),
+    )?;
+
     if operation == Operation::Initialize {
         info!("Validator ledger initialization complete");
         return Ok(());
@@ -975,9 +1269,13 @@ pub fn execute(
             max_unstaked_connections: tpu_max_unstaked_connections.try_into().unwrap(),
             max_connections_per_ipaddr_per_min: tpu_max_connections_per_ipaddr_per_minute,
             num_threads: tpu_transaction_receive_threads,
+            enable_transaction_acks: tpu_enable_transaction_acks,
             ..Default::default()
         },
-        qos_config: SwQosConfig { max_streams_per_ms },
+        qos_config: SwQosConfig {
+            max_streams_per_ms,
+            exempt_senders: tpu_qos_exempt_senders.clone(),
+        },
     };
 
     let tpu_fwd_quic_server_config = SwQosQuicStreamerConfig {
@@ -992,9 +1290,13 @@ pub fn execute(
             max_unstaked_connections: tpu_max_fwd_unstaked_connections.try_into().unwrap(),
             max_connections_per_ipaddr_per_min: tpu_max_connections_per_ipaddr_per_minute,
             num_threads: tpu_transaction_forward_receive_threads,
+            enable_transaction_acks: tpu_enable_transaction_acks,
             ..Default::default()
         },
-        qos_config: SwQosConfig { max_streams_per_ms },
+        qos_config: SwQosConfig {
+            max_streams_per_ms,
+            exempt_senders: tpu_qos_exempt_senders,
+        },
     };
 
     let vote_quic_server_config = SimpleQosQuicStreamerConfig {
@@ -1050,6 +1352,7 @@ pub fn execute(
             Err(format!("{err:?}"))
         }
     }?;
+    diagnostics.set_blockstore(validator.blockstore.clone());
 
     if let Some(filename) = init_complete_file {
         File::create(filename).map_err(|err| format!("unable to create {filename}: {err}"))?;
@@ -1274,6 +1577,13 @@ fn new_snapshot_config(
 
     let snapshot_packager_niceness_adj =
         value_t_or_exit!(matches, "snapshot_packager_niceness_adj", i8);
+    let snapshot_archive_upload =
+        matches
+            .value_of("snapshot_archive_upload_command")
+            .map(|command| ArchiveUploadConfig {
+                command: command.to_string(),
+                keep_local_archive: !matches.is_present("snapshot_archive_upload_only"),
+            });
 
     let snapshot_config = SnapshotConfig {
         usage: if full_snapshot_archive_interval == SnapshotInterval::Disabled {
@@ -1291,6 +1601,7 @@ fn new_snapshot_config(
         maximum_full_snapshot_archives_to_retain,
         maximum_incremental_snapshot_archives_to_retain,
         packager_thread_niceness_adj: snapshot_packager_niceness_adj,
+        archive_upload: snapshot_archive_upload,
     };
 
     if !is_snapshot_config_valid(&snapshot_config) {
@@ -1,4 +1,5 @@
 pub mod args;
 pub mod execute;
+pub mod preflight;
 
 pub use {args::add_args, execute::execute};
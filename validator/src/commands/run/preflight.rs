@@ -0,0 +1,339 @@
+//! Checks that run after the snapshot to start from has been selected, but before the validator
+//! begins the expensive work of loading it.
+
+use {
+    agave_snapshots::{
+        paths as snapshot_paths, snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_config::SnapshotConfig, ArchiveFormat,
+    },
+    log::*,
+    solana_accounts_db::accounts_index::{
+        memory_estimate::AccountsIndexMemoryEstimate, AccountsIndexConfig,
+    },
+    solana_metrics::datapoint_info,
+    std::{fs, path::Path, str::FromStr},
+};
+
+pub const ACCOUNTS_INDEX_MEMORY_CHECK_NAMES: &[&str] = &["enforce", "warn", "skip"];
+pub const DEFAULT_ACCOUNTS_INDEX_MEMORY_CHECK: &str = "enforce";
+
+/// Approximate size of one account in a zstd compressed snapshot archive, as observed on mainnet
+const ZSTD_ARCHIVE_BYTES_PER_ACCOUNT: u64 = 128;
+/// Approximate size of one account in a lz4 compressed snapshot archive, as observed on mainnet
+const LZ4_ARCHIVE_BYTES_PER_ACCOUNT: u64 = 192;
+
+const KB: u64 = 1_024;
+const GB: u64 = KB * KB * KB;
+
+/// What to do when the accounts index is not expected to fit in memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccountsIndexMemoryCheck {
+    /// Refuse to start if the host clearly lacks the memory, warn if it is tight
+    Enforce,
+    /// Only warn
+    Warn,
+    /// Do not check
+    Skip,
+}
+
+impl FromStr for AccountsIndexMemoryCheck {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "enforce" => Ok(Self::Enforce),
+            "warn" => Ok(Self::Warn),
+            "skip" => Ok(Self::Skip),
+            _ => Err(format!("invalid accounts index memory check: {value}")),
+        }
+    }
+}
+
+/// Host memory, in bytes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HostMemory {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl HostMemory {
+    pub fn read() -> Option<Self> {
+        sys_info::mem_info()
+            .inspect_err(|err| warn!("Unable to read host memory info: {err}"))
+            .ok()
+            .map(|info| Self {
+                total_bytes: info.total.saturating_mul(KB),
+                available_bytes: info.avail.saturating_mul(KB),
+            })
+    }
+}
+
+/// Outcome of comparing the estimated accounts index size against the host memory
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccountsIndexMemoryVerdict {
+    /// The index is expected to fit in the currently available memory
+    Fits,
+    /// The index fits in total memory, but not in what is currently available
+    Tight,
+    /// The index is larger than the total memory of the host
+    Insufficient,
+}
+
+impl AccountsIndexMemoryVerdict {
+    pub fn new(estimate: &AccountsIndexMemoryEstimate, host_memory: &HostMemory) -> Self {
+        if estimate.in_mem_bytes > host_memory.total_bytes {
+            Self::Insufficient
+        } else if estimate.in_mem_bytes > host_memory.available_bytes {
+            Self::Tight
+        } else {
+            Self::Fits
+        }
+    }
+}
+
+/// Estimate the number of accounts in the snapshot the validator is going to start from
+///
+/// An explicit `--accounts-index-initial-accounts-count` takes precedence. Otherwise the count is
+/// derived from the size of the highest local full snapshot archive, and its latest incremental
+/// snapshot archive if there is one.
+pub fn estimate_num_accounts(
+    snapshot_config: &SnapshotConfig,
+    accounts_index_config: &AccountsIndexConfig,
+) -> Option<u64> {
+    if let Some(num_initial_accounts) = accounts_index_config.num_initial_accounts {
+        return Some(num_initial_accounts as u64);
+    }
+
+    let full_snapshot_archive_info = snapshot_paths::get_highest_full_snapshot_archive_info(
+        &snapshot_config.full_snapshot_archives_dir,
+    )?;
+    let incremental_snapshot_archive_info =
+        snapshot_paths::get_highest_incremental_snapshot_archive_info(
+            &snapshot_config.incremental_snapshot_archives_dir,
+            full_snapshot_archive_info.slot(),
+        );
+
+    let num_accounts = estimate_num_accounts_in_archive(
+        full_snapshot_archive_info.path(),
+        full_snapshot_archive_info.archive_format(),
+    )? + incremental_snapshot_archive_info
+        .and_then(|info| estimate_num_accounts_in_archive(info.path(), info.archive_format()))
+        .unwrap_or_default();
+    Some(num_accounts)
+}
+
+fn estimate_num_accounts_in_archive(path: &Path, archive_format: ArchiveFormat) -> Option<u64> {
+    let archive_size = fs::metadata(path)
+        .inspect_err(|err| warn!("Unable to read size of {}: {err}", path.display()))
+        .ok()?
+        .len();
+    let bytes_per_account = match archive_format {
+        ArchiveFormat::TarZstd { .. } => ZSTD_ARCHIVE_BYTES_PER_ACCOUNT,
+        ArchiveFormat::TarLz4 => LZ4_ARCHIVE_BYTES_PER_ACCOUNT,
+    };
+    Some(archive_size / bytes_per_account)
+}
+
+/// Check that the host has enough memory to generate the accounts index for the snapshot the
+/// validator is about to load
+pub fn check_accounts_index_memory(
+    check: AccountsIndexMemoryCheck,
+    snapshot_config: &SnapshotConfig,
+    accounts_index_config: &AccountsIndexConfig,
+) -> Result<(), String> {
+    if check == AccountsIndexMemoryCheck::Skip {
+        return Ok(());
+    }
+    let Some(num_accounts) = estimate_num_accounts(snapshot_config, accounts_index_config) else {
+        info!("Accounts index memory check skipped: no local snapshot archive found");
+        return Ok(());
+    };
+    let Some(host_memory) = HostMemory::read() else {
+        return Ok(());
+    };
+
+    let estimate =
+        AccountsIndexMemoryEstimate::new(num_accounts, accounts_index_config.index_limit_mb);
+    let verdict = AccountsIndexMemoryVerdict::new(&estimate, &host_memory);
+    datapoint_info!(
+        "accounts_index_memory_preflight",
+        ("num_accounts", estimate.num_accounts, i64),
+        ("estimated_in_mem_bytes", estimate.in_mem_bytes, i64),
+        ("estimated_disk_bytes", estimate.disk_bytes, i64),
+        ("host_total_bytes", host_memory.total_bytes, i64),
+        ("host_available_bytes", host_memory.available_bytes, i64),
+        ("verdict", format!("{verdict:?}"), String),
+    );
+
+    let summary = format!(
+        "accounts index for ~{num_accounts} accounts ({:?}) is estimated to need {:.1} GB of \
+         memory; host has {:.1} GB total, {:.1} GB available",
+        accounts_index_config.index_limit_mb,
+        estimate.in_mem_bytes as f64 / GB as f64,
+        host_memory.total_bytes as f64 / GB as f64,
+        host_memory.available_bytes as f64 / GB as f64,
+    );
+    match verdict {
+        AccountsIndexMemoryVerdict::Fits => {
+            info!("Accounts index memory check passed: {summary}");
+            Ok(())
+        }
+        AccountsIndexMemoryVerdict::Tight => {
+            warn!("Accounts index memory check: {summary}. Startup may run out of memory");
+            Ok(())
+        }
+        AccountsIndexMemoryVerdict::Insufficient => match check {
+            AccountsIndexMemoryCheck::Enforce => Err(format!(
+                "Accounts index memory check failed: {summary}. Consider \
+                 --enable-accounts-disk-index, or override with \
+                 --accounts-index-memory-check warn"
+            )),
+            AccountsIndexMemoryCheck::Warn | AccountsIndexMemoryCheck::Skip => {
+                warn!("Accounts index memory check failed: {summary}");
+                Ok(())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        agave_snapshots::{snapshot_hash::SnapshotHash, ZstdConfig},
+        solana_accounts_db::accounts_index::IndexLimitMb,
+        solana_hash::Hash,
+    };
+
+    fn host_memory(total_gb: u64, available_gb: u64) -> HostMemory {
+        HostMemory {
+            total_bytes: total_gb * GB,
+            available_bytes: available_gb * GB,
+        }
+    }
+
+    fn estimate(in_mem_gb: u64) -> AccountsIndexMemoryEstimate {
+        AccountsIndexMemoryEstimate {
+            num_accounts: 0,
+            in_mem_bytes: in_mem_gb * GB,
+            disk_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_accounts_index_memory_check_from_str() {
+        for name in ACCOUNTS_INDEX_MEMORY_CHECK_NAMES {
+            assert!(name.parse::<AccountsIndexMemoryCheck>().is_ok());
+        }
+        assert_eq!(
+            DEFAULT_ACCOUNTS_INDEX_MEMORY_CHECK.parse(),
+            Ok(AccountsIndexMemoryCheck::Enforce),
+        );
+        assert!("bogus".parse::<AccountsIndexMemoryCheck>().is_err());
+    }
+
+    #[test]
+    fn test_accounts_index_memory_verdict() {
+        let host_memory = host_memory(512, 256);
+        assert_eq!(
+            AccountsIndexMemoryVerdict::new(&estimate(128), &host_memory),
+            AccountsIndexMemoryVerdict::Fits,
+        );
+        assert_eq!(
+            AccountsIndexMemoryVerdict::new(&estimate(384), &host_memory),
+            AccountsIndexMemoryVerdict::Tight,
+        );
+        assert_eq!(
+            AccountsIndexMemoryVerdict::new(&estimate(1024), &host_memory),
+            AccountsIndexMemoryVerdict::Insufficient,
+        );
+    }
+
+    #[test]
+    fn test_estimate_num_accounts() {
+        let full_snapshot_archives_dir = tempfile::tempdir().unwrap();
+        let incremental_snapshot_archives_dir = tempfile::tempdir().unwrap();
+        let snapshot_config = SnapshotConfig {
+            full_snapshot_archives_dir: full_snapshot_archives_dir.path().to_path_buf(),
+            incremental_snapshot_archives_dir: incremental_snapshot_archives_dir
+                .path()
+                .to_path_buf(),
+            ..SnapshotConfig::default()
+        };
+        let accounts_index_config = AccountsIndexConfig::default();
+
+        // no snapshot archives, nothing to estimate from
+        assert_eq!(
+            estimate_num_accounts(&snapshot_config, &accounts_index_config),
+            None,
+        );
+
+        let full_snapshot_archive_path = snapshot_paths::build_full_snapshot_archive_path(
+            full_snapshot_archives_dir.path(),
+            100,
+            &SnapshotHash(Hash::default()),
+            ArchiveFormat::TarZstd {
+                config: ZstdConfig::default(),
+            },
+        );
+        fs::write(
+            &full_snapshot_archive_path,
+            vec![0; 10 * ZSTD_ARCHIVE_BYTES_PER_ACCOUNT as usize],
+        )
+        .unwrap();
+        assert_eq!(
+            estimate_num_accounts(&snapshot_config, &accounts_index_config),
+            Some(10),
+        );
+
+        let incremental_snapshot_archive_path =
+            snapshot_paths::build_incremental_snapshot_archive_path(
+                incremental_snapshot_archives_dir.path(),
+                100,
+                150,
+                &SnapshotHash(Hash::default()),
+                ArchiveFormat::TarLz4,
+            );
+        fs::write(
+            &incremental_snapshot_archive_path,
+            vec![0; 5 * LZ4_ARCHIVE_BYTES_PER_ACCOUNT as usize],
+        )
+        .unwrap();
+        assert_eq!(
+            estimate_num_accounts(&snapshot_config, &accounts_index_config),
+            Some(15),
+        );
+
+        // an explicit initial accounts count takes precedence
+        let accounts_index_config = AccountsIndexConfig {
+            num_initial_accounts: Some(42),
+            index_limit_mb: IndexLimitMb::Minimal,
+            ..AccountsIndexConfig::default()
+        };
+        assert_eq!(
+            estimate_num_accounts(&snapshot_config, &accounts_index_config),
+            Some(42),
+        );
+    }
+
+    #[test]
+    fn test_check_accounts_index_memory_skip() {
+        let snapshot_config = SnapshotConfig::default();
+        let accounts_index_config = AccountsIndexConfig {
+            num_initial_accounts: Some(usize::MAX),
+            ..AccountsIndexConfig::default()
+        };
+        assert!(check_accounts_index_memory(
+            AccountsIndexMemoryCheck::Skip,
+            &snapshot_config,
+            &accounts_index_config,
+        )
+        .is_ok());
+        assert!(check_accounts_index_memory(
+            AccountsIndexMemoryCheck::Warn,
+            &snapshot_config,
+            &accounts_index_config,
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,374 @@
+use {
+    crate::commands::{
+        doctor::checks::{CheckResult, CheckStatus},
+        Error, FromClapArgMatches, Result,
+    },
+    agave_logger::redirect_stderr_to_file,
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_commitment_config::CommitmentConfig,
+    solana_keypair::Keypair,
+    solana_net_utils::PortRange,
+    solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_signer::Signer,
+    solana_streamer::socket::SocketAddrSpace,
+    solana_system_interface::instruction as system_instruction,
+    solana_test_validator::{TestValidator, TestValidatorGenesis},
+    solana_transaction::Transaction,
+    std::{
+        net::{IpAddr, Ipv4Addr},
+        path::PathBuf,
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+};
+
+const COMMAND: &str = "self-test";
+
+/// Moves the default dynamic port range to 18000-20000
+const DEFAULT_PORT_OFFSET: &str = "10000";
+const DEFAULT_TIMEOUT_SECS: &str = "120";
+
+const STARTUP: &str = "startup";
+const RPC: &str = "rpc";
+const BLOCK_PRODUCTION: &str = "block production";
+const TRANSACTION: &str = "transaction";
+const REPLAY: &str = "replay";
+
+/// Number of blocks the node must produce for the block production check to pass
+const MIN_BLOCKS_PRODUCED: usize = 10;
+const POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+#[derive(Debug, PartialEq)]
+pub struct SelfTestArgs {
+    pub port_offset: u16,
+    pub timeout: Duration,
+    pub keep_ledger: bool,
+}
+
+impl FromClapArgMatches for SelfTestArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(SelfTestArgs {
+            port_offset: value_t!(matches, "port_offset", u16)?,
+            timeout: Duration::from_secs(value_t!(matches, "timeout", u64)?),
+            keep_ledger: matches.is_present("keep_ledger"),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Run the validator on an ephemeral single node cluster and check that it works")
+        .arg(
+            Arg::with_name("port_offset")
+                .long("port-offset")
+                .takes_value(true)
+                .value_name("PORTS")
+                .validator(is_parsable::<u16>)
+                .default_value(DEFAULT_PORT_OFFSET)
+                .help(
+                    "Offset added to --dynamic-port-range so that the test does not collide with \
+                     a validator running on this host",
+                ),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .default_value(DEFAULT_TIMEOUT_SECS)
+                .help("How long to wait for each check to pass"),
+        )
+        .arg(
+            Arg::with_name("keep_ledger")
+                .long("keep-ledger")
+                .takes_value(false)
+                .help("Keep the ephemeral ledger and its log once the test is done"),
+        )
+        .after_help(
+            "The arguments given to the validator before the subcommand are used for the test, \
+             with the RPC, PubSub and Geyser plugin settings carried over. The node runs with a \
+             new genesis, identity and ledger in a temporary directory, and binds only to \
+             localhost, so it is safe to run next to a validator on mainnet. It produces blocks, \
+             processes a transfer and replays its own blocks to a root",
+        )
+}
+
+/// `matches` are the top level arguments, which hold the validator configuration
+pub fn execute(matches: &ArgMatches, subcommand_matches: &ArgMatches) -> Result<()> {
+    let self_test_args = SelfTestArgs::from_clap_arg_match(subcommand_matches)?;
+
+    let port_range = remap_port_range(
+        solana_net_utils::parse_port_range(matches.value_of("dynamic_port_range").unwrap())
+            .ok_or_else(|| Error::Dynamic("invalid --dynamic-port-range".into()))?,
+        self_test_args.port_offset,
+    )
+    .ok_or_else(|| Error::Dynamic("--port-offset moves the port range beyond 65535".into()))?;
+    let mut rpc_config = JsonRpcConfig::from_clap_arg_match(matches)?;
+    // The ephemeral cluster has no history worth uploading or fetching
+    rpc_config.rpc_bigtable_config = None;
+    let pubsub_config = PubSubConfig::from_clap_arg_match(matches)?;
+
+    let ledger_dir = tempfile::Builder::new()
+        .prefix("agave-validator-self-test-")
+        .tempdir()?;
+    let logfile = ledger_dir.path().join("validator.log");
+    println!("Ledger: {}", ledger_dir.path().display());
+    println!("Log file: {}", logfile.display());
+    let _logger_thread = redirect_stderr_to_file(Some(logfile));
+
+    let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let mut genesis = TestValidatorGenesis::default();
+    genesis
+        .ledger_path(ledger_dir.path())
+        .rpc_config(rpc_config)
+        .pubsub_config(pubsub_config)
+        .bind_ip_addr(localhost)
+        .gossip_host(localhost)
+        .gossip_port(port_range.0)
+        .port_range(port_range);
+    genesis.geyser_plugin_config_files = matches
+        .values_of("geyser_plugin_config")
+        .map(|files| files.map(PathBuf::from).collect());
+
+    let mint_keypair = Keypair::new();
+    let mut results = vec![];
+    match genesis.start_with_mint_address(
+        mint_keypair.pubkey(),
+        SocketAddrSpace::new(/*allow_private_addr=*/ true),
+    ) {
+        Ok(test_validator) => {
+            results.push(CheckResult::new(
+                STARTUP,
+                CheckStatus::Pass,
+                format!(
+                    "gossip on {}, RPC on {}",
+                    test_validator.gossip(),
+                    test_validator.rpc_url()
+                ),
+            ));
+            results.extend(run_checks(
+                &test_validator,
+                &mint_keypair,
+                self_test_args.timeout,
+            ));
+        }
+        Err(err) => results.push(CheckResult::new(
+            STARTUP,
+            CheckStatus::Fail,
+            err.to_string(),
+        )),
+    }
+
+    for result in &results {
+        println!("{result}");
+    }
+    if self_test_args.keep_ledger {
+        let ledger_path = ledger_dir.keep();
+        println!("Kept the ledger at {}", ledger_path.display());
+    }
+
+    let num_failed = results
+        .iter()
+        .filter(|result| result.status == CheckStatus::Fail)
+        .count();
+    if num_failed > 0 {
+        return Err(Error::Dynamic(
+            format!("{num_failed} of {} self-test checks failed", results.len()).into(),
+        ));
+    }
+    println!("\nAll {} self-test checks passed", results.len());
+    Ok(())
+}
+
+fn remap_port_range((start, end): PortRange, offset: u16) -> Option<PortRange> {
+    Some((start.checked_add(offset)?, end.checked_add(offset)?))
+}
+
+/// Runs the checks in order, skipping the remaining ones after the first failure
+fn run_checks(
+    test_validator: &TestValidator,
+    mint_keypair: &Keypair,
+    timeout: Duration,
+) -> Vec<CheckResult> {
+    let rpc_client = test_validator.get_rpc_client();
+    let mut results = vec![];
+    let mut failed = false;
+    for name in [RPC, BLOCK_PRODUCTION, TRANSACTION, REPLAY] {
+        if failed {
+            results.push(CheckResult::new(
+                name,
+                CheckStatus::Skip,
+                "skipped after a failed check",
+            ));
+            continue;
+        }
+        let result = match name {
+            RPC => check_rpc(&rpc_client),
+            BLOCK_PRODUCTION => check_block_production(&rpc_client, timeout),
+            TRANSACTION => check_transaction(&rpc_client, mint_keypair),
+            REPLAY => check_replay(&rpc_client, timeout),
+            _ => unreachable!(),
+        };
+        results.push(match result {
+            Ok(detail) => CheckResult::new(name, CheckStatus::Pass, detail),
+            Err(detail) => {
+                failed = true;
+                CheckResult::new(name, CheckStatus::Fail, detail)
+            }
+        });
+    }
+    results
+}
+
+fn check_rpc(rpc_client: &RpcClient) -> std::result::Result<String, String> {
+    rpc_client
+        .get_health()
+        .map_err(|err| format!("getHealth failed: {err}"))?;
+    let version = rpc_client
+        .get_version()
+        .map_err(|err| format!("getVersion failed: {err}"))?;
+    Ok(format!("healthy, version {}", version.solana_core))
+}
+
+fn check_block_production(
+    rpc_client: &RpcClient,
+    timeout: Duration,
+) -> std::result::Result<String, String> {
+    let identity = rpc_client
+        .get_identity()
+        .map_err(|err| format!("getIdentity failed: {err}"))?
+        .to_string();
+    poll_until(timeout, || {
+        let block_production = rpc_client
+            .get_block_production()
+            .map_err(|err| format!("getBlockProduction failed: {err}"))?
+            .value;
+        let (leader_slots, blocks_produced) = block_production
+            .by_identity
+            .get(&identity)
+            .copied()
+            .unwrap_or_default();
+        Ok((blocks_produced >= MIN_BLOCKS_PRODUCED)
+            .then(|| format!("{blocks_produced} blocks produced in {leader_slots} leader slots")))
+    })
+    .map_err(|err| format!("{err}, fewer than {MIN_BLOCKS_PRODUCED} blocks were produced"))
+}
+
+fn check_transaction(
+    rpc_client: &RpcClient,
+    mint_keypair: &Keypair,
+) -> std::result::Result<String, String> {
+    let recipient = Keypair::new().pubkey();
+    let lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(0)
+        .map_err(|err| format!("getMinimumBalanceForRentExemption failed: {err}"))?;
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| format!("getLatestBlockhash failed: {err}"))?;
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &mint_keypair.pubkey(),
+            &recipient,
+            lamports,
+        )],
+        Some(&mint_keypair.pubkey()),
+        &[mint_keypair],
+        blockhash,
+    );
+    let signature = rpc_client
+        .send_and_confirm_transaction(&transaction)
+        .map_err(|err| format!("transfer failed: {err}"))?;
+    let balance = rpc_client
+        .get_balance(&recipient)
+        .map_err(|err| format!("getBalance failed: {err}"))?;
+    if balance != lamports {
+        return Err(format!(
+            "transfer {signature} confirmed but the recipient holds {balance} lamports instead of \
+             {lamports}"
+        ));
+    }
+    Ok(format!("transfer {signature} confirmed"))
+}
+
+fn check_replay(rpc_client: &RpcClient, timeout: Duration) -> std::result::Result<String, String> {
+    poll_until(timeout, || {
+        let root = rpc_client
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .map_err(|err| format!("getSlot failed: {err}"))?;
+        Ok((root > 0).then(|| format!("rooted slot {root}")))
+    })
+    .map_err(|err| format!("{err}, no slot was rooted"))
+}
+
+/// Calls `check` until it returns a value or an error, or `timeout` elapses
+fn poll_until(
+    timeout: Duration,
+    mut check: impl FnMut() -> std::result::Result<Option<String>, String>,
+) -> std::result::Result<String, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(detail) = check()? {
+            return Ok(detail);
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("timed out after {}s", timeout.as_secs()));
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    impl Default for SelfTestArgs {
+        fn default() -> Self {
+            SelfTestArgs {
+                port_offset: DEFAULT_PORT_OFFSET
+                    .parse()
+                    .expect("invalid DEFAULT_PORT_OFFSET"),
+                timeout: Duration::from_secs(
+                    DEFAULT_TIMEOUT_SECS
+                        .parse()
+                        .expect("invalid DEFAULT_TIMEOUT_SECS"),
+                ),
+                keep_ledger: false,
+            }
+        }
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_self_test_default() {
+        verify_args_struct_by_command(command(), vec![COMMAND], SelfTestArgs::default());
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_self_test_with_args() {
+        verify_args_struct_by_command(
+            command(),
+            vec![
+                COMMAND,
+                "--port-offset",
+                "2000",
+                "--timeout",
+                "30",
+                "--keep-ledger",
+            ],
+            SelfTestArgs {
+                port_offset: 2000,
+                timeout: Duration::from_secs(30),
+                keep_ledger: true,
+            },
+        );
+    }
+
+    #[test]
+    fn test_remap_port_range() {
+        assert_eq!(remap_port_range((8000, 10000), 10000), Some((18000, 20000)));
+        assert_eq!(remap_port_range((8000, 10000), 0), Some((8000, 10000)));
+        assert_eq!(remap_port_range((60000, 62000), 5000), None);
+    }
+}
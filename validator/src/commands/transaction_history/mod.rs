@@ -0,0 +1,135 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
+    solana_rpc::transaction_status_service::TransactionHistoryStatus,
+    std::path::Path,
+};
+
+pub const COMMAND: &str = "transaction-history";
+
+#[derive(Debug, PartialEq)]
+pub struct TransactionHistoryStartArgs {
+    pub extended_metadata: bool,
+}
+
+impl FromClapArgMatches for TransactionHistoryStartArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(TransactionHistoryStartArgs {
+            extended_metadata: matches.is_present("extended_metadata"),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Manage the writing of the validator's RPC transaction history")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::InferSubcommands)
+        .subcommand(
+            SubCommand::with_name("status")
+                .about("Display whether the transaction history is being written"),
+        )
+        .subcommand(
+            SubCommand::with_name("start")
+                .about("Start writing the transaction history")
+                .arg(
+                    Arg::with_name("extended_metadata")
+                        .long("extended-metadata")
+                        .takes_value(false)
+                        .help(
+                            "Include CPI inner instructions, logs, and return data in the \
+                             transaction history",
+                        ),
+                )
+                .after_help(
+                    "Note: writing starts with the next slot the validator replays or produces. \
+                     The history has no entries for the slots before it",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stop")
+                .about("Stop writing the transaction history")
+                .after_help(
+                    "Note: writing stops with the next slot the validator replays or produces. \
+                     Block metadata is still written, but the transactions of the following \
+                     slots are missing from the history, also after writing is started again",
+                ),
+        )
+        .after_help(
+            "Note: the validator must have been started with --enable-rpc-transaction-history. \
+             Add --rpc-transaction-history-paused to start it without writing the history",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let status = match matches.subcommand() {
+        ("status", _) => admin_rpc_service::runtime()
+            .block_on(async move { admin_client.await?.transaction_history().await })?,
+        ("start", Some(subcommand_matches)) => {
+            let TransactionHistoryStartArgs { extended_metadata } =
+                TransactionHistoryStartArgs::from_clap_arg_match(subcommand_matches)?;
+            admin_rpc_service::runtime().block_on(async move {
+                admin_client
+                    .await?
+                    .set_transaction_history(true, extended_metadata)
+                    .await
+            })?
+        }
+        ("stop", _) => admin_rpc_service::runtime().block_on(async move {
+            admin_client
+                .await?
+                .set_transaction_history(false, false)
+                .await
+        })?,
+        _ => unreachable!(),
+    };
+    print_status(&status);
+
+    Ok(())
+}
+
+fn print_status(status: &TransactionHistoryStatus) {
+    match (
+        status.enable_rpc_transaction_history,
+        status.recording_since,
+    ) {
+        (true, Some(slot)) => println!("Transaction history: written since slot {slot}"),
+        (true, None) => println!("Transaction history: written from the next slot"),
+        (false, Some(slot)) => {
+            println!("Transaction history: written since slot {slot}, stopping at the next slot")
+        }
+        (false, None) => println!("Transaction history: not written"),
+    }
+    println!(
+        "Extended metadata: {}",
+        if status.enable_extended_tx_metadata_storage {
+            "included"
+        } else {
+            "not included"
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_args_struct_by_command_transaction_history_start() {
+        for (args, extended_metadata) in [
+            (vec![COMMAND, "start"], false),
+            (vec![COMMAND, "start", "--extended-metadata"], true),
+        ] {
+            let matches = command().get_matches_from(args);
+            let subcommand_matches = matches.subcommand_matches("start").unwrap();
+            assert_eq!(
+                TransactionHistoryStartArgs::from_clap_arg_match(subcommand_matches).unwrap(),
+                TransactionHistoryStartArgs { extended_metadata }
+            );
+        }
+    }
+}
@@ -6,6 +6,7 @@ use {
     },
     clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
     console::style,
+    log::*,
     solana_clap_utils::{
         input_parsers::pubkey_of,
         input_validators::{is_parsable, is_pubkey_or_keypair, is_valid_percentage},
@@ -96,6 +97,13 @@ pub(crate) fn command<'a>() -> App<'a, 'a> {
 pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
     let wait_for_restart_window_args = WaitForRestartWindowArgs::from_clap_arg_match(matches)?;
 
+    // Deprecated in v4.0.0
+    agave_logger::setup_with_default("warn");
+    warn!(
+        "The wait-for-restart-window command has been deprecated, use \"agave-validator \
+         restart\" instead"
+    );
+
     wait_for_restart_window(
         ledger_path,
         wait_for_restart_window_args.identity,
@@ -0,0 +1,88 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{App, Arg, ArgMatches, SubCommand},
+    solana_cli_output::OutputFormat,
+    std::path::Path,
+};
+
+const COMMAND: &str = "wen-restart-status";
+
+#[derive(Debug, PartialEq)]
+pub struct WenRestartStatusArgs {
+    pub output: OutputFormat,
+}
+
+impl FromClapArgMatches for WenRestartStatusArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(WenRestartStatusArgs {
+            output: OutputFormat::from_matches(matches, "output", false),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Display whether the cluster appears halted, and the progress of the wen restart")
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+        .after_help(
+            "The cluster is reported as halted when the root has not advanced for \
+             --cluster-halt-detection-secs. While the validator runs with --wen-restart, the \
+             progress of the restart is read from its progress file, and once the restart is \
+             done, the next step holds the arguments to restart the validator with. The JSON \
+             output is meant for scripts automating the restart",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let wen_restart_status_args = WenRestartStatusArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let wen_restart_status = admin_rpc_service::runtime()
+        .block_on(async move { admin_client.await?.wen_restart_status().await })?;
+
+    println!(
+        "{}",
+        wen_restart_status_args
+            .output
+            .formatted_string(&wen_restart_status)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::commands::tests::verify_args_struct_by_command};
+
+    #[test]
+    fn verify_args_struct_by_command_wen_restart_status_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND],
+            WenRestartStatusArgs {
+                output: OutputFormat::Display,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_wen_restart_status_with_output() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--output", "json"],
+            WenRestartStatusArgs {
+                output: OutputFormat::Json,
+            },
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use {
+    crate::{
+        admin_rpc_service,
+        commands::{FromClapArgMatches, Result},
+    },
+    clap::{value_t, App, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::input_validators::is_parsable,
+    solana_cli_output::OutputFormat,
+    std::path::Path,
+};
+
+const COMMAND: &str = "write-lock-contention";
+
+#[derive(Debug, PartialEq)]
+pub struct WriteLockContentionArgs {
+    pub limit: Option<usize>,
+    pub output: OutputFormat,
+}
+
+impl FromClapArgMatches for WriteLockContentionArgs {
+    fn from_clap_arg_match(matches: &ArgMatches) -> Result<Self> {
+        Ok(WriteLockContentionArgs {
+            limit: value_t!(matches, "limit", usize).ok(),
+            output: OutputFormat::from_matches(matches, "output", false),
+        })
+    }
+}
+
+pub fn command<'a>() -> App<'a, 'a> {
+    SubCommand::with_name(COMMAND)
+        .about("Display the writable accounts with the most lock contention in recent leader slots")
+        .arg(
+            Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .help("Number of accounts to display [default: 20]"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .value_name("MODE")
+                .possible_values(&["json", "json-compact"])
+                .help("Output display mode"),
+        )
+        .after_help(
+            "Every account written by a transaction that had to wait for account locks is \
+             charged with that transaction and the time it waited, so the hottest accounts rise \
+             to the top",
+        )
+}
+
+pub fn execute(matches: &ArgMatches, ledger_path: &Path) -> Result<()> {
+    let write_lock_contention_args = WriteLockContentionArgs::from_clap_arg_match(matches)?;
+
+    let admin_client = admin_rpc_service::connect(ledger_path);
+    let write_lock_contention = admin_rpc_service::runtime().block_on(async move {
+        admin_client
+            .await?
+            .write_lock_contention(write_lock_contention_args.limit)
+            .await
+    })?;
+
+    println!(
+        "{}",
+        write_lock_contention_args
+            .output
+            .formatted_string(&write_lock_contention)
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::commands::tests::{
+            verify_args_struct_by_command, verify_args_struct_by_command_is_error,
+        },
+    };
+
+    #[test]
+    fn verify_args_struct_by_command_write_lock_contention_default() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND],
+            WriteLockContentionArgs {
+                limit: None,
+                output: OutputFormat::Display,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_write_lock_contention_with_args() {
+        verify_args_struct_by_command(
+            command(),
+            vec![COMMAND, "--limit", "5", "--output", "json"],
+            WriteLockContentionArgs {
+                limit: Some(5),
+                output: OutputFormat::Json,
+            },
+        );
+    }
+
+    #[test]
+    fn verify_args_struct_by_command_write_lock_contention_invalid_limit() {
+        verify_args_struct_by_command_is_error::<WriteLockContentionArgs>(
+            command(),
+            vec![COMMAND, "--limit", "many"],
+        );
+    }
+}
@@ -0,0 +1,660 @@
+//! On-demand sampling of the CPU time of the validator's threads
+//!
+//! Investigating a performance problem on a production node used to mean installing `perf` or
+//! another profiler on it. Instead, the validator samples its own threads for a short burst: a
+//! `perf_event_open` task clock event per thread interrupts it at a fixed frequency while it runs
+//! and records its user space call chain. The samples are returned as collapsed stacks, one line
+//! per distinct stack with the thread name, the frames from the outermost in, and the number of
+//! samples, which `flamegraph.pl` or `inferno-flamegraph` render as a flame graph.
+//!
+//! The kernel walks the user space stacks along the frame pointers, so the stacks are only
+//! complete for a validator built with `RUSTFLAGS="-C force-frame-pointers=yes"`. Otherwise only
+//! the innermost frame of each sample can be relied upon.
+
+use {
+    serde::{Deserialize, Serialize},
+    std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    },
+};
+
+/// Sampling frequency when none is given, off the round numbers to avoid sampling in lockstep
+/// with periodic work
+pub const DEFAULT_PROFILE_FREQUENCY: u64 = 99;
+pub const MAX_PROFILE_FREQUENCY: u64 = 1_000;
+pub const MAX_PROFILE_DURATION: Duration = Duration::from_secs(60);
+
+static PROFILE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Held while a profile is taken. The samplers of concurrent profiles would interrupt the same
+/// threads, so only one profile is taken at a time
+pub struct ProfileGuard(());
+
+impl ProfileGuard {
+    /// `None` while another profile is taken
+    pub fn try_acquire() -> Option<Self> {
+        (!PROFILE_RUNNING.swap(true, Ordering::Acquire)).then_some(Self(()))
+    }
+}
+
+impl Drop for ProfileGuard {
+    fn drop(&mut self) {
+        PROFILE_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuProfile {
+    /// Number of threads sampled
+    pub threads: usize,
+    pub samples: u64,
+    /// Samples the kernel dropped because they were not read in time
+    pub lost_samples: u64,
+    /// One line per distinct stack: the thread name and the frames from the outermost in,
+    /// separated by `;`, then the number of samples
+    pub collapsed_stacks: String,
+}
+
+/// Samples the threads whose names start with one of `thread_names`, or all threads if there
+/// are none, `frequency` times per second of CPU time for `duration`
+#[cfg(target_os = "linux")]
+pub fn profile_cpu(
+    thread_names: &[String],
+    duration: Duration,
+    frequency: u64,
+) -> Result<CpuProfile, String> {
+    linux::profile_cpu(thread_names, duration, frequency)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn profile_cpu(
+    _thread_names: &[String],
+    _duration: Duration,
+    _frequency: u64,
+) -> Result<CpuProfile, String> {
+    Err("CPU profiling is only available on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use {
+        super::CpuProfile,
+        std::{
+            collections::HashMap,
+            fmt::Write as _,
+            fs::{self, File},
+            io,
+            os::unix::fs::FileExt,
+            path::{Path, PathBuf},
+            ptr,
+            sync::atomic::{fence, Ordering},
+            thread::sleep,
+            time::{Duration, Instant},
+        },
+    };
+
+    const PERF_TYPE_SOFTWARE: u32 = 1;
+    const PERF_COUNT_SW_TASK_CLOCK: u64 = 1;
+    const PERF_SAMPLE_IP: u64 = 1 << 0;
+    const PERF_SAMPLE_TID: u64 = 1 << 1;
+    const PERF_SAMPLE_CALLCHAIN: u64 = 1 << 5;
+    const PERF_ATTR_FLAG_DISABLED: u64 = 1 << 0;
+    const PERF_ATTR_FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const PERF_ATTR_FLAG_EXCLUDE_HV: u64 = 1 << 6;
+    const PERF_ATTR_FLAG_FREQ: u64 = 1 << 10;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_RECORD_LOST: u32 = 2;
+    const PERF_RECORD_SAMPLE: u32 = 9;
+    /// Call chain entries from here on mark whether the following frames are in the kernel or in
+    /// user space
+    const PERF_CONTEXT_MAX: u64 = -4095i64 as u64;
+    /// Offsets of `data_head` and `data_tail` in `struct perf_event_mmap_page`
+    const DATA_HEAD_OFFSET: usize = 1024;
+    const DATA_TAIL_OFFSET: usize = 1032;
+    /// Pages of the ring buffer of each thread, a power of two
+    const RING_DATA_PAGES: usize = 16;
+    const DRAIN_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// `struct perf_event_attr` up to `PERF_ATTR_SIZE_VER5`
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_freq: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved: u16,
+    }
+
+    pub(super) fn profile_cpu(
+        thread_names: &[String],
+        duration: Duration,
+        frequency: u64,
+    ) -> Result<CpuProfile, String> {
+        // SAFETY: sysconf only reads a system limit
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        // SAFETY: gettid takes no arguments and can not fail
+        let own_tid = unsafe { libc::syscall(libc::SYS_gettid) } as i32;
+        let mut samplers = Vec::new();
+        let mut open_error = None;
+        for (tid, name) in threads(thread_names) {
+            if tid == own_tid {
+                continue;
+            }
+            match ThreadSampler::open(tid, name, frequency, page_size) {
+                Ok(sampler) => samplers.push(sampler),
+                // The thread exited in the meantime
+                Err(err) if err.raw_os_error() == Some(libc::ESRCH) => {}
+                Err(err) => open_error = Some(err),
+            }
+        }
+        if samplers.is_empty() {
+            return Err(match open_error {
+                Some(err) => format!(
+                    "failed to start sampling: {err}. Sampling needs \
+                     /proc/sys/kernel/perf_event_paranoid to be 2 or lower, or CAP_PERFMON"
+                ),
+                None => format!("no thread is named {}", thread_names.join(" or ")),
+            });
+        }
+
+        let mut stacks = HashMap::<(usize, Vec<u64>), u64>::new();
+        let mut lost_samples = 0;
+        samplers.iter().for_each(ThreadSampler::enable);
+        let deadline = Instant::now() + duration;
+        loop {
+            let now = Instant::now();
+            let done = now >= deadline;
+            if done {
+                samplers.iter().for_each(ThreadSampler::disable);
+            } else {
+                sleep(DRAIN_INTERVAL.min(deadline - now));
+            }
+            for (index, sampler) in samplers.iter().enumerate() {
+                sampler.drain(&mut lost_samples, |call_chain| {
+                    *stacks.entry((index, call_chain)).or_default() += 1;
+                });
+            }
+            if done {
+                break;
+            }
+        }
+
+        let mut symbolizer = Symbolizer::new();
+        let mut lines: Vec<_> = stacks
+            .iter()
+            .map(|((index, call_chain), count)| {
+                let mut line = samplers[*index].name.replace(';', ":");
+                // Innermost first, and all but the sampled address are return addresses, which
+                // point past the call
+                for (depth, address) in call_chain.iter().enumerate().rev() {
+                    let address = if depth == 0 { *address } else { address - 1 };
+                    line.push(';');
+                    line.push_str(&symbolizer.symbolize(address).replace(';', ":"));
+                }
+                let _ = write!(line, " {count}");
+                line
+            })
+            .collect();
+        lines.sort_unstable();
+
+        let threads = samplers.len();
+        Ok(CpuProfile {
+            threads,
+            samples: stacks.values().sum(),
+            lost_samples,
+            collapsed_stacks: lines.into_iter().fold(String::new(), |mut stacks, line| {
+                stacks.push_str(&line);
+                stacks.push('\n');
+                stacks
+            }),
+        })
+    }
+
+    /// The threads of the process whose names start with one of `names`, all of them if there
+    /// are none
+    fn threads(names: &[String]) -> Vec<(i32, String)> {
+        let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+            return vec![];
+        };
+        tasks
+            .flatten()
+            .filter_map(|task| {
+                let tid = task.file_name().to_str()?.parse().ok()?;
+                let name = fs::read_to_string(task.path().join("comm")).ok()?;
+                let name = name.trim().to_string();
+                (names.is_empty() || names.iter().any(|prefix| name.starts_with(prefix)))
+                    .then_some((tid, name))
+            })
+            .collect()
+    }
+
+    struct ThreadSampler {
+        name: String,
+        fd: libc::c_int,
+        ring: *mut u8,
+        ring_len: usize,
+        page_size: usize,
+    }
+
+    impl ThreadSampler {
+        fn open(tid: i32, name: String, frequency: u64, page_size: usize) -> io::Result<Self> {
+            let attr = PerfEventAttr {
+                type_: PERF_TYPE_SOFTWARE,
+                size: std::mem::size_of::<PerfEventAttr>() as u32,
+                config: PERF_COUNT_SW_TASK_CLOCK,
+                sample_freq: frequency,
+                sample_type: PERF_SAMPLE_IP | PERF_SAMPLE_TID | PERF_SAMPLE_CALLCHAIN,
+                flags: PERF_ATTR_FLAG_DISABLED
+                    | PERF_ATTR_FLAG_EXCLUDE_KERNEL
+                    | PERF_ATTR_FLAG_EXCLUDE_HV
+                    | PERF_ATTR_FLAG_FREQ,
+                ..PerfEventAttr::default()
+            };
+            // Follows the thread on any CPU, in no group
+            // SAFETY: `attr` is a valid `perf_event_attr` that outlives the call, and its `size`
+            // tells the kernel how much of it to read
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_perf_event_open,
+                    &attr as *const PerfEventAttr,
+                    tid,
+                    -1,
+                    -1,
+                    PERF_FLAG_FD_CLOEXEC,
+                )
+            } as libc::c_int;
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let ring_len = (1 + RING_DATA_PAGES) * page_size;
+            // SAFETY: a new shared mapping of the ring buffer of `fd`, which is checked for
+            // failure below and unmapped only on drop
+            let ring = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    ring_len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    fd,
+                    0,
+                )
+            };
+            if ring == libc::MAP_FAILED {
+                let err = io::Error::last_os_error();
+                // SAFETY: `fd` was opened above and is not used after this
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+            Ok(Self {
+                name,
+                fd,
+                ring: ring.cast(),
+                ring_len,
+                page_size,
+            })
+        }
+
+        fn enable(&self) {
+            // SAFETY: `fd` is a perf event owned by `self`, and the ioctl takes no pointer
+            unsafe { libc::ioctl(self.fd, PERF_EVENT_IOC_ENABLE as _, 0) };
+        }
+
+        fn disable(&self) {
+            // SAFETY: `fd` is a perf event owned by `self`, and the ioctl takes no pointer
+            unsafe { libc::ioctl(self.fd, PERF_EVENT_IOC_DISABLE as _, 0) };
+        }
+
+        /// Consumes the records written since the last call, passing the call chain of each
+        /// sample to `on_sample`, innermost frame first
+        fn drain(&self, lost_samples: &mut u64, mut on_sample: impl FnMut(Vec<u64>)) {
+            // SAFETY: the first page of the mapping is the aligned `perf_event_mmap_page`, which
+            // the kernel updates concurrently, hence the volatile accesses. The data pages follow
+            // it up to `ring_len`, and the mapping lives as long as `self`
+            let data_head =
+                unsafe { ptr::read_volatile(self.ring.add(DATA_HEAD_OFFSET).cast::<u64>()) };
+            // Pairs with the kernel's write barrier before it advances `data_head`
+            fence(Ordering::Acquire);
+            let data_tail =
+                unsafe { ptr::read_volatile(self.ring.add(DATA_TAIL_OFFSET).cast::<u64>()) };
+            let data = unsafe {
+                std::slice::from_raw_parts(
+                    self.ring.add(self.page_size),
+                    self.ring_len - self.page_size,
+                )
+            };
+
+            let mut tail = data_tail;
+            while tail < data_head {
+                let header = read_ring(data, tail, 8);
+                let record_type = u32::from_ne_bytes(header[0..4].try_into().unwrap());
+                let record_size = u16::from_ne_bytes(header[6..8].try_into().unwrap()) as u64;
+                if record_size < 8 {
+                    break;
+                }
+                let record = read_ring(data, tail, record_size as usize);
+                match record_type {
+                    PERF_RECORD_SAMPLE => on_sample(parse_sample(&record)),
+                    PERF_RECORD_LOST if record.len() >= 24 => {
+                        *lost_samples += u64::from_ne_bytes(record[16..24].try_into().unwrap());
+                    }
+                    _ => {}
+                }
+                tail += record_size;
+            }
+
+            // The records must be read before the kernel is allowed to overwrite them
+            fence(Ordering::SeqCst);
+            // SAFETY: as above, `data_tail` is in the first page of the mapping
+            unsafe {
+                ptr::write_volatile(self.ring.add(DATA_TAIL_OFFSET).cast::<u64>(), data_head)
+            };
+        }
+    }
+
+    impl Drop for ThreadSampler {
+        fn drop(&mut self) {
+            // SAFETY: the mapping and the file descriptor are owned by `self` and are not used
+            // after this
+            unsafe {
+                libc::munmap(self.ring.cast(), self.ring_len);
+                libc::close(self.fd);
+            }
+        }
+    }
+
+    /// Copies `len` bytes at `position` out of the ring buffer `data`, which they may wrap around
+    fn read_ring(data: &[u8], position: u64, len: usize) -> Vec<u8> {
+        let start = (position % data.len() as u64) as usize;
+        data[start..]
+            .iter()
+            .chain(&data[..start])
+            .take(len)
+            .copied()
+            .collect()
+    }
+
+    /// The call chain of a `PERF_RECORD_SAMPLE` of `PERF_SAMPLE_IP | PERF_SAMPLE_TID |
+    /// PERF_SAMPLE_CALLCHAIN`, innermost frame first
+    fn parse_sample(record: &[u8]) -> Vec<u64> {
+        let read_u64 = |offset: usize| {
+            record
+                .get(offset..offset + 8)
+                .map(|bytes| u64::from_ne_bytes(bytes.try_into().unwrap()))
+        };
+        // After the header come the sampled address, the pid and tid, then the call chain
+        let ip = read_u64(8).unwrap_or_default();
+        let num_frames = read_u64(24).unwrap_or_default() as usize;
+        let call_chain: Vec<_> = (0..num_frames)
+            .map_while(|index| read_u64(32 + index * 8))
+            .filter(|address| *address < PERF_CONTEXT_MAX)
+            .collect();
+        if call_chain.is_empty() {
+            vec![ip]
+        } else {
+            call_chain
+        }
+    }
+
+    struct Mapping {
+        start: u64,
+        end: u64,
+        offset: u64,
+        path: PathBuf,
+    }
+
+    /// Names the functions containing addresses of the process, from the symbol tables of the
+    /// files mapped into it
+    struct Symbolizer {
+        mappings: Vec<Mapping>,
+        symbols: HashMap<PathBuf, Option<ElfSymbols>>,
+        names: HashMap<u64, String>,
+    }
+
+    impl Symbolizer {
+        fn new() -> Self {
+            let mappings = fs::read_to_string("/proc/self/maps")
+                .unwrap_or_default()
+                .lines()
+                .filter_map(parse_mapping)
+                .collect();
+            Self {
+                mappings,
+                symbols: HashMap::new(),
+                names: HashMap::new(),
+            }
+        }
+
+        fn symbolize(&mut self, address: u64) -> String {
+            if let Some(name) = self.names.get(&address) {
+                return name.clone();
+            }
+            let name = self.lookup(address);
+            self.names.insert(address, name.clone());
+            name
+        }
+
+        fn lookup(&mut self, address: u64) -> String {
+            let Some(mapping) = self
+                .mappings
+                .iter()
+                .find(|mapping| (mapping.start..mapping.end).contains(&address))
+            else {
+                return "[unknown]".to_string();
+            };
+            let file_offset = address - mapping.start + mapping.offset;
+            let symbols = self
+                .symbols
+                .entry(mapping.path.clone())
+                .or_insert_with(|| ElfSymbols::load(&mapping.path).ok());
+            symbols
+                .as_ref()
+                .and_then(|symbols| symbols.lookup(file_offset))
+                .map(|name| format!("{:#}", rustc_demangle::demangle(name)))
+                .unwrap_or_else(|| {
+                    let file_name = mapping
+                        .path
+                        .file_name()
+                        .map(|file_name| file_name.to_string_lossy())
+                        .unwrap_or_default();
+                    format!("[{file_name}+{file_offset:#x}]")
+                })
+        }
+    }
+
+    /// Parses an executable, file backed line of `/proc/self/maps`
+    fn parse_mapping(line: &str) -> Option<Mapping> {
+        let mut fields = line.split_whitespace();
+        let (start, end) = fields.next()?.split_once('-')?;
+        let permissions = fields.next()?;
+        let offset = fields.next()?;
+        let path = fields.nth(2)?;
+        if !permissions.contains('x') || !path.starts_with('/') {
+            return None;
+        }
+        Some(Mapping {
+            start: u64::from_str_radix(start, 16).ok()?,
+            end: u64::from_str_radix(end, 16).ok()?,
+            offset: u64::from_str_radix(offset, 16).ok()?,
+            path: PathBuf::from(path),
+        })
+    }
+
+    /// The function symbols of a 64-bit little endian ELF file
+    struct ElfSymbols {
+        /// File offset, virtual address and size of each loaded segment
+        segments: Vec<(u64, u64, u64)>,
+        /// Address, size and name of each function, by address
+        functions: Vec<(u64, u64, String)>,
+    }
+
+    impl ElfSymbols {
+        fn load(path: &Path) -> io::Result<Self> {
+            const PT_LOAD: u32 = 1;
+            const SHT_SYMTAB: u32 = 2;
+            const SHT_DYNSYM: u32 = 11;
+            const STT_FUNC: u8 = 2;
+
+            let file = File::open(path)?;
+            let read = |offset: u64, len: usize| -> io::Result<Vec<u8>> {
+                let mut buffer = vec![0; len];
+                file.read_exact_at(&mut buffer, offset)?;
+                Ok(buffer)
+            };
+            let header = read(0, 64)?;
+            if header[0..4] != *b"\x7fELF" || header[4] != 2 || header[5] != 1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not a 64-bit little endian ELF file",
+                ));
+            }
+            let program_headers = read(
+                u64_at(&header, 0x20),
+                u16_at(&header, 0x36) as usize * u16_at(&header, 0x38) as usize,
+            )?;
+            let segments = program_headers
+                .chunks_exact(u16_at(&header, 0x36).max(1) as usize)
+                .filter(|program_header| u32_at(program_header, 0) == PT_LOAD)
+                .map(|program_header| {
+                    (
+                        u64_at(program_header, 8),
+                        u64_at(program_header, 16),
+                        u64_at(program_header, 32),
+                    )
+                })
+                .collect();
+
+            let section_header_size = u16_at(&header, 0x3a).max(1) as usize;
+            let section_headers = read(
+                u64_at(&header, 0x28),
+                section_header_size * u16_at(&header, 0x3c) as usize,
+            )?;
+            let section_headers: Vec<_> =
+                section_headers.chunks_exact(section_header_size).collect();
+            // The full symbol table if the file was not stripped, the dynamic one otherwise
+            let symbol_table = [SHT_SYMTAB, SHT_DYNSYM]
+                .into_iter()
+                .find_map(|section_type| {
+                    section_headers
+                        .iter()
+                        .find(|section_header| u32_at(section_header, 4) == section_type)
+                });
+            let mut functions = Vec::new();
+            if let Some(symbol_table) = symbol_table {
+                let string_table = section_headers
+                    .get(u32_at(symbol_table, 40) as usize)
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+                let strings = read(u64_at(string_table, 24), u64_at(string_table, 32) as usize)?;
+                let symbols = read(u64_at(symbol_table, 24), u64_at(symbol_table, 32) as usize)?;
+                for symbol in symbols.chunks_exact(24) {
+                    let address = u64_at(symbol, 8);
+                    if symbol[4] & 0xf != STT_FUNC || address == 0 {
+                        continue;
+                    }
+                    let name_start = u32_at(symbol, 0) as usize;
+                    let Some(name) = strings.get(name_start..).and_then(|name| {
+                        name.split(|byte| *byte == 0)
+                            .next()
+                            .and_then(|name| std::str::from_utf8(name).ok())
+                    }) else {
+                        continue;
+                    };
+                    functions.push((address, u64_at(symbol, 16), name.to_string()));
+                }
+            }
+            functions.sort_unstable_by_key(|(address, ..)| *address);
+            functions.dedup_by_key(|(address, ..)| *address);
+            Ok(Self {
+                segments,
+                functions,
+            })
+        }
+
+        /// The name of the function at `file_offset` of the file
+        fn lookup(&self, file_offset: u64) -> Option<&str> {
+            let address = self
+                .segments
+                .iter()
+                .find(|(offset, _, size)| (*offset..offset + size).contains(&file_offset))
+                .map(|(offset, address, _)| file_offset - offset + address)?;
+            let index = self
+                .functions
+                .partition_point(|(function_address, ..)| *function_address <= address)
+                .checked_sub(1)?;
+            let (function_address, size, name) = &self.functions[index];
+            (*size == 0 || address < function_address + size).then_some(name.as_str())
+        }
+    }
+
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[inline(never)]
+        fn cpu_profiler_test_marker() -> u64 {
+            std::hint::black_box(42)
+        }
+
+        #[test]
+        fn test_symbolize() {
+            assert_eq!(cpu_profiler_test_marker(), 42);
+            let mut symbolizer = Symbolizer::new();
+            let name = symbolizer.symbolize(cpu_profiler_test_marker as *const () as u64 + 1);
+            assert!(name.ends_with("cpu_profiler_test_marker"), "{name}");
+            assert_eq!(symbolizer.symbolize(1), "[unknown]");
+        }
+
+        #[test]
+        fn test_parse_sample() {
+            let sample = |ip: u64, call_chain: &[u64]| {
+                let mut record = vec![0; 24];
+                record[8..16].copy_from_slice(&ip.to_ne_bytes());
+                record.extend((call_chain.len() as u64).to_ne_bytes());
+                for address in call_chain {
+                    record.extend(address.to_ne_bytes());
+                }
+                record
+            };
+            let user_context = -512i64 as u64;
+            assert_eq!(
+                parse_sample(&sample(0x10, &[user_context, 0x10, 0x20, 0x30])),
+                vec![0x10, 0x20, 0x30]
+            );
+            assert_eq!(parse_sample(&sample(0x10, &[])), vec![0x10]);
+
+            let data: Vec<u8> = (0..16).collect();
+            assert_eq!(read_ring(&data, 14, 4), vec![14, 15, 0, 1]);
+            assert_eq!(read_ring(&data, 18, 2), vec![2, 3]);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Crash reports written when the validator panics
+//!
+//! A panic ends the validator process. Before it does, a report is written to
+//! `<ledger>/crash-reports` as JSON, holding:
+//! - the panic message and location, and the thread and subsystem it happened on
+//! - the backtrace of the panicking thread
+//! - the threads of the process and the subsystem each belongs to
+//! - the root, highest frozen and working bank slots
+//! - the work in flight on each banking stage worker thread
+//! - the version, and a digest of the configuration the validator started with
+//!
+//! The report is also posted to the `--crash-report-webhook` URL, if any, so that operators
+//! learn about the crash without having to look for whatever the supervisor captured of stderr.
+
+use {
+    crate::diagnostics_bundle::{thread_dump, thread_subsystem},
+    log::*,
+    serde::Serialize,
+    solana_clock::Slot,
+    solana_core::admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
+    std::{
+        backtrace::Backtrace,
+        fs::{self, File},
+        io::{self, BufWriter, Write},
+        panic::PanicHookInfo,
+        path::PathBuf,
+        process,
+        sync::{Arc, RwLock},
+        thread,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+/// Time allowed for the webhook to accept a report, the process exits afterwards regardless
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+struct CrashReport {
+    timestamp: u64,
+    version: String,
+    config_digest: String,
+    message: String,
+    location: String,
+    thread: String,
+    subsystem: &'static str,
+    backtrace: String,
+    threads: String,
+    /// `None` if the validator was not initialized yet, or the banks were locked
+    slots: Option<CrashSlots>,
+    banking_in_flight: Vec<WorkerInFlight>,
+}
+
+#[derive(Debug, Serialize)]
+struct CrashSlots {
+    root: Slot,
+    highest_frozen: Option<Slot>,
+    working: Slot,
+}
+
+#[derive(Debug, Serialize)]
+struct WorkerInFlight {
+    worker: usize,
+    num_batches: usize,
+    num_transactions: usize,
+    cus: u64,
+}
+
+/// Writes a crash report when a thread of the validator panics
+pub struct CrashReporter {
+    ledger_path: PathBuf,
+    version: String,
+    config_digest: String,
+    post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+    webhook: Option<String>,
+}
+
+impl CrashReporter {
+    pub fn new(
+        ledger_path: PathBuf,
+        version: String,
+        effective_config: &str,
+        post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+        webhook: Option<String>,
+    ) -> Self {
+        Self {
+            ledger_path,
+            version,
+            config_digest: solana_sha256_hasher::hash(effective_config.as_bytes()).to_string(),
+            post_init,
+            webhook,
+        }
+    }
+
+    /// Writes a report on every panic, after the panic hook already in place ran
+    ///
+    /// Must be installed before `solana_metrics::set_panic_hook`, which exits the process once
+    /// the hooks before it ran.
+    pub fn install(self: Arc<Self>) {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            previous_hook(panic_info);
+            let location = panic_info
+                .location()
+                .map(|location| location.to_string())
+                .unwrap_or_else(|| "?".to_string());
+            let report = self.report(panic_message(panic_info), location);
+            match self.write_report(&report) {
+                Ok(path) => error!("Wrote crash report to {}", path.display()),
+                Err(err) => error!("Failed to write crash report: {err}"),
+            }
+            if let Some(webhook) = &self.webhook {
+                post_report(webhook, &report);
+            }
+        }));
+    }
+
+    fn report(&self, message: String, location: String) -> CrashReport {
+        let thread = thread::current().name().unwrap_or("?").to_string();
+        // The panicking thread may hold any lock, only take the ones that are free
+        let (slots, banking_in_flight) = match self.post_init.try_read() {
+            Ok(post_init) => match post_init.as_ref() {
+                Some(post_init) => (
+                    post_init
+                        .bank_forks
+                        .try_read()
+                        .ok()
+                        .map(|bank_forks| CrashSlots {
+                            root: bank_forks.root(),
+                            highest_frozen: bank_forks.frozen_banks().map(|(slot, _)| slot).max(),
+                            working: bank_forks.working_bank().slot(),
+                        }),
+                    post_init
+                        .in_flight_batches_report
+                        .per_thread()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(worker, in_flight)| WorkerInFlight {
+                            worker,
+                            num_batches: in_flight.num_batches,
+                            num_transactions: in_flight.num_transactions,
+                            cus: in_flight.cus,
+                        })
+                        .collect(),
+                ),
+                None => (None, vec![]),
+            },
+            Err(_) => (None, vec![]),
+        };
+        CrashReport {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            version: self.version.clone(),
+            config_digest: self.config_digest.clone(),
+            message,
+            location,
+            subsystem: thread_subsystem(&thread),
+            thread,
+            backtrace: Backtrace::force_capture().to_string(),
+            threads: thread_dump(),
+            slots,
+            banking_in_flight,
+        }
+    }
+
+    /// Writes `report` to a new file, never to the report of a thread that panicked in the same second
+    fn write_report(&self, report: &CrashReport) -> io::Result<PathBuf> {
+        let dir = self.ledger_path.join(CRASH_REPORTS_DIR);
+        fs::create_dir_all(&dir)?;
+        let prefix = format!("crash-{}-{}", report.timestamp, process::id());
+        let mut attempt = 0;
+        let (path, file) = loop {
+            let path = match attempt {
+                0 => dir.join(format!("{prefix}.json")),
+                attempt => dir.join(format!("{prefix}-{attempt}.json")),
+            };
+            match File::options().write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(err) => return Err(err),
+            }
+        };
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, report)?;
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+fn panic_message(panic_info: &PanicHookInfo) -> String {
+    if let Some(message) = panic_info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic_info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "?".to_string()
+    }
+}
+
+/// Posts `report` to `webhook` from a new thread, since the panicking thread may be running an
+/// async runtime where blocking requests are not allowed
+fn post_report(webhook: &str, report: &CrashReport) {
+    let Ok(body) = serde_json::to_string(report) else {
+        return;
+    };
+    let webhook = webhook.to_string();
+    let result = thread::Builder::new()
+        .name("solCrashReport".to_string())
+        .spawn(move || {
+            reqwest::blocking::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()?
+                .post(webhook)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()?
+                .error_for_status()
+                .map(|_| ())
+        })
+        .map(|handle| handle.join());
+    match result {
+        Ok(Ok(Ok(()))) => info!("Posted crash report to the webhook"),
+        Ok(Ok(Err(err))) => error!("Failed to post crash report to the webhook: {err}"),
+        Ok(Err(_)) | Err(_) => error!("Failed to post crash report to the webhook"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_write_report() {
+        let ledger_path = TempDir::new().unwrap();
+        let reporter = CrashReporter::new(
+            ledger_path.path().to_path_buf(),
+            "1.2.3".to_string(),
+            "config",
+            Arc::default(),
+            None,
+        );
+        let report = reporter.report("boom".to_string(), "src/lib.rs:1:1".to_string());
+        let path = reporter.write_report(&report).unwrap();
+        assert!(path.starts_with(ledger_path.path().join(CRASH_REPORTS_DIR)));
+
+        let written: serde_json::Value =
+            serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(written["version"], "1.2.3");
+        assert_eq!(
+            written["config_digest"],
+            solana_sha256_hasher::hash(b"config").to_string()
+        );
+        assert_eq!(written["message"], "boom");
+        assert_eq!(written["location"], "src/lib.rs:1:1");
+        assert!(!written["backtrace"].as_str().unwrap().is_empty());
+        // Not initialized yet
+        assert!(written["slots"].is_null());
+        assert_eq!(written["banking_in_flight"], serde_json::json!([]));
+
+        // Another panic within the same second does not overwrite the first report
+        let other_path = reporter.write_report(&report).unwrap();
+        assert_ne!(other_path, path);
+        assert_eq!(
+            fs::read_dir(ledger_path.path().join(CRASH_REPORTS_DIR))
+                .unwrap()
+                .count(),
+            2
+        );
+    }
+}
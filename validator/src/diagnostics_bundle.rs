@@ -0,0 +1,575 @@
+//! On-demand bundles of the state of a running validator, to attach to bug reports
+//!
+//! A bundle is a single tar archive written to `<ledger>/diagnostics`, holding:
+//! - `threads.txt`: every thread of the process, the subsystem it belongs to, its state, CPU time
+//!   and the kernel function it waits in
+//! - `metrics.txt`: the metrics points recorded since the last submission to the metrics database
+//! - `config.txt`: the version, the command line and the configuration parsed from it
+//! - `blockstore.txt`: the slot range of the blockstore and the size of each column family
+//! - `accounts.txt`: the banks in memory and the state of the accounts cache and index
+//! - `log.txt`: the last lines of the log file
+//!
+//! A bundle is requested with `agave-validator diagnostics-bundle`, or by sending SIGUSR2 to the
+//! validator process when the admin RPC service is unresponsive. Only the last
+//! [`MAX_RETAINED_BUNDLES`] bundles are kept.
+
+use {
+    crate::commands::run::args::RunArgs,
+    log::*,
+    solana_core::admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
+    solana_ledger::blockstore::Blockstore,
+    solana_signer::Signer,
+    std::{
+        collections::BTreeMap,
+        fmt::Write as _,
+        fs::{self, File},
+        io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, OnceLock, RwLock},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const DIAGNOSTICS_DIR: &str = "diagnostics";
+pub const DEFAULT_LOG_LINES: usize = 10_000;
+/// Number of bundles kept in the diagnostics directory, older ones are removed
+pub const MAX_RETAINED_BUNDLES: usize = 10;
+const BUNDLE_PREFIX: &str = "diagnostics-";
+const BUNDLE_EXTENSION: &str = ".tar";
+/// Bound on the log read for a bundle, whatever the length of its lines
+const MAX_LOG_BYTES: u64 = 64 * 1024 * 1024;
+const LOG_READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Thread name prefixes and the subsystem they belong to, longer prefixes first where they overlap
+const THREAD_SUBSYSTEMS: &[(&str, &str)] = &[
+    ("solRpcPubSub", "rpc-pubsub"),
+    ("solRpcNotif", "rpc-pubsub"),
+    ("solRpcUsage", "rpc"),
+    ("solRpcEl", "rpc"),
+    ("solJsonRpc", "rpc"),
+    ("solStx", "rpc"),
+    ("solTxStatus", "rpc"),
+    ("solAdminRpc", "admin-rpc"),
+    ("solBanknStgVote", "banking-stage"),
+    ("solBankn", "banking-stage"),
+    ("solBnkTx", "banking-stage"),
+    ("solReplay", "replay"),
+    ("solRepair", "repair"),
+    ("solTvuRecvRpr", "repair"),
+    ("solTvu", "turbine"),
+    ("solRetransmit", "turbine"),
+    ("solBroadcast", "turbine"),
+    ("solShredVerifr", "turbine"),
+    ("solWinInsert", "turbine"),
+    ("solGossip", "gossip"),
+    ("solTpu", "tpu"),
+    ("solSigVerify", "tpu"),
+    ("solPoh", "poh"),
+    ("solVote", "voting"),
+    ("solAccts", "accounts-db"),
+    ("solSnapshot", "snapshots"),
+    ("solBstore", "blockstore"),
+    ("solMetrics", "metrics"),
+    ("solSystemMonitr", "metrics"),
+    ("solDiagBundle", "diagnostics"),
+    ("solCrashReport", "diagnostics"),
+];
+
+pub(crate) fn thread_subsystem(name: &str) -> &'static str {
+    THREAD_SUBSYSTEMS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, subsystem)| *subsystem)
+        .unwrap_or("other")
+}
+
+/// Writes diagnostics bundles for a validator
+pub struct DiagnosticsBundler {
+    ledger_path: PathBuf,
+    logfile: Option<PathBuf>,
+    /// Description of the configuration the validator started with
+    effective_config: String,
+    post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+    /// Set once the validator opened its blockstore
+    blockstore: OnceLock<Arc<Blockstore>>,
+    /// Serializes bundles requested over the admin RPC and by signal
+    write_lock: Mutex<()>,
+}
+
+impl DiagnosticsBundler {
+    pub fn new(
+        ledger_path: PathBuf,
+        logfile: Option<PathBuf>,
+        effective_config: String,
+        post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+    ) -> Self {
+        Self {
+            ledger_path,
+            logfile,
+            effective_config,
+            post_init,
+            blockstore: OnceLock::new(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn set_blockstore(&self, blockstore: Arc<Blockstore>) {
+        let _ = self.blockstore.set(blockstore);
+    }
+
+    /// Writes a bundle with the last `log_lines` lines of the log file, returning its path
+    ///
+    /// A section that cannot be collected holds the reason instead, so a bundle is written even
+    /// when part of the validator is unavailable.
+    pub fn write_bundle(&self, log_lines: usize) -> io::Result<PathBuf> {
+        let _write_lock = self.write_lock.lock().unwrap();
+        let dir = self.ledger_path.join(DIAGNOSTICS_DIR);
+        fs::create_dir_all(&dir)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let path = dir.join(format!("{BUNDLE_PREFIX}{timestamp}{BUNDLE_EXTENSION}"));
+
+        let mut builder = tar::Builder::new(BufWriter::new(File::create(&path)?));
+        for (name, contents) in [
+            ("threads.txt", thread_dump()),
+            ("metrics.txt", metrics_snapshot()),
+            ("config.txt", self.effective_config.clone()),
+            ("blockstore.txt", self.blockstore_stats()),
+            ("accounts.txt", self.accounts_stats()),
+            ("log.txt", self.log_tail(log_lines)),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(timestamp);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents.as_bytes())?;
+        }
+        builder.into_inner()?.flush()?;
+
+        if let Err(err) = remove_old_bundles(&dir, MAX_RETAINED_BUNDLES) {
+            warn!("Failed to remove old diagnostics bundles: {err}");
+        }
+        Ok(path)
+    }
+
+    fn blockstore_stats(&self) -> String {
+        let Some(blockstore) = self.blockstore.get() else {
+            return "blockstore not opened yet\n".to_string();
+        };
+        let mut stats = String::new();
+        let _ = writeln!(stats, "lowest slot: {}", blockstore.lowest_slot());
+        match blockstore.highest_slot() {
+            Ok(highest_slot) => {
+                let _ = writeln!(stats, "highest slot: {highest_slot:?}");
+            }
+            Err(err) => {
+                let _ = writeln!(stats, "highest slot: {err}");
+            }
+        }
+        let _ = writeln!(stats, "max root: {}", blockstore.max_root());
+
+        match blockstore.live_files_metadata() {
+            Ok(live_files) => {
+                // Bytes and entries per column family
+                let mut column_families = BTreeMap::<_, (u64, u64, usize)>::new();
+                for live_file in &live_files {
+                    let (size, entries, files) = column_families
+                        .entry(live_file.column_family_name.as_str())
+                        .or_default();
+                    *size += live_file.size as u64;
+                    *entries += live_file.num_entries;
+                    *files += 1;
+                }
+                let _ = writeln!(stats, "\ncolumn family, bytes, entries, files");
+                for (name, (size, entries, files)) in column_families {
+                    let _ = writeln!(stats, "{name}, {size}, {entries}, {files}");
+                }
+            }
+            Err(err) => {
+                let _ = writeln!(stats, "live files: {err}");
+            }
+        }
+        stats
+    }
+
+    fn accounts_stats(&self) -> String {
+        let Some(bank_forks) = self
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|post_init| post_init.bank_forks.clone())
+        else {
+            return "validator not initialized yet\n".to_string();
+        };
+        let (root_bank, working_bank, num_banks) = {
+            let bank_forks = bank_forks.read().unwrap();
+            (
+                bank_forks.root_bank(),
+                bank_forks.working_bank(),
+                bank_forks.len(),
+            )
+        };
+        let accounts_db = &root_bank.rc.accounts.accounts_db;
+
+        let mut stats = String::new();
+        let _ = writeln!(stats, "root slot: {}", root_bank.slot());
+        let _ = writeln!(stats, "working slot: {}", working_bank.slot());
+        let _ = writeln!(stats, "banks: {num_banks}");
+        let _ = writeln!(stats, "epoch: {}", root_bank.epoch());
+        let _ = writeln!(stats, "capitalization: {}", root_bank.capitalization());
+        let _ = writeln!(
+            stats,
+            "accounts cache slots: {}",
+            accounts_db.accounts_cache.num_slots()
+        );
+        let _ = writeln!(
+            stats,
+            "accounts cache bytes: {}",
+            accounts_db.accounts_cache.size()
+        );
+        let _ = writeln!(
+            stats,
+            "accounts cache max flushed root: {}",
+            accounts_db.accounts_cache.fetch_max_flush_root()
+        );
+        let _ = writeln!(
+            stats,
+            "accounts index max root: {}",
+            accounts_db.accounts_index.max_root_inclusive()
+        );
+        let _ = writeln!(
+            stats,
+            "slots with uncleaned accounts: {}",
+            accounts_db.get_len_of_slots_with_uncleaned_pubkeys()
+        );
+        stats
+    }
+
+    fn log_tail(&self, log_lines: usize) -> String {
+        let Some(logfile) = &self.logfile else {
+            return "logging to stderr, not to a file\n".to_string();
+        };
+        match File::open(logfile).and_then(|mut file| tail(&mut file, log_lines, MAX_LOG_BYTES)) {
+            Ok(lines) => lines,
+            Err(err) => format!("failed to read {}: {err}\n", logfile.display()),
+        }
+    }
+}
+
+/// Removes all but the newest `num_retained` bundles in `dir`
+fn remove_old_bundles(dir: &Path, num_retained: usize) -> io::Result<()> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let timestamp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(BUNDLE_PREFIX))
+            .and_then(|name| name.strip_suffix(BUNDLE_EXTENSION))
+            .and_then(|timestamp| timestamp.parse::<u64>().ok());
+        if let Some(timestamp) = timestamp {
+            bundles.push((timestamp, path));
+        }
+    }
+    bundles.sort_unstable();
+    let num_removed = bundles.len().saturating_sub(num_retained);
+    for (_, path) in bundles.into_iter().take(num_removed) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Reads the last `num_lines` lines of `file`, reading at most `max_bytes` from its end
+fn tail(file: &mut File, num_lines: usize, max_bytes: u64) -> io::Result<String> {
+    let len = file.seek(SeekFrom::End(0))?;
+    let start = len.saturating_sub(max_bytes);
+    let mut position = len;
+    let mut buffer = Vec::new();
+    let mut newlines = 0;
+    while position > start {
+        let chunk_size = (position - start).min(LOG_READ_CHUNK_SIZE as u64);
+        position -= chunk_size;
+        let mut chunk = vec![0; chunk_size as usize];
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|byte| **byte == b'\n').count();
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+        if newlines > num_lines {
+            break;
+        }
+    }
+
+    let mut line_starts = buffer
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .map(|(index, _)| index + 1)
+        .filter(|index| *index < buffer.len())
+        .collect::<Vec<_>>();
+    if position == 0 {
+        line_starts.insert(0, 0);
+    }
+    let first_line = line_starts.len().saturating_sub(num_lines);
+    let tail_start = match line_starts.get(first_line) {
+        Some(start) if num_lines > 0 => *start,
+        _ => buffer.len(),
+    };
+    Ok(String::from_utf8_lossy(&buffer[tail_start..]).into_owned())
+}
+
+fn metrics_snapshot() -> String {
+    let mut snapshot = String::new();
+    for point in solana_metrics::metrics::snapshot() {
+        let _ = writeln!(snapshot, "{point}");
+    }
+    snapshot
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn thread_dump() -> String {
+    let read_task_file = |tid: &str, file: &str| {
+        fs::read_to_string(format!("/proc/self/task/{tid}/{file}"))
+            .map(|contents| contents.trim().to_string())
+    };
+    // SAFETY: sysconf() only reads a system setting, and returns -1 for an unknown name
+    let clock_ticks_per_second = match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    };
+
+    let tasks = match fs::read_dir("/proc/self/task") {
+        Ok(tasks) => tasks,
+        Err(err) => return format!("failed to list threads: {err}\n"),
+    };
+    let mut threads = Vec::new();
+    for task in tasks.flatten() {
+        let tid = task.file_name().to_string_lossy().into_owned();
+        let Ok(tid_number) = tid.parse::<u32>() else {
+            continue;
+        };
+        // The thread may exit while it is being read
+        let (Ok(name), Ok(stat)) = (read_task_file(&tid, "comm"), read_task_file(&tid, "stat"))
+        else {
+            continue;
+        };
+        // The fields after the name, which may itself contain spaces and parentheses
+        let fields = stat
+            .rsplit_once(')')
+            .map(|(_, fields)| fields.split_whitespace().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let state = fields.first().copied().unwrap_or("?").to_string();
+        let cpu_seconds = |index: usize| {
+            fields
+                .get(index)
+                .and_then(|ticks| ticks.parse::<u64>().ok())
+                .map(|ticks| ticks as f64 / clock_ticks_per_second)
+                .unwrap_or_default()
+        };
+        let (user_seconds, system_seconds) = (cpu_seconds(11), cpu_seconds(12));
+        let wchan = read_task_file(&tid, "wchan").unwrap_or_default();
+        threads.push((
+            thread_subsystem(&name),
+            name,
+            tid_number,
+            state,
+            user_seconds,
+            system_seconds,
+            wchan,
+        ));
+    }
+    threads.sort_by(|a, b| (a.0, &a.1, a.2).cmp(&(b.0, &b.1, b.2)));
+
+    let mut per_subsystem = BTreeMap::<_, usize>::new();
+    for thread in &threads {
+        *per_subsystem.entry(thread.0).or_default() += 1;
+    }
+    let mut dump = String::new();
+    let _ = writeln!(dump, "{} threads", threads.len());
+    for (subsystem, count) in per_subsystem {
+        let _ = writeln!(dump, "{subsystem}: {count}");
+    }
+    let _ = writeln!(
+        dump,
+        "\n{:<8} {:<14} {:<16} {:<5} {:>10} {:>10} wchan",
+        "tid", "subsystem", "name", "state", "user s", "system s"
+    );
+    for (subsystem, name, tid, state, user_seconds, system_seconds, wchan) in threads {
+        let _ = writeln!(
+            dump,
+            "{tid:<8} {subsystem:<14} {name:<16} {state:<5} {user_seconds:>10.2} \
+             {system_seconds:>10.2} {wchan}"
+        );
+    }
+    dump
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn thread_dump() -> String {
+    "thread dumps are only available on Linux\n".to_string()
+}
+
+/// Describes the configuration parsed from the command line, leaving out the identity keypair
+pub fn describe_effective_config(run_args: &RunArgs, version: &str) -> String {
+    let RunArgs {
+        identity_keypair,
+        ledger_path,
+        logfile,
+        entrypoints,
+        known_validators,
+        socket_addr_space,
+        rpc_bootstrap_config,
+        blockstore_options,
+        json_rpc_config,
+        pub_sub_config,
+        send_transaction_service_config,
+    } = run_args;
+    let mut config = String::new();
+    let _ = writeln!(config, "version: {version}");
+    let _ = writeln!(
+        config,
+        "command line: {:?}",
+        std::env::args().collect::<Vec<_>>()
+    );
+    let _ = writeln!(config, "identity: {}", identity_keypair.pubkey());
+    let _ = writeln!(config, "ledger path: {}", ledger_path.display());
+    let _ = writeln!(config, "log file: {logfile:?}");
+    let _ = writeln!(config, "entrypoints: {entrypoints:?}");
+    let _ = writeln!(config, "known validators: {known_validators:?}");
+    let _ = writeln!(config, "socket address space: {socket_addr_space:?}");
+    let _ = writeln!(config, "rpc bootstrap: {rpc_bootstrap_config:#?}");
+    let _ = writeln!(config, "blockstore: {blockstore_options:#?}");
+    let _ = writeln!(config, "json rpc: {json_rpc_config:#?}");
+    let _ = writeln!(config, "pubsub: {pub_sub_config:#?}");
+    let _ = writeln!(
+        config,
+        "send transaction service: {send_transaction_service_config:#?}"
+    );
+    config
+}
+
+/// Writes a bundle with the default number of log lines whenever the process receives SIGUSR2
+#[cfg(unix)]
+pub fn spawn_signal_handler(bundler: Arc<DiagnosticsBundler>) -> io::Result<()> {
+    let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGUSR2])?;
+    std::thread::Builder::new()
+        .name("solDiagBundle".to_string())
+        .spawn(move || {
+            for _ in signals.forever() {
+                match bundler.write_bundle(DEFAULT_LOG_LINES) {
+                    Ok(path) => info!("Wrote diagnostics bundle to {}", path.display()),
+                    Err(err) => warn!("Failed to write diagnostics bundle: {err}"),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    fn tail_of(contents: &str, num_lines: usize, max_bytes: u64) -> String {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("validator.log");
+        fs::write(&path, contents).unwrap();
+        tail(&mut File::open(path).unwrap(), num_lines, max_bytes).unwrap()
+    }
+
+    #[test]
+    fn test_tail() {
+        let log = "one\ntwo\nthree\n";
+        assert_eq!(tail_of(log, 0, 1024), "");
+        assert_eq!(tail_of(log, 1, 1024), "three\n");
+        assert_eq!(tail_of(log, 2, 1024), "two\nthree\n");
+        assert_eq!(tail_of(log, 10, 1024), log);
+        // A last line still being written
+        assert_eq!(tail_of("one\ntwo\nthr", 2, 1024), "two\nthr");
+        // A line cut by the byte limit is left out
+        assert_eq!(tail_of(log, 10, 8), "three\n");
+        assert_eq!(tail_of("", 10, 1024), "");
+
+        let long_log = (0..100_000)
+            .map(|i| format!("line {i}\n"))
+            .collect::<String>();
+        assert_eq!(
+            tail_of(&long_log, 2, MAX_LOG_BYTES),
+            "line 99998\nline 99999\n"
+        );
+    }
+
+    #[test]
+    fn test_thread_subsystem() {
+        assert_eq!(thread_subsystem("solRpcPubSub"), "rpc-pubsub");
+        assert_eq!(thread_subsystem("solJsonRpcSvc"), "rpc");
+        assert_eq!(thread_subsystem("solTvuRecvRpr"), "repair");
+        assert_eq!(thread_subsystem("solTvuFetchQuic"), "turbine");
+        assert_eq!(thread_subsystem("tokio-runtime-w"), "other");
+    }
+
+    #[test]
+    fn test_write_bundle() {
+        let ledger_path = TempDir::new().unwrap();
+        let logfile = ledger_path.path().join("validator.log");
+        fs::write(&logfile, "first\nsecond\n").unwrap();
+        let bundler = DiagnosticsBundler::new(
+            ledger_path.path().to_path_buf(),
+            Some(logfile),
+            "identity: test\n".to_string(),
+            Arc::new(RwLock::new(None)),
+        );
+
+        let path = bundler.write_bundle(1).unwrap();
+        assert!(path.starts_with(ledger_path.path().join(DIAGNOSTICS_DIR)));
+        let mut archive = tar::Archive::new(File::open(path).unwrap());
+        let entries = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().display().to_string();
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).unwrap();
+                (name, contents)
+            })
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            entries.keys().collect::<Vec<_>>(),
+            [
+                "accounts.txt",
+                "blockstore.txt",
+                "config.txt",
+                "log.txt",
+                "metrics.txt",
+                "threads.txt"
+            ]
+        );
+        assert_eq!(entries["config.txt"], "identity: test\n");
+        assert_eq!(entries["log.txt"], "second\n");
+        assert_eq!(entries["blockstore.txt"], "blockstore not opened yet\n");
+    }
+
+    #[test]
+    fn test_remove_old_bundles() {
+        let dir = TempDir::new().unwrap();
+        for timestamp in [3, 1, 10, 2] {
+            fs::write(dir.path().join(format!("diagnostics-{timestamp}.tar")), "").unwrap();
+        }
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        remove_old_bundles(dir.path(), 2).unwrap();
+        let mut names = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            ["diagnostics-10.tar", "diagnostics-3.tar", "notes.txt"]
+        );
+    }
+}
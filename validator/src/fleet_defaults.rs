@@ -0,0 +1,411 @@
+//! Machine-wide defaults for validator arguments
+//!
+//! Operators running several validators can keep shared settings (metrics, known validators,
+//! alerting, ...) in a single TOML file instead of repeating them on every command line. Each key
+//! is the long name of a validator argument, without the leading `--`:
+//!
+//! ```toml
+//! known-validator = ["7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2"]
+//! only-known-rpc = true
+//! limit-ledger-size = 50000000
+//! ```
+//!
+//! `true` passes a flag, `false` leaves it out, and an array passes the argument once per element.
+//! The file sits beneath the command line: any argument that is also given on the command line,
+//! under its long or short name or any of its aliases, is taken from the command line only.
+
+use {
+    clap::App,
+    std::{
+        ffi::{OsStr, OsString},
+        fs, io,
+        path::{Path, PathBuf},
+    },
+    toml::{Table, Value},
+};
+
+pub const DEFAULT_FLEET_DEFAULTS_PATH: &str = "/etc/agave/fleet.toml";
+pub const FLEET_DEFAULTS_ARG: &str = "fleet-defaults";
+pub const NO_FLEET_DEFAULTS_ARG: &str = "no-fleet-defaults";
+
+/// Returns `args` with the arguments from the fleet defaults file inserted after the program name
+///
+/// The file is read from the path given by `--fleet-defaults`, or from
+/// [`DEFAULT_FLEET_DEFAULTS_PATH`] if that exists. Nothing is added if `--no-fleet-defaults` is
+/// given. Arguments are matched against the command line through their definitions in `app`.
+pub fn apply_fleet_defaults(app: &App, args: Vec<OsString>) -> Result<Vec<OsString>, String> {
+    if args
+        .iter()
+        .any(|arg| is_long_arg(arg, NO_FLEET_DEFAULTS_ARG))
+    {
+        return Ok(args);
+    }
+
+    let (path, explicit) = match long_arg_value(&args, FLEET_DEFAULTS_ARG) {
+        Some(path) => (PathBuf::from(path), true),
+        None => (PathBuf::from(DEFAULT_FLEET_DEFAULTS_PATH), false),
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if !explicit && err.kind() == io::ErrorKind::NotFound => return Ok(args),
+        Err(err) => return Err(format!("unable to read {}: {err}", path.display())),
+    };
+    let fleet_args = fleet_args(&path, &contents, &ArgDefs::new(app), &args)?;
+
+    let mut args = args.into_iter();
+    Ok(args
+        .next()
+        .into_iter()
+        .chain(fleet_args)
+        .chain(args)
+        .collect())
+}
+
+/// Returns the arguments from the fleet defaults file at `path`
+pub fn load_fleet_args(path: &Path) -> Result<Vec<OsString>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+    fleet_args(path, &contents, &ArgDefs::default(), &[])
+}
+
+/// Names under which an argument can be given on the command line
+struct ArgNames<'a> {
+    short: Option<char>,
+    longs: Vec<&'a str>,
+}
+
+/// Short and long names of the arguments of an [`App`]
+#[derive(Default)]
+struct ArgDefs<'a> {
+    args: Vec<ArgNames<'a>>,
+    /// Short names of the arguments that take a value
+    shorts_with_value: Vec<char>,
+}
+
+impl<'a> ArgDefs<'a> {
+    fn new(app: &App<'_, 'a>) -> Self {
+        // clap 2 only exposes the argument definitions through the app's parser
+        let flags = app.p.flags.iter().map(|flag| &flag.s);
+        let opts = app.p.opts.iter().map(|opt| &opt.s);
+        let args = flags
+            .chain(opts)
+            .map(|switched| ArgNames {
+                short: switched.short,
+                longs: switched
+                    .long
+                    .into_iter()
+                    .chain(
+                        switched
+                            .aliases
+                            .iter()
+                            .flatten()
+                            .map(|&(alias, _visible)| alias),
+                    )
+                    .collect(),
+            })
+            .collect();
+        let shorts_with_value = app.p.opts.iter().filter_map(|opt| opt.s.short).collect();
+        Self {
+            args,
+            shorts_with_value,
+        }
+    }
+
+    /// Whether the argument with the long name or alias `name` is given in `command_line`
+    ///
+    /// Arguments that `app` does not define are only matched by `--name`.
+    fn is_given(&self, name: &str, command_line: &[OsString]) -> bool {
+        let Some(arg) = self.args.iter().find(|arg| arg.longs.contains(&name)) else {
+            return command_line.iter().any(|arg| is_long_arg(arg, name));
+        };
+        command_line.iter().any(|cl_arg| {
+            arg.longs.iter().any(|long| is_long_arg(cl_arg, long))
+                || arg
+                    .short
+                    .is_some_and(|short| self.is_short_arg(cl_arg, short))
+        })
+    }
+
+    /// Whether `arg` is `-s`, `-s<value>` or a cluster of short flags that includes `-s`
+    fn is_short_arg(&self, arg: &OsStr, short: char) -> bool {
+        let Some(cluster) = arg
+            .to_str()
+            .and_then(|arg| arg.strip_prefix('-'))
+            .filter(|cluster| !cluster.starts_with('-'))
+        else {
+            return false;
+        };
+        for c in cluster.chars() {
+            if c == short {
+                return true;
+            }
+            if self.shorts_with_value.contains(&c) {
+                // The rest of the cluster is the value
+                return false;
+            }
+        }
+        false
+    }
+}
+
+/// Converts the fleet defaults in `contents` into command line arguments, skipping any argument
+/// that is already present in `command_line`
+fn fleet_args(
+    path: &Path,
+    contents: &str,
+    arg_defs: &ArgDefs,
+    command_line: &[OsString],
+) -> Result<Vec<OsString>, String> {
+    let table: Table = contents
+        .parse()
+        .map_err(|err| format!("unable to parse {}: {err}", path.display()))?;
+
+    let mut args = Vec::new();
+    for (name, value) in table {
+        if name == FLEET_DEFAULTS_ARG || name == NO_FLEET_DEFAULTS_ARG {
+            return Err(format!(
+                "{}: `{name}` can only be given on the command line",
+                path.display()
+            ));
+        }
+        if arg_defs.is_given(&name, command_line) {
+            continue;
+        }
+
+        let long = format!("--{name}");
+        let values = match value {
+            Value::Boolean(true) => {
+                args.push(OsString::from(long));
+                continue;
+            }
+            Value::Boolean(false) => continue,
+            Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Integer(value) => value.to_string(),
+                Value::Float(value) => value.to_string(),
+                value => {
+                    return Err(format!(
+                        "{}: unsupported value for `{name}`: {value}",
+                        path.display()
+                    ))
+                }
+            };
+            args.push(OsString::from(&long));
+            args.push(OsString::from(value));
+        }
+    }
+    Ok(args)
+}
+
+/// Whether `arg` is `--name` or `--name=<value>`
+fn is_long_arg(arg: &OsStr, name: &str) -> bool {
+    arg.to_str()
+        .and_then(|arg| arg.strip_prefix("--"))
+        .and_then(|arg| arg.strip_prefix(name))
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+}
+
+/// Value of the last `--name <value>` or `--name=<value>` in `args`
+fn long_arg_value<'a>(args: &'a [OsString], name: &str) -> Option<&'a OsStr> {
+    let mut value = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !is_long_arg(arg, name) {
+            continue;
+        }
+        value = match arg.to_str().and_then(|arg| arg.split_once('=')) {
+            Some((_, inline_value)) => Some(OsStr::new(inline_value)),
+            None => args.next().map(OsString::as_os_str),
+        };
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, clap::Arg, std::io::Write, tempfile::NamedTempFile};
+
+    fn test_app() -> App<'static, 'static> {
+        App::new("agave-validator")
+            .arg(
+                Arg::with_name("known_validators")
+                    .long("known-validator")
+                    .alias("trusted-validator")
+                    .takes_value(true)
+                    .multiple(true),
+            )
+            .arg(Arg::with_name("only_known_rpc").long("only-known-rpc"))
+            .arg(Arg::with_name("no_voting").long("no-voting"))
+            .arg(
+                Arg::with_name("limit_ledger_size")
+                    .long("limit-ledger-size")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("logfile")
+                    .short("o")
+                    .long("log")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("dynamic_port_range")
+                    .long("dynamic-port-range")
+                    .takes_value(true),
+            )
+            .arg(Arg::with_name("quiet").short("q").long("quiet"))
+    }
+
+    fn os_args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_fleet_args() {
+        let contents = r#"
+            known-validator = ["validator1", "validator2"]
+            only-known-rpc = true
+            no-voting = false
+            limit-ledger-size = 50000000
+            log = "/var/log/agave.log"
+            dynamic-port-range = "8000-8020"
+        "#;
+        let path = Path::new("fleet.toml");
+        let app = test_app();
+        let arg_defs = ArgDefs::new(&app);
+
+        let args = fleet_args(path, contents, &arg_defs, &os_args(&["agave-validator"])).unwrap();
+        assert_eq!(
+            args,
+            os_args(&[
+                "--dynamic-port-range",
+                "8000-8020",
+                "--known-validator",
+                "validator1",
+                "--known-validator",
+                "validator2",
+                "--limit-ledger-size",
+                "50000000",
+                "--log",
+                "/var/log/agave.log",
+                "--only-known-rpc",
+            ])
+        );
+
+        // arguments on the command line override the fleet defaults
+        let args = fleet_args(
+            path,
+            contents,
+            &arg_defs,
+            &os_args(&[
+                "agave-validator",
+                "--known-validator",
+                "validator3",
+                "--log=-",
+                "--limit-ledger-size-extra",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            os_args(&[
+                "--dynamic-port-range",
+                "8000-8020",
+                "--limit-ledger-size",
+                "50000000",
+                "--only-known-rpc",
+            ])
+        );
+
+        // ...under their short names and aliases too
+        let args = fleet_args(
+            path,
+            contents,
+            &arg_defs,
+            &os_args(&[
+                "agave-validator",
+                "--trusted-validator",
+                "validator3",
+                "-qo-",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            os_args(&[
+                "--dynamic-port-range",
+                "8000-8020",
+                "--limit-ledger-size",
+                "50000000",
+                "--only-known-rpc",
+            ])
+        );
+
+        // a short name inside the value of another short option is not a match
+        let contents = "known-validator = \"validator1\"\nlog = \"agave.log\"";
+        let args = fleet_args(
+            path,
+            contents,
+            &arg_defs,
+            &os_args(&["agave-validator", "-oq"]),
+        )
+        .unwrap();
+        assert_eq!(args, os_args(&["--known-validator", "validator1"]));
+        let contents = "quiet = true";
+        let args = fleet_args(
+            path,
+            contents,
+            &arg_defs,
+            &os_args(&["agave-validator", "-qofile"]),
+        )
+        .unwrap();
+        assert!(args.is_empty());
+
+        assert!(fleet_args(path, "log = {}", &arg_defs, &[]).is_err());
+        assert!(fleet_args(path, "no-fleet-defaults = true", &arg_defs, &[]).is_err());
+        assert!(fleet_args(path, "not toml", &arg_defs, &[]).is_err());
+    }
+
+    #[test]
+    fn test_apply_fleet_defaults() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "only-known-rpc = true").unwrap();
+        let path = file.path().to_str().unwrap();
+        let app = test_app();
+
+        assert_eq!(
+            apply_fleet_defaults(
+                &app,
+                os_args(&["agave-validator", "--fleet-defaults", path, "--no-voting"])
+            )
+            .unwrap(),
+            os_args(&[
+                "agave-validator",
+                "--only-known-rpc",
+                "--fleet-defaults",
+                path,
+                "--no-voting"
+            ])
+        );
+
+        let args = os_args(&[
+            "agave-validator",
+            &format!("--fleet-defaults={path}"),
+            "--no-fleet-defaults",
+        ]);
+        assert_eq!(apply_fleet_defaults(&app, args.clone()).unwrap(), args);
+
+        assert!(apply_fleet_defaults(
+            &app,
+            os_args(&[
+                "agave-validator",
+                "--fleet-defaults",
+                "/nonexistent/fleet.toml"
+            ])
+        )
+        .is_err());
+    }
+}
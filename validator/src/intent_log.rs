@@ -0,0 +1,407 @@
+//! Write-ahead log of the admin operations that change the keys of a running validator
+//!
+//! Swapping the identity updates the network layer, gossip and the metrics host one after the
+//! other, and authorized voters only live in memory. A validator that dies in the middle of such
+//! an operation, or after it, comes back with whatever keys its command line names, and nothing
+//! tells the operator that a change they made was lost.
+//!
+//! Before one of these operations starts, an intent entry signed by the current identity is
+//! appended to `<ledger>/admin-intent.log` and synced to disk. Once the operation is done and its
+//! effect has been checked, a completion entry is appended, signed by the identity in effect
+//! afterwards. On the next start [`IntentLog::open`] resolves every intent without a completion
+//! entry against the keys the validator starts with, records the outcome, and moves the log
+//! aside so each start begins with an empty one.
+//!
+//! Tower files are not covered, as every tower save is already atomic.
+
+use {
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_keypair::Keypair,
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    solana_signer::Signer,
+    std::{
+        collections::BTreeMap,
+        fs::{self, File, OpenOptions},
+        io::{self, BufRead, BufReader, Write},
+        iter,
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{Arc, Mutex},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+pub const INTENT_LOG_FILE: &str = "admin-intent.log";
+/// The log of the previous run, kept for inspection
+pub const PREVIOUS_INTENT_LOG_FILE: &str = "admin-intent.log.1";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum IntentOperation {
+    SetIdentity {
+        from: Pubkey,
+        to: Pubkey,
+        require_tower: bool,
+    },
+    AddAuthorizedVoter {
+        voter: Pubkey,
+    },
+    RemoveAllAuthorizedVoters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntentPhase {
+    /// The operation is about to start
+    Intent,
+    /// The operation finished and its effect was verified
+    Completed,
+    /// The operation failed or its effect could not be verified
+    Failed,
+    /// The validator restarted before the operation completed, and it does not apply anymore
+    RolledBack,
+}
+
+/// The signed part of an entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntentRecord {
+    id: u64,
+    /// Milliseconds since the Unix epoch
+    timestamp: u64,
+    phase: IntentPhase,
+    operation: IntentOperation,
+    signer: Pubkey,
+}
+
+impl IntentRecord {
+    fn message(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("intent records serialize")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntentEntry {
+    #[serde(flatten)]
+    record: IntentRecord,
+    signature: String,
+}
+
+impl IntentEntry {
+    fn verify(&self) -> bool {
+        Signature::from_str(&self.signature).is_ok_and(|signature| {
+            signature.verify(self.record.signer.as_ref(), &self.record.message())
+        })
+    }
+}
+
+/// An intent the previous run of the validator did not complete
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedIntent {
+    pub id: u64,
+    pub operation: IntentOperation,
+    /// `Completed` if the operation is in effect after the restart, `RolledBack` otherwise
+    pub resolution: IntentPhase,
+}
+
+#[derive(Debug)]
+struct IntentLogState {
+    file: File,
+    signer: Arc<Keypair>,
+    next_id: u64,
+    pending: BTreeMap<u64, IntentOperation>,
+}
+
+impl IntentLogState {
+    fn append(
+        &mut self,
+        id: u64,
+        phase: IntentPhase,
+        operation: IntentOperation,
+    ) -> io::Result<()> {
+        let record = IntentRecord {
+            id,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+            phase,
+            operation,
+            signer: self.signer.pubkey(),
+        };
+        let signature = self.signer.sign_message(&record.message()).to_string();
+        let mut line = serde_json::to_vec(&IntentEntry { record, signature })?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()
+    }
+}
+
+#[derive(Debug)]
+pub struct IntentLog {
+    path: PathBuf,
+    state: Mutex<IntentLogState>,
+}
+
+impl IntentLog {
+    /// Opens the log in `ledger_path` for a validator starting with `identity` and no
+    /// authorized voters added over the admin RPC
+    ///
+    /// Returns the intents the previous run left incomplete, with the outcome recorded for them.
+    pub fn open(
+        ledger_path: &Path,
+        identity: Arc<Keypair>,
+    ) -> io::Result<(Self, Vec<ResolvedIntent>)> {
+        let path = ledger_path.join(INTENT_LOG_FILE);
+        let entries = match File::open(&path) {
+            Ok(file) => read_entries(BufReader::new(file))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        // Ids keep increasing across runs, even when the previous run left an empty log
+        let next_id = entries
+            .iter()
+            .map(|entry| entry.record.id.saturating_add(1))
+            .chain(iter::once(now_micros()))
+            .max()
+            .unwrap_or_default();
+
+        let mut pending = BTreeMap::new();
+        for entry in entries {
+            match entry.record.phase {
+                IntentPhase::Intent => {
+                    pending.insert(entry.record.id, entry.record.operation);
+                }
+                IntentPhase::Completed | IntentPhase::Failed | IntentPhase::RolledBack => {
+                    pending.remove(&entry.record.id);
+                }
+            }
+        }
+        let resolved: Vec<_> = pending
+            .into_iter()
+            .map(|(id, operation)| {
+                let resolution = match &operation {
+                    IntentOperation::SetIdentity { to, .. } if *to == identity.pubkey() => {
+                        IntentPhase::Completed
+                    }
+                    _ => IntentPhase::RolledBack,
+                };
+                ResolvedIntent {
+                    id,
+                    operation,
+                    resolution,
+                }
+            })
+            .collect();
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let mut state = IntentLogState {
+            file,
+            signer: identity,
+            next_id,
+            pending: BTreeMap::new(),
+        };
+        for intent in &resolved {
+            state.append(intent.id, intent.resolution, intent.operation.clone())?;
+        }
+        // Every intent is resolved now, the next run only needs to look at its own log
+        fs::rename(&path, ledger_path.join(PREVIOUS_INTENT_LOG_FILE))?;
+        state.file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok((
+            Self {
+                path,
+                state: Mutex::new(state),
+            },
+            resolved,
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records the intent to start `operation`, which must not start if this fails
+    pub fn begin(&self, operation: IntentOperation) -> io::Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        // The id is used up even if the append fails, as part of the entry may have been written
+        let id = state.next_id;
+        state.next_id = id.saturating_add(1);
+        state.append(id, IntentPhase::Intent, operation.clone())?;
+        state.pending.insert(id, operation);
+        Ok(id)
+    }
+
+    /// Records the outcome of the operation started with `begin`
+    pub fn finish(&self, id: u64, succeeded: bool) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let Some(operation) = state.pending.remove(&id) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no pending intent {id}"),
+            ));
+        };
+        let phase = if succeeded {
+            IntentPhase::Completed
+        } else {
+            IntentPhase::Failed
+        };
+        state.append(id, phase, operation)
+    }
+
+    /// Signs the following entries with `identity`, once the validator switched to it
+    pub fn set_signer(&self, identity: Arc<Keypair>) {
+        self.state.lock().unwrap().signer = identity;
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_micros() as u64)
+        .unwrap_or_default()
+}
+
+/// Reads the entries of a log, skipping a torn last line and entries with a bad signature
+fn read_entries(reader: impl BufRead) -> io::Result<Vec<IntentEntry>> {
+    let mut entries = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        match serde_json::from_str::<IntentEntry>(&line) {
+            Ok(entry) if entry.verify() => entries.push(entry),
+            Ok(entry) => warn!(
+                "Ignoring intent log entry {} with an invalid signature by {}",
+                entry.record.id, entry.record.signer
+            ),
+            Err(err) => warn!(
+                "Ignoring unreadable intent log line {}: {err}",
+                line_number.saturating_add(1)
+            ),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    fn read_log(path: &Path) -> Vec<IntentEntry> {
+        read_entries(BufReader::new(File::open(path).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn test_intent_log_recovery() {
+        let ledger_path = TempDir::new().unwrap();
+        let old_identity = Arc::new(Keypair::new());
+        let new_identity = Arc::new(Keypair::new());
+        let voter = Keypair::new();
+
+        let (intent_log, resolved) =
+            IntentLog::open(ledger_path.path(), old_identity.clone()).unwrap();
+        assert!(resolved.is_empty());
+
+        let add_voter = IntentOperation::AddAuthorizedVoter {
+            voter: voter.pubkey(),
+        };
+        let id = intent_log.begin(add_voter.clone()).unwrap();
+        intent_log.finish(id, true).unwrap();
+        let set_identity = IntentOperation::SetIdentity {
+            from: old_identity.pubkey(),
+            to: new_identity.pubkey(),
+            require_tower: false,
+        };
+        let set_identity_id = intent_log.begin(set_identity.clone()).unwrap();
+        // The validator dies before the swap completes
+        drop(intent_log);
+
+        let entries = read_log(&ledger_path.path().join(INTENT_LOG_FILE));
+        assert_eq!(entries.len(), 3);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.record.signer == old_identity.pubkey()));
+
+        // Restarted with the new identity, the swap is in effect
+        let (intent_log, resolved) =
+            IntentLog::open(ledger_path.path(), new_identity.clone()).unwrap();
+        assert_eq!(
+            resolved,
+            vec![ResolvedIntent {
+                id: set_identity_id,
+                operation: set_identity.clone(),
+                resolution: IntentPhase::Completed,
+            }]
+        );
+        let previous_entries = read_log(&ledger_path.path().join(PREVIOUS_INTENT_LOG_FILE));
+        assert_eq!(previous_entries.len(), 4);
+        assert_eq!(previous_entries[3].record.signer, new_identity.pubkey());
+        assert!(read_log(intent_log.path()).is_empty());
+
+        // Ids continue from the previous log
+        let id = intent_log.begin(add_voter.clone()).unwrap();
+        assert!(id > set_identity_id);
+        drop(intent_log);
+
+        // Authorized voters do not survive a restart
+        let (_intent_log, resolved) = IntentLog::open(ledger_path.path(), new_identity).unwrap();
+        assert_eq!(
+            resolved,
+            vec![ResolvedIntent {
+                id,
+                operation: add_voter,
+                resolution: IntentPhase::RolledBack,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_intent_log_ignores_bad_entries() {
+        let ledger_path = TempDir::new().unwrap();
+        let identity = Arc::new(Keypair::new());
+        let (intent_log, _) = IntentLog::open(ledger_path.path(), identity.clone()).unwrap();
+        let id = intent_log
+            .begin(IntentOperation::RemoveAllAuthorizedVoters)
+            .unwrap();
+        let path = intent_log.path().to_path_buf();
+        drop(intent_log);
+
+        // A forged entry and a torn write
+        let mut entry = read_log(&path).pop().unwrap();
+        entry.record.id = 7;
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap()).unwrap();
+        write!(file, "{{\"id\":8,").unwrap();
+        drop(file);
+
+        let (_intent_log, resolved) = IntentLog::open(ledger_path.path(), identity).unwrap();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].id, id);
+        assert_eq!(resolved[0].resolution, IntentPhase::RolledBack);
+    }
+
+    #[test]
+    fn test_intent_log_ids_do_not_repeat() {
+        let ledger_path = TempDir::new().unwrap();
+        let identity = Arc::new(Keypair::new());
+        let (intent_log, _) = IntentLog::open(ledger_path.path(), identity.clone()).unwrap();
+        let id = intent_log
+            .begin(IntentOperation::RemoveAllAuthorizedVoters)
+            .unwrap();
+        intent_log.finish(id, true).unwrap();
+        drop(intent_log);
+
+        // A run without any intent leaves an empty log behind
+        let (intent_log, _) = IntentLog::open(ledger_path.path(), identity.clone()).unwrap();
+        drop(intent_log);
+        assert!(read_log(&ledger_path.path().join(PREVIOUS_INTENT_LOG_FILE)).is_empty());
+
+        let (intent_log, _) = IntentLog::open(ledger_path.path(), identity).unwrap();
+        let next_id = intent_log
+            .begin(IntentOperation::RemoveAllAuthorizedVoters)
+            .unwrap();
+        assert!(next_id > id);
+    }
+}
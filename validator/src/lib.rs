@@ -14,11 +14,19 @@ use {
     },
 };
 
+pub mod admin_rpc_http_service;
 pub mod admin_rpc_service;
 pub mod bootstrap;
 pub mod cli;
 pub mod commands;
+pub mod cpu_profiler;
+pub mod crash_report;
 pub mod dashboard;
+pub mod diagnostics_bundle;
+pub mod fleet_defaults;
+pub mod intent_log;
+pub mod restart_window;
+pub mod throttle_profile;
 
 pub fn format_name_value(name: &str, value: &str) -> String {
     format!("{} {}", style(name).bold(), value)
@@ -1,24 +1,41 @@
 #![allow(clippy::arithmetic_side_effects)]
-#[cfg(not(any(target_env = "msvc", target_os = "freebsd")))]
+#[cfg(all(
+    not(feature = "mimalloc"),
+    not(any(target_env = "msvc", target_os = "freebsd"))
+))]
 use jemallocator::Jemalloc;
+#[cfg(feature = "mimalloc")]
+use mimalloc::MiMalloc;
 use {
     agave_validator::{
         cli::{app, warn_for_deprecated_arguments, DefaultArgs},
-        commands,
+        commands, fleet_defaults,
     },
     log::error,
-    std::{path::PathBuf, process::exit},
+    std::{env, path::PathBuf, process::exit},
 };
 
-#[cfg(not(any(target_env = "msvc", target_os = "freebsd")))]
+#[cfg(all(
+    not(feature = "mimalloc"),
+    not(any(target_env = "msvc", target_os = "freebsd"))
+))]
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+#[cfg(feature = "mimalloc")]
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
 pub fn main() {
     let default_args = DefaultArgs::new();
     let solana_version = solana_version::version!();
     let cli_app = app(solana_version, &default_args);
-    let matches = cli_app.get_matches();
+    let args = fleet_defaults::apply_fleet_defaults(&cli_app, env::args_os().collect())
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to load fleet defaults: {err}");
+            exit(1);
+        });
+    let matches = cli_app.get_matches_from(args);
     warn_for_deprecated_arguments(&matches);
 
     let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
@@ -44,22 +61,55 @@ pub fn main() {
         ("plugin", Some(plugin_subcommand_matches)) => {
             commands::plugin::execute(plugin_subcommand_matches, &ledger_path)
         }
+        ("apply-config", Some(subcommand_matches)) => {
+            commands::apply_config::execute(subcommand_matches, &ledger_path)
+        }
         ("contact-info", Some(subcommand_matches)) => {
             commands::contact_info::execute(subcommand_matches, &ledger_path)
         }
+        ("diagnostics-bundle", Some(subcommand_matches)) => {
+            commands::diagnostics_bundle::execute(subcommand_matches, &ledger_path)
+        }
+        ("doctor", Some(subcommand_matches)) => {
+            commands::doctor::execute(subcommand_matches, &ledger_path)
+        }
+        ("profile-cpu", Some(subcommand_matches)) => {
+            commands::profile_cpu::execute(subcommand_matches, &ledger_path)
+        }
         ("exit", Some(subcommand_matches)) => {
             commands::exit::execute(subcommand_matches, &ledger_path)
         }
+        ("generate-service", Some(subcommand_matches)) => {
+            commands::generate_service::execute(subcommand_matches)
+        }
         ("monitor", _) => commands::monitor::execute(&matches, &ledger_path),
         ("staked-nodes-overrides", Some(subcommand_matches)) => {
             commands::staked_nodes_overrides::execute(subcommand_matches, &ledger_path)
         }
+        ("transaction-history", Some(subcommand_matches)) => {
+            commands::transaction_history::execute(subcommand_matches, &ledger_path)
+        }
+        ("rpc-follower", Some(subcommand_matches)) => {
+            commands::rpc_follower::execute(&matches, subcommand_matches)
+        }
+        ("self-test", Some(subcommand_matches)) => {
+            commands::self_test::execute(&matches, subcommand_matches)
+        }
         ("set-identity", Some(subcommand_matches)) => {
             commands::set_identity::execute(subcommand_matches, &ledger_path)
         }
         ("set-log-filter", Some(subcommand_matches)) => {
             commands::set_log_filter::execute(subcommand_matches, &ledger_path)
         }
+        ("rpc-threads", Some(subcommand_matches)) => {
+            commands::rpc_threads::execute(subcommand_matches, &ledger_path)
+        }
+        ("rpc-usage", Some(subcommand_matches)) => {
+            commands::rpc_usage::execute(subcommand_matches, &ledger_path)
+        }
+        ("restart", Some(subcommand_matches)) => {
+            commands::restart::execute(subcommand_matches, &ledger_path)
+        }
         ("wait-for-restart-window", Some(subcommand_matches)) => {
             commands::wait_for_restart_window::execute(subcommand_matches, &ledger_path)
         }
@@ -75,6 +125,15 @@ pub fn main() {
         ("manage-block-production", Some(subcommand_matches)) => {
             commands::manage_block_production::execute(subcommand_matches, &ledger_path)
         }
+        ("write-lock-contention", Some(subcommand_matches)) => {
+            commands::write_lock_contention::execute(subcommand_matches, &ledger_path)
+        }
+        ("poh-timing", Some(subcommand_matches)) => {
+            commands::poh_timing::execute(subcommand_matches, &ledger_path)
+        }
+        ("wen-restart-status", Some(subcommand_matches)) => {
+            commands::wen_restart_status::execute(subcommand_matches, &ledger_path)
+        }
         _ => unreachable!(),
     }
     .unwrap_or_else(|err| {
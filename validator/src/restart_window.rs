@@ -0,0 +1,289 @@
+//! Restarts timed by the validator itself
+//!
+//! `wait-for-restart-window` watches the validator over RPC and reports a good time to restart
+//! once it sees a long enough gap in the validator's leader schedule. By the time the exit request
+//! that follows arrives, the validator may have moved closer to its next leader slot. A
+//! [`RestartWindow`] instead checks the working bank every slot, from inside the validator, and
+//! exits as soon as the validator has no leader slot in the next `min_idle_slots` slots and the
+//! delinquent stake of the cluster is low enough. Starting the validator again is left to the
+//! process supervisor.
+
+use {
+    serde::{Deserialize, Serialize},
+    solana_clock::Slot,
+    solana_ledger::leader_schedule_cache::LeaderScheduleCache,
+    solana_pubkey::Pubkey,
+    solana_rpc_client_api::request::DELINQUENT_VALIDATOR_SLOT_DISTANCE,
+    solana_runtime::bank::Bank,
+    std::{sync::RwLock, time::SystemTime},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartWindowConfig {
+    /// Number of slots after the exit in which the validator must not be leader
+    pub min_idle_slots: u64,
+    /// Percentage of the stake that may be delinquent at most when the validator exits
+    pub max_delinquent_stake_percent: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RestartStatus {
+    /// The validator is leader within the next `min_idle_slots` slots
+    WaitingForIdleWindow,
+    DelinquencyTooHigh,
+    /// The validator is exiting
+    Exiting,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartProgress {
+    pub config: RestartWindowConfig,
+    pub requested_at: SystemTime,
+    pub status: RestartStatus,
+    /// Slot of the working bank at the latest check
+    pub slot: Option<Slot>,
+    /// The first leader slot of the validator from `slot` to `min_idle_slots` slots after it
+    pub next_leader_slot: Option<Slot>,
+    pub delinquent_stake_percent: f64,
+}
+
+#[derive(Default)]
+pub struct RestartWindow {
+    /// The progress of the latest request, and its id
+    progress: RwLock<Option<(u64, RestartProgress)>>,
+}
+
+impl RestartWindow {
+    /// Requests a restart with `config`
+    ///
+    /// Returns the id of the new request, which has to be checked until it is no longer pending,
+    /// or `None` if a pending request was updated with `config` instead.
+    pub fn request(&self, config: RestartWindowConfig) -> Option<u64> {
+        let mut progress = self.progress.write().unwrap();
+        if let Some((_, pending)) = progress
+            .as_mut()
+            .filter(|(_, progress)| is_pending(progress.status))
+        {
+            pending.config = config;
+            return None;
+        }
+        let id = progress.as_ref().map_or(0, |(id, _)| id.wrapping_add(1));
+        *progress = Some((
+            id,
+            RestartProgress {
+                config,
+                requested_at: SystemTime::now(),
+                status: RestartStatus::WaitingForIdleWindow,
+                slot: None,
+                next_leader_slot: None,
+                delinquent_stake_percent: 0.0,
+            },
+        ));
+        Some(id)
+    }
+
+    /// Returns whether a pending request was cancelled
+    pub fn cancel(&self) -> bool {
+        let mut progress = self.progress.write().unwrap();
+        match progress.as_mut() {
+            Some((_, progress)) if is_pending(progress.status) => {
+                progress.status = RestartStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn progress(&self) -> Option<RestartProgress> {
+        self.progress
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|(_, progress)| progress.clone())
+    }
+
+    /// Checks whether request `id` can be carried out at the slot of `bank`
+    ///
+    /// Returns `None` if the request has been replaced by another one.
+    pub fn check(
+        &self,
+        id: u64,
+        bank: &Bank,
+        identity: &Pubkey,
+        leader_schedule_cache: &LeaderScheduleCache,
+    ) -> Option<RestartStatus> {
+        let config = match self.progress.read().unwrap().as_ref() {
+            Some((pending_id, progress)) if *pending_id == id => {
+                if !is_pending(progress.status) {
+                    return Some(progress.status);
+                }
+                progress.config
+            }
+            _ => return None,
+        };
+
+        let slot = bank.slot();
+        // `slot` itself may still be in progress, so it counts too. A slot with an unknown leader
+        // counts as a leader slot of the validator.
+        let next_leader_slot = (slot..=slot.saturating_add(config.min_idle_slots)).find(|slot| {
+            leader_schedule_cache
+                .slot_leader_at(*slot, Some(bank))
+                .is_none_or(|leader| leader == *identity)
+        });
+        let delinquent_stake_percent = delinquent_stake_percent(bank);
+        let status = restart_status(&config, next_leader_slot, delinquent_stake_percent);
+
+        let mut progress = self.progress.write().unwrap();
+        match progress.as_mut() {
+            Some((pending_id, progress)) if *pending_id == id => {
+                // The request may have been cancelled meanwhile
+                if is_pending(progress.status) {
+                    progress.status = status;
+                    progress.slot = Some(slot);
+                    progress.next_leader_slot = next_leader_slot;
+                    progress.delinquent_stake_percent = delinquent_stake_percent;
+                }
+                Some(progress.status)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_pending(status: RestartStatus) -> bool {
+    matches!(
+        status,
+        RestartStatus::WaitingForIdleWindow | RestartStatus::DelinquencyTooHigh
+    )
+}
+
+fn restart_status(
+    config: &RestartWindowConfig,
+    next_leader_slot: Option<Slot>,
+    delinquent_stake_percent: f64,
+) -> RestartStatus {
+    if next_leader_slot.is_some() {
+        RestartStatus::WaitingForIdleWindow
+    } else if delinquent_stake_percent >= f64::from(config.max_delinquent_stake_percent) {
+        RestartStatus::DelinquencyTooHigh
+    } else {
+        RestartStatus::Exiting
+    }
+}
+
+/// Percentage of the stake whose latest vote landed more than the delinquency distance ago
+fn delinquent_stake_percent(bank: &Bank) -> f64 {
+    let min_vote_slot = bank
+        .slot()
+        .saturating_sub(DELINQUENT_VALIDATOR_SLOT_DISTANCE);
+    let (total_stake, delinquent_stake) = bank.vote_accounts().values().fold(
+        (0u64, 0u64),
+        |(total_stake, delinquent_stake), (stake, vote_account)| {
+            let last_vote = vote_account.vote_state_view().last_voted_slot();
+            let delinquent = last_vote.is_none_or(|last_vote| last_vote <= min_vote_slot);
+            (
+                total_stake.saturating_add(*stake),
+                delinquent_stake.saturating_add(if delinquent { *stake } else { 0 }),
+            )
+        },
+    );
+    if total_stake == 0 {
+        0.0
+    } else {
+        delinquent_stake as f64 * 100.0 / total_stake as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_runtime::genesis_utils::{create_genesis_config_with_leader, GenesisConfigInfo},
+    };
+
+    #[test]
+    fn test_restart_status() {
+        let config = RestartWindowConfig {
+            min_idle_slots: 100,
+            max_delinquent_stake_percent: 5,
+        };
+        assert_eq!(
+            restart_status(&config, Some(10), 0.0),
+            RestartStatus::WaitingForIdleWindow
+        );
+        assert_eq!(
+            restart_status(&config, None, 5.0),
+            RestartStatus::DelinquencyTooHigh
+        );
+        assert_eq!(restart_status(&config, None, 4.9), RestartStatus::Exiting);
+    }
+
+    #[test]
+    fn test_restart_window() {
+        let leader = Pubkey::new_unique();
+        let GenesisConfigInfo { genesis_config, .. } =
+            create_genesis_config_with_leader(10_000, &leader, 1_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&bank);
+        let config = RestartWindowConfig {
+            min_idle_slots: 10,
+            max_delinquent_stake_percent: 5,
+        };
+
+        let restart_window = RestartWindow::default();
+        assert_eq!(restart_window.progress(), None);
+        assert!(!restart_window.cancel());
+        let id = restart_window.request(config).unwrap();
+
+        // the only staked node is leader of every slot
+        assert_eq!(
+            restart_window.check(id, &bank, &leader, &leader_schedule_cache),
+            Some(RestartStatus::WaitingForIdleWindow)
+        );
+        let progress = restart_window.progress().unwrap();
+        assert_eq!(progress.slot, Some(0));
+        assert_eq!(progress.next_leader_slot, Some(0));
+
+        // another node is never leader, but the leader has not voted yet
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            restart_window.check(id, &bank, &other, &leader_schedule_cache),
+            Some(RestartStatus::DelinquencyTooHigh)
+        );
+        assert_eq!(
+            restart_window.progress().unwrap().delinquent_stake_percent,
+            100.0
+        );
+
+        // a second request updates the pending one
+        let config = RestartWindowConfig {
+            min_idle_slots: 0,
+            ..config
+        };
+        assert_eq!(restart_window.request(config), None);
+        assert_eq!(restart_window.progress().unwrap().config, config);
+        // the current slot counts even without idle slots, it may still be in progress
+        assert_eq!(
+            restart_window.check(id, &bank, &leader, &leader_schedule_cache),
+            Some(RestartStatus::WaitingForIdleWindow)
+        );
+        assert_eq!(restart_window.progress().unwrap().next_leader_slot, Some(0));
+
+        assert!(restart_window.cancel());
+        assert_eq!(
+            restart_window.check(id, &bank, &other, &leader_schedule_cache),
+            Some(RestartStatus::Cancelled)
+        );
+        // a new request replaces the cancelled one
+        let new_id = restart_window.request(config).unwrap();
+        assert_ne!(new_id, id);
+        assert_eq!(
+            restart_window.check(id, &bank, &other, &leader_schedule_cache),
+            None
+        );
+    }
+}
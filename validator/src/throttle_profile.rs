@@ -0,0 +1,247 @@
+//! Simulation of smaller hardware for capacity planning
+//!
+//! A throttle profile caps the CPU, I/O and memory the major subsystems may use, so an operator
+//! can find out on their current machine whether a cheaper hardware tier would keep up with the
+//! cluster. The profile is a TOML file, every limit is optional:
+//!
+//! ```toml
+//! [cpu]
+//! # Run on this many cores. Thread pools larger than that are shrunk to it.
+//! cores = 16
+//!
+//! [io]
+//! # Megabytes per second the blockstore may write in flushes and compactions
+//! blockstore-write-mb-per-second = 200
+//!
+//! [memory]
+//! accounts-db-read-cache-mb = 1024
+//! accounts-db-write-cache-mb = 2048
+//! # Keep the accounts index on disk rather than in memory
+//! accounts-index-on-disk = true
+//! ```
+//!
+//! The limits only ever lower what the other arguments configure. They are meant for testing,
+//! not for production use.
+
+use {
+    crate::cli::thread_args::NumThreadConfig,
+    serde::Deserialize,
+    solana_accounts_db::{accounts_db::AccountsDbConfig, accounts_index::IndexLimitMb},
+    solana_ledger::blockstore_options::BlockstoreOptions,
+    std::{
+        fs,
+        num::{NonZeroU64, NonZeroUsize},
+        path::Path,
+    },
+};
+
+const MB: u64 = 1_024 * 1_024;
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThrottleProfile {
+    pub cpu: CpuThrottle,
+    pub io: IoThrottle,
+    pub memory: MemoryThrottle,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CpuThrottle {
+    /// Number of cores the validator may run on
+    pub cores: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct IoThrottle {
+    /// Write bandwidth of blockstore flushes and compactions
+    pub blockstore_write_mb_per_second: Option<NonZeroU64>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct MemoryThrottle {
+    pub accounts_db_read_cache_mb: Option<u64>,
+    pub accounts_db_write_cache_mb: Option<u64>,
+    pub accounts_index_on_disk: bool,
+}
+
+impl ThrottleProfile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+        toml::from_str(&contents)
+            .map_err(|err| format!("unable to parse {}: {err}", path.display()))
+    }
+
+    /// Shrinks every thread pool to the number of cores of the profile
+    pub fn cap_threads(&self, config: &mut NumThreadConfig) {
+        let Some(cores) = self.cpu.cores else {
+            return;
+        };
+        let NumThreadConfig {
+            accounts_db_background_threads,
+            accounts_db_foreground_threads,
+            accounts_index_flush_threads,
+            block_production_num_workers,
+            ip_echo_server_threads,
+            rayon_global_threads,
+            replay_forks_threads,
+            replay_transactions_threads,
+            tpu_transaction_forward_receive_threads,
+            tpu_transaction_receive_threads,
+            tpu_vote_transaction_receive_threads,
+            tvu_receive_threads,
+            tvu_retransmit_threads,
+            tvu_sigverify_threads,
+            unpack_threads,
+        } = config;
+        for threads in [
+            accounts_db_background_threads,
+            accounts_db_foreground_threads,
+            accounts_index_flush_threads,
+            block_production_num_workers,
+            ip_echo_server_threads,
+            rayon_global_threads,
+            replay_forks_threads,
+            replay_transactions_threads,
+            tpu_transaction_forward_receive_threads,
+            tpu_transaction_receive_threads,
+            tpu_vote_transaction_receive_threads,
+            tvu_receive_threads,
+            tvu_retransmit_threads,
+            tvu_sigverify_threads,
+            unpack_threads,
+        ] {
+            *threads = (*threads).min(cores);
+        }
+    }
+
+    pub fn cap_blockstore(&self, options: &mut BlockstoreOptions) {
+        if let Some(cores) = self.cpu.cores {
+            options.num_rocksdb_compaction_threads =
+                options.num_rocksdb_compaction_threads.min(cores);
+            options.num_rocksdb_flush_threads = options.num_rocksdb_flush_threads.min(cores);
+        }
+        if let Some(mb_per_second) = self.io.blockstore_write_mb_per_second {
+            let limit = mb_per_second.saturating_mul(NonZeroU64::new(MB).unwrap());
+            options.write_rate_limit_bytes_per_second = Some(
+                options
+                    .write_rate_limit_bytes_per_second
+                    .map_or(limit, |rate| rate.min(limit)),
+            );
+        }
+    }
+
+    pub fn cap_accounts_db(&self, config: &mut AccountsDbConfig) {
+        if let Some(mb) = self.memory.accounts_db_read_cache_mb {
+            let hi = usize::try_from(mb.saturating_mul(MB)).unwrap_or(usize::MAX);
+            // Evict down to the same fraction of the limit as the default watermarks do
+            let lo = hi - hi / 32;
+            config.read_cache_limit_bytes = Some(
+                config
+                    .read_cache_limit_bytes
+                    .map_or((lo, hi), |(read_lo, read_hi)| {
+                        (read_lo.min(lo), read_hi.min(hi))
+                    }),
+            );
+        }
+        if let Some(mb) = self.memory.accounts_db_write_cache_mb {
+            let limit = mb.saturating_mul(MB);
+            config.write_cache_limit_bytes = Some(
+                config
+                    .write_cache_limit_bytes
+                    .map_or(limit, |write_limit| write_limit.min(limit)),
+            );
+        }
+        if self.memory.accounts_index_on_disk {
+            if let Some(index) = config.index.as_mut() {
+                index.index_limit_mb = IndexLimitMb::Minimal;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_accounts_db::accounts_index::AccountsIndexConfig};
+
+    #[test]
+    fn test_throttle_profile() {
+        let profile: ThrottleProfile = toml::from_str(
+            r#"
+            [cpu]
+            cores = 4
+
+            [io]
+            blockstore-write-mb-per-second = 100
+
+            [memory]
+            accounts-db-read-cache-mb = 64
+            accounts-index-on-disk = true
+            "#,
+        )
+        .unwrap();
+
+        let mut blockstore_options = BlockstoreOptions {
+            num_rocksdb_compaction_threads: NonZeroUsize::new(16).unwrap(),
+            num_rocksdb_flush_threads: NonZeroUsize::new(2).unwrap(),
+            ..BlockstoreOptions::default()
+        };
+        profile.cap_blockstore(&mut blockstore_options);
+        assert_eq!(blockstore_options.num_rocksdb_compaction_threads.get(), 4);
+        assert_eq!(blockstore_options.num_rocksdb_flush_threads.get(), 2);
+        assert_eq!(
+            blockstore_options.write_rate_limit_bytes_per_second,
+            NonZeroU64::new(100 * MB)
+        );
+
+        let mut accounts_db_config = AccountsDbConfig {
+            index: Some(AccountsIndexConfig::default()),
+            read_cache_limit_bytes: Some((1_000, 2_000)),
+            write_cache_limit_bytes: Some(MB),
+            ..AccountsDbConfig::default()
+        };
+        profile.cap_accounts_db(&mut accounts_db_config);
+        // smaller limits from the command line are kept
+        assert_eq!(
+            accounts_db_config.read_cache_limit_bytes,
+            Some((1_000, 2_000))
+        );
+        assert_eq!(accounts_db_config.write_cache_limit_bytes, Some(MB));
+        assert!(matches!(
+            accounts_db_config.index.unwrap().index_limit_mb,
+            IndexLimitMb::Minimal
+        ));
+
+        let mut accounts_db_config = AccountsDbConfig::default();
+        profile.cap_accounts_db(&mut accounts_db_config);
+        assert_eq!(
+            accounts_db_config.read_cache_limit_bytes,
+            Some((62 * MB as usize, 64 * MB as usize))
+        );
+        assert_eq!(accounts_db_config.write_cache_limit_bytes, None);
+
+        // huge limits saturate instead of overflowing
+        let profile: ThrottleProfile = toml::from_str(&format!(
+            "[memory]\naccounts-db-read-cache-mb = {0}\naccounts-db-write-cache-mb = {0}",
+            i64::MAX
+        ))
+        .unwrap();
+        let mut accounts_db_config = AccountsDbConfig::default();
+        profile.cap_accounts_db(&mut accounts_db_config);
+        assert_eq!(
+            accounts_db_config.read_cache_limit_bytes,
+            Some((usize::MAX - usize::MAX / 32, usize::MAX))
+        );
+        assert_eq!(accounts_db_config.write_cache_limit_bytes, Some(u64::MAX));
+
+        assert_eq!(
+            toml::from_str::<ThrottleProfile>("").unwrap(),
+            ThrottleProfile::default()
+        );
+        assert!(toml::from_str::<ThrottleProfile>("[cpu]\nthreads = 4").is_err());
+        assert!(toml::from_str::<ThrottleProfile>("[cpu]\ncores = 0").is_err());
+    }
+}
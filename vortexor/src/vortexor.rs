@@ -128,7 +128,10 @@ impl Vortexor {
                 wait_for_chunk_timeout: DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
                 ..Default::default()
             },
-            qos_config: SwQosConfig { max_streams_per_ms },
+            qos_config: SwQosConfig {
+                max_streams_per_ms,
+                ..SwQosConfig::default()
+            },
         };
 
         let mut quic_fwd_server_params = quic_server_params.clone();
@@ -108,6 +108,15 @@ impl VoteTransaction {
         }
     }
 
+    pub fn root(&self) -> Option<Slot> {
+        match self {
+            VoteTransaction::Vote(_) => None,
+            VoteTransaction::VoteStateUpdate(vote_state_update)
+            | VoteTransaction::CompactVoteStateUpdate(vote_state_update) => vote_state_update.root,
+            VoteTransaction::TowerSync(tower_sync) => tower_sync.root,
+        }
+    }
+
     pub fn last_voted_slot_hash(&self) -> Option<(Slot, Hash)> {
         Some((self.last_voted_slot()?, self.hash()))
     }
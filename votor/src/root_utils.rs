@@ -14,7 +14,7 @@ use {
         rpc_subscriptions::RpcSubscriptions,
     },
     solana_runtime::{
-        bank_forks::BankForks, installed_scheduler_pool::BankWithScheduler,
+        bank::Bank, bank_forks::BankForks, installed_scheduler_pool::BankWithScheduler,
         snapshot_controller::SnapshotController,
     },
     solana_time_utils::timestamp,
@@ -132,11 +132,11 @@ where
     CB: FnOnce(&BankForks),
 {
     // get the root bank before squash
-    let root_bank = bank_forks
-        .read()
-        .unwrap()
-        .get(new_root)
-        .expect("Root bank doesn't exist");
+    let (root_bank, old_root) = {
+        let bank_forks = bank_forks.read().unwrap();
+        let root_bank = bank_forks.get(new_root).expect("Root bank doesn't exist");
+        (root_bank, bank_forks.root())
+    };
     let mut rooted_banks = root_bank.parents();
     let oldest_parent = rooted_banks.last().map(|last| last.parent_slot());
     rooted_banks.push(root_bank.clone());
@@ -157,6 +157,7 @@ where
     // get dropped.
     leader_schedule_cache.set_root(rooted_banks.last().unwrap());
     blockstore.set_roots(rooted_slots.iter())?;
+    record_fork_stats(blockstore, &rooted_banks, old_root);
 
     set_bank_forks_root(
         new_root,
@@ -196,6 +197,26 @@ where
     Ok(())
 }
 
+/// Records the blocks rooted since `old_root` in the cluster fork statistics
+fn record_fork_stats(blockstore: &Blockstore, rooted_banks: &[Arc<Bank>], old_root: Slot) {
+    let mut new_rooted_banks: Vec<_> = rooted_banks
+        .iter()
+        .filter(|bank| bank.slot() > old_root)
+        .collect();
+    new_rooted_banks.sort_unstable_by_key(|bank| bank.slot());
+    for bank in new_rooted_banks {
+        let num_children = blockstore
+            .meta(bank.slot())
+            .ok()
+            .flatten()
+            .map_or(0, |meta| meta.next_slots.len());
+        blockstore
+            .fork_stats
+            .record_rooted_block(bank.slot(), bank.parent_slot(), num_children);
+    }
+    blockstore.fork_stats.maybe_report();
+}
+
 /// Sets the bank forks root:
 /// - Prune the program cache
 /// - Prune bank forks and drop the removed banks
@@ -16,7 +16,9 @@ use {
     solana_notifier::{NotificationType, Notifier},
     solana_pubkey::Pubkey,
     solana_rpc_client::rpc_client::RpcClient,
-    solana_rpc_client_api::{client_error, response::RpcVoteAccountStatus},
+    solana_rpc_client_api::{
+        client_error, request::MAX_MULTIPLE_ACCOUNTS, response::RpcVoteAccountStatus,
+    },
     std::{
         collections::HashMap,
         error,
@@ -32,6 +34,7 @@ struct Config {
     json_rpc_urls: Vec<String>,
     rpc_timeout: Duration,
     minimum_validator_identity_balance: u64,
+    minimum_vote_account_balance: u64,
     monitor_active_stake: bool,
     active_stake_alert_threshold: u8,
     unhealthy_threshold: usize,
@@ -147,6 +150,19 @@ fn get_config() -> Config {
                 .validator(is_parsable::<f64>)
                 .help("Alert when the validator identity balance is less than this amount of SOL"),
         )
+        .arg(
+            Arg::with_name("minimum_vote_account_balance")
+                .long("minimum-vote-account-balance")
+                .value_name("SOL")
+                .takes_value(true)
+                .default_value("0")
+                .validator(is_parsable::<f64>)
+                .help(
+                    "Alert when the balance of a vote account of a validator is less than this \
+                     amount of SOL. Vote accounts that are no longer rent exempt are always \
+                     reported",
+                ),
+        )
         .arg(
             // Deprecated parameter, now always enabled
             Arg::with_name("no_duplicate_notifications")
@@ -212,6 +228,10 @@ fn get_config() -> Config {
         .value_of("minimum_validator_identity_balance")
         .and_then(sol_str_to_lamports)
         .unwrap();
+    let minimum_vote_account_balance = matches
+        .value_of("minimum_vote_account_balance")
+        .and_then(sol_str_to_lamports)
+        .unwrap();
     let json_rpc_urls = values_t!(matches, "json_rpc_urls", String).unwrap_or_else(|_| {
         vec![value_t!(matches, "json_rpc_url", String).unwrap_or_else(|_| config.json_rpc_url)]
     });
@@ -238,6 +258,7 @@ fn get_config() -> Config {
         json_rpc_urls,
         rpc_timeout,
         minimum_validator_identity_balance,
+        minimum_vote_account_balance,
         monitor_active_stake,
         active_stake_alert_threshold,
         unhealthy_threshold,
@@ -254,10 +275,22 @@ fn get_config() -> Config {
     config
 }
 
-fn get_cluster_info(
-    config: &Config,
-    rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+struct VoteAccountBalance {
+    node_pubkey: Pubkey,
+    vote_pubkey: Pubkey,
+    balance: u64,
+    rent_exempt_minimum: u64,
+}
+
+struct ClusterInfo {
+    transaction_count: u64,
+    recent_blockhash: Hash,
+    vote_accounts: RpcVoteAccountStatus,
+    validator_balances: HashMap<Pubkey, u64>,
+    vote_account_balances: Vec<VoteAccountBalance>,
+}
+
+fn get_cluster_info(config: &Config, rpc_client: &RpcClient) -> client_error::Result<ClusterInfo> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_latest_blockhash()?;
     let vote_accounts = rpc_client.get_vote_accounts()?;
@@ -270,12 +303,58 @@ fn get_cluster_info(
         );
     }
 
-    Ok((
+    let monitored_vote_accounts: Vec<_> = vote_accounts
+        .current
+        .iter()
+        .chain(&vote_accounts.delinquent)
+        .filter_map(|vai| {
+            let node_pubkey = vai.node_pubkey.parse::<Pubkey>().ok()?;
+            let vote_pubkey = vai.vote_pubkey.parse::<Pubkey>().ok()?;
+            config
+                .validator_identity_pubkeys
+                .contains(&node_pubkey)
+                .then_some((node_pubkey, vote_pubkey))
+        })
+        .collect();
+    let vote_pubkeys: Vec<_> = monitored_vote_accounts
+        .iter()
+        .map(|(_, vote_pubkey)| *vote_pubkey)
+        .collect();
+    let mut vote_accounts_data = Vec::with_capacity(vote_pubkeys.len());
+    for vote_pubkeys in vote_pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        vote_accounts_data.extend(rpc_client.get_multiple_accounts(vote_pubkeys)?);
+    }
+    let mut rent_exempt_minimums = HashMap::new();
+    let mut vote_account_balances = vec![];
+    for ((node_pubkey, vote_pubkey), account) in
+        monitored_vote_accounts.into_iter().zip(vote_accounts_data)
+    {
+        let (balance, data_len) =
+            account.map_or((0, 0), |account| (account.lamports, account.data.len()));
+        let rent_exempt_minimum = match rent_exempt_minimums.get(&data_len) {
+            Some(rent_exempt_minimum) => *rent_exempt_minimum,
+            None => {
+                let rent_exempt_minimum =
+                    rpc_client.get_minimum_balance_for_rent_exemption(data_len)?;
+                rent_exempt_minimums.insert(data_len, rent_exempt_minimum);
+                rent_exempt_minimum
+            }
+        };
+        vote_account_balances.push(VoteAccountBalance {
+            node_pubkey,
+            vote_pubkey,
+            balance,
+            rent_exempt_minimum,
+        });
+    }
+
+    Ok(ClusterInfo {
         transaction_count,
         recent_blockhash,
         vote_accounts,
         validator_balances,
-    ))
+        vote_account_balances,
+    })
 }
 
 struct EndpointData {
@@ -291,7 +370,13 @@ fn query_endpoint(
     info!("Querying {}", endpoint.rpc_client.url());
 
     match get_cluster_info(config, &endpoint.rpc_client) {
-        Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+        Ok(ClusterInfo {
+            transaction_count,
+            recent_blockhash,
+            vote_accounts,
+            validator_balances,
+            vote_account_balances,
+        }) => {
             info!("Current transaction count: {transaction_count}");
             info!("Recent blockhash: {recent_blockhash}");
             info!("Current validator count: {}", vote_accounts.current.len());
@@ -372,6 +457,11 @@ fn query_endpoint(
                 }
 
                 if let Some(balance) = validator_balances.get(validator_identity) {
+                    datapoint_info!(
+                        "watchtower-identity-balance",
+                        ("identity", validator_identity.to_string(), String),
+                        ("balance", *balance, i64),
+                    );
                     if *balance < config.minimum_validator_identity_balance {
                         failures.push((
                             "balance",
@@ -381,6 +471,40 @@ fn query_endpoint(
                 }
             }
 
+            for vote_account in &vote_account_balances {
+                datapoint_info!(
+                    "watchtower-vote-account-balance",
+                    ("identity", vote_account.node_pubkey.to_string(), String),
+                    ("vote_account", vote_account.vote_pubkey.to_string(), String),
+                    ("balance", vote_account.balance, i64),
+                    ("rent_exempt_minimum", vote_account.rent_exempt_minimum, i64),
+                );
+                let formatted_vote_account = format_labeled_address(
+                    &vote_account.vote_pubkey.to_string(),
+                    &config.address_labels,
+                );
+                if vote_account.balance < vote_account.rent_exempt_minimum {
+                    failures.push((
+                        "vote-account-rent",
+                        format!(
+                            "{} has {}, which is less than the rent exempt minimum of {}",
+                            formatted_vote_account,
+                            Sol(vote_account.balance),
+                            Sol(vote_account.rent_exempt_minimum)
+                        ),
+                    ));
+                } else if vote_account.balance < config.minimum_vote_account_balance {
+                    failures.push((
+                        "vote-account-balance",
+                        format!(
+                            "{} has {}",
+                            formatted_vote_account,
+                            Sol(vote_account.balance)
+                        ),
+                    ));
+                }
+            }
+
             if !validator_errors.is_empty() {
                 failures.push(("delinquent", validator_errors.join(",")));
             }
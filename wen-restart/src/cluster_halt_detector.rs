@@ -0,0 +1,193 @@
+//! Detects a halted cluster.
+//!
+//! When the cluster stops making roots, every validator keeps running and nothing fails: operators
+//! learn from each other that a coordinated restart is needed. The detector watches the root of
+//! this node and the roots staked peers vote for in gossip, so that a node falling behind on its
+//! own is not mistaken for a halted cluster. Once none of them has advanced for a while, it reports
+//! the cluster as halted, tells the operator how to join a wen restart, and keeps the halt for the
+//! admin RPC service to surface.
+
+use {
+    log::*,
+    solana_clock::Slot,
+    solana_gossip::{cluster_info::ClusterInfo, crds::Cursor},
+    solana_metrics::datapoint_info,
+    solana_runtime::bank_forks::BankForks,
+    solana_time_utils::timestamp,
+    solana_vote::vote_parser::parse_vote_transaction,
+    std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+/// How often the root is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClusterHalt {
+    /// Highest root of this node or voted by a staked peer, which has not advanced since
+    pub root: Slot,
+    /// Highest slot of the banks of this node
+    pub highest_slot: Slot,
+    /// When the root last advanced, in milliseconds since the UNIX epoch
+    pub root_timestamp_ms: u64,
+}
+
+impl ClusterHalt {
+    /// Returns what the operator should do next
+    pub fn next_step(&self) -> String {
+        format!(
+            "The root has not advanced from slot {} for {}s, the cluster may be halted. If a \
+             coordinated restart is announced, restart with --wen-restart FILE \
+             --wen-restart-coordinator PUBKEY",
+            self.root,
+            timestamp().saturating_sub(self.root_timestamp_ms) / 1_000
+        )
+    }
+}
+
+/// Whether the cluster is halted, as last seen by the detector
+#[derive(Debug, Default)]
+pub struct ClusterHaltStatus {
+    halt: RwLock<Option<ClusterHalt>>,
+}
+
+impl ClusterHaltStatus {
+    pub fn halt(&self) -> Option<ClusterHalt> {
+        *self.halt.read().unwrap()
+    }
+}
+
+/// Tracks when the root last advanced
+struct RootTracker {
+    halt_timeout_ms: u64,
+    root: Slot,
+    root_timestamp_ms: u64,
+}
+
+impl RootTracker {
+    fn new(halt_timeout: Duration, root: Slot, now_ms: u64) -> Self {
+        Self {
+            halt_timeout_ms: halt_timeout.as_millis() as u64,
+            root,
+            root_timestamp_ms: now_ms,
+        }
+    }
+
+    /// Records the root at `now_ms`, and returns the halt if it has not advanced in time
+    fn update(&mut self, root: Slot, highest_slot: Slot, now_ms: u64) -> Option<ClusterHalt> {
+        if root != self.root {
+            self.root = root;
+            self.root_timestamp_ms = now_ms;
+            return None;
+        }
+        (now_ms.saturating_sub(self.root_timestamp_ms) >= self.halt_timeout_ms).then_some(
+            ClusterHalt {
+                root,
+                highest_slot,
+                root_timestamp_ms: self.root_timestamp_ms,
+            },
+        )
+    }
+}
+
+pub struct ClusterHaltDetector {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl ClusterHaltDetector {
+    pub fn new(
+        bank_forks: Arc<RwLock<BankForks>>,
+        cluster_info: Arc<ClusterInfo>,
+        halt_timeout: Duration,
+        status: Arc<ClusterHaltStatus>,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("solClusterHalt".to_string())
+            .spawn(move || Self::run(&bank_forks, &cluster_info, halt_timeout, &status, &exit))
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    fn run(
+        bank_forks: &RwLock<BankForks>,
+        cluster_info: &ClusterInfo,
+        halt_timeout: Duration,
+        status: &ClusterHaltStatus,
+        exit: &AtomicBool,
+    ) {
+        let mut tracker =
+            RootTracker::new(halt_timeout, bank_forks.read().unwrap().root(), timestamp());
+        let mut cursor = Cursor::default();
+        let mut peer_root = 0;
+        while !exit.load(Ordering::Relaxed) {
+            sleep(CHECK_INTERVAL);
+            let (root_bank, highest_slot) = {
+                let bank_forks = bank_forks.read().unwrap();
+                (bank_forks.root_bank(), bank_forks.highest_slot())
+            };
+            // Anyone can gossip votes, only those of staked vote accounts are trusted
+            let vote_accounts = root_bank.vote_accounts();
+            for vote in cluster_info.get_votes(&mut cursor) {
+                let Some((vote_pubkey, vote, ..)) = parse_vote_transaction(&vote) else {
+                    continue;
+                };
+                if vote_accounts
+                    .get(&vote_pubkey)
+                    .is_some_and(|(stake, _)| *stake > 0)
+                {
+                    peer_root = peer_root.max(vote.root().unwrap_or_default());
+                }
+            }
+            let root = root_bank.slot().max(peer_root);
+            let halt = tracker.update(root, highest_slot, timestamp());
+            let mut status = status.halt.write().unwrap();
+            match (&*status, &halt) {
+                (None, Some(halt)) => warn!("{}", halt.next_step()),
+                (Some(_), None) => info!("The root advanced to slot {root}, the cluster resumed"),
+                _ => continue,
+            }
+            *status = halt;
+            datapoint_info!(
+                "cluster_halt_detector",
+                ("halted", halt.is_some(), bool),
+                ("root", root, i64),
+                ("highest_slot", highest_slot, i64),
+            );
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_tracker() {
+        let mut tracker = RootTracker::new(Duration::from_secs(10), 5, 1_000);
+        assert_eq!(tracker.update(5, 8, 10_999), None);
+        assert_eq!(
+            tracker.update(5, 9, 11_000),
+            Some(ClusterHalt {
+                root: 5,
+                highest_slot: 9,
+                root_timestamp_ms: 1_000,
+            })
+        );
+
+        // The halt ends as soon as the root advances, and the timeout starts over
+        assert_eq!(tracker.update(6, 9, 12_000), None);
+        assert_eq!(tracker.update(6, 9, 21_999), None);
+        assert!(tracker.update(6, 9, 22_000).is_some());
+    }
+}
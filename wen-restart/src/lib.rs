@@ -13,6 +13,8 @@ pub(crate) mod solana {
     }
 }
 
+pub mod cluster_halt_detector;
 pub(crate) mod heaviest_fork_aggregate;
 pub(crate) mod last_voted_fork_slots_aggregate;
 pub mod wen_restart;
+pub mod wen_restart_status;
@@ -13,6 +13,7 @@ use {
             LastVotedForkSlotsAggregateRecord, LastVotedForkSlotsEpochInfoRecord,
             LastVotedForkSlotsRecord, State as RestartState, WenRestartProgress,
         },
+        wen_restart_status::restart_arguments,
     },
     agave_snapshots::{
         paths::{
@@ -1110,9 +1111,8 @@ pub fn wait_for_wen_restart(config: WenRestartConfig) -> Result<()> {
                 shred_version,
             } => {
                 error!(
-                    "Wen start finished, please remove --wen_restart and restart with \
-                     --wait-for-supermajority {slot} --expected-bank-hash {hash} \
-                     --expected-shred-version {shred_version} --no-snapshot-fetch",
+                    "Wen start finished, please remove --wen_restart and restart with {}",
+                    restart_arguments(slot, &hash, u32::from(shred_version)),
                 );
                 if config.cluster_info.id() == config.wen_restart_coordinator {
                     aggregate_restart_heaviest_fork(
@@ -1387,7 +1387,7 @@ pub(crate) fn initialize(
     }
 }
 
-fn read_wen_restart_records(records_path: &PathBuf) -> Result<WenRestartProgress> {
+pub(crate) fn read_wen_restart_records(records_path: &Path) -> Result<WenRestartProgress> {
     let buffer = read(records_path)?;
     let progress = WenRestartProgress::decode(&mut Cursor::new(buffer))?;
     info!("read record {progress:?}");
@@ -0,0 +1,301 @@
+//! Summary of the progress of a wen restart, for operators following it from outside the
+//! validator.
+//!
+//! While in wen restart mode the validator records its progress in a proto3 file, which is hard
+//! to read by hand. The summary tells which step the restart is at, how much of the cluster this
+//! node has heard from, whether this node agrees with the coordinator on the heaviest fork, and
+//! once the restart is done, the arguments to restart the validator with.
+
+use {
+    crate::{
+        solana::wen_restart_proto::{
+            GenerateSnapshotRecord, HeaviestForkRecord, State as RestartState, WenRestartProgress,
+        },
+        wen_restart::read_wen_restart_records,
+    },
+    anyhow::Result,
+    solana_clock::Slot,
+    std::{fmt, path::Path},
+};
+
+/// Returns the arguments to restart the validator with once a wen restart is done
+pub fn restart_arguments(slot: Slot, bankhash: &impl fmt::Display, shred_version: u32) -> String {
+    format!(
+        "--wait-for-supermajority {slot} --expected-bank-hash {bankhash} --expected-shred-version \
+         {shred_version} --no-snapshot-fetch"
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WenRestartStep {
+    Init,
+    LastVotedForkSlots,
+    HeaviestFork,
+    GenerateSnapshot,
+    Done,
+}
+
+impl fmt::Display for WenRestartStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let step = match self {
+            Self::Init => "init",
+            Self::LastVotedForkSlots => "last voted fork slots",
+            Self::HeaviestFork => "heaviest fork",
+            Self::GenerateSnapshot => "generate snapshot",
+            Self::Done => "done",
+        };
+        write!(f, "{step}")
+    }
+}
+
+impl From<RestartState> for WenRestartStep {
+    fn from(state: RestartState) -> Self {
+        match state {
+            RestartState::Init => Self::Init,
+            RestartState::LastVotedForkSlots => Self::LastVotedForkSlots,
+            RestartState::HeaviestFork => Self::HeaviestFork,
+            RestartState::GenerateSnapshot => Self::GenerateSnapshot,
+            RestartState::Done => Self::Done,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestartFork {
+    pub slot: Slot,
+    pub bankhash: String,
+}
+
+impl From<&HeaviestForkRecord> for RestartFork {
+    fn from(record: &HeaviestForkRecord) -> Self {
+        Self {
+            slot: record.slot,
+            bankhash: record.bankhash.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestartSnapshot {
+    pub path: String,
+    pub slot: Slot,
+    pub bankhash: String,
+    pub shred_version: u32,
+}
+
+impl From<&GenerateSnapshotRecord> for RestartSnapshot {
+    fn from(record: &GenerateSnapshotRecord) -> Self {
+        Self {
+            path: record.path.clone(),
+            slot: record.slot,
+            bankhash: record.bankhash.clone(),
+            shred_version: record.shred_version,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WenRestartStatus {
+    pub step: WenRestartStep,
+    /// Last slot this node voted on before the restart
+    pub my_last_voted_slot: Option<Slot>,
+    /// Number of validators whose last voted fork slots were received
+    pub last_voted_fork_slots_received: usize,
+    /// Heaviest fork selected by this node
+    pub my_heaviest_fork: Option<RestartFork>,
+    /// Heaviest fork selected by the coordinator
+    pub coordinator_heaviest_fork: Option<RestartFork>,
+    /// Number of heaviest forks received from other validators
+    pub heaviest_fork_received: usize,
+    /// Number of the received heaviest forks with the slot and bank hash of the coordinator
+    pub heaviest_fork_agreed: usize,
+    pub snapshot: Option<RestartSnapshot>,
+    /// Number of validators that gossiped conflicting restart messages
+    pub conflicts: usize,
+}
+
+/// Reads the summary of the wen restart progress recorded at `path`
+pub fn read_wen_restart_status(path: &Path) -> Result<WenRestartStatus> {
+    read_wen_restart_records(path).map(|progress| WenRestartStatus::new(&progress))
+}
+
+impl WenRestartStatus {
+    pub(crate) fn new(progress: &WenRestartProgress) -> Self {
+        let my_heaviest_fork = progress.my_heaviest_fork.as_ref().map(RestartFork::from);
+        let coordinator_heaviest_fork = progress
+            .coordinator_heaviest_fork
+            .as_ref()
+            .map(RestartFork::from);
+        // The coordinator compares the forks it received with its own
+        let agreed_fork = coordinator_heaviest_fork
+            .as_ref()
+            .or(my_heaviest_fork.as_ref());
+        let received_heaviest_forks = progress
+            .heaviest_fork_aggregate
+            .as_ref()
+            .map(|aggregate| aggregate.received.as_slice())
+            .unwrap_or_default();
+        let heaviest_fork_agreed = received_heaviest_forks
+            .iter()
+            .filter(|record| Some(&RestartFork::from(*record)) == agreed_fork)
+            .count();
+        Self {
+            step: progress.state().into(),
+            my_last_voted_slot: progress
+                .my_last_voted_fork_slots
+                .as_ref()
+                .and_then(|record| record.last_voted_fork_slots.iter().max().copied()),
+            last_voted_fork_slots_received: progress
+                .last_voted_fork_slots_aggregate
+                .as_ref()
+                .map(|aggregate| aggregate.received.len())
+                .unwrap_or_default(),
+            my_heaviest_fork,
+            coordinator_heaviest_fork,
+            heaviest_fork_received: received_heaviest_forks.len(),
+            heaviest_fork_agreed,
+            snapshot: progress.my_snapshot.as_ref().map(RestartSnapshot::from),
+            conflicts: progress.conflict_message.len(),
+        }
+    }
+
+    /// Returns whether this node and the coordinator selected the same heaviest fork, once both
+    /// are known
+    pub fn agrees_with_coordinator(&self) -> Option<bool> {
+        match (&self.my_heaviest_fork, &self.coordinator_heaviest_fork) {
+            (Some(mine), Some(coordinator)) => Some(mine == coordinator),
+            _ => None,
+        }
+    }
+
+    /// Returns the arguments to restart the validator with, once the restart is done
+    pub fn restart_arguments(&self) -> Option<String> {
+        let snapshot = self.snapshot.as_ref()?;
+        (self.step == WenRestartStep::Done)
+            .then(|| restart_arguments(snapshot.slot, &snapshot.bankhash, snapshot.shred_version))
+    }
+
+    /// Returns what the operator should do next
+    pub fn next_step(&self) -> String {
+        if self.agrees_with_coordinator() == Some(false) {
+            return "The heaviest fork of this node differs from the coordinator's, check with \
+                    the other operators before restarting"
+                .to_string();
+        }
+        match self.step {
+            WenRestartStep::Init => {
+                "Wait for the validator to send its last voted fork slots".to_string()
+            }
+            WenRestartStep::LastVotedForkSlots => format!(
+                "Wait for enough stake to send its last voted fork slots, received from {} \
+                 validators so far",
+                self.last_voted_fork_slots_received
+            ),
+            WenRestartStep::HeaviestFork => {
+                "Wait for the heaviest fork to be repaired and agreed with the coordinator"
+                    .to_string()
+            }
+            WenRestartStep::GenerateSnapshot => {
+                "Wait for the snapshot of the heaviest fork to be generated".to_string()
+            }
+            WenRestartStep::Done => match self.restart_arguments() {
+                Some(restart_arguments) => format!(
+                    "Remove --wen-restart and --wen-restart-coordinator, and restart with \
+                     {restart_arguments}"
+                ),
+                None => "The restart is done but recorded no snapshot, check the validator log"
+                    .to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            solana::wen_restart_proto::{
+                HeaviestForkAggregateRecord, LastVotedForkSlotsAggregateRecord,
+                LastVotedForkSlotsRecord,
+            },
+            wen_restart::write_wen_restart_records,
+        },
+        std::collections::HashMap,
+    };
+
+    fn heaviest_fork_record(slot: Slot, bankhash: &str) -> HeaviestForkRecord {
+        HeaviestForkRecord {
+            slot,
+            bankhash: bankhash.to_string(),
+            ..HeaviestForkRecord::default()
+        }
+    }
+
+    #[test]
+    fn test_read_wen_restart_status() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("wen_restart_progress.proto");
+        let mut progress = WenRestartProgress {
+            state: RestartState::HeaviestFork.into(),
+            my_last_voted_fork_slots: Some(LastVotedForkSlotsRecord {
+                last_voted_fork_slots: vec![12, 11, 10],
+                ..LastVotedForkSlotsRecord::default()
+            }),
+            last_voted_fork_slots_aggregate: Some(LastVotedForkSlotsAggregateRecord {
+                received: HashMap::from([
+                    ("a".to_string(), LastVotedForkSlotsRecord::default()),
+                    ("b".to_string(), LastVotedForkSlotsRecord::default()),
+                ]),
+                final_result: None,
+            }),
+            my_heaviest_fork: Some(heaviest_fork_record(11, "hash")),
+            heaviest_fork_aggregate: Some(HeaviestForkAggregateRecord {
+                received: vec![
+                    heaviest_fork_record(11, "hash"),
+                    heaviest_fork_record(11, "other"),
+                    heaviest_fork_record(12, "hash"),
+                ],
+                total_active_stake: 0,
+            }),
+            ..WenRestartProgress::default()
+        };
+        write_wen_restart_records(&path, &progress).unwrap();
+
+        let status = read_wen_restart_status(&path).unwrap();
+        assert_eq!(status.step, WenRestartStep::HeaviestFork);
+        assert_eq!(status.my_last_voted_slot, Some(12));
+        assert_eq!(status.last_voted_fork_slots_received, 2);
+        assert_eq!(status.heaviest_fork_received, 3);
+        assert_eq!(status.heaviest_fork_agreed, 1);
+        assert_eq!(status.agrees_with_coordinator(), None);
+        assert_eq!(status.restart_arguments(), None);
+
+        progress.coordinator_heaviest_fork = Some(heaviest_fork_record(11, "other"));
+        let status = WenRestartStatus::new(&progress);
+        assert_eq!(status.heaviest_fork_agreed, 1);
+        assert_eq!(status.agrees_with_coordinator(), Some(false));
+        assert!(status.next_step().contains("differs from the coordinator"));
+
+        progress.coordinator_heaviest_fork = Some(heaviest_fork_record(11, "hash"));
+        progress.state = RestartState::Done.into();
+        progress.my_snapshot = Some(GenerateSnapshotRecord {
+            path: "snapshot".to_string(),
+            slot: 11,
+            bankhash: "hash".to_string(),
+            shred_version: 2,
+        });
+        let status = WenRestartStatus::new(&progress);
+        assert_eq!(status.agrees_with_coordinator(), Some(true));
+        assert_eq!(
+            status.restart_arguments().unwrap(),
+            "--wait-for-supermajority 11 --expected-bank-hash hash --expected-shred-version 2 \
+             --no-snapshot-fetch"
+        );
+        assert!(status
+            .next_step()
+            .ends_with(&status.restart_arguments().unwrap()));
+
+        assert!(read_wen_restart_status(&temp_dir.path().join("missing")).is_err());
+    }
+}